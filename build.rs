@@ -0,0 +1,13 @@
+//! Generates the C header of the `ffi` module with cbindgen
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        cbindgen::generate(&crate_dir)
+            .expect("the ffi module can be parsed")
+            .write_to_file(format!("{}/include/gbemu.h", crate_dir));
+    }
+}
@@ -0,0 +1,49 @@
+//! Prints the video and audio hashes of every frame, to record or check a
+//! golden dump.
+//!
+//! ```sh
+//! cargo run --example av_dump -- game.gb 600 > game.avdump
+//! cargo run --example av_dump -- game.gb 600 game.avdump
+//! ```
+use std::process::ExitCode;
+
+use gbemu::{avdump, memory::RamInit, GameBoy};
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let usage = "Usage: av_dump <rom> <frames> [golden dump]";
+    let rom = std::fs::read(args.next().expect(usage)).expect("Failed to read the ROM");
+    let frames: u64 = args.next().expect(usage).parse().expect(usage);
+    // Random RAM would make every run differ
+    let mut gb = GameBoy::builder(&rom)
+        .ram_init(RamInit::Zeros)
+        .sram_init(RamInit::Zeros)
+        .build();
+
+    let Some(path) = args.next() else {
+        avdump::write_dump(&mut gb, frames, std::io::stdout().lock())
+            .expect("Failed to write the dump");
+        return ExitCode::SUCCESS;
+    };
+
+    let text = std::fs::read_to_string(path).expect("Failed to read the golden dump");
+    let golden = avdump::parse_dump(&text).expect("Invalid golden dump");
+    let actual = gb.av_dump(frames);
+    match avdump::first_mismatch(&golden, &actual) {
+        None => {
+            println!("{} frames match", actual.len());
+            ExitCode::SUCCESS
+        }
+        Some(index) => {
+            let show = |records: &[avdump::AvRecord]| {
+                records
+                    .get(index)
+                    .map_or("missing".to_string(), |r| r.to_string())
+            };
+            println!("Mismatch at record {}", index);
+            println!("expected {}", show(&golden));
+            println!("actual   {}", show(&actual));
+            ExitCode::FAILURE
+        }
+    }
+}
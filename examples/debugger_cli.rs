@@ -0,0 +1,129 @@
+//! A minimal command line debugger, type `help` for the commands.
+//!
+//! ```sh
+//! cargo run --example debugger_cli -- game.gb [game.sym]
+//! ```
+use std::io::{BufRead, Write};
+
+use gbemu::{
+    cpu::{Cpu, Registers},
+    debug::{self, SymbolTable},
+    instructions::disassemble,
+    memory::Read,
+    run::Condition,
+    GameBoy,
+};
+
+const HELP: &str = "\
+s              step one instruction
+n              step over calls
+o              step out of the current function
+c [frames]     continue for some frames (1 by default)
+b <location>   set a breakpoint and continue until one is reached
+w <location>   watch the location for changes
+x <address>    dump 16 bytes of memory
+               (addresses in hex or I/O register names, e.g. STAT,
+               locations may add a bank (05:4000) or be a symbol)
+r              show the registers
+bt             show the backtrace
+q              quit";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let usage = "Usage: debugger_cli <rom> [symbols]";
+    let rom = std::fs::read(args.next().expect(usage)).expect("Failed to read the ROM");
+
+    let mut gb = GameBoy::new(&rom);
+    if let Some(path) = args.next() {
+        let symbols = std::fs::read_to_string(path).expect("Failed to read the symbols");
+        gb.debugger_mut().set_symbols(SymbolTable::parse(&symbols));
+    }
+
+    show_next(&gb);
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("s");
+        let argument = words.next();
+        let address = argument.and_then(debug::parse_address).map(usize::from);
+        let location = argument.and_then(|text| {
+            gb.debugger()
+                .symbols()
+                .address_of(text)
+                .or_else(|| debug::parse_banked_address(text))
+        });
+        match (command, address, location) {
+            ("s", _, _) => {
+                gb.step();
+            }
+            ("n", _, _) => report(gb.step_over().map(|_| ())),
+            ("o", _, _) => report(gb.step_out().map(|_| ())),
+            ("c", _, _) => {
+                let frames = argument.and_then(|s| s.parse().ok()).unwrap_or(1);
+                report(
+                    gb.run_until(&Condition::Frames(frames), frames + 1)
+                        .map(|_| ()),
+                );
+            }
+            ("b", _, Some(location)) => {
+                gb.debugger_mut().add_breakpoint(location);
+                // Leave the current breakpoint first
+                gb.step();
+                report(gb.run_until(&Condition::Breakpoint, 600).map(|_| ()));
+            }
+            ("w", _, Some(location)) => {
+                gb.debugger_mut().watch(location);
+                continue;
+            }
+            ("x", Some(address), _) => {
+                let bytes: Vec<_> = (address..address + 16)
+                    .map(|a| format!("{:02X}", gb.read_u8(a)))
+                    .collect();
+                println!("{:04X}  {}", address, bytes.join(" "));
+                continue;
+            }
+            ("r", _, _) => {
+                println!("{}", gb.registers());
+                continue;
+            }
+            ("bt", _, _) => {
+                for entry in gb.backtrace() {
+                    println!("  {}", entry);
+                }
+                continue;
+            }
+            ("q", _, _) => break,
+            _ => {
+                println!("{}", HELP);
+                continue;
+            }
+        }
+
+        for hit in gb.debugger_mut().take_watch_hits() {
+            println!(
+                "watch {:04X}: {:02X} -> {:02X} at {:04X}",
+                hit.address, hit.old, hit.new, hit.pc
+            );
+        }
+        show_next(&gb);
+    }
+}
+
+fn show_next(gb: &GameBoy) {
+    let pc = *gb.registers().pc as usize;
+    let (text, _) = disassemble(gb, pc);
+    println!("{:04X}  {}", pc, text);
+}
+
+fn report<E: std::fmt::Display>(result: Result<(), E>) {
+    if let Err(error) = result {
+        println!("{}", error);
+    }
+}
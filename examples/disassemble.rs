@@ -0,0 +1,32 @@
+//! Prints the instructions found from an address of a ROM.
+//!
+//! ```sh
+//! cargo run --example disassemble -- game.gb 0x150 32
+//! ```
+use gbemu::{instructions::disassemble, memory::Read, GameBoy};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let usage = "Usage: disassemble <rom> [address] [count]";
+    let rom = std::fs::read(args.next().expect(usage)).expect("Failed to read the ROM");
+    let mut address = args
+        .next()
+        .map_or(0x100, |s| parse_address(&s).expect(usage));
+    let count: usize = args.next().map_or(16, |s| s.parse().expect(usage));
+
+    // Bank 1 is mapped at power on, the same view the CPU starts with
+    let gb = GameBoy::new(&rom);
+    for _ in 0..count {
+        let (text, length) = disassemble(&gb, address);
+        let bytes: Vec<_> = (0..length as usize)
+            .map(|i| format!("{:02X}", gb.read_u8(address + i)))
+            .collect();
+        println!("{:04X}  {:<9} {}", address, bytes.join(" "), text);
+        address += length as usize;
+    }
+}
+
+fn parse_address(text: &str) -> Option<usize> {
+    let text = text.trim_start_matches("0x").trim_start_matches('$');
+    usize::from_str_radix(text, 16).ok()
+}
@@ -0,0 +1,48 @@
+//! Saves a PNG of the screen every emulated second.
+//!
+//! ```sh
+//! cargo run --example dump_frames -- game.gb 10 frames/
+//! ```
+use std::{fs::File, io::BufWriter, path::Path};
+
+use gbemu::{
+    lcd::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    pacing::FRAME_RATE,
+    run::Condition,
+    video::PixelFormat,
+    GameBoy,
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let usage = "Usage: dump_frames <rom> [seconds] [directory]";
+    let rom = std::fs::read(args.next().expect(usage)).expect("Failed to read the ROM");
+    let seconds: u32 = args.next().map_or(10, |s| s.parse().expect(usage));
+    let directory = args.next().unwrap_or_else(|| ".".to_string());
+    std::fs::create_dir_all(&directory).expect("Failed to create the directory");
+
+    let mut gb = GameBoy::new(&rom);
+    let mut pixels = vec![0; PixelFormat::Rgba8888.frame_size()];
+    let second = Condition::Frames(FRAME_RATE.round() as u64);
+    for i in 1..=seconds {
+        gb.run_until(&second, 2 * FRAME_RATE as u64)
+            .expect("The frame counter stopped");
+
+        let path = Path::new(&directory).join(format!("frame_{:04}.png", i));
+        gb.frame_into(PixelFormat::Rgba8888, &mut pixels);
+        save_png(&path, &pixels);
+        println!("{}", path.display());
+    }
+}
+
+fn save_png(path: &Path, pixels: &[u8]) {
+    let file = BufWriter::new(File::create(path).expect("Failed to create the PNG"));
+    let mut encoder = png::Encoder::new(file, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels))
+        .expect("Failed to write the PNG");
+}
@@ -0,0 +1,43 @@
+//! Runs a test ROM (e.g. one of blargg's `cpu_instrs`) without any output
+//! device and prints what it sends on the serial port.
+//!
+//! ```sh
+//! cargo run --example headless_test_rom -- cpu_instrs.gb
+//! ```
+use std::io::Write;
+
+use gbemu::{run::Condition, GameBoy};
+
+/// Emulated time after which the ROM is considered stuck
+const TIMEOUT_FRAMES: u64 = 60 * 120;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("Usage: headless_test_rom <rom>");
+    let rom = std::fs::read(path).expect("Failed to read the ROM");
+
+    let mut gb = GameBoy::new(&rom);
+    gb.hooks_mut().on_serial = Some(Box::new(|byte| {
+        print!("{}", byte as char);
+        std::io::stdout().flush().unwrap();
+    }));
+
+    // blargg's ROMs report the result on the serial port
+    let done = Condition::Any(vec![
+        Condition::SerialOutputContains("Passed".to_string()),
+        Condition::SerialOutputContains("Failed".to_string()),
+    ]);
+    match gb.run_until(&done, TIMEOUT_FRAMES) {
+        Ok(_) => println!(),
+        Err(timeout) => {
+            eprintln!("\n{}", timeout);
+            std::process::exit(1);
+        }
+    }
+
+    let output = String::from_utf8_lossy(gb.serial_output());
+    if output.contains("Failed") {
+        std::process::exit(1);
+    }
+}
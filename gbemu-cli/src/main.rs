@@ -0,0 +1,43 @@
+use gbemu::{
+    cartridge::{self, CartridgeHolder},
+    runner::{Event, Runner},
+    serial::TextSink,
+};
+
+fn main() {
+    env_logger::init();
+
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "rom/pkmn_yel.gb".to_string());
+    let mut game = std::fs::read(path).expect("Failed to read game file.");
+    // An IPS or BPS patch may follow the game
+    if let Some(path) = args.next() {
+        let patch = std::fs::read(path).expect("Failed to read patch file.");
+        game = cartridge::apply_patch(&game, &patch).expect("Failed to apply patch");
+    }
+
+    // 0x603C
+
+    // No input is read yet
+    let runner = Runner::spawn(
+        move || {
+            let gb = gbemu::GameBoy::new(&game);
+            log::info!("Game loaded!");
+            log::info!("Game Info: {:#?}.", gb.cartridge_header());
+            gb
+        },
+        |_, _: &()| {},
+    );
+
+    let mut serial = TextSink::with_callback(|line| println!("{}", line));
+    for event in runner.events() {
+        match event {
+            Event::Frame(number) => log::trace!("Frame {}", number),
+            // Test ROMs print their results there
+            Event::Serial(byte) => serial.push(byte),
+            Event::Stopped => break,
+            _ => {}
+        }
+    }
+    serial.flush();
+}
@@ -0,0 +1,94 @@
+#ifndef GBEMU_H
+#define GBEMU_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A joypad button, in the P1 bit order
+enum GbemuButton
+#if __STDC_VERSION__ >= 202311L
+  : uint8_t
+#endif // __STDC_VERSION__ >= 202311L
+ {
+  GBEMU_BUTTON_RIGHT = 1,
+  GBEMU_BUTTON_LEFT = 2,
+  GBEMU_BUTTON_UP = 4,
+  GBEMU_BUTTON_DOWN = 8,
+  GBEMU_BUTTON_A = 16,
+  GBEMU_BUTTON_B = 32,
+  GBEMU_BUTTON_SELECT = 64,
+  GBEMU_BUTTON_START = 128,
+};
+#if __STDC_VERSION__ >= 202311L
+typedef enum GbemuButton GbemuButton;
+#else
+typedef uint8_t GbemuButton;
+#endif // __STDC_VERSION__ >= 202311L
+
+// A console and the frame lent to the caller
+typedef struct Gbemu Gbemu;
+
+// Powers up a console with an empty cartridge slot, see [`gbemu_load_rom`]
+struct Gbemu *gbemu_create(void);
+
+// Frees a console, null is ignored
+//
+// # Safety
+//
+// `gb` must come from [`gbemu_create`] and not be used afterwards.
+void gbemu_destroy(struct Gbemu *gb);
+
+// Powers up a new console with the cartridge image, replacing the previous
+// one. Returns false, keeping the previous console, if the image is invalid.
+//
+// # Safety
+//
+// `gb` must be a live handle and `rom` point to `len` readable bytes.
+bool gbemu_load_rom(struct Gbemu *gb, const uint8_t *rom, size_t len);
+
+// Runs a frame, returns the T-cycles taken
+//
+// # Safety
+//
+// `gb` must be a live handle.
+size_t gbemu_run_frame(struct Gbemu *gb);
+
+// Returns the last complete frame, 160 × 144 RGBA pixels row by row,
+// 4 bytes each. Null if the handle is.
+//
+// # Safety
+//
+// `gb` must be a live handle. The frame is overwritten by the next call.
+const uint8_t *gbemu_framebuffer(struct Gbemu *gb);
+
+// Presses or releases a button, the others are left as they are
+//
+// # Safety
+//
+// `gb` must be a live handle.
+void gbemu_set_button(struct Gbemu *gb, GbemuButton button, bool pressed);
+
+// Saves the state into `out` if it fits in `capacity` bytes, and returns
+// its size: call it with a null `out` to size the buffer. 0 if the handle
+// is null.
+//
+// # Safety
+//
+// `gb` must be a live handle and `out`, unless null, point to `capacity`
+// writable bytes.
+size_t gbemu_save_state(const struct Gbemu *gb, uint8_t *out, size_t capacity);
+
+// Loads a state saved by [`gbemu_save_state`], returns false, leaving the
+// console untouched, if it is invalid or saved with another cartridge
+//
+// # Safety
+//
+// `gb` must be a live handle and `state` point to `len` readable bytes.
+bool gbemu_load_state(struct Gbemu *gb, const uint8_t *state, size_t len);
+
+#endif  /* GBEMU_H */
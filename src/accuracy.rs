@@ -0,0 +1,66 @@
+//! Toggles trading emulation speed for hardware accuracy.
+//!
+//! They choose the bus and LCD quirks emulated and the diagnostics, the
+//! timing model stays the same: every profile draws whole scanlines and
+//! executes whole instructions.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Presets of [`AccuracyConfig`] so users don't have to learn every toggle
+pub enum AccuracyProfile {
+    /// Skip every quirk in favour of speed
+    Fast,
+    /// Emulate the quirks games are known to rely on: the OAM DMA bus
+    /// conflicts and the DMG STAT write bug
+    #[default]
+    Balanced,
+    /// Emulate every quirk, the open bus latch included, and report the OAM
+    /// DMA violations
+    Strict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccuracyConfig {
+    /// During OAM DMA the CPU can only access 0xFF00..=0xFFFF,
+    /// other reads return 0xFF and writes are dropped.
+    pub oam_dma_bus_conflicts: bool,
+    /// Log a warning whenever code runs outside HRAM during OAM DMA, and on
+    /// every access the transfer blocks, with its address and the PC.
+    pub report_oam_dma_violations: bool,
+    /// Unmapped reads return the last value driven on the bus instead of 0xFF.
+    pub open_bus_latch: bool,
+    /// On DMG and SGB, writing STAT in HBlank, VBlank or while LY=LYC
+    /// requests a spurious STAT interrupt, as every source is briefly
+    /// enabled. Road Rash and Zerd no Densetsu rely on it.
+    pub dmg_stat_write_bug: bool,
+}
+
+impl Default for AccuracyConfig {
+    fn default() -> Self {
+        AccuracyProfile::default().into()
+    }
+}
+
+impl From<AccuracyProfile> for AccuracyConfig {
+    fn from(value: AccuracyProfile) -> Self {
+        match value {
+            AccuracyProfile::Fast => Self {
+                oam_dma_bus_conflicts: false,
+                report_oam_dma_violations: false,
+                open_bus_latch: false,
+                dmg_stat_write_bug: false,
+            },
+            AccuracyProfile::Balanced => Self {
+                oam_dma_bus_conflicts: true,
+                report_oam_dma_violations: false,
+                open_bus_latch: false,
+                dmg_stat_write_bug: true,
+            },
+            AccuracyProfile::Strict => Self {
+                oam_dma_bus_conflicts: true,
+                report_oam_dma_violations: true,
+                open_bus_latch: true,
+                dmg_stat_write_bug: true,
+            },
+        }
+    }
+}
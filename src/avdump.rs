@@ -0,0 +1,134 @@
+//! Audio/video dumps for conformance tests, in the spirit of mGBA's
+//! `--dump`: one line per frame with a hash of the picture and a checksum
+//! of the audio, instead of the media itself.
+//!
+//! The hashes only depend on the emulated machine, so a dump recorded once
+//! can be checked in as a golden file and compared on every platform, as
+//! long as the machine is deterministic: RAM must be initialised from a
+//! seed, see [`determinism`](crate::determinism).
+//!
+//! No APU is emulated yet: the audio checksum covers the sound registers
+//! and the wave RAM at the end of the frame, which is what the output will
+//! be generated from.
+//!
+//! ```
+//! use gbemu::{avdump, cartridge, memory::RamInit, GameBoy};
+//!
+//! let rom = cartridge::test_rom();
+//! let console = || GameBoy::builder(&rom).ram_init(RamInit::Zeros).build();
+//! let mut dump = Vec::new();
+//! avdump::write_dump(&mut console(), 3, &mut dump).unwrap();
+//!
+//! let mut gb = console();
+//! let golden = avdump::parse_dump(std::str::from_utf8(&dump).unwrap()).unwrap();
+//! assert_eq!(avdump::first_mismatch(&golden, &gb.av_dump(3)), None);
+//! ```
+use std::{fmt, io, str::FromStr};
+
+use crate::{memory::locations, savestate::fnv1a, GameBoy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Hashes of one frame, formatted as `frame video audio` in hex
+pub struct AvRecord {
+    /// Frames elapsed since power on
+    pub frame: u64,
+    /// Hash of the shades of the screen
+    pub video: u64,
+    /// Checksum of the audio state
+    pub audio: u64,
+}
+
+impl fmt::Display for AvRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08x} {:016x} {:016x}",
+            self.frame, self.video, self.audio
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A dump line is not three hex numbers
+pub struct ParseRecordError(pub String);
+
+impl fmt::Display for ParseRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid dump line {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRecordError {}
+
+impl FromStr for AvRecord {
+    type Err = ParseRecordError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRecordError(line.to_string());
+        let mut fields = line
+            .split_whitespace()
+            .map(|field| u64::from_str_radix(field, 16).map_err(|_| error()));
+        let mut field = || fields.next().unwrap_or_else(|| Err(error()));
+        let record = Self {
+            frame: field()?,
+            video: field()?,
+            audio: field()?,
+        };
+        match fields.next() {
+            Some(_) => Err(error()),
+            None => Ok(record),
+        }
+    }
+}
+
+impl GameBoy {
+    /// Hashes the frame last drawn and the audio state
+    pub fn av_record(&self) -> AvRecord {
+        AvRecord {
+            frame: self.frames(),
+            video: fnv1a(self.frame()),
+            audio: fnv1a(
+                self.memory[locations::NR10..=locations::NR52]
+                    .iter()
+                    .chain(&self.memory[locations::WAVE_PATTERN_RAM]),
+            ),
+        }
+    }
+
+    /// Runs `frames` frames, recording the hashes after each one
+    pub fn av_dump(&mut self, frames: u64) -> Vec<AvRecord> {
+        (0..frames)
+            .map(|_| {
+                self.run_frame();
+                self.av_record()
+            })
+            .collect()
+    }
+}
+
+/// Runs `frames` frames, writing a line of hashes after each one
+pub fn write_dump(gb: &mut GameBoy, frames: u64, mut out: impl io::Write) -> io::Result<()> {
+    for _ in 0..frames {
+        gb.run_frame();
+        writeln!(out, "{}", gb.av_record())?;
+    }
+    Ok(())
+}
+
+/// Parses a dump written by [`write_dump`], blank lines are skipped
+pub fn parse_dump(text: &str) -> Result<Vec<AvRecord>, ParseRecordError> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Returns the first record that differs from the golden one, or the first
+/// one missing from either dump
+pub fn first_mismatch(golden: &[AvRecord], actual: &[AvRecord]) -> Option<usize> {
+    golden
+        .iter()
+        .zip(actual)
+        .position(|(golden, actual)| golden != actual)
+        .or_else(|| (golden.len() != actual.len()).then(|| golden.len().min(actual.len())))
+}
@@ -0,0 +1,115 @@
+//! Pluggable PPU and APU backends, to swap the renderer or the sound
+//! emulation without forking the core.
+//!
+//! The core keeps the timing: `LY`, the `STAT` modes, the interrupts and the
+//! [`Hooks`](crate::hooks::Hooks) don't depend on the backends. A
+//! [`PpuBackend`] draws each line when entering HBlank, [`DmgPpu`] by
+//! default, [`NullPpu`] draws nothing for headless CPU work at full speed.
+//! An [`ApuBackend`] is given the writes to the sound registers and the
+//! time elapsed; the core has no sound of its own, so [`NullApu`] is the
+//! default.
+//!
+//! Each backend is compiled in with its feature, `ppu` and `apu`, on by
+//! default. Backends are chosen with [`GameBoyBuilder`](crate::GameBoyBuilder).
+//! A PPU backend draws from the memory map alone, an APU backend keeps its
+//! state in [save states](crate::savestate), and so in the
+//! [run-ahead](GameBoy::run_frame_ahead) checkpoints, through
+//! [`ApuBackend::save_state`].
+//!
+//! ```
+//! use gbemu::{backend::NullPpu, cartridge, GameBoy};
+//!
+//! let mut gb = GameBoy::builder(&cartridge::test_rom())
+//!     .ppu_backend(NullPpu)
+//!     .build();
+//! gb.run_frame();
+//! assert!(gb.frame().iter().all(|&shade| shade == 0));
+//! ```
+#[cfg(feature = "ppu")]
+use crate::lcd::SCREEN_WIDTH;
+#[cfg(feature = "apu")]
+use crate::{
+    memory::{
+        io::{IoHandler, IoMap},
+        locations,
+    },
+    savestate::SaveStateError,
+    GameBoy,
+};
+
+#[cfg(feature = "ppu")]
+pub use crate::lcd::DmgPpu;
+
+#[cfg(feature = "ppu")]
+/// Draws the scanlines of the screen
+pub trait PpuBackend {
+    /// Draws `line` from the memory map into `shades` (0 is white, 3 is
+    /// black), one per pixel of the line, as it stands when entering HBlank.
+    ///
+    /// `window_line` is the line of the window drawn next, to advance on
+    /// lines showing it. It is kept by the core, which resets it every frame.
+    fn render_line(
+        &mut self,
+        memory: &[u8],
+        line: u8,
+        window_line: &mut u8,
+        shades: &mut [u8; SCREEN_WIDTH],
+    );
+}
+
+#[cfg(feature = "apu")]
+/// Emulates the sound hardware
+pub trait ApuBackend {
+    /// A sound register or the wave RAM was written, the value is stored in
+    /// the memory map already
+    fn write(&mut self, address: u16, value: u8);
+
+    /// Called after every instruction with the dots (normal speed T-cycles)
+    /// elapsed
+    fn step(&mut self, _dots: u64) {}
+
+    /// Returns the state to keep in save states, empty for a backend without
+    /// any
+    fn save_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Restores a state returned by [`ApuBackend::save_state`], empty for
+    /// states saved before the backend kept one
+    fn load_state(&mut self, _state: &[u8]) -> Result<(), SaveStateError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ppu")]
+#[derive(Debug, Clone, Copy, Default)]
+/// Draws nothing, the screen stays white
+pub struct NullPpu;
+
+#[cfg(feature = "ppu")]
+impl PpuBackend for NullPpu {
+    fn render_line(&mut self, _: &[u8], _: u8, _: &mut u8, _: &mut [u8; SCREEN_WIDTH]) {}
+}
+
+#[cfg(feature = "apu")]
+#[derive(Debug, Clone, Copy, Default)]
+/// Ignores the sound registers
+pub struct NullApu;
+
+#[cfg(feature = "apu")]
+impl ApuBackend for NullApu {
+    fn write(&mut self, _: u16, _: u8) {}
+}
+
+#[cfg(feature = "apu")]
+pub(crate) fn install_io(io: &mut IoMap) {
+    for address in (locations::NR10..=locations::NR52).chain(locations::WAVE_PATTERN_RAM) {
+        io.install(address, IoHandler::write(write_sound));
+    }
+}
+
+#[cfg(feature = "apu")]
+fn write_sound(gb: &mut GameBoy, address: usize, value: u8) {
+    gb.memory[address] = value;
+    gb.apu.write(address as u16, value);
+}
@@ -0,0 +1,103 @@
+//! Runs many independent [`GameBoy`] instances across a pool of threads, for
+//! reinforcement learning and large test ROM sweeps.
+//!
+//! Each instance is described by a [`GameBoyBuilder`] and built on the
+//! worker thread that runs it, as a console cannot be sent across threads
+//! once hooks are installed. For reproducible runs the builders must not use
+//! [`RamInit::Random`](crate::memory::RamInit::Random), the default: seed
+//! each instance with [`RamInit::Seeded`](crate::memory::RamInit::Seeded)
+//! instead.
+//!
+//! ```no_run
+//! use std::ops::ControlFlow;
+//! use gbemu::{batch, memory::RamInit, GameBoy};
+//!
+//! let rom = std::fs::read("game.gb").unwrap();
+//! let configs = (0..64)
+//!     .map(|seed| GameBoy::builder(&rom).ram_init(RamInit::Seeded(seed)))
+//!     .collect();
+//! // Runs every instance for 10 seconds, then keeps its state hash
+//! let hashes = batch::run_parallel(configs, |_instance, gb| {
+//!     if gb.frames() < 600 {
+//!         ControlFlow::Continue(())
+//!     } else {
+//!         ControlFlow::Break(gb.state_hash())
+//!     }
+//! });
+//! ```
+use std::{
+    num::NonZeroUsize,
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{GameBoy, GameBoyBuilder};
+
+/// Runs every instance on as many threads as the machine has cores, see
+/// [`run_parallel_on`]
+pub fn run_parallel<R, F>(configs: Vec<GameBoyBuilder<'_>>, per_frame: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize, &mut GameBoy) -> ControlFlow<R> + Sync,
+{
+    let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    run_parallel_on(threads, configs, per_frame)
+}
+
+/// Builds and runs every instance on a pool of `threads` threads.
+///
+/// `per_frame` is called after every frame with the index of the instance
+/// in `configs`, which runs until it returns [`ControlFlow::Break`]. The
+/// results are returned in the order of `configs`. A panic in any instance
+/// is propagated once the others are done.
+pub fn run_parallel_on<R, F>(
+    threads: usize,
+    configs: Vec<GameBoyBuilder<'_>>,
+    per_frame: F,
+) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize, &mut GameBoy) -> ControlFlow<R> + Sync,
+{
+    assert!(threads > 0, "The batch needs at least one thread");
+    let count = configs.len();
+    // Workers take the instances in order, the slots are emptied as they go
+    let configs: Vec<_> = configs.into_iter().map(|c| Mutex::new(Some(c))).collect();
+    let results: Vec<_> = (0..count).map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.min(count) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(config) = configs.get(index) else {
+                    break;
+                };
+                let config = config.lock().unwrap().take().unwrap();
+                let result = run_instance(index, config.build(), &per_frame);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| result.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+fn run_instance<R>(
+    index: usize,
+    mut gb: GameBoy,
+    per_frame: &impl Fn(usize, &mut GameBoy) -> ControlFlow<R>,
+) -> R {
+    loop {
+        gb.run_frame();
+        if let ControlFlow::Break(result) = per_frame(index, &mut gb) {
+            return result;
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Persistence of the battery backed external RAM.
+//!
+//! Writes to external RAM mark its pages dirty, so frontends only write
+//! back what changed, and only when needed: games disable the RAM once they
+//! are done saving, which [`AutoSave`] takes as the moment to flush. This
+//! keeps flash storage (SD cards on embedded frontends) from being written
+//! every frame.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use gbemu::{battery::AutoSave, GameBoy};
+//!
+//! let rom = std::fs::read("game.gb").unwrap();
+//! let mut gb = GameBoy::new(&rom);
+//! if let Ok(save) = std::fs::read("game.sav") {
+//!     gb.load_battery_ram(&save);
+//! }
+//!
+//! let mut autosave = AutoSave::new("game.sav").interval(Duration::from_secs(10));
+//! loop {
+//!     gb.run_frame();
+//!     autosave.poll(&mut gb).unwrap();
+//! }
+//! ```
+use std::{
+    fs::OpenOptions,
+    io::{self, Seek, SeekFrom, Write},
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::GameBoy;
+
+/// Granularity of the dirty tracking, one SD card sector
+pub const PAGE_SIZE: usize = 0x200;
+
+#[derive(Debug, Clone, Default)]
+/// External RAM changes since they were last taken
+pub(crate) struct DirtyPages {
+    pages: Vec<bool>,
+    /// The game disabled the RAM after writing to it
+    disabled: bool,
+}
+
+impl DirtyPages {
+    pub(crate) fn new(ram_len: usize) -> Self {
+        Self {
+            pages: vec![false; ram_len.div_ceil(PAGE_SIZE)],
+            disabled: false,
+        }
+    }
+
+    pub(crate) fn mark(&mut self, index: usize) {
+        self.pages[index / PAGE_SIZE] = true;
+    }
+
+    pub(crate) fn mark_all(&mut self) {
+        self.pages.fill(true);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pages.fill(false);
+        self.disabled = false;
+    }
+
+    pub(crate) fn disabled(&mut self) {
+        if self.pages.contains(&true) {
+            self.disabled = true;
+        }
+    }
+}
+
+impl GameBoy {
+    /// External RAM was written since the last [`GameBoy::take_dirty_pages`]
+    pub fn save_ram_dirty(&self) -> bool {
+        self.sram_dirty.pages.contains(&true)
+    }
+
+    /// Returns the byte ranges of the external RAM pages written since the
+    /// last call, in order
+    pub fn take_dirty_pages(&mut self) -> Vec<Range<usize>> {
+        let len = self.banks.len();
+        let dirty = &mut self.sram_dirty.pages;
+        let ranges = dirty
+            .iter()
+            .enumerate()
+            .filter(|(_, dirty)| **dirty)
+            .map(|(page, _)| page * PAGE_SIZE..((page + 1) * PAGE_SIZE).min(len))
+            .collect();
+        dirty.fill(false);
+        ranges
+    }
+
+    /// Returns true if the game disabled the external RAM after writing to
+    /// it since the last call, which is how games signal a completed save
+    pub fn take_ram_disabled(&mut self) -> bool {
+        std::mem::take(&mut self.sram_dirty.disabled)
+    }
+}
+
+/// Writes the battery backed RAM to a file when the game is done saving, or
+/// once it stayed dirty for an interval. Only dirty pages are rewritten.
+///
+/// The file holds the RAM followed by the clock footer, if any, in the
+/// format of [`GameBoy::battery_ram`] and [`GameBoy::rtc_footer`].
+pub struct AutoSave {
+    path: PathBuf,
+    interval: Duration,
+    on_ram_disable: bool,
+    /// When the RAM became dirty after the last flush
+    dirty_since: Option<Instant>,
+}
+
+impl AutoSave {
+    /// Flushes to `path` on RAM disable, or 5 seconds after the first
+    /// unsaved write
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: Duration::from_secs(5),
+            on_ram_disable: true,
+            dirty_since: None,
+        }
+    }
+
+    /// Longest time a write stays unsaved
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Whether to flush as soon as the game disables the RAM
+    pub fn on_ram_disable(mut self, enabled: bool) -> Self {
+        self.on_ram_disable = enabled;
+        self
+    }
+
+    /// Flushes if due, meant to be called after every frame. Returns true
+    /// if the file was written.
+    pub fn poll(&mut self, gb: &mut GameBoy) -> io::Result<bool> {
+        let disabled = gb.take_ram_disabled();
+        if gb.battery_ram().is_none() || !gb.save_ram_dirty() {
+            return Ok(false);
+        }
+
+        let dirty_since = *self.dirty_since.get_or_insert_with(Instant::now);
+        if (self.on_ram_disable && disabled) || dirty_since.elapsed() >= self.interval {
+            self.flush(gb)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Writes the dirty pages now, e.g. before exiting. The whole RAM is
+    /// written when the file doesn't match its size.
+    pub fn flush(&mut self, gb: &mut GameBoy) -> io::Result<()> {
+        self.dirty_since = None;
+        let footer = gb.rtc_footer();
+        let Some(ram) = gb.battery_ram() else {
+            return Ok(());
+        };
+        let ram_len = ram.len();
+        let file_len = (ram_len + footer.map_or(0, |footer| footer.len())) as u64;
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        let pages = gb.take_dirty_pages();
+        let ram = gb.battery_ram().unwrap_or_default();
+        if file.metadata()?.len() == file_len {
+            log::debug!(target: "gbemu::battery", "Saving {} pages to {}", pages.len(), self.path.display());
+            for page in pages {
+                file.seek(SeekFrom::Start(page.start as u64))?;
+                file.write_all(&ram[page])?;
+            }
+        } else {
+            log::debug!(target: "gbemu::battery", "Saving the whole RAM to {}", self.path.display());
+            file.set_len(0)?;
+            file.write_all(ram)?;
+        }
+        if let Some(footer) = footer {
+            file.seek(SeekFrom::Start(ram_len as u64))?;
+            file.write_all(&footer)?;
+        }
+        file.sync_data()
+    }
+}
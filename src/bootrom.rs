@@ -0,0 +1,185 @@
+//! The boot ROM, run at power up before the cartridge: it scrolls the logo,
+//! then locks up unless the logo and the header checksum of the cartridge
+//! match, and hands over at 0x0100 after unmapping itself through `BOOT`.
+//!
+//! Without one (the default) the console powers up in the state the boot
+//! ROM leaves, for the selected [`Model`]. Its checks can still be emulated
+//! with [`GameBoyBuilder::boot_checks`](crate::GameBoyBuilder::boot_checks),
+//! to test tampered headers.
+//!
+//! The DMG and SGB boot ROMs are 256 bytes mapped at 0x0000. The CGB one is
+//! 2304 bytes mapped in two chunks around the cartridge header, 0x0000 to
+//! 0x00FF and 0x0200 to 0x08FF.
+//!
+//! ```no_run
+//! use gbemu::{bootrom::BootRom, cartridge, GameBoy};
+//!
+//! let boot_rom = BootRom::new(std::fs::read("dmg_boot.bin").unwrap()).unwrap();
+//! let mut gb = GameBoy::builder(&cartridge::test_rom())
+//!     .boot_rom(boot_rom)
+//!     .build();
+//! gb.run_frame();
+//! ```
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/Power_Up_Sequence.html)
+use std::fmt;
+
+use crate::{
+    cartridge::NINTENDO_LOGO,
+    memory::{
+        io::{IoHandler, IoMap},
+        locations,
+    },
+    GameBoy, Model,
+};
+
+/// Size of the DMG and SGB boot ROMs
+pub const DMG_BOOT_ROM_SIZE: usize = 0x100;
+/// Size of the CGB boot ROM, the header area in the middle is not mapped
+pub const CGB_BOOT_ROM_SIZE: usize = 0x900;
+/// Bytes of the logo checked by the CGB boot ROM, its top half
+const CGB_LOGO_CHECKED: usize = 0x18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootRomError {
+    /// Neither a DMG/SGB nor a CGB boot ROM
+    InvalidSize(usize),
+}
+
+impl fmt::Display for BootRomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize(size) => write!(
+                f,
+                "boot ROM is {} bytes, expected {} or {}",
+                size, DMG_BOOT_ROM_SIZE, CGB_BOOT_ROM_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BootRomError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A boot ROM image, told apart by its size
+pub struct BootRom {
+    bytes: Vec<u8>,
+}
+
+impl BootRom {
+    pub fn new(bytes: Vec<u8>) -> Result<Self, BootRomError> {
+        match bytes.len() {
+            DMG_BOOT_ROM_SIZE | CGB_BOOT_ROM_SIZE => Ok(Self { bytes }),
+            size => Err(BootRomError::InvalidSize(size)),
+        }
+    }
+
+    /// A CGB boot ROM, mapped in two chunks
+    pub fn is_cgb(&self) -> bool {
+        self.bytes.len() == CGB_BOOT_ROM_SIZE
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the byte mapped at the address, `None` where the cartridge shows
+    fn read(&self, address: usize) -> Option<u8> {
+        match address {
+            0x0000..=0x00FF => Some(self.bytes[address]),
+            0x0200..=0x08FF if self.is_cgb() => Some(self.bytes[address]),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Check of the boot ROM failed by the cartridge, locking the console up
+pub enum BootFailure {
+    Logo,
+    HeaderChecksum,
+}
+
+impl fmt::Display for BootFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Logo => write!(f, "the logo does not match"),
+            Self::HeaderChecksum => write!(f, "the header checksum does not match"),
+        }
+    }
+}
+
+/// Runs the checks of the boot ROM of the model on the cartridge, the CGB
+/// one only compares the top half of the logo
+pub fn check(model: Model, rom: &[u8]) -> Result<(), BootFailure> {
+    let logo_checked = match model {
+        Model::Cgb => CGB_LOGO_CHECKED,
+        Model::Dmg | Model::Sgb => NINTENDO_LOGO.len(),
+    };
+    let logo = rom.get(locations::NINTENDO_GRAPHICS).unwrap_or_default();
+    if logo.get(..logo_checked) != Some(&NINTENDO_LOGO[..logo_checked]) {
+        return Err(BootFailure::Logo);
+    }
+    let verification = crate::cartridge::verify(rom);
+    if !verification.header_checksum {
+        return Err(BootFailure::HeaderChecksum);
+    }
+    Ok(())
+}
+
+impl GameBoy {
+    /// The boot ROM is mapped over the cartridge, it is still running
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some() && self.memory[locations::BOOT] & 1 == 0
+    }
+
+    /// Returns the check failed at power up, when emulated with
+    /// [`GameBoyBuilder::boot_checks`](crate::GameBoyBuilder::boot_checks):
+    /// the console is locked up, halted with every interrupt disabled
+    pub fn boot_failure(&self) -> Option<BootFailure> {
+        self.boot_failure
+    }
+
+    /// Returns the byte of the boot ROM mapped at the address, if any
+    pub(crate) fn mapped_boot_rom(&self, address: usize) -> Option<u8> {
+        if !self.boot_rom_mapped() {
+            return None;
+        }
+        self.boot_rom.as_ref()?.read(address)
+    }
+
+    /// Starts the boot ROM, or emulates its checks, once the registers hold
+    /// their post-boot values
+    pub(crate) fn boot(&mut self) {
+        self.boot_failure = None;
+        if self.boot_rom.is_some() {
+            self.registers = Default::default();
+            self.memory[locations::BOOT] = 0x00;
+            self.memory[locations::LCDC] = 0x00;
+            self.memory[locations::DIV] = 0x00;
+            return;
+        }
+
+        self.memory[locations::BOOT] = 0xFF;
+        if !self.boot_checks || self.cartridge.is_empty() {
+            return;
+        }
+        if let Err(failure) = check(self.model, &self.cartridge) {
+            log::warn!(target: "gbemu::cartridge", "Boot ROM locks up: {}", failure);
+            self.boot_failure = Some(failure);
+            self.registers.mode = crate::cpu::CpuMode::Halted;
+            self.memory[locations::IE] = 0x00;
+        }
+    }
+}
+
+pub(crate) fn install_io(io: &mut IoMap) {
+    io.install(locations::BOOT, IoHandler::write(write_boot));
+}
+
+/// Any write with bit 0 set unmaps the boot ROM, until the next reset
+fn write_boot(gb: &mut GameBoy, address: usize, value: u8) {
+    if value & 1 != 0 {
+        gb.memory[address] = 0xFF;
+    }
+}
@@ -0,0 +1,339 @@
+use std::cell::{Cell, RefCell};
+
+use crate::{
+    accuracy::{AccuracyConfig, AccuracyProfile},
+    battery::DirtyPages,
+    bootrom::BootRom,
+    cartridge::{CartridgeError, CartridgeHeader, Mapper},
+    cpu::{self, ClockConfig, Cpu},
+    determinism::AuditMode,
+    hooks::Hooks,
+    joypad::{Buttons, SgbJoypad},
+    latency::LatencyProbe,
+    lint::Linter,
+    memory::{dma::OamDma, io::IoMap, RamInit, DISABLED_RAM_VALUE, OPEN_BUS_VALUE},
+    rtc::{Rtc, RtcTime},
+    timer::Timer,
+    tracelog::TraceFormat,
+    GameBoy, Model,
+};
+
+#[cfg(feature = "apu")]
+use crate::backend::{ApuBackend, NullApu};
+#[cfg(feature = "serial")]
+use crate::serial::Serial;
+#[cfg(feature = "ppu")]
+use crate::{
+    backend::{DmgPpu, PpuBackend},
+    lcd::Lcd,
+    video::{Video, DMG_PALETTE},
+};
+#[cfg(feature = "debugger")]
+use crate::{debug::Debugger, profiler::Profiler};
+
+/// Sample rate used when none is chosen
+pub const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+/// Configures a [`GameBoy`] before powering it up
+pub struct GameBoyBuilder<'a> {
+    cartridge: &'a [u8],
+    model: Model,
+    clock_speed: Option<f64>,
+    overclock: u32,
+    accuracy: AccuracyConfig,
+    sample_rate: u32,
+    ram_init: RamInit,
+    sram_init: RamInit,
+    disabled_ram_value: u8,
+    lenient_header: bool,
+    #[cfg(feature = "ppu")]
+    ghosting: f32,
+    #[cfg(feature = "ppu")]
+    palette: [[u8; 3]; 4],
+    audit: AuditMode,
+    rtc_time: RtcTime,
+    boot_rom: Option<BootRom>,
+    boot_checks: bool,
+    #[cfg(feature = "ppu")]
+    ppu: Box<dyn PpuBackend + Send>,
+    #[cfg(feature = "apu")]
+    apu: Box<dyn ApuBackend + Send>,
+}
+
+impl<'a> GameBoyBuilder<'a> {
+    pub fn new(cartridge: &'a [u8]) -> Self {
+        Self {
+            cartridge,
+            model: Model::default(),
+            clock_speed: None,
+            overclock: 1,
+            accuracy: AccuracyConfig::default(),
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            ram_init: RamInit::default(),
+            sram_init: RamInit::default(),
+            disabled_ram_value: DISABLED_RAM_VALUE,
+            lenient_header: false,
+            #[cfg(feature = "ppu")]
+            ghosting: 0.0,
+            #[cfg(feature = "ppu")]
+            palette: DMG_PALETTE,
+            audit: AuditMode::Off,
+            rtc_time: RtcTime::Emulated,
+            boot_rom: None,
+            boot_checks: false,
+            #[cfg(feature = "ppu")]
+            ppu: Box::new(DmgPpu),
+            #[cfg(feature = "apu")]
+            apu: Box::new(NullApu),
+        }
+    }
+
+    /// Selects the hardware model to emulate
+    pub fn model(mut self, model: Model) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Selects the master clock speed in Hz, the model's one by default
+    /// ([`SGB_CLOCK_SPEED`](cpu::SGB_CLOCK_SPEED) on the Super Game Boy)
+    pub fn clock_speed(mut self, speed: f64) -> Self {
+        self.clock_speed = Some(speed);
+        self
+    }
+
+    /// Runs `factor` CPU cycles for every cycle of the rest of the hardware,
+    /// see [`ClockConfig::overclock`]
+    pub fn overclock(mut self, factor: u32) -> Self {
+        assert!(factor > 0, "The overclock factor must be at least 1");
+        self.overclock = factor;
+        self
+    }
+
+    /// Selects an accuracy preset
+    pub fn accuracy(mut self, profile: AccuracyProfile) -> Self {
+        self.accuracy = profile.into();
+        self
+    }
+
+    /// Selects the accuracy toggles one by one
+    pub fn accuracy_config(mut self, config: AccuracyConfig) -> Self {
+        self.accuracy = config;
+        self
+    }
+
+    /// Selects the power on contents of the internal RAM
+    pub fn ram_init(mut self, init: RamInit) -> Self {
+        self.ram_init = init;
+        self
+    }
+
+    /// Selects the power on contents of the external (cartridge) RAM,
+    /// overwritten by [`GameBoy::load_battery_ram`] if the game has saved
+    pub fn sram_init(mut self, init: RamInit) -> Self {
+        self.sram_init = init;
+        self
+    }
+
+    /// Runs the boot ROM at power up, see [`bootrom`](crate::bootrom)
+    pub fn boot_rom(mut self, boot_rom: BootRom) -> Self {
+        self.boot_rom = Some(boot_rom);
+        self
+    }
+
+    /// Without a boot ROM, emulates its checks of the cartridge header: the
+    /// console locks up if they fail, see [`GameBoy::boot_failure`]. Off by
+    /// default, to run images without a valid header.
+    pub fn boot_checks(mut self, enabled: bool) -> Self {
+        self.boot_checks = enabled;
+        self
+    }
+
+    /// Selects what advances the MBC3 clock, see [`rtc`](crate::rtc)
+    pub fn rtc_time(mut self, time: RtcTime) -> Self {
+        self.rtc_time = time;
+        self
+    }
+
+    /// Looks for nondeterminism sources from power on, see
+    /// [`determinism`](crate::determinism)
+    pub fn determinism_audit(mut self, mode: AuditMode) -> Self {
+        self.audit = mode;
+        self
+    }
+
+    /// Selects the value read from external RAM and RTC registers while disabled,
+    /// `0xFF` by default. Some cartridge revisions pull the bus differently.
+    pub fn disabled_ram_value(mut self, value: u8) -> Self {
+        self.disabled_ram_value = value;
+        self
+    }
+
+    /// Tolerates invalid header values and image sizes instead of panicking,
+    /// see [`CartridgeHeader::parse_lenient`]
+    pub fn lenient_header(mut self, lenient: bool) -> Self {
+        self.lenient_header = lenient;
+        self
+    }
+
+    #[cfg(feature = "ppu")]
+    /// Blends each frame with the previous one, see [`GameBoy::set_ghosting`]
+    pub fn ghosting(mut self, strength: f32) -> Self {
+        self.ghosting = strength;
+        self
+    }
+
+    #[cfg(feature = "ppu")]
+    /// Selects the colors of the four shades, see [`GameBoy::set_palette`]
+    pub fn palette(mut self, palette: [[u8; 3]; 4]) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    #[cfg(feature = "ppu")]
+    /// Selects the renderer of the scanlines, see [`backend`](crate::backend)
+    pub fn ppu_backend(mut self, ppu: impl PpuBackend + Send + 'static) -> Self {
+        self.ppu = Box::new(ppu);
+        self
+    }
+
+    #[cfg(feature = "apu")]
+    /// Selects the sound emulation, see [`backend`](crate::backend)
+    pub fn apu_backend(mut self, apu: impl ApuBackend + Send + 'static) -> Self {
+        self.apu = Box::new(apu);
+        self
+    }
+
+    /// Selects the rate at which the audio device consumes samples
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Powers up the console, an empty cartridge image leaves the slot empty
+    ///
+    /// # Panics
+    ///
+    /// If the header is invalid, see [`GameBoyBuilder::try_build`].
+    pub fn build(self) -> GameBoy {
+        self.try_build().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Powers up the console, failing on images it can't run: too big for
+    /// the mapper or, unless parsed leniently, truncated, of a size other
+    /// than the declared one, with invalid header values or a mapper that is
+    /// not emulated.
+    pub fn try_build(self) -> Result<GameBoy, CartridgeError> {
+        // An empty slot reads as open bus through the whole ROM area
+        if self.cartridge.is_empty() {
+            return Ok(self.power_on(CartridgeHeader::empty(), Vec::new()));
+        }
+
+        let ch = if self.lenient_header {
+            CartridgeHeader::parse_lenient(self.cartridge)
+        } else {
+            CartridgeHeader::try_parse(self.cartridge)?
+        };
+        for warning in &ch.warnings {
+            log::warn!(target: "gbemu::cartridge", "{}", warning);
+        }
+        if matches!(ch.cart_type.mapper, Mapper::NotSupported | Mapper::Unknown) {
+            return Err(CartridgeError::InvalidHeader {
+                field: "cartridge type",
+                code: ch.type_code,
+            });
+        }
+
+        let max_rom_banks = ch.cart_type.mapper.max_rom_banks();
+        if ch.rom_banks() > max_rom_banks {
+            return Err(CartridgeError::RomTooBig {
+                banks: ch.rom_banks(),
+                max: max_rom_banks,
+            });
+        }
+
+        let mut cart = vec![0; ch.rom_bytes()];
+        if self.lenient_header {
+            let len = cart.len().min(self.cartridge.len());
+            cart[..len].copy_from_slice(&self.cartridge[..len]);
+        } else if cart.len() == self.cartridge.len() {
+            cart.copy_from_slice(self.cartridge);
+        } else {
+            return Err(CartridgeError::SizeMismatch {
+                declared: cart.len(),
+                actual: self.cartridge.len(),
+            });
+        }
+
+        Ok(self.power_on(ch, cart))
+    }
+
+    fn power_on(self, ch: CartridgeHeader, cart: Vec<u8>) -> GameBoy {
+        let ram_len = ch.ram_bytes();
+
+        let default_clock = ClockConfig::for_model(self.model);
+        let mut tmp = GameBoy {
+            model: self.model,
+            clock: ClockConfig {
+                speed: self.clock_speed.unwrap_or(default_clock.speed),
+                overclock: self.overclock,
+            },
+            registers: cpu::RegisterFile::default(),
+            memory: [0; 0x10000],
+            memory_mode: ch.cart_type.into(),
+            cartridge: cart,
+            banks: vec![0; ram_len],
+            sram_dirty: DirtyPages::new(ram_len),
+            io: IoMap::with_subsystems(),
+            cartridge_header: ch,
+            oam_dma: OamDma::default(),
+            accuracy: self.accuracy,
+            hooks: Hooks::default(),
+            sample_rate: self.sample_rate,
+            sample_cycles_debt: 0.0,
+            log_rom_writes: false,
+            ram_init: self.ram_init,
+            sram_init: self.sram_init,
+            disabled_ram_value: self.disabled_ram_value,
+            bus_latch: Cell::new(OPEN_BUS_VALUE),
+            counters: cpu::Counters::default(),
+            timer: Timer::default(),
+            serial_output: Vec::new(),
+            #[cfg(feature = "serial")]
+            serial: Serial::default(),
+            buttons: Buttons::empty(),
+            sgb_joypad: SgbJoypad::default(),
+            rtc: Rtc::new(self.rtc_time),
+            #[cfg(feature = "debugger")]
+            debugger: Debugger::default(),
+            #[cfg(feature = "debugger")]
+            profiler: Profiler::default(),
+            #[cfg(feature = "ppu")]
+            lcd: Lcd::default(),
+            #[cfg(feature = "ppu")]
+            video: Video::new(self.ghosting, self.palette),
+            #[cfg(feature = "ppu")]
+            ppu: self.ppu,
+            #[cfg(feature = "apu")]
+            apu: self.apu,
+            bus_faults: RefCell::default(),
+            audit: self.audit,
+            nondeterminism: RefCell::default(),
+            fixed_step: false,
+            paused: false,
+            latency: LatencyProbe::default(),
+            frame_events: None,
+            trace_format: TraceFormat::default(),
+            linter: Linter::default(),
+            instruction_pc: 0,
+            dma_trace: RefCell::default(),
+            irq_stats: None,
+            boot_rom: self.boot_rom,
+            boot_checks: self.boot_checks,
+            boot_failure: None,
+        };
+
+        tmp.reset();
+
+        tmp
+    }
+}
@@ -0,0 +1,82 @@
+//! Checks the boot ROM and the tools run on the header, and their repair
+//! after editing an image.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/The_Cartridge_Header.html#014d--header-checksum)
+use super::NINTENDO_LOGO;
+use crate::memory::locations;
+
+/// Bytes covered by the header checksum
+const HEADER_CHECKED: std::ops::RangeInclusive<usize> = 0x0134..=0x014C;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Outcome of [`verify`]
+pub struct Verification {
+    /// The logo matches, checked by the boot ROM
+    pub logo: bool,
+    /// The header checksum matches, checked by the boot ROM
+    pub header_checksum: bool,
+    /// The global checksum matches, only checked by tools
+    pub global_checksum: bool,
+}
+
+impl Verification {
+    /// The image would boot on hardware
+    pub fn boots(&self) -> bool {
+        self.logo && self.header_checksum
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.boots() && self.global_checksum
+    }
+}
+
+/// Computes the header checksum of an image, stored at 0x014D
+///
+/// # Panics
+///
+/// If the image is too short to hold a header.
+pub fn header_checksum(rom: &[u8]) -> u8 {
+    rom[HEADER_CHECKED].iter().fold(0u8, |checksum, byte| {
+        checksum.wrapping_sub(*byte).wrapping_sub(1)
+    })
+}
+
+/// Computes the global checksum of an image, stored big-endian at 0x014E:
+/// the sum of every byte but its own
+///
+/// # Panics
+///
+/// If the image is too short to hold a header.
+pub fn global_checksum(rom: &[u8]) -> u16 {
+    let stored = rom[locations::CHECKSUM]
+        .iter()
+        .fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16));
+    rom.iter()
+        .fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16))
+        .wrapping_sub(stored)
+}
+
+/// Checks the logo and the checksums of an image
+///
+/// # Panics
+///
+/// If the image is too short to hold a header.
+pub fn verify(rom: &[u8]) -> Verification {
+    Verification {
+        logo: rom[locations::NINTENDO_GRAPHICS] == NINTENDO_LOGO,
+        header_checksum: rom[locations::COMPLEMENT_CHECK] == header_checksum(rom),
+        global_checksum: rom[locations::CHECKSUM] == global_checksum(rom).to_be_bytes(),
+    }
+}
+
+/// Recomputes both checksums, e.g. after patching, leaving the logo alone
+///
+/// # Panics
+///
+/// If the image is too short to hold a header.
+pub fn fix_checksums(rom: &mut [u8]) {
+    rom[locations::COMPLEMENT_CHECK] = header_checksum(rom);
+    // The header checksum is part of the global one
+    let checksum = global_checksum(rom);
+    rom[locations::CHECKSUM].copy_from_slice(&checksum.to_be_bytes());
+}
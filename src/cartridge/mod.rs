@@ -0,0 +1,842 @@
+use std::{
+    fmt,
+    ops::{BitOr, BitOrAssign},
+};
+
+use crate::{
+    memory::{locations, Memory},
+    MBC2_RAM_SIZE, RAM_BANK_SIZE, ROM_BANK_SIZE,
+};
+
+pub use self::checksum::{fix_checksums, global_checksum, header_checksum, verify, Verification};
+pub use self::patch::{apply_bps, apply_ips, apply_patch, PatchError};
+
+mod checksum;
+mod patch;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Destination {
+    Japanese = 0x00,
+    NonJapanese = 0x01,
+}
+
+impl Destination {
+    /// Decodes the header byte, `None` if invalid
+    pub fn from_code(value: u8) -> Option<Self> {
+        match value {
+            0x00 => Some(Self::Japanese),
+            0x01 => Some(Self::NonJapanese),
+            _ => None,
+        }
+    }
+}
+
+impl From<u8> for Destination {
+    fn from(value: u8) -> Self {
+        Self::from_code(value).expect("Invalid destination value")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Indicates the mapper present on the cartridge
+pub enum Mapper {
+    /// No mapper, with up to 8 KiB of unbanked RAM
+    RomOnly,
+    MBC1,
+    MBC2,
+    MBC3,
+    MBC5,
+    NotSupported,
+    Unknown,
+}
+
+impl Mapper {
+    /// ROM banks of 16 KiB the mapper can address, MBC3 counting the MBC30
+    /// variant. Unsupported mappers fail later, when mapped.
+    pub const fn max_rom_banks(&self) -> usize {
+        match self {
+            Self::RomOnly => 0x02,
+            Self::MBC1 => 0x80,
+            Self::MBC2 => 0x10,
+            Self::MBC3 => 0x100,
+            Self::MBC5 | Self::NotSupported | Self::Unknown => 0x200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// Set of the hardware present on the cartridge besides the mapper
+pub struct CartridgeFeatures(u8);
+
+impl CartridgeFeatures {
+    pub const RAM: Self = Self(0b0000_0001);
+    pub const BATTERY: Self = Self(0b0000_0010);
+    /// The MBC3 real time clock
+    pub const TIMER: Self = Self(0b0000_0100);
+    pub const RUMBLE: Self = Self(0b0000_1000);
+    /// The MBC7 accelerometer
+    pub const SENSOR: Self = Self(0b0001_0000);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for CartridgeFeatures {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for CartridgeFeatures {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Decoded cartridge type byte: the mapper and the hardware around it
+pub struct CartridgeType {
+    pub mapper: Mapper,
+    pub features: CartridgeFeatures,
+}
+
+impl CartridgeType {
+    pub const fn new(mapper: Mapper, features: CartridgeFeatures) -> Self {
+        Self { mapper, features }
+    }
+}
+
+impl From<u8> for CartridgeType {
+    fn from(value: u8) -> Self {
+        use CartridgeFeatures as F;
+        let (mapper, features) = match value {
+            0x00 => (Mapper::RomOnly, F::empty()),
+            0x01 => (Mapper::MBC1, F::empty()),
+            0x02 => (Mapper::MBC1, F::RAM),
+            0x03 => (Mapper::MBC1, F::RAM | F::BATTERY),
+            0x05 => (Mapper::MBC2, F::empty()),
+            0x06 => (Mapper::MBC2, F::BATTERY),
+            0x08 => (Mapper::RomOnly, F::RAM),
+            0x09 => (Mapper::RomOnly, F::RAM | F::BATTERY),
+            0x0F => (Mapper::MBC3, F::TIMER | F::BATTERY),
+            0x10 => (Mapper::MBC3, F::TIMER | F::RAM | F::BATTERY),
+            0x11 => (Mapper::MBC3, F::empty()),
+            0x12 => (Mapper::MBC3, F::RAM),
+            0x13 => (Mapper::MBC3, F::RAM | F::BATTERY),
+            0x19 => (Mapper::MBC5, F::empty()),
+            0x1A => (Mapper::MBC5, F::RAM),
+            0x1B => (Mapper::MBC5, F::RAM | F::BATTERY),
+            0x1C => (Mapper::MBC5, F::RUMBLE),
+            0x1D => (Mapper::MBC5, F::RUMBLE | F::RAM),
+            0x1E => (Mapper::MBC5, F::RUMBLE | F::RAM | F::BATTERY),
+            // MBC6
+            0x20 => (Mapper::NotSupported, F::empty()),
+            // MBC7
+            0x22 => (
+                Mapper::NotSupported,
+                F::SENSOR | F::RUMBLE | F::RAM | F::BATTERY,
+            ),
+            // Pocket Camera, TAMA5, HuC3
+            0xFC..=0xFE => (Mapper::NotSupported, F::empty()),
+            // HuC1
+            0xFF => (Mapper::NotSupported, F::RAM | F::BATTERY),
+            _ => (Mapper::Unknown, F::empty()),
+        };
+        Self::new(mapper, features)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// ROM size declared by the header
+pub enum RomSize {
+    /// No banking
+    KiB32,
+    KiB64,
+    KiB128,
+    KiB256,
+    KiB512,
+    MiB1,
+    MiB2,
+    MiB4,
+    MiB8,
+    MiB1Point1,
+    MiB1Point2,
+    MiB1Point5,
+}
+
+impl RomSize {
+    /// Decodes the header byte, `None` if invalid
+    pub fn from_code(value: u8) -> Option<Self> {
+        Some(match value {
+            0x00 => Self::KiB32,
+            0x01 => Self::KiB64,
+            0x02 => Self::KiB128,
+            0x03 => Self::KiB256,
+            0x04 => Self::KiB512,
+            0x05 => Self::MiB1,
+            0x06 => Self::MiB2,
+            0x07 => Self::MiB4,
+            0x08 => Self::MiB8,
+            0x52 => Self::MiB1Point1,
+            0x53 => Self::MiB1Point2,
+            0x54 => Self::MiB1Point5,
+            _ => return None,
+        })
+    }
+
+    /// Encodes the size as the header byte
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::MiB1Point1 => 0x52,
+            Self::MiB1Point2 => 0x53,
+            Self::MiB1Point5 => 0x54,
+            size => (size.banks() / Self::KiB32.banks()).trailing_zeros() as u8,
+        }
+    }
+
+    /// ROM banks of 16 KiB
+    pub const fn banks(&self) -> usize {
+        match self {
+            Self::KiB32 => 2,
+            Self::KiB64 => 4,
+            Self::KiB128 => 8,
+            Self::KiB256 => 16,
+            Self::KiB512 => 32,
+            Self::MiB1 => 64,
+            Self::MiB2 => 128,
+            Self::MiB4 => 256,
+            Self::MiB8 => 512,
+            Self::MiB1Point1 => 72,
+            Self::MiB1Point2 => 80,
+            Self::MiB1Point5 => 96,
+        }
+    }
+
+    /// ROM size in bytes, [`RomSize::banks`] banks of 16 KiB
+    pub const fn bytes(&self) -> usize {
+        self.banks() * ROM_BANK_SIZE
+    }
+
+    /// Returns the smallest standard size holding an image of `len` bytes
+    pub fn fitting(len: usize) -> Self {
+        [
+            Self::KiB32,
+            Self::KiB64,
+            Self::KiB128,
+            Self::KiB256,
+            Self::KiB512,
+            Self::MiB1,
+            Self::MiB2,
+            Self::MiB4,
+        ]
+        .into_iter()
+        .find(|size| size.bytes() >= len)
+        .unwrap_or(Self::MiB8)
+    }
+}
+
+impl fmt::Display for RomSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_size(f, self.bytes())
+    }
+}
+
+impl From<u8> for RomSize {
+    fn from(value: u8) -> Self {
+        Self::from_code(value).expect("Invalid rom size value")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// External RAM size declared by the header
+pub enum RamSize {
+    None,
+    KiB8,
+    KiB32,
+    KiB128,
+    KiB64,
+}
+
+impl RamSize {
+    /// Decodes the header byte, `None` if invalid
+    pub fn from_code(value: u8) -> Option<Self> {
+        Some(match value {
+            0x00 => Self::None,
+            0x02 => Self::KiB8,
+            0x03 => Self::KiB32,
+            0x04 => Self::KiB128,
+            0x05 => Self::KiB64,
+            _ => return None,
+        })
+    }
+
+    /// Encodes the size as the header byte
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::None => 0x00,
+            Self::KiB8 => 0x02,
+            Self::KiB32 => 0x03,
+            Self::KiB128 => 0x04,
+            Self::KiB64 => 0x05,
+        }
+    }
+
+    /// RAM banks of 8 KiB
+    pub const fn banks(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::KiB8 => 1,
+            Self::KiB32 => 4,
+            Self::KiB64 => 8,
+            Self::KiB128 => 16,
+        }
+    }
+
+    /// RAM size in bytes, [`RamSize::banks`] banks of 8 KiB
+    pub const fn bytes(&self) -> usize {
+        self.banks() * RAM_BANK_SIZE
+    }
+}
+
+impl fmt::Display for RamSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            _ => write_size(f, self.bytes()),
+        }
+    }
+}
+
+/// Writes a size in KiB or MiB, with a decimal for the odd ROM sizes
+fn write_size(f: &mut fmt::Formatter<'_>, bytes: usize) -> fmt::Result {
+    const KIB: usize = 1024;
+    const MIB: usize = 1024 * KIB;
+    if bytes < MIB {
+        write!(f, "{} KiB", bytes / KIB)
+    } else if bytes.is_multiple_of(MIB) {
+        write!(f, "{} MiB", bytes / MIB)
+    } else {
+        write!(f, "{:.1} MiB", bytes as f64 / MIB as f64)
+    }
+}
+
+impl From<u8> for RamSize {
+    fn from(value: u8) -> Self {
+        Self::from_code(value).expect("Invalid ram size value")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Header oddities tolerated while parsing
+pub enum HeaderWarning {
+    /// The image is too short to hold a header, it was padded
+    Truncated { len: usize },
+    /// Unknown or unsupported cartridge type, replaced by the given mapper
+    CartridgeType { code: u8, fallback: Mapper },
+    /// Invalid ROM size, replaced by the size of the image
+    RomSize { code: u8 },
+    /// Invalid RAM size, no RAM is assumed
+    RamSize { code: u8 },
+    /// Invalid destination code, non-Japanese is assumed
+    Destination { code: u8 },
+    /// The image size differs from the header one, it was padded or cut
+    SizeMismatch { declared: usize, actual: usize },
+    /// The header checksum doesn't match its contents
+    HeaderChecksum { declared: u8, computed: u8 },
+}
+
+impl std::fmt::Display for HeaderWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated { len } => write!(f, "image of {} bytes has no full header", len),
+            Self::CartridgeType { code, fallback } => {
+                write!(f, "cartridge type {:#04x} handled as {:?}", code, fallback)
+            }
+            Self::RomSize { code } => write!(f, "invalid ROM size {:#04x}", code),
+            Self::RamSize { code } => write!(f, "invalid RAM size {:#04x}", code),
+            Self::Destination { code } => write!(f, "invalid destination {:#04x}", code),
+            Self::SizeMismatch { declared, actual } => write!(
+                f,
+                "header declares {} bytes of ROM, image has {}",
+                declared, actual
+            ),
+            Self::HeaderChecksum { declared, computed } => write!(
+                f,
+                "header checksum {:#04x}, computed {:#04x}",
+                declared, computed
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Cartridge images the console can't be powered up with
+pub enum CartridgeError {
+    /// The header declares more ROM banks than the mapper can address
+    RomTooBig { banks: usize, max: usize },
+    /// The image size differs from the header one, and the header is not
+    /// parsed leniently
+    SizeMismatch { declared: usize, actual: usize },
+    /// A header field holds a code with no meaning, or a mapper that is not
+    /// emulated, and the header is not parsed leniently
+    InvalidHeader { field: &'static str, code: u8 },
+    /// The image ends before the header does, and the header is not parsed
+    /// leniently
+    Truncated { len: usize },
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RomTooBig { banks, max } => write!(
+                f,
+                "header declares {} ROM banks, at most {} are supported",
+                banks, max
+            ),
+            Self::SizeMismatch { declared, actual } => write!(
+                f,
+                "header declares {} bytes of ROM, image has {}",
+                declared, actual
+            ),
+            Self::InvalidHeader { field, code } => {
+                write!(f, "invalid {} {:#04x} in the header", field, code)
+            }
+            Self::Truncated { len } => {
+                write!(f, "image of {} bytes ends before the header", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {}
+
+#[derive(Debug, Clone)]
+pub struct CartridgeHeader {
+    /// Title of the game in uppercase ASCII
+    pub title: String,
+    /// Game supports Color-Mode
+    pub color: bool,
+    /// Game supports SGB functions
+    pub sgb: bool,
+    /// Indicates what kind of hardware is present on the cartridge (notably the mapper).
+    pub cart_type: CartridgeType,
+    /// Raw cartridge type byte, [`CartridgeHeader::cart_type`] decoded
+    pub type_code: u8,
+    /// How much ROM is present on the cartridge.
+    pub rom_size: RomSize,
+    /// How much RAM is present on the cartridge.
+    pub ram_size: RamSize,
+    /// RAM contents are kept by a battery and should be persisted.
+    pub battery: bool,
+    /// Indicates whether this version of the fame is intended to be sold in Japan or elsewhere.
+    pub destination: Destination,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    /// Oddities found while parsing, only non-fatal ones unless parsed leniently
+    pub warnings: Vec<HeaderWarning>,
+}
+
+impl From<&[u8]> for CartridgeHeader {
+    /// Parses the header, panicking on invalid ones, see
+    /// [`CartridgeHeader::try_parse`]
+    fn from(value: &[u8]) -> Self {
+        Self::try_parse(value).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl CartridgeHeader {
+    /// Header reported when no cartridge is inserted
+    pub fn empty() -> Self {
+        CartridgeHeader {
+            title: String::new(),
+            color: false,
+            sgb: false,
+            cart_type: CartridgeType::new(Mapper::RomOnly, CartridgeFeatures::empty()),
+            type_code: 0x00,
+            rom_size: RomSize::KiB32,
+            ram_size: RamSize::None,
+            battery: false,
+            destination: Destination::NonJapanese,
+            version: 0,
+            header_checksum: 0,
+            global_checksum: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// ROM banks of 16 KiB
+    pub fn rom_banks(&self) -> usize {
+        self.rom_size.banks()
+    }
+
+    pub fn rom_bytes(&self) -> usize {
+        self.rom_size.bytes()
+    }
+
+    /// External RAM banks of 8 KiB, none for the MBC2 built-in RAM
+    pub fn ram_banks(&self) -> usize {
+        self.ram_size.banks()
+    }
+
+    /// External RAM size, including the 512 half-bytes built into the MBC2
+    pub fn ram_bytes(&self) -> usize {
+        match self.cart_type.mapper {
+            Mapper::MBC2 => MBC2_RAM_SIZE,
+            _ => self.ram_size.bytes(),
+        }
+    }
+
+    /// RAM contents are kept by a battery and should be persisted
+    pub fn has_battery(&self) -> bool {
+        self.cart_type.features.contains(CartridgeFeatures::BATTERY)
+    }
+
+    /// The MBC3 real time clock is present
+    pub fn has_rtc(&self) -> bool {
+        self.cart_type.features.contains(CartridgeFeatures::TIMER)
+    }
+
+    /// The MBC5 rumble motor is present
+    pub fn has_rumble(&self) -> bool {
+        self.cart_type.features.contains(CartridgeFeatures::RUMBLE)
+    }
+
+    /// Parses the header mapping invalid values to sane defaults, each
+    /// recorded in [`CartridgeHeader::warnings`], so homebrew and test ROMs
+    /// with sloppy headers can run.
+    pub fn parse_lenient(value: &[u8]) -> Self {
+        Self::parse(value, true).expect("lenient parsing falls back to defaults")
+    }
+
+    /// Parses the header, failing on truncated images and invalid size or
+    /// destination codes
+    pub fn try_parse(value: &[u8]) -> Result<Self, CartridgeError> {
+        Self::parse(value, false)
+    }
+
+    fn parse(value: &[u8], lenient: bool) -> Result<Self, CartridgeError> {
+        let mut warnings = vec![];
+
+        let padded;
+        let value = if value.len() <= *locations::CHECKSUM.end() {
+            if !lenient {
+                return Err(CartridgeError::Truncated { len: value.len() });
+            }
+            warnings.push(HeaderWarning::Truncated { len: value.len() });
+            padded = [value, &[0; 0x150][value.len()..]].concat();
+            padded.as_slice()
+        } else {
+            value
+        };
+
+        let is_newer = value[locations::LICENSEE_CODE_OLDER] == 0x33;
+
+        let type_code = value[locations::CARTRIDGE_TYPE];
+        let mut cart_type = CartridgeType::from(type_code);
+        if lenient && matches!(cart_type.mapper, Mapper::NotSupported | Mapper::Unknown) {
+            let fallback = if value.len() > 0x8000 {
+                Mapper::MBC5
+            } else {
+                Mapper::RomOnly
+            };
+            warnings.push(HeaderWarning::CartridgeType {
+                code: type_code,
+                fallback,
+            });
+            cart_type.mapper = fallback;
+        }
+
+        let rom_code = value[locations::ROM_SIZE];
+        let rom_size = match RomSize::from_code(rom_code) {
+            Some(size) => size,
+            None if lenient => {
+                warnings.push(HeaderWarning::RomSize { code: rom_code });
+                RomSize::fitting(value.len())
+            }
+            None => {
+                return Err(CartridgeError::InvalidHeader {
+                    field: "ROM size",
+                    code: rom_code,
+                })
+            }
+        };
+        if value.len() != rom_size.bytes() {
+            warnings.push(HeaderWarning::SizeMismatch {
+                declared: rom_size.bytes(),
+                actual: value.len(),
+            });
+        }
+
+        let ram_code = value[locations::RAM_SIZE];
+        let ram_size = match RamSize::from_code(ram_code) {
+            Some(size) => size,
+            None if lenient => {
+                warnings.push(HeaderWarning::RamSize { code: ram_code });
+                RamSize::None
+            }
+            None => {
+                return Err(CartridgeError::InvalidHeader {
+                    field: "RAM size",
+                    code: ram_code,
+                })
+            }
+        };
+
+        let destination_code = value[locations::DESTINATION_CODE];
+        let destination = match Destination::from_code(destination_code) {
+            Some(destination) => destination,
+            None if lenient => {
+                warnings.push(HeaderWarning::Destination {
+                    code: destination_code,
+                });
+                Destination::NonJapanese
+            }
+            None => {
+                return Err(CartridgeError::InvalidHeader {
+                    field: "destination",
+                    code: destination_code,
+                })
+            }
+        };
+
+        let header_checksum = value[locations::COMPLEMENT_CHECK];
+        let computed = checksum::header_checksum(value);
+        if computed != header_checksum {
+            warnings.push(HeaderWarning::HeaderChecksum {
+                declared: header_checksum,
+                computed,
+            });
+        }
+
+        Ok(CartridgeHeader {
+            title: String::from_utf8(
+                value[if is_newer {
+                    locations::GAME_TITLE
+                } else {
+                    locations::GAME_TITLE_OLDER
+                }]
+                .to_vec(),
+            )
+            .unwrap_or(String::from("Unknown"))
+            .trim_end_matches('\0')
+            .trim()
+            .to_string(),
+            color: value[locations::COLOR_INDICATOR] == 0x80,
+            sgb: is_newer && value[locations::GB_SGB_INDICATOR] == 0x03,
+            cart_type,
+            type_code,
+            rom_size,
+            ram_size,
+            battery: cart_type.features.contains(CartridgeFeatures::BATTERY),
+            destination,
+            version: value[locations::MASK_ROM_VERSION_NUMBER],
+            header_checksum,
+            global_checksum: u16::from_be_bytes(value[locations::CHECKSUM].try_into().unwrap()),
+            warnings,
+        })
+    }
+}
+
+/// Logo the boot ROM compares to before running the cartridge
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+/// Builds a valid ROM image around code, to synthesize test cartridges.
+///
+/// By default the image is a 32 KiB ROM only cartridge whose entrypoint
+/// jumps to 0x0150, where [`HeaderBuilder::code`] is placed.
+///
+/// ```
+/// use gbemu::{cartridge::{CartridgeHeader, HeaderBuilder, RamSize}, GameBoy};
+///
+/// let rom = HeaderBuilder::new()
+///     .title("EXAMPLE")
+///     // MBC1+RAM+BATTERY
+///     .cartridge_type(0x03)
+///     .ram_size(RamSize::KiB8)
+///     // JR -2
+///     .code(&[0x18, 0xFE])
+///     .build();
+/// let header = CartridgeHeader::from(rom.as_slice());
+/// assert!(header.warnings.is_empty());
+/// assert!(header.has_battery());
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderBuilder {
+    title: String,
+    type_code: u8,
+    rom_size: RomSize,
+    ram_size: RamSize,
+    color: bool,
+    sgb: bool,
+    destination: Destination,
+    version: u8,
+    code: Vec<(usize, Vec<u8>)>,
+}
+
+impl Default for HeaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeaderBuilder {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            type_code: 0x00,
+            rom_size: RomSize::KiB32,
+            ram_size: RamSize::None,
+            color: false,
+            sgb: false,
+            destination: Destination::NonJapanese,
+            version: 0,
+            // NOP; JP 0x0150
+            code: vec![(*locations::ENTRYPOINT.start(), vec![0x00, 0xC3, 0x50, 0x01])],
+        }
+    }
+
+    /// Title in uppercase ASCII, up to 11 characters
+    pub fn title(mut self, title: &str) -> Self {
+        assert!(
+            title.is_ascii() && title.len() <= locations::GAME_TITLE.clone().count(),
+            "The title must be ASCII of up to 11 characters"
+        );
+        self.title = title.to_string();
+        self
+    }
+
+    /// Raw cartridge type byte, see [`CartridgeType`]
+    pub fn cartridge_type(mut self, type_code: u8) -> Self {
+        self.type_code = type_code;
+        self
+    }
+
+    /// Also the size of the image built
+    pub fn rom_size(mut self, size: RomSize) -> Self {
+        self.rom_size = size;
+        self
+    }
+
+    pub fn ram_size(mut self, size: RamSize) -> Self {
+        self.ram_size = size;
+        self
+    }
+
+    /// Marks the game as supporting Color-Mode
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Marks the game as supporting SGB functions
+    pub fn sgb(mut self, sgb: bool) -> Self {
+        self.sgb = sgb;
+        self
+    }
+
+    pub fn destination(mut self, destination: Destination) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    pub fn version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Places `bytes` at 0x0150, right after the header
+    pub fn code(self, bytes: &[u8]) -> Self {
+        self.code_at(0x0150, bytes)
+    }
+
+    /// Places `bytes` at `address`, the entrypoint included. The header is
+    /// written last, over anything placed on it.
+    pub fn code_at(mut self, address: usize, bytes: &[u8]) -> Self {
+        self.code.push((address, bytes.to_vec()));
+        self
+    }
+
+    /// Builds the image, with the logo and the checksums filled in
+    pub fn build(&self) -> Vec<u8> {
+        let mut rom = vec![0; self.rom_size.bytes()];
+        for (address, bytes) in &self.code {
+            rom[*address..][..bytes.len()].copy_from_slice(bytes);
+        }
+
+        rom[locations::NINTENDO_GRAPHICS].copy_from_slice(&NINTENDO_LOGO);
+        rom[locations::GAME_TITLE_OLDER].fill(0);
+        rom[*locations::GAME_TITLE.start()..][..self.title.len()]
+            .copy_from_slice(self.title.as_bytes());
+        rom[locations::COLOR_INDICATOR] = if self.color { 0x80 } else { 0x00 };
+        rom[locations::LICENSEE_CODE].fill(0);
+        rom[locations::GB_SGB_INDICATOR] = if self.sgb { 0x03 } else { 0x00 };
+        rom[locations::CARTRIDGE_TYPE] = self.type_code;
+        rom[locations::ROM_SIZE] = self.rom_size.code();
+        rom[locations::RAM_SIZE] = self.ram_size.code();
+        rom[locations::DESTINATION_CODE] = self.destination as u8;
+        // The new licensee code is used, which keeps the title short of the
+        // Color-Mode flag
+        rom[locations::LICENSEE_CODE_OLDER] = 0x33;
+        rom[locations::MASK_ROM_VERSION_NUMBER] = self.version;
+
+        fix_checksums(&mut rom);
+
+        rom
+    }
+}
+
+/// Builds a minimal 32 KiB ROM only cartridge, free to distribute, to run
+/// the emulator without shipping a game.
+///
+/// The program counts up the byte at 0xC000 forever:
+///
+/// ```text
+/// 0x0150: LD HL, 0xC000
+///         LD [HL], 0
+/// 0x0155: INC [HL]
+///         JR 0x0155
+/// ```
+pub fn test_rom() -> Vec<u8> {
+    HeaderBuilder::new()
+        .title("GBEMU TEST")
+        .code(&[0x21, 0x00, 0xC0, 0x36, 0x00, 0x34, 0x18, 0xFD])
+        .build()
+}
+
+pub trait CartridgeHolder: Memory {
+    fn cartridge_header(&self) -> CartridgeHeader {
+        CartridgeHeader::from(self.cartridge())
+    }
+}
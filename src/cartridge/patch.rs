@@ -0,0 +1,249 @@
+//! ROM patches in the IPS and BPS formats, as romhacks and translations are
+//! distributed.
+//!
+//! [IPS](https://zerosoft.zophar.net/ips.php),
+//! [BPS](https://github.com/blakesmith/rombp/blob/master/docs/bps_spec.md)
+use std::fmt;
+
+use super::RomSize;
+
+const IPS_MAGIC: &[u8; 5] = b"PATCH";
+const IPS_EOF: &[u8; 3] = b"EOF";
+const BPS_MAGIC: &[u8; 4] = b"BPS1";
+/// Checksums of the source, the target and the patch itself
+const BPS_FOOTER_SIZE: usize = 12;
+
+/// Largest image a patch may produce
+const MAX_ROM_SIZE: usize = RomSize::MiB8.bytes();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// Neither an IPS nor a BPS patch
+    InvalidMagic,
+    /// Data ends before the patch does
+    Truncated,
+    /// A record reaches past the image or past the largest ROM size
+    OutOfBounds { offset: usize },
+    /// The patch was made for a different ROM
+    SourceMismatch,
+    /// The patched image or the patch itself fails its checksum
+    Corrupted,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "not an IPS or BPS patch"),
+            Self::Truncated => write!(f, "patch is truncated"),
+            Self::OutOfBounds { offset } => {
+                write!(f, "patch writes out of the ROM at {:#x}", offset)
+            }
+            Self::SourceMismatch => write!(f, "patch was made for a different ROM"),
+            Self::Corrupted => write!(f, "patch is corrupted"),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Applies an IPS or a BPS patch, told apart by their magic
+pub fn apply_patch(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, patch)
+    } else {
+        Err(PatchError::InvalidMagic)
+    }
+}
+
+/// Applies an IPS patch. Records past the end grow the image, up to the
+/// largest ROM size, and the optional truncation extension is honoured.
+pub fn apply_ips(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut reader = PatchReader::new(patch);
+    if reader.take(IPS_MAGIC.len())? != IPS_MAGIC {
+        return Err(PatchError::InvalidMagic);
+    }
+
+    let mut out = rom.to_vec();
+    loop {
+        let offset = reader.take(3)?;
+        if offset == IPS_EOF {
+            break;
+        }
+        let offset = u32::from_be_bytes([0, offset[0], offset[1], offset[2]]) as usize;
+        let size = reader.u16_be()? as usize;
+        // Size 0 marks a run of the same byte
+        let (size, run) = match size {
+            0 => (reader.u16_be()? as usize, Some(reader.u8()?)),
+            size => (size, None),
+        };
+        let end = offset + size;
+        if end > MAX_ROM_SIZE {
+            return Err(PatchError::OutOfBounds { offset });
+        }
+        if end > out.len() {
+            out.resize(end, 0);
+        }
+        match run {
+            Some(byte) => out[offset..end].fill(byte),
+            None => out[offset..end].copy_from_slice(reader.take(size)?),
+        }
+    }
+
+    if let Ok(length) = reader.take(3) {
+        let length = u32::from_be_bytes([0, length[0], length[1], length[2]]) as usize;
+        out.truncate(length);
+    }
+
+    Ok(out)
+}
+
+/// Applies a BPS patch, checking the source and the result against the
+/// checksums it carries
+pub fn apply_bps(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if patch.len() < BPS_MAGIC.len() + BPS_FOOTER_SIZE {
+        return Err(PatchError::Truncated);
+    }
+    let (body, footer) = patch.split_at(patch.len() - BPS_FOOTER_SIZE);
+    let checksum = |index: usize| u32::from_le_bytes(footer[index * 4..][..4].try_into().unwrap());
+    if crc32(&patch[..patch.len() - 4]) != checksum(2) {
+        return Err(PatchError::Corrupted);
+    }
+
+    let mut reader = PatchReader::new(body);
+    if reader.take(BPS_MAGIC.len())? != BPS_MAGIC {
+        return Err(PatchError::InvalidMagic);
+    }
+    let source_size = reader.varint()?;
+    let target_size = reader.varint()?;
+    let metadata_size = reader.varint()?;
+    reader.take(metadata_size)?;
+
+    if source_size != rom.len() || crc32(rom) != checksum(0) {
+        return Err(PatchError::SourceMismatch);
+    }
+    if target_size > MAX_ROM_SIZE {
+        return Err(PatchError::OutOfBounds {
+            offset: target_size,
+        });
+    }
+
+    let mut out = Vec::with_capacity(target_size);
+    let mut source_offset = 0usize;
+    let mut target_offset = 0usize;
+    while !reader.is_empty() {
+        let action = reader.varint()?;
+        let length = (action >> 2) + 1;
+        let offset = out.len();
+        if offset + length > target_size {
+            return Err(PatchError::OutOfBounds { offset });
+        }
+        match action & 0b11 {
+            // SourceRead, the bytes at the same offset in the source
+            0 => out.extend_from_slice(
+                rom.get(offset..offset + length)
+                    .ok_or(PatchError::OutOfBounds { offset })?,
+            ),
+            // TargetRead, the bytes that follow in the patch
+            1 => out.extend_from_slice(reader.take(length)?),
+            // SourceCopy, from anywhere in the source
+            2 => {
+                source_offset = reader.relative(source_offset)?;
+                out.extend_from_slice(rom.get(source_offset..source_offset + length).ok_or(
+                    PatchError::OutOfBounds {
+                        offset: source_offset,
+                    },
+                )?);
+                source_offset += length;
+            }
+            // TargetCopy, from the target written so far, byte by byte as
+            // the copy may overlap its own output
+            _ => {
+                target_offset = reader.relative(target_offset)?;
+                if target_offset >= offset {
+                    return Err(PatchError::OutOfBounds {
+                        offset: target_offset,
+                    });
+                }
+                for _ in 0..length {
+                    out.push(out[target_offset]);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    if out.len() != target_size || crc32(&out) != checksum(1) {
+        return Err(PatchError::Corrupted);
+    }
+    Ok(out)
+}
+
+/// CRC-32 (IEEE), as used by BPS
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+struct PatchReader<'a>(&'a [u8]);
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self(data)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        if self.0.len() < len {
+            return Err(PatchError::Truncated);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, PatchError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16_be(&mut self) -> Result<u16, PatchError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    /// BPS variable length number, 7 bits per byte with the last flagged
+    fn varint(&mut self) -> Result<usize, PatchError> {
+        let mut value = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = self.u8()?;
+            value = (byte as usize & 0x7F)
+                .checked_mul(shift)
+                .and_then(|bits| value.checked_add(bits))
+                .ok_or(PatchError::Corrupted)?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_mul(0x80).ok_or(PatchError::Corrupted)?;
+            value = value.checked_add(shift).ok_or(PatchError::Corrupted)?;
+        }
+    }
+
+    /// Moves `offset` by a signed BPS number, the sign in the lowest bit
+    fn relative(&mut self, offset: usize) -> Result<usize, PatchError> {
+        let value = self.varint()?;
+        let distance = value >> 1;
+        let moved = if value & 1 != 0 {
+            offset.checked_sub(distance)
+        } else {
+            offset.checked_add(distance)
+        };
+        moved.ok_or(PatchError::OutOfBounds { offset })
+    }
+}
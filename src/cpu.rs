@@ -1,12 +1,25 @@
-use crate::instructions::InstructionDecoder;
+use crate::determinism::Nondeterminism;
+#[cfg(debug_assertions)]
+use crate::instructions::opcode_info;
+use crate::instructions::{decode, Instruction, InstructionDecoder};
 use crate::memory::locations;
 use crate::memory::Read;
 use crate::memory::Write;
+use crate::Model;
 
 /// The clock speed of the CPU in cycles per second
-const CPU_CLOCK_SPEED: f64 = 4194304.0;
-const SCANLINE_CLOCK_SPEED: f64 = CPU_CLOCK_SPEED / 456.0;
+pub const CPU_CLOCK_SPEED: f64 = 4194304.0;
+/// The clock speed of the Super Game Boy, derived from the SNES clock
+pub const SGB_CLOCK_SPEED: f64 = 4295454.0;
+/// T-cycles taken to draw a full frame
+pub const CYCLES_PER_FRAME: u64 = 70224;
+/// T-cycles the CPU stays paused while performing a CGB speed switch
+pub const SPEED_SWITCH_CYCLES: usize = 0x20000;
+/// T-cycles taken to jump to an interrupt handler, pushing PC included
+pub const INTERRUPT_DISPATCH_CYCLES: usize = 20;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Interrupt sources, in priority order
 pub enum Interrupt {
     VBlank,
     LCDStat,
@@ -15,6 +28,22 @@ pub enum Interrupt {
     Joypad,
 }
 
+impl Interrupt {
+    /// Every source, in priority order
+    pub const ALL: [Self; 5] = [
+        Self::VBlank,
+        Self::LCDStat,
+        Self::TimerOverflow,
+        Self::SerialTranferComplete,
+        Self::Joypad,
+    ];
+
+    /// Bit of the source in IF and IE
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
 pub enum Flag {
     Zero,
     Subtract,
@@ -22,22 +51,71 @@ pub enum Flag {
     Carry,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Master clock of the console
+pub struct ClockConfig {
+    /// Cycles per second, [`CPU_CLOCK_SPEED`] or [`SGB_CLOCK_SPEED`]
+    pub speed: f64,
+    /// CPU cycles run for every cycle seen by the rest of the hardware.
+    /// Above 1 games get more CPU time per frame, which reduces slowdown,
+    /// while the LCD, the timers and the frame rate keep their timing.
+    pub overclock: u32,
+}
+
+impl ClockConfig {
+    /// The clock of the given model, without overclocking
+    pub fn for_model(model: Model) -> Self {
+        Self {
+            speed: match model {
+                Model::Sgb => SGB_CLOCK_SPEED,
+                Model::Dmg | Model::Cgb => CPU_CLOCK_SPEED,
+            },
+            overclock: 1,
+        }
+    }
+
+    /// Frames per second at this clock speed
+    pub fn frame_rate(&self) -> f64 {
+        self.speed / CYCLES_PER_FRAME as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Power state of the CPU
+pub enum CpuMode {
+    /// Fetching and executing instructions
+    #[default]
+    Running,
+    /// Waiting for an interrupt to become pending (HALT)
+    Halted,
+    /// Very low power mode, left when a selected joypad line goes low (STOP)
+    Stopped,
+    /// CGB speed switch in progress, paused for the remaining T-cycles
+    SpeedSwitch(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+/// The two halves of a [`Register`], laid out to overlap the matching
+/// bytes of the 16-bit value
+pub struct RegisterBytes {
+    #[cfg(target_endian = "little")]
+    pub lo: u8,
+    pub hi: u8,
+    #[cfg(target_endian = "big")]
+    pub lo: u8,
+}
+
 #[derive(Clone, Copy)]
+#[repr(C)]
 pub union Register {
     pub value: u16,
-    pub hi: u8,
-    pub lo: u8,
+    pub bytes: RegisterBytes,
 }
 
 impl std::fmt::Debug for Register {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        unsafe {
-            f.debug_struct("Register")
-                .field("value", &self.value)
-                .field("hi", &self.hi)
-                .field("lo", &self.lo)
-                .finish()
-        }
+        write!(f, "{:#06X}", **self)
     }
 }
 
@@ -74,6 +152,8 @@ pub struct RegisterFile {
 
     /// Interrupt Master Enable
     pub ime: bool,
+    /// Current power state
+    pub mode: CpuMode,
 }
 
 impl Default for RegisterFile {
@@ -86,10 +166,33 @@ impl Default for RegisterFile {
             sp: Register { value: 0x0000 },
             pc: Register { value: 0x0000 },
             ime: false,
+            mode: CpuMode::Running,
         }
     }
 }
 
+impl std::fmt::Display for RegisterFile {
+    /// Formats the registers as `AF=01B0 BC=0013 DE=00D8 HL=014D SP=FFFE PC=0100 [Z-HC]`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flags = unsafe { self.af.bytes.lo };
+        let flag = |mask: u8, name: char| if flags & mask != 0 { name } else { '-' };
+        write!(
+            f,
+            "AF={:04X} BC={:04X} DE={:04X} HL={:04X} SP={:04X} PC={:04X} [{}{}{}{}]",
+            *self.af,
+            *self.bc,
+            *self.de,
+            *self.hl,
+            *self.sp,
+            *self.pc,
+            flag(0b1000_0000, 'Z'),
+            flag(0b0100_0000, 'N'),
+            flag(0b0010_0000, 'H'),
+            flag(0b0001_0000, 'C'),
+        )
+    }
+}
+
 pub trait Registers {
     fn registers(&self) -> &RegisterFile;
     fn registers_mut(&mut self) -> &mut RegisterFile;
@@ -99,30 +202,30 @@ pub trait Registers {
             match flag {
                 Flag::Zero => {
                     if value {
-                        self.registers_mut().af.lo |= 0b1000_0000;
+                        self.registers_mut().af.bytes.lo |= 0b1000_0000;
                     } else {
-                        self.registers_mut().af.lo &= 0b0111_1111;
+                        self.registers_mut().af.bytes.lo &= 0b0111_1111;
                     }
                 }
                 Flag::Subtract => {
                     if value {
-                        self.registers_mut().af.lo |= 0b0100_0000;
+                        self.registers_mut().af.bytes.lo |= 0b0100_0000;
                     } else {
-                        self.registers_mut().af.lo &= 0b1011_1111;
+                        self.registers_mut().af.bytes.lo &= 0b1011_1111;
                     }
                 }
                 Flag::HalfCarry => {
                     if value {
-                        self.registers_mut().af.lo |= 0b0010_0000;
+                        self.registers_mut().af.bytes.lo |= 0b0010_0000;
                     } else {
-                        self.registers_mut().af.lo &= 0b1101_1111;
+                        self.registers_mut().af.bytes.lo &= 0b1101_1111;
                     }
                 }
                 Flag::Carry => {
                     if value {
-                        self.registers_mut().af.lo |= 0b0001_0000;
+                        self.registers_mut().af.bytes.lo |= 0b0001_0000;
                     } else {
-                        self.registers_mut().af.lo &= 0b1110_1111;
+                        self.registers_mut().af.bytes.lo &= 0b1110_1111;
                     }
                 }
             }
@@ -132,29 +235,199 @@ pub trait Registers {
     fn test_flag(&self, flag: Flag) -> bool {
         unsafe {
             match flag {
-                Flag::Zero => self.registers().af.lo & 0b1000_0000 != 0,
-                Flag::Subtract => self.registers().af.lo & 0b0100_0000 != 0,
-                Flag::HalfCarry => self.registers().af.lo & 0b0010_0000 != 0,
-                Flag::Carry => self.registers().af.lo & 0b0001_0000 != 0,
+                Flag::Zero => self.registers().af.bytes.lo & 0b1000_0000 != 0,
+                Flag::Subtract => self.registers().af.bytes.lo & 0b0100_0000 != 0,
+                Flag::HalfCarry => self.registers().af.bytes.lo & 0b0010_0000 != 0,
+                Flag::Carry => self.registers().af.bytes.lo & 0b0001_0000 != 0,
             }
         }
     }
 }
 
 pub trait Cpu: Read + Write + Registers + InstructionDecoder {
-    /// Executes clock cycles based on the delta time
-    fn tick(&mut self, delta_time: f64)
+    /// Returns the hardware model being emulated
+    fn model(&self) -> Model;
+
+    /// Returns the master clock configuration
+    fn clock(&self) -> ClockConfig {
+        ClockConfig::for_model(self.model())
+    }
+
+    /// Returns true when the CGB double speed mode is active
+    fn double_speed(&self) -> bool {
+        self.model() == Model::Cgb && self.read_u8(locations::KEY1) & 0b1000_0000 != 0
+    }
+
+    /// Returns true when an enabled interrupt is requested, regardless of IME
+    fn interrupt_pending(&self) -> bool {
+        self.read_u8(locations::IF) & self.read_u8(locations::IE) & 0b1_1111 != 0
+    }
+
+    /// Returns true when a button is held on a line selected in P1
+    fn joypad_held(&self) -> bool {
+        let p1 = self.read_u8(locations::P1);
+        p1 & 0b11_0000 != 0b11_0000 && p1 & 0b1111 != 0b1111
+    }
+
+    /// Executes a single instruction, or waits 4 T-cycles when not running.
+    /// A pending interrupt is serviced instead when IME is set, jumping to
+    /// its handler (and leaving HALT).
+    ///
+    /// Debug builds check every instruction against the opcode table, and
+    /// panic on the first mismatch (see [`OpcodeInfo::check`](crate::instructions::OpcodeInfo::check)).
+    ///
+    /// Returns the T-cycles taken.
+    fn step(&mut self) -> usize
+    where
+        Self: Sized,
+    {
+        let mut retired = false;
+        let serviceable = matches!(self.registers().mode, CpuMode::Running | CpuMode::Halted)
+            && self.registers().ime
+            && self.interrupt_pending();
+        let cycles = match self.registers().mode {
+            _ if serviceable => self.service_interrupt(),
+            CpuMode::Running => {
+                let pc = *self.registers().pc as usize;
+                if self.oam_dma().active()
+                    && self.accuracy().report_oam_dma_violations
+                    && !locations::HRAM.contains(&pc)
+                {
+                    log::warn!(
+                        target: "gbemu::dma",
+                        "Executing outside HRAM during OAM DMA at PC {:#06x}",
+                        pc
+                    );
+                }
+
+                self.executing();
+                let sp = *self.registers().sp;
+                let bytes = self.fetch_instruction();
+                let opcode = bytes.opcode();
+                log::trace!(target: "gbemu::cpu", "{:#06x}: {:02x?}", pc, bytes.as_slice());
+                let instruction = decode(bytes.as_slice());
+                retired = true;
+                let cycles = instruction.execute(self);
+                #[cfg(debug_assertions)]
+                {
+                    let next = bytes.as_slice().get(1).copied().unwrap_or(0);
+                    let advance = self.registers().pc.wrapping_sub(pc as u16);
+                    if let Err(mismatch) = opcode_info(opcode, next).check(advance, cycles) {
+                        panic!("Opcode table mismatch at PC {:#06x}: {}", pc, mismatch);
+                    }
+                }
+                self.executed(pc as u16, opcode, sp);
+                cycles
+            }
+            CpuMode::Halted => {
+                if self.interrupt_pending() {
+                    log::debug!(target: "gbemu::cpu", "Leaving HALT");
+                    self.registers_mut().mode = CpuMode::Running;
+                }
+                4
+            }
+            CpuMode::Stopped => {
+                if self.joypad_held() {
+                    log::debug!(target: "gbemu::cpu", "Leaving STOP");
+                    self.registers_mut().mode = CpuMode::Running;
+                }
+                4
+            }
+            CpuMode::SpeedSwitch(remaining) => {
+                self.registers_mut().mode = match remaining.checked_sub(4) {
+                    Some(remaining) if remaining > 0 => CpuMode::SpeedSwitch(remaining),
+                    _ => CpuMode::Running,
+                };
+                4
+            }
+        };
+        self.oam_dma_mut().step(cycles);
+        self.stepped(cycles, retired);
+
+        cycles
+    }
+
+    /// Jumps to the handler of the pending interrupt of highest priority,
+    /// clearing IME and its bit in IF.
+    ///
+    /// Returns the T-cycles taken.
+    fn service_interrupt(&mut self) -> usize
     where
         Self: Sized,
     {
-        let cycles_to_execute = (delta_time * CPU_CLOCK_SPEED) as usize; // TODO: Sum this somewhere to fix sync
+        let interrupt_flag = self.read_u8(locations::IF);
+        let enabled = interrupt_flag & self.read_u8(locations::IE);
+        let Some(interrupt) = Interrupt::ALL
+            .into_iter()
+            .find(|interrupt| enabled & interrupt.mask() != 0)
+        else {
+            return 0;
+        };
+        log::debug!(
+            target: "gbemu::irq",
+            "Servicing interrupt {:?} at PC {:#06x}",
+            interrupt,
+            *self.registers().pc
+        );
+        if self.registers().mode == CpuMode::Halted {
+            log::debug!(target: "gbemu::cpu", "Leaving HALT");
+        }
+        self.registers_mut().mode = CpuMode::Running;
+        self.registers_mut().ime = false;
+        self.write_u8(locations::IF, interrupt_flag & !interrupt.mask());
+
+        // make a CALL
+        let return_address = *self.registers().pc;
+        *self.registers_mut().sp = self.registers().sp.wrapping_sub(2);
+        self.write_u16(*self.registers().sp as usize, return_address);
+        // Handlers are 8 bytes apart from 0x40, in priority order
+        self.registers_mut().pc.value = 0x40 + 8 * interrupt as u16;
+        self.interrupt_entered(return_address);
+
+        INTERRUPT_DISPATCH_CYCLES
+    }
 
+    /// Called before an instruction is fetched
+    fn executing(&mut self) {}
+
+    /// Called after an instruction executed, with its address, its opcode and
+    /// SP before the execution
+    fn executed(&mut self, _pc: u16, _opcode: u8, _sp: u16) {}
+
+    /// Called after jumping to an interrupt handler, with the address it returns to
+    fn interrupt_entered(&mut self, _return_address: u16) {}
+
+    /// Called after every [`Cpu::step`] with the T-cycles taken and whether
+    /// an instruction was executed
+    fn stepped(&mut self, _cycles: usize, _retired: bool) {}
+
+    /// Called when [`Cpu::tick`] starts, with the time to emulate
+    fn ticking(&mut self, _delta_time: f64) {}
+
+    /// Called at the end of [`Cpu::reset`], once the registers hold their
+    /// post-boot values
+    fn powered_up(&mut self) {}
+
+    /// Executes clock cycles based on the delta time
+    ///
+    /// Returns the cycles actually executed, measured at the normal speed
+    /// (and not overclocked) clock, which overshoot the delta time by up to
+    /// one instruction.
+    fn tick(&mut self, delta_time: f64) -> usize
+    where
+        Self: Sized,
+    {
+        self.ticking(delta_time);
+        let double_speed = self.double_speed();
+        let clock = self.clock();
+        let mut cycles_to_execute = (delta_time * clock.speed) as usize * clock.overclock as usize; // TODO: Sum this somewhere to fix sync
+        if double_speed {
+            cycles_to_execute *= 2;
+        }
         // Instructions execution
         let mut cycles_count = 0;
         loop {
-            let opcode = self.fetch();
-            let instruction = self.decode(opcode);
-            cycles_count += instruction.execute(self);
+            cycles_count += self.step();
 
             // We finished executing the instructions for this tick
             if cycles_count >= cycles_to_execute {
@@ -162,94 +435,17 @@ pub trait Cpu: Read + Write + Registers + InstructionDecoder {
             }
         }
 
-        // Timers
-        let divider_counter = self.read_u8(locations::DIV);
-        let divider_ticks = (delta_time * 16384.0) as u64; // TODO: Sum this somewhere to fix sync
-        for _ in 0..divider_ticks {
-            // Cannot use set_u8 because it would trigger the write memory trap
-            self.memory_mut()[locations::DIV] = divider_counter.wrapping_add(1);
-        }
-
-        let timer_ctrl = self.read_u8(locations::TAC);
-        let timer_enable = timer_ctrl & 0b100 != 0;
-        if timer_enable {
-            let timer_freq = match timer_ctrl & 0b11 {
-                0b00 => 4096.0,
-                0b01 => 262144.0,
-                0b10 => 65536.0,
-                0b11 => 16384.0,
-                _ => unreachable!(),
-            };
-            let timer_ticks = (delta_time * timer_freq) as u64; // TODO: Sum this somewhere to fix sync
-            for _ in 0..timer_ticks {
-                let timer_counter = self.read_u8(locations::TIMA);
-
-                if timer_counter == 255 {
-                    let timer_modulo = self.read_u8(locations::TMA);
-                    self.write_u8(locations::TIMA, timer_modulo);
-                    self.interrupt(Interrupt::TimerOverflow);
-                } else {
-                    self.write_u8(locations::TIMA, timer_counter.wrapping_add(1));
-                }
-            }
-        }
-
-        // LCD
-        let scanline_ticks = (delta_time * SCANLINE_CLOCK_SPEED) as u64; // TODO: Sum this somewhere to fix sync
-        for _ in 0..scanline_ticks {}
-
-        // Interrupts
-        if self.registers().ime {
-            let interrupt_flag = self.read_u8(locations::IF);
-            let interrupt_enable = self.read_u8(locations::IE);
-
-            // There are interrupts to process
-            if interrupt_flag > 0 {
-                let enabled_interrupts = interrupt_flag & interrupt_enable;
-                // In reverse priority order because of the way the interrupts
-                // are serviced, first is the highest priority then a RET in the
-                // int. handler will service the other interrupts
-                for i in (0..5).rev() {
-                    // Service i-th interrupt
-                    if enabled_interrupts & (1 << i as u8) != 0 {
-                        self.registers_mut().ime = false;
-                        // Reset bit i of IF
-                        self.write_u8(locations::IF, interrupt_flag & !(1 << i));
-
-                        // make a CALL
-                        *self.registers_mut().sp -= 2;
-                        self.write_u16(*self.registers().sp as usize, *self.registers().pc);
-
-                        match 1 << i as u8 {
-                            0b0000_0001 => {
-                                // VBlank
-                                self.registers_mut().pc.value = 0x40;
-                            }
-                            0b0000_0010 => {
-                                // LCDStat
-                                self.registers_mut().pc.value = 0x48;
-                            }
-                            0b0000_0100 => {
-                                // TimerOverflow
-                                self.registers_mut().pc.value = 0x50;
-                            }
-                            0b0000_1000 => {
-                                // SerialTranferComplete
-                                self.registers_mut().pc.value = 0x58;
-                            }
-                            0b0001_0000 => {
-                                // Joypad
-                                self.registers_mut().pc.value = 0x60;
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                }
-            }
+        let cycles_count = cycles_count / clock.overclock as usize;
+        if double_speed {
+            cycles_count / 2
+        } else {
+            cycles_count
         }
     }
 
+    /// Requests an interrupt by setting its bit in IF
     fn interrupt(&mut self, interrupt: Interrupt) {
+        log::trace!(target: "gbemu::irq", "Requested {:?}", interrupt);
         let interrupt_flag = self.read_u8(locations::IF);
         match interrupt {
             Interrupt::VBlank => {
@@ -270,25 +466,31 @@ pub trait Cpu: Read + Write + Registers + InstructionDecoder {
         }
     }
 
-    /// TODO: CHANGE VALUES WHEN IMPLEMENTING THE GAMEBOY COLOR (CGB)
-    /// TODO: [REFERENCE](https://gbdev.io/pandocs/Power_Up_Sequence.html)
+    /// Puts the machine in the state the boot ROM of the model leaves
+    ///
+    /// [REFERENCE](https://gbdev.io/pandocs/Power_Up_Sequence.html)
     fn reset(&mut self) {
-        self.memory_mut().fill_with(rand::random);
-        self.ram_mut().fill_with(rand::random);
+        let (ram_init, sram_init) = (self.ram_init(), self.sram_init());
+        ram_init.fill(self.memory_mut());
+        sram_init.fill(self.ram_mut());
 
-        self.registers_mut().af.hi = 0x01; // TODO: 0x11 if GBColor
-        self.registers_mut().af.lo = if self.memory()[locations::COMPLEMENT_CHECK] == 0x00 {
-            0b1000_0000
-        } else {
-            0b1011_0000
+        let (af, bc, de, hl) = match self.model() {
+            // The DMG flags depend on the header checksum
+            Model::Dmg if self.read_u8(locations::COMPLEMENT_CHECK) == 0x00 => {
+                (0x0180, 0x0013, 0x00D8, 0x014D)
+            }
+            Model::Dmg => (0x01B0, 0x0013, 0x00D8, 0x014D),
+            Model::Sgb => (0x0100, 0x0014, 0x0000, 0xC060),
+            Model::Cgb => (0x1180, 0x0000, 0xFF56, 0x000D),
         };
-        self.registers_mut().bc.lo = 0x13;
-        self.registers_mut().de.lo = 0xD8;
-        self.registers_mut().hl.hi = 0x01;
-        self.registers_mut().hl.lo = 0x4D;
+        self.registers_mut().af.value = af;
+        self.registers_mut().bc.value = bc;
+        self.registers_mut().de.value = de;
+        self.registers_mut().hl.value = hl;
         self.registers_mut().pc.value = 0x0100;
         self.registers_mut().sp.value = 0xFFFE;
         self.registers_mut().ime = false;
+        self.registers_mut().mode = CpuMode::Running;
 
         self.memory_mut()[locations::P1] = 0xCF;
         self.memory_mut()[locations::SB] = 0x00;
@@ -318,7 +520,11 @@ pub trait Cpu: Read + Write + Registers + InstructionDecoder {
         self.memory_mut()[locations::NR44] = 0xBF;
         self.memory_mut()[locations::NR50] = 0x77;
         self.memory_mut()[locations::NR51] = 0xF3;
-        self.memory_mut()[locations::NR52] = 0xF1; // TODO: 0xF0 if SGB
+        self.memory_mut()[locations::NR52] = if self.model() == Model::Sgb {
+            0xF0
+        } else {
+            0xF1
+        };
         self.memory_mut()[locations::LCDC] = 0x91;
         self.memory_mut()[locations::STAT] = 0x85;
         self.memory_mut()[locations::SCY] = 0x00;
@@ -331,8 +537,96 @@ pub trait Cpu: Read + Write + Registers + InstructionDecoder {
         self.memory_mut()[locations::OBP1] = 0xFF;
         self.memory_mut()[locations::WY] = 0x00;
         self.memory_mut()[locations::WX] = 0x00;
+        self.memory_mut()[locations::KEY1] = if self.model() == Model::Cgb {
+            0x7E
+        } else {
+            0xFF
+        };
         self.memory_mut()[locations::IE] = 0x00;
+        self.powered_up();
     }
 }
 
-impl Cpu for crate::GameBoy {}
+#[derive(Debug, Clone, Copy, Default)]
+/// Monotonic execution counters, kept across resets and state loads but
+/// for the dot remainder, which is saved in states
+pub(crate) struct Counters {
+    /// T-cycles executed, at the current speed
+    pub(crate) cycles: u64,
+    /// Instructions executed
+    pub(crate) instructions: u64,
+    /// Frames elapsed
+    pub(crate) frames: u64,
+    /// Normal speed T-cycles elapsed in the current frame
+    pub(crate) frame_cycles: u64,
+    /// T-cycles not yet turned into LCD dots, when double speed or overclocked
+    pub(crate) dot_remainder: u64,
+}
+
+impl Cpu for crate::GameBoy {
+    fn model(&self) -> Model {
+        self.model
+    }
+
+    fn executing(&mut self) {
+        self.instruction_pc = *self.registers.pc;
+        self.trace_instruction();
+    }
+
+    #[cfg(feature = "debugger")]
+    fn executed(&mut self, pc: u16, opcode: u8, sp: u16) {
+        self.track_call_stack(pc, opcode, sp);
+    }
+
+    #[cfg_attr(not(feature = "debugger"), allow(unused_variables))]
+    fn interrupt_entered(&mut self, return_address: u16) {
+        #[cfg(feature = "debugger")]
+        self.track_interrupt(return_address);
+        // Handlers are 8 bytes apart from 0x40, in priority order
+        let handler = (*self.registers().pc as usize - 0x40) / 8;
+        self.log_interrupt(Interrupt::ALL[handler]);
+        self.interrupt_serviced(Interrupt::ALL[handler]);
+    }
+
+    fn clock(&self) -> ClockConfig {
+        self.clock
+    }
+
+    fn stepped(&mut self, cycles: usize, retired: bool) {
+        // The LCD runs at the normal speed clock, and is not overclocked
+        let divider = self.clock.overclock as u64 * if self.double_speed() { 2 } else { 1 };
+        let counters = &mut self.counters;
+        counters.cycles += cycles as u64;
+        counters.instructions += retired as u64;
+
+        counters.dot_remainder += cycles as u64;
+        let dots = counters.dot_remainder / divider;
+        counters.dot_remainder %= divider;
+
+        counters.frame_cycles += dots;
+        counters.frames += counters.frame_cycles / CYCLES_PER_FRAME;
+        counters.frame_cycles %= CYCLES_PER_FRAME;
+
+        #[cfg(feature = "ppu")]
+        self.lcd_step(dots);
+        #[cfg(feature = "serial")]
+        self.serial_step(dots);
+        #[cfg(feature = "apu")]
+        self.apu.step(dots);
+        self.rtc.step(dots, self.clock.speed as u64);
+        self.timer_step(dots);
+        #[cfg(feature = "debugger")]
+        self.profile_step(cycles as u64);
+        self.watch_interrupt_flags();
+    }
+
+    fn powered_up(&mut self) {
+        self.boot();
+    }
+
+    fn ticking(&mut self, _delta_time: f64) {
+        if !self.fixed_step {
+            self.nondeterministic(Nondeterminism::HostTimeTick);
+        }
+    }
+}
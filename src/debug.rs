@@ -0,0 +1,541 @@
+//! Debugging facilities: breakpoints, watchpoints, frozen addresses and a
+//! shadow call stack.
+//!
+//! Locations are [`BankedAddr`]s, so that `05:4000` (bank 5) is told apart
+//! from `4000` (whatever bank is mapped), in the switchable ROM area and in
+//! the cartridge RAM. They are resolved against the MBC state on every
+//! access.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    cpu::{Cpu, Registers, CYCLES_PER_FRAME},
+    memory::{locations, Memory, MemoryMode, Read},
+    run::Timeout,
+    GameBoy,
+};
+
+/// Frames [`GameBoy::step_over`] and [`GameBoy::step_out`] run at most
+/// before giving up, in case the subroutine never returns
+pub const STEP_TIMEOUT_FRAMES: u64 = 600;
+
+/// Maximum depth of the shadow call stack, the outermost frames are dropped
+/// past it (e.g. code jumping out of subroutines without returning)
+pub const MAX_CALL_DEPTH: usize = 256;
+
+/// Parses an address typed by the user: hex, with or without a `$` or `0x`
+/// prefix, or the name of an I/O register such as `STAT`
+pub fn parse_address(text: &str) -> Option<u16> {
+    if let Some(address) = locations::by_name(text) {
+        return Some(address as u16);
+    }
+    let hex = text.trim_start_matches("0x").trim_start_matches('$');
+    u16::from_str_radix(hex, 16).ok()
+}
+
+/// Parses a location typed by the user: `BB:AAAA` for an address in a
+/// bank, or an address as accepted by [`parse_address`] for any bank
+pub fn parse_banked_address(text: &str) -> Option<BankedAddr> {
+    match text.split_once(':') {
+        Some((bank, address)) => Some(BankedAddr::new(
+            usize::from_str_radix(bank, 16).ok()?,
+            u16::from_str_radix(address, 16).ok()?,
+        )),
+        None => parse_address(text).map(BankedAddr::from),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An address, optionally in a given ROM or cartridge RAM bank.
+///
+/// The bank only matters in the banked areas (0x4000..=0x7FFF and
+/// 0xA000..=0xBFFF), it is dropped elsewhere.
+pub struct BankedAddr {
+    /// Bank the address must be mapped from, `None` for whatever bank is
+    pub bank: Option<usize>,
+    pub addr: u16,
+}
+
+impl BankedAddr {
+    /// The address in the bank
+    pub fn new(bank: usize, addr: u16) -> Self {
+        Self {
+            bank: Self::is_banked(addr).then_some(bank),
+            addr,
+        }
+    }
+
+    /// The address in whatever bank is mapped
+    pub fn any(addr: u16) -> Self {
+        Self { bank: None, addr }
+    }
+
+    /// Returns true if the address is in an area where banks are switched
+    pub fn is_banked(addr: u16) -> bool {
+        matches!(addr, 0x4000..=0x7FFF | 0xA000..=0xBFFF)
+    }
+
+    /// Returns true if the location is the address, read from the bank
+    /// mapped there (see [`GameBoy::mapped_bank`])
+    pub fn matches(&self, addr: u16, mapped_bank: Option<usize>) -> bool {
+        self.addr == addr && (self.bank.is_none() || self.bank == mapped_bank)
+    }
+}
+
+impl From<u16> for BankedAddr {
+    fn from(addr: u16) -> Self {
+        Self::any(addr)
+    }
+}
+
+impl std::fmt::Display for BankedAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.bank {
+            Some(bank) => write!(f, "{:02X}:{:04X}", bank, self.addr),
+            None => write!(f, "{:04X}", self.addr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How a frame was entered
+pub enum FrameKind {
+    Call,
+    Rst,
+    Interrupt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An entry of the shadow call stack
+pub struct Frame {
+    pub kind: FrameKind,
+    /// Address of the subroutine or handler entered
+    pub target: u16,
+    /// ROM bank mapped when entering, relevant for targets in 0x4000..=0x7FFF
+    pub target_bank: usize,
+    /// Address execution resumes at once the frame returns
+    pub return_address: u16,
+    /// ROM bank mapped at the call site
+    pub return_bank: usize,
+    /// Stack address holding the return address
+    pub sp: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A location of a [`GameBoy::backtrace`]
+pub struct BacktraceEntry {
+    pub address: u16,
+    /// ROM bank, 0 outside 0x4000..=0x7FFF
+    pub bank: usize,
+    /// Closest symbol at or before the address, with the offset from it
+    pub symbol: Option<(String, u16)>,
+}
+
+impl std::fmt::Display for BacktraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02X}:{:04X}", self.bank, self.address)?;
+        match &self.symbol {
+            Some((name, 0)) => write!(f, " {}", name),
+            Some((name, offset)) => write!(f, " {}+{:#x}", name, offset),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Labels by bank and address, as found in RGBDS/no$gmb `.sym` files
+pub struct SymbolTable {
+    symbols: BTreeMap<(usize, u16), String>,
+}
+
+impl SymbolTable {
+    /// Parses `BB:AAAA Label` lines, skipping comments (`;`) and anything unparsable
+    pub fn parse(text: &str) -> Self {
+        let symbols = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.split(';').next()?.trim();
+                let (location, name) = line.split_once(char::is_whitespace)?;
+                let (bank, address) = location.split_once(':')?;
+                Some((
+                    (
+                        usize::from_str_radix(bank, 16).ok()?,
+                        u16::from_str_radix(address, 16).ok()?,
+                    ),
+                    name.trim().to_string(),
+                ))
+            })
+            .collect();
+
+        Self { symbols }
+    }
+
+    pub fn insert(&mut self, bank: usize, address: u16, name: impl Into<String>) {
+        self.symbols.insert((bank, address), name.into());
+    }
+
+    /// Returns the closest symbol at or before the address in the same bank,
+    /// with the offset from it
+    pub fn lookup(&self, bank: usize, address: u16) -> Option<(&str, u16)> {
+        self.symbols
+            .range(..=(bank, address))
+            .next_back()
+            .filter(|((symbol_bank, _), _)| *symbol_bank == bank)
+            .map(|((_, symbol_address), name)| (name.as_str(), address - symbol_address))
+    }
+
+    /// Returns the location of the label, e.g. to break on it
+    pub fn address_of(&self, name: &str) -> Option<BankedAddr> {
+        self.symbols
+            .iter()
+            .find(|(_, symbol)| *symbol == name)
+            .map(|((bank, address), _)| BankedAddr::new(*bank, *address))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A write changed the value of a watched address
+pub struct WatchHit {
+    pub address: u16,
+    pub old: u8,
+    pub new: u8,
+    /// Address of the instruction following the write
+    pub pc: u16,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Breakpoints, watchpoints, frozen addresses, shadow call stack and symbols
+pub struct Debugger {
+    breakpoints: BTreeSet<BankedAddr>,
+    watches: BTreeSet<BankedAddr>,
+    freezes: BTreeMap<BankedAddr, u8>,
+    hits: Vec<WatchHit>,
+    call_stack: Vec<Frame>,
+    symbols: SymbolTable,
+}
+
+impl Debugger {
+    /// Returns the shadow call stack, outermost frame first
+    pub fn call_stack(&self) -> &[Frame] {
+        &self.call_stack
+    }
+
+    /// Returns the symbols used to name addresses
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Replaces the symbols used to name addresses
+    pub fn set_symbols(&mut self, symbols: SymbolTable) {
+        self.symbols = symbols;
+    }
+
+    fn push_frame(&mut self, frame: Frame) {
+        if self.call_stack.len() == MAX_CALL_DEPTH {
+            self.call_stack.remove(0);
+        }
+        self.call_stack.push(frame);
+    }
+
+    /// Drops the frames whose return address slot was released, by a return
+    /// or by the code manipulating the stack
+    fn unwind(&mut self, sp: u16) {
+        while self.call_stack.last().is_some_and(|frame| sp > frame.sp) {
+            self.call_stack.pop();
+        }
+    }
+
+    /// Stops [`Condition::Breakpoint`](crate::run::Condition::Breakpoint)
+    /// runs before executing the instruction at the location
+    pub fn add_breakpoint(&mut self, location: impl Into<BankedAddr>) {
+        self.breakpoints.insert(location.into());
+    }
+
+    pub fn remove_breakpoint(&mut self, location: impl Into<BankedAddr>) {
+        self.breakpoints.remove(&location.into());
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &BankedAddr> {
+        self.breakpoints.iter()
+    }
+
+    /// Reports every change to the value at the location
+    pub fn watch(&mut self, location: impl Into<BankedAddr>) {
+        self.watches.insert(location.into());
+    }
+
+    pub fn unwatch(&mut self, location: impl Into<BankedAddr>) {
+        self.watches.remove(&location.into());
+    }
+
+    /// Locks the location to the value, like RAM-lock cheats do:
+    /// reads return it and writes are dropped. A location in a bank only
+    /// applies while the bank is mapped.
+    pub fn freeze(&mut self, location: impl Into<BankedAddr>, value: u8) {
+        self.freezes.insert(location.into(), value);
+    }
+
+    pub fn unfreeze(&mut self, location: impl Into<BankedAddr>) {
+        self.freezes.remove(&location.into());
+    }
+
+    /// Returns the watch hits since the last call, oldest first
+    pub fn take_watch_hits(&mut self) -> Vec<WatchHit> {
+        std::mem::take(&mut self.hits)
+    }
+
+    /// Returns true if watch hits are waiting to be taken
+    pub fn has_watch_hits(&self) -> bool {
+        !self.hits.is_empty()
+    }
+
+    /// Looks the address up in the bank it is mapped from, then in any bank
+    fn lookup<T>(
+        map: impl Fn(&BankedAddr) -> Option<T>,
+        address: u16,
+        mapped_bank: Option<usize>,
+    ) -> Option<T> {
+        mapped_bank
+            .and_then(|bank| map(&BankedAddr::new(bank, address)))
+            .or_else(|| map(&BankedAddr::any(address)))
+    }
+
+    // The mapped bank is only computed once a location could match, these
+    // run on every memory access
+
+    pub(crate) fn frozen(
+        &self,
+        address: u16,
+        mapped_bank: impl FnOnce() -> Option<usize>,
+    ) -> Option<u8> {
+        if self.freezes.is_empty() {
+            return None;
+        }
+        Self::lookup(|at| self.freezes.get(at).copied(), address, mapped_bank())
+    }
+
+    pub(crate) fn watched(
+        &self,
+        address: u16,
+        mapped_bank: impl FnOnce() -> Option<usize>,
+    ) -> bool {
+        !self.watches.is_empty()
+            && Self::lookup(
+                |at| self.watches.contains(at).then_some(()),
+                address,
+                mapped_bank(),
+            )
+            .is_some()
+    }
+
+    pub(crate) fn is_breakpoint(
+        &self,
+        address: u16,
+        mapped_bank: impl FnOnce() -> Option<usize>,
+    ) -> bool {
+        !self.breakpoints.is_empty()
+            && Self::lookup(
+                |at| self.breakpoints.contains(at).then_some(()),
+                address,
+                mapped_bank(),
+            )
+            .is_some()
+    }
+}
+
+impl GameBoy {
+    /// Returns the debugger state
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Returns the mutable debugger state
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    /// Returns the bank the address is read from in the banked areas: the
+    /// ROM bank in 0x4000..=0x7FFF, the cartridge RAM bank in
+    /// 0xA000..=0xBFFF (`None` while an RTC register is mapped there)
+    pub fn mapped_bank(&self, address: u16) -> Option<usize> {
+        match address {
+            0x4000..=0x7FFF => Some(self.rom_bank_idx()),
+            0xA000..=0xBFFF => match self.memory_mode() {
+                MemoryMode::MBC3 {
+                    rtc_selected: Some(_),
+                    ..
+                } => None,
+                _ => Some(self.ram_bank_idx()),
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the location the address currently maps to
+    pub fn banked_address(&self, address: u16) -> BankedAddr {
+        BankedAddr {
+            bank: self.mapped_bank(address),
+            addr: address,
+        }
+    }
+
+    /// Returns the address the location is visible at, `None` while another
+    /// bank is mapped there
+    pub fn resolve(&self, location: BankedAddr) -> Option<u16> {
+        location
+            .matches(location.addr, self.mapped_bank(location.addr))
+            .then_some(location.addr)
+    }
+
+    /// Returns true if the next instruction is at a breakpoint
+    pub fn at_breakpoint(&self) -> bool {
+        let pc = *self.registers().pc;
+        self.debugger.is_breakpoint(pc, || self.mapped_bank(pc))
+    }
+
+    /// Returns the ROM bank an address is read from, 0 outside the switchable bank
+    fn bank_of(&self, address: u16) -> usize {
+        match address {
+            0x4000..=0x7FFF => self.rom_bank_idx(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the current PC followed by the return addresses of the shadow
+    /// call stack, innermost first
+    pub fn backtrace(&self) -> Vec<BacktraceEntry> {
+        let pc = *self.registers().pc;
+        std::iter::once((pc, self.bank_of(pc)))
+            .chain(
+                self.debugger
+                    .call_stack
+                    .iter()
+                    .rev()
+                    .map(|frame| (frame.return_address, frame.return_bank)),
+            )
+            .map(|(address, bank)| BacktraceEntry {
+                address,
+                bank,
+                symbol: self
+                    .debugger
+                    .symbols
+                    .lookup(bank, address)
+                    .map(|(name, offset)| (name.to_string(), offset)),
+            })
+            .collect()
+    }
+
+    /// Executes the next instruction, running a called subroutine (CALL or RST)
+    /// until it returns to the instruction after it.
+    ///
+    /// Returns the T-cycles taken.
+    pub fn step_over(&mut self) -> Result<u64, Timeout> {
+        let pc = *self.registers().pc;
+        let return_address = match self.read_u8(pc as usize) {
+            0xCD | 0xC4 | 0xCC | 0xD4 | 0xDC => pc.wrapping_add(3),
+            0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => pc.wrapping_add(1),
+            _ => return Ok(self.step() as u64),
+        };
+
+        // Temporary breakpoint on the return address, at the current depth so
+        // recursive calls don't stop it early
+        let depth = self.debugger.call_stack.len();
+        self.run_while(|gb| {
+            *gb.registers().pc != return_address || gb.debugger.call_stack.len() > depth
+        })
+    }
+
+    /// Runs until the innermost frame of the shadow call stack returns.
+    ///
+    /// With an empty call stack it only executes the next instruction.
+    /// Returns the T-cycles taken.
+    pub fn step_out(&mut self) -> Result<u64, Timeout> {
+        let depth = self.debugger.call_stack.len();
+        if depth == 0 {
+            return Ok(self.step() as u64);
+        }
+
+        self.run_while(|gb| gb.debugger.call_stack.len() >= depth)
+    }
+
+    /// Steps at least once and while the predicate holds, up to [`STEP_TIMEOUT_FRAMES`]
+    fn run_while(&mut self, predicate: impl Fn(&GameBoy) -> bool) -> Result<u64, Timeout> {
+        let start = self.cycles();
+        let limit = STEP_TIMEOUT_FRAMES * CYCLES_PER_FRAME;
+        loop {
+            self.step();
+            if !predicate(self) {
+                return Ok(self.cycles() - start);
+            }
+            if self.cycles() - start >= limit {
+                return Err(Timeout {
+                    frames: STEP_TIMEOUT_FRAMES,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn track_call_stack(&mut self, pc: u16, opcode: u8, sp: u16) {
+        let new_sp = *self.registers().sp;
+        self.debugger.unwind(new_sp);
+
+        let kind = match opcode {
+            // CALL a16, CALL cc,a16
+            0xCD | 0xC4 | 0xCC | 0xD4 | 0xDC => FrameKind::Call,
+            // RST n
+            0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => FrameKind::Rst,
+            _ => return,
+        };
+        // Not taken
+        if new_sp != sp.wrapping_sub(2) {
+            return;
+        }
+
+        let target = *self.registers().pc;
+        let return_address = pc.wrapping_add(if kind == FrameKind::Call { 3 } else { 1 });
+        self.debugger.push_frame(Frame {
+            kind,
+            target,
+            target_bank: self.bank_of(target),
+            return_address,
+            return_bank: self.bank_of(return_address),
+            sp: new_sp,
+        });
+    }
+
+    pub(crate) fn track_interrupt(&mut self, return_address: u16) {
+        let sp = *self.registers().sp;
+        self.debugger.unwind(sp);
+
+        let target = *self.registers().pc;
+        self.debugger.push_frame(Frame {
+            kind: FrameKind::Interrupt,
+            target,
+            target_bank: 0,
+            return_address,
+            return_bank: self.bank_of(return_address),
+            sp,
+        });
+    }
+
+    pub(crate) fn record_watch_hit(&mut self, address: usize, old: u8, new: u8) {
+        let pc = *self.registers().pc;
+        log::debug!(
+            target: "gbemu::debug",
+            "{:#06x} changed {:#04x} -> {:#04x} before PC {:#06x}",
+            address,
+            old,
+            new,
+            pc
+        );
+        self.debugger.hits.push(WatchHit {
+            address: address as u16,
+            old,
+            new,
+            pc,
+        });
+    }
+}
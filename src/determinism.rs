@@ -0,0 +1,85 @@
+//! Audit of the nondeterminism sources, for TAS, netplay and reinforcement
+//! learning setups which need every run with the same inputs to match.
+//!
+//! The emulation itself is deterministic, only the host can make it diverge:
+//!
+//! - RAM filled with [`RamInit::Random`](crate::memory::RamInit::Random) at
+//!   power on, the default, instead of a seed
+//! - [`Cpu::tick`](crate::cpu::Cpu::tick) called with host measured time,
+//!   where [`GameBoy::run_frame`] or [`GameBoy::run_until_samples`] advance
+//!   by fixed steps
+//! - the MBC3 clock following the host clock, see [`RtcTime`](crate::rtc::RtcTime)
+use std::fmt;
+
+use crate::GameBoy;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// What to do when a nondeterminism source is used
+pub enum AuditMode {
+    /// Don't look for nondeterminism sources
+    #[default]
+    Off,
+    /// Log a warning and record the source, see
+    /// [`GameBoy::take_nondeterminism`]
+    Report,
+    /// Panic, for setups which must never diverge
+    Strict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A source of divergence between runs with the same inputs
+pub enum Nondeterminism {
+    /// Internal RAM filled from the host random generator at power on
+    RandomRam,
+    /// External RAM filled from the host random generator at power on
+    RandomSram,
+    /// The emulation was advanced by an arbitrary, likely host measured, time
+    HostTimeTick,
+    /// The MBC3 clock follows the host clock
+    HostRtc,
+}
+
+impl fmt::Display for Nondeterminism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RandomRam => write!(f, "internal RAM initialised at random, use a seed"),
+            Self::RandomSram => write!(f, "external RAM initialised at random, use a seed"),
+            Self::HostTimeTick => write!(
+                f,
+                "emulation advanced by host time, use run_frame or run_until_samples"
+            ),
+            Self::HostRtc => write!(f, "cartridge clock following the host clock"),
+        }
+    }
+}
+
+impl GameBoy {
+    pub fn determinism_audit(&self) -> AuditMode {
+        self.audit
+    }
+
+    /// Selects what happens when a nondeterminism source is used from now on
+    pub fn set_determinism_audit(&mut self, mode: AuditMode) {
+        self.audit = mode;
+    }
+
+    /// Returns the sources used since the last call, each once, in
+    /// [`AuditMode::Report`]
+    pub fn take_nondeterminism(&mut self) -> Vec<Nondeterminism> {
+        std::mem::take(self.nondeterminism.get_mut())
+    }
+
+    pub(crate) fn nondeterministic(&self, source: Nondeterminism) {
+        match self.audit {
+            AuditMode::Off => {}
+            AuditMode::Report => {
+                let mut sources = self.nondeterminism.borrow_mut();
+                if !sources.contains(&source) {
+                    log::warn!(target: "gbemu::determinism", "Nondeterminism: {}", source);
+                    sources.push(source);
+                }
+            }
+            AuditMode::Strict => panic!("Nondeterminism: {}", source),
+        }
+    }
+}
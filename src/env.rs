@@ -0,0 +1,178 @@
+//! Reinforcement learning environment backend, in the style of
+//! [Gym](https://gymnasium.farama.org): the agent observes the screen or
+//! slices of memory, acts by holding [`Buttons`] for a few frames and is
+//! rewarded by a closure reading the game state.
+//!
+//! ```no_run
+//! use gbemu::{
+//!     env::{Env, Observation},
+//!     joypad::Buttons,
+//!     memory::Read,
+//!     video::PixelFormat,
+//!     GameBoy,
+//! };
+//!
+//! let rom = std::fs::read("game.gb").unwrap();
+//! let mut env = Env::new(GameBoy::new(&rom), Observation::Screen(PixelFormat::Indexed))
+//!     .frame_skip(4)
+//!     // Score in WRAM, the game is over when the lives reach 0
+//!     .reward(|gb| gb.read_u8(0xC0A0) as f32)
+//!     .done(|gb| gb.read_u8(0xC0A1) == 0);
+//!
+//! let mut observation = env.reset();
+//! loop {
+//!     let step = env.step(Buttons::A | Buttons::RIGHT);
+//!     observation = step.observation;
+//!     if step.done {
+//!         observation = env.reset();
+//!     }
+//! }
+//! ```
+use std::ops::RangeInclusive;
+
+use crate::{joypad::Buttons, memory::Read, savestate::SaveState, video::PixelFormat, GameBoy};
+
+/// Computes a value from the game state after a frame
+pub type StateFn<T> = Box<dyn FnMut(&GameBoy) -> T>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// What the agent sees after every step
+pub enum Observation {
+    /// The last complete frame, converted to the format
+    Screen(PixelFormat),
+    /// Bytes of the memory ranges, one after the other
+    Memory(Vec<RangeInclusive<usize>>),
+}
+
+impl Observation {
+    /// Size of the observations in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Screen(format) => format.frame_size(),
+            Self::Memory(ranges) => ranges.iter().map(|range| range.clone().count()).sum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Outcome of an action
+pub struct Step {
+    pub observation: Vec<u8>,
+    /// Sum of the rewards of the frames run
+    pub reward: f32,
+    /// The episode ended, the environment must be reset
+    pub done: bool,
+}
+
+/// A [`GameBoy`] wrapped as an environment, see the [module](self) docs
+pub struct Env {
+    gb: GameBoy,
+    observation: Observation,
+    /// Frames run for every action
+    frame_skip: u32,
+    reward: StateFn<f32>,
+    done: StateFn<bool>,
+    /// State episodes start from
+    start: SaveState,
+}
+
+impl Env {
+    /// Wraps the console, episodes start from its current state. By default
+    /// every action lasts one frame, with no reward and no end.
+    pub fn new(gb: GameBoy, observation: Observation) -> Self {
+        let start = gb.save_state();
+        Self {
+            gb,
+            observation,
+            frame_skip: 1,
+            reward: Box::new(|_| 0.0),
+            done: Box::new(|_| false),
+            start,
+        }
+    }
+
+    /// Holds every action for `frames` frames
+    pub fn frame_skip(mut self, frames: u32) -> Self {
+        assert!(frames > 0, "The frame skip must be at least 1");
+        self.frame_skip = frames;
+        self
+    }
+
+    /// Rewards the agent after every frame
+    pub fn reward(mut self, reward: impl FnMut(&GameBoy) -> f32 + 'static) -> Self {
+        self.reward = Box::new(reward);
+        self
+    }
+
+    /// Ends the episode when true after a frame
+    pub fn done(mut self, done: impl FnMut(&GameBoy) -> bool + 'static) -> Self {
+        self.done = Box::new(done);
+        self
+    }
+
+    /// Starts the next episodes from `state`, e.g. past the title screen
+    pub fn set_start(&mut self, state: SaveState) {
+        self.start = state;
+    }
+
+    pub fn gb(&self) -> &GameBoy {
+        &self.gb
+    }
+
+    pub fn gb_mut(&mut self) -> &mut GameBoy {
+        &mut self.gb
+    }
+
+    /// Every action, one button held at a time
+    pub fn actions(&self) -> [Buttons; 8] {
+        Buttons::ALL
+    }
+
+    /// Starts a new episode and returns the first observation.
+    ///
+    /// Save states don't hold the screen, so a frame is run with no button
+    /// held before observing.
+    pub fn reset(&mut self) -> Vec<u8> {
+        self.gb
+            .load_state(&self.start)
+            .expect("The start state was saved by this console");
+        self.gb.set_buttons(Buttons::empty());
+        self.gb.run_frame();
+        self.observe()
+    }
+
+    /// Holds `action` for the frame skip, stopping early when the episode ends
+    pub fn step(&mut self, action: Buttons) -> Step {
+        self.gb.set_buttons(action);
+        let mut reward = 0.0;
+        let mut done = false;
+        for _ in 0..self.frame_skip {
+            self.gb.run_frame();
+            reward += (self.reward)(&self.gb);
+            done = (self.done)(&self.gb);
+            if done {
+                break;
+            }
+        }
+
+        Step {
+            observation: self.observe(),
+            reward,
+            done,
+        }
+    }
+
+    fn observe(&self) -> Vec<u8> {
+        match &self.observation {
+            Observation::Screen(format) => {
+                let mut out = vec![0; format.frame_size()];
+                self.gb.frame_into(*format, &mut out);
+                out
+            }
+            Observation::Memory(ranges) => ranges
+                .iter()
+                .flat_map(|range| range.clone().map(|address| self.gb.read_u8(address)))
+                .collect(),
+        }
+    }
+}
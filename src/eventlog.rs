@@ -0,0 +1,173 @@
+//! Per-frame event log, for analysis pipelines and bug reports capturing
+//! exactly what the emulator did.
+//!
+//! Once started, the core collects the interrupts serviced, the bank
+//! switches and the serial bytes of every frame. [`EventLog`] writes them as
+//! one JSON object per line, with the buttons held and the
+//! [state hash](GameBoy::state_hash) at the end of the frame:
+//!
+//! ```text
+//! {"frame":0,"buttons":["A"],"interrupts":["VBlank"],"bank_switches":[{"rom":2,"ram":0,"ram_enabled":false}],"serial":[72,105],"state_hash":"5be1cd8e2a4f7c01"}
+//! ```
+//!
+//! Other formats (e.g. CBOR) can be produced from the [`FrameRecord`]s
+//! returned by [`GameBoy::take_frame_record`].
+//!
+//! ```
+//! use gbemu::{cartridge, eventlog::EventLog, GameBoy};
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! let mut log = EventLog::start(&mut gb, Vec::new());
+//! for _ in 0..3 {
+//!     gb.run_frame();
+//!     log.log_frame(&mut gb).unwrap();
+//! }
+//! assert_eq!(log.into_inner().split(|b| *b == b'\n').count(), 4);
+//! ```
+use std::io::{self, Write};
+
+use crate::{joypad::Buttons, memory::BankingState, GameBoy, Interrupt};
+
+/// Names of the buttons, in [`Buttons::ALL`] order
+const BUTTON_NAMES: [&str; 8] = ["Right", "Left", "Up", "Down", "A", "B", "Select", "Start"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// What happened during a frame
+pub struct FrameRecord {
+    /// Number of the frame, counted from the start of the log
+    pub frame: u64,
+    /// Buttons held at the end of the frame
+    pub buttons: Buttons,
+    /// Interrupts serviced, in order
+    pub interrupts: Vec<Interrupt>,
+    /// MBC states after every bank switch, in order
+    pub bank_switches: Vec<BankingState>,
+    /// Bytes sent on the serial port
+    pub serial: Vec<u8>,
+    pub state_hash: u64,
+}
+
+impl FrameRecord {
+    /// Writes the record as a single line JSON object, without the newline
+    pub fn to_json(&self) -> String {
+        let buttons: Vec<_> = Buttons::ALL
+            .iter()
+            .zip(BUTTON_NAMES)
+            .filter(|(button, _)| self.buttons.contains(**button))
+            .map(|(_, name)| format!("\"{}\"", name))
+            .collect();
+        let interrupts: Vec<_> = self
+            .interrupts
+            .iter()
+            .map(|interrupt| format!("\"{:?}\"", interrupt))
+            .collect();
+        let bank_switches: Vec<_> = self
+            .bank_switches
+            .iter()
+            .map(|state| {
+                format!(
+                    "{{\"rom\":{},\"ram\":{},\"ram_enabled\":{}}}",
+                    state.rom_bank, state.ram_bank, state.ram_enabled
+                )
+            })
+            .collect();
+        let serial: Vec<_> = self.serial.iter().map(u8::to_string).collect();
+
+        format!(
+            "{{\"frame\":{},\"buttons\":[{}],\"interrupts\":[{}],\"bank_switches\":[{}],\"serial\":[{}],\"state_hash\":\"{:016x}\"}}",
+            self.frame,
+            buttons.join(","),
+            interrupts.join(","),
+            bank_switches.join(","),
+            serial.join(","),
+            self.state_hash
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Events collected during the current frame
+pub(crate) struct FrameEvents {
+    frame: u64,
+    interrupts: Vec<Interrupt>,
+    bank_switches: Vec<BankingState>,
+    serial: Vec<u8>,
+}
+
+/// Writes a [`FrameRecord`] per frame to the writer, as JSON lines
+pub struct EventLog<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> EventLog<W> {
+    /// Starts collecting the events of the console, from its current frame
+    pub fn start(gb: &mut GameBoy, writer: W) -> Self {
+        gb.set_frame_events(true);
+        Self { writer }
+    }
+
+    /// Writes the record of the frame just run, meant to be called after
+    /// every frame
+    pub fn log_frame(&mut self, gb: &mut GameBoy) -> io::Result<()> {
+        let record = gb.take_frame_record();
+        writeln!(self.writer, "{}", record.to_json())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl GameBoy {
+    /// Starts or stops collecting the events of every frame, see
+    /// [`GameBoy::take_frame_record`]
+    pub fn set_frame_events(&mut self, enabled: bool) {
+        self.frame_events = enabled.then(FrameEvents::default);
+    }
+
+    /// Returns the record of the events collected since the last call,
+    /// and starts the next frame. Empty while not collecting.
+    pub fn take_frame_record(&mut self) -> FrameRecord {
+        let (frame, interrupts, bank_switches, serial) = match self.frame_events.as_mut() {
+            Some(events) => {
+                let frame = events.frame;
+                events.frame += 1;
+                (
+                    frame,
+                    std::mem::take(&mut events.interrupts),
+                    std::mem::take(&mut events.bank_switches),
+                    std::mem::take(&mut events.serial),
+                )
+            }
+            None => Default::default(),
+        };
+
+        FrameRecord {
+            frame,
+            buttons: self.buttons,
+            interrupts,
+            bank_switches,
+            serial,
+            state_hash: self.state_hash(),
+        }
+    }
+
+    pub(crate) fn log_interrupt(&mut self, interrupt: Interrupt) {
+        if let Some(events) = self.frame_events.as_mut() {
+            events.interrupts.push(interrupt);
+        }
+    }
+
+    pub(crate) fn log_bank_switch(&mut self, state: BankingState) {
+        if let Some(events) = self.frame_events.as_mut() {
+            events.bank_switches.push(state);
+        }
+    }
+
+    #[cfg(feature = "serial")]
+    pub(crate) fn log_serial(&mut self, byte: u8) {
+        if let Some(events) = self.frame_events.as_mut() {
+            events.serial.push(byte);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! C ABI bindings, to embed the core in frontends written in other languages
+//! (Python through ctypes/cffi, C#, C++).
+//!
+//! The console lives behind an opaque [`Gbemu`] handle, created with
+//! [`gbemu_create`] and freed with [`gbemu_destroy`]. Building with the
+//! `ffi` feature produces a `cdylib` and regenerates the header with
+//! cbindgen in `include/gbemu.h`:
+//!
+//! ```c
+//! Gbemu *gb = gbemu_create();
+//! if (!gbemu_load_rom(gb, rom, rom_len)) { /* invalid image */ }
+//! gbemu_set_button(gb, GBEMU_BUTTON_START, true);
+//! gbemu_run_frame(gb);
+//! const uint8_t *rgba = gbemu_framebuffer(gb);
+//! gbemu_destroy(gb);
+//! ```
+//!
+//! Handles are not thread safe, and the pointers returned are only valid
+//! until the next call on the same handle.
+use std::slice;
+
+use crate::{joypad::Buttons, savestate::SaveState, video::PixelFormat, GameBoy};
+
+/// A console and the frame lent to the caller
+pub struct Gbemu {
+    gb: GameBoy,
+    /// Last frame converted by [`gbemu_framebuffer`]
+    framebuffer: Vec<u8>,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A joypad button, in the P1 bit order
+pub enum GbemuButton {
+    Right = 0b0000_0001,
+    Left = 0b0000_0010,
+    Up = 0b0000_0100,
+    Down = 0b0000_1000,
+    A = 0b0001_0000,
+    B = 0b0010_0000,
+    Select = 0b0100_0000,
+    Start = 0b1000_0000,
+}
+
+/// Powers up a console with an empty cartridge slot, see [`gbemu_load_rom`]
+#[no_mangle]
+pub extern "C" fn gbemu_create() -> *mut Gbemu {
+    Box::into_raw(Box::new(Gbemu {
+        gb: GameBoy::without_cartridge(),
+        framebuffer: vec![0; PixelFormat::Rgba8888.frame_size()],
+    }))
+}
+
+/// Frees a console, null is ignored
+///
+/// # Safety
+///
+/// `gb` must come from [`gbemu_create`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_destroy(gb: *mut Gbemu) {
+    if !gb.is_null() {
+        drop(Box::from_raw(gb));
+    }
+}
+
+/// Powers up a new console with the cartridge image, replacing the previous
+/// one. Returns false, keeping the previous console, if the image is invalid.
+///
+/// # Safety
+///
+/// `gb` must be a live handle and `rom` point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_load_rom(gb: *mut Gbemu, rom: *const u8, len: usize) -> bool {
+    let (Some(gb), false) = (gb.as_mut(), rom.is_null()) else {
+        return false;
+    };
+    let rom = slice::from_raw_parts(rom, len);
+    match GameBoy::try_new(rom) {
+        Ok(console) => {
+            gb.gb = console;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Runs a frame, returns the T-cycles taken
+///
+/// # Safety
+///
+/// `gb` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_run_frame(gb: *mut Gbemu) -> usize {
+    match gb.as_mut() {
+        Some(gb) => gb.gb.run_frame(),
+        None => 0,
+    }
+}
+
+/// Returns the last complete frame, 160 × 144 RGBA pixels row by row,
+/// 4 bytes each. Null if the handle is.
+///
+/// # Safety
+///
+/// `gb` must be a live handle. The frame is overwritten by the next call.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_framebuffer(gb: *mut Gbemu) -> *const u8 {
+    let Some(gb) = gb.as_mut() else {
+        return std::ptr::null();
+    };
+    gb.gb.frame_into(PixelFormat::Rgba8888, &mut gb.framebuffer);
+    gb.framebuffer.as_ptr()
+}
+
+/// Presses or releases a button, the others are left as they are
+///
+/// # Safety
+///
+/// `gb` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_set_button(gb: *mut Gbemu, button: GbemuButton, pressed: bool) {
+    let Some(gb) = gb.as_mut() else {
+        return;
+    };
+    let held = gb.gb.buttons().bits();
+    let bits = if pressed {
+        held | button as u8
+    } else {
+        held & !(button as u8)
+    };
+    gb.gb.set_buttons(Buttons::from_bits(bits));
+}
+
+/// Saves the state into `out` if it fits in `capacity` bytes, and returns
+/// its size: call it with a null `out` to size the buffer. 0 if the handle
+/// is null.
+///
+/// # Safety
+///
+/// `gb` must be a live handle and `out`, unless null, point to `capacity`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_save_state(
+    gb: *const Gbemu,
+    out: *mut u8,
+    capacity: usize,
+) -> usize {
+    let Some(gb) = gb.as_ref() else {
+        return 0;
+    };
+    let state = gb.gb.save_state().to_bytes();
+    if !out.is_null() && state.len() <= capacity {
+        slice::from_raw_parts_mut(out, state.len()).copy_from_slice(&state);
+    }
+    state.len()
+}
+
+/// Loads a state saved by [`gbemu_save_state`], returns false, leaving the
+/// console untouched, if it is invalid or saved with another cartridge
+///
+/// # Safety
+///
+/// `gb` must be a live handle and `state` point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn gbemu_load_state(gb: *mut Gbemu, state: *const u8, len: usize) -> bool {
+    let (Some(gb), false) = (gb.as_mut(), state.is_null()) else {
+        return false;
+    };
+    let bytes = slice::from_raw_parts(state, len);
+    SaveState::from_bytes(bytes)
+        .and_then(|state| gb.gb.load_state(&state))
+        .is_ok()
+}
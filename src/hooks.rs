@@ -0,0 +1,35 @@
+//! Callbacks invoked by the emulator core on notable events, useful for
+//! debuggers and tools.
+#[cfg(feature = "ppu")]
+use crate::lcd::Mode;
+use crate::memory::BankingState;
+
+pub type BankSwitchHook = Box<dyn FnMut(BankingState)>;
+#[cfg(feature = "ppu")]
+pub type VBlankHook = Box<dyn FnMut()>;
+#[cfg(feature = "ppu")]
+pub type HBlankHook = Box<dyn FnMut(u8)>;
+#[cfg(feature = "ppu")]
+pub type ModeChangeHook = Box<dyn FnMut(Mode)>;
+pub type SerialHook = Box<dyn FnMut(u8)>;
+pub type TraceHook = Box<dyn FnMut(&str)>;
+
+#[derive(Default)]
+pub struct Hooks {
+    /// Fired after a write to the MBC registers changed the banking state
+    pub on_bank_switch: Option<BankSwitchHook>,
+    /// Fired when the PPU enters VBlank, once per frame
+    #[cfg(feature = "ppu")]
+    pub on_vblank: Option<VBlankHook>,
+    /// Fired when the PPU enters HBlank, with the line just drawn
+    #[cfg(feature = "ppu")]
+    pub on_hblank: Option<HBlankHook>,
+    /// Fired at every PPU mode change, after the mode specific hooks
+    #[cfg(feature = "ppu")]
+    pub on_mode_change: Option<ModeChangeHook>,
+    /// Fired with every byte sent on the serial port, as it is sent
+    pub on_serial: Option<SerialHook>,
+    /// Fired before every instruction with its trace line, see
+    /// [`tracelog`](crate::tracelog)
+    pub on_trace: Option<TraceHook>,
+}
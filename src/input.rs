@@ -0,0 +1,223 @@
+//! Input layer between the host devices and the joypad: keys of any kind
+//! (keyboard codes, gamepad buttons) are bound to logical [`Action`]s, and
+//! once per frame the held keys are turned into an [`InputState`].
+//!
+//! The snapshot is what the console sees, so it is also what input
+//! recordings store: replaying the snapshots frame by frame reproduces the
+//! run, turbo and D-pad policy included, without the bindings.
+//!
+//! ```
+//! use gbemu::{
+//!     cartridge,
+//!     input::{Action, DpadPolicy, InputMapper},
+//!     joypad::Buttons,
+//!     GameBoy,
+//! };
+//!
+//! let mut input = InputMapper::new()
+//!     .bind('z', Action::Hold(Buttons::A))
+//!     .bind('a', Action::Turbo(Buttons::A))
+//!     .bind('h', Action::Hold(Buttons::LEFT))
+//!     .bind('l', Action::Hold(Buttons::RIGHT))
+//!     .dpad_policy(DpadPolicy::LastPressed);
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! input.press('a');
+//! for _ in 0..4 {
+//!     let state = input.next_frame();
+//!     gb.apply_input(&state);
+//!     gb.run_frame();
+//! }
+//! ```
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{joypad::Buttons, pacing::FRAME_RATE, GameBoy};
+
+/// Turbo rate used when none is chosen, in presses per second
+pub const DEFAULT_TURBO_RATE: f64 = 15.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// What a key does while held
+pub enum Action {
+    /// Holds the buttons
+    Hold(Buttons),
+    /// Presses and releases the buttons repeatedly, at the turbo rate
+    Turbo(Buttons),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// How opposite directions held together reach the console. The D-pad of
+/// the hardware can't press both, some games misbehave when they are.
+pub enum DpadPolicy {
+    /// Both are pressed
+    Allow,
+    /// Neither is pressed
+    #[default]
+    Neutral,
+    /// Only the one pressed last is
+    LastPressed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// The buttons held during a frame, as the console sees them
+pub struct InputState {
+    /// Number of the frame, counted by the [`InputMapper`]
+    pub frame: u64,
+    pub buttons: Buttons,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Held {
+    /// Order of the press among all the presses
+    order: u64,
+    /// Frame of the press, turbo starts pressed on it
+    frame: u64,
+}
+
+/// Turns the keys held on the host into [`InputState`]s
+pub struct InputMapper<K> {
+    bindings: HashMap<K, Action>,
+    held: HashMap<K, Held>,
+    turbo_rate: f64,
+    frame_rate: f64,
+    dpad_policy: DpadPolicy,
+    frame: u64,
+    presses: u64,
+}
+
+impl<K: Hash + Eq> Default for InputMapper<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq> InputMapper<K> {
+    /// A mapper without bindings, neutralising opposite directions
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            held: HashMap::new(),
+            turbo_rate: DEFAULT_TURBO_RATE,
+            frame_rate: FRAME_RATE,
+            dpad_policy: DpadPolicy::default(),
+            frame: 0,
+            presses: 0,
+        }
+    }
+
+    /// Binds the key to the action, replacing its previous one
+    pub fn bind(mut self, key: K, action: Action) -> Self {
+        self.bindings.insert(key, action);
+        self
+    }
+
+    /// Presses per second of the turbo actions, rounded to a whole number
+    /// of frames
+    pub fn turbo_rate(mut self, rate: f64) -> Self {
+        self.turbo_rate = rate;
+        self
+    }
+
+    /// Frames per second the turbo rate is synced to, the hardware one by
+    /// default
+    pub fn frame_rate(mut self, rate: f64) -> Self {
+        self.frame_rate = rate;
+        self
+    }
+
+    pub fn dpad_policy(mut self, policy: DpadPolicy) -> Self {
+        self.dpad_policy = policy;
+        self
+    }
+
+    /// The key went down, unbound keys are ignored
+    pub fn press(&mut self, key: K) {
+        if !self.bindings.contains_key(&key) || self.held.contains_key(&key) {
+            return;
+        }
+        self.presses += 1;
+        self.held.insert(
+            key,
+            Held {
+                order: self.presses,
+                frame: self.frame,
+            },
+        );
+    }
+
+    pub fn release(&mut self, key: &K) {
+        self.held.remove(key);
+    }
+
+    /// Releases every key, e.g. when the window loses focus
+    pub fn release_all(&mut self) {
+        self.held.clear();
+    }
+
+    /// Frames a turbo press lasts, followed by as many released
+    fn turbo_half_period(&self) -> u64 {
+        ((self.frame_rate / self.turbo_rate / 2.0).round() as u64).max(1)
+    }
+
+    /// Computes the state of the current frame and moves to the next one
+    pub fn next_frame(&mut self) -> InputState {
+        let half_period = self.turbo_half_period();
+        let mut buttons = Buttons::empty();
+        // Order of the last press of each direction
+        let mut directions = [0u64; 4];
+        for (key, held) in &self.held {
+            let pressed = match self.bindings[key] {
+                Action::Hold(pressed) => pressed,
+                Action::Turbo(pressed)
+                    if ((self.frame - held.frame) / half_period).is_multiple_of(2) =>
+                {
+                    pressed
+                }
+                Action::Turbo(_) => continue,
+            };
+            buttons |= pressed;
+            for (i, direction) in Buttons::ALL[..4].iter().enumerate() {
+                if pressed.contains(*direction) {
+                    directions[i] = directions[i].max(held.order);
+                }
+            }
+        }
+
+        for (first, second) in [
+            (Buttons::RIGHT, Buttons::LEFT),
+            (Buttons::UP, Buttons::DOWN),
+        ] {
+            if !buttons.contains(first | second) {
+                continue;
+            }
+            match self.dpad_policy {
+                DpadPolicy::Allow => {}
+                DpadPolicy::Neutral => buttons.remove(first | second),
+                DpadPolicy::LastPressed => {
+                    let order = |direction: Buttons| {
+                        directions[Buttons::ALL.iter().position(|b| *b == direction).unwrap()]
+                    };
+                    buttons.remove(if order(first) > order(second) {
+                        second
+                    } else {
+                        first
+                    });
+                }
+            }
+        }
+
+        let state = InputState {
+            frame: self.frame,
+            buttons,
+        };
+        self.frame += 1;
+        state
+    }
+}
+
+impl GameBoy {
+    /// Holds the buttons of the snapshot, see [`GameBoy::set_buttons`]
+    pub fn apply_input(&mut self, state: &InputState) {
+        self.set_buttons(state.buttons);
+    }
+}
@@ -0,0 +1,131 @@
+//! A snapshot of the machine state for frontends, cheap enough to take
+//! every frame to refresh the panels of a debugger UI.
+#[cfg(feature = "ppu")]
+use crate::lcd::Mode;
+use crate::{
+    cpu::{CpuMode, RegisterFile},
+    memory::{locations, BankingState},
+    timer, GameBoy, Interrupt,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The flags held in F
+pub struct Flags {
+    pub zero: bool,
+    pub subtract: bool,
+    pub half_carry: bool,
+    pub carry: bool,
+}
+
+impl From<u8> for Flags {
+    fn from(f: u8) -> Self {
+        Self {
+            zero: f & 0b1000_0000 != 0,
+            subtract: f & 0b0100_0000 != 0,
+            half_carry: f & 0b0010_0000 != 0,
+            carry: f & 0b0001_0000 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The timer registers.
+///
+/// The core counts DIV and TIMA from the T-cycles elapsed at the end of each
+/// tick rather than from a system counter, so TIMA is reloaded from TMA as
+/// soon as it overflows, a reload is never pending.
+pub struct TimerState {
+    pub div: u8,
+    pub tima: u8,
+    pub tma: u8,
+    pub tac: u8,
+}
+
+impl TimerState {
+    /// TIMA is counting
+    pub fn enabled(&self) -> bool {
+        self.tac & 0b100 != 0
+    }
+
+    /// TIMA increments per second, while enabled
+    pub fn frequency(&self) -> u32 {
+        timer::frequency(self.tac)
+    }
+
+    /// Bit of the system counter clocking TIMA on hardware, see
+    /// [`timer::edge_bit`]
+    pub fn edge_bit(&self) -> u8 {
+        timer::edge_bit(self.tac)
+    }
+
+    /// TIMA increments left before it overflows and requests the interrupt
+    pub fn ticks_to_overflow(&self) -> u16 {
+        0x100 - self.tima as u16
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// State of the machine returned by [`GameBoy::inspect`]
+pub struct GameBoyState {
+    pub registers: RegisterFile,
+    /// Decoded from F
+    pub flags: Flags,
+    /// Interrupt Master Enable
+    pub ime: bool,
+    pub cpu_mode: CpuMode,
+    pub banking: BankingState,
+    #[cfg(feature = "ppu")]
+    pub lcd_mode: Mode,
+    pub ly: u8,
+    pub timer: TimerState,
+    /// IF, the requested interrupts
+    pub interrupt_flags: u8,
+    /// IE, the enabled interrupts
+    pub interrupt_enable: u8,
+    /// T-cycles executed since power on
+    pub cycles: u64,
+    /// Frames elapsed since power on
+    pub frames: u64,
+}
+
+impl GameBoyState {
+    /// The interrupt is both requested and enabled, it is serviced as soon
+    /// as IME allows
+    pub fn is_pending(&self, interrupt: Interrupt) -> bool {
+        self.interrupt_flags & self.interrupt_enable & interrupt.mask() != 0
+    }
+
+    /// The pending interrupts, in priority order
+    pub fn pending_interrupts(&self) -> impl Iterator<Item = Interrupt> + '_ {
+        Interrupt::ALL
+            .into_iter()
+            .filter(|interrupt| self.is_pending(*interrupt))
+    }
+}
+
+impl GameBoy {
+    /// Returns a snapshot of the registers, banks, LCD, timer and interrupts
+    pub fn inspect(&self) -> GameBoyState {
+        let io = |address: usize| self.memory[address];
+        GameBoyState {
+            registers: self.registers,
+            flags: Flags::from(unsafe { self.registers.af.bytes.lo }),
+            ime: self.registers.ime,
+            cpu_mode: self.registers.mode,
+            banking: self.banking_state(),
+            #[cfg(feature = "ppu")]
+            lcd_mode: self.lcd_mode(),
+            ly: io(locations::LY),
+            timer: TimerState {
+                div: io(locations::DIV),
+                tima: io(locations::TIMA),
+                tma: io(locations::TMA),
+                tac: io(locations::TAC),
+            },
+            interrupt_flags: io(locations::IF),
+            interrupt_enable: io(locations::IE),
+            cycles: self.counters.cycles,
+            frames: self.counters.frames,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Arithmetic and logic shared by the instructions, as pure functions
+//! returning the result and the flags to store.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/CPU_Instruction_Set.html)
+
+use crate::cpu::{Cpu, Flag};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Flags produced by an operation, `None` when the flag is left unchanged
+pub struct Flags {
+    pub zero: Option<bool>,
+    pub subtract: Option<bool>,
+    pub half_carry: Option<bool>,
+    pub carry: Option<bool>,
+}
+
+impl Flags {
+    /// Flags for an operation which affects all of them
+    pub const fn new(zero: bool, subtract: bool, half_carry: bool, carry: bool) -> Self {
+        Self {
+            zero: Some(zero),
+            subtract: Some(subtract),
+            half_carry: Some(half_carry),
+            carry: Some(carry),
+        }
+    }
+
+    /// Stores the affected flags into F
+    pub fn apply(&self, cpu: &mut dyn Cpu) {
+        let flags = [
+            (Flag::Zero, self.zero),
+            (Flag::Subtract, self.subtract),
+            (Flag::HalfCarry, self.half_carry),
+            (Flag::Carry, self.carry),
+        ];
+        for (flag, value) in flags {
+            if let Some(value) = value {
+                cpu.set_flag(flag, value);
+            }
+        }
+    }
+}
+
+pub fn add8(a: u8, value: u8) -> (u8, Flags) {
+    adc8(a, value, false)
+}
+
+pub fn adc8(a: u8, value: u8, carry: bool) -> (u8, Flags) {
+    let carry = carry as u8;
+    let result = a as u16 + value as u16 + carry as u16;
+    let half_carry = (a & 0x0F) + (value & 0x0F) + carry > 0x0F;
+    let result_u8 = result as u8;
+
+    (
+        result_u8,
+        Flags::new(result_u8 == 0, false, half_carry, result > 0xFF),
+    )
+}
+
+pub fn sub8(a: u8, value: u8) -> (u8, Flags) {
+    sbc8(a, value, false)
+}
+
+pub fn sbc8(a: u8, value: u8, carry: bool) -> (u8, Flags) {
+    let carry = carry as u8;
+    let result = a.wrapping_sub(value).wrapping_sub(carry);
+    let half_carry = (a & 0x0F) < (value & 0x0F) + carry;
+    let borrow = (a as u16) < value as u16 + carry as u16;
+
+    (result, Flags::new(result == 0, true, half_carry, borrow))
+}
+
+pub fn and8(a: u8, value: u8) -> (u8, Flags) {
+    let result = a & value;
+    (result, Flags::new(result == 0, false, true, false))
+}
+
+pub fn xor8(a: u8, value: u8) -> (u8, Flags) {
+    let result = a ^ value;
+    (result, Flags::new(result == 0, false, false, false))
+}
+
+pub fn or8(a: u8, value: u8) -> (u8, Flags) {
+    let result = a | value;
+    (result, Flags::new(result == 0, false, false, false))
+}
+
+/// INC r, the carry is left unchanged
+pub fn inc8(value: u8) -> (u8, Flags) {
+    let result = value.wrapping_add(1);
+    let flags = Flags {
+        carry: None,
+        ..Flags::new(result == 0, false, value & 0x0F == 0x0F, false)
+    };
+    (result, flags)
+}
+
+/// DEC r, the carry is left unchanged
+pub fn dec8(value: u8) -> (u8, Flags) {
+    let result = value.wrapping_sub(1);
+    let flags = Flags {
+        carry: None,
+        ..Flags::new(result == 0, true, value & 0x0F == 0x00, false)
+    };
+    (result, flags)
+}
+
+/// ADD HL,rr, Z is left unchanged while H and C come from bits 11 and 15
+pub fn add16(a: u16, value: u16) -> (u16, Flags) {
+    let (result, carry) = a.overflowing_add(value);
+    let half_carry = (a & 0x0FFF) + (value & 0x0FFF) > 0x0FFF;
+    let flags = Flags {
+        zero: None,
+        ..Flags::new(false, false, half_carry, carry)
+    };
+    (result, flags)
+}
+
+/// ADD SP,e8 and LD HL,SP+e8: the offset is sign extended, but the flags come
+/// from the unsigned addition of the low bytes
+pub fn add_sp(sp: u16, offset: i8) -> (u16, Flags) {
+    let offset = offset as i16 as u16;
+    let result = sp.wrapping_add(offset);
+    let half_carry = (sp & 0x0F) + (offset & 0x0F) > 0x0F;
+    let carry = (sp & 0xFF) + (offset & 0xFF) > 0xFF;
+
+    (result, Flags::new(false, false, half_carry, carry))
+}
+
+/// Adjusts A to BCD after an addition or subtraction, as told by the N, H and
+/// C flags of the previous operation. N is left unchanged.
+pub fn daa(a: u8, subtract: bool, half_carry: bool, carry: bool) -> (u8, Flags) {
+    let mut adjust = 0;
+    let mut carry_out = carry;
+
+    let result = if subtract {
+        if half_carry {
+            adjust |= 0x06;
+        }
+        if carry {
+            adjust |= 0x60;
+        }
+        a.wrapping_sub(adjust)
+    } else {
+        if half_carry || a & 0x0F > 0x09 {
+            adjust |= 0x06;
+        }
+        if carry || a > 0x99 {
+            adjust |= 0x60;
+            carry_out = true;
+        }
+        a.wrapping_add(adjust)
+    };
+
+    let flags = Flags {
+        subtract: None,
+        ..Flags::new(result == 0, false, false, carry_out)
+    };
+    (result, flags)
+}
@@ -1,8 +1,9 @@
 use crate::cpu::{Cpu, Flag};
 
-use super::{Instruction, Register16Index, Register8Index};
+use super::{alu, Instruction, Register16Index, Register8Index};
 
-pub(crate) enum Adc {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Adc {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -13,28 +14,20 @@ impl Instruction for Adc {
             Adc::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let carry = cpu.test_flag(Flag::Carry) as u8;
-                let (result, overflow) = a.overflowing_add(value + carry);
+                let carry = cpu.test_flag(Flag::Carry);
+                let (result, flags) = alu::adc8(a, value, carry);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) + (value & 0x0F) + carry > 0x0F);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Adc::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let carry = cpu.test_flag(Flag::Carry) as u8;
-                let (result, overflow) = a.overflowing_add(value + carry);
+                let carry = cpu.test_flag(Flag::Carry);
+                let (result, flags) = alu::adc8(a, *value, carry);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) + (value & 0x0F) + carry > 0x0F);
+                flags.apply(cpu);
 
                 8
             }
@@ -42,7 +35,8 @@ impl Instruction for Adc {
     }
 }
 
-pub(crate) enum Add {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Add {
     Internal(Register8Index),
     Immediate(u8),
     Internal16(Register16Index),
@@ -55,49 +49,35 @@ impl Instruction for Add {
             Add::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let (result, overflow) = a.overflowing_add(value);
+                let (result, flags) = alu::add8(a, value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) + (value & 0x0F) > 0x0F);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Add::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let (result, overflow) = a.overflowing_add(*value);
+                let (result, flags) = alu::add8(a, *value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) + (value & 0x0F) > 0x0F);
+                flags.apply(cpu);
 
                 8
             }
             Add::Internal16(src) => {
                 let value = src.get(cpu);
                 let hl = Register16Index::HL.get(cpu);
-                let (result, overflow) = hl.overflowing_add(value);
+                let (result, flags) = alu::add16(hl, value);
                 Register16Index::HL.set(cpu, result);
-
-                cpu.set_flag(Flag::Zero, false);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (hl & 0x0FFF) + (value & 0x0FFF) > 0x0FFF);
+                flags.apply(cpu);
 
                 8
             }
-            Add::StackPointer(value) => {
+            Add::StackPointer(offset) => {
                 let sp = Register16Index::SP.get(cpu);
-                let (result, overflow) = sp.overflowing_add_signed(*value as i16);
+                let (result, flags) = alu::add_sp(sp, *offset);
                 Register16Index::SP.set(cpu, result);
-
-                cpu.set_flag(Flag::Zero, false);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (sp & 0x0F) + (*value as u16 & 0x0F) > 0x0F);
+                flags.apply(cpu);
 
                 16
             }
@@ -105,7 +85,8 @@ impl Instruction for Add {
     }
 }
 
-pub(crate) enum And {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum And {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -116,26 +97,18 @@ impl Instruction for And {
             And::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let result = a & value;
+                let (result, flags) = alu::and8(a, value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, false);
-                cpu.set_flag(Flag::HalfCarry, true);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             And::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let result = a & value;
+                let (result, flags) = alu::and8(a, *value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, false);
-                cpu.set_flag(Flag::HalfCarry, true);
+                flags.apply(cpu);
 
                 8
             }
@@ -143,7 +116,8 @@ impl Instruction for And {
     }
 }
 
-pub(crate) enum Cp {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cp {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -154,24 +128,16 @@ impl Instruction for Cp {
             Cp::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let result = a.wrapping_sub(value);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, a < value);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) < (value & 0x0F));
+                let (_result, flags) = alu::sub8(a, value);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Cp::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let result = a.wrapping_sub(*value);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, a < *value);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) < (value & 0x0F));
+                let (_result, flags) = alu::sub8(a, *value);
+                flags.apply(cpu);
 
                 8
             }
@@ -179,7 +145,8 @@ impl Instruction for Cp {
     }
 }
 
-pub(crate) enum Dec {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dec {
     Internal(Register8Index),
     Internal16(Register16Index),
 }
@@ -189,20 +156,17 @@ impl Instruction for Dec {
         match self {
             Dec::Internal(src) => {
                 let value = src.get(cpu);
-                let (result, _overflow) = value.overflowing_sub(1);
+                let (result, flags) = alu::dec8(value);
                 src.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::HalfCarry, (value & 0x0F) == 0);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 12
                     + (*src != Register8Index::HL) as usize * 4
             }
             Dec::Internal16(src) => {
+                // No flags are affected
                 let value = src.get(cpu);
-                let (result, _overflow) = value.overflowing_sub(1);
-                src.set(cpu, result);
+                src.set(cpu, value.wrapping_sub(1));
 
                 8
             }
@@ -210,7 +174,8 @@ impl Instruction for Dec {
     }
 }
 
-pub(crate) enum Inc {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Inc {
     Internal(Register8Index),
     Internal16(Register16Index),
 }
@@ -220,20 +185,17 @@ impl Instruction for Inc {
         match self {
             Inc::Internal(src) => {
                 let value = src.get(cpu);
-                let (result, _overflow) = value.overflowing_add(1);
+                let (result, flags) = alu::inc8(value);
                 src.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::HalfCarry, (value & 0x0F) == 0x0F);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 12
                     + (*src != Register8Index::HL) as usize * 4
             }
             Inc::Internal16(src) => {
+                // No flags are affected
                 let value = src.get(cpu);
-                let (result, _overflow) = value.overflowing_add(1);
-                src.set(cpu, result);
+                src.set(cpu, value.wrapping_add(1));
 
                 8
             }
@@ -241,7 +203,8 @@ impl Instruction for Inc {
     }
 }
 
-pub(crate) enum Or {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Or {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -252,26 +215,18 @@ impl Instruction for Or {
             Or::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let result = a | value;
+                let (result, flags) = alu::or8(a, value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, false);
-                cpu.set_flag(Flag::HalfCarry, false);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Or::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let result = a | value;
+                let (result, flags) = alu::or8(a, *value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, false);
-                cpu.set_flag(Flag::HalfCarry, false);
+                flags.apply(cpu);
 
                 8
             }
@@ -279,7 +234,8 @@ impl Instruction for Or {
     }
 }
 
-pub(crate) enum Sbc {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sbc {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -290,28 +246,20 @@ impl Instruction for Sbc {
             Sbc::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let carry = cpu.test_flag(Flag::Carry) as u8;
-                let (result, overflow) = a.overflowing_sub(value + carry);
+                let carry = cpu.test_flag(Flag::Carry);
+                let (result, flags) = alu::sbc8(a, value, carry);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) < (value & 0x0F) + carry);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Sbc::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let carry = cpu.test_flag(Flag::Carry) as u8;
-                let (result, overflow) = a.overflowing_sub(value + carry);
+                let carry = cpu.test_flag(Flag::Carry);
+                let (result, flags) = alu::sbc8(a, *value, carry);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, overflow);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) < (value & 0x0F) + carry);
+                flags.apply(cpu);
 
                 8
             }
@@ -319,7 +267,8 @@ impl Instruction for Sbc {
     }
 }
 
-pub(crate) enum Sub {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sub {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -330,26 +279,18 @@ impl Instruction for Sub {
             Sub::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let result = a.wrapping_sub(value);
+                let (result, flags) = alu::sub8(a, value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, a < value);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) < (value & 0x0F));
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Sub::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let result = a.wrapping_sub(*value);
+                let (result, flags) = alu::sub8(a, *value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, true);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, a < *value);
-                cpu.set_flag(Flag::HalfCarry, (a & 0x0F) < (value & 0x0F));
+                flags.apply(cpu);
 
                 8
             }
@@ -357,7 +298,8 @@ impl Instruction for Sub {
     }
 }
 
-pub(crate) enum Xor {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Xor {
     Internal(Register8Index),
     Immediate(u8),
 }
@@ -368,26 +310,18 @@ impl Instruction for Xor {
             Xor::Internal(src) => {
                 let value = src.get(cpu);
                 let a = Register8Index::A.get(cpu);
-                let result = a ^ value;
+                let (result, flags) = alu::xor8(a, value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, false);
-                cpu.set_flag(Flag::HalfCarry, false);
+                flags.apply(cpu);
 
                 (*src == Register8Index::HL) as usize * 8
                     + (*src != Register8Index::HL) as usize * 4
             }
             Xor::Immediate(value) => {
                 let a = Register8Index::A.get(cpu);
-                let result = a ^ value;
+                let (result, flags) = alu::xor8(a, *value);
                 Register8Index::A.set(cpu, result);
-
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Carry, false);
-                cpu.set_flag(Flag::HalfCarry, false);
+                flags.apply(cpu);
 
                 8
             }
@@ -395,38 +329,27 @@ impl Instruction for Xor {
     }
 }
 
-pub(crate) struct Daa;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Daa;
 
 impl Instruction for Daa {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
         let a = Register8Index::A.get(cpu);
-        let mut result = a;
-
-        if cpu.test_flag(Flag::Subtract) {
-            if cpu.test_flag(Flag::HalfCarry) {
-                result = result.wrapping_sub(0x06);
-            }
-            if cpu.test_flag(Flag::Carry) {
-                result = result.wrapping_sub(0x60);
-            }
-        } else {
-            if cpu.test_flag(Flag::HalfCarry) || (result & 0x0F) > 0x09 {
-                result = result.wrapping_add(0x06);
-            }
-            if cpu.test_flag(Flag::Carry) || result > 0x9F {
-                result = result.wrapping_add(0x60);
-            }
-        }
-
-        cpu.set_flag(Flag::Zero, result == 0);
-        cpu.set_flag(Flag::HalfCarry, false);
-        cpu.set_flag(Flag::Carry, a < result);
+        let (result, flags) = alu::daa(
+            a,
+            cpu.test_flag(Flag::Subtract),
+            cpu.test_flag(Flag::HalfCarry),
+            cpu.test_flag(Flag::Carry),
+        );
+        Register8Index::A.set(cpu, result);
+        flags.apply(cpu);
 
         4
     }
 }
 
-pub(crate) struct Cpl;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cpl;
 
 impl Instruction for Cpl {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
@@ -440,7 +363,8 @@ impl Instruction for Cpl {
     }
 }
 
-pub(crate) struct Ccf;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ccf;
 
 impl Instruction for Ccf {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
@@ -452,7 +376,8 @@ impl Instruction for Ccf {
     }
 }
 
-pub(crate) struct Scf;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scf;
 
 impl Instruction for Scf {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
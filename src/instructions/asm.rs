@@ -0,0 +1,420 @@
+//! Assembler for short programs, the inverse of [`decode`](super::decode),
+//! so tests can write CPU scenarios as instructions instead of bytes.
+//!
+//! ```
+//! use gbemu::{
+//!     cartridge::HeaderBuilder,
+//!     instructions::{asm::Program, Register8Index as R},
+//! };
+//!
+//! let mut program = Program::at(0x0150).ld_imm(R::A, 0x42).ld_mem_a(0xC000);
+//! let end = program.here();
+//! program = program.jr_to(end);
+//!
+//! let rom = HeaderBuilder::new().code(&program.bytes()).build();
+//! ```
+use super::{
+    arithmetics::*, bits::*, loads::*, routines::*, Assemble, Instr, Register16Index,
+    Register16StackIndex, Register8Index,
+};
+
+/// Operand bits of an 8-bit register, `[HL]` included
+fn r8(register: Register8Index) -> u8 {
+    match register {
+        Register8Index::B => 0,
+        Register8Index::C => 1,
+        Register8Index::D => 2,
+        Register8Index::E => 3,
+        Register8Index::H => 4,
+        Register8Index::L => 5,
+        Register8Index::HL => 6,
+        Register8Index::A => 7,
+        Register8Index::F => panic!("F can't be an operand"),
+    }
+}
+
+/// Operand bits of a 16-bit register, where 3 is SP
+fn r16(register: Register16Index) -> u8 {
+    match register {
+        Register16Index::BC => 0,
+        Register16Index::DE => 1,
+        Register16Index::HL => 2,
+        Register16Index::SP => 3,
+        Register16Index::AF | Register16Index::PC => {
+            panic!("{:?} can't be an operand", register)
+        }
+    }
+}
+
+fn r16_stack(register: Register16StackIndex) -> u8 {
+    match register {
+        Register16StackIndex::BC => 0,
+        Register16StackIndex::DE => 1,
+        Register16StackIndex::HL => 2,
+        Register16StackIndex::AF => 3,
+    }
+}
+
+fn condition(condition: Condition) -> u8 {
+    match condition {
+        Condition::NotZero => 0b00,
+        Condition::Zero => 0b01,
+        Condition::NotCarry => 0b10,
+        Condition::Carry => 0b11,
+    }
+}
+
+/// Instruction with a 16-bit immediate, stored little-endian
+fn with_u16(opcode: u8, value: u16) -> Vec<u8> {
+    let [low, high] = value.to_le_bytes();
+    vec![opcode, low, high]
+}
+
+/// ALU instruction on A: `base | r8` or its immediate form
+fn alu(base: u8, immediate: u8, operand: Option<Register8Index>, value: u8) -> Vec<u8> {
+    match operand {
+        Some(register) => vec![base | r8(register)],
+        None => vec![immediate, value],
+    }
+}
+
+impl Assemble for Instr {
+    fn assemble(&self) -> Vec<u8> {
+        match *self {
+            Self::Nop => vec![0x00],
+            // The byte after STOP is usually skipped, see Stop
+            Self::Stop => vec![0x10, 0x00],
+            Self::Halt => vec![0x76],
+            Self::Di => vec![0xF3],
+            Self::Ei => vec![0xFB],
+            Self::Jump(Jump::Internal) => vec![0xE9],
+            Self::Jump(Jump::Immediate(None, address)) => with_u16(0xC3, address),
+            Self::Jump(Jump::Immediate(Some(cond), address)) => {
+                with_u16(0xC2 | condition(cond) << 3, address)
+            }
+            Self::Jump(Jump::Relative(None, offset)) => vec![0x18, offset as u8],
+            Self::Jump(Jump::Relative(Some(cond), offset)) => {
+                vec![0x20 | condition(cond) << 3, offset as u8]
+            }
+            Self::Call(Call(None, address)) => with_u16(0xCD, address),
+            Self::Call(Call(Some(cond), address)) => with_u16(0xC4 | condition(cond) << 3, address),
+            Self::Ret(Ret::Internal(None)) => vec![0xC9],
+            Self::Ret(Ret::Internal(Some(cond))) => vec![0xC0 | condition(cond) << 3],
+            Self::Ret(Ret::EnableInterrupts) => vec![0xD9],
+            Self::Rst(Rst(address)) => {
+                assert!(address & !0x38 == 0, "Invalid RST vector: {:#04x}", address);
+                vec![0xC7 | address]
+            }
+            Self::Add(Add::Internal(register)) => alu(0x80, 0xC6, Some(register), 0),
+            Self::Add(Add::Immediate(value)) => alu(0x80, 0xC6, None, value),
+            Self::Add(Add::Internal16(register)) => vec![0x09 | r16(register) << 4],
+            Self::Add(Add::StackPointer(offset)) => vec![0xE8, offset as u8],
+            Self::Adc(Adc::Internal(register)) => alu(0x88, 0xCE, Some(register), 0),
+            Self::Adc(Adc::Immediate(value)) => alu(0x88, 0xCE, None, value),
+            Self::Sub(Sub::Internal(register)) => alu(0x90, 0xD6, Some(register), 0),
+            Self::Sub(Sub::Immediate(value)) => alu(0x90, 0xD6, None, value),
+            Self::Sbc(Sbc::Internal(register)) => alu(0x98, 0xDE, Some(register), 0),
+            Self::Sbc(Sbc::Immediate(value)) => alu(0x98, 0xDE, None, value),
+            Self::And(And::Internal(register)) => alu(0xA0, 0xE6, Some(register), 0),
+            Self::And(And::Immediate(value)) => alu(0xA0, 0xE6, None, value),
+            Self::Xor(Xor::Internal(register)) => alu(0xA8, 0xEE, Some(register), 0),
+            Self::Xor(Xor::Immediate(value)) => alu(0xA8, 0xEE, None, value),
+            Self::Or(Or::Internal(register)) => alu(0xB0, 0xF6, Some(register), 0),
+            Self::Or(Or::Immediate(value)) => alu(0xB0, 0xF6, None, value),
+            Self::Cp(Cp::Internal(register)) => alu(0xB8, 0xFE, Some(register), 0),
+            Self::Cp(Cp::Immediate(value)) => alu(0xB8, 0xFE, None, value),
+            Self::Inc(Inc::Internal(register)) => vec![0x04 | r8(register) << 3],
+            Self::Inc(Inc::Internal16(register)) => vec![0x03 | r16(register) << 4],
+            Self::Dec(Dec::Internal(register)) => vec![0x05 | r8(register) << 3],
+            Self::Dec(Dec::Internal16(register)) => vec![0x0B | r16(register) << 4],
+            Self::Daa => vec![0x27],
+            Self::Cpl => vec![0x2F],
+            Self::Scf => vec![0x37],
+            Self::Ccf => vec![0x3F],
+            Self::Load8(Load8::Internal(src, dst)) => {
+                assert!(
+                    !(src == Register8Index::HL && dst == Register8Index::HL),
+                    "LD [HL], [HL] is HALT"
+                );
+                vec![0x40 | r8(dst) << 3 | r8(src)]
+            }
+            Self::Load8(Load8::Immediate(dst, value)) => vec![0x06 | r8(dst) << 3, value],
+            Self::Load8(Load8::ImmediateMemory(address, LoadDirection::Into)) => {
+                with_u16(0xEA, address)
+            }
+            Self::Load8(Load8::ImmediateMemory(address, LoadDirection::From)) => {
+                with_u16(0xFA, address)
+            }
+            Self::Load8(Load8::ImmediatePointer(offset, LoadDirection::Into)) => vec![0xE0, offset],
+            Self::Load8(Load8::ImmediatePointer(offset, LoadDirection::From)) => vec![0xF0, offset],
+            Self::Load8(Load8::CPointer(LoadDirection::Into)) => vec![0xE2],
+            Self::Load8(Load8::CPointer(LoadDirection::From)) => vec![0xF2],
+            Self::Load8(Load8::InternalPointer(register, direction, step)) => {
+                let pointer = match (register, step) {
+                    (Register16Index::BC | Register16Index::DE, None) => r16(register) << 4,
+                    (Register16Index::HL, Some(true)) => 0x20,
+                    (Register16Index::HL, Some(false)) => 0x30,
+                    _ => panic!("Invalid pointer: {:?} {:?}", register, step),
+                };
+                match direction {
+                    LoadDirection::Into => vec![0x02 | pointer],
+                    LoadDirection::From => vec![0x0A | pointer],
+                }
+            }
+            Self::Load16(Load16::Immediate(register, value)) => {
+                with_u16(0x01 | r16(register) << 4, value)
+            }
+            Self::Load16(Load16::StackToMemory(address)) => with_u16(0x08, address),
+            Self::Load16(Load16::StackHL(None)) => vec![0xF9],
+            Self::Load16(Load16::StackHL(Some(offset))) => vec![0xF8, offset as u8],
+            Self::Load16(Load16::Push(register)) => vec![0xC5 | r16_stack(register) << 4],
+            Self::Load16(Load16::Pop(register)) => vec![0xC1 | r16_stack(register) << 4],
+            Self::Rotate(rotate) => {
+                let (base, register) = match rotate {
+                    Rotate::Rlc(register) => (0x00, register),
+                    Rotate::Rrc(register) => (0x08, register),
+                    Rotate::Rl(register) => (0x10, register),
+                    Rotate::Rr(register) => (0x18, register),
+                };
+                vec![0xCB, base | r8(register)]
+            }
+            Self::RotateA(rotate) => vec![match rotate {
+                RotateA::Rlca => 0x07,
+                RotateA::Rla => 0x17,
+                RotateA::Rrca => 0x0F,
+                RotateA::Rra => 0x1F,
+            }],
+            Self::Shift(shift) => {
+                let (base, register) = match shift {
+                    Shift::Sla(register) => (0x20, register),
+                    Shift::Sra(register) => (0x28, register),
+                    Shift::Srl(register) => (0x38, register),
+                };
+                vec![0xCB, base | r8(register)]
+            }
+            Self::Swap(Swap(register)) => vec![0xCB, 0x30 | r8(register)],
+            Self::Bit(bit) => {
+                let (base, index, register) = match bit {
+                    Bit::Test(index, register) => (0x40, index, register),
+                    Bit::Reset(index, register) => (0x80, index, register),
+                    Bit::Set(index, register) => (0xC0, index, register),
+                };
+                assert!(index < 8, "Invalid bit index: {}", index);
+                vec![0xCB, base | index << 3 | r8(register)]
+            }
+            Self::Invalid(opcode) => vec![opcode],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// A program assembled one instruction at a time, see the [module](self) docs
+pub struct Program {
+    /// Address of the first byte, to resolve jumps
+    origin: u16,
+    bytes: Vec<u8>,
+}
+
+impl Program {
+    /// Starts a program placed at address 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a program placed at `origin`, e.g. 0x0150 after the header
+    pub fn at(origin: u16) -> Self {
+        Self {
+            origin,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Address of the next instruction
+    pub fn here(&self) -> u16 {
+        self.origin.wrapping_add(self.bytes.len() as u16)
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Appends any instruction
+    pub fn instr(mut self, instruction: impl Assemble) -> Self {
+        self.bytes.extend(instruction.assemble());
+        self
+    }
+
+    /// Appends raw bytes, e.g. data or invalid opcodes
+    pub fn raw(mut self, bytes: &[u8]) -> Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn nop(self) -> Self {
+        self.instr(Instr::Nop)
+    }
+
+    pub fn halt(self) -> Self {
+        self.instr(Instr::Halt)
+    }
+
+    pub fn stop(self) -> Self {
+        self.instr(Instr::Stop)
+    }
+
+    pub fn di(self) -> Self {
+        self.instr(Instr::Di)
+    }
+
+    pub fn ei(self) -> Self {
+        self.instr(Instr::Ei)
+    }
+
+    /// LD dst, src
+    pub fn ld(self, dst: Register8Index, src: Register8Index) -> Self {
+        self.instr(Instr::Load8(Load8::Internal(src, dst)))
+    }
+
+    /// LD dst, value
+    pub fn ld_imm(self, dst: Register8Index, value: u8) -> Self {
+        self.instr(Instr::Load8(Load8::Immediate(dst, value)))
+    }
+
+    /// LD A, value
+    pub fn ld_a_imm(self, value: u8) -> Self {
+        self.ld_imm(Register8Index::A, value)
+    }
+
+    /// LD dst, value
+    pub fn ld16(self, dst: Register16Index, value: u16) -> Self {
+        self.instr(Instr::Load16(Load16::Immediate(dst, value)))
+    }
+
+    /// LD \[address\], A
+    pub fn ld_mem_a(self, address: u16) -> Self {
+        self.instr(Instr::Load8(Load8::ImmediateMemory(
+            address,
+            LoadDirection::Into,
+        )))
+    }
+
+    /// LD A, \[address\]
+    pub fn ld_a_mem(self, address: u16) -> Self {
+        self.instr(Instr::Load8(Load8::ImmediateMemory(
+            address,
+            LoadDirection::From,
+        )))
+    }
+
+    /// LDH [0xFF00 + offset], A
+    pub fn ldh_mem_a(self, offset: u8) -> Self {
+        self.instr(Instr::Load8(Load8::ImmediatePointer(
+            offset,
+            LoadDirection::Into,
+        )))
+    }
+
+    /// LDH A, [0xFF00 + offset]
+    pub fn ldh_a_mem(self, offset: u8) -> Self {
+        self.instr(Instr::Load8(Load8::ImmediatePointer(
+            offset,
+            LoadDirection::From,
+        )))
+    }
+
+    pub fn inc(self, register: Register8Index) -> Self {
+        self.instr(Instr::Inc(Inc::Internal(register)))
+    }
+
+    pub fn dec(self, register: Register8Index) -> Self {
+        self.instr(Instr::Dec(Dec::Internal(register)))
+    }
+
+    pub fn inc16(self, register: Register16Index) -> Self {
+        self.instr(Instr::Inc(Inc::Internal16(register)))
+    }
+
+    pub fn dec16(self, register: Register16Index) -> Self {
+        self.instr(Instr::Dec(Dec::Internal16(register)))
+    }
+
+    /// ADD A, value
+    pub fn add_imm(self, value: u8) -> Self {
+        self.instr(Instr::Add(Add::Immediate(value)))
+    }
+
+    /// SUB A, value
+    pub fn sub_imm(self, value: u8) -> Self {
+        self.instr(Instr::Sub(Sub::Immediate(value)))
+    }
+
+    /// CP A, value
+    pub fn cp_imm(self, value: u8) -> Self {
+        self.instr(Instr::Cp(Cp::Immediate(value)))
+    }
+
+    pub fn push(self, register: Register16StackIndex) -> Self {
+        self.instr(Instr::Load16(Load16::Push(register)))
+    }
+
+    pub fn pop(self, register: Register16StackIndex) -> Self {
+        self.instr(Instr::Load16(Load16::Pop(register)))
+    }
+
+    /// JP address
+    pub fn jp(self, address: u16) -> Self {
+        self.instr(Instr::Jump(Jump::Immediate(None, address)))
+    }
+
+    /// JP cond, address
+    pub fn jp_if(self, cond: Condition, address: u16) -> Self {
+        self.instr(Instr::Jump(Jump::Immediate(Some(cond), address)))
+    }
+
+    /// JR offset, relative to the next instruction
+    pub fn jr(self, offset: i8) -> Self {
+        self.instr(Instr::Jump(Jump::Relative(None, offset)))
+    }
+
+    /// JR to an absolute address, e.g. one from [`Program::here`]
+    pub fn jr_to(self, address: u16) -> Self {
+        let offset = self.relative(address);
+        self.jr(offset)
+    }
+
+    /// JR cond to an absolute address
+    pub fn jr_if_to(self, cond: Condition, address: u16) -> Self {
+        let offset = self.relative(address);
+        self.instr(Instr::Jump(Jump::Relative(Some(cond), offset)))
+    }
+
+    /// CALL address
+    pub fn call(self, address: u16) -> Self {
+        self.instr(Instr::Call(Call(None, address)))
+    }
+
+    pub fn ret(self) -> Self {
+        self.instr(Instr::Ret(Ret::Internal(None)))
+    }
+
+    pub fn reti(self) -> Self {
+        self.instr(Instr::Ret(Ret::EnableInterrupts))
+    }
+
+    /// Offset of a JR placed here to reach `address`
+    fn relative(&self, address: u16) -> i8 {
+        // Relative to the byte after the two of JR
+        let offset = address as i32 - self.here().wrapping_add(2) as i32;
+        i8::try_from(offset).unwrap_or_else(|_| {
+            panic!(
+                "{:#06x} is out of reach of a JR at {:#06x}",
+                address,
+                self.here()
+            )
+        })
+    }
+}
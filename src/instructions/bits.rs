@@ -2,9 +2,10 @@ use crate::cpu::{Cpu, Flag};
 
 use super::{Instruction, Register8Index};
 
-pub(crate) type BitIndex = u8;
+pub type BitIndex = u8;
 
-pub(crate) enum Bit {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bit {
     Set(BitIndex, Register8Index),
     Reset(BitIndex, Register8Index),
     Test(BitIndex, Register8Index),
@@ -41,146 +42,132 @@ impl Instruction for Bit {
     }
 }
 
-pub(crate) struct Swap(pub(crate) Register8Index);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Swap(pub Register8Index);
 
 impl Instruction for Swap {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
         let value = self.0.get(cpu);
-        let result = (value << 4) | (value >> 4);
+        let result = value.rotate_right(4);
         self.0.set(cpu, result);
+        cpu.set_flag(Flag::Zero, result == 0);
+        cpu.set_flag(Flag::Subtract, false);
+        cpu.set_flag(Flag::HalfCarry, false);
+        cpu.set_flag(Flag::Carry, false);
 
         (self.0 == Register8Index::HL) as usize * 16 + (self.0 != Register8Index::HL) as usize * 8
     }
 }
 
-pub(crate) enum Rotate {
-    Left(Register8Index),
-    LeftCarry(Register8Index),
-    Right(Register8Index),
-    RightCarry(Register8Index),
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rotate {
+    /// Rotate left, bit 7 goes to the carry and to bit 0
+    Rlc(Register8Index),
+    /// Rotate left through the carry
+    Rl(Register8Index),
+    /// Rotate right, bit 0 goes to the carry and to bit 7
+    Rrc(Register8Index),
+    /// Rotate right through the carry
+    Rr(Register8Index),
 }
 
 impl Instruction for Rotate {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
-        match self {
-            Self::Left(dst) => {
+        let carry = cpu.test_flag(Flag::Carry) as u8;
+        let (dst, result, carry) = match self {
+            Self::Rlc(dst) => {
                 let value = dst.get(cpu);
-                let result = value.rotate_left(1);
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0 && *dst != Register8Index::A);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x80 != 0);
-
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+                (dst, value.rotate_left(1), value & 0x80 != 0)
             }
-            Self::LeftCarry(dst) => {
+            Self::Rl(dst) => {
                 let value = dst.get(cpu);
-                let result = (value << 1) | (cpu.test_flag(Flag::Carry) as u8);
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0 && *dst != Register8Index::A);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x80 != 0);
-
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+                (dst, (value << 1) | carry, value & 0x80 != 0)
             }
-            Self::Right(dst) => {
+            Self::Rrc(dst) => {
                 let value = dst.get(cpu);
-                let result = value.rotate_right(1);
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0 && *dst != Register8Index::A);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x01 != 0);
-
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+                (dst, value.rotate_right(1), value & 0x01 != 0)
             }
-            Self::RightCarry(dst) => {
+            Self::Rr(dst) => {
                 let value = dst.get(cpu);
-                let result = (value >> 1) | ((cpu.test_flag(Flag::Carry) as u8) << 7);
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0 && *dst != Register8Index::A);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x01 != 0);
-
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+                (dst, (value >> 1) | (carry << 7), value & 0x01 != 0)
             }
-        }
+        };
+        dst.set(cpu, result);
+        cpu.set_flag(Flag::Zero, result == 0);
+        cpu.set_flag(Flag::Subtract, false);
+        cpu.set_flag(Flag::HalfCarry, false);
+        cpu.set_flag(Flag::Carry, carry);
+
+        (*dst == Register8Index::HL) as usize * 16 + (*dst != Register8Index::HL) as usize * 8
     }
 }
 
-pub(crate) enum Shift {
-    Left(Register8Index),
-    LeftLogically(Register8Index),
-    Right(Register8Index),
-    RightLogically(Register8Index),
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The unprefixed rotates of A, which unlike their prefixed counterparts
+/// always clear Z
+pub enum RotateA {
+    /// Rotate left, bit 7 goes to the carry and to bit 0
+    Rlca,
+    /// Rotate left through the carry
+    Rla,
+    /// Rotate right, bit 0 goes to the carry and to bit 7
+    Rrca,
+    /// Rotate right through the carry
+    Rra,
 }
 
-impl Instruction for Shift {
+impl Instruction for RotateA {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
-        match self {
-            Self::Left(dst) => {
-                let value = dst.get(cpu);
-                let result = value << 1;
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x80 != 0);
+        let value = Register8Index::A.get(cpu);
+        let carry = cpu.test_flag(Flag::Carry) as u8;
+        let (result, carry) = match self {
+            Self::Rlca => (value.rotate_left(1), value & 0x80 != 0),
+            Self::Rla => ((value << 1) | carry, value & 0x80 != 0),
+            Self::Rrca => (value.rotate_right(1), value & 0x01 != 0),
+            Self::Rra => ((value >> 1) | (carry << 7), value & 0x01 != 0),
+        };
+        Register8Index::A.set(cpu, result);
+        cpu.set_flag(Flag::Zero, false);
+        cpu.set_flag(Flag::Subtract, false);
+        cpu.set_flag(Flag::HalfCarry, false);
+        cpu.set_flag(Flag::Carry, carry);
+
+        4
+    }
+}
 
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
-            }
-            Self::LeftLogically(dst) => {
-                let value = dst.get(cpu);
-                let result = value << 1;
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x80 != 0);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shift {
+    /// Shift left arithmetically, bit 0 is cleared
+    Sla(Register8Index),
+    /// Shift right arithmetically, bit 7 is kept
+    Sra(Register8Index),
+    /// Shift right logically, bit 7 is cleared
+    Srl(Register8Index),
+}
 
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+impl Instruction for Shift {
+    fn execute(&self, cpu: &mut dyn Cpu) -> usize {
+        let (dst, result, carry) = match self {
+            Self::Sla(dst) => {
+                let value = dst.get(cpu);
+                (dst, value << 1, value & 0x80 != 0)
             }
-            Self::Right(dst) => {
+            Self::Sra(dst) => {
                 let value = dst.get(cpu);
-                let result = value >> 1;
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x01 != 0);
-
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+                (dst, (value as i8 >> 1) as u8, value & 0x01 != 0)
             }
-            Self::RightLogically(dst) => {
+            Self::Srl(dst) => {
                 let value = dst.get(cpu);
-                let result = (value as i8 >> 1) as u8;
-                dst.set(cpu, result);
-                cpu.set_flag(Flag::Zero, result == 0);
-                cpu.set_flag(Flag::Subtract, false);
-                cpu.set_flag(Flag::HalfCarry, false);
-                cpu.set_flag(Flag::Carry, value & 0x01 != 0);
-
-                (*dst == Register8Index::HL) as usize * 16
-                    + (*dst != Register8Index::HL && *dst != Register8Index::A) as usize * 8
-                    + (*dst == Register8Index::A) as usize * 4
+                (dst, value >> 1, value & 0x01 != 0)
             }
-        }
+        };
+        dst.set(cpu, result);
+        cpu.set_flag(Flag::Zero, result == 0);
+        cpu.set_flag(Flag::Subtract, false);
+        cpu.set_flag(Flag::HalfCarry, false);
+        cpu.set_flag(Flag::Carry, carry);
+
+        (*dst == Register8Index::HL) as usize * 16 + (*dst != Register8Index::HL) as usize * 8
     }
 }
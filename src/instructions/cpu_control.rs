@@ -1,8 +1,11 @@
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, CpuMode, SPEED_SWITCH_CYCLES};
+use crate::memory::locations;
+use crate::Model;
 
 use super::Instruction;
 
-pub(crate) struct Nop;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nop;
 
 impl Instruction for Nop {
     fn execute(&self, _cpu: &mut dyn Cpu) -> usize {
@@ -10,7 +13,8 @@ impl Instruction for Nop {
     }
 }
 
-pub(crate) struct Di;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Di;
 
 impl Instruction for Di {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
@@ -21,7 +25,8 @@ impl Instruction for Di {
     }
 }
 
-pub(crate) struct Ei;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ei;
 
 impl Instruction for Ei {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
@@ -32,18 +37,59 @@ impl Instruction for Ei {
     }
 }
 
-pub(crate) struct Halt;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Halt;
 
 impl Instruction for Halt {
-    fn execute(&self, _cpu: &mut dyn Cpu) -> usize {
-        unimplemented!("Halt instruction not implemented")
+    fn execute(&self, cpu: &mut dyn Cpu) -> usize {
+        cpu.registers_mut().mode = CpuMode::Halted;
+
+        4
     }
 }
 
-pub(crate) struct Stop;
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// STOP behaves differently depending on the joypad, pending interrupts
+/// and (on CGB) an armed speed switch.
+///
+/// [REFERENCE](https://gbdev.io/pandocs/Reducing_Power_Consumption.html#using-the-stop-instruction)
+pub struct Stop;
 
 impl Instruction for Stop {
-    fn execute(&self, _cpu: &mut dyn Cpu) -> usize {
-        unimplemented!("Stop instruction not implemented")
+    fn execute(&self, cpu: &mut dyn Cpu) -> usize {
+        let pending = cpu.interrupt_pending();
+        let key1 = cpu.read_u8(locations::KEY1);
+        let speed_switch = cpu.model() == Model::Cgb && key1 & 0b1 != 0;
+
+        // Whether the byte following STOP is consumed as an operand
+        let two_bytes = !pending;
+
+        if cpu.joypad_held() {
+            // DIV is not reset, HALT is entered only without pending interrupts
+            if !pending {
+                cpu.registers_mut().mode = CpuMode::Halted;
+            }
+        } else {
+            cpu.write_u8(locations::DIV, 0);
+
+            if speed_switch {
+                if pending && cpu.registers().ime {
+                    log::warn!(target: "gbemu::cpu", "STOP speed switch with a pending interrupt and IME set is non-deterministic on hardware");
+                }
+
+                cpu.memory_mut()[locations::KEY1] = (key1 ^ 0b1000_0000) & !0b1;
+                if !pending {
+                    cpu.registers_mut().mode = CpuMode::SpeedSwitch(SPEED_SWITCH_CYCLES);
+                }
+            } else {
+                cpu.registers_mut().mode = CpuMode::Stopped;
+            }
+        }
+
+        if two_bytes {
+            *cpu.registers_mut().pc = cpu.registers().pc.wrapping_add(1);
+        }
+
+        4
     }
 }
@@ -0,0 +1,46 @@
+//! Text rendering of instructions, driven by the opcode metadata.
+use crate::memory::{locations, Read};
+
+use super::{routines::relative_target, table::opcode_info};
+
+/// Renders the instruction at `address` with its operands and returns it
+/// together with its length in bytes.
+///
+/// Absolute operands are shown in hex, `JR` targets as the address they jump
+/// to and the `SP` offsets as signed decimals. Accesses to I/O registers are
+/// annotated with their name, e.g. `LDH [$FF41],A ; STAT`.
+pub fn disassemble(memory: &impl Read, address: usize) -> (String, u8) {
+    let opcode = memory.read_u8(address);
+    let info = opcode_info(opcode, memory.read_u8(address + 1));
+    let n8 = memory.read_u8(address + 1);
+    let n16 = u16::from_le_bytes([n8, memory.read_u8(address + 2)]);
+    let e8 = n8 as i8;
+
+    let mnemonic = info.mnemonic;
+    let register = if mnemonic.contains("a8") {
+        locations::name(0xFF00 + n8 as usize)
+    } else if mnemonic.contains("a16") {
+        locations::name(n16 as usize)
+    } else {
+        None
+    };
+    let text = if mnemonic.starts_with("JR") {
+        let target = relative_target((address as u16).wrapping_add(info.length as u16), e8);
+        mnemonic.replace("e8", &format!("${:04X}", target))
+    } else if mnemonic.contains("SP+e8") {
+        mnemonic.replace("+e8", &format!("{:+}", e8))
+    } else {
+        mnemonic
+            .replace("n16", &format!("${:04X}", n16))
+            .replace("a16", &format!("${:04X}", n16))
+            .replace("n8", &format!("${:02X}", n8))
+            .replace("a8", &format!("$FF{:02X}", n8))
+            .replace("e8", &format!("{:+}", e8))
+    };
+    let text = match register {
+        Some(name) => format!("{} ; {}", text, name),
+        None => text,
+    };
+
+    (text, info.length)
+}
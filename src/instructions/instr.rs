@@ -0,0 +1,83 @@
+//! Data-only representation of a decoded instruction with its operands.
+use crate::cpu::Cpu;
+
+use super::{arithmetics::*, bits::*, cpu_control, loads::*, routines::*, Instruction};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// An instruction as decoded, inspectable without executing it
+pub enum Instr {
+    Nop,
+    Stop,
+    Halt,
+    Di,
+    Ei,
+    Jump(Jump),
+    Call(Call),
+    Ret(Ret),
+    Rst(Rst),
+    Add(Add),
+    Adc(Adc),
+    Sub(Sub),
+    Sbc(Sbc),
+    And(And),
+    Xor(Xor),
+    Or(Or),
+    Cp(Cp),
+    Inc(Inc),
+    Dec(Dec),
+    Daa,
+    Cpl,
+    Scf,
+    Ccf,
+    Load8(Load8),
+    Load16(Load16),
+    Rotate(Rotate),
+    RotateA(RotateA),
+    Shift(Shift),
+    Swap(Swap),
+    Bit(Bit),
+    /// An opcode with no instruction
+    Invalid(u8),
+}
+
+impl Instruction for Instr {
+    fn execute(&self, cpu: &mut dyn Cpu) -> usize {
+        match self {
+            Self::Nop => cpu_control::Nop.execute(cpu),
+            Self::Stop => cpu_control::Stop.execute(cpu),
+            Self::Halt => cpu_control::Halt.execute(cpu),
+            Self::Di => cpu_control::Di.execute(cpu),
+            Self::Ei => cpu_control::Ei.execute(cpu),
+            Self::Jump(instruction) => instruction.execute(cpu),
+            Self::Call(instruction) => instruction.execute(cpu),
+            Self::Ret(instruction) => instruction.execute(cpu),
+            Self::Rst(instruction) => instruction.execute(cpu),
+            Self::Add(instruction) => instruction.execute(cpu),
+            Self::Adc(instruction) => instruction.execute(cpu),
+            Self::Sub(instruction) => instruction.execute(cpu),
+            Self::Sbc(instruction) => instruction.execute(cpu),
+            Self::And(instruction) => instruction.execute(cpu),
+            Self::Xor(instruction) => instruction.execute(cpu),
+            Self::Or(instruction) => instruction.execute(cpu),
+            Self::Cp(instruction) => instruction.execute(cpu),
+            Self::Inc(instruction) => instruction.execute(cpu),
+            Self::Dec(instruction) => instruction.execute(cpu),
+            Self::Daa => Daa.execute(cpu),
+            Self::Cpl => Cpl.execute(cpu),
+            Self::Scf => Scf.execute(cpu),
+            Self::Ccf => Ccf.execute(cpu),
+            Self::Load8(instruction) => instruction.execute(cpu),
+            Self::Load16(instruction) => instruction.execute(cpu),
+            Self::Rotate(instruction) => instruction.execute(cpu),
+            Self::RotateA(instruction) => instruction.execute(cpu),
+            Self::Shift(instruction) => instruction.execute(cpu),
+            Self::Swap(instruction) => instruction.execute(cpu),
+            Self::Bit(instruction) => instruction.execute(cpu),
+            Self::Invalid(opcode) => panic!(
+                "Unimplemented opcode: {:#04x} ({})",
+                opcode,
+                super::OPCODE_TABLE[*opcode as usize].mnemonic
+            ),
+        }
+    }
+}
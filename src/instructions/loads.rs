@@ -1,6 +1,9 @@
-use crate::cpu::{Cpu, Flag};
+use crate::cpu::Cpu;
 
-use super::{Instruction, Register16Index, Register8Destination, Register8Index, Register8Source};
+use super::{
+    alu, Instruction, Register16Index, Register16StackIndex, Register8Destination, Register8Index,
+    Register8Source,
+};
 
 // Load internal
 // LD r, r   0b01xxxyyy        | 0b01000000..=0b01111111
@@ -33,12 +36,14 @@ use super::{Instruction, Register16Index, Register8Destination, Register8Index,
 // LD A, (HL+) 0b00101010
 // LD (HL+), A 0b00100010
 
-pub(crate) enum LoadDirection {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadDirection {
     From,
     Into,
 }
 
-pub(crate) enum Load8 {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Load8 {
     /// Loads the value from one 8-bit register into another.
     Internal(Register8Source, Register8Destination),
     /// Loads immediates bytes into an 8-bit register.
@@ -60,8 +65,8 @@ impl Instruction for Load8 {
                 let value = src.get(cpu);
                 dst.set(cpu, value);
 
-                (*dst == Register8Index::HL) as usize * 8
-                    + (*dst != Register8Index::HL) as usize * 4
+                (*dst == Register8Index::HL || *src == Register8Index::HL) as usize * 8
+                    + (*dst != Register8Index::HL && *src != Register8Index::HL) as usize * 4
             }
             Self::Immediate(dst, value) => {
                 dst.set(cpu, *value);
@@ -73,10 +78,10 @@ impl Instruction for Load8 {
                 match dir {
                     LoadDirection::From => {
                         let value = cpu.read_u8(*addr as usize);
-                        cpu.registers_mut().af.hi = value;
+                        cpu.registers_mut().af.bytes.hi = value;
                     }
                     LoadDirection::Into => {
-                        let value = unsafe { cpu.registers().af.hi };
+                        let value = unsafe { cpu.registers().af.bytes.hi };
                         cpu.write_u8(*addr as usize, value);
                     }
                 }
@@ -87,10 +92,10 @@ impl Instruction for Load8 {
                 match dir {
                     LoadDirection::From => {
                         let value = cpu.read_u8(0xFF00 + *offset as usize);
-                        cpu.registers_mut().af.hi = value;
+                        cpu.registers_mut().af.bytes.hi = value;
                     }
                     LoadDirection::Into => {
-                        let value = unsafe { cpu.registers().af.hi };
+                        let value = unsafe { cpu.registers().af.bytes.hi };
                         cpu.write_u8(0xFF00 + *offset as usize, value);
                     }
                 }
@@ -102,10 +107,10 @@ impl Instruction for Load8 {
                 match dir {
                     LoadDirection::From => {
                         let value = cpu.read_u8(addr as usize);
-                        cpu.registers_mut().af.hi = value;
+                        cpu.registers_mut().af.bytes.hi = value;
                     }
                     LoadDirection::Into => {
-                        let value = unsafe { cpu.registers().af.hi };
+                        let value = unsafe { cpu.registers().af.bytes.hi };
                         cpu.write_u8(addr as usize, value);
                     }
                 }
@@ -123,12 +128,16 @@ impl Instruction for Load8 {
             Self::CPointer(dir) => {
                 match dir {
                     LoadDirection::From => {
-                        let value = cpu.read_u8(0xff00 + unsafe { cpu.registers().bc.lo } as usize);
-                        cpu.registers_mut().af.hi = value;
+                        let value =
+                            cpu.read_u8(0xff00 + unsafe { cpu.registers().bc.bytes.lo } as usize);
+                        cpu.registers_mut().af.bytes.hi = value;
                     }
                     LoadDirection::Into => {
-                        let value = unsafe { cpu.registers().af.hi };
-                        cpu.write_u8(0xff00 + unsafe { cpu.registers().bc.lo } as usize, value);
+                        let value = unsafe { cpu.registers().af.bytes.hi };
+                        cpu.write_u8(
+                            0xff00 + unsafe { cpu.registers().bc.bytes.lo } as usize,
+                            value,
+                        );
                     }
                 }
 
@@ -138,12 +147,13 @@ impl Instruction for Load8 {
     }
 }
 
-pub(crate) enum Load16 {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Load16 {
     Immediate(Register16Index, u16),
     StackToMemory(u16),
     StackHL(Option<i8>),
-    Push(Register16Index),
-    Pop(Register16Index),
+    Push(Register16StackIndex),
+    Pop(Register16StackIndex),
 }
 
 impl Instruction for Load16 {
@@ -163,7 +173,9 @@ impl Instruction for Load16 {
             Self::StackHL(offset) => match offset {
                 Some(offset) => {
                     let sp = *cpu.registers().sp;
-                    *cpu.registers_mut().hl = sp + *offset as u16;
+                    let (result, flags) = alu::add_sp(sp, *offset);
+                    *cpu.registers_mut().hl = result;
+                    flags.apply(cpu);
                     12
                 }
                 None => {
@@ -173,34 +185,23 @@ impl Instruction for Load16 {
             },
             Self::Push(src) => {
                 let sp = *cpu.registers().sp;
-                let value = src.get(cpu);
-                if *src == Register16Index::AF {
-                    cpu.write_u16(
-                        sp as usize - 2,
-                        value & 0xFF00
-                            | (if cpu.test_flag(Flag::Zero) { 1 } else { 0 } << 7)
-                            | (if cpu.test_flag(Flag::Subtract) { 1 } else { 0 } << 6)
-                            | (if cpu.test_flag(Flag::HalfCarry) { 1 } else { 0 } << 5)
-                            | (if cpu.test_flag(Flag::Carry) { 1 } else { 0 } << 4),
-                    );
-                } else {
-                    cpu.write_u16(sp as usize - 2, value);
+                let mut value = src.get(cpu);
+                // The low nibble of F does not exist
+                if *src == Register16StackIndex::AF {
+                    value &= 0xFFF0;
                 }
-                *cpu.registers_mut().sp -= 2;
+                cpu.write_u16(sp.wrapping_sub(2) as usize, value);
+                *cpu.registers_mut().sp = sp.wrapping_sub(2);
                 16
             }
             Self::Pop(dst) => {
-                if *dst == Register16Index::AF {
-                    cpu.set_flag(Flag::Zero, dst.get(cpu) & (1 << 7) != 0);
-                    cpu.set_flag(Flag::Subtract, dst.get(cpu) & (1 << 6) != 0);
-                    cpu.set_flag(Flag::HalfCarry, dst.get(cpu) & (1 << 5) != 0);
-                    cpu.set_flag(Flag::Carry, dst.get(cpu) & (1 << 4) != 0);
-                }
-
                 let sp = *cpu.registers().sp;
-                let value = cpu.read_u16(sp as usize);
+                let mut value = cpu.read_u16(sp as usize);
+                if *dst == Register16StackIndex::AF {
+                    value &= 0xFFF0;
+                }
                 dst.set(cpu, value);
-                *cpu.registers_mut().sp += 2;
+                *cpu.registers_mut().sp = sp.wrapping_add(2);
 
                 12
             }
@@ -3,13 +3,23 @@ use crate::{
     memory::Read,
 };
 
+pub use self::disasm::disassemble;
+pub use self::instr::Instr;
 use self::loads::LoadDirection;
+pub use self::table::{
+    opcode_info, FlagEffect, Mismatch, OpcodeInfo, CB_OPCODE_TABLE, OPCODE_TABLE,
+};
 
-mod arithmetics;
-mod bits;
-mod cpu_control;
-mod loads;
-mod routines;
+pub mod alu;
+pub mod arithmetics;
+pub mod asm;
+pub mod bits;
+pub mod cpu_control;
+mod disasm;
+mod instr;
+pub mod loads;
+pub mod routines;
+pub mod table;
 
 pub type Register8Source = Register8Index;
 pub type Register8Destination = Register8Index;
@@ -29,14 +39,14 @@ pub enum Register8Index {
 impl Register8Index {
     pub fn set(&self, cpu: &mut dyn Cpu, value: u8) {
         match self {
-            Self::A => cpu.registers_mut().af.hi = value,
-            Self::B => cpu.registers_mut().bc.hi = value,
-            Self::C => cpu.registers_mut().bc.lo = value,
-            Self::D => cpu.registers_mut().de.hi = value,
-            Self::E => cpu.registers_mut().de.lo = value,
-            Self::H => cpu.registers_mut().hl.hi = value,
-            Self::L => cpu.registers_mut().hl.lo = value,
-            Self::F => cpu.registers_mut().af.lo = value,
+            Self::A => cpu.registers_mut().af.bytes.hi = value,
+            Self::B => cpu.registers_mut().bc.bytes.hi = value,
+            Self::C => cpu.registers_mut().bc.bytes.lo = value,
+            Self::D => cpu.registers_mut().de.bytes.hi = value,
+            Self::E => cpu.registers_mut().de.bytes.lo = value,
+            Self::H => cpu.registers_mut().hl.bytes.hi = value,
+            Self::L => cpu.registers_mut().hl.bytes.lo = value,
+            Self::F => cpu.registers_mut().af.bytes.lo = value,
             Self::HL => cpu.write_u8(*cpu.registers().hl as usize, value),
         }
     }
@@ -44,14 +54,14 @@ impl Register8Index {
     pub fn get(&self, cpu: &dyn Cpu) -> u8 {
         unsafe {
             match self {
-                Self::A => cpu.registers().af.hi,
-                Self::B => cpu.registers().bc.hi,
-                Self::C => cpu.registers().bc.lo,
-                Self::D => cpu.registers().de.hi,
-                Self::E => cpu.registers().de.lo,
-                Self::H => cpu.registers().hl.hi,
-                Self::L => cpu.registers().hl.lo,
-                Self::F => cpu.registers().af.lo,
+                Self::A => cpu.registers().af.bytes.hi,
+                Self::B => cpu.registers().bc.bytes.hi,
+                Self::C => cpu.registers().bc.bytes.lo,
+                Self::D => cpu.registers().de.bytes.hi,
+                Self::E => cpu.registers().de.bytes.lo,
+                Self::H => cpu.registers().hl.bytes.hi,
+                Self::L => cpu.registers().hl.bytes.lo,
+                Self::F => cpu.registers().af.bytes.lo,
                 Self::HL => cpu.read_u8(*cpu.registers().hl as usize),
             }
         }
@@ -122,6 +132,49 @@ impl From<u8> for Register16Index {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// 16-bit register operand of PUSH and POP, where index 3 selects AF
+/// instead of SP
+pub enum Register16StackIndex {
+    BC,
+    DE,
+    HL,
+    AF,
+}
+
+impl Register16StackIndex {
+    pub fn set(&self, cpu: &mut dyn Cpu, value: u16) {
+        Register16Index::from(*self).set(cpu, value)
+    }
+
+    pub fn get(&self, cpu: &dyn Cpu) -> u16 {
+        Register16Index::from(*self).get(cpu)
+    }
+}
+
+impl From<u8> for Register16StackIndex {
+    fn from(value: u8) -> Self {
+        match value {
+            0x0 => Register16StackIndex::BC,
+            0x1 => Register16StackIndex::DE,
+            0x2 => Register16StackIndex::HL,
+            0x3 => Register16StackIndex::AF,
+            _ => panic!("Invalid register index: {:#02x}", value),
+        }
+    }
+}
+
+impl From<Register16StackIndex> for Register16Index {
+    fn from(value: Register16StackIndex) -> Self {
+        match value {
+            Register16StackIndex::BC => Register16Index::BC,
+            Register16StackIndex::DE => Register16Index::DE,
+            Register16StackIndex::HL => Register16Index::HL,
+            Register16StackIndex::AF => Register16Index::AF,
+        }
+    }
+}
+
 pub trait Instruction {
     /// ### Execute
     ///
@@ -134,332 +187,377 @@ pub trait Assemble {
     fn assemble(&self) -> Vec<u8>;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An instruction as fetched from memory: the opcode followed by its operands
+pub struct InstructionBytes {
+    bytes: [u8; 3],
+    length: u8,
+}
+
+impl InstructionBytes {
+    pub fn opcode(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.length as usize]
+    }
+}
+
+/// Returns the bytes making up the instruction starting with `opcode`, as
+/// consumed by [`decode`]
+pub fn instruction_length(opcode: u8) -> u8 {
+    match opcode {
+        // Prefixed instructions are all two bytes long, see the CB table
+        0xCB => 2,
+        // The byte after STOP is skipped at execution, depending on the interrupts
+        0x10 => 1,
+        _ => OPCODE_TABLE[opcode as usize].length,
+    }
+}
+
 pub trait InstructionDecoder: Registers + Read {
     fn fetch(&mut self) -> u8 {
-        let pc = self.registers().pc;
-        *self.registers_mut().pc += 1;
-        self.read_u8(*pc as usize)
+        let pc = *self.registers().pc;
+        *self.registers_mut().pc = pc.wrapping_add(1);
+        self.read_u8(pc as usize)
     }
 
-    fn decode(&mut self, opcode: u8) -> Box<dyn Instruction> {
-        match opcode {
-            // == Misc/Control ==
-            0x0 => Box::new(cpu_control::Nop),
-            0x10 => Box::new(cpu_control::Stop),
-            0x76 => Box::new(cpu_control::Halt),
-            0xF3 => Box::new(cpu_control::Di),
-            0xFB => Box::new(cpu_control::Ei),
-
-            // == Jump/Routines ==
-
-            // JR
-            0x18 => Box::new(routines::Jump::Relative(None, self.fetch() as i8)),
-
-            // JR cond
-            // 0b100000 | 0b110000 | 0b101000 | 0b111000
-            0x20 | 0x30 | 0x28 | 0x38 => Box::new(routines::Jump::Relative(
-                Some(routines::Condition::from((opcode >> 3) & 0b11)),
-                self.fetch() as i8,
-            )),
-
-            // JP
-            0xC3 => Box::new(routines::Jump::Immediate(
-                None,
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-            )),
-
-            // JP cond
-            0xC2 | 0xD2 | 0xCA | 0xDA => Box::new(routines::Jump::Immediate(
-                Some(routines::Condition::from((opcode >> 3) & 0b11)),
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-            )),
-
-            // JP HL
-            0xE9 => Box::new(routines::Jump::Internal),
-
-            // Call
-            0xCD => Box::new(routines::Call(
-                None,
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-            )),
-
-            // Call cond
-            0xC4 | 0xD4 | 0xCC | 0xDC => Box::new(routines::Call(
-                Some(routines::Condition::from((opcode >> 3) & 0b11)),
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-            )),
-
-            // Ret
-            0xC9 => Box::new(routines::Ret::Internal(None)),
-
-            // Ret cond
-            0xC0 | 0xD0 | 0xC8 | 0xD8 => Box::new(routines::Ret::Internal(Some(
-                routines::Condition::from((opcode >> 3) & 0b11),
-            ))),
-
-            // Reti
-            0xD9 => Box::new(routines::Ret::EnableInterrupts),
-
-            // Rst
-            0xCF | 0xDF | 0xEF | 0xFF => Box::new(routines::Rst(opcode & 0b00110000 | 0x08)),
-
-            // == Arithmetic/Logic ==
-
-            // Adds
-            0x80..=0x87 => Box::new(arithmetics::Add::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xC6 => Box::new(arithmetics::Add::Immediate(self.fetch())),
-            0x9 | 0x19 | 0x29 | 0x39 => Box::new(arithmetics::Add::Internal16(
-                Register16Index::from((opcode >> 4) & 0b11),
-            )),
-            0xE8 => Box::new(arithmetics::Add::StackPointer(self.fetch() as i8)),
-
-            // Adc
-            0x88..=0x8F => Box::new(arithmetics::Adc::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xCE => Box::new(arithmetics::Adc::Immediate(self.fetch())),
-
-            // Sub
-            0x90..=0x97 => Box::new(arithmetics::Sub::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xD6 => Box::new(arithmetics::Sub::Immediate(self.fetch())),
-
-            // Sbc
-            0x98..=0x9F => Box::new(arithmetics::Sbc::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xDE => Box::new(arithmetics::Sbc::Immediate(self.fetch())),
-
-            // And
-            0xA0..=0xA7 => Box::new(arithmetics::And::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xE6 => Box::new(arithmetics::And::Immediate(self.fetch())),
-
-            // Xor
-            0xA8..=0xAF => Box::new(arithmetics::Xor::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xEE => Box::new(arithmetics::Xor::Immediate(self.fetch())),
-
-            // Or
-            0xB0..=0xB7 => Box::new(arithmetics::Or::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xF6 => Box::new(arithmetics::Or::Immediate(self.fetch())),
-
-            // Cp
-            0xB8..=0xBF => Box::new(arithmetics::Cp::Internal(Register8Index::from(
-                opcode & 0b111,
-            ))),
-            0xFE => Box::new(arithmetics::Cp::Immediate(self.fetch())),
-
-            // Inc
-            0x4 | 0xC | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => Box::new(
-                arithmetics::Inc::Internal(Register8Index::from((opcode >> 3) & 0b111)),
-            ),
-            0x3 | 0x13 | 0x23 | 0x33 => Box::new(arithmetics::Inc::Internal16(
-                Register16Index::from((opcode >> 4) & 0b11),
-            )),
-
-            // Dec
-            0x5 | 0xD | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => Box::new(
-                arithmetics::Dec::Internal(Register8Index::from((opcode >> 3) & 0b111)),
-            ),
-            0xB | 0x1B | 0x2B | 0x3B => Box::new(arithmetics::Dec::Internal16(
-                Register16Index::from((opcode >> 4) & 0b11),
-            )),
-
-            // Daa
-            0x27 => Box::new(arithmetics::Daa),
-
-            // Cpl
-            0x2F => Box::new(arithmetics::Cpl),
-
-            // Scf
-            0x37 => Box::new(arithmetics::Scf),
-
-            // Ccf
-            0x3F => Box::new(arithmetics::Ccf),
-
-            // == Loads/Stack ==
-
-            // == Load8 ==
-
-            // LD r8, r8 Internal
-            0x40..=0x6F | 0x70..=0x75 | 0x77..=0x7F => Box::new(loads::Load8::Internal(
-                Register8Index::from(opcode & 0b111),
-                Register8Index::from((opcode >> 3) & 0b111),
-            )),
-
-            // LD r8, n8 Immediate
-            0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E => Box::new(
-                loads::Load8::Immediate(Register8Index::from((opcode >> 3) & 0b111), self.fetch()),
-            ),
-
-            // LD [C], A
-            0xE2 => Box::new(loads::Load8::CPointer(LoadDirection::Into)),
-            // LD A, [C]
-            0xF2 => Box::new(loads::Load8::CPointer(LoadDirection::From)),
-
-            // LD [n8], A
-            0xE0 => Box::new(loads::Load8::ImmediatePointer(
-                self.fetch(),
-                LoadDirection::Into,
-            )),
-            // LD A, [n8]
-            0xF0 => Box::new(loads::Load8::ImmediatePointer(
-                self.fetch(),
-                LoadDirection::From,
-            )),
-
-            // LD [r16], A
-            0x02 | 0x12 => Box::new(loads::Load8::InternalPointer(
-                Register16Index::from((opcode >> 4) & 0b11),
-                LoadDirection::Into,
-                None,
-            )),
-            // LD A, [r16]
-            0x0A | 0x1A => Box::new(loads::Load8::InternalPointer(
-                Register16Index::from((opcode >> 4) & 0b11),
-                LoadDirection::From,
-                None,
-            )),
-
-            // LD [HL+], A
-            0x22 => Box::new(loads::Load8::InternalPointer(
-                Register16Index::from((opcode >> 4) & 0b11),
-                LoadDirection::Into,
-                Some(true),
-            )),
-            // LD A, [HL+]
-            0x2A => Box::new(loads::Load8::InternalPointer(
-                Register16Index::from((opcode >> 4) & 0b11),
-                LoadDirection::From,
-                Some(true),
-            )),
-
-            // LD [HL-], A
-            0x32 => Box::new(loads::Load8::InternalPointer(
-                Register16Index::from((opcode >> 4) & 0b11),
-                LoadDirection::Into,
-                Some(false),
-            )),
-            // LD A, [HL-]
-            0x3A => Box::new(loads::Load8::InternalPointer(
-                Register16Index::from((opcode >> 4) & 0b11),
-                LoadDirection::From,
-                Some(false),
-            )),
-
-            // LD [a16], A
-            0xEA => Box::new(loads::Load8::ImmediateMemory(
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-                LoadDirection::Into,
-            )),
-            // LD A, [a16]
-            0xFA => Box::new(loads::Load8::ImmediateMemory(
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-                LoadDirection::From,
-            )),
-
-            // == Load16 ==
-
-            // LD r16, n16 Immediate
-            0x01 | 0x11 | 0x21 | 0x31 => Box::new(loads::Load16::Immediate(
-                Register16Index::from((opcode >> 4) & 0b11),
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-            )),
-
-            // LD SP, HL
-            0xF9 => Box::new(loads::Load16::StackHL(None)),
-            // LD HL, SP+e8
-            0xF8 => Box::new(loads::Load16::StackHL(Some(self.fetch() as i8))),
-
-            // LD [n16], SP
-            0x08 => Box::new(loads::Load16::StackToMemory(
-                self.fetch() as u16 | ((self.fetch() as u16) << 8),
-            )),
-
-            // PUSH
-            0xC5 | 0xD5 | 0xE5 | 0xF5 => Box::new(loads::Load16::Push(Register16Index::from(
-                (opcode >> 4) & 0b11,
-            ))),
-
-            // POP
-            0xC1 | 0xD1 | 0xE1 | 0xF1 => Box::new(loads::Load16::Pop(Register16Index::from(
-                (opcode >> 4) & 0b11,
-            ))),
-
-            // == Prefixed ==
-            0xCB => match self.fetch() {
+    /// Fetches a 16-bit immediate, stored little-endian: the byte at PC is
+    /// the low one
+    fn fetch_u16(&mut self) -> u16 {
+        let low = self.fetch();
+        let high = self.fetch();
+        u16::from_le_bytes([low, high])
+    }
+
+    /// Fetches the whole instruction at PC, leaving PC after it
+    fn fetch_instruction(&mut self) -> InstructionBytes {
+        let mut bytes = [0; 3];
+        bytes[0] = self.fetch();
+        let length = instruction_length(bytes[0]);
+        match length {
+            2 => bytes[1] = self.fetch(),
+            3 => bytes[1..].copy_from_slice(&self.fetch_u16().to_le_bytes()),
+            _ => {}
+        }
+        InstructionBytes { bytes, length }
+    }
+}
+
+/// Decodes the instruction at `pc` without touching the registers, returning
+/// it together with its length in bytes
+pub fn decode_at(memory: &impl Read, pc: u16) -> (Instr, u8) {
+    let opcode = memory.read_u8(pc as usize);
+    let length = instruction_length(opcode);
+    let mut bytes = [opcode, 0, 0];
+    for (offset, byte) in bytes.iter_mut().enumerate().take(length as usize).skip(1) {
+        *byte = memory.read_u8(pc.wrapping_add(offset as u16) as usize);
+    }
+    (decode(&bytes[..length as usize]), length)
+}
+
+/// Decodes an instruction from its bytes, without side effects.
+///
+/// # Panics
+///
+/// If `bytes` is shorter than [`instruction_length`] of its first byte.
+pub fn decode(bytes: &[u8]) -> Instr {
+    let opcode = bytes[0];
+    let n8 = || bytes[1];
+    // Immediates are stored little-endian
+    let n16 = || u16::from_le_bytes([bytes[1], bytes[2]]);
+
+    match opcode {
+        // == Misc/Control ==
+        0x0 => Instr::Nop,
+        0x10 => Instr::Stop,
+        0x76 => Instr::Halt,
+        0xF3 => Instr::Di,
+        0xFB => Instr::Ei,
+
+        // == Jump/Routines ==
+
+        // JR
+        0x18 => Instr::Jump(routines::Jump::Relative(None, n8() as i8)),
+
+        // JR cond
+        // 0b100000 | 0b110000 | 0b101000 | 0b111000
+        0x20 | 0x30 | 0x28 | 0x38 => Instr::Jump(routines::Jump::Relative(
+            Some(routines::Condition::from((opcode >> 3) & 0b11)),
+            n8() as i8,
+        )),
+
+        // JP
+        0xC3 => Instr::Jump(routines::Jump::Immediate(None, n16())),
+
+        // JP cond
+        0xC2 | 0xD2 | 0xCA | 0xDA => Instr::Jump(routines::Jump::Immediate(
+            Some(routines::Condition::from((opcode >> 3) & 0b11)),
+            n16(),
+        )),
+
+        // JP HL
+        0xE9 => Instr::Jump(routines::Jump::Internal),
+
+        // Call
+        0xCD => Instr::Call(routines::Call(None, n16())),
+
+        // Call cond
+        0xC4 | 0xD4 | 0xCC | 0xDC => Instr::Call(routines::Call(
+            Some(routines::Condition::from((opcode >> 3) & 0b11)),
+            n16(),
+        )),
+
+        // Ret
+        0xC9 => Instr::Ret(routines::Ret::Internal(None)),
+
+        // Ret cond
+        0xC0 | 0xD0 | 0xC8 | 0xD8 => Instr::Ret(routines::Ret::Internal(Some(
+            routines::Condition::from((opcode >> 3) & 0b11),
+        ))),
+
+        // Reti
+        0xD9 => Instr::Ret(routines::Ret::EnableInterrupts),
+
+        // Rst
+        0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => {
+            Instr::Rst(routines::Rst(opcode & 0b0011_1000))
+        }
+
+        // == Arithmetic/Logic ==
+
+        // Adds
+        0x80..=0x87 => Instr::Add(arithmetics::Add::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xC6 => Instr::Add(arithmetics::Add::Immediate(n8())),
+        0x9 | 0x19 | 0x29 | 0x39 => Instr::Add(arithmetics::Add::Internal16(
+            Register16Index::from((opcode >> 4) & 0b11),
+        )),
+        0xE8 => Instr::Add(arithmetics::Add::StackPointer(n8() as i8)),
+
+        // Adc
+        0x88..=0x8F => Instr::Adc(arithmetics::Adc::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xCE => Instr::Adc(arithmetics::Adc::Immediate(n8())),
+
+        // Sub
+        0x90..=0x97 => Instr::Sub(arithmetics::Sub::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xD6 => Instr::Sub(arithmetics::Sub::Immediate(n8())),
+
+        // Sbc
+        0x98..=0x9F => Instr::Sbc(arithmetics::Sbc::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xDE => Instr::Sbc(arithmetics::Sbc::Immediate(n8())),
+
+        // And
+        0xA0..=0xA7 => Instr::And(arithmetics::And::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xE6 => Instr::And(arithmetics::And::Immediate(n8())),
+
+        // Xor
+        0xA8..=0xAF => Instr::Xor(arithmetics::Xor::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xEE => Instr::Xor(arithmetics::Xor::Immediate(n8())),
+
+        // Or
+        0xB0..=0xB7 => Instr::Or(arithmetics::Or::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xF6 => Instr::Or(arithmetics::Or::Immediate(n8())),
+
+        // Cp
+        0xB8..=0xBF => Instr::Cp(arithmetics::Cp::Internal(Register8Index::from(
+            opcode & 0b111,
+        ))),
+        0xFE => Instr::Cp(arithmetics::Cp::Immediate(n8())),
+
+        // Inc
+        0x4 | 0xC | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => Instr::Inc(
+            arithmetics::Inc::Internal(Register8Index::from((opcode >> 3) & 0b111)),
+        ),
+        0x3 | 0x13 | 0x23 | 0x33 => Instr::Inc(arithmetics::Inc::Internal16(
+            Register16Index::from((opcode >> 4) & 0b11),
+        )),
+
+        // Dec
+        0x5 | 0xD | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => Instr::Dec(
+            arithmetics::Dec::Internal(Register8Index::from((opcode >> 3) & 0b111)),
+        ),
+        0xB | 0x1B | 0x2B | 0x3B => Instr::Dec(arithmetics::Dec::Internal16(
+            Register16Index::from((opcode >> 4) & 0b11),
+        )),
+
+        // Daa
+        0x27 => Instr::Daa,
+
+        // Cpl
+        0x2F => Instr::Cpl,
+
+        // Scf
+        0x37 => Instr::Scf,
+
+        // Ccf
+        0x3F => Instr::Ccf,
+
+        // Rotates of A
+        0x07 => Instr::RotateA(bits::RotateA::Rlca),
+        0x17 => Instr::RotateA(bits::RotateA::Rla),
+        0x0F => Instr::RotateA(bits::RotateA::Rrca),
+        0x1F => Instr::RotateA(bits::RotateA::Rra),
+
+        // == Loads/Stack ==
+
+        // == Load8 ==
+
+        // LD r8, r8 Internal
+        0x40..=0x6F | 0x70..=0x75 | 0x77..=0x7F => Instr::Load8(loads::Load8::Internal(
+            Register8Index::from(opcode & 0b111),
+            Register8Index::from((opcode >> 3) & 0b111),
+        )),
+
+        // LD r8, n8 Immediate
+        0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E => Instr::Load8(
+            loads::Load8::Immediate(Register8Index::from((opcode >> 3) & 0b111), n8()),
+        ),
+
+        // LD [C], A
+        0xE2 => Instr::Load8(loads::Load8::CPointer(LoadDirection::Into)),
+        // LD A, [C]
+        0xF2 => Instr::Load8(loads::Load8::CPointer(LoadDirection::From)),
+
+        // LD [n8], A
+        0xE0 => Instr::Load8(loads::Load8::ImmediatePointer(n8(), LoadDirection::Into)),
+        // LD A, [n8]
+        0xF0 => Instr::Load8(loads::Load8::ImmediatePointer(n8(), LoadDirection::From)),
+
+        // LD [r16], A
+        0x02 | 0x12 => Instr::Load8(loads::Load8::InternalPointer(
+            Register16Index::from((opcode >> 4) & 0b11),
+            LoadDirection::Into,
+            None,
+        )),
+        // LD A, [r16]
+        0x0A | 0x1A => Instr::Load8(loads::Load8::InternalPointer(
+            Register16Index::from((opcode >> 4) & 0b11),
+            LoadDirection::From,
+            None,
+        )),
+
+        // LD [HL+], A
+        0x22 => Instr::Load8(loads::Load8::InternalPointer(
+            Register16Index::HL,
+            LoadDirection::Into,
+            Some(true),
+        )),
+        // LD A, [HL+]
+        0x2A => Instr::Load8(loads::Load8::InternalPointer(
+            Register16Index::HL,
+            LoadDirection::From,
+            Some(true),
+        )),
+
+        // LD [HL-], A
+        0x32 => Instr::Load8(loads::Load8::InternalPointer(
+            Register16Index::HL,
+            LoadDirection::Into,
+            Some(false),
+        )),
+        // LD A, [HL-]
+        0x3A => Instr::Load8(loads::Load8::InternalPointer(
+            Register16Index::HL,
+            LoadDirection::From,
+            Some(false),
+        )),
+
+        // LD [a16], A
+        0xEA => Instr::Load8(loads::Load8::ImmediateMemory(n16(), LoadDirection::Into)),
+        // LD A, [a16]
+        0xFA => Instr::Load8(loads::Load8::ImmediateMemory(n16(), LoadDirection::From)),
+
+        // == Load16 ==
+
+        // LD r16, n16 Immediate
+        0x01 | 0x11 | 0x21 | 0x31 => Instr::Load16(loads::Load16::Immediate(
+            Register16Index::from((opcode >> 4) & 0b11),
+            n16(),
+        )),
+
+        // LD SP, HL
+        0xF9 => Instr::Load16(loads::Load16::StackHL(None)),
+        // LD HL, SP+e8
+        0xF8 => Instr::Load16(loads::Load16::StackHL(Some(n8() as i8))),
+
+        // LD [n16], SP
+        0x08 => Instr::Load16(loads::Load16::StackToMemory(n16())),
+
+        // PUSH
+        0xC5 | 0xD5 | 0xE5 | 0xF5 => Instr::Load16(loads::Load16::Push(
+            Register16StackIndex::from((opcode >> 4) & 0b11),
+        )),
+
+        // POP
+        0xC1 | 0xD1 | 0xE1 | 0xF1 => Instr::Load16(loads::Load16::Pop(Register16StackIndex::from(
+            (opcode >> 4) & 0b11,
+        ))),
+
+        // == Prefixed ==
+        // The register is in bits 0-2 of the second byte, the bit index in bits 3-5
+        0xCB => {
+            let cb = n8();
+            let register = Register8Index::from(cb & 0b111);
+            let bit = (cb >> 3) & 0b111;
+            match cb {
                 // RLC
-                0x00..=0x07 => Box::new(bits::Rotate::LeftCarry(Register8Index::from(
-                    opcode & 0b111,
-                ))),
+                0x00..=0x07 => Instr::Rotate(bits::Rotate::Rlc(register)),
 
                 // RRC
-                0x08..=0x0E => Box::new(bits::Rotate::RightCarry(Register8Index::from(
-                    opcode & 0b111,
-                ))),
+                0x08..=0x0F => Instr::Rotate(bits::Rotate::Rrc(register)),
 
                 // RL
-                0x10..=0x17 => Box::new(bits::Rotate::Left(Register8Index::from(opcode & 0b111))),
+                0x10..=0x17 => Instr::Rotate(bits::Rotate::Rl(register)),
 
                 // RR
-                0x18..=0x1F => Box::new(bits::Rotate::Right(Register8Index::from(opcode & 0b111))),
+                0x18..=0x1F => Instr::Rotate(bits::Rotate::Rr(register)),
 
                 // SLA
-                0x20..=0x27 => Box::new(bits::Shift::Left(Register8Index::from(opcode & 0b111))),
+                0x20..=0x27 => Instr::Shift(bits::Shift::Sla(register)),
 
                 // SRA
-                0x28..=0x2F => Box::new(bits::Shift::Right(Register8Index::from(opcode & 0b111))),
+                0x28..=0x2F => Instr::Shift(bits::Shift::Sra(register)),
 
                 // Swap
-                0x30..=0x37 => Box::new(bits::Swap(Register8Index::from(opcode & 0b111))),
+                0x30..=0x37 => Instr::Swap(bits::Swap(register)),
 
                 // SRL
-                0x38..=0x3F => Box::new(bits::Shift::RightLogically(Register8Index::from(
-                    opcode & 0b111,
-                ))),
+                0x38..=0x3F => Instr::Shift(bits::Shift::Srl(register)),
 
                 // Bit
-                0x40..=0x7F => Box::new(bits::Bit::Test(
-                    (opcode & 0b111) >> 3,
-                    Register8Index::from(opcode & 0b111),
-                )),
+                0x40..=0x7F => Instr::Bit(bits::Bit::Test(bit, register)),
 
                 // Res
-                0x80..=0xBF => Box::new(bits::Bit::Reset(
-                    (opcode & 0b111) >> 3,
-                    Register8Index::from(opcode & 0b111),
-                )),
+                0x80..=0xBF => Instr::Bit(bits::Bit::Reset(bit, register)),
 
                 // Set
-                0xC0..=0xFF => Box::new(bits::Bit::Set(
-                    (opcode & 0b111) >> 3,
-                    Register8Index::from(opcode & 0b111),
-                )),
-
-                _ => panic!(
-                    "Unimplemented prefixed opcode: {:#04x}",
-                    0xCB00 | opcode as u16
-                ),
-            },
-
-            _ => panic!("Unimplemented opcode: {:#02x}", opcode),
+                0xC0..=0xFF => Instr::Bit(bits::Bit::Set(bit, register)),
+            }
         }
+
+        _ => Instr::Invalid(opcode),
     }
 }
 
 impl Iterator for dyn InstructionDecoder {
-    type Item = Box<dyn Instruction>;
+    type Item = Instr;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let opcode = self.fetch();
-        Some(self.decode(opcode))
+        Some(decode(self.fetch_instruction().as_slice()))
     }
 }
@@ -2,8 +2,8 @@ use crate::cpu::{Cpu, Flag};
 
 use super::Instruction;
 
-#[derive(Debug, Clone, Copy)]
-pub(crate) enum Condition {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
     Zero,
     NotZero,
     Carry,
@@ -34,22 +34,45 @@ impl From<u8> for Condition {
     // 0b100000 | 0b110000 | 0b101000 | 0b111000
 }
 
-pub(crate) type Conditional = Option<Condition>;
+impl Condition {
+    /// Whether the flags let the instruction go ahead
+    pub fn met(&self, cpu: &dyn Cpu) -> bool {
+        let set = cpu.test_flag((*self).into());
+        match self {
+            Self::Zero | Self::Carry => set,
+            Self::NotZero | Self::NotCarry => !set,
+        }
+    }
+}
+
+pub type Conditional = Option<Condition>;
+
+/// Whether an instruction with an optional condition goes ahead
+fn taken(cond: &Conditional, cpu: &dyn Cpu) -> bool {
+    cond.is_none_or(|cond| cond.met(cpu))
+}
+
+/// Returns where a relative jump lands: the offset counts from the
+/// instruction following the two bytes of JR
+pub fn relative_target(next_instruction: u16, offset: i8) -> u16 {
+    next_instruction.wrapping_add(offset as i16 as u16)
+}
 
-pub(crate) struct Call(pub(crate) Conditional, pub(crate) u16);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Call(pub Conditional, pub u16);
 
 impl Instruction for Call {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
-        if self.0.is_some() && !cpu.test_flag(self.0.unwrap().into()) {
+        if !taken(&self.0, cpu) {
             return 12;
         }
 
         // Push next instruction onto stack
         let pc = *cpu.registers().pc;
         let sp = *cpu.registers().sp;
-        cpu.write_u8(sp as usize - 1, (pc >> 8) as u8);
-        cpu.write_u8(sp as usize - 2, (pc & 0xff) as u8);
-        *cpu.registers_mut().sp -= 2;
+        cpu.write_u8(sp.wrapping_sub(1) as usize, (pc >> 8) as u8);
+        cpu.write_u8(sp.wrapping_sub(2) as usize, (pc & 0xff) as u8);
+        *cpu.registers_mut().sp = sp.wrapping_sub(2);
 
         // Jump to address
         *cpu.registers_mut().pc = self.1;
@@ -58,7 +81,8 @@ impl Instruction for Call {
     }
 }
 
-pub(crate) enum Jump {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Jump {
     Internal,
     Immediate(Conditional, u16),
     Relative(Conditional, i8),
@@ -73,7 +97,7 @@ impl Instruction for Jump {
                 4
             }
             Self::Immediate(cond, value) => {
-                if cond.is_some() && !cpu.test_flag(cond.unwrap().into()) {
+                if !taken(cond, cpu) {
                     return 12;
                 }
 
@@ -82,12 +106,13 @@ impl Instruction for Jump {
                 16
             }
             Self::Relative(cond, value) => {
-                if cond.is_some() && !cpu.test_flag(cond.unwrap().into()) {
+                if !taken(cond, cpu) {
                     return 8;
                 }
 
+                // PC was already moved past the operand by the fetch
                 let pc = *cpu.registers().pc;
-                *cpu.registers_mut().pc = pc.wrapping_add(*value as u16);
+                *cpu.registers_mut().pc = relative_target(pc, *value);
 
                 12
             }
@@ -95,7 +120,8 @@ impl Instruction for Jump {
     }
 }
 
-pub(crate) enum Ret {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ret {
     Internal(Conditional),
     EnableInterrupts,
 }
@@ -104,13 +130,13 @@ impl Instruction for Ret {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
         match self {
             Self::Internal(cond) => {
-                if cond.is_some() && !cpu.test_flag(cond.unwrap().into()) {
+                if !taken(cond, cpu) {
                     return 8;
                 }
 
                 let sp = *cpu.registers().sp;
                 let pc = cpu.read_u16(sp as usize);
-                *cpu.registers_mut().sp += 2;
+                *cpu.registers_mut().sp = sp.wrapping_add(2);
                 *cpu.registers_mut().pc = pc;
 
                 cond.is_some() as usize * 20 + cond.is_none() as usize * 16
@@ -120,7 +146,7 @@ impl Instruction for Ret {
 
                 let sp = *cpu.registers().sp;
                 let pc = cpu.read_u16(sp as usize);
-                *cpu.registers_mut().sp += 2;
+                *cpu.registers_mut().sp = sp.wrapping_add(2);
                 *cpu.registers_mut().pc = pc;
 
                 16
@@ -128,15 +154,16 @@ impl Instruction for Ret {
         }
     }
 }
-pub(crate) struct Rst(pub(crate) u8);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rst(pub u8);
 
 impl Instruction for Rst {
     fn execute(&self, cpu: &mut dyn Cpu) -> usize {
         let sp = *cpu.registers().sp;
         let pc = *cpu.registers().pc;
-        cpu.write_u8(sp as usize - 1, (pc >> 8) as u8);
-        cpu.write_u8(sp as usize - 2, (pc & 0xff) as u8);
-        *cpu.registers_mut().sp -= 2;
+        cpu.write_u8(sp.wrapping_sub(1) as usize, (pc >> 8) as u8);
+        cpu.write_u8(sp.wrapping_sub(2) as usize, (pc & 0xff) as u8);
+        *cpu.registers_mut().sp = sp.wrapping_sub(2);
 
         *cpu.registers_mut().pc = self.0 as u16;
 
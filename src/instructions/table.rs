@@ -0,0 +1,663 @@
+//! Static metadata of every opcode, usable by external tools (assemblers,
+//! disassemblers, analyzers) and as a self-check of the implementation.
+//!
+//! [REFERENCE](https://gbdev.io/gb-opcodes/optables/)
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How an instruction affects a flag
+pub enum FlagEffect {
+    Unchanged,
+    Set,
+    Reset,
+    /// Depends on the result
+    Affected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    /// Mnemonic with operand placeholders (`n8`, `n16`, `a8`, `a16`, `e8`)
+    pub mnemonic: &'static str,
+    /// Length in bytes, including the opcode (and the 0xCB prefix)
+    pub length: u8,
+    /// T-cycles taken, for conditional instructions when the condition is not met
+    pub cycles: u8,
+    /// T-cycles taken by conditional instructions when the condition is met
+    pub cycles_taken: Option<u8>,
+    /// Effect on the Z, N, H and C flags, in this order
+    pub flags: [FlagEffect; 4],
+}
+
+impl OpcodeInfo {
+    /// Opcodes that lock up the CPU
+    pub const INVALID: Self = Self {
+        mnemonic: "INVALID",
+        length: 1,
+        cycles: 4,
+        cycles_taken: None,
+        flags: [FlagEffect::Unchanged; 4],
+    };
+
+    pub fn is_valid(&self) -> bool {
+        *self != Self::INVALID
+    }
+
+    /// Jumps, calls, returns, HALT and STOP: PC does not just move past them
+    pub fn is_control_flow(&self) -> bool {
+        ["JR", "JP", "CALL", "RET", "RST", "HALT", "STOP"]
+            .iter()
+            .any(|prefix| self.mnemonic.starts_with(prefix))
+    }
+
+    /// Checks an executed instruction against the table, `advance` being how
+    /// far PC moved and `cycles` the T-cycles it took. PC is only checked
+    /// when not [controlling the flow](Self::is_control_flow), and invalid
+    /// opcodes are not checked.
+    pub fn check(&self, advance: u16, cycles: usize) -> Result<(), Mismatch> {
+        let length_ok = self.is_control_flow() || advance == self.length as u16;
+        let cycles_ok = cycles == self.cycles as usize
+            || self
+                .cycles_taken
+                .is_some_and(|taken| cycles == taken as usize);
+        if !self.is_valid() || (length_ok && cycles_ok) {
+            return Ok(());
+        }
+
+        Err(Mismatch {
+            mnemonic: self.mnemonic,
+            advance,
+            cycles,
+            expected: *self,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An executed instruction disagreeing with the table, returned by
+/// [`OpcodeInfo::check`]
+pub struct Mismatch {
+    pub mnemonic: &'static str,
+    /// How far PC moved
+    pub advance: u16,
+    pub cycles: usize,
+    pub expected: OpcodeInfo,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} moved PC by {} and took {} cycles, the table says {} bytes and {} cycles",
+            self.mnemonic, self.advance, self.cycles, self.expected.length, self.expected.cycles
+        )?;
+        if let Some(taken) = self.expected.cycles_taken {
+            write!(f, " ({} taken)", taken)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the metadata of an opcode, looking at the second byte for prefixed ones
+pub fn opcode_info(opcode: u8, next: u8) -> &'static OpcodeInfo {
+    if opcode == 0xCB {
+        &CB_OPCODE_TABLE[next as usize]
+    } else {
+        &OPCODE_TABLE[opcode as usize]
+    }
+}
+
+/// Flags are written as in the usual opcode tables: `Z`, `N`, `H`, `C` when
+/// affected, `0`/`1` when reset/set and `-` when unchanged.
+const fn op(
+    mnemonic: &'static str,
+    length: u8,
+    cycles: u8,
+    cycles_taken: u8,
+    flags: &'static str,
+) -> OpcodeInfo {
+    let bytes = flags.as_bytes();
+    let mut effects = [FlagEffect::Unchanged; 4];
+    let mut i = 0;
+    while i < 4 {
+        effects[i] = match bytes[i] {
+            b'-' => FlagEffect::Unchanged,
+            b'0' => FlagEffect::Reset,
+            b'1' => FlagEffect::Set,
+            _ => FlagEffect::Affected,
+        };
+        i += 1;
+    }
+
+    OpcodeInfo {
+        mnemonic,
+        length,
+        cycles,
+        cycles_taken: if cycles_taken == 0 {
+            None
+        } else {
+            Some(cycles_taken)
+        },
+        flags: effects,
+    }
+}
+
+#[rustfmt::skip]
+pub static OPCODE_TABLE: [OpcodeInfo; 256] = [
+    /* 0x00 */ op("NOP", 1, 4, 0, "----"),
+    /* 0x01 */ op("LD BC,n16", 3, 12, 0, "----"),
+    /* 0x02 */ op("LD [BC],A", 1, 8, 0, "----"),
+    /* 0x03 */ op("INC BC", 1, 8, 0, "----"),
+    /* 0x04 */ op("INC B", 1, 4, 0, "Z0H-"),
+    /* 0x05 */ op("DEC B", 1, 4, 0, "Z1H-"),
+    /* 0x06 */ op("LD B,n8", 2, 8, 0, "----"),
+    /* 0x07 */ op("RLCA", 1, 4, 0, "000C"),
+    /* 0x08 */ op("LD [a16],SP", 3, 20, 0, "----"),
+    /* 0x09 */ op("ADD HL,BC", 1, 8, 0, "-0HC"),
+    /* 0x0a */ op("LD A,[BC]", 1, 8, 0, "----"),
+    /* 0x0b */ op("DEC BC", 1, 8, 0, "----"),
+    /* 0x0c */ op("INC C", 1, 4, 0, "Z0H-"),
+    /* 0x0d */ op("DEC C", 1, 4, 0, "Z1H-"),
+    /* 0x0e */ op("LD C,n8", 2, 8, 0, "----"),
+    /* 0x0f */ op("RRCA", 1, 4, 0, "000C"),
+    /* 0x10 */ op("STOP n8", 2, 4, 0, "----"),
+    /* 0x11 */ op("LD DE,n16", 3, 12, 0, "----"),
+    /* 0x12 */ op("LD [DE],A", 1, 8, 0, "----"),
+    /* 0x13 */ op("INC DE", 1, 8, 0, "----"),
+    /* 0x14 */ op("INC D", 1, 4, 0, "Z0H-"),
+    /* 0x15 */ op("DEC D", 1, 4, 0, "Z1H-"),
+    /* 0x16 */ op("LD D,n8", 2, 8, 0, "----"),
+    /* 0x17 */ op("RLA", 1, 4, 0, "000C"),
+    /* 0x18 */ op("JR e8", 2, 12, 0, "----"),
+    /* 0x19 */ op("ADD HL,DE", 1, 8, 0, "-0HC"),
+    /* 0x1a */ op("LD A,[DE]", 1, 8, 0, "----"),
+    /* 0x1b */ op("DEC DE", 1, 8, 0, "----"),
+    /* 0x1c */ op("INC E", 1, 4, 0, "Z0H-"),
+    /* 0x1d */ op("DEC E", 1, 4, 0, "Z1H-"),
+    /* 0x1e */ op("LD E,n8", 2, 8, 0, "----"),
+    /* 0x1f */ op("RRA", 1, 4, 0, "000C"),
+    /* 0x20 */ op("JR NZ,e8", 2, 8, 12, "----"),
+    /* 0x21 */ op("LD HL,n16", 3, 12, 0, "----"),
+    /* 0x22 */ op("LD [HL+],A", 1, 8, 0, "----"),
+    /* 0x23 */ op("INC HL", 1, 8, 0, "----"),
+    /* 0x24 */ op("INC H", 1, 4, 0, "Z0H-"),
+    /* 0x25 */ op("DEC H", 1, 4, 0, "Z1H-"),
+    /* 0x26 */ op("LD H,n8", 2, 8, 0, "----"),
+    /* 0x27 */ op("DAA", 1, 4, 0, "Z-0C"),
+    /* 0x28 */ op("JR Z,e8", 2, 8, 12, "----"),
+    /* 0x29 */ op("ADD HL,HL", 1, 8, 0, "-0HC"),
+    /* 0x2a */ op("LD A,[HL+]", 1, 8, 0, "----"),
+    /* 0x2b */ op("DEC HL", 1, 8, 0, "----"),
+    /* 0x2c */ op("INC L", 1, 4, 0, "Z0H-"),
+    /* 0x2d */ op("DEC L", 1, 4, 0, "Z1H-"),
+    /* 0x2e */ op("LD L,n8", 2, 8, 0, "----"),
+    /* 0x2f */ op("CPL", 1, 4, 0, "-11-"),
+    /* 0x30 */ op("JR NC,e8", 2, 8, 12, "----"),
+    /* 0x31 */ op("LD SP,n16", 3, 12, 0, "----"),
+    /* 0x32 */ op("LD [HL-],A", 1, 8, 0, "----"),
+    /* 0x33 */ op("INC SP", 1, 8, 0, "----"),
+    /* 0x34 */ op("INC [HL]", 1, 12, 0, "Z0H-"),
+    /* 0x35 */ op("DEC [HL]", 1, 12, 0, "Z1H-"),
+    /* 0x36 */ op("LD [HL],n8", 2, 12, 0, "----"),
+    /* 0x37 */ op("SCF", 1, 4, 0, "-001"),
+    /* 0x38 */ op("JR C,e8", 2, 8, 12, "----"),
+    /* 0x39 */ op("ADD HL,SP", 1, 8, 0, "-0HC"),
+    /* 0x3a */ op("LD A,[HL-]", 1, 8, 0, "----"),
+    /* 0x3b */ op("DEC SP", 1, 8, 0, "----"),
+    /* 0x3c */ op("INC A", 1, 4, 0, "Z0H-"),
+    /* 0x3d */ op("DEC A", 1, 4, 0, "Z1H-"),
+    /* 0x3e */ op("LD A,n8", 2, 8, 0, "----"),
+    /* 0x3f */ op("CCF", 1, 4, 0, "-00C"),
+    /* 0x40 */ op("LD B,B", 1, 4, 0, "----"),
+    /* 0x41 */ op("LD B,C", 1, 4, 0, "----"),
+    /* 0x42 */ op("LD B,D", 1, 4, 0, "----"),
+    /* 0x43 */ op("LD B,E", 1, 4, 0, "----"),
+    /* 0x44 */ op("LD B,H", 1, 4, 0, "----"),
+    /* 0x45 */ op("LD B,L", 1, 4, 0, "----"),
+    /* 0x46 */ op("LD B,[HL]", 1, 8, 0, "----"),
+    /* 0x47 */ op("LD B,A", 1, 4, 0, "----"),
+    /* 0x48 */ op("LD C,B", 1, 4, 0, "----"),
+    /* 0x49 */ op("LD C,C", 1, 4, 0, "----"),
+    /* 0x4a */ op("LD C,D", 1, 4, 0, "----"),
+    /* 0x4b */ op("LD C,E", 1, 4, 0, "----"),
+    /* 0x4c */ op("LD C,H", 1, 4, 0, "----"),
+    /* 0x4d */ op("LD C,L", 1, 4, 0, "----"),
+    /* 0x4e */ op("LD C,[HL]", 1, 8, 0, "----"),
+    /* 0x4f */ op("LD C,A", 1, 4, 0, "----"),
+    /* 0x50 */ op("LD D,B", 1, 4, 0, "----"),
+    /* 0x51 */ op("LD D,C", 1, 4, 0, "----"),
+    /* 0x52 */ op("LD D,D", 1, 4, 0, "----"),
+    /* 0x53 */ op("LD D,E", 1, 4, 0, "----"),
+    /* 0x54 */ op("LD D,H", 1, 4, 0, "----"),
+    /* 0x55 */ op("LD D,L", 1, 4, 0, "----"),
+    /* 0x56 */ op("LD D,[HL]", 1, 8, 0, "----"),
+    /* 0x57 */ op("LD D,A", 1, 4, 0, "----"),
+    /* 0x58 */ op("LD E,B", 1, 4, 0, "----"),
+    /* 0x59 */ op("LD E,C", 1, 4, 0, "----"),
+    /* 0x5a */ op("LD E,D", 1, 4, 0, "----"),
+    /* 0x5b */ op("LD E,E", 1, 4, 0, "----"),
+    /* 0x5c */ op("LD E,H", 1, 4, 0, "----"),
+    /* 0x5d */ op("LD E,L", 1, 4, 0, "----"),
+    /* 0x5e */ op("LD E,[HL]", 1, 8, 0, "----"),
+    /* 0x5f */ op("LD E,A", 1, 4, 0, "----"),
+    /* 0x60 */ op("LD H,B", 1, 4, 0, "----"),
+    /* 0x61 */ op("LD H,C", 1, 4, 0, "----"),
+    /* 0x62 */ op("LD H,D", 1, 4, 0, "----"),
+    /* 0x63 */ op("LD H,E", 1, 4, 0, "----"),
+    /* 0x64 */ op("LD H,H", 1, 4, 0, "----"),
+    /* 0x65 */ op("LD H,L", 1, 4, 0, "----"),
+    /* 0x66 */ op("LD H,[HL]", 1, 8, 0, "----"),
+    /* 0x67 */ op("LD H,A", 1, 4, 0, "----"),
+    /* 0x68 */ op("LD L,B", 1, 4, 0, "----"),
+    /* 0x69 */ op("LD L,C", 1, 4, 0, "----"),
+    /* 0x6a */ op("LD L,D", 1, 4, 0, "----"),
+    /* 0x6b */ op("LD L,E", 1, 4, 0, "----"),
+    /* 0x6c */ op("LD L,H", 1, 4, 0, "----"),
+    /* 0x6d */ op("LD L,L", 1, 4, 0, "----"),
+    /* 0x6e */ op("LD L,[HL]", 1, 8, 0, "----"),
+    /* 0x6f */ op("LD L,A", 1, 4, 0, "----"),
+    /* 0x70 */ op("LD [HL],B", 1, 8, 0, "----"),
+    /* 0x71 */ op("LD [HL],C", 1, 8, 0, "----"),
+    /* 0x72 */ op("LD [HL],D", 1, 8, 0, "----"),
+    /* 0x73 */ op("LD [HL],E", 1, 8, 0, "----"),
+    /* 0x74 */ op("LD [HL],H", 1, 8, 0, "----"),
+    /* 0x75 */ op("LD [HL],L", 1, 8, 0, "----"),
+    /* 0x76 */ op("HALT", 1, 4, 0, "----"),
+    /* 0x77 */ op("LD [HL],A", 1, 8, 0, "----"),
+    /* 0x78 */ op("LD A,B", 1, 4, 0, "----"),
+    /* 0x79 */ op("LD A,C", 1, 4, 0, "----"),
+    /* 0x7a */ op("LD A,D", 1, 4, 0, "----"),
+    /* 0x7b */ op("LD A,E", 1, 4, 0, "----"),
+    /* 0x7c */ op("LD A,H", 1, 4, 0, "----"),
+    /* 0x7d */ op("LD A,L", 1, 4, 0, "----"),
+    /* 0x7e */ op("LD A,[HL]", 1, 8, 0, "----"),
+    /* 0x7f */ op("LD A,A", 1, 4, 0, "----"),
+    /* 0x80 */ op("ADD A,B", 1, 4, 0, "Z0HC"),
+    /* 0x81 */ op("ADD A,C", 1, 4, 0, "Z0HC"),
+    /* 0x82 */ op("ADD A,D", 1, 4, 0, "Z0HC"),
+    /* 0x83 */ op("ADD A,E", 1, 4, 0, "Z0HC"),
+    /* 0x84 */ op("ADD A,H", 1, 4, 0, "Z0HC"),
+    /* 0x85 */ op("ADD A,L", 1, 4, 0, "Z0HC"),
+    /* 0x86 */ op("ADD A,[HL]", 1, 8, 0, "Z0HC"),
+    /* 0x87 */ op("ADD A,A", 1, 4, 0, "Z0HC"),
+    /* 0x88 */ op("ADC A,B", 1, 4, 0, "Z0HC"),
+    /* 0x89 */ op("ADC A,C", 1, 4, 0, "Z0HC"),
+    /* 0x8a */ op("ADC A,D", 1, 4, 0, "Z0HC"),
+    /* 0x8b */ op("ADC A,E", 1, 4, 0, "Z0HC"),
+    /* 0x8c */ op("ADC A,H", 1, 4, 0, "Z0HC"),
+    /* 0x8d */ op("ADC A,L", 1, 4, 0, "Z0HC"),
+    /* 0x8e */ op("ADC A,[HL]", 1, 8, 0, "Z0HC"),
+    /* 0x8f */ op("ADC A,A", 1, 4, 0, "Z0HC"),
+    /* 0x90 */ op("SUB B", 1, 4, 0, "Z1HC"),
+    /* 0x91 */ op("SUB C", 1, 4, 0, "Z1HC"),
+    /* 0x92 */ op("SUB D", 1, 4, 0, "Z1HC"),
+    /* 0x93 */ op("SUB E", 1, 4, 0, "Z1HC"),
+    /* 0x94 */ op("SUB H", 1, 4, 0, "Z1HC"),
+    /* 0x95 */ op("SUB L", 1, 4, 0, "Z1HC"),
+    /* 0x96 */ op("SUB [HL]", 1, 8, 0, "Z1HC"),
+    /* 0x97 */ op("SUB A", 1, 4, 0, "Z1HC"),
+    /* 0x98 */ op("SBC A,B", 1, 4, 0, "Z1HC"),
+    /* 0x99 */ op("SBC A,C", 1, 4, 0, "Z1HC"),
+    /* 0x9a */ op("SBC A,D", 1, 4, 0, "Z1HC"),
+    /* 0x9b */ op("SBC A,E", 1, 4, 0, "Z1HC"),
+    /* 0x9c */ op("SBC A,H", 1, 4, 0, "Z1HC"),
+    /* 0x9d */ op("SBC A,L", 1, 4, 0, "Z1HC"),
+    /* 0x9e */ op("SBC A,[HL]", 1, 8, 0, "Z1HC"),
+    /* 0x9f */ op("SBC A,A", 1, 4, 0, "Z1HC"),
+    /* 0xa0 */ op("AND B", 1, 4, 0, "Z010"),
+    /* 0xa1 */ op("AND C", 1, 4, 0, "Z010"),
+    /* 0xa2 */ op("AND D", 1, 4, 0, "Z010"),
+    /* 0xa3 */ op("AND E", 1, 4, 0, "Z010"),
+    /* 0xa4 */ op("AND H", 1, 4, 0, "Z010"),
+    /* 0xa5 */ op("AND L", 1, 4, 0, "Z010"),
+    /* 0xa6 */ op("AND [HL]", 1, 8, 0, "Z010"),
+    /* 0xa7 */ op("AND A", 1, 4, 0, "Z010"),
+    /* 0xa8 */ op("XOR B", 1, 4, 0, "Z000"),
+    /* 0xa9 */ op("XOR C", 1, 4, 0, "Z000"),
+    /* 0xaa */ op("XOR D", 1, 4, 0, "Z000"),
+    /* 0xab */ op("XOR E", 1, 4, 0, "Z000"),
+    /* 0xac */ op("XOR H", 1, 4, 0, "Z000"),
+    /* 0xad */ op("XOR L", 1, 4, 0, "Z000"),
+    /* 0xae */ op("XOR [HL]", 1, 8, 0, "Z000"),
+    /* 0xaf */ op("XOR A", 1, 4, 0, "Z000"),
+    /* 0xb0 */ op("OR B", 1, 4, 0, "Z000"),
+    /* 0xb1 */ op("OR C", 1, 4, 0, "Z000"),
+    /* 0xb2 */ op("OR D", 1, 4, 0, "Z000"),
+    /* 0xb3 */ op("OR E", 1, 4, 0, "Z000"),
+    /* 0xb4 */ op("OR H", 1, 4, 0, "Z000"),
+    /* 0xb5 */ op("OR L", 1, 4, 0, "Z000"),
+    /* 0xb6 */ op("OR [HL]", 1, 8, 0, "Z000"),
+    /* 0xb7 */ op("OR A", 1, 4, 0, "Z000"),
+    /* 0xb8 */ op("CP B", 1, 4, 0, "Z1HC"),
+    /* 0xb9 */ op("CP C", 1, 4, 0, "Z1HC"),
+    /* 0xba */ op("CP D", 1, 4, 0, "Z1HC"),
+    /* 0xbb */ op("CP E", 1, 4, 0, "Z1HC"),
+    /* 0xbc */ op("CP H", 1, 4, 0, "Z1HC"),
+    /* 0xbd */ op("CP L", 1, 4, 0, "Z1HC"),
+    /* 0xbe */ op("CP [HL]", 1, 8, 0, "Z1HC"),
+    /* 0xbf */ op("CP A", 1, 4, 0, "Z1HC"),
+    /* 0xc0 */ op("RET NZ", 1, 8, 20, "----"),
+    /* 0xc1 */ op("POP BC", 1, 12, 0, "----"),
+    /* 0xc2 */ op("JP NZ,a16", 3, 12, 16, "----"),
+    /* 0xc3 */ op("JP a16", 3, 16, 0, "----"),
+    /* 0xc4 */ op("CALL NZ,a16", 3, 12, 24, "----"),
+    /* 0xc5 */ op("PUSH BC", 1, 16, 0, "----"),
+    /* 0xc6 */ op("ADD A,n8", 2, 8, 0, "Z0HC"),
+    /* 0xc7 */ op("RST $00", 1, 16, 0, "----"),
+    /* 0xc8 */ op("RET Z", 1, 8, 20, "----"),
+    /* 0xc9 */ op("RET", 1, 16, 0, "----"),
+    /* 0xca */ op("JP Z,a16", 3, 12, 16, "----"),
+    /* 0xcb */ op("PREFIX", 1, 4, 0, "----"),
+    /* 0xcc */ op("CALL Z,a16", 3, 12, 24, "----"),
+    /* 0xcd */ op("CALL a16", 3, 24, 0, "----"),
+    /* 0xce */ op("ADC A,n8", 2, 8, 0, "Z0HC"),
+    /* 0xcf */ op("RST $08", 1, 16, 0, "----"),
+    /* 0xd0 */ op("RET NC", 1, 8, 20, "----"),
+    /* 0xd1 */ op("POP DE", 1, 12, 0, "----"),
+    /* 0xd2 */ op("JP NC,a16", 3, 12, 16, "----"),
+    /* 0xd3 */ OpcodeInfo::INVALID,
+    /* 0xd4 */ op("CALL NC,a16", 3, 12, 24, "----"),
+    /* 0xd5 */ op("PUSH DE", 1, 16, 0, "----"),
+    /* 0xd6 */ op("SUB n8", 2, 8, 0, "Z1HC"),
+    /* 0xd7 */ op("RST $10", 1, 16, 0, "----"),
+    /* 0xd8 */ op("RET C", 1, 8, 20, "----"),
+    /* 0xd9 */ op("RETI", 1, 16, 0, "----"),
+    /* 0xda */ op("JP C,a16", 3, 12, 16, "----"),
+    /* 0xdb */ OpcodeInfo::INVALID,
+    /* 0xdc */ op("CALL C,a16", 3, 12, 24, "----"),
+    /* 0xdd */ OpcodeInfo::INVALID,
+    /* 0xde */ op("SBC A,n8", 2, 8, 0, "Z1HC"),
+    /* 0xdf */ op("RST $18", 1, 16, 0, "----"),
+    /* 0xe0 */ op("LDH [a8],A", 2, 12, 0, "----"),
+    /* 0xe1 */ op("POP HL", 1, 12, 0, "----"),
+    /* 0xe2 */ op("LDH [C],A", 1, 8, 0, "----"),
+    /* 0xe3 */ OpcodeInfo::INVALID,
+    /* 0xe4 */ OpcodeInfo::INVALID,
+    /* 0xe5 */ op("PUSH HL", 1, 16, 0, "----"),
+    /* 0xe6 */ op("AND n8", 2, 8, 0, "Z010"),
+    /* 0xe7 */ op("RST $20", 1, 16, 0, "----"),
+    /* 0xe8 */ op("ADD SP,e8", 2, 16, 0, "00HC"),
+    /* 0xe9 */ op("JP HL", 1, 4, 0, "----"),
+    /* 0xea */ op("LD [a16],A", 3, 16, 0, "----"),
+    /* 0xeb */ OpcodeInfo::INVALID,
+    /* 0xec */ OpcodeInfo::INVALID,
+    /* 0xed */ OpcodeInfo::INVALID,
+    /* 0xee */ op("XOR n8", 2, 8, 0, "Z000"),
+    /* 0xef */ op("RST $28", 1, 16, 0, "----"),
+    /* 0xf0 */ op("LDH A,[a8]", 2, 12, 0, "----"),
+    /* 0xf1 */ op("POP AF", 1, 12, 0, "ZNHC"),
+    /* 0xf2 */ op("LDH A,[C]", 1, 8, 0, "----"),
+    /* 0xf3 */ op("DI", 1, 4, 0, "----"),
+    /* 0xf4 */ OpcodeInfo::INVALID,
+    /* 0xf5 */ op("PUSH AF", 1, 16, 0, "----"),
+    /* 0xf6 */ op("OR n8", 2, 8, 0, "Z000"),
+    /* 0xf7 */ op("RST $30", 1, 16, 0, "----"),
+    /* 0xf8 */ op("LD HL,SP+e8", 2, 12, 0, "00HC"),
+    /* 0xf9 */ op("LD SP,HL", 1, 8, 0, "----"),
+    /* 0xfa */ op("LD A,[a16]", 3, 16, 0, "----"),
+    /* 0xfb */ op("EI", 1, 4, 0, "----"),
+    /* 0xfc */ OpcodeInfo::INVALID,
+    /* 0xfd */ OpcodeInfo::INVALID,
+    /* 0xfe */ op("CP n8", 2, 8, 0, "Z1HC"),
+    /* 0xff */ op("RST $38", 1, 16, 0, "----"),
+];
+
+/// Opcodes following the 0xCB prefix, the cycles include the prefix fetch.
+/// Operating on `[HL]` takes 16 cycles, 12 for BIT which does not write back.
+#[rustfmt::skip]
+pub static CB_OPCODE_TABLE: [OpcodeInfo; 256] = [
+    /* 0x00 */ op("RLC B", 2, 8, 0, "Z00C"),
+    /* 0x01 */ op("RLC C", 2, 8, 0, "Z00C"),
+    /* 0x02 */ op("RLC D", 2, 8, 0, "Z00C"),
+    /* 0x03 */ op("RLC E", 2, 8, 0, "Z00C"),
+    /* 0x04 */ op("RLC H", 2, 8, 0, "Z00C"),
+    /* 0x05 */ op("RLC L", 2, 8, 0, "Z00C"),
+    /* 0x06 */ op("RLC [HL]", 2, 16, 0, "Z00C"),
+    /* 0x07 */ op("RLC A", 2, 8, 0, "Z00C"),
+    /* 0x08 */ op("RRC B", 2, 8, 0, "Z00C"),
+    /* 0x09 */ op("RRC C", 2, 8, 0, "Z00C"),
+    /* 0x0a */ op("RRC D", 2, 8, 0, "Z00C"),
+    /* 0x0b */ op("RRC E", 2, 8, 0, "Z00C"),
+    /* 0x0c */ op("RRC H", 2, 8, 0, "Z00C"),
+    /* 0x0d */ op("RRC L", 2, 8, 0, "Z00C"),
+    /* 0x0e */ op("RRC [HL]", 2, 16, 0, "Z00C"),
+    /* 0x0f */ op("RRC A", 2, 8, 0, "Z00C"),
+    /* 0x10 */ op("RL B", 2, 8, 0, "Z00C"),
+    /* 0x11 */ op("RL C", 2, 8, 0, "Z00C"),
+    /* 0x12 */ op("RL D", 2, 8, 0, "Z00C"),
+    /* 0x13 */ op("RL E", 2, 8, 0, "Z00C"),
+    /* 0x14 */ op("RL H", 2, 8, 0, "Z00C"),
+    /* 0x15 */ op("RL L", 2, 8, 0, "Z00C"),
+    /* 0x16 */ op("RL [HL]", 2, 16, 0, "Z00C"),
+    /* 0x17 */ op("RL A", 2, 8, 0, "Z00C"),
+    /* 0x18 */ op("RR B", 2, 8, 0, "Z00C"),
+    /* 0x19 */ op("RR C", 2, 8, 0, "Z00C"),
+    /* 0x1a */ op("RR D", 2, 8, 0, "Z00C"),
+    /* 0x1b */ op("RR E", 2, 8, 0, "Z00C"),
+    /* 0x1c */ op("RR H", 2, 8, 0, "Z00C"),
+    /* 0x1d */ op("RR L", 2, 8, 0, "Z00C"),
+    /* 0x1e */ op("RR [HL]", 2, 16, 0, "Z00C"),
+    /* 0x1f */ op("RR A", 2, 8, 0, "Z00C"),
+    /* 0x20 */ op("SLA B", 2, 8, 0, "Z00C"),
+    /* 0x21 */ op("SLA C", 2, 8, 0, "Z00C"),
+    /* 0x22 */ op("SLA D", 2, 8, 0, "Z00C"),
+    /* 0x23 */ op("SLA E", 2, 8, 0, "Z00C"),
+    /* 0x24 */ op("SLA H", 2, 8, 0, "Z00C"),
+    /* 0x25 */ op("SLA L", 2, 8, 0, "Z00C"),
+    /* 0x26 */ op("SLA [HL]", 2, 16, 0, "Z00C"),
+    /* 0x27 */ op("SLA A", 2, 8, 0, "Z00C"),
+    /* 0x28 */ op("SRA B", 2, 8, 0, "Z00C"),
+    /* 0x29 */ op("SRA C", 2, 8, 0, "Z00C"),
+    /* 0x2a */ op("SRA D", 2, 8, 0, "Z00C"),
+    /* 0x2b */ op("SRA E", 2, 8, 0, "Z00C"),
+    /* 0x2c */ op("SRA H", 2, 8, 0, "Z00C"),
+    /* 0x2d */ op("SRA L", 2, 8, 0, "Z00C"),
+    /* 0x2e */ op("SRA [HL]", 2, 16, 0, "Z00C"),
+    /* 0x2f */ op("SRA A", 2, 8, 0, "Z00C"),
+    /* 0x30 */ op("SWAP B", 2, 8, 0, "Z000"),
+    /* 0x31 */ op("SWAP C", 2, 8, 0, "Z000"),
+    /* 0x32 */ op("SWAP D", 2, 8, 0, "Z000"),
+    /* 0x33 */ op("SWAP E", 2, 8, 0, "Z000"),
+    /* 0x34 */ op("SWAP H", 2, 8, 0, "Z000"),
+    /* 0x35 */ op("SWAP L", 2, 8, 0, "Z000"),
+    /* 0x36 */ op("SWAP [HL]", 2, 16, 0, "Z000"),
+    /* 0x37 */ op("SWAP A", 2, 8, 0, "Z000"),
+    /* 0x38 */ op("SRL B", 2, 8, 0, "Z00C"),
+    /* 0x39 */ op("SRL C", 2, 8, 0, "Z00C"),
+    /* 0x3a */ op("SRL D", 2, 8, 0, "Z00C"),
+    /* 0x3b */ op("SRL E", 2, 8, 0, "Z00C"),
+    /* 0x3c */ op("SRL H", 2, 8, 0, "Z00C"),
+    /* 0x3d */ op("SRL L", 2, 8, 0, "Z00C"),
+    /* 0x3e */ op("SRL [HL]", 2, 16, 0, "Z00C"),
+    /* 0x3f */ op("SRL A", 2, 8, 0, "Z00C"),
+    /* 0x40 */ op("BIT 0,B", 2, 8, 0, "Z01-"),
+    /* 0x41 */ op("BIT 0,C", 2, 8, 0, "Z01-"),
+    /* 0x42 */ op("BIT 0,D", 2, 8, 0, "Z01-"),
+    /* 0x43 */ op("BIT 0,E", 2, 8, 0, "Z01-"),
+    /* 0x44 */ op("BIT 0,H", 2, 8, 0, "Z01-"),
+    /* 0x45 */ op("BIT 0,L", 2, 8, 0, "Z01-"),
+    /* 0x46 */ op("BIT 0,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x47 */ op("BIT 0,A", 2, 8, 0, "Z01-"),
+    /* 0x48 */ op("BIT 1,B", 2, 8, 0, "Z01-"),
+    /* 0x49 */ op("BIT 1,C", 2, 8, 0, "Z01-"),
+    /* 0x4a */ op("BIT 1,D", 2, 8, 0, "Z01-"),
+    /* 0x4b */ op("BIT 1,E", 2, 8, 0, "Z01-"),
+    /* 0x4c */ op("BIT 1,H", 2, 8, 0, "Z01-"),
+    /* 0x4d */ op("BIT 1,L", 2, 8, 0, "Z01-"),
+    /* 0x4e */ op("BIT 1,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x4f */ op("BIT 1,A", 2, 8, 0, "Z01-"),
+    /* 0x50 */ op("BIT 2,B", 2, 8, 0, "Z01-"),
+    /* 0x51 */ op("BIT 2,C", 2, 8, 0, "Z01-"),
+    /* 0x52 */ op("BIT 2,D", 2, 8, 0, "Z01-"),
+    /* 0x53 */ op("BIT 2,E", 2, 8, 0, "Z01-"),
+    /* 0x54 */ op("BIT 2,H", 2, 8, 0, "Z01-"),
+    /* 0x55 */ op("BIT 2,L", 2, 8, 0, "Z01-"),
+    /* 0x56 */ op("BIT 2,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x57 */ op("BIT 2,A", 2, 8, 0, "Z01-"),
+    /* 0x58 */ op("BIT 3,B", 2, 8, 0, "Z01-"),
+    /* 0x59 */ op("BIT 3,C", 2, 8, 0, "Z01-"),
+    /* 0x5a */ op("BIT 3,D", 2, 8, 0, "Z01-"),
+    /* 0x5b */ op("BIT 3,E", 2, 8, 0, "Z01-"),
+    /* 0x5c */ op("BIT 3,H", 2, 8, 0, "Z01-"),
+    /* 0x5d */ op("BIT 3,L", 2, 8, 0, "Z01-"),
+    /* 0x5e */ op("BIT 3,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x5f */ op("BIT 3,A", 2, 8, 0, "Z01-"),
+    /* 0x60 */ op("BIT 4,B", 2, 8, 0, "Z01-"),
+    /* 0x61 */ op("BIT 4,C", 2, 8, 0, "Z01-"),
+    /* 0x62 */ op("BIT 4,D", 2, 8, 0, "Z01-"),
+    /* 0x63 */ op("BIT 4,E", 2, 8, 0, "Z01-"),
+    /* 0x64 */ op("BIT 4,H", 2, 8, 0, "Z01-"),
+    /* 0x65 */ op("BIT 4,L", 2, 8, 0, "Z01-"),
+    /* 0x66 */ op("BIT 4,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x67 */ op("BIT 4,A", 2, 8, 0, "Z01-"),
+    /* 0x68 */ op("BIT 5,B", 2, 8, 0, "Z01-"),
+    /* 0x69 */ op("BIT 5,C", 2, 8, 0, "Z01-"),
+    /* 0x6a */ op("BIT 5,D", 2, 8, 0, "Z01-"),
+    /* 0x6b */ op("BIT 5,E", 2, 8, 0, "Z01-"),
+    /* 0x6c */ op("BIT 5,H", 2, 8, 0, "Z01-"),
+    /* 0x6d */ op("BIT 5,L", 2, 8, 0, "Z01-"),
+    /* 0x6e */ op("BIT 5,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x6f */ op("BIT 5,A", 2, 8, 0, "Z01-"),
+    /* 0x70 */ op("BIT 6,B", 2, 8, 0, "Z01-"),
+    /* 0x71 */ op("BIT 6,C", 2, 8, 0, "Z01-"),
+    /* 0x72 */ op("BIT 6,D", 2, 8, 0, "Z01-"),
+    /* 0x73 */ op("BIT 6,E", 2, 8, 0, "Z01-"),
+    /* 0x74 */ op("BIT 6,H", 2, 8, 0, "Z01-"),
+    /* 0x75 */ op("BIT 6,L", 2, 8, 0, "Z01-"),
+    /* 0x76 */ op("BIT 6,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x77 */ op("BIT 6,A", 2, 8, 0, "Z01-"),
+    /* 0x78 */ op("BIT 7,B", 2, 8, 0, "Z01-"),
+    /* 0x79 */ op("BIT 7,C", 2, 8, 0, "Z01-"),
+    /* 0x7a */ op("BIT 7,D", 2, 8, 0, "Z01-"),
+    /* 0x7b */ op("BIT 7,E", 2, 8, 0, "Z01-"),
+    /* 0x7c */ op("BIT 7,H", 2, 8, 0, "Z01-"),
+    /* 0x7d */ op("BIT 7,L", 2, 8, 0, "Z01-"),
+    /* 0x7e */ op("BIT 7,[HL]", 2, 12, 0, "Z01-"),
+    /* 0x7f */ op("BIT 7,A", 2, 8, 0, "Z01-"),
+    /* 0x80 */ op("RES 0,B", 2, 8, 0, "----"),
+    /* 0x81 */ op("RES 0,C", 2, 8, 0, "----"),
+    /* 0x82 */ op("RES 0,D", 2, 8, 0, "----"),
+    /* 0x83 */ op("RES 0,E", 2, 8, 0, "----"),
+    /* 0x84 */ op("RES 0,H", 2, 8, 0, "----"),
+    /* 0x85 */ op("RES 0,L", 2, 8, 0, "----"),
+    /* 0x86 */ op("RES 0,[HL]", 2, 16, 0, "----"),
+    /* 0x87 */ op("RES 0,A", 2, 8, 0, "----"),
+    /* 0x88 */ op("RES 1,B", 2, 8, 0, "----"),
+    /* 0x89 */ op("RES 1,C", 2, 8, 0, "----"),
+    /* 0x8a */ op("RES 1,D", 2, 8, 0, "----"),
+    /* 0x8b */ op("RES 1,E", 2, 8, 0, "----"),
+    /* 0x8c */ op("RES 1,H", 2, 8, 0, "----"),
+    /* 0x8d */ op("RES 1,L", 2, 8, 0, "----"),
+    /* 0x8e */ op("RES 1,[HL]", 2, 16, 0, "----"),
+    /* 0x8f */ op("RES 1,A", 2, 8, 0, "----"),
+    /* 0x90 */ op("RES 2,B", 2, 8, 0, "----"),
+    /* 0x91 */ op("RES 2,C", 2, 8, 0, "----"),
+    /* 0x92 */ op("RES 2,D", 2, 8, 0, "----"),
+    /* 0x93 */ op("RES 2,E", 2, 8, 0, "----"),
+    /* 0x94 */ op("RES 2,H", 2, 8, 0, "----"),
+    /* 0x95 */ op("RES 2,L", 2, 8, 0, "----"),
+    /* 0x96 */ op("RES 2,[HL]", 2, 16, 0, "----"),
+    /* 0x97 */ op("RES 2,A", 2, 8, 0, "----"),
+    /* 0x98 */ op("RES 3,B", 2, 8, 0, "----"),
+    /* 0x99 */ op("RES 3,C", 2, 8, 0, "----"),
+    /* 0x9a */ op("RES 3,D", 2, 8, 0, "----"),
+    /* 0x9b */ op("RES 3,E", 2, 8, 0, "----"),
+    /* 0x9c */ op("RES 3,H", 2, 8, 0, "----"),
+    /* 0x9d */ op("RES 3,L", 2, 8, 0, "----"),
+    /* 0x9e */ op("RES 3,[HL]", 2, 16, 0, "----"),
+    /* 0x9f */ op("RES 3,A", 2, 8, 0, "----"),
+    /* 0xa0 */ op("RES 4,B", 2, 8, 0, "----"),
+    /* 0xa1 */ op("RES 4,C", 2, 8, 0, "----"),
+    /* 0xa2 */ op("RES 4,D", 2, 8, 0, "----"),
+    /* 0xa3 */ op("RES 4,E", 2, 8, 0, "----"),
+    /* 0xa4 */ op("RES 4,H", 2, 8, 0, "----"),
+    /* 0xa5 */ op("RES 4,L", 2, 8, 0, "----"),
+    /* 0xa6 */ op("RES 4,[HL]", 2, 16, 0, "----"),
+    /* 0xa7 */ op("RES 4,A", 2, 8, 0, "----"),
+    /* 0xa8 */ op("RES 5,B", 2, 8, 0, "----"),
+    /* 0xa9 */ op("RES 5,C", 2, 8, 0, "----"),
+    /* 0xaa */ op("RES 5,D", 2, 8, 0, "----"),
+    /* 0xab */ op("RES 5,E", 2, 8, 0, "----"),
+    /* 0xac */ op("RES 5,H", 2, 8, 0, "----"),
+    /* 0xad */ op("RES 5,L", 2, 8, 0, "----"),
+    /* 0xae */ op("RES 5,[HL]", 2, 16, 0, "----"),
+    /* 0xaf */ op("RES 5,A", 2, 8, 0, "----"),
+    /* 0xb0 */ op("RES 6,B", 2, 8, 0, "----"),
+    /* 0xb1 */ op("RES 6,C", 2, 8, 0, "----"),
+    /* 0xb2 */ op("RES 6,D", 2, 8, 0, "----"),
+    /* 0xb3 */ op("RES 6,E", 2, 8, 0, "----"),
+    /* 0xb4 */ op("RES 6,H", 2, 8, 0, "----"),
+    /* 0xb5 */ op("RES 6,L", 2, 8, 0, "----"),
+    /* 0xb6 */ op("RES 6,[HL]", 2, 16, 0, "----"),
+    /* 0xb7 */ op("RES 6,A", 2, 8, 0, "----"),
+    /* 0xb8 */ op("RES 7,B", 2, 8, 0, "----"),
+    /* 0xb9 */ op("RES 7,C", 2, 8, 0, "----"),
+    /* 0xba */ op("RES 7,D", 2, 8, 0, "----"),
+    /* 0xbb */ op("RES 7,E", 2, 8, 0, "----"),
+    /* 0xbc */ op("RES 7,H", 2, 8, 0, "----"),
+    /* 0xbd */ op("RES 7,L", 2, 8, 0, "----"),
+    /* 0xbe */ op("RES 7,[HL]", 2, 16, 0, "----"),
+    /* 0xbf */ op("RES 7,A", 2, 8, 0, "----"),
+    /* 0xc0 */ op("SET 0,B", 2, 8, 0, "----"),
+    /* 0xc1 */ op("SET 0,C", 2, 8, 0, "----"),
+    /* 0xc2 */ op("SET 0,D", 2, 8, 0, "----"),
+    /* 0xc3 */ op("SET 0,E", 2, 8, 0, "----"),
+    /* 0xc4 */ op("SET 0,H", 2, 8, 0, "----"),
+    /* 0xc5 */ op("SET 0,L", 2, 8, 0, "----"),
+    /* 0xc6 */ op("SET 0,[HL]", 2, 16, 0, "----"),
+    /* 0xc7 */ op("SET 0,A", 2, 8, 0, "----"),
+    /* 0xc8 */ op("SET 1,B", 2, 8, 0, "----"),
+    /* 0xc9 */ op("SET 1,C", 2, 8, 0, "----"),
+    /* 0xca */ op("SET 1,D", 2, 8, 0, "----"),
+    /* 0xcb */ op("SET 1,E", 2, 8, 0, "----"),
+    /* 0xcc */ op("SET 1,H", 2, 8, 0, "----"),
+    /* 0xcd */ op("SET 1,L", 2, 8, 0, "----"),
+    /* 0xce */ op("SET 1,[HL]", 2, 16, 0, "----"),
+    /* 0xcf */ op("SET 1,A", 2, 8, 0, "----"),
+    /* 0xd0 */ op("SET 2,B", 2, 8, 0, "----"),
+    /* 0xd1 */ op("SET 2,C", 2, 8, 0, "----"),
+    /* 0xd2 */ op("SET 2,D", 2, 8, 0, "----"),
+    /* 0xd3 */ op("SET 2,E", 2, 8, 0, "----"),
+    /* 0xd4 */ op("SET 2,H", 2, 8, 0, "----"),
+    /* 0xd5 */ op("SET 2,L", 2, 8, 0, "----"),
+    /* 0xd6 */ op("SET 2,[HL]", 2, 16, 0, "----"),
+    /* 0xd7 */ op("SET 2,A", 2, 8, 0, "----"),
+    /* 0xd8 */ op("SET 3,B", 2, 8, 0, "----"),
+    /* 0xd9 */ op("SET 3,C", 2, 8, 0, "----"),
+    /* 0xda */ op("SET 3,D", 2, 8, 0, "----"),
+    /* 0xdb */ op("SET 3,E", 2, 8, 0, "----"),
+    /* 0xdc */ op("SET 3,H", 2, 8, 0, "----"),
+    /* 0xdd */ op("SET 3,L", 2, 8, 0, "----"),
+    /* 0xde */ op("SET 3,[HL]", 2, 16, 0, "----"),
+    /* 0xdf */ op("SET 3,A", 2, 8, 0, "----"),
+    /* 0xe0 */ op("SET 4,B", 2, 8, 0, "----"),
+    /* 0xe1 */ op("SET 4,C", 2, 8, 0, "----"),
+    /* 0xe2 */ op("SET 4,D", 2, 8, 0, "----"),
+    /* 0xe3 */ op("SET 4,E", 2, 8, 0, "----"),
+    /* 0xe4 */ op("SET 4,H", 2, 8, 0, "----"),
+    /* 0xe5 */ op("SET 4,L", 2, 8, 0, "----"),
+    /* 0xe6 */ op("SET 4,[HL]", 2, 16, 0, "----"),
+    /* 0xe7 */ op("SET 4,A", 2, 8, 0, "----"),
+    /* 0xe8 */ op("SET 5,B", 2, 8, 0, "----"),
+    /* 0xe9 */ op("SET 5,C", 2, 8, 0, "----"),
+    /* 0xea */ op("SET 5,D", 2, 8, 0, "----"),
+    /* 0xeb */ op("SET 5,E", 2, 8, 0, "----"),
+    /* 0xec */ op("SET 5,H", 2, 8, 0, "----"),
+    /* 0xed */ op("SET 5,L", 2, 8, 0, "----"),
+    /* 0xee */ op("SET 5,[HL]", 2, 16, 0, "----"),
+    /* 0xef */ op("SET 5,A", 2, 8, 0, "----"),
+    /* 0xf0 */ op("SET 6,B", 2, 8, 0, "----"),
+    /* 0xf1 */ op("SET 6,C", 2, 8, 0, "----"),
+    /* 0xf2 */ op("SET 6,D", 2, 8, 0, "----"),
+    /* 0xf3 */ op("SET 6,E", 2, 8, 0, "----"),
+    /* 0xf4 */ op("SET 6,H", 2, 8, 0, "----"),
+    /* 0xf5 */ op("SET 6,L", 2, 8, 0, "----"),
+    /* 0xf6 */ op("SET 6,[HL]", 2, 16, 0, "----"),
+    /* 0xf7 */ op("SET 6,A", 2, 8, 0, "----"),
+    /* 0xf8 */ op("SET 7,B", 2, 8, 0, "----"),
+    /* 0xf9 */ op("SET 7,C", 2, 8, 0, "----"),
+    /* 0xfa */ op("SET 7,D", 2, 8, 0, "----"),
+    /* 0xfb */ op("SET 7,E", 2, 8, 0, "----"),
+    /* 0xfc */ op("SET 7,H", 2, 8, 0, "----"),
+    /* 0xfd */ op("SET 7,L", 2, 8, 0, "----"),
+    /* 0xfe */ op("SET 7,[HL]", 2, 16, 0, "----"),
+    /* 0xff */ op("SET 7,A", 2, 8, 0, "----"),
+];
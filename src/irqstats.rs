@@ -0,0 +1,152 @@
+//! Optional statistics of the interrupt latency, the T-cycles between an
+//! interrupt being requested in IF and its handler being entered, to find
+//! the source of audio or video jitter.
+//!
+//! Once enabled, the core watches IF after every instruction, whatever set
+//! the bit (the hardware or a write from the game), and times the request
+//! until its handler is entered. A request cleared before being serviced is
+//! not counted. Times are [`GameBoy::cycles`] counts, at instruction
+//! granularity, and include the [`INTERRUPT_DISPATCH_CYCLES`]: a request
+//! serviced right after the instruction making it takes 20 cycles.
+//!
+//! ```
+//! use gbemu::{cartridge, GameBoy, Interrupt};
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! gb.set_interrupt_stats(true);
+//! gb.run_frame();
+//!
+//! let stats = gb.take_interrupt_stats();
+//! for interrupt in Interrupt::ALL {
+//!     let latency = stats.get(interrupt);
+//!     println!("{:?}: {} serviced, {:?} cycles on average", interrupt, latency.count, latency.mean());
+//! }
+//! ```
+//!
+use crate::{cpu::INTERRUPT_DISPATCH_CYCLES, memory::locations, GameBoy, Interrupt};
+
+/// Buckets of a [`LatencyHistogram`], the last one collects the outliers
+pub const HISTOGRAM_BUCKETS: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Latencies of an interrupt, in T-cycles
+pub struct LatencyHistogram {
+    /// Requests serviced
+    pub count: u64,
+    pub total: u64,
+    pub min: u64,
+    pub max: u64,
+    /// Bucket 0 counts the latencies of 0 cycles, bucket `i` those in
+    /// `2^(i-1)..2^i`
+    pub buckets: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            total: 0,
+            min: u64::MAX,
+            max: 0,
+            buckets: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Bucket counting the latency
+    pub fn bucket(latency: u64) -> usize {
+        (u64::BITS - latency.leading_zeros()).min(HISTOGRAM_BUCKETS as u32 - 1) as usize
+    }
+
+    /// Average latency, `None` before any request was serviced
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.total as f64 / self.count as f64)
+    }
+
+    fn add(&mut self, latency: u64) {
+        self.count += 1;
+        self.total += latency;
+        self.min = self.min.min(latency);
+        self.max = self.max.max(latency);
+        self.buckets[Self::bucket(latency)] += 1;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Latencies of every interrupt, see [`irqstats`](self)
+pub struct InterruptStats {
+    /// In [`Interrupt::ALL`] order
+    histograms: [LatencyHistogram; 5],
+}
+
+impl InterruptStats {
+    pub fn get(&self, interrupt: Interrupt) -> &LatencyHistogram {
+        &self.histograms[interrupt as usize]
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Requests in flight and the latencies measured
+pub(crate) struct IrqStats {
+    /// IF after the last instruction
+    flags: u8,
+    /// When each pending request was seen, in [`Interrupt::ALL`] order
+    requested: [Option<u64>; 5],
+    stats: InterruptStats,
+}
+
+impl GameBoy {
+    /// Starts or stops measuring the interrupt latency, see
+    /// [`irqstats`](self)
+    pub fn set_interrupt_stats(&mut self, enabled: bool) {
+        self.irq_stats = enabled.then(|| {
+            Box::new(IrqStats {
+                flags: self.memory[locations::IF],
+                ..Default::default()
+            })
+        });
+    }
+
+    /// Returns the statistics since the last call, and starts over. Empty
+    /// while not measuring.
+    pub fn take_interrupt_stats(&mut self) -> InterruptStats {
+        match self.irq_stats.as_mut() {
+            Some(irq_stats) => std::mem::take(&mut irq_stats.stats),
+            None => InterruptStats::default(),
+        }
+    }
+
+    /// Notes the requests set and cleared since the last instruction
+    pub(crate) fn watch_interrupt_flags(&mut self) {
+        let Some(irq_stats) = self.irq_stats.as_mut() else {
+            return;
+        };
+        let flags = self.memory[locations::IF];
+        let set = flags & !irq_stats.flags;
+        for interrupt in Interrupt::ALL {
+            let requested = &mut irq_stats.requested[interrupt as usize];
+            if flags & interrupt.mask() == 0 {
+                *requested = None;
+            } else if set & interrupt.mask() != 0 {
+                *requested = Some(self.counters.cycles);
+            }
+        }
+        irq_stats.flags = flags;
+    }
+
+    /// Measures the latency of the interrupt whose handler is entered
+    pub(crate) fn interrupt_serviced(&mut self, interrupt: Interrupt) {
+        let Some(irq_stats) = self.irq_stats.as_mut() else {
+            return;
+        };
+        // Requested outside an instruction, by the timer at the end of a tick
+        let requested = irq_stats.requested[interrupt as usize]
+            .take()
+            .unwrap_or(self.counters.cycles);
+        // Entered once the dispatch, not counted yet, is over
+        let latency = self.counters.cycles + INTERRUPT_DISPATCH_CYCLES as u64 - requested;
+        irq_stats.stats.histograms[interrupt as usize].add(latency);
+        irq_stats.flags &= !interrupt.mask();
+    }
+}
@@ -0,0 +1,261 @@
+//! The joypad, read by the game through the P1 register.
+//!
+//! The game selects the direction keys and/or the action buttons with bits 4
+//! and 5 of P1 (low selects), then reads the held ones as low bits 0-3.
+//!
+//! On the Super Game Boy P1 also carries the command packets sent to the
+//! SNES. Games detect it by enabling the multiplayer mode with `MLT_REQ`:
+//! with both lines deselected the low bits then read the current player
+//! (0xF for the first, 0xE for the second...), the next being selected when
+//! P15 goes high. Other models always read 0xF. Only the first player has
+//! buttons, the others read as nothing held.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/Joypad_Input.html)
+//! [REFERENCE](https://gbdev.io/pandocs/SGB_Command_Multiplayer.html)
+use std::ops::{BitOr, BitOrAssign};
+
+use crate::{
+    cpu::Cpu,
+    latency::LatencyEvent,
+    memory::{
+        io::{IoHandler, IoMap},
+        locations,
+    },
+    savestate::{SaveStateError, StateReader, StateWriter},
+    GameBoy, Interrupt, Model,
+};
+
+/// Bits in a SGB command packet
+const PACKET_BITS: usize = 128;
+/// SGB command enabling the multiplayer mode
+const MLT_REQ: u8 = 0x11;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// Set of held buttons, the directions in the low nibble and the actions in
+/// the high one, in the P1 bit order
+pub struct Buttons(u8);
+
+impl Buttons {
+    pub const RIGHT: Self = Self(0b0000_0001);
+    pub const LEFT: Self = Self(0b0000_0010);
+    pub const UP: Self = Self(0b0000_0100);
+    pub const DOWN: Self = Self(0b0000_1000);
+    pub const A: Self = Self(0b0001_0000);
+    pub const B: Self = Self(0b0010_0000);
+    pub const SELECT: Self = Self(0b0100_0000);
+    pub const START: Self = Self(0b1000_0000);
+    /// Every button, in bit order
+    pub const ALL: [Self; 8] = [
+        Self::RIGHT,
+        Self::LEFT,
+        Self::UP,
+        Self::DOWN,
+        Self::A,
+        Self::B,
+        Self::SELECT,
+        Self::START,
+    ];
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Low P1 bits for the lines selected in `p1`, a held button reads as 0
+    pub(crate) fn p1_lines(&self, p1: u8) -> u8 {
+        let mut held = 0;
+        if p1 & 0b0001_0000 == 0 {
+            held |= self.0 & 0b1111;
+        }
+        if p1 & 0b0010_0000 == 0 {
+            held |= self.0 >> 4;
+        }
+        !held & 0b1111
+    }
+}
+
+impl BitOr for Buttons {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Buttons {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The SNES side of P1 on the Super Game Boy
+pub(crate) struct SgbJoypad {
+    packet: [u8; PACKET_BITS / 8],
+    /// Bits received since the reset pulse, `None` when not receiving
+    bit: Option<usize>,
+    /// Packets left of the command being sent, after the first
+    packets_left: u8,
+    /// Players minus one, 0, 1 or 3
+    player_mask: u8,
+    player: u8,
+}
+
+impl SgbJoypad {
+    /// Writes the packet being received and the multiplayer mode
+    pub(crate) fn save(&self, w: &mut StateWriter) {
+        w.bytes(&self.packet);
+        w.u8(self.bit.map_or(0xFF, |bit| bit as u8));
+        w.u8(self.packets_left);
+        w.u8(self.player_mask);
+        w.u8(self.player);
+    }
+
+    /// Reads the state written by [`SgbJoypad::save`]
+    pub(crate) fn load(&mut self, r: &mut StateReader) -> Result<(), SaveStateError> {
+        self.packet.copy_from_slice(r.bytes(PACKET_BITS / 8)?);
+        self.bit = match r.u8()? {
+            0xFF => None,
+            bit if bit as usize <= PACKET_BITS => Some(bit as usize),
+            _ => return Err(SaveStateError::Corrupted),
+        };
+        self.packets_left = r.u8()?;
+        self.player_mask = match r.u8()? {
+            mask @ (0 | 1 | 3) => mask,
+            _ => return Err(SaveStateError::Corrupted),
+        };
+        self.player = r.u8()? & self.player_mask;
+        Ok(())
+    }
+
+    /// Follows the lines written to P1: a reset pulse (both low) starts a
+    /// packet, then every pulse of P14 sends a 0 and every pulse of P15 a 1
+    fn write(&mut self, before: u8, lines: u8) {
+        match lines {
+            0b00_0000 => {
+                self.packet = Default::default();
+                self.bit = Some(0);
+            }
+            0b01_0000 | 0b10_0000 if before == 0b11_0000 => {
+                let Some(bit) = self.bit else {
+                    return;
+                };
+                // The stop bit ends the packet
+                if bit == PACKET_BITS {
+                    self.bit = None;
+                    return;
+                }
+                if lines == 0b01_0000 {
+                    self.packet[bit / 8] |= 1 << (bit % 8);
+                }
+                self.bit = Some(bit + 1);
+                if bit + 1 == PACKET_BITS {
+                    self.received();
+                }
+            }
+            0b11_0000 if before & 0b10_0000 == 0 && self.bit.is_none() => {
+                self.player = (self.player + 1) & self.player_mask;
+            }
+            _ => {}
+        }
+    }
+
+    fn received(&mut self) {
+        if self.packets_left > 0 {
+            self.packets_left -= 1;
+            return;
+        }
+        let (command, length) = (self.packet[0] >> 3, self.packet[0] & 0b111);
+        self.packets_left = length.saturating_sub(1);
+        if command == MLT_REQ {
+            self.player_mask = match self.packet[1] & 0b11 {
+                0b01 => 1,
+                0b11 => 3,
+                _ => 0,
+            };
+            self.player = 0;
+        }
+    }
+
+    /// Low P1 bits with both lines deselected
+    fn player_id(&self) -> u8 {
+        0b1111 - self.player
+    }
+}
+
+impl GameBoy {
+    /// Returns the buttons held, see [`GameBoy::set_buttons`]
+    pub fn buttons(&self) -> Buttons {
+        self.buttons
+    }
+
+    /// Holds `buttons` and releases the others until the next call.
+    ///
+    /// Pressing a button on a line selected in P1 requests the joypad
+    /// interrupt, which also wakes the CPU from STOP.
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        let p1 = self.memory[locations::P1];
+        let before = self.buttons.p1_lines(p1);
+        if buttons != self.buttons {
+            self.record_latency(LatencyEvent::Input(buttons));
+        }
+        self.buttons = buttons;
+        // Interrupts fire on falling edges of the lines
+        if before & !buttons.p1_lines(p1) != 0 {
+            self.interrupt(Interrupt::Joypad);
+        }
+    }
+}
+
+pub(crate) fn install_io(io: &mut IoMap) {
+    io.install(
+        locations::P1,
+        IoHandler {
+            read: Some(read_p1),
+            write: Some(write_p1),
+        },
+    );
+}
+
+/// Only the line selection is stored, the buttons are read live
+fn read_p1(gb: &GameBoy, address: usize) -> u8 {
+    let p1 = gb.memory[address];
+    gb.record_latency(LatencyEvent::JoypadRead);
+    let lines = match gb.sgb_joypad.player {
+        _ if p1 & 0b0011_0000 == 0b0011_0000 => gb.sgb_joypad.player_id(),
+        0 => gb.buttons.p1_lines(p1),
+        _ => 0b1111,
+    };
+    p1 & 0b1111_0000 | lines
+}
+
+fn write_p1(gb: &mut GameBoy, address: usize, value: u8) {
+    if gb.model == Model::Sgb {
+        gb.sgb_joypad
+            .write(gb.memory[address] & 0b0011_0000, value & 0b0011_0000);
+    }
+    gb.memory[address] = 0b1100_1111 | value & 0b0011_0000;
+}
@@ -0,0 +1,142 @@
+//! Optional instrumentation measuring the input latency, from the buttons
+//! given to the core to the first frame shown after the game read them.
+//!
+//! Once enabled, the core timestamps three events: the buttons changing
+//! ([`GameBoy::set_buttons`]), the game first reading them through `P1`, and
+//! every frame presented to the [`FrontBuffer`](crate::video::FrontBuffer).
+//! [`samples`] pairs them up, to compare run-ahead settings or find where a
+//! frontend adds lag: the host side (polling, vsync, display) comes on top.
+//!
+//! With run-ahead the speculative frames are presented too, right after the
+//! real one, so their reaction to the input shows up as a shorter latency.
+//!
+//! ```
+//! use gbemu::{cartridge, joypad::Buttons, latency, GameBoy};
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! gb.set_latency_tracking(true);
+//! gb.set_buttons(Buttons::A);
+//! gb.run_frame();
+//!
+//! for sample in latency::samples(&gb.take_latency_records()) {
+//!     println!("{:?} until presented", sample.input_to_presented());
+//! }
+//! ```
+use std::{
+    cell::{Cell, RefCell},
+    time::{Duration, Instant},
+};
+
+use crate::{joypad::Buttons, GameBoy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyEvent {
+    /// The buttons held changed
+    Input(Buttons),
+    /// The game read `P1` for the first time since the buttons changed
+    JoypadRead,
+    /// A frame was presented, with its [`Frame::number`](crate::video::Frame::number)
+    FramePresented(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An event, with the host time and the emulated T-cycles it happened at
+pub struct LatencyRecord {
+    pub at: Instant,
+    pub cycles: u64,
+    pub event: LatencyEvent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The path of an input through the core
+pub struct LatencySample {
+    pub input: LatencyRecord,
+    pub read: LatencyRecord,
+    /// The first frame presented after the read
+    pub presented: LatencyRecord,
+}
+
+impl LatencySample {
+    /// Host time until the game read the input
+    pub fn input_to_read(&self) -> Duration {
+        self.read.at - self.input.at
+    }
+
+    /// Host time until a frame reacting to the input could be shown
+    pub fn input_to_presented(&self) -> Duration {
+        self.presented.at - self.input.at
+    }
+
+    /// Emulated T-cycles until the game read the input
+    pub fn cycles_to_read(&self) -> u64 {
+        self.read.cycles - self.input.cycles
+    }
+}
+
+/// Pairs every input with the read and the frame following it. Inputs
+/// changed again before being read, or not read yet, are skipped.
+pub fn samples(records: &[LatencyRecord]) -> Vec<LatencySample> {
+    let mut samples = vec![];
+    let mut input = None;
+    let mut read = None;
+    for record in records {
+        match record.event {
+            LatencyEvent::Input(_) => {
+                input = Some(*record);
+                read = None;
+            }
+            LatencyEvent::JoypadRead if input.is_some() => read = Some(*record),
+            LatencyEvent::FramePresented(_) => {
+                if let (Some(input_record), Some(read_record)) = (input, read) {
+                    samples.push(LatencySample {
+                        input: input_record,
+                        read: read_record,
+                        presented: *record,
+                    });
+                    input = None;
+                    read = None;
+                }
+            }
+            LatencyEvent::JoypadRead => {}
+        }
+    }
+    samples
+}
+
+#[derive(Debug, Default)]
+/// Recorder of the latency events, off by default
+pub(crate) struct LatencyProbe {
+    enabled: bool,
+    /// `P1` was read since the last input, later reads are not recorded
+    read: Cell<bool>,
+    records: RefCell<Vec<LatencyRecord>>,
+}
+
+impl GameBoy {
+    /// Starts or stops recording the latency events, see [`latency`](self)
+    pub fn set_latency_tracking(&mut self, enabled: bool) {
+        self.latency.enabled = enabled;
+        self.latency.read.set(true);
+    }
+
+    /// Returns the events recorded since the last call, oldest first
+    pub fn take_latency_records(&mut self) -> Vec<LatencyRecord> {
+        std::mem::take(self.latency.records.get_mut())
+    }
+
+    pub(crate) fn record_latency(&self, event: LatencyEvent) {
+        if !self.latency.enabled {
+            return;
+        }
+        match event {
+            LatencyEvent::Input(_) => self.latency.read.set(false),
+            LatencyEvent::JoypadRead if self.latency.read.replace(true) => return,
+            _ => {}
+        }
+        self.latency.records.borrow_mut().push(LatencyRecord {
+            at: Instant::now(),
+            cycles: self.cycles(),
+            event,
+        });
+    }
+}
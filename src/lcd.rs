@@ -0,0 +1,505 @@
+//! LCD controller: scanline timing, STAT modes and a DMG renderer.
+//!
+//! The timing drives `LY`, the `STAT` mode and coincidence bits, the VBlank
+//! and STAT interrupts and the [`Hooks`](crate::hooks::Hooks) callbacks.
+//! Each line is rendered at once when entering HBlank by the [`PpuBackend`],
+//! [`DmgPpu`] by default, so mid-line register changes are not visible.
+use crate::{
+    backend::PpuBackend,
+    cpu::{Cpu, Interrupt, CYCLES_PER_FRAME},
+    memory::{
+        io::{IoHandler, IoMap},
+        locations,
+        view::OamEntry,
+        Memory,
+    },
+    run::Timeout,
+    savestate::{SaveStateError, StateReader, StateWriter},
+    GameBoy, Model,
+};
+
+/// Dots (normal speed T-cycles) per scanline
+pub const DOTS_PER_LINE: u64 = 456;
+/// Scanlines drawn on the screen, the rest of the frame is VBlank
+pub const VISIBLE_LINES: u8 = 144;
+/// Scanlines per frame, VBlank included
+pub const LINES_PER_FRAME: u8 = 154;
+/// Width of the screen in pixels
+pub const SCREEN_WIDTH: usize = 160;
+/// Height of the screen in pixels
+pub const SCREEN_HEIGHT: usize = VISIBLE_LINES as usize;
+/// Frames [`GameBoy::run_to_vblank`] and [`GameBoy::run_to_scanline`] run
+/// at most, the LCD may be off for a while (e.g. while loading)
+pub const PPU_EVENT_TIMEOUT_FRAMES: u64 = 60;
+/// Sprites drawn at most on a line
+const SPRITES_PER_LINE: usize = 10;
+/// Length of mode 2
+const OAM_SCAN_DOTS: u64 = 80;
+/// Length of mode 3 without sprites, window or fine scrolling, which stretch
+/// it up to 289 dots
+const DRAWING_DOTS: u64 = 172;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The PPU mode, as reported in bits 0-1 of `STAT`
+pub enum Mode {
+    HBlank = 0,
+    VBlank = 1,
+    #[default]
+    OamScan = 2,
+    Drawing = 3,
+}
+
+impl Mode {
+    fn at(line: u8, dot: u64) -> Self {
+        if line >= VISIBLE_LINES {
+            Self::VBlank
+        } else if dot < OAM_SCAN_DOTS {
+            Self::OamScan
+        } else if dot < OAM_SCAN_DOTS + DRAWING_DOTS {
+            Self::Drawing
+        } else {
+            Self::HBlank
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Lcd {
+    line: u8,
+    /// Dots elapsed in the current line
+    dot: u64,
+    mode: Mode,
+    /// The first line after turning the LCD on skips the OAM scan, reporting
+    /// mode 0 instead
+    first_line: bool,
+    /// Line of the window drawn next, it only advances on lines showing it
+    window_line: u8,
+    /// The first frame after turning the LCD on is drawn but not shown
+    first_frame: bool,
+    /// Level of the STAT interrupt line, the sources enabled in `STAT` ORed
+    /// together: the interrupt is requested on its rising edge
+    stat_line: bool,
+    /// Shades (0 is white, 3 is black) of the pixels, row by row
+    pub(crate) frame: Vec<u8>,
+    /// VBlank periods entered, to wait for the next one
+    vblanks: u64,
+    /// Lines entered, to wait for the next one
+    lines: u64,
+}
+
+impl Default for Lcd {
+    fn default() -> Self {
+        Self {
+            line: 0,
+            dot: 0,
+            mode: Mode::default(),
+            first_line: false,
+            window_line: 0,
+            first_frame: false,
+            stat_line: false,
+            frame: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            vblanks: 0,
+            lines: 0,
+        }
+    }
+}
+
+impl Lcd {
+    /// Writes the timing kept in save states, the frame being drawn and the
+    /// event counters are left out
+    pub(crate) fn save(&self, w: &mut StateWriter) {
+        w.u8(self.line);
+        w.u16(self.dot as u16);
+        w.u8(self.mode as u8);
+        w.bool(self.first_line);
+        w.u8(self.window_line);
+        w.bool(self.first_frame);
+        w.bool(self.stat_line);
+    }
+
+    /// Reads the timing written by [`Lcd::save`]
+    pub(crate) fn load(&mut self, r: &mut StateReader) -> Result<(), SaveStateError> {
+        let line = r.u8()?;
+        let dot = r.u16()? as u64;
+        if line >= LINES_PER_FRAME || dot >= DOTS_PER_LINE {
+            return Err(SaveStateError::Corrupted);
+        }
+        self.line = line;
+        self.dot = dot;
+        self.mode = match r.u8()? {
+            0 => Mode::HBlank,
+            1 => Mode::VBlank,
+            2 => Mode::OamScan,
+            3 => Mode::Drawing,
+            _ => return Err(SaveStateError::Corrupted),
+        };
+        self.first_line = r.bool()?;
+        self.window_line = r.u8()?;
+        self.first_frame = r.bool()?;
+        self.stat_line = r.bool()?;
+        Ok(())
+    }
+
+    /// Dot at which the mode may change next on the current line
+    fn next_boundary(&self) -> u64 {
+        if self.line >= VISIBLE_LINES {
+            return DOTS_PER_LINE;
+        }
+
+        [OAM_SCAN_DOTS, OAM_SCAN_DOTS + DRAWING_DOTS, DOTS_PER_LINE]
+            .into_iter()
+            .find(|&boundary| boundary > self.dot)
+            .unwrap_or(DOTS_PER_LINE)
+    }
+}
+
+impl GameBoy {
+    /// Returns the current PPU mode
+    pub fn lcd_mode(&self) -> Mode {
+        self.lcd.mode
+    }
+
+    /// Returns the shades (0 is white, 3 is black) of the
+    /// [`SCREEN_WIDTH`] × [`SCREEN_HEIGHT`] pixels, row by row.
+    ///
+    /// Lines are updated as they are drawn, the frame is complete on VBlank.
+    /// While the LCD is off it is white.
+    pub fn frame(&self) -> &[u8] {
+        &self.lcd.frame
+    }
+
+    /// Runs until the LCD enters VBlank, stopping at the first instruction
+    /// boundary after it, so the frame is complete (see [`GameBoy::frame`]).
+    ///
+    /// Returns the T-cycles taken.
+    pub fn run_to_vblank(&mut self) -> Result<u64, Timeout> {
+        let vblanks = self.lcd.vblanks;
+        self.run_to_ppu_event(|gb| gb.lcd.vblanks != vblanks)
+    }
+
+    /// Runs until the LCD starts drawing line `ly` (0 to 153), stopping at
+    /// the first instruction boundary after it, e.g. to look at the raster
+    /// effects set up for the line. A line being drawn is waited for in the
+    /// next frame.
+    ///
+    /// Returns the T-cycles taken.
+    ///
+    /// # Panics
+    ///
+    /// If the line is past the frame.
+    pub fn run_to_scanline(&mut self, ly: u8) -> Result<u64, Timeout> {
+        assert!(ly < LINES_PER_FRAME, "line {} is past the frame", ly);
+        let lines = self.lcd.lines;
+        self.run_to_ppu_event(|gb| gb.lcd.lines != lines && gb.lcd.line == ly)
+    }
+
+    fn run_to_ppu_event(&mut self, reached: impl Fn(&GameBoy) -> bool) -> Result<u64, Timeout> {
+        let start = self.cycles();
+        let limit = PPU_EVENT_TIMEOUT_FRAMES * CYCLES_PER_FRAME;
+        while !reached(self) {
+            if self.cycles() - start >= limit {
+                return Err(Timeout {
+                    frames: PPU_EVENT_TIMEOUT_FRAMES,
+                });
+            }
+            self.step();
+        }
+        Ok(self.cycles() - start)
+    }
+
+    /// Advances the LCD timing by the given dots, firing the hooks at every
+    /// mode change on the way
+    pub(crate) fn lcd_step(&mut self, dots: u64) {
+        if self.memory()[locations::LCDC] & 0x80 == 0 {
+            return;
+        }
+
+        let mut remaining = dots;
+        while remaining > 0 {
+            let elapsed = remaining.min(self.lcd.next_boundary() - self.lcd.dot);
+            self.lcd.dot += elapsed;
+            remaining -= elapsed;
+
+            if self.lcd.dot == DOTS_PER_LINE {
+                self.lcd.dot = 0;
+                self.lcd.line = (self.lcd.line + 1) % LINES_PER_FRAME;
+                self.lcd.first_line = false;
+                self.lcd.lines += 1;
+                self.line_changed();
+            }
+
+            let mode = match Mode::at(self.lcd.line, self.lcd.dot) {
+                Mode::OamScan if self.lcd.first_line => Mode::HBlank,
+                mode => mode,
+            };
+            if mode != self.lcd.mode {
+                self.lcd.mode = mode;
+                self.mode_changed(mode);
+            }
+            self.update_stat_line();
+        }
+    }
+
+    /// Returns the level of the STAT interrupt line for the given `STAT`
+    /// sources: LYC=LY (bit 6), mode 2 (bit 5), mode 1 (bit 4) and mode 0
+    /// (bit 3)
+    fn stat_line(&self, stat: u8) -> bool {
+        if self.memory()[locations::LCDC] & 0x80 == 0 {
+            return false;
+        }
+        let coincidence = self.memory()[locations::STAT] & 0b100 != 0;
+        let mode_source = match self.lcd.mode {
+            Mode::HBlank => 0x08,
+            Mode::VBlank => 0x10,
+            Mode::OamScan => 0x20,
+            Mode::Drawing => 0x00,
+        };
+        (stat & 0x40 != 0 && coincidence) || stat & mode_source != 0
+    }
+
+    /// Follows the STAT interrupt line, requesting the interrupt when it rises
+    fn update_stat_line(&mut self) {
+        let line = self.stat_line(self.memory()[locations::STAT]);
+        if line && !self.lcd.stat_line {
+            log::trace!(target: "gbemu::lcd", "STAT interrupt in {:?}", self.lcd.mode);
+            self.interrupt(Interrupt::LCDStat);
+        }
+        self.lcd.stat_line = line;
+    }
+
+    /// Stops or restarts the LCD timing after a write to `LCDC`
+    pub(crate) fn lcd_power(&mut self, on: bool) {
+        log::debug!(target: "gbemu::lcd", "LCD {}", if on { "on" } else { "off" });
+        if !on && self.lcd.mode != Mode::VBlank {
+            // Real DMG hardware can be damaged by this, games wait for VBlank
+            log::warn!(
+                target: "gbemu::lcd",
+                "LCD turned off outside VBlank, at line {} in {:?}",
+                self.lcd.line,
+                self.lcd.mode
+            );
+        }
+
+        // While off LY reads 0 and STAT mode 0, the frame restarts from the
+        // top once back on
+        self.lcd.line = 0;
+        self.lcd.dot = 0;
+        self.lcd.mode = Mode::HBlank;
+        self.lcd.first_line = on;
+        self.lcd.first_frame = on;
+        self.lcd.window_line = 0;
+        self.lcd.lines += on as u64;
+        self.line_changed();
+        self.memory_mut()[locations::STAT] &= !0b11;
+        self.update_stat_line();
+        if !on {
+            self.lcd.frame.fill(0);
+            self.produce_blank_frame();
+        }
+    }
+
+    fn line_changed(&mut self) {
+        let line = self.lcd.line;
+        let coincidence = line == self.memory()[locations::LYC];
+        self.memory_mut()[locations::LY] = line;
+        let stat = &mut self.memory_mut()[locations::STAT];
+        *stat = (*stat & !0b100) | ((coincidence as u8) << 2);
+    }
+
+    fn mode_changed(&mut self, mode: Mode) {
+        let stat = &mut self.memory_mut()[locations::STAT];
+        *stat = (*stat & !0b11) | mode as u8;
+
+        match mode {
+            Mode::HBlank => {
+                self.render_line();
+                if let Some(hook) = self.hooks.on_hblank.as_mut() {
+                    hook(self.lcd.line);
+                }
+            }
+            Mode::VBlank => {
+                self.interrupt(Interrupt::VBlank);
+                self.lcd.vblanks += 1;
+                self.lcd.window_line = 0;
+                if std::mem::take(&mut self.lcd.first_frame) {
+                    self.produce_blank_frame();
+                } else {
+                    self.produce_frame();
+                }
+                if let Some(hook) = self.hooks.on_vblank.as_mut() {
+                    hook();
+                }
+            }
+            Mode::OamScan | Mode::Drawing => {}
+        }
+        if let Some(hook) = self.hooks.on_mode_change.as_mut() {
+            hook(mode);
+        }
+    }
+}
+
+pub(crate) fn install_io(io: &mut IoMap) {
+    io.install(locations::LCDC, IoHandler::write(write_lcdc));
+    // LY is read-only, it is only reset by turning the LCD off
+    io.install(locations::LY, IoHandler::write(|_, _, _| ()));
+    io.install(locations::STAT, IoHandler::write(write_stat));
+}
+
+/// Only the interrupt sources are writable, the mode and coincidence bits
+/// follow the timing and bit 7 is unused
+fn write_stat(gb: &mut GameBoy, address: usize, value: u8) {
+    let stat = gb.memory[address];
+    gb.memory[address] = 0x80 | (value & 0x78) | (stat & 0x07);
+
+    // DMG bug: STAT reads 0xFF during the write cycle, enabling every source
+    // but the OAM scan for a moment, which raises the line if it was low
+    if gb.accuracy.dmg_stat_write_bug
+        && matches!(gb.model, Model::Dmg | Model::Sgb)
+        && !gb.lcd.stat_line
+        && gb.stat_line(0x58)
+    {
+        log::trace!(target: "gbemu::lcd", "STAT write bug in {:?}", gb.lcd.mode);
+        gb.interrupt(Interrupt::LCDStat);
+        gb.lcd.stat_line = true;
+    }
+    // Enabling a source whose condition holds raises the line
+    gb.update_stat_line();
+}
+
+fn write_lcdc(gb: &mut GameBoy, address: usize, value: u8) {
+    let was_on = gb.memory[address] & 0x80 != 0;
+    let on = value & 0x80 != 0;
+    gb.memory[address] = value;
+    if on != was_on {
+        gb.lcd_power(on);
+    }
+}
+
+impl GameBoy {
+    /// Draws the current line into the frame with the PPU backend
+    fn render_line(&mut self) {
+        let line = self.lcd.line;
+        let mut shades = [0; SCREEN_WIDTH];
+        self.ppu
+            .render_line(&self.memory, line, &mut self.lcd.window_line, &mut shades);
+        self.lcd.frame[line as usize * SCREEN_WIDTH..][..SCREEN_WIDTH].copy_from_slice(&shades);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// The default [`PpuBackend`], drawing background, window and sprites like
+/// the DMG
+pub struct DmgPpu;
+
+impl PpuBackend for DmgPpu {
+    fn render_line(
+        &mut self,
+        memory: &[u8],
+        line: u8,
+        window_line: &mut u8,
+        shades: &mut [u8; SCREEN_WIDTH],
+    ) {
+        let lcdc = memory[locations::LCDC];
+        let line = line as usize;
+
+        // Color numbers before the palette, sprites need them for priority
+        let mut colors = [0; SCREEN_WIDTH];
+        if lcdc & 0x01 != 0 {
+            let map = if lcdc & 0x08 != 0 { 0x9C00 } else { 0x9800 };
+            let y = (line + memory[locations::SCY] as usize) & 0xFF;
+            let scx = memory[locations::SCX] as usize;
+            for (x, color) in colors.iter_mut().enumerate() {
+                *color = tile_map_pixel(memory, lcdc, map, (x + scx) & 0xFF, y);
+            }
+
+            let wy = memory[locations::WY] as usize;
+            let wx = memory[locations::WX] as usize;
+            if lcdc & 0x20 != 0 && line >= wy && wx < SCREEN_WIDTH + 7 {
+                let map = if lcdc & 0x40 != 0 { 0x9C00 } else { 0x9800 };
+                let y = *window_line as usize;
+                let start = wx.saturating_sub(7);
+                for (x, color) in colors.iter_mut().enumerate().skip(start) {
+                    *color = tile_map_pixel(memory, lcdc, map, x + 7 - wx, y);
+                }
+                *window_line += 1;
+            }
+        }
+
+        let bgp = memory[locations::BGP];
+        *shades = colors.map(|color| shade(bgp, color));
+
+        if lcdc & 0x02 != 0 {
+            let height = if lcdc & 0x04 != 0 { 16 } else { 8 };
+            let mut sprites: Vec<_> = memory[locations::OAM]
+                .chunks_exact(4)
+                .map(|bytes| OamEntry::from([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .filter(|sprite| {
+                    let top = sprite.y as usize;
+                    line + 16 >= top && line + 16 < top + height
+                })
+                .take(SPRITES_PER_LINE)
+                .collect();
+            // The leftmost sprite wins, then the first in OAM: draw it last
+            sprites.sort_by_key(|sprite| sprite.x);
+
+            for sprite in sprites.into_iter().rev() {
+                let mut row = line + 16 - sprite.y as usize;
+                if sprite.y_flip() {
+                    row = height - 1 - row;
+                }
+                let tile = if height == 16 {
+                    sprite.tile & 0xFE
+                } else {
+                    sprite.tile
+                };
+                let address = 0x8000 + tile as usize * 16 + row * 2;
+                let palette = if sprite.palette() {
+                    memory[locations::OBP1]
+                } else {
+                    memory[locations::OBP0]
+                };
+
+                for column in 0..8 {
+                    let Some(x) = (sprite.x as usize + column).checked_sub(8) else {
+                        continue;
+                    };
+                    if x >= SCREEN_WIDTH {
+                        continue;
+                    }
+
+                    let bit = if sprite.x_flip() { column } else { 7 - column };
+                    let color = tile_pixel(memory, address, bit);
+                    // Color 0 is transparent, the background can be drawn over sprites
+                    if color == 0 || (sprite.behind_background() && colors[x] != 0) {
+                        continue;
+                    }
+                    shades[x] = shade(palette, color);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the color number of a pixel of a background or window tile map
+fn tile_map_pixel(memory: &[u8], lcdc: u8, map: usize, x: usize, y: usize) -> u8 {
+    let index = memory[map + (y / 8) * 32 + x / 8];
+    // Tiles are numbered from 0x8000, or signed around 0x9000
+    let tile = if lcdc & 0x10 != 0 {
+        0x8000 + index as usize * 16
+    } else {
+        (0x9000 + index as i8 as isize * 16) as usize
+    };
+    tile_pixel(memory, tile + (y % 8) * 2, 7 - x % 8)
+}
+
+/// Returns the color number of a pixel from the two bytes of a tile row
+fn tile_pixel(memory: &[u8], row: usize, bit: usize) -> u8 {
+    let low = (memory[row] >> bit) & 1;
+    let high = (memory[row + 1] >> bit) & 1;
+    (high << 1) | low
+}
+
+/// Maps a color number through a palette register
+fn shade(palette: u8, color: u8) -> u8 {
+    (palette >> (color * 2)) & 0b11
+}
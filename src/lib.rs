@@ -4,23 +4,120 @@
 //!
 //! This project is based on information found on the [GameBoy CPU Manual](http://marc.rawer.de/Gameboy/Docs/GBCPUman.pdf)
 //! and the [Pan Docs](https://gbdev.io/pandocs/About.html).
-use cartridge::{CartridgeHeader, CartridgeHolder};
-use cpu::{Cpu, RegisterFile, Registers};
+//!
+//! ## Logging
+//!
+//! The core logs through the [`log`](https://docs.rs/log) facade with a
+//! target per subsystem, so each can be enabled on its own
+//! (e.g. `RUST_LOG=gbemu::mbc=trace`):
+//!
+//! - `gbemu::cpu`: executed opcodes (trace), HALT/STOP/speed switch transitions (debug)
+//! - `gbemu::irq`: requested (trace) and serviced (debug) interrupts
+//! - `gbemu::mbc`: bank switches (trace) and ROM writes (debug, see [`GameBoy::set_log_rom_writes`])
+//! - `gbemu::dma`: OAM DMA transfers (debug) and bus violations (warn)
+//! - `gbemu::timer`: TIMA overflows (trace)
+//! - `gbemu::cartridge`: tolerated header oddities and failed boot checks (warn)
+//! - `gbemu::debug`: watchpoint hits (debug)
+//! - `gbemu::bus`: accesses that could not be served as requested (warn)
+//! - `gbemu::lcd`: LCD switched on and off (debug), STAT interrupts and the STAT write bug (trace), off outside VBlank (warn)
+//! - `gbemu::determinism`: nondeterminism sources used, once each (warn, see [`GameBoy::set_determinism_audit`])
+//! - `gbemu::runner`: emulation thread stopped (debug)
+//! - `gbemu::session`: async session handle dropped (debug)
+//! - `gbemu::battery`: external RAM written back by [`battery::AutoSave`] (debug)
+//! - `gbemu::serial`: bytes sent and received (trace), link peer lost or late (warn)
+//! - `gbemu::lint`: homebrew lints, once per instruction address (warn, see [`GameBoy::set_lints`])
+use accuracy::AccuracyConfig;
+pub use builder::GameBoyBuilder;
+use cartridge::{CartridgeError, CartridgeHeader, CartridgeHolder};
+pub use cpu::Interrupt;
+use cpu::{ClockConfig, Cpu, RegisterFile, Registers};
+#[cfg(feature = "debugger")]
+use debug::Debugger;
+use determinism::{AuditMode, Nondeterminism};
+use hooks::Hooks;
 use instructions::InstructionDecoder;
-use memory::{Memory, MemoryMode, Read, Write};
+use memory::{
+    dma::OamDma,
+    fault::{BusFault, MAX_BUS_FAULTS},
+    io::IoMap,
+    locations, BankingState, Memory, MemoryMode, RamInit, Read, Write,
+};
+#[cfg(feature = "debugger")]
+use profiler::Profiler;
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+};
 
+pub mod accuracy;
+#[cfg(feature = "ppu")]
+pub mod avdump;
+#[cfg(any(feature = "ppu", feature = "apu"))]
+pub mod backend;
+pub mod batch;
+pub mod battery;
+pub mod bootrom;
+mod builder;
 pub mod cartridge;
 pub mod cpu;
+#[cfg(feature = "debugger")]
+pub mod debug;
+pub mod determinism;
+#[cfg(feature = "env")]
+pub mod env;
+pub mod eventlog;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod hooks;
+pub mod input;
+pub mod inspect;
 pub mod instructions;
+pub mod irqstats;
+pub mod joypad;
+pub mod latency;
+#[cfg(feature = "ppu")]
+pub mod lcd;
+pub mod lint;
 pub mod memory;
+pub mod netplay;
+pub mod pacing;
+#[cfg(feature = "debugger")]
+pub mod profiler;
+pub mod rtc;
+pub mod run;
+#[cfg(feature = "ppu")]
+mod runahead;
+#[cfg(feature = "runner")]
+pub mod runner;
+pub mod savestate;
+#[cfg(feature = "serial")]
+pub mod serial;
+#[cfg(feature = "session")]
+pub mod session;
 pub mod timer;
+pub mod tracelog;
+#[cfg(feature = "ppu")]
+pub mod video;
 
 pub(crate) const ROM_BANK_SIZE: usize = 0x4000;
 pub(crate) const RAM_BANK_SIZE: usize = 0x2000;
-pub(crate) const MAX_ROM_BANKS: usize = 0x80;
-pub(crate) const MAX_RAM_BANKS: usize = 0x10;
+pub(crate) const MBC2_RAM_SIZE: usize = 0x200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The hardware revision being emulated
+pub enum Model {
+    /// Original Game Boy
+    #[default]
+    Dmg,
+    /// Super Game Boy
+    Sgb,
+    /// Game Boy Color
+    Cgb,
+}
 
 pub struct GameBoy {
+    model: Model,
+    clock: ClockConfig,
     cartridge_header: CartridgeHeader,
     memory_mode: MemoryMode,
     registers: cpu::RegisterFile,
@@ -34,35 +131,307 @@ pub struct GameBoy {
     /// We keep all banks loaded in memory without swapping,
     /// only dinamically change addressing
     banks: Vec<u8>,
+    /// External RAM pages written since they were last persisted
+    sram_dirty: battery::DirtyPages,
+    oam_dma: OamDma,
+    /// Handlers of the I/O registers trapped by the subsystems
+    io: IoMap,
+    accuracy: AccuracyConfig,
+    hooks: Hooks,
+    /// Rate at which the audio device consumes samples
+    sample_rate: u32,
+    /// Cycles owed to (positive) or borrowed from (negative) the next
+    /// [`GameBoy::run_until_samples`] call
+    sample_cycles_debt: f64,
+    /// Log every write to the ROM region
+    log_rom_writes: bool,
+    /// Power on contents of the internal RAM
+    ram_init: RamInit,
+    /// Power on contents of the external RAM
+    sram_init: RamInit,
+    /// Value read from external RAM while disabled
+    disabled_ram_value: u8,
+    /// Last value driven on the data bus. Not saved in states as every
+    /// instruction drives the bus with its opcode before any data access.
+    bus_latch: Cell<u8>,
+    counters: cpu::Counters,
+    timer: timer::Timer,
+    /// Bytes sent on the serial port
+    serial_output: Vec<u8>,
+    /// Link port peer, not saved in states, and transfer in progress
+    #[cfg(feature = "serial")]
+    serial: serial::Serial,
+    /// Held buttons, an input rather than state: not saved in states
+    buttons: joypad::Buttons,
+    sgb_joypad: joypad::SgbJoypad,
+    /// Live MBC3 clock, the registers read by the game are latched from it
+    rtc: rtc::Rtc,
+    #[cfg(feature = "debugger")]
+    debugger: Debugger,
+    #[cfg(feature = "debugger")]
+    profiler: Profiler,
+    #[cfg(feature = "ppu")]
+    lcd: lcd::Lcd,
+    /// Renderer of the scanlines, not saved in states
+    #[cfg(feature = "ppu")]
+    ppu: Box<dyn backend::PpuBackend>,
+    /// Sound emulation, saved in states through the backend
+    #[cfg(feature = "apu")]
+    apu: Box<dyn backend::ApuBackend>,
+    #[cfg(feature = "ppu")]
+    video: video::Video,
+    /// Faults reported by the bus since the last [`GameBoy::take_bus_faults`]
+    bus_faults: RefCell<VecDeque<BusFault>>,
+    audit: AuditMode,
+    /// Sources used since the last [`GameBoy::take_nondeterminism`]
+    nondeterminism: RefCell<Vec<Nondeterminism>>,
+    /// Set while a fixed step method drives [`Cpu::tick`], other callers
+    /// are assumed to pass host measured time
+    fixed_step: bool,
+    /// Frames are only run by [`GameBoy::advance_one_frame`]
+    paused: bool,
+    latency: latency::LatencyProbe,
+    /// Events of the current frame, while an event log is collecting them
+    frame_events: Option<eventlog::FrameEvents>,
+    /// Format of the lines given to the trace hook
+    trace_format: tracelog::TraceFormat,
+    linter: lint::Linter,
+    /// Address of the instruction running, for the diagnostics
+    instruction_pc: u16,
+    /// OAM DMA activity, while a trace is collecting it
+    dma_trace: RefCell<Option<memory::dma::DmaTrace>>,
+    /// Interrupt latencies, while measuring them
+    irq_stats: Option<Box<irqstats::IrqStats>>,
+    /// Run at power up if given, see [`bootrom`]
+    boot_rom: Option<bootrom::BootRom>,
+    /// Emulate the checks of the boot ROM without one
+    boot_checks: bool,
+    boot_failure: Option<bootrom::BootFailure>,
 }
 
 impl GameBoy {
+    /// Powers up a console with the given cartridge image
+    ///
+    /// ```
+    /// use gbemu::{cartridge, cpu::Cpu, memory::Read, GameBoy};
+    ///
+    /// let mut gb = GameBoy::new(&cartridge::test_rom());
+    /// for _ in 0..10 {
+    ///     gb.step();
+    /// }
+    /// assert!(gb.read_u8(0xC000) > 0);
+    /// ```
     pub fn new(cartridge: &[u8]) -> Self {
-        let ch = CartridgeHeader::from(cartridge);
+        Self::with_model(cartridge, Model::default())
+    }
+
+    pub fn with_model(cartridge: &[u8], model: Model) -> Self {
+        GameBoyBuilder::new(cartridge).model(model).build()
+    }
+
+    /// Powers up a console, failing instead of panicking on images it can't
+    /// run, see [`GameBoyBuilder::try_build`]
+    pub fn try_new(cartridge: &[u8]) -> Result<Self, CartridgeError> {
+        GameBoyBuilder::new(cartridge).try_build()
+    }
+
+    /// Powers up a console with an empty cartridge slot, the ROM area reads as open bus
+    pub fn without_cartridge() -> Self {
+        Self::new(&[])
+    }
+
+    /// Returns a builder to configure the emulator before powering it up
+    pub fn builder(cartridge: &[u8]) -> GameBoyBuilder<'_> {
+        GameBoyBuilder::new(cartridge)
+    }
+
+    /// Returns the current state of the memory bank controller
+    pub fn banking_state(&self) -> BankingState {
+        Memory::banking_state(self)
+    }
 
-        if (ch.ram_size as usize) > MAX_RAM_BANKS {
-            panic!("RAM size is too big");
+    /// Runs the emulation for a frame worth of time, the main loop of
+    /// frontends paced by [`FrameLimiter`](pacing::FrameLimiter).
+    ///
+    /// Returns the cycles executed, see [`Cpu::tick`].
+    ///
+    /// Does nothing while paused.
+    pub fn run_frame(&mut self) -> usize {
+        if self.paused {
+            return 0;
         }
+        self.advance_one_frame()
+    }
 
-        if (ch.rom_size as usize) > MAX_ROM_BANKS {
-            panic!("ROM size is too big");
+    /// Runs a frame even while paused, to step through a game frame by frame
+    pub fn advance_one_frame(&mut self) -> usize {
+        self.fixed_step(|gb| gb.tick(1.0 / gb.clock.frame_rate()))
+    }
+
+    /// Stops [`GameBoy::run_frame`] and [`GameBoy::run_until_samples`]
+    /// from emulating, so frontends can keep calling them from their loop
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Runs the emulation for exactly `samples` audio samples worth of time,
+    /// at the sample rate chosen on the builder.
+    ///
+    /// Meant to be called from the audio device callback so emulation speed
+    /// follows the audio clock, which avoids crackling. The sub-instruction
+    /// overshoot is carried to the next call so no drift accumulates.
+    ///
+    /// The core makes no sound of its own: the samples come from the
+    /// [`ApuBackend`](backend::ApuBackend) chosen on the builder, which is
+    /// stepped through the emulated time and should have `samples` ready
+    /// for the callback when this returns. The default
+    /// [`NullApu`](backend::NullApu) makes none, and without the `apu`
+    /// feature the emulated time is the only output.
+    ///
+    /// Does nothing while paused.
+    pub fn run_until_samples(&mut self, samples: usize) {
+        if self.paused {
+            return;
         }
+        let speed = self.clock.speed;
+        let cycles = samples as f64 * speed / self.sample_rate as f64 + self.sample_cycles_debt;
+        let executed = self.fixed_step(|gb| gb.tick(cycles / speed));
+        self.sample_cycles_debt = cycles - executed as f64;
+    }
 
-        let mut cart = vec![0; ROM_BANK_SIZE * ch.rom_size as usize];
-        cart.copy_from_slice(cartridge);
+    fn fixed_step<R>(&mut self, run: impl FnOnce(&mut Self) -> R) -> R {
+        self.fixed_step = true;
+        let result = run(self);
+        self.fixed_step = false;
+        result
+    }
+
+    /// Returns a one-line summary of the machine state, for trace logs and monitors:
+    /// registers, CPU state, current banks, LCD line, timer and interrupts
+    pub fn status_line(&self) -> String {
+        let banking = self.banking_state();
+        let io = |address: usize| self.memory[address];
+        format!(
+            "{} IME={} {:?} ROM={:02X} RAM={:02X} LY={:02X} DIV={:02X} TIMA={:02X} TAC={:02X} IF={:02X} IE={:02X}",
+            self.registers,
+            self.registers.ime as u8,
+            self.registers.mode,
+            banking.rom_bank,
+            banking.ram_bank,
+            io(locations::LY),
+            io(locations::DIV),
+            io(locations::TIMA),
+            io(locations::TAC),
+            io(locations::IF),
+            io(locations::IE),
+        )
+    }
+
+    /// Returns the T-cycles executed since power on, twice as fast in CGB double speed
+    pub fn cycles(&self) -> u64 {
+        self.counters.cycles
+    }
+
+    /// Returns the instructions executed since power on
+    pub fn instructions_retired(&self) -> u64 {
+        self.counters.instructions
+    }
+
+    /// Returns the frames (of [`CYCLES_PER_FRAME`](cpu::CYCLES_PER_FRAME)
+    /// normal speed T-cycles) elapsed since power on
+    pub fn frames(&self) -> u64 {
+        self.counters.frames
+    }
+
+    /// Returns the bytes sent on the serial port since power on, test ROMs
+    /// print their results there
+    pub fn serial_output(&self) -> &[u8] {
+        &self.serial_output
+    }
+
+    /// Changes how many CPU cycles run for every cycle of the rest of the
+    /// hardware, see [`ClockConfig::overclock`]. 1 restores the real speed.
+    pub fn set_overclock(&mut self, factor: u32) {
+        assert!(factor > 0, "The overclock factor must be at least 1");
+        self.clock.overclock = factor;
+    }
 
-        let mut tmp = Self {
-            registers: cpu::RegisterFile::default(),
-            memory: [0; 0x10000],
-            memory_mode: ch.cart_type.into(),
-            cartridge: cart,
-            banks: vec![0; RAM_BANK_SIZE * ch.ram_size as usize],
-            cartridge_header: ch,
-        };
+    /// Returns the accuracy toggles
+    pub fn accuracy(&self) -> &AccuracyConfig {
+        &self.accuracy
+    }
+
+    /// Returns the mutable accuracy toggles
+    pub fn accuracy_mut(&mut self) -> &mut AccuracyConfig {
+        &mut self.accuracy
+    }
+
+    /// Enables logging (at debug level) of every write to the ROM region,
+    /// along with the PC and the MBC register it hits.
+    ///
+    /// Games writing to ROM by mistake end up writing MBC registers,
+    /// this helps spotting those and bank mismatches.
+    pub fn set_log_rom_writes(&mut self, enabled: bool) {
+        self.log_rom_writes = enabled;
+    }
+
+    /// Returns the bus faults reported since the last call, oldest first.
+    ///
+    /// Only the last [`MAX_BUS_FAULTS`] are kept.
+    pub fn take_bus_faults(&mut self) -> Vec<BusFault> {
+        self.bus_faults.get_mut().drain(..).collect()
+    }
 
-        tmp.reset();
+    /// Returns the callbacks invoked by the core
+    pub fn hooks_mut(&mut self) -> &mut Hooks {
+        &mut self.hooks
+    }
+
+    /// Returns the external RAM contents to be persisted, if the cartridge
+    /// has a battery.
+    pub fn battery_ram(&self) -> Option<&[u8]> {
+        self.cartridge_header
+            .battery
+            .then_some(self.banks.as_slice())
+    }
+
+    /// Restores previously persisted external RAM contents, ignored if the
+    /// cartridge has no battery. A clock footer following the RAM is loaded
+    /// too, see [`GameBoy::rtc_footer`].
+    pub fn load_battery_ram(&mut self, data: &[u8]) {
+        if self.cartridge_header.battery {
+            let len = data.len().min(self.banks.len());
+            self.banks[..len].copy_from_slice(&data[..len]);
+            self.sram_dirty.clear();
+            self.load_rtc_footer(&data[len..]);
+        }
+    }
 
-        tmp
+    /// Requests an interrupt as if it was raised by hardware, it will be
+    /// serviced once enabled in IE and IME is set.
+    ///
+    /// This lets frontends simulate events coming from outside the core,
+    /// e.g. a link partner completing a transfer or a joypad edge.
+    ///
+    /// Internally requested by:
+    /// - [`Interrupt::VBlank`]: the LCD, when it enters VBlank
+    /// - [`Interrupt::LCDStat`]: the LCD, on rising edges of the STAT line
+    ///   and on the DMG STAT write bug (see
+    ///   [`AccuracyConfig::dmg_stat_write_bug`])
+    /// - [`Interrupt::TimerOverflow`]: the timer, when TIMA overflows
+    /// - [`Interrupt::SerialTranferComplete`]: the serial port, when a
+    ///   transfer completes
+    /// - [`Interrupt::Joypad`]: the joypad, when a selected button is pressed
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt(interrupt);
     }
 }
 
@@ -71,6 +440,14 @@ impl Memory for GameBoy {
         &self.cartridge
     }
 
+    fn boot_rom_byte(&self, address: usize) -> Option<u8> {
+        self.mapped_boot_rom(address)
+    }
+
+    fn has_rumble(&self) -> bool {
+        self.cartridge_header.has_rumble()
+    }
+
     fn cartridge_mut(&mut self) -> &mut [u8] {
         &mut self.cartridge
     }
@@ -98,6 +475,131 @@ impl Memory for GameBoy {
     fn memory_mode_mut(&mut self) -> &mut MemoryMode {
         &mut self.memory_mode
     }
+
+    fn oam_dma(&self) -> &OamDma {
+        &self.oam_dma
+    }
+
+    fn oam_dma_mut(&mut self) -> &mut OamDma {
+        &mut self.oam_dma
+    }
+
+    fn accuracy(&self) -> &AccuracyConfig {
+        &self.accuracy
+    }
+
+    fn rom_written(&mut self, address: usize, value: u8, state: BankingState) {
+        if self.log_rom_writes {
+            log::debug!(
+                target: "gbemu::mbc",
+                "ROM write {:#06x} <- {:#04x} at PC {:#06x}: {} => {:?}",
+                address,
+                value,
+                self.instruction_pc,
+                self.memory_mode
+                    .register_name(address)
+                    .unwrap_or("no effect"),
+                state
+            );
+        }
+    }
+
+    fn ram_init(&self) -> RamInit {
+        if self.ram_init == RamInit::Random {
+            self.nondeterministic(Nondeterminism::RandomRam);
+        }
+        self.ram_init
+    }
+
+    fn sram_init(&self) -> RamInit {
+        if self.sram_init == RamInit::Random && !self.banks.is_empty() {
+            self.nondeterministic(Nondeterminism::RandomSram);
+        }
+        self.sram_init
+    }
+
+    fn disabled_ram_value(&self) -> u8 {
+        self.disabled_ram_value
+    }
+
+    fn bus_latch(&self) -> u8 {
+        self.bus_latch.get()
+    }
+
+    fn drive_bus(&self, value: u8) {
+        self.bus_latch.set(value);
+    }
+
+    #[cfg(feature = "debugger")]
+    fn frozen(&self, address: usize) -> Option<u8> {
+        let address = address as u16;
+        self.debugger.frozen(address, || self.mapped_bank(address))
+    }
+
+    #[cfg(feature = "debugger")]
+    fn watched(&self, address: usize) -> bool {
+        let address = address as u16;
+        self.debugger.watched(address, || self.mapped_bank(address))
+    }
+
+    #[cfg(feature = "debugger")]
+    fn watch_hit(&mut self, address: usize, old: u8, new: u8) {
+        self.record_watch_hit(address, old, new);
+    }
+
+    fn io_read(&self, address: usize) -> Option<u8> {
+        let read = self.io.get(address)?.read?;
+        Some(read(self, address))
+    }
+
+    fn io_write(&mut self, address: usize, value: u8) -> bool {
+        match self.io.get(address).and_then(|handler| handler.write) {
+            Some(write) => {
+                write(self, address, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn bus_fault(&self, fault: BusFault) {
+        log::warn!(target: "gbemu::bus", "Bus fault at PC {:#06x}: {}", *self.registers.pc, fault);
+        let mut faults = self.bus_faults.borrow_mut();
+        if faults.len() == MAX_BUS_FAULTS {
+            faults.pop_front();
+        }
+        faults.push_back(fault);
+    }
+
+    fn accessed(&self, address: usize, write: bool) {
+        self.lint_access(address, write);
+        self.trace_dma_access(address, write);
+        self.report_dma_access(address, write);
+    }
+
+    fn rtc_latch(&mut self) {
+        self.latch_rtc();
+    }
+
+    fn sram_written(&mut self, index: usize) {
+        self.sram_dirty.mark(index);
+    }
+
+    fn sram_disabled(&mut self) {
+        self.sram_dirty.disabled();
+    }
+
+    fn rtc_write(&mut self, register: u8, value: u8) {
+        self.write_rtc(register, value);
+    }
+
+    fn bank_switched(&mut self, state: BankingState) {
+        log::trace!(target: "gbemu::mbc", "Bank switch: {:?}", state);
+        self.log_bank_switch(state);
+        if let Some(hook) = self.hooks.on_bank_switch.as_mut() {
+            hook(state);
+        }
+    }
 }
 
 impl Read for GameBoy {}
@@ -0,0 +1,123 @@
+//! Optional diagnostics for homebrew developers, flagging accesses that work
+//! in emulators but are wrong or fragile on hardware, like BGB's exceptions.
+//!
+//! Once enabled, each offending access is reported once per instruction
+//! address, logged as a warning and kept until [`GameBoy::take_lints`]:
+//!
+//! - [`LintKind::EchoRam`]: echo RAM (0xE000..=0xFDFF) is read or written,
+//!   it mirrors WRAM but Nintendo forbids using it
+//! - [`LintKind::WriteOnlyRead`]: a write-only register is read, hardware
+//!   returns 0xFF rather than the value written
+//! - [`LintKind::VramWriteInMode3`]: VRAM is written while the PPU is
+//!   drawing, the write is dropped on hardware
+//!
+//! The SM83 has no alignment requirements, 16-bit accesses are never flagged.
+//!
+//! ```
+//! use gbemu::{cartridge, GameBoy};
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! gb.set_lints(true);
+//! gb.run_frame();
+//! for lint in gb.take_lints() {
+//!     println!("{}", lint);
+//! }
+//! ```
+use std::{cell::RefCell, collections::HashSet};
+
+use crate::{memory::locations, GameBoy};
+
+/// Registers returning 0xFF instead of the value written
+const WRITE_ONLY_REGISTERS: [usize; 5] = [
+    locations::NR13,
+    locations::NR23,
+    locations::NR31,
+    locations::NR33,
+    locations::NR41,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    EchoRam,
+    WriteOnlyRead,
+    VramWriteInMode3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An access flagged by the lints
+pub struct Lint {
+    /// Address of the instruction making the access
+    pub pc: u16,
+    pub address: u16,
+    pub kind: LintKind,
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = match self.kind {
+            LintKind::EchoRam => "echo RAM access",
+            LintKind::WriteOnlyRead => "read of a write-only register",
+            LintKind::VramWriteInMode3 => "VRAM write while drawing",
+        };
+        write!(f, "PC {:#06x}: {} at {:#06x}", self.pc, what, self.address)
+    }
+}
+
+#[derive(Debug, Default)]
+/// Lints found, off by default
+pub(crate) struct Linter {
+    enabled: bool,
+    /// Reported so far, each only once
+    seen: RefCell<HashSet<(LintKind, u16)>>,
+    lints: RefCell<Vec<Lint>>,
+}
+
+impl GameBoy {
+    /// Starts or stops checking the accesses, see [`lint`](self)
+    pub fn set_lints(&mut self, enabled: bool) {
+        self.linter.enabled = enabled;
+    }
+
+    pub fn lints_enabled(&self) -> bool {
+        self.linter.enabled
+    }
+
+    /// Returns the lints found since the last call, oldest first. Sites
+    /// already reported are not reported again.
+    pub fn take_lints(&mut self) -> Vec<Lint> {
+        std::mem::take(self.linter.lints.get_mut())
+    }
+
+    /// Checks a CPU access to the bus
+    pub(crate) fn lint_access(&self, address: usize, write: bool) {
+        if !self.linter.enabled {
+            return;
+        }
+
+        let kind = match address {
+            0xE000..=0xFDFF => LintKind::EchoRam,
+            _ if !write && WRITE_ONLY_REGISTERS.contains(&address) => LintKind::WriteOnlyRead,
+            #[cfg(feature = "ppu")]
+            _ if write
+                && locations::VRAM.contains(&address)
+                && self.memory[locations::LCDC] & 0x80 != 0
+                && self.lcd_mode() == crate::lcd::Mode::Drawing =>
+            {
+                LintKind::VramWriteInMode3
+            }
+            _ => return,
+        };
+
+        let pc = self.instruction_pc;
+        if !self.linter.seen.borrow_mut().insert((kind, pc)) {
+            return;
+        }
+        let lint = Lint {
+            pc,
+            address: address as u16,
+            kind,
+        };
+        log::warn!(target: "gbemu::lint", "{}", lint);
+        self.linter.lints.borrow_mut().push(lint);
+    }
+}
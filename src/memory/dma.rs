@@ -0,0 +1,215 @@
+//! OAM DMA, and its trace for debugger timing diagrams.
+//!
+//! Once [started](GameBoy::set_dma_trace), the core records every transfer
+//! and the CPU accesses made while one is active, blocked or not, until
+//! [`GameBoy::take_dma_record`]. Times are [`GameBoy::cycles`] counts; the
+//! CPU steps whole instructions, so an access is timed at the start of its
+//! instruction, and a transfer ends at the first instruction boundary past
+//! [`OAM_DMA_CYCLES`].
+//!
+//! ```
+//! use gbemu::{cartridge, GameBoy};
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! gb.set_dma_trace(true);
+//! gb.run_frame();
+//! let record = gb.take_dma_record();
+//! for access in record.accesses.iter().filter(|access| access.blocked) {
+//!     println!("PC {:#06x} blocked at {:#06x}", access.pc, access.address);
+//! }
+//! ```
+use crate::GameBoy;
+
+use super::{
+    io::{IoHandler, IoMap},
+    locations, Memory, Read,
+};
+
+/// T-cycles taken to transfer the 160 bytes of OAM
+pub const OAM_DMA_CYCLES: usize = 640;
+
+#[derive(Debug, Clone, Copy, Default)]
+/// State of the OAM DMA transfer started by writing to DMA (0xFF46)
+pub struct OamDma {
+    /// T-cycles left before the transfer completes
+    pub(crate) remaining: usize,
+    /// The transfer reads its source, not the CPU
+    pub(crate) copying: bool,
+}
+
+impl OamDma {
+    pub fn start(&mut self) {
+        self.remaining = OAM_DMA_CYCLES;
+    }
+
+    /// Advances the transfer by the given T-cycles
+    pub fn step(&mut self, cycles: usize) {
+        self.remaining = self.remaining.saturating_sub(cycles);
+    }
+
+    pub fn active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Returns true if the CPU can reach the address while the transfer is active,
+    /// only the internal bus (I/O registers and HRAM) is free.
+    pub fn accessible(address: usize) -> bool {
+        address >= locations::P1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An OAM DMA transfer, in T-cycles
+pub struct DmaTransfer {
+    /// Address of the first byte copied
+    pub source: u16,
+    pub start: u64,
+    /// When the bus is released, earlier if another transfer was started
+    pub end: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A CPU access while OAM DMA was active
+pub struct DmaAccess {
+    /// T-cycles at the start of the instruction making the access
+    pub cycle: u64,
+    /// Address of the instruction
+    pub pc: u16,
+    pub address: u16,
+    pub write: bool,
+    /// The access lost the bus to the transfer, reading 0xFF or dropping
+    /// the write, see [`AccuracyConfig::oam_dma_bus_conflicts`](crate::accuracy::AccuracyConfig::oam_dma_bus_conflicts)
+    pub blocked: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// OAM DMA activity between two [`GameBoy::take_dma_record`] calls
+pub struct DmaRecord {
+    /// T-cycles the record starts at
+    pub start: u64,
+    /// T-cycles the record ends at
+    pub end: u64,
+    /// Transfers active during the record, in order, the first and the last
+    /// may extend past it
+    pub transfers: Vec<DmaTransfer>,
+    /// CPU accesses during the transfers, in order
+    pub accesses: Vec<DmaAccess>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Activity collected since the record started
+pub(crate) struct DmaTrace {
+    start: u64,
+    transfers: Vec<DmaTransfer>,
+    accesses: Vec<DmaAccess>,
+}
+
+impl GameBoy {
+    /// Starts or stops collecting the OAM DMA activity, see
+    /// [`dma`](self)
+    pub fn set_dma_trace(&mut self, enabled: bool) {
+        let start = self.cycles();
+        *self.dma_trace.get_mut() = enabled.then(|| DmaTrace {
+            start,
+            ..Default::default()
+        });
+    }
+
+    /// Returns the activity collected since the last call, meant to be
+    /// called after every frame. Empty while not collecting.
+    pub fn take_dma_record(&mut self) -> DmaRecord {
+        let now = self.cycles();
+        let Some(trace) = self.dma_trace.get_mut() else {
+            return DmaRecord {
+                start: now,
+                end: now,
+                ..Default::default()
+            };
+        };
+
+        // A transfer still running shows in the next record too
+        let running = trace
+            .transfers
+            .last()
+            .filter(|transfer| transfer.end > now)
+            .copied();
+        let record = DmaRecord {
+            start: trace.start,
+            end: now,
+            transfers: std::mem::take(&mut trace.transfers),
+            accesses: std::mem::take(&mut trace.accesses),
+        };
+        trace.start = now;
+        trace.transfers.extend(running);
+        record
+    }
+
+    /// Records a CPU access to the bus, if a transfer is active
+    pub(crate) fn trace_dma_access(&self, address: usize, write: bool) {
+        if !self.oam_dma.active() || self.oam_dma.copying {
+            return;
+        }
+        let mut trace = self.dma_trace.borrow_mut();
+        let Some(trace) = trace.as_mut() else {
+            return;
+        };
+        trace.accesses.push(DmaAccess {
+            cycle: self.counters.cycles,
+            pc: self.instruction_pc,
+            address: address as u16,
+            write,
+            blocked: self.dma_blocked(address),
+        });
+    }
+
+    fn trace_dma_transfer(&mut self, source: usize) {
+        let now = self.cycles();
+        let Some(trace) = self.dma_trace.get_mut() else {
+            return;
+        };
+        if let Some(last) = trace.transfers.last_mut() {
+            last.end = last.end.min(now);
+        }
+        trace.transfers.push(DmaTransfer {
+            source: source as u16,
+            start: now,
+            end: now + OAM_DMA_CYCLES as u64,
+        });
+    }
+
+    /// Warns about a CPU access blocked by the transfer, see
+    /// [`AccuracyConfig::report_oam_dma_violations`](crate::accuracy::AccuracyConfig::report_oam_dma_violations)
+    pub(crate) fn report_dma_access(&self, address: usize, write: bool) {
+        if !self.accuracy.report_oam_dma_violations
+            || self.oam_dma.copying
+            || !self.dma_blocked(address)
+        {
+            return;
+        }
+        log::warn!(
+            target: "gbemu::dma",
+            "{} {:#06x} outside HRAM during OAM DMA at PC {:#06x}",
+            if write { "Writing" } else { "Reading" },
+            address,
+            self.instruction_pc
+        );
+    }
+}
+
+pub(crate) fn install_io(io: &mut IoMap) {
+    io.install(locations::DMA, IoHandler::write(start_oam_dma));
+}
+
+/// The copy itself is done at once, the bus stays blocked for its duration
+fn start_oam_dma(gb: &mut GameBoy, address: usize, value: u8) {
+    log::debug!(target: "gbemu::dma", "OAM DMA from {:#06x}", (value as usize) << 8);
+    gb.memory[address] = value;
+    let source = (value as usize) << 8;
+    gb.trace_dma_transfer(source);
+    gb.oam_dma.copying = true;
+    for (i, oam) in locations::OAM.enumerate() {
+        gb.memory[oam] = gb.read_u8(source + i);
+    }
+    gb.oam_dma.copying = false;
+    gb.oam_dma.start();
+}
@@ -0,0 +1,50 @@
+/// Maximum number of faults kept before the oldest are dropped
+pub const MAX_BUS_FAULTS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What went wrong with a bus access
+pub enum BusFaultKind {
+    /// The address does not fit in the 16-bit address space, it was wrapped
+    AddressOutOfRange,
+    /// The selected ROM bank is past the end of the cartridge, it was mirrored
+    RomBankOutOfRange { bank: usize },
+    /// The selected RAM bank is past the end of the external RAM, it was mirrored
+    RamBankOutOfRange { bank: usize },
+    /// External RAM was accessed on a cartridge without any
+    NoRam,
+    /// A non-existent RTC register is selected
+    InvalidRtcRegister(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An access the hardware would have answered in a way we can only
+/// approximate, reported instead of panicking.
+///
+/// These usually point at a bad dump, a wrong header or an emulation bug.
+pub struct BusFault {
+    /// Address as requested by the CPU
+    pub address: usize,
+    pub kind: BusFaultKind,
+}
+
+impl std::fmt::Display for BusFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            BusFaultKind::AddressOutOfRange => {
+                write!(f, "address {:#x} out of the address space", self.address)
+            }
+            BusFaultKind::RomBankOutOfRange { bank } => {
+                write!(f, "ROM bank {} out of range at {:#06x}", bank, self.address)
+            }
+            BusFaultKind::RamBankOutOfRange { bank } => {
+                write!(f, "RAM bank {} out of range at {:#06x}", bank, self.address)
+            }
+            BusFaultKind::NoRam => write!(f, "no external RAM at {:#06x}", self.address),
+            BusFaultKind::InvalidRtcRegister(register) => write!(
+                f,
+                "invalid RTC register {:#04x} at {:#06x}",
+                register, self.address
+            ),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Registry of the I/O register handlers, through which the subsystems
+//! (timer, LCD, DMA, serial, joypad, APU backend) trap the bus accesses to their
+//! registers.
+//!
+//! Registers without a handler are plain storage, as are the ones of the
+//! subsystems compiled out by their feature (except `LY`, see `STUB_LY`).
+use crate::GameBoy;
+
+use super::locations;
+
+/// Computes the value read at the address
+pub type IoRead = fn(&GameBoy, usize) -> u8;
+/// Handles a write of the value to the address, storing it is up to it
+pub type IoWrite = fn(&mut GameBoy, usize, u8);
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Traps for the accesses to one register, `None` for plain storage
+pub struct IoHandler {
+    pub read: Option<IoRead>,
+    pub write: Option<IoWrite>,
+}
+
+impl IoHandler {
+    /// Traps writes only, reads return the stored value
+    pub fn write(write: IoWrite) -> Self {
+        Self {
+            read: None,
+            write: Some(write),
+        }
+    }
+}
+
+/// LY without the LCD controller: always at the start of VBlank, so games
+/// waiting for it go on. The value other headless tools expect in traces.
+#[cfg(not(feature = "ppu"))]
+const STUB_LY: IoHandler = IoHandler {
+    read: Some(|_, _| 0x90),
+    write: Some(|_, _, _| ()),
+};
+
+/// Handlers of the I/O page (0xFF00..=0xFF7F) and of IE (0xFFFF)
+#[derive(Debug, Clone)]
+pub struct IoMap {
+    handlers: [IoHandler; 0x81],
+}
+
+impl Default for IoMap {
+    fn default() -> Self {
+        Self {
+            handlers: [IoHandler::default(); 0x81],
+        }
+    }
+}
+
+impl IoMap {
+    /// Map with the handlers of every emulated subsystem installed
+    pub(crate) fn with_subsystems() -> Self {
+        let mut io = Self::default();
+        crate::timer::install_io(&mut io);
+        #[cfg(feature = "ppu")]
+        crate::lcd::install_io(&mut io);
+        #[cfg(not(feature = "ppu"))]
+        io.install(locations::LY, STUB_LY);
+        crate::joypad::install_io(&mut io);
+        #[cfg(feature = "serial")]
+        crate::serial::install_io(&mut io);
+        #[cfg(feature = "apu")]
+        crate::backend::install_io(&mut io);
+        super::dma::install_io(&mut io);
+        crate::bootrom::install_io(&mut io);
+        io
+    }
+
+    fn index(address: usize) -> Option<usize> {
+        match address {
+            0xFF00..=0xFF7F => Some(address - 0xFF00),
+            locations::IE => Some(0x80),
+            _ => None,
+        }
+    }
+
+    /// Installs the handler of the register at `address`, replacing the
+    /// previous one
+    ///
+    /// # Panics
+    ///
+    /// If the address is not in the I/O page nor IE.
+    pub fn install(&mut self, address: usize, handler: IoHandler) {
+        let index = Self::index(address)
+            .unwrap_or_else(|| panic!("{:#06x} is not an I/O register", address));
+        self.handlers[index] = handler;
+    }
+
+    /// Returns the handler of the register at `address`, if any
+    pub fn get(&self, address: usize) -> Option<IoHandler> {
+        Self::index(address).map(|index| self.handlers[index])
+    }
+}
+
+impl GameBoy {
+    /// Installs a handler trapping the accesses to the I/O register at
+    /// `address`, e.g. to emulate a peripheral the core lacks
+    pub fn install_io_handler(&mut self, address: usize, handler: IoHandler) {
+        self.io.install(address, handler);
+    }
+}
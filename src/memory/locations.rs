@@ -29,6 +29,15 @@ pub const MASK_ROM_VERSION_NUMBER: usize = 0x014C;
 pub const COMPLEMENT_CHECK: usize = 0x014D;
 pub const CHECKSUM: RangeInclusive<usize> = 0x014E..=0x014F;
 
+/// Video RAM, tile data then the two tile maps
+pub const VRAM: RangeInclusive<usize> = 0x8000..=0x9FFF;
+/// Work RAM, mirrored at 0xE000..=0xFDFF
+pub const WRAM: RangeInclusive<usize> = 0xC000..=0xDFFF;
+/// Sprite attribute table
+pub const OAM: RangeInclusive<usize> = 0xFE00..=0xFE9F;
+/// High RAM
+pub const HRAM: RangeInclusive<usize> = 0xFF80..=0xFFFE;
+
 /// Register for reading joy pad info and determining system type.
 pub const P1: usize = 0xFF00;
 /// Serial transfer data
@@ -95,7 +104,7 @@ pub const NR24: usize = 0xFF19;
 /// Sound Mode 3 register, sound on/off
 pub const NR30: usize = 0xFF1A;
 /// Sound Mode 3 register, sound length
-pub const NR31: usize = 0xFF1A;
+pub const NR31: usize = 0xFF1B;
 /// Sound Mode 3 register, select output level
 pub const NR32: usize = 0xFF1C;
 /// Sound Mode 3 register, frequency lo
@@ -192,6 +201,21 @@ pub const WY: usize = 0xFF4A;
 /// 0 <= WX <= 166
 pub const WX: usize = 0xFF4B;
 
+/// Prepare speed switch (CGB only)
+///
+/// - Bit 7: Current speed (0 = Normal, 1 = Double)
+/// - Bit 0: Switch armed (0 = No, 1 = Yes)
+///
+/// The switch is performed by executing STOP while bit 0 is set.
+pub const KEY1: usize = 0xFF4D;
+
+/// Boot ROM mapping
+///
+/// - Bit 0: Unmapped (0 = Boot ROM over the cartridge, 1 = Cartridge)
+///
+/// Set by the boot ROM when handing over, it can't be cleared.
+pub const BOOT: usize = 0xFF50;
+
 /// Interrupt Enable
 ///
 /// - Bit 4: Transition from High to Low of Pin number P10-P13.
@@ -203,4 +227,67 @@ pub const WX: usize = 0xFF4B;
 /// Values
 /// - 0: disable
 /// - 1: enable
-pub const IE: usize = 0xFF40;
+pub const IE: usize = 0xFFFF;
+
+/// Names of the memory-mapped I/O registers, in address order
+pub const IO_REGISTERS: &[(usize, &str)] = &[
+    (P1, "P1"),
+    (SB, "SB"),
+    (SC, "SC"),
+    (DIV, "DIV"),
+    (TIMA, "TIMA"),
+    (TMA, "TMA"),
+    (TAC, "TAC"),
+    (IF, "IF"),
+    (NR10, "NR10"),
+    (NR11, "NR11"),
+    (NR12, "NR12"),
+    (NR13, "NR13"),
+    (NR14, "NR14"),
+    (NR21, "NR21"),
+    (NR22, "NR22"),
+    (NR23, "NR23"),
+    (NR24, "NR24"),
+    (NR30, "NR30"),
+    (NR31, "NR31"),
+    (NR32, "NR32"),
+    (NR33, "NR33"),
+    (NR34, "NR34"),
+    (NR41, "NR41"),
+    (NR42, "NR42"),
+    (NR43, "NR43"),
+    (NR44, "NR44"),
+    (NR50, "NR50"),
+    (NR51, "NR51"),
+    (NR52, "NR52"),
+    (LCDC, "LCDC"),
+    (STAT, "STAT"),
+    (SCY, "SCY"),
+    (SCX, "SCX"),
+    (LY, "LY"),
+    (LYC, "LYC"),
+    (DMA, "DMA"),
+    (BGP, "BGP"),
+    (OBP0, "OBP0"),
+    (OBP1, "OBP1"),
+    (WY, "WY"),
+    (WX, "WX"),
+    (KEY1, "KEY1"),
+    (IE, "IE"),
+];
+
+/// Returns the name of the I/O register at `address`, e.g. `STAT` for 0xFF41
+pub fn name(address: usize) -> Option<&'static str> {
+    IO_REGISTERS
+        .binary_search_by_key(&address, |(address, _)| *address)
+        .ok()
+        .map(|index| IO_REGISTERS[index].1)
+}
+
+/// Returns the address of the I/O register named `name`, in any case
+pub fn by_name(name: &str) -> Option<usize> {
+    IO_REGISTERS
+        .iter()
+        .find(|(_, register)| register.eq_ignore_ascii_case(name))
+        .map(|(address, _)| *address)
+}
@@ -1,17 +1,77 @@
-use crate::{cartridge::CartridgeType, RAM_BANK_SIZE};
+use crate::{
+    accuracy::AccuracyConfig,
+    cartridge::{CartridgeFeatures, CartridgeType, Mapper},
+    RAM_BANK_SIZE, ROM_BANK_SIZE,
+};
 
+use self::{
+    dma::OamDma,
+    fault::{BusFault, BusFaultKind},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub mod dma;
+pub mod fault;
+pub mod io;
 pub mod locations;
+pub mod view;
+
+/// Value read from unmapped areas when the bus floats high
+pub const OPEN_BUS_VALUE: u8 = 0xFF;
+
+/// Value usually read from disabled external RAM, the bus is left floating high
+pub const DISABLED_RAM_VALUE: u8 = 0xFF;
+
+/// ROM banks addressable by a plain MBC3
+const MBC3_ROM_BANKS: usize = 0x80;
+/// RAM banks addressable by a plain MBC3
+const MBC3_RAM_BANKS: usize = 0x04;
+/// ROM banks past which the MBC1 upper bits reach the ROM, smaller
+/// cartridges leave them unconnected
+const MBC1_SMALL_ROM_BANKS: usize = 0x20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Contents of a RAM at power on
+pub enum RamInit {
+    /// All bits cleared
+    Zeros,
+    /// All bits set
+    Ones,
+    /// Pseudo-random contents, reproducible from the seed
+    Seeded(u64),
+    /// Pseudo-random contents, different at every power on
+    #[default]
+    Random,
+}
+
+impl RamInit {
+    /// Fills the RAM with the power on contents
+    pub fn fill(self, ram: &mut [u8]) {
+        match self {
+            Self::Zeros => ram.fill(0x00),
+            Self::Ones => ram.fill(0xFF),
+            Self::Seeded(seed) => StdRng::seed_from_u64(seed).fill(ram),
+            Self::Random => rand::thread_rng().fill(ram),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 /// Indicates how the controller should behave
 pub enum MemoryMode {
     RomOnly,
+    /// Straight external RAM at 0xA000..=0xBFFF, no banking
+    RomRam,
     MBC1 {
+        /// ROM bank mapped at 0x4000..=0x7FFF, the upper bits included
         rom_bank_idx: usize,
+        /// 2 bits written to 0x4000..=0x5FFF, the upper bits of the ROM bank
+        /// and, in mode 1, the RAM bank
         ram_bank_idx: usize,
         ram_enabled: bool,
-        /// If true address 0x4000..=0x5FFF selects ram bank,
-        /// select upper bits of ROM bank otherwise
+        /// Mode 1, the 2 bits also select the RAM bank and the ROM bank
+        /// mapped at 0x0000..=0x3FFF. Both are bank 0 in mode 0.
         ram_banking: bool,
     },
     MBC2 {
@@ -25,7 +85,8 @@ pub enum MemoryMode {
         /// If true address 0xA000..=0xBFFF points to RTC registers,
         /// points to ram bank otherwise
         rtc_selected: Option<u8>,
-        /// If true RTC registers are latched (don't update)
+        /// Last bit written to the latch register, the clock is latched into
+        /// the registers below on a 0 to 1 transition
         rtc_latched: bool,
         /// Seconds register for RTC
         rtc_seconds: u8,
@@ -33,7 +94,7 @@ pub enum MemoryMode {
         rtc_minutes: u8,
         /// Hours register for RTC
         rtc_hours: u8,
-        /// Days register for RTC
+        /// Days register for RTC, the low byte then the high register:
         ///
         /// - Bit 0: MSB of day counter
         /// - Bit 6: Halt RTC (0 = Active, 1 = Halt)
@@ -44,25 +105,27 @@ pub enum MemoryMode {
         rom_bank_idx: usize,
         ram_bank_idx: usize,
         ram_enabled: bool,
+        /// Rumble motor running, on cartridges having one
         rumble_enabled: bool,
     },
 }
 
 impl From<CartridgeType> for MemoryMode {
     fn from(value: CartridgeType) -> Self {
-        match value {
-            CartridgeType::RomOnly => Self::RomOnly,
-            CartridgeType::MBC1 => Self::MBC1 {
+        match value.mapper {
+            Mapper::RomOnly if value.features.contains(CartridgeFeatures::RAM) => Self::RomRam,
+            Mapper::RomOnly => Self::RomOnly,
+            Mapper::MBC1 => Self::MBC1 {
                 rom_bank_idx: 1,
                 ram_bank_idx: 0,
                 ram_enabled: false,
-                ram_banking: true,
+                ram_banking: false,
             },
-            CartridgeType::MBC2 => Self::MBC2 {
+            Mapper::MBC2 => Self::MBC2 {
                 rom_bank_idx: 1,
                 ram_enabled: false,
             },
-            CartridgeType::MBC3 => Self::MBC3 {
+            Mapper::MBC3 => Self::MBC3 {
                 rom_bank_idx: 1,
                 ram_bank_idx: 0,
                 ram_rtc_enabled: false,
@@ -73,19 +136,127 @@ impl From<CartridgeType> for MemoryMode {
                 rtc_hours: 0,
                 rtc_days: 0,
             },
-            CartridgeType::MBC5 => Self::MBC5 {
+            Mapper::MBC5 => Self::MBC5 {
                 rom_bank_idx: 1,
                 ram_bank_idx: 0,
                 ram_enabled: false,
                 rumble_enabled: false,
             },
-            CartridgeType::NotSupported | CartridgeType::Unknown => {
+            Mapper::NotSupported | Mapper::Unknown => {
                 panic!("Unsupported cartridge type")
             }
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Snapshot of the memory bank controller registers
+pub struct BankingState {
+    /// ROM bank mapped at 0x0000..=0x3FFF, not always bank 0 on large MBC1
+    /// cartridges
+    pub rom_bank0: usize,
+    /// ROM bank mapped at 0x4000..=0x7FFF
+    pub rom_bank: usize,
+    /// RAM bank mapped at 0xA000..=0xBFFF
+    pub ram_bank: usize,
+    /// External RAM (and RTC) access enabled
+    pub ram_enabled: bool,
+    /// MBC1 banking mode, true in mode 1 when 0x4000..=0x5FFF also selects
+    /// the RAM bank and the ROM bank at 0x0000..=0x3FFF
+    pub ram_banking: Option<bool>,
+    /// MBC3 RTC register mapped at 0xA000..=0xBFFF instead of RAM
+    pub rtc_selected: Option<u8>,
+    /// MBC5 rumble motor running
+    pub rumble: bool,
+}
+
+impl MemoryMode {
+    /// Returns the banking state as held by the registers. The banks mapped
+    /// also depend on the cartridge sizes, see [`Memory::banking_state`].
+    pub fn banking_state(&self) -> BankingState {
+        let mut state = BankingState {
+            rom_bank0: 0,
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: false,
+            ram_banking: None,
+            rtc_selected: None,
+            rumble: false,
+        };
+
+        match *self {
+            MemoryMode::RomOnly => (),
+            MemoryMode::RomRam => state.ram_enabled = true,
+            MemoryMode::MBC1 {
+                rom_bank_idx,
+                ram_bank_idx,
+                ram_enabled,
+                ram_banking,
+            } => {
+                if ram_banking {
+                    state.ram_bank = ram_bank_idx;
+                }
+                state.rom_bank = rom_bank_idx;
+                state.ram_enabled = ram_enabled;
+                state.ram_banking = Some(ram_banking);
+            }
+            MemoryMode::MBC2 {
+                rom_bank_idx,
+                ram_enabled,
+            } => {
+                state.rom_bank = rom_bank_idx;
+                state.ram_enabled = ram_enabled;
+            }
+            MemoryMode::MBC3 {
+                rom_bank_idx,
+                ram_bank_idx,
+                ram_rtc_enabled,
+                rtc_selected,
+                ..
+            } => {
+                state.rom_bank = rom_bank_idx;
+                state.ram_bank = ram_bank_idx;
+                state.ram_enabled = ram_rtc_enabled;
+                state.rtc_selected = rtc_selected;
+            }
+            MemoryMode::MBC5 {
+                rom_bank_idx,
+                ram_bank_idx,
+                ram_enabled,
+                rumble_enabled,
+            } => {
+                state.rom_bank = rom_bank_idx;
+                state.ram_bank = ram_bank_idx;
+                state.ram_enabled = ram_enabled;
+                state.rumble = rumble_enabled;
+            }
+        }
+
+        state
+    }
+
+    /// Returns the name of the MBC register mapped at a ROM address
+    pub fn register_name(&self, address: usize) -> Option<&'static str> {
+        match (self, address) {
+            (MemoryMode::RomOnly | MemoryMode::RomRam, _) => None,
+            (MemoryMode::MBC1 { .. }, 0x0000..=0x1FFF) => Some("RAM enable"),
+            (MemoryMode::MBC1 { .. }, 0x2000..=0x3FFF) => Some("ROM bank"),
+            (MemoryMode::MBC1 { .. }, 0x4000..=0x5FFF) => Some("RAM bank/ROM bank upper bits"),
+            (MemoryMode::MBC1 { .. }, 0x6000..=0x7FFF) => Some("Banking mode"),
+            (MemoryMode::MBC2 { .. }, 0x0000..=0x3FFF) => Some("RAM enable/ROM bank"),
+            (MemoryMode::MBC3 { .. }, 0x0000..=0x1FFF) => Some("RAM/RTC enable"),
+            (MemoryMode::MBC3 { .. }, 0x2000..=0x3FFF) => Some("ROM bank"),
+            (MemoryMode::MBC3 { .. }, 0x4000..=0x5FFF) => Some("RAM bank/RTC register"),
+            (MemoryMode::MBC3 { .. }, 0x6000..=0x7FFF) => Some("Latch clock data"),
+            (MemoryMode::MBC5 { .. }, 0x0000..=0x1FFF) => Some("RAM enable"),
+            (MemoryMode::MBC5 { .. }, 0x2000..=0x2FFF) => Some("ROM bank lower bits"),
+            (MemoryMode::MBC5 { .. }, 0x3000..=0x3FFF) => Some("ROM bank upper bit"),
+            (MemoryMode::MBC5 { .. }, 0x4000..=0x5FFF) => Some("RAM bank/Rumble"),
+            _ => None,
+        }
+    }
+}
+
 pub trait Memory {
     /// Returns a slice of the entire memory (0x0000..0xFFFF)
     fn memory(&self) -> &[u8; 0x10000];
@@ -102,10 +273,25 @@ pub trait Memory {
     /// Returns a mutable slice of the RAM
     fn ram_mut(&mut self) -> &mut [u8];
 
+    /// Returns the ROM bank mapped at 0x0000..=0x3FFF, not always bank 0 on
+    /// large MBC1 cartridges
+    fn rom_bank0_idx(&self) -> usize {
+        match self.memory_mode() {
+            MemoryMode::MBC1 {
+                ram_bank_idx,
+                ram_banking: true,
+                ..
+            } if self.mbc1_large_rom() => ram_bank_idx << 5,
+            _ => 0,
+        }
+    }
     /// Returns the current ROM bank
     fn rom_bank_idx(&self) -> usize {
         match self.memory_mode() {
-            MemoryMode::RomOnly => 1,
+            MemoryMode::RomOnly | MemoryMode::RomRam => 1,
+            MemoryMode::MBC1 { rom_bank_idx, .. } if !self.mbc1_large_rom() => {
+                rom_bank_idx & 0b11111
+            }
             MemoryMode::MBC1 { rom_bank_idx, .. } => rom_bank_idx,
             MemoryMode::MBC2 { rom_bank_idx, .. } => rom_bank_idx,
             MemoryMode::MBC3 { rom_bank_idx, .. } => rom_bank_idx,
@@ -115,8 +301,13 @@ pub trait Memory {
     /// Returns the current RAM bank
     fn ram_bank_idx(&self) -> usize {
         match self.memory_mode() {
-            MemoryMode::RomOnly => 0,
-            MemoryMode::MBC1 { ram_bank_idx, .. } => ram_bank_idx,
+            MemoryMode::RomOnly | MemoryMode::RomRam => 0,
+            MemoryMode::MBC1 {
+                ram_bank_idx,
+                ram_banking: true,
+                ..
+            } if self.ram().len() > RAM_BANK_SIZE => ram_bank_idx,
+            MemoryMode::MBC1 { .. } => 0,
             MemoryMode::MBC2 { .. } => 0,
             MemoryMode::MBC3 { ram_bank_idx, .. } => ram_bank_idx,
             MemoryMode::MBC5 { ram_bank_idx, .. } => ram_bank_idx,
@@ -125,46 +316,235 @@ pub trait Memory {
 
     fn memory_mode(&self) -> MemoryMode;
     fn memory_mode_mut(&mut self) -> &mut MemoryMode;
+
+    /// Returns the current banking state, with the banks the reads are
+    /// served from
+    fn banking_state(&self) -> BankingState {
+        BankingState {
+            rom_bank0: self.rom_bank0_idx(),
+            rom_bank: self.rom_bank_idx(),
+            ram_bank: self.ram_bank_idx(),
+            ..self.memory_mode().banking_state()
+        }
+    }
+
+    /// Returns true if the MBC1 upper bits select ROM banks, on cartridges
+    /// of 1 MiB or more. Only their RAM is banked otherwise.
+    fn mbc1_large_rom(&self) -> bool {
+        self.cartridge().len() > MBC1_SMALL_ROM_BANKS * ROM_BANK_SIZE
+    }
+
+    /// Returns true if the MBC3 is the MBC30 variant, which has 256 ROM banks
+    /// and 8 RAM banks. It is told apart by the cartridge sizes.
+    fn mbc30(&self) -> bool {
+        matches!(self.memory_mode(), MemoryMode::MBC3 { .. })
+            && (self.cartridge().len() > MBC3_ROM_BANKS * ROM_BANK_SIZE
+                || self.ram().len() > MBC3_RAM_BANKS * RAM_BANK_SIZE)
+    }
+
+    /// Returns true if the cartridge has a rumble motor, driven by bit 3 of
+    /// the MBC5 RAM bank register instead of the bank
+    fn has_rumble(&self) -> bool {
+        false
+    }
+
+    /// Returns the power on contents of the internal RAM
+    fn ram_init(&self) -> RamInit {
+        RamInit::default()
+    }
+
+    /// Returns the power on contents of the external (cartridge) RAM
+    fn sram_init(&self) -> RamInit {
+        RamInit::default()
+    }
+
+    /// Returns the value read from external RAM (and RTC registers) while disabled
+    fn disabled_ram_value(&self) -> u8 {
+        DISABLED_RAM_VALUE
+    }
+
+    /// Returns the last value driven on the data bus
+    fn bus_latch(&self) -> u8 {
+        OPEN_BUS_VALUE
+    }
+
+    /// Records the value driven on the data bus by an access
+    fn drive_bus(&self, _value: u8) {}
+
+    /// Returns the value read from unmapped areas, nothing drives the bus so
+    /// it either keeps the last value or floats high depending on accuracy
+    fn open_bus(&self) -> u8 {
+        if self.accuracy().open_bus_latch {
+            self.bus_latch()
+        } else {
+            OPEN_BUS_VALUE
+        }
+    }
+
+    /// Returns the OAM DMA transfer state
+    fn oam_dma(&self) -> &OamDma;
+    /// Returns the mutable OAM DMA transfer state
+    fn oam_dma_mut(&mut self) -> &mut OamDma;
+
+    /// Returns the accuracy toggles
+    fn accuracy(&self) -> &AccuracyConfig;
+
+    /// Returns true if a CPU access to the address is blocked by OAM DMA
+    fn dma_blocked(&self, address: usize) -> bool {
+        self.accuracy().oam_dma_bus_conflicts
+            && self.oam_dma().active()
+            && !OamDma::accessible(address)
+    }
+
+    /// Called after a write to the MBC registers changed the banking state
+    fn bank_switched(&mut self, _state: BankingState) {}
+
+    /// Called after every write to the ROM region (0x0000..=0x7FFF)
+    fn rom_written(&mut self, _address: usize, _value: u8, _state: BankingState) {}
+
+    /// Returns the byte of the boot ROM mapped over the cartridge at the address
+    fn boot_rom_byte(&self, _address: usize) -> Option<u8> {
+        None
+    }
+
+    /// Returns the value the address is frozen to, reads return it and writes are dropped
+    fn frozen(&self, _address: usize) -> Option<u8> {
+        None
+    }
+
+    /// Returns true if changes to the address should be reported to [`Memory::watch_hit`]
+    fn watched(&self, _address: usize) -> bool {
+        false
+    }
+
+    /// Called when a write changes the value read at a watched address
+    fn watch_hit(&mut self, _address: usize, _old: u8, _new: u8) {}
+
+    /// Returns the value of the I/O register if a subsystem traps its reads
+    fn io_read(&self, _address: usize) -> Option<u8> {
+        None
+    }
+
+    /// Returns true if a subsystem trapped the write to the I/O register,
+    /// otherwise it is stored as is
+    fn io_write(&mut self, _address: usize, _value: u8) -> bool {
+        false
+    }
+
+    /// Called when the MBC3 clock registers are latched
+    fn rtc_latch(&mut self) {}
+
+    /// Called with writes to the MBC3 clock register selected (0x08..=0x0C)
+    fn rtc_write(&mut self, _register: u8, _value: u8) {}
+
+    /// Called after a write to the external RAM at `index` in [`Memory::ram`]
+    fn sram_written(&mut self, _index: usize) {}
+
+    /// Called after a write to the MBC registers disabled the external RAM
+    fn sram_disabled(&mut self) {}
+
+    /// Called when an access could not be served as requested
+    fn bus_fault(&self, _fault: BusFault) {}
+
+    /// Called with every read and write of the bus, before it is served
+    fn accessed(&self, _address: usize, _write: bool) {}
+
+    /// Returns the index in the cartridge of the address in the given ROM bank,
+    /// mirroring banks past the end of the cartridge
+    fn rom_index(&self, address: usize, bank: usize) -> Option<usize> {
+        let len = self.cartridge().len();
+        let index = bank * ROM_BANK_SIZE + (address & (ROM_BANK_SIZE - 1));
+        if index < len {
+            return Some(index);
+        }
+        // No cartridge inserted
+        if len == 0 {
+            return None;
+        }
+
+        self.bus_fault(BusFault {
+            address,
+            kind: BusFaultKind::RomBankOutOfRange { bank },
+        });
+        Some(index % len)
+    }
+
+    /// Returns the index in the external RAM of the address in the given RAM bank,
+    /// mirroring banks past the end of the RAM
+    fn ram_index(&self, address: usize, bank: usize) -> Option<usize> {
+        let len = self.ram().len();
+        let index = bank * RAM_BANK_SIZE + (address & (RAM_BANK_SIZE - 1));
+        if index < len {
+            return Some(index);
+        }
+
+        self.bus_fault(BusFault {
+            address,
+            kind: if len == 0 {
+                BusFaultKind::NoRam
+            } else {
+                BusFaultKind::RamBankOutOfRange { bank }
+            },
+        });
+        (len > 0).then(|| index % len)
+    }
+
+    /// Wraps the address in the 16-bit address space
+    fn wrap_address(&self, address: usize) -> usize {
+        if address > 0xFFFF {
+            self.bus_fault(BusFault {
+                address,
+                kind: BusFaultKind::AddressOutOfRange,
+            });
+        }
+        address & 0xFFFF
+    }
 }
 
 pub trait Read: Memory {
     fn read_u8(&self, address: usize) -> u8 {
-        match address {
-            // Read from ROM Bank 0
-            0x0000..=0x3FFF => self.cartridge()[address],
+        let address = self.wrap_address(address);
+        self.accessed(address, false);
+        if self.dma_blocked(address) {
+            return 0xFF;
+        }
+        if let Some(value) = self.frozen(address) {
+            self.drive_bus(value);
+            return value;
+        }
+
+        let read_rom = |bank| {
+            self.rom_index(address, bank)
+                .map_or_else(|| self.open_bus(), |index| self.cartridge()[index])
+        };
+        let read_ram = |bank| {
+            self.ram_index(address, bank)
+                .map_or_else(|| self.open_bus(), |index| self.ram()[index])
+        };
+
+        let value = match address {
+            // Read from ROM Bank 0, unless the boot ROM is mapped over it
+            0x0000..=0x3FFF => self
+                .boot_rom_byte(address)
+                .unwrap_or_else(|| read_rom(self.rom_bank0_idx())),
             // Read from ROM Bank
-            0x4000..=0x7FFF => {
-                self.cartridge()[address - 0x4000 + (self.rom_bank_idx() * crate::ROM_BANK_SIZE)]
-            }
+            0x4000..=0x7FFF => read_rom(self.rom_bank_idx()),
             // Read from RAM Bank
             0xA000..=0xBFFF => match self.memory_mode() {
-                MemoryMode::MBC1 {
-                    ram_bank_idx,
-                    ram_enabled,
-                    ..
-                }
-                | MemoryMode::MBC5 {
-                    ram_bank_idx,
-                    ram_enabled,
-                    ..
-                } => {
+                MemoryMode::MBC1 { ram_enabled, .. } | MemoryMode::MBC5 { ram_enabled, .. } => {
                     if ram_enabled {
-                        self.ram()[address - 0xA000 + (ram_bank_idx * crate::RAM_BANK_SIZE)]
+                        read_ram(self.ram_bank_idx())
                     } else {
-                        0
+                        self.disabled_ram_value()
                     }
                 }
                 MemoryMode::MBC2 { ram_enabled, .. } => {
                     if ram_enabled {
-                        let address = address - 0xA000;
-                        let address = match address {
-                            0xA000..=0xA1FF => address,
-                            0xA200..=0xBFFF => address & 0x1FF,
-                            _ => unreachable!(),
-                        };
-                        self.ram()[address]
+                        // Only 512 half-bytes, mirrored across the whole region
+                        self.ram_index(address & 0x1FF, 0)
+                            .map_or_else(|| self.open_bus(), |index| self.ram()[index])
                     } else {
-                        0
+                        self.disabled_ram_value()
                     }
                 }
                 MemoryMode::MBC3 {
@@ -185,26 +565,40 @@ pub trait Read: Memory {
                                 0x0A => rtc_hours,
                                 0x0B => (rtc_days & 0xFF) as u8,
                                 0x0C => (rtc_days >> 8) as u8,
-                                _ => unreachable!(),
+                                _ => {
+                                    self.bus_fault(BusFault {
+                                        address,
+                                        kind: BusFaultKind::InvalidRtcRegister(selected),
+                                    });
+                                    self.open_bus()
+                                }
                             }
                         } else {
-                            self.ram()[address - 0xA000 + (ram_bank_idx * crate::RAM_BANK_SIZE)]
+                            read_ram(ram_bank_idx)
                         }
                     } else {
-                        0
+                        self.disabled_ram_value()
                     }
                 }
-                _ => self.ram()[address - 0xA000 + (self.ram_bank_idx() * crate::RAM_BANK_SIZE)],
+                _ => read_ram(self.ram_bank_idx()),
             },
             // Echo RAM
             0xE000..=0xFDFF => self.memory()[address - 0x2000],
+            // Unusable
+            0xFEA0..=0xFEFF => return self.open_bus(),
+            0xFF00..=0xFF7F | locations::IE => self
+                .io_read(address)
+                .unwrap_or_else(|| self.memory()[address]),
             _ => self.memory()[address],
-        }
+        };
+        self.drive_bus(value);
+
+        value
     }
 
     fn read_u16(&self, address: usize) -> u16 {
         let lower = self.read_u8(address) as u16;
-        let upper = self.read_u8(address + 1) as u16;
+        let upper = self.read_u8(address.wrapping_add(1) & 0xFFFF) as u16;
         (upper << 8) | lower
     }
 
@@ -213,11 +607,41 @@ pub trait Read: Memory {
     }
 }
 
-pub trait Write: Memory {
+pub trait Write: Read {
     fn write_u8(&mut self, address: usize, value: u8) {
+        let address = self.wrap_address(address);
+        self.accessed(address, true);
+        if self.frozen(address).is_some() {
+            return;
+        }
+
+        if self.watched(address) {
+            let old = self.read_u8(address);
+            self.store_u8(address, value);
+            let new = self.read_u8(address);
+            if old != new {
+                self.watch_hit(address, old, new);
+            }
+        } else {
+            self.store_u8(address, value);
+        }
+    }
+
+    /// Performs a write on the bus, with all its side effects
+    fn store_u8(&mut self, address: usize, value: u8) {
+        if self.dma_blocked(address) {
+            return;
+        }
+        self.drive_bus(value);
+
+        let banking_state = self.banking_state();
+        let mbc30 = self.mbc30();
+        let has_rumble = self.has_rumble();
+        let mut latch_clock = false;
+
         // Handle MBC Registers
         match self.memory_mode_mut() {
-            MemoryMode::RomOnly => (),
+            MemoryMode::RomOnly | MemoryMode::RomRam => (),
             MemoryMode::MBC1 {
                 rom_bank_idx,
                 ram_bank_idx,
@@ -226,19 +650,15 @@ pub trait Write: Memory {
             } => match address {
                 // Ram enable
                 0x0000..=0x1FFF => *ram_enabled = value & 0b1111 == 0b1010,
-                // Rom bank select
+                // Rom bank select, 0 selects 1 even with upper bits set
                 0x2000..=0x3FFF => {
-                    let bank = value & 0b11111;
-                    *rom_bank_idx = if bank == 0 { 1 } else { bank as usize };
+                    let bank = value as usize & 0b11111;
+                    *rom_bank_idx = (*ram_bank_idx << 5) + if bank == 0 { 1 } else { bank };
                 }
-                // Ram bank select or upper bits of rom bank select
+                // Upper bits of rom bank select, and ram bank select in mode 1
                 0x4000..=0x5FFF => {
-                    let bank = value as usize & 0b11;
-                    if *ram_banking {
-                        *ram_bank_idx = bank;
-                    } else {
-                        *rom_bank_idx = (bank << 5) + (*rom_bank_idx & 0b11111);
-                    }
+                    *ram_bank_idx = value as usize & 0b11;
+                    *rom_bank_idx = (*ram_bank_idx << 5) + (*rom_bank_idx & 0b11111);
                 }
                 // Rom/Ram banking mode select
                 0x6000..=0x7FFF => *ram_banking = value & 0b1 == 0b1,
@@ -269,23 +689,26 @@ pub trait Write: Memory {
             } => match address {
                 // Ram enable/Rom bank select
                 0x0000..=0x1FFF => *ram_rtc_enabled = value & 0b1111 == 0b1010,
-                // Rom bank select
+                // Rom bank select, MBC30 decodes all 8 bits
                 0x2000..=0x3FFF => {
-                    let bank = value & 0b1111111;
+                    let bank = if mbc30 { value } else { value & 0b1111111 };
                     *rom_bank_idx = if bank == 0 { 1 } else { bank as usize };
                 }
-                // Ram bank select or RTC register select
+                // Ram bank select or RTC register select, MBC30 has 8 RAM banks
                 0x4000..=0x5FFF => match value {
-                    0x00..=0x03 => {
-                        let bank = value as usize & 0b11;
-                        *ram_bank_idx = bank;
+                    0x00..=0x07 if mbc30 || value <= 0x03 => {
+                        *ram_bank_idx = value as usize;
                         *rtc_selected = None
                     }
                     0x08..=0x0C => *rtc_selected = Some(value),
                     _ => (),
                 },
-                // Latch clock data
-                0x6000..=0x7FFF => *rtc_latched = value & 0b1 == 0b1,
+                // Latch clock data, on a 0 to 1 transition
+                0x6000..=0x7FFF => {
+                    let latch = value & 0b1 == 0b1;
+                    latch_clock = latch && !*rtc_latched;
+                    *rtc_latched = latch;
+                }
                 _ => (),
             },
             MemoryMode::MBC5 {
@@ -296,43 +719,60 @@ pub trait Write: Memory {
             } => match address {
                 // Ram enable
                 0x0000..=0x1FFF => *ram_enabled = value & 0b1111 == 0b1010,
-                // Rom bank select lower 8 bits
+                // Rom bank select lower 8 bits, keeping the upper bit. Unlike
+                // the other MBCs, bank 0 can be mapped at 0x4000
                 0x2000..=0x2FFF => {
-                    let bank = value as usize;
-                    *rom_bank_idx = if bank == 0 { 1 } else { bank };
+                    *rom_bank_idx = (*rom_bank_idx & 0x100) + value as usize;
                 }
                 // Rom bank select upper bit
                 0x3000..=0x3FFF => {
                     let bank = value as usize & 0b1;
                     *rom_bank_idx = (bank << 8) + (*rom_bank_idx & 0b11111111);
                 }
-                // Ram bank select
+                // Ram bank select, bit 3 drives the motor on rumble carts
                 0x4000..=0x5FFF => {
-                    // TODO: Check if mask is wrong
-                    *ram_bank_idx = value as usize & 0b1111;
-                    *rumble_enabled = value & 0b100 == 0b100;
+                    if has_rumble {
+                        *ram_bank_idx = value as usize & 0b111;
+                        *rumble_enabled = value & 0b1000 == 0b1000;
+                    } else {
+                        *ram_bank_idx = value as usize & 0b1111;
+                    }
                 }
                 _ => (),
             },
         };
+        if latch_clock {
+            self.rtc_latch();
+        }
+
+        let new_banking_state = self.banking_state();
+        if address <= 0x7FFF {
+            self.rom_written(address, value, new_banking_state);
+        }
+        if new_banking_state != banking_state {
+            self.bank_switched(new_banking_state);
+        }
+        if banking_state.ram_enabled && !new_banking_state.ram_enabled {
+            self.sram_disabled();
+        }
 
         // Handle RAM bank writes
         if (0xA000..=0xBFFF).contains(&address) {
+            #[allow(clippy::collapsible_match)]
             match self.memory_mode() {
-                MemoryMode::MBC1 {
-                    ram_bank_idx,
-                    ram_enabled,
-                    ..
-                }
-                | MemoryMode::MBC5 {
-                    ram_bank_idx,
-                    ram_enabled,
-                    ..
-                } => {
+                MemoryMode::MBC1 { ram_enabled, .. } | MemoryMode::MBC5 { ram_enabled, .. } => {
                     if ram_enabled {
-                        self.ram_mut()[address - 0xA000 + ram_bank_idx * RAM_BANK_SIZE] = value;
+                        if let Some(index) = self.ram_index(address, self.ram_bank_idx()) {
+                            self.ram_mut()[index] = value;
+                            self.sram_written(index);
+                        }
                     }
                 }
+                MemoryMode::MBC3 {
+                    ram_rtc_enabled: true,
+                    rtc_selected: Some(register),
+                    ..
+                } => self.rtc_write(register, value),
                 MemoryMode::MBC3 {
                     ram_bank_idx,
                     ram_rtc_enabled,
@@ -340,23 +780,29 @@ pub trait Write: Memory {
                     ..
                 } => {
                     if rtc_selected.is_none() && ram_rtc_enabled {
-                        self.ram_mut()[address - 0xA000 + ram_bank_idx * RAM_BANK_SIZE] = value;
+                        if let Some(index) = self.ram_index(address, ram_bank_idx) {
+                            self.ram_mut()[index] = value;
+                            self.sram_written(index);
+                        }
                     }
                 }
 
-                MemoryMode::MBC2 { ram_enabled, .. } => match address {
-                    0xA000..=0xA1FF => {
-                        if ram_enabled {
-                            self.ram_mut()[address - 0xA000] = value;
-                        }
+                MemoryMode::RomRam => {
+                    if let Some(index) = self.ram_index(address, 0) {
+                        self.ram_mut()[index] = value;
+                        self.sram_written(index);
                     }
-                    0xA200..=0xBFFF => {
-                        if ram_enabled {
-                            self.ram_mut()[(address - 0xA000) & 0x1FF] = value;
+                }
+
+                MemoryMode::MBC2 { ram_enabled, .. } => {
+                    // Only 512 half-bytes, mirrored across the whole region
+                    if ram_enabled {
+                        if let Some(index) = self.ram_index(address & 0x1FF, 0) {
+                            self.ram_mut()[index] = value | 0xF0;
+                            self.sram_written(index);
                         }
                     }
-                    _ => (),
-                },
+                }
                 _ => (),
             };
 
@@ -369,14 +815,10 @@ pub trait Write: Memory {
             0x0000..=0x7FFF /* ROM */ | 0xFEA0..=0xFEFF /* Restricted */ => (),
             // Echo RAM
             0xE000..=0xFDFF => self.memory_mut()[address - 0x2000] = value,
-            // Trap DIV | LY writes
-            locations::DIV | locations::LY => self.memory_mut()[address] = 0,
-            // Trap timer frequency changes
-            locations::TAC => {
-                let current_freq = self.memory()[locations::TAC] & 0b11;
-                let new_freq = value & 0b11;
-                if current_freq != new_freq {
-                    self.memory_mut()[locations::TIMA] = 0;
+            // Registers trapped by a subsystem
+            0xFF00..=0xFF7F | locations::IE => {
+                if !self.io_write(address, value) {
+                    self.memory_mut()[address] = value;
                 }
             }
             _ => self.memory_mut()[address] = value,
@@ -387,7 +829,7 @@ pub trait Write: Memory {
         let upper = (value >> 8) as u8;
         let lower = value as u8;
         self.write_u8(address, lower);
-        self.write_u8(address + 1, upper);
+        self.write_u8(address.wrapping_add(1) & 0xFFFF, upper);
     }
 
     fn write_bytes(&mut self, addresses: std::ops::RangeInclusive<usize>, values: &[u8]) {
@@ -0,0 +1,178 @@
+//! Typed views of the console memory regions, for save editors and map
+//! viewers, and iteration over every bank of a region for dump tools.
+//!
+//! The views access the memory directly, not through the bus: watches,
+//! freezes and OAM DMA don't apply, and writes have no side effects.
+use crate::{GameBoy, RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+use super::locations;
+
+/// Sprites in OAM
+pub const OAM_ENTRIES: usize = 40;
+/// Size of a VRAM bank
+pub const VRAM_BANK_SIZE: usize = 0x2000;
+/// Size of the work RAM
+pub const WRAM_SIZE: usize = 0x2000;
+/// Size of the high RAM
+pub const HRAM_SIZE: usize = 0x7F;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A memory region, with all its banks
+pub enum Region {
+    /// The cartridge ROM, bank 0 included
+    Rom,
+    /// The cartridge (external) RAM
+    CartRam,
+    Vram,
+    Wram,
+    Oam,
+    Hram,
+}
+
+impl Region {
+    /// Size of a bank of the region, or of the region when not banked
+    pub fn bank_size(self) -> usize {
+        match self {
+            Self::Rom => ROM_BANK_SIZE,
+            Self::CartRam => RAM_BANK_SIZE,
+            Self::Vram => VRAM_BANK_SIZE,
+            Self::Wram => WRAM_SIZE,
+            Self::Oam => OAM_ENTRIES * 4,
+            Self::Hram => HRAM_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// A sprite of the object attribute memory
+pub struct OamEntry {
+    /// Vertical position plus 16, 0 hides the sprite
+    pub y: u8,
+    /// Horizontal position plus 8, 0 hides the sprite
+    pub x: u8,
+    pub tile: u8,
+    /// Priority, flips and palette
+    pub attributes: u8,
+}
+
+impl OamEntry {
+    /// The background and window colors 1-3 are drawn over the sprite
+    pub fn behind_background(&self) -> bool {
+        self.attributes & 0x80 != 0
+    }
+
+    pub fn y_flip(&self) -> bool {
+        self.attributes & 0x40 != 0
+    }
+
+    pub fn x_flip(&self) -> bool {
+        self.attributes & 0x20 != 0
+    }
+
+    /// Uses OBP1 instead of OBP0
+    pub fn palette(&self) -> bool {
+        self.attributes & 0x10 != 0
+    }
+
+    pub fn to_bytes(self) -> [u8; 4] {
+        [self.y, self.x, self.tile, self.attributes]
+    }
+}
+
+impl From<[u8; 4]> for OamEntry {
+    fn from([y, x, tile, attributes]: [u8; 4]) -> Self {
+        Self {
+            y,
+            x,
+            tile,
+            attributes,
+        }
+    }
+}
+
+/// Returns the region of the memory as a fixed size array
+fn region<const N: usize>(memory: &[u8], start: usize) -> &[u8; N] {
+    memory[start..][..N].try_into().unwrap()
+}
+
+fn region_mut<const N: usize>(memory: &mut [u8], start: usize) -> &mut [u8; N] {
+    (&mut memory[start..][..N]).try_into().unwrap()
+}
+
+impl GameBoy {
+    /// Returns the work RAM (0xC000..=0xDFFF)
+    pub fn wram(&self) -> &[u8; WRAM_SIZE] {
+        region(&self.memory, *locations::WRAM.start())
+    }
+
+    pub fn wram_mut(&mut self) -> &mut [u8; WRAM_SIZE] {
+        region_mut(&mut self.memory, *locations::WRAM.start())
+    }
+
+    /// Returns the video RAM (0x8000..=0x9FFF)
+    pub fn vram(&self) -> &[u8; VRAM_BANK_SIZE] {
+        region(&self.memory, *locations::VRAM.start())
+    }
+
+    pub fn vram_mut(&mut self) -> &mut [u8; VRAM_BANK_SIZE] {
+        region_mut(&mut self.memory, *locations::VRAM.start())
+    }
+
+    /// Returns a bank of the video RAM, `None` past the last one. Only the
+    /// DMG single bank is emulated, the CGB second bank is not.
+    pub fn vram_bank(&self, bank: usize) -> Option<&[u8; VRAM_BANK_SIZE]> {
+        (bank == 0).then(|| self.vram())
+    }
+
+    /// Returns the high RAM (0xFF80..=0xFFFE)
+    pub fn hram(&self) -> &[u8; HRAM_SIZE] {
+        region(&self.memory, *locations::HRAM.start())
+    }
+
+    pub fn hram_mut(&mut self) -> &mut [u8; HRAM_SIZE] {
+        region_mut(&mut self.memory, *locations::HRAM.start())
+    }
+
+    /// Iterates over every byte of the region as `(bank, offset, value)`,
+    /// all the banks in order and not only the mapped one. The offset is
+    /// from the start of the bank.
+    ///
+    /// ```
+    /// use gbemu::{cartridge, memory::view::Region, GameBoy};
+    ///
+    /// let gb = GameBoy::new(&cartridge::test_rom());
+    /// let banks = gb.iter_region(Region::Rom).filter(|(_, offset, _)| *offset == 0);
+    /// assert_eq!(banks.count(), 2);
+    /// ```
+    pub fn iter_region(&self, region: Region) -> impl Iterator<Item = (usize, usize, u8)> + '_ {
+        let bytes: &[u8] = match region {
+            Region::Rom => &self.cartridge,
+            Region::CartRam => &self.banks,
+            Region::Vram => self.vram(),
+            Region::Wram => self.wram(),
+            Region::Oam => &self.memory[locations::OAM],
+            Region::Hram => self.hram(),
+        };
+        let bank_size = region.bank_size();
+        bytes
+            .iter()
+            .enumerate()
+            .map(move |(index, value)| (index / bank_size, index % bank_size, *value))
+    }
+
+    /// Returns the sprites of the object attribute memory, in OAM order
+    pub fn oam(&self) -> [OamEntry; OAM_ENTRIES] {
+        let oam: &[u8; OAM_ENTRIES * 4] = region(&self.memory, *locations::OAM.start());
+        std::array::from_fn(|i| OamEntry::from(*region::<4>(oam, i * 4)))
+    }
+
+    /// Replaces a sprite of the object attribute memory
+    ///
+    /// # Panics
+    ///
+    /// If the index is not below [`OAM_ENTRIES`].
+    pub fn set_oam_entry(&mut self, index: usize, entry: OamEntry) {
+        assert!(index < OAM_ENTRIES, "OAM holds {} sprites", OAM_ENTRIES);
+        *region_mut(&mut self.memory, *locations::OAM.start() + index * 4) = entry.to_bytes();
+    }
+}
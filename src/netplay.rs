@@ -0,0 +1,135 @@
+//! Emulator side building blocks for deterministic lockstep and rollback netplay.
+//!
+//! Transport is left to the frontend: these helpers only decide which input
+//! each frame is emulated with and rewind the emulator when a prediction
+//! turns out wrong.
+use std::collections::VecDeque;
+
+use crate::{savestate::SaveState, GameBoy};
+
+/// Delays local inputs by a fixed number of frames, giving remote inputs
+/// time to arrive before they are needed.
+pub struct InputDelay<I> {
+    queue: VecDeque<I>,
+}
+
+impl<I: Clone> InputDelay<I> {
+    /// The first `delay` frames are emulated with `initial`
+    pub fn new(delay: usize, initial: I) -> Self {
+        Self {
+            queue: std::iter::repeat_n(initial, delay).collect(),
+        }
+    }
+
+    /// Queues the input read this frame and returns the one to emulate with
+    pub fn push(&mut self, input: I) -> I {
+        self.queue.push_back(input);
+        self.queue.pop_front().unwrap()
+    }
+
+    pub fn delay(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+struct Frame<I> {
+    /// State before the frame was emulated
+    state: SaveState,
+    input: I,
+}
+
+/// Emulates frames with predicted inputs and re-simulates them once the
+/// actual inputs are known.
+///
+/// `run_frame` applies an input and emulates one frame, it must be
+/// deterministic for the rollback to be correct.
+pub struct Rollback<I, F: FnMut(&mut GameBoy, &I)> {
+    run_frame: F,
+    /// Frames that can still be rolled back, oldest first
+    frames: VecDeque<Frame<I>>,
+    /// Number of the next frame to be emulated
+    frame: u64,
+    max_frames: usize,
+}
+
+impl<I: Clone + PartialEq, F: FnMut(&mut GameBoy, &I)> Rollback<I, F> {
+    /// Keeps up to `max_frames` unconfirmed frames
+    pub fn new(max_frames: usize, run_frame: F) -> Self {
+        Self {
+            run_frame,
+            frames: VecDeque::with_capacity(max_frames),
+            frame: 0,
+            max_frames,
+        }
+    }
+
+    /// Number of the next frame to be emulated
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Number of the oldest frame that can still be rolled back
+    fn oldest_frame(&self) -> u64 {
+        self.frame - self.frames.len() as u64
+    }
+
+    /// Emulates the next frame with an input, possibly a prediction.
+    ///
+    /// The oldest frame is confirmed implicitly when more than `max_frames`
+    /// are pending.
+    pub fn advance(&mut self, gb: &mut GameBoy, input: I) {
+        if self.frames.len() == self.max_frames {
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(Frame {
+            state: gb.save_state(),
+            input: input.clone(),
+        });
+        (self.run_frame)(gb, &input);
+        self.frame += 1;
+    }
+
+    /// Provides the actual input of a past frame, rewinding and re-simulating
+    /// up to the current frame if it differs from the one emulated.
+    ///
+    /// Returns false if the frame is too old to be corrected.
+    pub fn correct(&mut self, gb: &mut GameBoy, frame: u64, input: I) -> bool {
+        if frame >= self.frame {
+            return true;
+        }
+        if frame < self.oldest_frame() {
+            return false;
+        }
+
+        let first = (frame - self.oldest_frame()) as usize;
+        if self.frames[first].input == input {
+            return true;
+        }
+        self.frames[first].input = input;
+
+        gb.load_state(&self.frames[first].state)
+            .expect("rollback state belongs to the same emulator");
+        for i in first..self.frames.len() {
+            if i != first {
+                self.frames[i].state = gb.save_state();
+            }
+            let input = self.frames[i].input.clone();
+            (self.run_frame)(gb, &input);
+        }
+
+        true
+    }
+
+    /// Marks every frame before `frame` as final, they won't be rolled back anymore
+    pub fn confirm(&mut self, frame: u64) {
+        while self.oldest_frame() < frame.min(self.frame) {
+            self.frames.pop_front();
+        }
+    }
+}
+
+/// Hash of the emulator state, exchanged by peers to detect desyncs
+pub fn frame_hash(gb: &GameBoy) -> u64 {
+    gb.state_hash()
+}
@@ -0,0 +1,124 @@
+//! Helpers for frontends to run the emulator at real hardware speed.
+use std::time::{Duration, Instant};
+
+use crate::cpu::{CPU_CLOCK_SPEED, CYCLES_PER_FRAME};
+
+/// Frames per second of the real hardware (~59.7275), see
+/// [`ClockConfig::frame_rate`](crate::cpu::ClockConfig::frame_rate) for the Super Game Boy
+pub const FRAME_RATE: f64 = CPU_CLOCK_SPEED / CYCLES_PER_FRAME as f64;
+
+/// Falling behind more than this drops the backlog instead of racing to catch up
+pub const MAX_DRIFT: Duration = Duration::from_millis(100);
+/// Sleeping is coarse, the last stretch is spent spinning
+pub const SPIN_THRESHOLD: Duration = Duration::from_millis(1);
+
+/// Source of host time the emulation is synchronised to
+pub trait Clock {
+    /// Time elapsed since an arbitrary fixed point
+    fn now(&self) -> Duration;
+
+    /// Waits for about the duration, the thread can be woken late
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+
+    /// Waits for a moment while spinning, before checking the time again
+    fn spin(&self) {
+        std::hint::spin_loop();
+    }
+}
+
+/// Wall clock time
+pub struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Keeps emulation at hardware speed by waiting on a [`Clock`] after
+/// each batch of emulated cycles.
+///
+/// Time is accounted against the total emulated cycles rather than per frame,
+/// so rounding errors don't accumulate. An audio driven frontend can
+/// implement [`Clock`] on top of the samples consumed by the device.
+pub struct FrameLimiter<C: Clock = SystemClock> {
+    clock: C,
+    /// Host time matching `cycles == 0`
+    origin: Duration,
+    /// Cycles emulated since `origin`
+    cycles: u64,
+    /// Emulated cycles per second
+    speed: f64,
+}
+
+impl Default for FrameLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameLimiter {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::default())
+    }
+}
+
+impl<C: Clock> FrameLimiter<C> {
+    pub fn with_clock(clock: C) -> Self {
+        let origin = clock.now();
+        Self {
+            clock,
+            origin,
+            cycles: 0,
+            speed: CPU_CLOCK_SPEED,
+        }
+    }
+
+    /// Paces a console running at another clock speed, see
+    /// [`ClockConfig`](crate::cpu::ClockConfig)
+    pub fn clock_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Accounts for a frame worth of emulated cycles and waits for it
+    pub fn wait_frame(&mut self) {
+        self.wait(CYCLES_PER_FRAME);
+    }
+
+    /// Accounts for the emulated cycles and waits until the host catches up
+    pub fn wait(&mut self, cycles: u64) {
+        self.cycles += cycles;
+        let target = self.origin + Duration::from_secs_f64(self.cycles as f64 / self.speed);
+
+        let now = self.clock.now();
+        if now > target + MAX_DRIFT {
+            // Too far behind (e.g. the process was suspended), start over
+            self.origin = now;
+            self.cycles = 0;
+            return;
+        }
+
+        loop {
+            let now = self.clock.now();
+            if now >= target {
+                break;
+            }
+
+            let remaining = target - now;
+            if remaining > SPIN_THRESHOLD {
+                self.clock.sleep(remaining - SPIN_THRESHOLD);
+            } else {
+                self.clock.spin();
+            }
+        }
+    }
+}
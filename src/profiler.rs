@@ -0,0 +1,121 @@
+//! Cycle attribution to the functions of the shadow call stack, exported in
+//! the folded stacks format understood by flamegraph tools.
+use std::collections::HashMap;
+
+use crate::{debug::SymbolTable, GameBoy};
+
+/// A function, identified by the ROM bank and address it was entered at
+pub type FunctionId = (usize, u16);
+
+/// The function running before any tracked call
+pub const ROOT: FunctionId = (0, 0x0100);
+
+#[derive(Debug, Clone, Default)]
+/// Accumulates T-cycles per call stack while running.
+///
+/// Each instruction is charged to the stack left after it, so a `CALL` counts
+/// towards the callee and a `RET` towards the caller.
+pub struct Profiler {
+    running: bool,
+    stacks: HashMap<Vec<FunctionId>, u64>,
+    /// Reused to build the current stack without allocating at every step
+    scratch: Vec<FunctionId>,
+}
+
+impl Profiler {
+    /// Starts accumulating cycles, keeping the ones accumulated so far
+    pub fn start(&mut self) {
+        self.running = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    /// Discards the cycles accumulated so far
+    pub fn reset(&mut self) {
+        self.stacks.clear();
+    }
+
+    pub(crate) fn record(&mut self, stack: &[FunctionId], cycles: u64) {
+        match self.stacks.get_mut(stack) {
+            Some(total) => *total += cycles,
+            None => {
+                self.stacks.insert(stack.to_vec(), cycles);
+            }
+        }
+    }
+
+    /// Returns the T-cycles spent in each function itself, excluding callees,
+    /// most expensive first
+    pub fn self_cycles(&self) -> Vec<(FunctionId, u64)> {
+        let mut functions = HashMap::<FunctionId, u64>::new();
+        for (stack, cycles) in &self.stacks {
+            *functions.entry(*stack.last().unwrap_or(&ROOT)).or_default() += cycles;
+        }
+
+        let mut functions: Vec<_> = functions.into_iter().collect();
+        functions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        functions
+    }
+
+    /// Returns one `outer;inner cycles` line per call stack, sorted, naming
+    /// functions by symbol or as `BB:AAAA`
+    pub fn folded(&self, symbols: &SymbolTable) -> String {
+        let name = |(bank, address): FunctionId| match symbols.lookup(bank, address) {
+            Some((name, 0)) => name.to_string(),
+            Some((name, offset)) => format!("{}+{:#x}", name, offset),
+            None => format!("{:02X}:{:04X}", bank, address),
+        };
+
+        let mut lines: Vec<_> = self
+            .stacks
+            .iter()
+            .map(|(stack, cycles)| {
+                let frames: Vec<_> = stack.iter().map(|function| name(*function)).collect();
+                format!("{} {}", frames.join(";"), cycles)
+            })
+            .collect();
+        lines.sort();
+        lines.join("\n")
+    }
+}
+
+impl GameBoy {
+    /// Returns the profiler
+    pub fn profiler(&self) -> &Profiler {
+        &self.profiler
+    }
+
+    /// Returns the mutable profiler
+    pub fn profiler_mut(&mut self) -> &mut Profiler {
+        &mut self.profiler
+    }
+
+    /// Returns the folded stacks report, named after the debugger symbols
+    pub fn profile_report(&self) -> String {
+        self.profiler.folded(self.debugger().symbols())
+    }
+
+    pub(crate) fn profile_step(&mut self, cycles: u64) {
+        if !self.profiler.running {
+            return;
+        }
+
+        let mut stack = std::mem::take(&mut self.profiler.scratch);
+        stack.clear();
+        stack.push(ROOT);
+        stack.extend(
+            self.debugger
+                .call_stack()
+                .iter()
+                .map(|frame| (frame.target_bank, frame.target)),
+        );
+        self.profiler.record(&stack, cycles);
+        self.profiler.scratch = stack;
+    }
+}
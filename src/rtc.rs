@@ -0,0 +1,405 @@
+//! The MBC3 real time clock, advanced from emulated time or from a
+//! [`TimeSource`].
+//!
+//! Emulated time (the default) keeps runs reproducible: the clock only moves
+//! while the game runs, like a console left on. A [`HostTime`] source follows
+//! the host clock as the cartridge battery would, a [`ManualTime`] lets test
+//! harnesses and TAS playback set or freeze the time.
+//!
+//! The game reads the latched registers, refreshed from the live counter by
+//! writing 0 then 1 to the latch register.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/MBC3.html)
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{determinism::Nondeterminism, memory::MemoryMode, GameBoy};
+
+/// The day counter is 9 bits, overflowing sets the carry
+const DAYS: u64 = 512;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Size of the RTC footer appended to battery saves by VBA-M, BGB and others
+pub const FOOTER_SIZE: usize = 48;
+/// Older emulators wrote the timestamp on 32 bits
+const SHORT_FOOTER_SIZE: usize = 44;
+
+/// Bits of the days high register
+const DAYS_MSB: u8 = 0b0000_0001;
+const HALT: u8 = 0b0100_0000;
+const CARRY: u8 = 0b1000_0000;
+
+/// Wall clock time as seen by the cartridge
+pub trait TimeSource {
+    /// Seconds since the UNIX epoch
+    fn unix_time(&self) -> u64;
+
+    /// True when the time comes from the host, which makes runs diverge
+    fn follows_host(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// The host clock, shifted by `offset` seconds
+pub struct HostTime {
+    pub offset: i64,
+}
+
+impl TimeSource for HostTime {
+    fn unix_time(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        now.saturating_add_signed(self.offset)
+    }
+
+    fn follows_host(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Time set by hand, frozen in between. Clones share the time, so one can be
+/// kept to move the clock of the console holding the other.
+pub struct ManualTime(Arc<AtomicU64>);
+
+impl ManualTime {
+    pub fn new(unix_time: u64) -> Self {
+        Self(Arc::new(AtomicU64::new(unix_time)))
+    }
+
+    pub fn set(&self, unix_time: u64) {
+        self.0.store(unix_time, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.0.fetch_add(seconds, Ordering::Relaxed);
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn unix_time(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Default)]
+/// What advances the clock
+pub enum RtcTime {
+    /// Emulated cycles, at the console clock speed
+    #[default]
+    Emulated,
+    Source(Box<dyn TimeSource + Send>),
+}
+
+impl RtcTime {
+    /// Follows the host clock
+    pub fn host() -> Self {
+        Self::Source(Box::new(HostTime::default()))
+    }
+}
+
+impl fmt::Debug for RtcTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Emulated => write!(f, "Emulated"),
+            Self::Source(_) => write!(f, "Source"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Contents of the clock registers
+pub struct RtcRegisters {
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    /// 9 bits
+    pub days: u16,
+    pub halted: bool,
+    /// The day counter overflowed, until cleared by the game
+    pub carry: bool,
+}
+
+impl RtcRegisters {
+    fn from_counter(counter: u64, halted: bool, carry: bool) -> Self {
+        Self {
+            seconds: (counter % 60) as u8,
+            minutes: (counter / 60 % 60) as u8,
+            hours: (counter / 3600 % 24) as u8,
+            days: (counter / SECONDS_PER_DAY) as u16,
+            halted,
+            carry,
+        }
+    }
+
+    fn counter(&self) -> u64 {
+        (self.days as u64 % DAYS) * SECONDS_PER_DAY
+            + (self.hours as u64 % 24) * 3600
+            + (self.minutes as u64 % 60) * 60
+            + self.seconds as u64 % 60
+    }
+
+    /// The days high register
+    fn days_high(&self) -> u8 {
+        (self.days >> 8) as u8 & DAYS_MSB
+            | if self.halted { HALT } else { 0 }
+            | if self.carry { CARRY } else { 0 }
+    }
+
+    fn set_days_high(&mut self, value: u8) {
+        self.days = self.days & 0xFF | ((value & DAYS_MSB) as u16) << 8;
+        self.halted = value & HALT != 0;
+        self.carry = value & CARRY != 0;
+    }
+
+    /// Values of the registers selected with 0x08..=0x0C
+    fn to_array(self) -> [u8; 5] {
+        [
+            self.seconds,
+            self.minutes,
+            self.hours,
+            self.days as u8,
+            self.days_high(),
+        ]
+    }
+
+    fn from_array(values: [u8; 5]) -> Self {
+        let mut registers = Self {
+            seconds: values[0],
+            minutes: values[1],
+            hours: values[2],
+            days: values[3] as u16,
+            ..Self::default()
+        };
+        registers.set_days_high(values[4]);
+        registers
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Rtc {
+    time: RtcTime,
+    /// Live counter in seconds, below 512 days
+    counter: u64,
+    halted: bool,
+    carry: bool,
+    /// Emulated: cycles into the current second
+    subsecond: u64,
+    /// Source: time of the last sync
+    synced_at: u64,
+}
+
+impl Rtc {
+    pub(crate) fn new(time: RtcTime) -> Self {
+        let mut rtc = Self {
+            time,
+            ..Self::default()
+        };
+        rtc.synced_at = rtc.source_time().unwrap_or(0);
+        rtc
+    }
+
+    fn source_time(&self) -> Option<u64> {
+        match &self.time {
+            RtcTime::Emulated => None,
+            RtcTime::Source(source) => Some(source.unix_time()),
+        }
+    }
+
+    fn advance(&mut self, seconds: u64) {
+        if self.halted {
+            return;
+        }
+        let counter = self.counter + seconds;
+        self.carry |= counter >= DAYS * SECONDS_PER_DAY;
+        self.counter = counter % (DAYS * SECONDS_PER_DAY);
+    }
+
+    /// Advances by emulated cycles at `speed` cycles per second
+    pub(crate) fn step(&mut self, cycles: u64, speed: u64) {
+        if let RtcTime::Emulated = self.time {
+            self.subsecond += cycles;
+            if self.subsecond >= speed {
+                self.advance(self.subsecond / speed);
+                self.subsecond %= speed;
+            }
+        }
+    }
+
+    /// Catches up with the time source
+    fn sync(&mut self) {
+        if let Some(now) = self.source_time() {
+            self.advance(now.saturating_sub(self.synced_at));
+            self.synced_at = now;
+        }
+    }
+
+    fn registers(&self) -> RtcRegisters {
+        RtcRegisters::from_counter(self.counter, self.halted, self.carry)
+    }
+
+    fn set_registers(&mut self, registers: RtcRegisters) {
+        self.counter = registers.counter();
+        self.halted = registers.halted;
+        self.carry = registers.carry;
+    }
+
+    pub(crate) fn save(&self) -> (u64, bool, bool, u64) {
+        (self.counter, self.halted, self.carry, self.subsecond)
+    }
+
+    /// Restores a saved clock, a source keeps its own time
+    pub(crate) fn load(&mut self, (counter, halted, carry, subsecond): (u64, bool, bool, u64)) {
+        self.counter = counter % (DAYS * SECONDS_PER_DAY);
+        self.halted = halted;
+        self.carry = carry;
+        self.subsecond = subsecond;
+        self.synced_at = self.source_time().unwrap_or(0);
+    }
+}
+
+impl GameBoy {
+    /// Selects what advances the MBC3 clock, emulated time by default
+    pub fn set_rtc_time(&mut self, time: RtcTime) {
+        self.rtc.sync();
+        self.rtc.time = time;
+        self.rtc.synced_at = self.rtc.source_time().unwrap_or(0);
+    }
+
+    /// Returns the live clock registers, brought up to date
+    pub fn rtc(&mut self) -> RtcRegisters {
+        self.sync_rtc();
+        self.rtc.registers()
+    }
+
+    /// Sets the live clock registers, e.g. to start a game at a given time
+    pub fn set_rtc(&mut self, registers: RtcRegisters) {
+        self.sync_rtc();
+        self.rtc.set_registers(registers);
+    }
+
+    /// Returns the footer to append to [`GameBoy::battery_ram`] in `.sav`
+    /// files, in the format of VBA-M and BGB, for cartridges with a clock.
+    ///
+    /// The timestamp is the time source's, 0 with emulated time.
+    pub fn rtc_footer(&mut self) -> Option<[u8; FOOTER_SIZE]> {
+        if !self.cartridge_header.has_rtc() {
+            return None;
+        }
+        let MemoryMode::MBC3 {
+            rtc_seconds,
+            rtc_minutes,
+            rtc_hours,
+            rtc_days,
+            ..
+        } = self.memory_mode
+        else {
+            return None;
+        };
+        let live = self.rtc().to_array();
+        let latched = [
+            rtc_seconds,
+            rtc_minutes,
+            rtc_hours,
+            rtc_days as u8,
+            (rtc_days >> 8) as u8,
+        ];
+
+        let mut footer = [0; FOOTER_SIZE];
+        for (i, value) in live.into_iter().chain(latched).enumerate() {
+            footer[i * 4] = value;
+        }
+        let timestamp = self.rtc.source_time().unwrap_or(0);
+        footer[40..].copy_from_slice(&timestamp.to_le_bytes());
+        Some(footer)
+    }
+
+    /// Restores the clock from a footer found after the battery RAM, see
+    /// [`GameBoy::rtc_footer`]. With a time source the clock is fast
+    /// forwarded by the time elapsed since the file was written.
+    pub(crate) fn load_rtc_footer(&mut self, footer: &[u8]) {
+        if !self.cartridge_header.has_rtc()
+            || !matches!(footer.len(), SHORT_FOOTER_SIZE | FOOTER_SIZE)
+        {
+            return;
+        }
+        let MemoryMode::MBC3 {
+            rtc_seconds,
+            rtc_minutes,
+            rtc_hours,
+            rtc_days,
+            ..
+        } = &mut self.memory_mode
+        else {
+            return;
+        };
+
+        let register = |i: usize| footer[i * 4];
+        *rtc_seconds = register(5);
+        *rtc_minutes = register(6);
+        *rtc_hours = register(7);
+        *rtc_days = register(8) as u16 | (register(9) as u16) << 8;
+
+        let live = RtcRegisters::from_array([0, 1, 2, 3, 4].map(register));
+        self.rtc.set_registers(live);
+        let mut timestamp = [0; 8];
+        timestamp[..footer.len() - 40].copy_from_slice(&footer[40..]);
+        let timestamp = u64::from_le_bytes(timestamp);
+
+        if let Some(now) = self.rtc.source_time() {
+            // Emulated time has no timestamp to catch up from
+            if timestamp != 0 {
+                self.rtc.advance(now.saturating_sub(timestamp));
+            }
+            self.rtc.synced_at = now;
+        }
+    }
+
+    fn sync_rtc(&mut self) {
+        if matches!(&self.rtc.time, RtcTime::Source(source) if source.follows_host()) {
+            self.nondeterministic(Nondeterminism::HostRtc);
+        }
+        self.rtc.sync();
+    }
+
+    /// Copies the live counter into the registers read by the game
+    pub(crate) fn latch_rtc(&mut self) {
+        let [seconds, minutes, hours, days_low, days_high] = self.rtc().to_array();
+        if let MemoryMode::MBC3 {
+            rtc_seconds,
+            rtc_minutes,
+            rtc_hours,
+            rtc_days,
+            ..
+        } = &mut self.memory_mode
+        {
+            *rtc_seconds = seconds;
+            *rtc_minutes = minutes;
+            *rtc_hours = hours;
+            *rtc_days = days_low as u16 | (days_high as u16) << 8;
+        }
+    }
+
+    /// Writes a live register, selected with 0x08..=0x0C
+    pub(crate) fn write_rtc(&mut self, register: u8, value: u8) {
+        let mut values = self.rtc().to_array();
+        let Some(slot) = values.get_mut(register.wrapping_sub(0x08) as usize) else {
+            return;
+        };
+        *slot = value;
+        self.rtc.set_registers(RtcRegisters::from_array(values));
+        if register == 0x08 {
+            // Writing the seconds resets the divider
+            self.rtc.subsecond = 0;
+        }
+        // The latched copy is written too, games read back what they set
+        self.latch_rtc();
+    }
+}
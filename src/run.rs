@@ -0,0 +1,102 @@
+//! Running the emulation until something happens, with a guard against
+//! running forever.
+use crate::{
+    cpu::{Cpu, Registers},
+    GameBoy,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Something to wait for, counts are relative to the start of the run
+pub enum Condition {
+    /// The given number of frames elapsed
+    Frames(u64),
+    /// The given number of T-cycles elapsed
+    Cycles(u64),
+    /// The next instruction is at the address
+    PcEquals(u16),
+    /// The serial output contains the text
+    SerialOutputContains(String),
+    /// The next instruction is at a breakpoint, see
+    /// [`Debugger::add_breakpoint`](crate::debug::Debugger::add_breakpoint)
+    #[cfg(feature = "debugger")]
+    Breakpoint,
+    /// A watched address changed, see [`Debugger::watch`](crate::debug::Debugger::watch)
+    #[cfg(feature = "debugger")]
+    WatchHit,
+    /// Any of the conditions is met
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    /// Returns the first condition met, looking inside [`Condition::Any`]
+    fn met(&self, gb: &GameBoy, start_frames: u64, start_cycles: u64) -> Option<&Condition> {
+        let met = match self {
+            Self::Frames(frames) => gb.frames() - start_frames >= *frames,
+            Self::Cycles(cycles) => gb.cycles() - start_cycles >= *cycles,
+            Self::PcEquals(pc) => *gb.registers().pc == *pc,
+            Self::SerialOutputContains(text) => gb
+                .serial_output()
+                .windows(text.len().max(1))
+                .any(|window| window == text.as_bytes()),
+            #[cfg(feature = "debugger")]
+            Self::Breakpoint => gb.at_breakpoint(),
+            #[cfg(feature = "debugger")]
+            Self::WatchHit => gb.debugger().has_watch_hits(),
+            Self::Any(conditions) => {
+                return conditions
+                    .iter()
+                    .find_map(|condition| condition.met(gb, start_frames, start_cycles))
+            }
+        };
+
+        met.then_some(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The run was stopped after `frames` frames without meeting the condition
+pub struct Timeout {
+    pub frames: u64,
+}
+
+impl std::fmt::Display for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "condition not met after {} frames", self.frames)
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+impl GameBoy {
+    /// Runs until the condition is met, checked after every instruction,
+    /// or until `timeout_frames` frames elapse.
+    ///
+    /// Returns the condition met, the innermost one for [`Condition::Any`].
+    ///
+    /// ```
+    /// use gbemu::{cartridge, run::Condition, GameBoy};
+    ///
+    /// let mut gb = GameBoy::new(&cartridge::test_rom());
+    /// let met = gb.run_until(&Condition::PcEquals(0x0155), 1).unwrap();
+    /// assert_eq!(met, &Condition::PcEquals(0x0155));
+    /// ```
+    pub fn run_until<'a>(
+        &mut self,
+        condition: &'a Condition,
+        timeout_frames: u64,
+    ) -> Result<&'a Condition, Timeout> {
+        let (start_frames, start_cycles) = (self.frames(), self.cycles());
+        loop {
+            if let Some(met) = condition.met(self, start_frames, start_cycles) {
+                return Ok(met);
+            }
+            if self.frames() - start_frames >= timeout_frames {
+                return Err(Timeout {
+                    frames: timeout_frames,
+                });
+            }
+
+            self.step();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Run-ahead, hiding the input lag games have by design: each frame is
+//! emulated, then a few more with the same input are emulated and shown
+//! before rolling back to the real one. A button press then shows up on
+//! screen as many frames earlier as were run ahead.
+//!
+//! The speculative frames are only seen through the
+//! [`FrontBuffer`](crate::video::FrontBuffer) and the hooks, which fire for
+//! them too; counters, serial output, battery saves, the event log,
+//! the DMA trace and the interrupt statistics only follow the real frames.
+use crate::{
+    battery::DirtyPages, cpu::Counters, eventlog::FrameEvents, irqstats::IrqStats, lcd::Lcd,
+    memory::dma::DmaTrace, savestate::SaveState, GameBoy,
+};
+
+/// What a rollback restores, the state plus the timing and bookkeeping
+/// left out of save states
+struct Checkpoint {
+    state: SaveState,
+    counters: Counters,
+    lcd: Lcd,
+    serial_output: usize,
+    sram_dirty: DirtyPages,
+    frame_events: Option<FrameEvents>,
+    dma_trace: Option<DmaTrace>,
+    irq_stats: Option<Box<IrqStats>>,
+}
+
+impl GameBoy {
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            state: self.save_state(),
+            counters: self.counters,
+            lcd: self.lcd.clone(),
+            serial_output: self.serial_output.len(),
+            sram_dirty: self.sram_dirty.clone(),
+            frame_events: self.frame_events.clone(),
+            dma_trace: self.dma_trace.borrow().clone(),
+            irq_stats: self.irq_stats.clone(),
+        }
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.load_state(&checkpoint.state)
+            .expect("a state saved by the same console loads");
+        self.counters = checkpoint.counters;
+        self.lcd = checkpoint.lcd;
+        self.serial_output.truncate(checkpoint.serial_output);
+        self.sram_dirty = checkpoint.sram_dirty;
+        self.frame_events = checkpoint.frame_events;
+        *self.dma_trace.get_mut() = checkpoint.dma_trace;
+        self.irq_stats = checkpoint.irq_stats;
+    }
+
+    /// Runs a frame like [`GameBoy::run_frame`], then `frames` more with the
+    /// same buttons held, shown and rolled back. 0 runs no frame ahead.
+    ///
+    /// Costs `frames + 1` frames of emulation per frame, the buttons must
+    /// be set before the call.
+    pub fn run_frame_ahead(&mut self, frames: usize) -> usize {
+        let cycles = self.run_frame();
+        if frames == 0 || self.paused {
+            return cycles;
+        }
+
+        let checkpoint = self.checkpoint();
+        for _ in 0..frames {
+            self.run_frame();
+        }
+        self.rollback(checkpoint);
+        cycles
+    }
+}
@@ -0,0 +1,253 @@
+//! Runs a [`GameBoy`] on a dedicated thread, driven through channels: inputs
+//! and control [`Command`]s go in, [`Event`]s come out and the frames are
+//! read from the [`FrontBuffer`].
+//!
+//! This is the architecture most GUI frontends end up with, so the UI thread
+//! never waits on the emulation. No APU is emulated yet, so there is no audio
+//! to send out.
+//!
+//! ```no_run
+//! use gbemu::{runner::{Command, Event, Runner}, GameBoy};
+//!
+//! let rom = std::fs::read("game.gb").unwrap();
+//! // The input type and how it reaches the console are up to the frontend
+//! let runner = Runner::spawn(move || GameBoy::new(&rom), |_gb, _input: &u8| {});
+//! runner.send(Command::Input(0b0000_1000));
+//! for event in runner.events() {
+//!     if let Event::Frame(_) = event {
+//!         let frame = runner.front_buffer().lock();
+//!         // Upload frame.rgba() to a texture
+//!     }
+//! }
+//! ```
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError, TrySendError};
+
+use crate::{
+    cpu::Cpu,
+    pacing::FrameLimiter,
+    savestate::{SaveState, SaveStateError},
+    video::FrontBuffer,
+    GameBoy,
+};
+
+/// Events kept while the frontend is not reading them, frame events beyond
+/// this are dropped
+const EVENTS_CAPACITY: usize = 64;
+
+/// Requests to the emulation thread
+pub enum Command<I> {
+    /// Input applied before every frame, until the next one
+    Input(I),
+    /// Stops emulating frames, replied with [`Event::Paused`]
+    Pause,
+    /// Resumes emulating frames, replied with [`Event::Resumed`]
+    Resume,
+    /// Emulates one frame while paused, replied with [`Event::Frame`]
+    AdvanceFrame,
+    /// Runs that many frames ahead to hide input lag, 0 (the default)
+    /// disables it, see [`GameBoy::run_frame_ahead`]
+    RunAhead(usize),
+    /// Runs at hardware speed (the default) or as fast as possible
+    Throttle(bool),
+    /// Restarts the console, replied with [`Event::Reset`]
+    Reset,
+    /// Replied with [`Event::State`]
+    SaveState,
+    /// Replied with [`Event::StateLoaded`]
+    LoadState(SaveState),
+    /// Ends the thread, replied with [`Event::Stopped`]
+    Stop,
+}
+
+#[derive(Debug)]
+/// Notifications from the emulation thread
+pub enum Event {
+    /// A frame was emulated, with the number of the frame now in the
+    /// [`FrontBuffer`]. Dropped when the frontend falls behind.
+    Frame(u64),
+    /// A byte was sent on the serial port
+    Serial(u8),
+    Paused,
+    Resumed,
+    Reset,
+    State(SaveState),
+    StateLoaded(Result<(), SaveStateError>),
+    /// The thread ended, no more events follow
+    Stopped,
+}
+
+/// Handle on a [`GameBoy`] running on its own thread, stopped when dropped
+pub struct Runner<I> {
+    commands: Sender<Command<I>>,
+    events: Receiver<Event>,
+    front: FrontBuffer,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<I: Send + 'static> Runner<I> {
+    /// Starts the emulation thread, running at hardware speed.
+    ///
+    /// The console is built on the thread by `build`, as it cannot be sent
+    /// across threads once hooks are installed. `apply_input` feeds the last
+    /// [`Command::Input`] to the console before every frame.
+    pub fn spawn<B, F>(build: B, mut apply_input: F) -> Self
+    where
+        B: FnOnce() -> GameBoy + Send + 'static,
+        F: FnMut(&mut GameBoy, &I) + Send + 'static,
+    {
+        let (commands, command_receiver) = crossbeam_channel::unbounded();
+        let (event_sender, events) = crossbeam_channel::bounded(EVENTS_CAPACITY);
+        let (front_sender, front_receiver) = crossbeam_channel::bounded(1);
+
+        let thread = std::thread::spawn(move || {
+            let mut gb = build();
+            let _ = front_sender.send(gb.frame_front());
+
+            let serial = event_sender.clone();
+            let mut previous = gb.hooks_mut().on_serial.take();
+            gb.hooks_mut().on_serial = Some(Box::new(move |byte| {
+                if let Some(hook) = previous.as_mut() {
+                    hook(byte);
+                }
+                let _ = serial.send(Event::Serial(byte));
+            }));
+
+            run(gb, &command_receiver, &event_sender, &mut apply_input);
+            log::debug!(target: "gbemu::runner", "Emulation thread stopped");
+            let _ = event_sender.send(Event::Stopped);
+        });
+
+        let front = front_receiver
+            .recv()
+            .expect("The emulation thread panicked while building the console");
+
+        Self {
+            commands,
+            events,
+            front,
+            thread: Some(thread),
+        }
+    }
+
+    /// Sends a command, ignored once the thread has stopped
+    pub fn send(&self, command: Command<I>) {
+        let _ = self.commands.send(command);
+    }
+
+    /// Returns the command sender, to be cloned for other threads (e.g. input)
+    pub fn commands(&self) -> &Sender<Command<I>> {
+        &self.commands
+    }
+
+    /// Returns the event receiver, iterate it to wait for events
+    pub fn events(&self) -> &Receiver<Event> {
+        &self.events
+    }
+
+    /// Returns the handle on the last complete frame
+    pub fn front_buffer(&self) -> &FrontBuffer {
+        &self.front
+    }
+}
+
+impl<I> Drop for Runner<I> {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Stop);
+        if let Some(thread) = self.thread.take() {
+            // A panic on the emulation thread was already reported there
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Emulates frames and serves commands until stopped or disconnected
+fn run<I>(
+    mut gb: GameBoy,
+    commands: &Receiver<Command<I>>,
+    events: &Sender<Event>,
+    apply_input: &mut impl FnMut(&mut GameBoy, &I),
+) {
+    let speed = gb.clock().speed;
+    let mut limiter = FrameLimiter::new().clock_speed(speed);
+    let mut input = None;
+    let mut paused = false;
+    let mut throttle = true;
+    let mut run_ahead = 0;
+
+    loop {
+        // While paused there is nothing to do but wait for commands
+        let command = if paused {
+            commands.recv().ok()
+        } else {
+            match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        };
+
+        let reply = match command {
+            None if paused => return,
+            None => {
+                if let Some(input) = input.as_ref() {
+                    apply_input(&mut gb, input);
+                }
+                gb.run_frame_ahead(run_ahead);
+                if throttle {
+                    limiter.wait_frame();
+                }
+
+                let number = gb.frame_front().lock().number();
+                match events.try_send(Event::Frame(number)) {
+                    Ok(()) | Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Disconnected(_)) => return,
+                }
+                continue;
+            }
+            Some(Command::Input(value)) => {
+                input = Some(value);
+                continue;
+            }
+            Some(Command::Pause) => {
+                paused = true;
+                Event::Paused
+            }
+            Some(Command::Resume) => {
+                paused = false;
+                // Don't race to catch up with the time spent paused
+                limiter = FrameLimiter::new().clock_speed(speed);
+                Event::Resumed
+            }
+            Some(Command::AdvanceFrame) if paused => {
+                if let Some(input) = input.as_ref() {
+                    apply_input(&mut gb, input);
+                }
+                gb.advance_one_frame();
+                Event::Frame(gb.frame_front().lock().number())
+            }
+            Some(Command::AdvanceFrame) => continue,
+            Some(Command::RunAhead(frames)) => {
+                run_ahead = frames;
+                continue;
+            }
+            Some(Command::Throttle(enabled)) => {
+                throttle = enabled;
+                limiter = FrameLimiter::new().clock_speed(speed);
+                continue;
+            }
+            Some(Command::Reset) => {
+                gb.reset();
+                Event::Reset
+            }
+            Some(Command::SaveState) => Event::State(gb.save_state()),
+            Some(Command::LoadState(state)) => Event::StateLoaded(gb.load_state(&state)),
+            Some(Command::Stop) => return,
+        };
+
+        if events.send(reply).is_err() {
+            return;
+        }
+    }
+}
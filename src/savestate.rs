@@ -0,0 +1,730 @@
+//! Save states: snapshots of the whole emulator state that can be restored later.
+//!
+//! ### On-disk format
+//!
+//! All values are little-endian.
+//!
+//! | Field     | Size         | Notes                                   |
+//! |-----------|--------------|-----------------------------------------|
+//! | Magic     | 8            | `GBEMUSTA`                              |
+//! | Version   | 2            | [`STATE_VERSION`] when written          |
+//! | Thumbnail | 4 + len      | Optional frontend provided screenshot   |
+//! | Body      | rest         | Emulator state, layout depends on version |
+//!
+//! States written by an older version are migrated on load, states written
+//! by a newer version are refused.
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "ppu")]
+use crate::lcd::Lcd;
+use crate::{
+    cpu::{CpuMode, Register},
+    joypad::SgbJoypad,
+    memory::{locations, MemoryMode},
+    timer::Timer,
+    GameBoy, Model,
+};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a, stable across platforms and runs
+pub(crate) fn fnv1a<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u64 {
+    bytes.into_iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+pub const STATE_MAGIC: &[u8; 8] = b"GBEMUSTA";
+/// Version of the body layout written by this build
+pub const STATE_VERSION: u16 = 3;
+
+#[derive(Debug)]
+pub enum SaveStateError {
+    Io(io::Error),
+    /// Not a save state
+    InvalidMagic,
+    /// Written by a newer, incompatible version of the emulator
+    UnsupportedVersion(u16),
+    /// Data ends before the state does
+    Truncated,
+    /// Data contains values that can't be decoded
+    Corrupted,
+    /// The state belongs to a different cartridge
+    CartridgeMismatch,
+}
+
+impl fmt::Display for SaveStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::InvalidMagic => write!(f, "not a save state"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "save state version {} is newer than the supported {}",
+                version, STATE_VERSION
+            ),
+            Self::Truncated => write!(f, "save state is truncated"),
+            Self::Corrupted => write!(f, "save state is corrupted"),
+            Self::CartridgeMismatch => write!(f, "save state belongs to a different cartridge"),
+        }
+    }
+}
+
+impl std::error::Error for SaveStateError {}
+
+impl From<io::Error> for SaveStateError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveState {
+    /// Screenshot shown by frontends in slot pickers, in a format of their choice
+    pub thumbnail: Option<Vec<u8>>,
+    /// Emulator state in the [`STATE_VERSION`] layout
+    body: Vec<u8>,
+}
+
+impl SaveState {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = StateWriter::default();
+        w.bytes(STATE_MAGIC);
+        w.u16(STATE_VERSION);
+        let thumbnail = self.thumbnail.as_deref().unwrap_or_default();
+        w.u32(thumbnail.len() as u32);
+        w.bytes(thumbnail);
+        w.bytes(&self.body);
+        w.0
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveStateError> {
+        let mut r = StateReader::new(bytes);
+        if r.bytes(STATE_MAGIC.len())? != STATE_MAGIC {
+            return Err(SaveStateError::InvalidMagic);
+        }
+
+        let version = r.u16()?;
+        if version > STATE_VERSION {
+            return Err(SaveStateError::UnsupportedVersion(version));
+        }
+
+        let len = r.u32()? as usize;
+        let thumbnail = r.bytes(len)?;
+        let body = migrate(version, r.rest().to_vec())?;
+
+        Ok(Self {
+            thumbnail: (!thumbnail.is_empty()).then(|| thumbnail.to_vec()),
+            body,
+        })
+    }
+}
+
+/// Upgrades a body written by an older version to the current layout
+fn migrate(version: u16, body: Vec<u8>) -> Result<Vec<u8>, SaveStateError> {
+    // Version 0 was never released
+    if version == 0 {
+        return Err(SaveStateError::UnsupportedVersion(version));
+    }
+
+    let mut w = StateWriter(body);
+    // Version 2 appended the live MBC3 clock, which did not run before
+    if version < 2 {
+        w.u64(0);
+        w.bool(false);
+        w.bool(false);
+        w.u64(0);
+    }
+    // Version 3 appended the timing left out before: the dot remainder, the
+    // timer and SGB joypad state, and the LCD, serial and APU sections. The
+    // sections are left empty, so the console keeps its own
+    if version < 3 {
+        w.u64(0);
+        w.u64(0);
+        w.u64(0);
+        SgbJoypad::default().save(&mut w);
+        for _ in 0..3 {
+            w.section(|_| {});
+        }
+    }
+    Ok(w.0)
+}
+
+/// Loads a section into a copy of `current`, an empty one leaves it as is
+#[cfg(feature = "ppu")]
+fn load_section<T: Clone>(
+    current: &T,
+    section: &[u8],
+    load: impl FnOnce(&mut T, &mut StateReader) -> Result<(), SaveStateError>,
+) -> Result<T, SaveStateError> {
+    let mut value = current.clone();
+    if !section.is_empty() {
+        load(&mut value, &mut StateReader::new(section))?;
+    }
+    Ok(value)
+}
+
+impl GameBoy {
+    /// Takes a snapshot of the emulator state
+    pub fn save_state(&self) -> SaveState {
+        let mut w = StateWriter::default();
+
+        w.u16(self.cartridge_header.global_checksum);
+        w.u8(self.cartridge_header.header_checksum);
+        w.u8(self.model as u8);
+
+        for register in [
+            self.registers.af,
+            self.registers.bc,
+            self.registers.de,
+            self.registers.hl,
+            self.registers.sp,
+            self.registers.pc,
+        ] {
+            w.u16(*register);
+        }
+        w.bool(self.registers.ime);
+        match self.registers.mode {
+            CpuMode::Running => w.u8(0),
+            CpuMode::Halted => w.u8(1),
+            CpuMode::Stopped => w.u8(2),
+            CpuMode::SpeedSwitch(remaining) => {
+                w.u8(3);
+                w.u32(remaining as u32);
+            }
+        }
+
+        write_memory_mode(&mut w, &self.memory_mode);
+        w.u32(self.oam_dma.remaining as u32);
+        w.bytes(&self.memory);
+        w.u32(self.banks.len() as u32);
+        w.bytes(&self.banks);
+
+        let (counter, halted, carry, subsecond) = self.rtc.save();
+        w.u64(counter);
+        w.bool(halted);
+        w.bool(carry);
+        w.u64(subsecond);
+
+        w.u64(self.counters.dot_remainder);
+        w.u64(self.timer.div_cycles);
+        w.u64(self.timer.tima_cycles);
+        self.sgb_joypad.save(&mut w);
+        // The subsystems that can be compiled out
+        #[cfg(feature = "ppu")]
+        w.section(|w| self.lcd.save(w));
+        #[cfg(not(feature = "ppu"))]
+        w.section(|_| {});
+        #[cfg(feature = "serial")]
+        w.section(|w| w.u64(self.serial.remaining.unwrap_or(u64::MAX)));
+        #[cfg(not(feature = "serial"))]
+        w.section(|_| {});
+        #[cfg(feature = "apu")]
+        w.section(|w| w.bytes(&self.apu.save_state()));
+        #[cfg(not(feature = "apu"))]
+        w.section(|_| {});
+
+        SaveState {
+            thumbnail: None,
+            body: w.0,
+        }
+    }
+
+    /// Restores a snapshot taken with [`GameBoy::save_state`], the emulator
+    /// is left untouched on error.
+    pub fn load_state(&mut self, state: &SaveState) -> Result<(), SaveStateError> {
+        let mut r = StateReader::new(&state.body);
+
+        if r.u16()? != self.cartridge_header.global_checksum
+            || r.u8()? != self.cartridge_header.header_checksum
+        {
+            return Err(SaveStateError::CartridgeMismatch);
+        }
+
+        let model = match r.u8()? {
+            0 => Model::Dmg,
+            1 => Model::Sgb,
+            2 => Model::Cgb,
+            _ => return Err(SaveStateError::Corrupted),
+        };
+
+        let mut registers = self.registers;
+        for register in [
+            &mut registers.af,
+            &mut registers.bc,
+            &mut registers.de,
+            &mut registers.hl,
+            &mut registers.sp,
+            &mut registers.pc,
+        ] {
+            *register = Register { value: r.u16()? };
+        }
+        registers.ime = r.bool()?;
+        registers.mode = match r.u8()? {
+            0 => CpuMode::Running,
+            1 => CpuMode::Halted,
+            2 => CpuMode::Stopped,
+            3 => CpuMode::SpeedSwitch(r.u32()? as usize),
+            _ => return Err(SaveStateError::Corrupted),
+        };
+
+        let memory_mode = read_memory_mode(&mut r)?;
+        let dma_remaining = r.u32()? as usize;
+        let memory = r.bytes(self.memory.len())?;
+        let banks_len = r.u32()? as usize;
+        if banks_len != self.banks.len() {
+            return Err(SaveStateError::CartridgeMismatch);
+        }
+        let banks = r.bytes(banks_len)?;
+        let rtc = (r.u64()?, r.bool()?, r.bool()?, r.u64()?);
+
+        let dot_remainder = r.u64()?;
+        let timer = Timer {
+            div_cycles: r.u64()?,
+            tima_cycles: r.u64()?,
+        };
+        let mut sgb_joypad = self.sgb_joypad.clone();
+        sgb_joypad.load(&mut r)?;
+        #[cfg(feature = "ppu")]
+        let lcd = load_section(&self.lcd, r.section()?, Lcd::load)?;
+        #[cfg(not(feature = "ppu"))]
+        r.section()?;
+        #[cfg(feature = "serial")]
+        let serial_remaining = match r.section()? {
+            [] => self.serial.remaining,
+            section => match StateReader::new(section).u64()? {
+                u64::MAX => None,
+                remaining => Some(remaining),
+            },
+        };
+        #[cfg(not(feature = "serial"))]
+        r.section()?;
+        // Last, as the backend can't be rolled back
+        #[cfg(feature = "apu")]
+        self.apu.load_state(r.section()?)?;
+
+        self.model = model;
+        self.registers = registers;
+        self.memory_mode = memory_mode;
+        self.oam_dma.remaining = dma_remaining;
+        self.memory.copy_from_slice(memory);
+        self.banks.copy_from_slice(banks);
+        // The RAM no longer matches what was persisted
+        self.sram_dirty.mark_all();
+        self.rtc.load(rtc);
+        self.counters.dot_remainder = dot_remainder;
+        self.timer = timer;
+        self.sgb_joypad = sgb_joypad;
+        #[cfg(feature = "ppu")]
+        {
+            self.lcd = lcd;
+        }
+        #[cfg(feature = "serial")]
+        {
+            self.serial.remaining = serial_remaining;
+        }
+
+        Ok(())
+    }
+}
+
+impl GameBoy {
+    /// Fast and stable hash of the architecturally visible state: registers,
+    /// VRAM, WRAM, OAM, I/O, HRAM, cartridge RAM and MBC registers.
+    ///
+    /// Meant to be compared at frame boundaries between instances running in
+    /// parallel (netplay peers, differential testing).
+    pub fn state_hash(&self) -> u64 {
+        let mut w = StateWriter::default();
+        for register in [
+            self.registers.af,
+            self.registers.bc,
+            self.registers.de,
+            self.registers.hl,
+            self.registers.sp,
+            self.registers.pc,
+        ] {
+            w.u16(*register);
+        }
+        w.bool(self.registers.ime);
+        write_memory_mode(&mut w, &self.memory_mode);
+
+        fnv1a(
+            [
+                w.0.as_slice(),
+                &self.memory[0x8000..0xA000],
+                &self.memory[0xC000..0xE000],
+                &self.memory[locations::OAM],
+                &self.memory[0xFF00..],
+                &self.banks,
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+
+    /// Encodes the difference between the current state and a baseline,
+    /// usually a few bytes per frame against ~70 KiB for a full state.
+    ///
+    /// The bodies are XORed and runs of unchanged bytes are skipped:
+    /// a sequence of `(skip, len, bytes[len])` records with LEB128 lengths.
+    /// The bytes past the end of the baseline are never skipped.
+    pub fn state_delta(&self, baseline: &SaveState) -> Vec<u8> {
+        let current = self.save_state().body;
+        let mut w = StateWriter::default();
+        w.u32(current.len() as u32);
+
+        let base = |i: usize| baseline.body.get(i).copied().unwrap_or(0);
+        let changed = |i: usize| i >= baseline.body.len() || current[i] != base(i);
+
+        let mut position = 0;
+        while position < current.len() {
+            let start = (position..current.len())
+                .find(|i| changed(*i))
+                .unwrap_or(current.len());
+            let end = (start..current.len())
+                .find(|i| !changed(*i))
+                .unwrap_or(current.len());
+
+            w.varint(start - position);
+            w.varint(end - start);
+            for (i, value) in current[start..end].iter().enumerate() {
+                w.u8(value ^ base(start + i));
+            }
+
+            position = end;
+        }
+
+        w.0
+    }
+
+    /// Restores the state encoded by [`GameBoy::state_delta`] against the same baseline
+    pub fn apply_delta(
+        &mut self,
+        baseline: &SaveState,
+        delta: &[u8],
+    ) -> Result<(), SaveStateError> {
+        let mut r = StateReader::new(delta);
+        let len = r.u32()? as usize;
+        // The delta carries every byte past the end of the baseline
+        if len > baseline.body.len() + delta.len() {
+            return Err(SaveStateError::Corrupted);
+        }
+
+        let mut body = baseline.body.clone();
+        body.resize(len, 0);
+
+        let mut position: usize = 0;
+        while position < len {
+            position = position
+                .checked_add(r.varint()?)
+                .ok_or(SaveStateError::Corrupted)?;
+            let changed = r.varint()?;
+            let bytes = r.bytes(changed)?;
+            let end = position
+                .checked_add(changed)
+                .ok_or(SaveStateError::Corrupted)?;
+            body.get_mut(position..end)
+                .ok_or(SaveStateError::Corrupted)?
+                .iter_mut()
+                .zip(bytes)
+                .for_each(|(value, diff)| *value ^= diff);
+            position = end;
+        }
+
+        self.load_state(&SaveState {
+            thumbnail: None,
+            body,
+        })
+    }
+}
+
+fn write_memory_mode(w: &mut StateWriter, mode: &MemoryMode) {
+    match *mode {
+        MemoryMode::RomOnly => w.u8(0),
+        MemoryMode::RomRam => w.u8(1),
+        MemoryMode::MBC1 {
+            rom_bank_idx,
+            ram_bank_idx,
+            ram_enabled,
+            ram_banking,
+        } => {
+            w.u8(2);
+            w.u32(rom_bank_idx as u32);
+            w.u32(ram_bank_idx as u32);
+            w.bool(ram_enabled);
+            w.bool(ram_banking);
+        }
+        MemoryMode::MBC2 {
+            rom_bank_idx,
+            ram_enabled,
+        } => {
+            w.u8(3);
+            w.u32(rom_bank_idx as u32);
+            w.bool(ram_enabled);
+        }
+        MemoryMode::MBC3 {
+            rom_bank_idx,
+            ram_bank_idx,
+            ram_rtc_enabled,
+            rtc_selected,
+            rtc_latched,
+            rtc_seconds,
+            rtc_minutes,
+            rtc_hours,
+            rtc_days,
+        } => {
+            w.u8(4);
+            w.u32(rom_bank_idx as u32);
+            w.u32(ram_bank_idx as u32);
+            w.bool(ram_rtc_enabled);
+            w.u8(rtc_selected.unwrap_or(0xFF));
+            w.bool(rtc_latched);
+            w.u8(rtc_seconds);
+            w.u8(rtc_minutes);
+            w.u8(rtc_hours);
+            w.u16(rtc_days);
+        }
+        MemoryMode::MBC5 {
+            rom_bank_idx,
+            ram_bank_idx,
+            ram_enabled,
+            rumble_enabled,
+        } => {
+            w.u8(5);
+            w.u32(rom_bank_idx as u32);
+            w.u32(ram_bank_idx as u32);
+            w.bool(ram_enabled);
+            w.bool(rumble_enabled);
+        }
+    }
+}
+
+fn read_memory_mode(r: &mut StateReader) -> Result<MemoryMode, SaveStateError> {
+    Ok(match r.u8()? {
+        0 => MemoryMode::RomOnly,
+        1 => MemoryMode::RomRam,
+        2 => MemoryMode::MBC1 {
+            rom_bank_idx: r.u32()? as usize,
+            ram_bank_idx: r.u32()? as usize,
+            ram_enabled: r.bool()?,
+            ram_banking: r.bool()?,
+        },
+        3 => MemoryMode::MBC2 {
+            rom_bank_idx: r.u32()? as usize,
+            ram_enabled: r.bool()?,
+        },
+        4 => MemoryMode::MBC3 {
+            rom_bank_idx: r.u32()? as usize,
+            ram_bank_idx: r.u32()? as usize,
+            ram_rtc_enabled: r.bool()?,
+            rtc_selected: match r.u8()? {
+                0xFF => None,
+                selected => Some(selected),
+            },
+            rtc_latched: r.bool()?,
+            rtc_seconds: r.u8()?,
+            rtc_minutes: r.u8()?,
+            rtc_hours: r.u8()?,
+            rtc_days: r.u16()?,
+        },
+        5 => MemoryMode::MBC5 {
+            rom_bank_idx: r.u32()? as usize,
+            ram_bank_idx: r.u32()? as usize,
+            ram_enabled: r.bool()?,
+            rumble_enabled: r.bool()?,
+        },
+        _ => return Err(SaveStateError::Corrupted),
+    })
+}
+
+/// Stores save states as numbered slot files in a directory
+pub struct SlotManager {
+    dir: PathBuf,
+    /// File name prefix, usually the game title
+    name: String,
+}
+
+impl SlotManager {
+    /// Stores the slots in `dir`, the characters of `name` that can't be
+    /// part of a file name, such as path separators, are replaced with `_`
+    pub fn new(dir: impl AsRef<Path>, name: &str) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            name: name
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the path of the file backing a slot
+    pub fn slot_path(&self, slot: u8) -> PathBuf {
+        self.dir.join(format!("{}.ss{}", self.name, slot))
+    }
+
+    pub fn save_slot(
+        &self,
+        gb: &GameBoy,
+        slot: u8,
+        thumbnail: Option<Vec<u8>>,
+    ) -> Result<(), SaveStateError> {
+        let mut state = gb.save_state();
+        state.thumbnail = thumbnail;
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.slot_path(slot), state.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_slot(&self, gb: &mut GameBoy, slot: u8) -> Result<(), SaveStateError> {
+        let state = self.read_slot(slot)?;
+        gb.load_state(&state)
+    }
+
+    /// Reads a slot without loading it, e.g. to show its thumbnail
+    pub fn read_slot(&self, slot: u8) -> Result<SaveState, SaveStateError> {
+        SaveState::from_bytes(&std::fs::read(self.slot_path(slot))?)
+    }
+
+    /// Returns the slots that have a save state
+    pub fn occupied_slots(&self) -> Vec<u8> {
+        (0..=u8::MAX)
+            .filter(|slot| self.slot_path(*slot).is_file())
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StateWriter(Vec<u8>);
+
+impl StateWriter {
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.0.push(value as u8);
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn bytes(&mut self, value: &[u8]) {
+        self.0.extend_from_slice(value);
+    }
+
+    /// Writes the state of a subsystem that can be compiled out, prefixed
+    /// with its length so that builds without it skip it
+    pub(crate) fn section(&mut self, save: impl FnOnce(&mut Self)) {
+        let mut section = Self::default();
+        save(&mut section);
+        self.u32(section.0.len() as u32);
+        self.bytes(&section.0);
+    }
+
+    /// Unsigned LEB128
+    pub(crate) fn varint(&mut self, mut value: usize) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.0.push(byte);
+                break;
+            }
+            self.0.push(byte | 0x80);
+        }
+    }
+}
+
+pub(crate) struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], SaveStateError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(SaveStateError::Truncated)?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or(SaveStateError::Truncated)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position..];
+        self.position = self.data.len();
+        rest
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SaveStateError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, SaveStateError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SaveStateError::Corrupted),
+        }
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, SaveStateError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, SaveStateError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, SaveStateError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a section written by [`StateWriter::section`], empty when the
+    /// subsystem was compiled out of the build that wrote it
+    pub(crate) fn section(&mut self) -> Result<&'a [u8], SaveStateError> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    /// Unsigned LEB128
+    pub(crate) fn varint(&mut self) -> Result<usize, SaveStateError> {
+        let mut value = 0;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SaveStateError::Corrupted)
+    }
+}
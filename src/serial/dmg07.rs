@@ -0,0 +1,236 @@
+//! The DMG-07 four player adapter, a hub clocking the transfers of up to
+//! four consoles at once.
+//!
+//! The adapter starts in the ping phase, sending every console a 4 bytes
+//! packet: 0xFE, then three times a status byte holding the connected
+//! players in the high nibble and the player number in the low bits. The
+//! consoles answer with ACK1 and ACK2 (0x88), then RATE and SIZE. Player 1
+//! answering 0xAA instead of ACK1 starts the transmission phase, with the
+//! RATE and SIZE of that packet, after four 0xCC bytes.
+//!
+//! In the transmission phase, packets are `4 * SIZE` bytes long: each
+//! console sends its data in the first SIZE bytes, and receives the data
+//! of every player collected during the previous packet, player 1 first,
+//! 0xFF for the missing ones. A packet where every player sent only 0xFF
+//! restarts the ping phase.
+//!
+//! The adapter clock is only roughly documented, the periods used here are
+//! approximations: games depend on the order of the bytes, not their exact
+//! timing.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/Four_Player_Adapter.html)
+use crate::{
+    cpu::{Cpu, CYCLES_PER_FRAME},
+    GameBoy,
+};
+
+/// Consoles the adapter links at most
+pub const PLAYERS: usize = 4;
+/// Dots between the bytes of the ping phase, a packet per frame
+pub const PING_BYTE_DOTS: u64 = CYCLES_PER_FRAME / 4;
+
+const PING: u8 = 0xFE;
+const ACK: u8 = 0x88;
+const START: u8 = 0xAA;
+const RESTART: u8 = 0xCC;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Looking for players, until player 1 starts the transmission
+    Ping,
+    /// Announcing the transmission phase, with the 0xCC bytes left to send
+    Restart(u8),
+    /// Relaying the data of every player to every player
+    Transmission,
+}
+
+/// The adapter, clocking the consoles given to [`FourPlayerAdapter::run_frame`]
+/// in player order
+pub struct FourPlayerAdapter {
+    phase: Phase,
+    /// Byte of the current packet
+    index: usize,
+    /// Players which answered the last ping packet, as a bit per player
+    connected: u8,
+    /// Players answering the current ping packet
+    answering: u8,
+    rate: u8,
+    size: u8,
+    /// RATE and SIZE of player 1 in the current ping packet, set when it
+    /// asked for the transmission
+    start: Option<(u8, u8)>,
+    /// Data sent by every player during the current packet
+    incoming: [Vec<u8>; PLAYERS],
+    /// Data relayed during the current packet
+    outgoing: Vec<u8>,
+    /// Dots before the next byte
+    countdown: u64,
+    /// Dots every console ran past the last byte, an instruction is not
+    /// interrupted
+    overshoot: [u64; PLAYERS],
+}
+
+impl Default for FourPlayerAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FourPlayerAdapter {
+    pub fn new() -> Self {
+        Self {
+            phase: Phase::Ping,
+            index: 0,
+            connected: 0,
+            answering: 0,
+            rate: 0,
+            size: 0,
+            start: None,
+            incoming: Default::default(),
+            outgoing: vec![],
+            countdown: PING_BYTE_DOTS,
+            overshoot: [0; PLAYERS],
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Players which answered the last ping packet, as a bit per player
+    pub fn connected(&self) -> u8 {
+        self.connected
+    }
+
+    /// Bytes per player of the transmission packets
+    pub fn packet_size(&self) -> usize {
+        self.size as usize
+    }
+
+    /// Dots between two bytes
+    pub fn byte_period(&self) -> u64 {
+        match self.phase {
+            Phase::Ping => PING_BYTE_DOTS,
+            Phase::Restart(_) | Phase::Transmission => 512 * (1 + (self.rate & 0x0F) as u64),
+        }
+    }
+
+    /// Runs the consoles, players 1 to 4 in order, for a frame, taking
+    /// turns between the bytes so that they stay in step
+    ///
+    /// # Panics
+    ///
+    /// If more than four consoles are given.
+    pub fn run_frame(&mut self, consoles: &mut [GameBoy]) {
+        assert!(
+            consoles.len() <= PLAYERS,
+            "the adapter links {} consoles at most",
+            PLAYERS
+        );
+
+        let mut left = CYCLES_PER_FRAME;
+        while left > 0 {
+            let dots = self.countdown.min(left);
+            for (gb, overshoot) in consoles.iter_mut().zip(&mut self.overshoot) {
+                let end = gb.cycles() + dots.saturating_sub(*overshoot);
+                *overshoot = overshoot.saturating_sub(dots);
+                while gb.cycles() < end {
+                    gb.step();
+                }
+                *overshoot += gb.cycles() - end;
+            }
+            left -= dots;
+            self.countdown -= dots;
+            if self.countdown == 0 {
+                self.clock(consoles);
+                self.countdown = self.byte_period();
+            }
+        }
+    }
+
+    /// Clocks the next byte into every console at once
+    pub fn clock(&mut self, consoles: &mut [GameBoy]) {
+        let mut responses = [None; PLAYERS];
+        for (player, gb) in consoles.iter_mut().enumerate() {
+            responses[player] = gb.clock_serial(self.next_byte(player));
+        }
+        self.received(responses);
+    }
+
+    /// The byte sent to the player at the current index
+    fn next_byte(&self, player: usize) -> u8 {
+        match self.phase {
+            Phase::Ping if self.index == 0 => PING,
+            Phase::Ping => self.connected << 4 | (player as u8 + 1),
+            Phase::Restart(_) => RESTART,
+            Phase::Transmission => self.outgoing.get(self.index).copied().unwrap_or(0xFF),
+        }
+    }
+
+    fn received(&mut self, responses: [Option<u8>; PLAYERS]) {
+        match self.phase {
+            Phase::Ping => {
+                for (player, response) in responses.into_iter().enumerate() {
+                    match (self.index, response) {
+                        (0 | 1, Some(ACK)) => self.answering |= 1 << player,
+                        (0, Some(START)) if player == 0 => {
+                            self.answering |= 1;
+                            self.start = Some((0, 0));
+                        }
+                        _ => {}
+                    }
+                }
+                match (self.start.as_mut(), self.index, responses[0]) {
+                    (Some((rate, _)), 2, Some(byte)) => *rate = byte,
+                    (Some((_, size)), 3, Some(byte)) => *size = byte,
+                    _ => {}
+                }
+
+                self.index += 1;
+                if self.index == 4 {
+                    self.index = 0;
+                    self.connected = std::mem::take(&mut self.answering);
+                    if let Some((rate, size)) = self.start.take().filter(|(_, size)| *size > 0) {
+                        log::debug!(
+                            target: "gbemu::serial",
+                            "Four player adapter transmitting, rate {:#04x} size {}",
+                            rate,
+                            size
+                        );
+                        self.rate = rate;
+                        self.size = size;
+                        self.outgoing.clear();
+                        self.incoming = Default::default();
+                        self.phase = Phase::Restart(4);
+                    }
+                }
+            }
+            Phase::Restart(left) => {
+                self.phase = match left {
+                    1 => Phase::Transmission,
+                    left => Phase::Restart(left - 1),
+                };
+            }
+            Phase::Transmission => {
+                let size = self.size as usize;
+                if self.index < size {
+                    for (incoming, response) in self.incoming.iter_mut().zip(responses) {
+                        incoming.push(response.unwrap_or(0xFF));
+                    }
+                }
+
+                self.index += 1;
+                if self.index == PLAYERS * size {
+                    self.index = 0;
+                    let incoming = std::mem::take(&mut self.incoming);
+                    if incoming.iter().flatten().all(|byte| *byte == 0xFF) {
+                        log::debug!(target: "gbemu::serial", "Four player adapter back to ping");
+                        self.connected = 0;
+                        self.phase = Phase::Ping;
+                    }
+                    self.outgoing = incoming.concat();
+                }
+            }
+        }
+    }
+}
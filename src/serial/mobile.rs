@@ -0,0 +1,258 @@
+//! A stub of the Mobile Adapter GB, the cell phone link of the Japanese
+//! Pokémon Crystal, answering with canned data instead of a network.
+//!
+//! The console clocks every transfer and sends packets:
+//! `0x99 0x66`, the command, `0x00`, the data length (big-endian `u16`),
+//! the data and the checksum (big-endian sum of the header and data bytes).
+//! While the console sends its device byte (0x81) and then 0x00, the
+//! adapter answers with its own (0x88, the blue PDC adapter) and the
+//! command with bit 7 set as acknowledgement. The reply packet follows the
+//! same format, the console idling with 0x4B, and ends with the same
+//! device byte exchange. The adapter sends 0xD2 when it has nothing to say.
+//!
+//! Only the calls are stubbed, the data travelling over them comes from
+//! [`MobileAdapter::server_data`].
+//!
+//! [REFERENCE](https://shonumi.github.io/dandocs.html#magb)
+use std::collections::{HashMap, VecDeque};
+
+use super::SerialDevice;
+
+/// Device byte of the adapter, the blue one for PDC phones
+pub const ADAPTER_ID: u8 = 0x88;
+/// Sent by the adapter when it has nothing to say
+pub const IDLE: u8 = 0xD2;
+/// Size of the configuration memory
+pub const CONFIG_SIZE: usize = 0xC0;
+
+const MAGIC: [u8; 2] = [0x99, 0x66];
+/// Acknowledgement of a packet failing its checksum
+const CHECKSUM_ERROR: u8 = 0xF1;
+/// Reply reporting a failed command
+const ERROR: u8 = 0x6E;
+
+pub const BEGIN_SESSION: u8 = 0x10;
+pub const END_SESSION: u8 = 0x11;
+pub const DIAL: u8 = 0x12;
+pub const HANG_UP: u8 = 0x13;
+pub const TRANSFER_DATA: u8 = 0x15;
+pub const TELEPHONE_STATUS: u8 = 0x17;
+pub const READ_CONFIG: u8 = 0x19;
+pub const WRITE_CONFIG: u8 = 0x1A;
+pub const ISP_LOGIN: u8 = 0x21;
+pub const ISP_LOGOUT: u8 = 0x22;
+pub const OPEN_TCP: u8 = 0x23;
+pub const CLOSE_TCP: u8 = 0x24;
+pub const DNS_QUERY: u8 = 0x28;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A packet sent by the console
+pub struct MobilePacket {
+    pub command: u8,
+    pub data: Vec<u8>,
+}
+
+/// The Mobile Adapter GB, to plug with
+/// [`GameBoy::connect_serial`](crate::GameBoy::connect_serial)
+pub struct MobileAdapter {
+    /// Bytes of the packet being received
+    packet: Vec<u8>,
+    /// Bytes to shift out, the acknowledgement and the reply
+    outgoing: VecDeque<u8>,
+    /// Byte shifted out during the transfer in progress
+    shifting: u8,
+    config: [u8; CONFIG_SIZE],
+    responses: HashMap<u8, Vec<u8>>,
+    server_data: VecDeque<Vec<u8>>,
+    dns: HashMap<Vec<u8>, [u8; 4]>,
+    address: [u8; 4],
+    in_call: bool,
+    received: Vec<MobilePacket>,
+}
+
+impl Default for MobileAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MobileAdapter {
+    /// An adapter with a blank configuration, answering every call
+    pub fn new() -> Self {
+        Self {
+            packet: vec![],
+            outgoing: VecDeque::new(),
+            shifting: IDLE,
+            config: [0; CONFIG_SIZE],
+            responses: HashMap::new(),
+            server_data: VecDeque::new(),
+            dns: HashMap::new(),
+            address: [127, 0, 0, 1],
+            in_call: false,
+            received: vec![],
+        }
+    }
+
+    /// Replies to the command with this data instead of the stub one
+    pub fn response(mut self, command: u8, data: impl Into<Vec<u8>>) -> Self {
+        self.responses.insert(command, data.into());
+        self
+    }
+
+    /// Queues data the server sends, one chunk per transfer command
+    pub fn server_data(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.server_data.push_back(data.into());
+        self
+    }
+
+    /// Resolves the host name to the address, other names fail
+    pub fn dns(mut self, name: &str, address: [u8; 4]) -> Self {
+        self.dns.insert(name.as_bytes().to_vec(), address);
+        self
+    }
+
+    /// Address given to the console on ISP login, 127.0.0.1 by default
+    pub fn address(mut self, address: [u8; 4]) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// The configuration memory, holding the user settings
+    pub fn config(&self) -> &[u8; CONFIG_SIZE] {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut [u8; CONFIG_SIZE] {
+        &mut self.config
+    }
+
+    /// Returns the packets received since the last call
+    pub fn take_packets(&mut self) -> Vec<MobilePacket> {
+        std::mem::take(&mut self.received)
+    }
+
+    /// Adds the byte to the packet being received, replying once complete
+    fn receive_byte(&mut self, byte: u8) {
+        // Idle bytes between packets
+        if self.packet.len() < MAGIC.len() && byte != MAGIC[self.packet.len()] {
+            self.packet.clear();
+            return;
+        }
+        self.packet.push(byte);
+
+        let Some(header) = self.packet.get(2..6) else {
+            return;
+        };
+        let length = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let end = 6 + length + 2;
+        if self.packet.len() < end {
+            return;
+        }
+
+        let packet = std::mem::take(&mut self.packet);
+        let command = packet[2];
+        let checksum = u16::from_be_bytes([packet[end - 2], packet[end - 1]]);
+        if checksum != sum(&packet[2..end - 2]) {
+            log::warn!(target: "gbemu::serial", "Mobile adapter packet {:#04x} fails its checksum", command);
+            self.outgoing.extend([ADAPTER_ID, CHECKSUM_ERROR]);
+            return;
+        }
+
+        let data = packet[6..end - 2].to_vec();
+        log::debug!(target: "gbemu::serial", "Mobile adapter command {:#04x} {:02x?}", command, data);
+        let reply = match self.responses.get(&command) {
+            Some(data) => Ok(data.clone()),
+            None => self.execute(command, &data),
+        };
+        self.received.push(MobilePacket { command, data });
+
+        self.outgoing.extend([ADAPTER_ID, command ^ 0x80]);
+        let (command, data) = match reply {
+            Ok(data) => (command ^ 0x80, data),
+            Err(error) => (ERROR, vec![command, error]),
+        };
+        let mut body = vec![command, 0x00];
+        body.extend((data.len() as u16).to_be_bytes());
+        body.extend(data);
+        self.outgoing.extend(MAGIC);
+        self.outgoing.extend(&body);
+        self.outgoing.extend(sum(&body).to_be_bytes());
+        self.outgoing.extend([ADAPTER_ID, 0x00]);
+    }
+
+    /// Stub behaviour of the commands, returns the reply data or an error
+    /// code
+    fn execute(&mut self, command: u8, data: &[u8]) -> Result<Vec<u8>, u8> {
+        match command {
+            // The console checks its greeting is echoed
+            BEGIN_SESSION => Ok(data.to_vec()),
+            END_SESSION | ISP_LOGOUT => Ok(vec![]),
+            DIAL => {
+                self.in_call = true;
+                Ok(vec![])
+            }
+            HANG_UP => {
+                self.in_call = false;
+                Ok(vec![])
+            }
+            TELEPHONE_STATUS => Ok(vec![if self.in_call { 0x05 } else { 0x00 }, 0x4D, 0x00]),
+            READ_CONFIG => {
+                let [offset, length] = *data else {
+                    return Err(0x02);
+                };
+                let range = offset as usize..offset as usize + length as usize;
+                let config = self.config.get(range).ok_or(0x02)?;
+                Ok([offset].into_iter().chain(config.iter().copied()).collect())
+            }
+            WRITE_CONFIG => {
+                let (&offset, bytes) = data.split_first().ok_or(0x02)?;
+                self.config
+                    .get_mut(offset as usize..offset as usize + bytes.len())
+                    .ok_or(0x02)?
+                    .copy_from_slice(bytes);
+                Ok(vec![offset, bytes.len() as u8])
+            }
+            ISP_LOGIN => {
+                let mut reply = self.address.to_vec();
+                // Primary and secondary DNS servers
+                reply.extend([0; 8]);
+                Ok(reply)
+            }
+            OPEN_TCP => Ok(vec![0x00]),
+            CLOSE_TCP => Ok(data.first().map(|id| vec![*id]).unwrap_or_default()),
+            TRANSFER_DATA => {
+                let id = *data.first().ok_or(0x02)?;
+                let mut reply = vec![id];
+                reply.extend(self.server_data.pop_front().unwrap_or_default());
+                Ok(reply)
+            }
+            DNS_QUERY => self
+                .dns
+                .get(data)
+                .map(|address| address.to_vec())
+                .ok_or(0x01),
+            _ => Err(0x00),
+        }
+    }
+}
+
+/// Checksum of the header and data bytes
+fn sum(bytes: &[u8]) -> u16 {
+    bytes
+        .iter()
+        .fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16))
+}
+
+impl SerialDevice for MobileAdapter {
+    fn send(&mut self, byte: u8) {
+        // Both ways at once: what the adapter shifts out can't depend on it
+        self.shifting = self.outgoing.pop_front().unwrap_or(IDLE);
+        if self.shifting == IDLE {
+            self.receive_byte(byte);
+        }
+    }
+
+    fn receive(&mut self) -> u8 {
+        self.shifting
+    }
+}
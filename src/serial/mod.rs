@@ -0,0 +1,155 @@
+//! The serial port, through which consoles are linked.
+//!
+//! The peer on the other end of the cable is a [`SerialDevice`]. Without one
+//! the line idles high: transfers clocked by the console receive 0xFF and
+//! the ones waiting for a clock from the peer never complete.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/Serial_Data_Transfer_(Link_Cable).html)
+use crate::{
+    cpu::{Cpu, Interrupt},
+    memory::{
+        io::{IoHandler, IoMap},
+        locations,
+    },
+    GameBoy, Model,
+};
+
+mod dmg07;
+pub mod mobile;
+mod tcp;
+mod text;
+
+pub use dmg07::{FourPlayerAdapter, Phase};
+pub use mobile::MobileAdapter;
+pub use tcp::TcpLink;
+pub use text::TextSink;
+
+/// Dots a transfer clocked by the console takes: 8 bits at 8192 Hz
+pub const TRANSFER_DOTS: u64 = 4096;
+/// Dots a transfer takes with the CGB fast clock, 32 times faster
+pub const FAST_TRANSFER_DOTS: u64 = TRANSFER_DOTS / 32;
+
+/// A peer plugged in the link port
+pub trait SerialDevice {
+    /// The console started a transfer with its own clock, shifting out
+    /// `byte`
+    fn send(&mut self, byte: u8);
+
+    /// The transfer clocked by the console completed, returns the byte
+    /// shifted in from the peer. It may block waiting for the peer: the
+    /// emulation is paused meanwhile, so its timing is unaffected.
+    fn receive(&mut self) -> u8;
+
+    /// The console waits for the peer to clock a transfer, with `byte` in
+    /// SB. Returns the byte shifted in once the peer clocked one, polled
+    /// after every instruction.
+    fn poll_external(&mut self, byte: u8) -> Option<u8> {
+        let _ = byte;
+        None
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Serial {
+    device: Option<Box<dyn SerialDevice>>,
+    /// Dots left before the transfer clocked by the console completes
+    pub(crate) remaining: Option<u64>,
+}
+
+impl GameBoy {
+    /// Plugs a device in the link port, replacing the previous one
+    pub fn connect_serial(&mut self, device: impl SerialDevice + 'static) {
+        self.serial.device = Some(Box::new(device));
+    }
+
+    /// Unplugs the device from the link port, returning it
+    pub fn disconnect_serial(&mut self) -> Option<Box<dyn SerialDevice>> {
+        self.serial.device.take()
+    }
+
+    /// A peer clocks a transfer into the console, e.g. a hub driving several
+    /// consoles: shifts `byte` in and returns the byte shifted out, `None`
+    /// if the console was not waiting for a peer clock
+    pub fn clock_serial(&mut self, byte: u8) -> Option<u8> {
+        if self.memory[locations::SC] & 0b1000_0001 != 0b1000_0000 {
+            return None;
+        }
+        let sent = self.memory[locations::SB];
+        self.sent(sent);
+        self.complete_transfer(byte);
+        Some(sent)
+    }
+
+    /// Advances the transfer in progress by the given dots
+    pub(crate) fn serial_step(&mut self, dots: u64) {
+        let sc = self.memory[locations::SC];
+        if sc & 0b1000_0000 == 0 {
+            return;
+        }
+
+        match self.serial.remaining {
+            Some(remaining) if remaining > dots => self.serial.remaining = Some(remaining - dots),
+            Some(_) => {
+                self.serial.remaining = None;
+                let received = self
+                    .serial
+                    .device
+                    .as_mut()
+                    .map_or(0xFF, |device| device.receive());
+                self.complete_transfer(received);
+            }
+            // Clocked by the peer
+            None if sc & 0b1 == 0 => {
+                let byte = self.memory[locations::SB];
+                let Some(device) = self.serial.device.as_mut() else {
+                    return;
+                };
+                if let Some(received) = device.poll_external(byte) {
+                    self.clock_serial(received);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn sent(&mut self, byte: u8) {
+        self.serial_output.push(byte);
+        self.log_serial(byte);
+        if let Some(hook) = self.hooks.on_serial.as_mut() {
+            hook(byte);
+        }
+    }
+
+    fn complete_transfer(&mut self, received: u8) {
+        log::trace!(target: "gbemu::serial", "Received {:#04x}", received);
+        self.memory[locations::SB] = received;
+        self.memory[locations::SC] &= 0b0111_1111;
+        self.interrupt(Interrupt::SerialTranferComplete);
+    }
+}
+
+pub(crate) fn install_io(io: &mut IoMap) {
+    io.install(locations::SC, IoHandler::write(write_sc));
+}
+
+fn write_sc(gb: &mut GameBoy, address: usize, value: u8) {
+    gb.memory[address] = value;
+    if value & 0b1000_0001 != 0b1000_0001 {
+        // Waiting for the peer clock, or cancelled
+        gb.serial.remaining = None;
+        return;
+    }
+
+    let byte = gb.memory[locations::SB];
+    log::trace!(target: "gbemu::serial", "Sending {:#04x}", byte);
+    gb.sent(byte);
+    if let Some(device) = gb.serial.device.as_mut() {
+        device.send(byte);
+    }
+    let fast = gb.model() == Model::Cgb && value & 0b10 != 0;
+    gb.serial.remaining = Some(if fast {
+        FAST_TRANSFER_DOTS
+    } else {
+        TRANSFER_DOTS
+    });
+}
@@ -0,0 +1,259 @@
+//! Link cable over TCP, between two emulator processes.
+//!
+//! Messages are length-prefixed: a big-endian `u16` length followed by a
+//! kind byte and its payload. Both sides greet each other with the protocol
+//! version, then the roles are negotiated transfer by transfer, as on
+//! hardware: the side clocking a transfer sends it, the side waiting for a
+//! clock replies with its own byte. When both clock at once, nobody listens
+//! and both read 0xFF.
+//!
+//! The byte is sent as soon as the transfer starts, so the network has the
+//! whole transfer time to deliver the reply, and the emulation waits for it
+//! at the end, keeping the emulated clocks exact: latency only slows the
+//! game down. A peer that does not answer in time is taken as not
+//! listening.
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+    time::{Duration, Instant},
+};
+
+use super::SerialDevice;
+
+const VERSION: u8 = 1;
+
+const HELLO: u8 = 0x00;
+const TRANSFER: u8 = 0x01;
+const REPLY: u8 = 0x02;
+const CANCEL: u8 = 0x03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Message {
+    /// Greeting, with the protocol version
+    Hello(u8),
+    /// A byte clocked by the peer, numbered to match its reply
+    Transfer { seq: u32, byte: u8 },
+    /// The byte shifted out in response to a transfer
+    Reply { seq: u32, byte: u8 },
+    /// The transfer timed out, it must not be answered
+    Cancel { seq: u32 },
+}
+
+impl Message {
+    fn encode(self) -> Vec<u8> {
+        let mut body = vec![];
+        match self {
+            Self::Hello(version) => body.extend([HELLO, version]),
+            Self::Transfer { seq, byte } => {
+                body.push(TRANSFER);
+                body.extend(seq.to_be_bytes());
+                body.push(byte);
+            }
+            Self::Reply { seq, byte } => {
+                body.push(REPLY);
+                body.extend(seq.to_be_bytes());
+                body.push(byte);
+            }
+            Self::Cancel { seq } => {
+                body.push(CANCEL);
+                body.extend(seq.to_be_bytes());
+            }
+        }
+        let mut message = (body.len() as u16).to_be_bytes().to_vec();
+        message.extend(body);
+        message
+    }
+
+    fn read(stream: &mut impl Read) -> io::Result<Self> {
+        let mut len = [0; 2];
+        stream.read_exact(&mut len)?;
+        let mut body = vec![0; u16::from_be_bytes(len) as usize];
+        stream.read_exact(&mut body)?;
+
+        let seq = || -> io::Result<u32> {
+            body.get(1..5)
+                .map(|seq| u32::from_be_bytes(seq.try_into().unwrap()))
+                .ok_or_else(invalid)
+        };
+        let byte = |index: usize| body.get(index).copied().ok_or_else(invalid);
+        match body.first() {
+            Some(&HELLO) => Ok(Self::Hello(byte(1)?)),
+            Some(&TRANSFER) => Ok(Self::Transfer {
+                seq: seq()?,
+                byte: byte(5)?,
+            }),
+            Some(&REPLY) => Ok(Self::Reply {
+                seq: seq()?,
+                byte: byte(5)?,
+            }),
+            Some(&CANCEL) => Ok(Self::Cancel { seq: seq()? }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid link message")
+}
+
+/// A [`SerialDevice`] linking to another emulator over TCP
+pub struct TcpLink {
+    stream: TcpStream,
+    /// Messages read by the receiving thread
+    messages: Receiver<Message>,
+    timeout: Duration,
+    /// Number of the last transfer clocked by this side
+    seq: u32,
+    /// Transfer clocked by the peer, waiting for the console to listen
+    incoming: Option<(u32, u8)>,
+    connected: bool,
+}
+
+impl TcpLink {
+    /// Waits for a peer to connect on the listener
+    pub fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        Self::handshake(stream)
+    }
+
+    /// Connects to a peer waiting in [`TcpLink::accept`]
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::handshake(TcpStream::connect(address)?)
+    }
+
+    fn handshake(mut stream: TcpStream) -> io::Result<Self> {
+        // Transfers are single bytes, they must not wait to be batched
+        stream.set_nodelay(true)?;
+        stream.write_all(&Message::Hello(VERSION).encode())?;
+        match Message::read(&mut stream)? {
+            Message::Hello(VERSION) => {}
+            Message::Hello(version) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("peer speaks link protocol {}, not {}", version, VERSION),
+                ))
+            }
+            _ => return Err(invalid()),
+        }
+
+        let (sender, messages) = mpsc::channel();
+        let mut reader = stream.try_clone()?;
+        std::thread::spawn(move || {
+            while let Ok(message) = Message::read(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            stream,
+            messages,
+            timeout: Duration::from_millis(100),
+            seq: 0,
+            incoming: None,
+            connected: true,
+        })
+    }
+
+    /// Longest wait for the peer to answer a transfer, 100 ms by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The peer is still connected
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn send_message(&mut self, message: Message) {
+        if !self.connected {
+            return;
+        }
+        if let Err(err) = self.stream.write_all(&message.encode()) {
+            log::warn!(target: "gbemu::serial", "Link peer lost: {}", err);
+            self.connected = false;
+        }
+    }
+
+    /// Handles a message that is not the reply being waited for
+    fn handle(&mut self, message: Message) {
+        match message {
+            Message::Transfer { seq, byte } => self.incoming = Some((seq, byte)),
+            Message::Cancel { seq }
+                if self.incoming.is_some_and(|(incoming, _)| incoming == seq) =>
+            {
+                self.incoming = None;
+            }
+            // Late replies and repeated greetings
+            _ => {}
+        }
+    }
+}
+
+impl SerialDevice for TcpLink {
+    fn send(&mut self, byte: u8) {
+        // The peer clocked first but nobody listened
+        if let Some((seq, _)) = self.incoming.take() {
+            self.send_message(Message::Reply { seq, byte: 0xFF });
+        }
+        self.seq = self.seq.wrapping_add(1);
+        let seq = self.seq;
+        self.send_message(Message::Transfer { seq, byte });
+    }
+
+    fn receive(&mut self) -> u8 {
+        let deadline = Instant::now() + self.timeout;
+        while self.connected {
+            let message = match self
+                .messages
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    log::warn!(target: "gbemu::serial", "Link peer lost");
+                    self.connected = false;
+                    break;
+                }
+            };
+            match message {
+                Message::Reply { seq, byte } if seq == self.seq => return byte,
+                // The peer clocked a transfer too, neither listens
+                Message::Transfer { seq, .. } => {
+                    self.send_message(Message::Reply { seq, byte: 0xFF })
+                }
+                message => self.handle(message),
+            }
+        }
+
+        if self.connected {
+            log::warn!(target: "gbemu::serial", "Link peer did not answer transfer {}", self.seq);
+            let seq = self.seq;
+            self.send_message(Message::Cancel { seq });
+        }
+        0xFF
+    }
+
+    fn poll_external(&mut self, byte: u8) -> Option<u8> {
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => self.handle(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if self.connected {
+                        log::warn!(target: "gbemu::serial", "Link peer lost");
+                    }
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+
+        let (seq, received) = self.incoming.take()?;
+        self.send_message(Message::Reply { seq, byte });
+        Some(received)
+    }
+}
@@ -0,0 +1,107 @@
+//! Text printed on the serial port by test ROMs and homebrew `printf`s,
+//! decoded into lines.
+use crate::GameBoy;
+
+/// Called with every complete line
+type LineCallback = Box<dyn FnMut(&str)>;
+
+/// Decodes the bytes sent on the serial port as text, split into lines.
+///
+/// Printable ASCII and tabs are kept, `\n` ends a line and anything else
+/// (carriage returns, the 0xFF of an idle line) is dropped. Complete lines
+/// are given to the callback if there is one, or collected otherwise.
+///
+/// ```
+/// use gbemu::serial::TextSink;
+///
+/// let mut sink = TextSink::new();
+/// sink.extend(b"cpu_instrs\n\nPass");
+/// assert_eq!(sink.lines(), ["cpu_instrs", ""]);
+/// assert_eq!(sink.text(), "cpu_instrs\n\nPass");
+/// ```
+#[derive(Default)]
+pub struct TextSink {
+    /// Line being printed
+    line: String,
+    lines: Vec<String>,
+    on_line: Option<LineCallback>,
+    /// Bytes of the serial output decoded by [`TextSink::update`]
+    decoded: usize,
+}
+
+impl TextSink {
+    /// Collects the lines, see [`TextSink::text`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gives every complete line to the callback, without the `\n`
+    pub fn with_callback(on_line: impl FnMut(&str) + 'static) -> Self {
+        Self {
+            on_line: Some(Box::new(on_line)),
+            ..Default::default()
+        }
+    }
+
+    /// Decodes a byte sent
+    pub fn push(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                let line = std::mem::take(&mut self.line);
+                match self.on_line.as_mut() {
+                    Some(on_line) => on_line(&line),
+                    None => self.lines.push(line),
+                }
+            }
+            b'\t' | 0x20..=0x7E => self.line.push(byte as char),
+            _ => {}
+        }
+    }
+
+    pub fn extend(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.push(byte);
+        }
+    }
+
+    /// Decodes the bytes the console sent since the last call, from
+    /// [`GameBoy::serial_output`]. With run-ahead it only sees the real
+    /// frames, unlike the [`on_serial`](crate::hooks::Hooks::on_serial) hook.
+    pub fn update(&mut self, gb: &GameBoy) {
+        let output = gb.serial_output();
+        // Another console
+        if self.decoded > output.len() {
+            self.decoded = 0;
+        }
+        self.extend(&output[self.decoded..]);
+        self.decoded = output.len();
+    }
+
+    /// Lines collected, empty with a callback
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The line being printed, not ended by `\n` yet
+    pub fn partial_line(&self) -> &str {
+        &self.line
+    }
+
+    /// Lines collected and the partial line, joined by `\n`
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text.push_str(&self.line);
+        text
+    }
+
+    /// Ends the partial line, if any, as if `\n` was sent
+    pub fn flush(&mut self) {
+        if !self.line.is_empty() {
+            self.push(b'\n');
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Async wrapper for running emulators inside [tokio](https://docs.rs/tokio)
+//! services, e.g. a chat bot where viewers vote on the next input.
+//!
+//! A [`Session`] owns the [`GameBoy`] and runs frames from async code,
+//! yielding to the runtime after every frame so many sessions share a few
+//! threads. Other tasks talk to it through a [`SessionHandle`]: inputs go in
+//! and complete [`Frame`]s come out, over bounded queues.
+//!
+//! The console cannot be sent across threads once hooks are installed, so a
+//! session runs on a [`LocalSet`](tokio::task::LocalSet), while its handle can
+//! go anywhere:
+//!
+//! ```no_run
+//! use gbemu::{session::Session, GameBoy};
+//!
+//! # async fn serve() {
+//! let rom = std::fs::read("game.gb").unwrap();
+//! // The input type and how it reaches the console are up to the service
+//! let (mut session, mut handle) = Session::new(GameBoy::new(&rom), 8, |_gb, _input: &u8| {});
+//! let local = tokio::task::LocalSet::new();
+//! local.spawn_local(async move { session.run().await });
+//!
+//! handle.inputs.send(0b0000_1000).await.unwrap();
+//! while let Some(frame) = local.run_until(handle.frames.recv()).await {
+//!     // Encode frame.rgba() and post it
+//! }
+//! # }
+//! ```
+//!
+//! A session is serialized as a [save state](crate::savestate), to be stored
+//! between restarts of the service.
+use std::time::Duration;
+
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+
+use crate::{
+    cpu::Cpu,
+    savestate::{SaveState, SaveStateError},
+    video::Frame,
+    GameBoy,
+};
+
+/// Feeds an input to the console before every frame
+pub type ApplyInput<I> = Box<dyn FnMut(&mut GameBoy, &I)>;
+
+/// Ends of the [`Session`] queues, both can be moved to other tasks
+pub struct SessionHandle<I> {
+    /// Inputs for the next frames, senders wait while the queue is full
+    pub inputs: mpsc::Sender<I>,
+    /// Complete frames, dropped by the session while the queue is full
+    pub frames: mpsc::Receiver<Frame>,
+}
+
+/// A [`GameBoy`] driven from async code
+pub struct Session<I> {
+    gb: GameBoy,
+    apply_input: ApplyInput<I>,
+    inputs: mpsc::Receiver<I>,
+    /// Last input received, applied until the next one
+    input: Option<I>,
+    frames: mpsc::Sender<Frame>,
+    /// Number of the last frame queued, to skip unchanged ones
+    last_frame: u64,
+}
+
+impl<I> Session<I> {
+    /// Wraps the console, with queues holding up to `capacity` inputs and
+    /// frames. `apply_input` feeds the last input to the console before
+    /// every frame.
+    pub fn new<F>(gb: GameBoy, capacity: usize, apply_input: F) -> (Self, SessionHandle<I>)
+    where
+        F: FnMut(&mut GameBoy, &I) + 'static,
+    {
+        let (input_sender, inputs) = mpsc::channel(capacity);
+        let (frames, frame_receiver) = mpsc::channel(capacity);
+        let last_frame = gb.frame_front().lock().number();
+        let session = Self {
+            gb,
+            apply_input: Box::new(apply_input),
+            inputs,
+            input: None,
+            frames,
+            last_frame,
+        };
+
+        let handle = SessionHandle {
+            inputs: input_sender,
+            frames: frame_receiver,
+        };
+        (session, handle)
+    }
+
+    pub fn gb(&self) -> &GameBoy {
+        &self.gb
+    }
+
+    pub fn gb_mut(&mut self) -> &mut GameBoy {
+        &mut self.gb
+    }
+
+    /// Runs one frame with the last input received, then yields.
+    ///
+    /// Returns the number of the last complete frame, which is queued when
+    /// it changed and the queue has room.
+    pub async fn run_frame(&mut self) -> u64 {
+        // Only the most recent input matters, earlier ones would not be seen
+        while let Ok(input) = self.inputs.try_recv() {
+            self.input = Some(input);
+        }
+        if let Some(input) = self.input.as_ref() {
+            (self.apply_input)(&mut self.gb, input);
+        }
+        self.gb.run_frame();
+
+        let number = {
+            let front = self.gb.frame_front();
+            let frame = front.lock();
+            if frame.number() != self.last_frame {
+                self.last_frame = frame.number();
+                // A slow reader misses frames rather than slowing the emulation
+                let _ = self.frames.try_send(frame.clone());
+            }
+            frame.number()
+        };
+
+        tokio::task::yield_now().await;
+        number
+    }
+
+    /// Runs `count` frames as fast as the runtime allows
+    pub async fn run_frames(&mut self, count: u64) -> u64 {
+        let mut number = self.last_frame;
+        for _ in 0..count {
+            number = self.run_frame().await;
+        }
+        number
+    }
+
+    /// Runs frames at hardware speed until the handle is dropped. Frames
+    /// missed while the runtime was busy are skipped, not caught up.
+    pub async fn run(&mut self) {
+        let period = Duration::from_secs_f64(1.0 / self.gb.clock().frame_rate());
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        while !self.frames.is_closed() {
+            interval.tick().await;
+            self.run_frame().await;
+        }
+        log::debug!(target: "gbemu::session", "Session handle dropped");
+    }
+
+    /// Serializes the session, see [`SaveState::to_bytes`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.save_state().to_bytes()
+    }
+
+    /// Restores a session serialized with [`Session::to_bytes`], the console
+    /// must have the same cartridge inserted
+    pub fn load_bytes(&mut self, bytes: &[u8]) -> Result<(), SaveStateError> {
+        self.load_state(&SaveState::from_bytes(bytes)?)
+    }
+
+    pub fn save_state(&self) -> SaveState {
+        self.gb.save_state()
+    }
+
+    pub fn load_state(&mut self, state: &SaveState) -> Result<(), SaveStateError> {
+        self.gb.load_state(state)?;
+        // The input belongs to the abandoned timeline
+        self.input = None;
+        Ok(())
+    }
+}
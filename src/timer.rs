@@ -1 +1,104 @@
+//! DIV and TIMA counting, advanced after every [`Cpu::step`], and the traps
+//! of the timer registers.
+//!
+//! [REFERENCE](https://gbdev.io/pandocs/Timer_and_Divider_Registers.html)
+use crate::{
+    cpu::{Cpu, Interrupt},
+    memory::{
+        io::{IoHandler, IoMap},
+        locations, Read, Write,
+    },
+    GameBoy,
+};
 
+/// Normal speed T-cycles between two DIV increments
+const DIV_PERIOD: u64 = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// T-cycles elapsed but not yet turned into increments, carried across steps
+/// so that many short steps count as much as a long one
+pub(crate) struct Timer {
+    /// Toward the next DIV increment
+    pub(crate) div_cycles: u64,
+    /// Toward the next TIMA increment, while enabled
+    pub(crate) tima_cycles: u64,
+}
+
+/// TIMA increments per second for the frequency selected by TAC bits 0-1
+pub fn frequency(tac: u8) -> u32 {
+    match tac & 0b11 {
+        0b00 => 4096,
+        0b01 => 262144,
+        0b10 => 65536,
+        _ => 16384,
+    }
+}
+
+/// Normal speed T-cycles between two TIMA increments for the frequency
+/// selected by TAC bits 0-1
+fn period(tac: u8) -> u64 {
+    (crate::cpu::CPU_CLOCK_SPEED as u32 / frequency(tac)) as u64
+}
+
+/// Bit of the 16-bit system counter whose falling edge increments TIMA on
+/// hardware, for the frequency selected by TAC bits 0-1. DIV holds bits 8-15.
+pub fn edge_bit(tac: u8) -> u8 {
+    match tac & 0b11 {
+        0b00 => 9,
+        0b01 => 3,
+        0b10 => 5,
+        _ => 7,
+    }
+}
+
+impl GameBoy {
+    /// Advances DIV and, while enabled, TIMA by the normal speed T-cycles
+    /// executed
+    pub(crate) fn timer_step(&mut self, cycles: u64) {
+        self.timer.div_cycles += cycles;
+        let div_ticks = self.timer.div_cycles / DIV_PERIOD;
+        self.timer.div_cycles %= DIV_PERIOD;
+        // Cannot use write_u8 because it would trigger the write memory trap
+        let div = &mut self.memory[locations::DIV];
+        *div = div.wrapping_add(div_ticks as u8);
+
+        let timer_ctrl = self.read_u8(locations::TAC);
+        if timer_ctrl & 0b100 == 0 {
+            return;
+        }
+        self.timer.tima_cycles += cycles;
+        let timer_ticks = self.timer.tima_cycles / period(timer_ctrl);
+        self.timer.tima_cycles %= period(timer_ctrl);
+        for _ in 0..timer_ticks {
+            let timer_counter = self.read_u8(locations::TIMA);
+
+            if timer_counter == 255 {
+                let timer_modulo = self.read_u8(locations::TMA);
+                self.write_u8(locations::TIMA, timer_modulo);
+                log::trace!(target: "gbemu::timer", "TIMA overflow, reloaded with {:#04x}", timer_modulo);
+                self.interrupt(Interrupt::TimerOverflow);
+            } else {
+                self.write_u8(locations::TIMA, timer_counter.wrapping_add(1));
+            }
+        }
+    }
+}
+
+pub(crate) fn install_io(io: &mut IoMap) {
+    io.install(locations::DIV, IoHandler::write(write_div));
+    io.install(locations::TAC, IoHandler::write(write_tac));
+}
+
+/// Any write resets the divider, along with the cycles toward its next increment
+fn write_div(gb: &mut GameBoy, address: usize, _value: u8) {
+    gb.memory[address] = 0;
+    gb.timer.div_cycles = 0;
+}
+
+/// Changing the frequency restarts the count, the unused bits read high
+fn write_tac(gb: &mut GameBoy, address: usize, value: u8) {
+    if gb.memory[address] & 0b11 != value & 0b11 {
+        gb.memory[locations::TIMA] = 0;
+    }
+    gb.memory[address] = 0b1111_1000 | value;
+}
@@ -0,0 +1,105 @@
+//! Instruction trace lines, in the formats of other emulators so that the
+//! community diffing scripts work unchanged against this one.
+//!
+//! Lines describe the state before an instruction executes. Set the
+//! [`Hooks::on_trace`](crate::hooks::Hooks::on_trace) hook to receive one
+//! per instruction, in the format chosen with [`GameBoy::set_trace_format`]:
+//!
+//! ```
+//! use std::{cell::RefCell, rc::Rc};
+//!
+//! use gbemu::{cartridge, cpu::Cpu, tracelog::TraceFormat, GameBoy};
+//!
+//! let mut gb = GameBoy::new(&cartridge::test_rom());
+//! let lines = Rc::new(RefCell::new(Vec::new()));
+//! let trace = lines.clone();
+//! gb.set_trace_format(TraceFormat::Bgb);
+//! gb.hooks_mut().on_trace = Some(Box::new(move |line| trace.borrow_mut().push(line.to_string())));
+//! gb.step();
+//! assert!(lines.borrow()[0].starts_with("A:01 F:Z-HC BC:0013"));
+//! ```
+use crate::{
+    cpu::Registers,
+    memory::{locations, Memory},
+    GameBoy,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// [`GameBoy::status_line`]
+    #[default]
+    Native,
+    /// BGB's trace log:
+    /// `A:01 F:Z-HC BC:0013 DE:00D8 HL:014D SP:FFFE PC:0100 (cy: 0)`
+    Bgb,
+    /// Gambatte's tracelog:
+    /// `PC:0100 SP:FFFE A:01 B:00 C:13 D:00 E:D8 F:B0 H:01 L:4D LY:00 cy:0`
+    Gambatte,
+}
+
+impl GameBoy {
+    /// Returns the trace line of the next instruction in the format
+    pub fn trace_line(&self, format: TraceFormat) -> String {
+        let registers = self.registers();
+        let [a, f] = (*registers.af).to_be_bytes();
+        let [b, c] = (*registers.bc).to_be_bytes();
+        let [d, e] = (*registers.de).to_be_bytes();
+        let [h, l] = (*registers.hl).to_be_bytes();
+        let cycles = self.cycles();
+        match format {
+            TraceFormat::Native => self.status_line(),
+            TraceFormat::Bgb => {
+                let flag = |mask: u8, name: char| if f & mask != 0 { name } else { '-' };
+                format!(
+                    "A:{:02X} F:{}{}{}{} BC:{:04X} DE:{:04X} HL:{:04X} SP:{:04X} PC:{:04X} (cy: {})",
+                    a,
+                    flag(0b1000_0000, 'Z'),
+                    flag(0b0100_0000, 'N'),
+                    flag(0b0010_0000, 'H'),
+                    flag(0b0001_0000, 'C'),
+                    *registers.bc,
+                    *registers.de,
+                    *registers.hl,
+                    *registers.sp,
+                    *registers.pc,
+                    cycles
+                )
+            }
+            TraceFormat::Gambatte => format!(
+                "PC:{:04X} SP:{:04X} A:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} F:{:02X} H:{:02X} L:{:02X} LY:{:02X} cy:{}",
+                *registers.pc,
+                *registers.sp,
+                a,
+                b,
+                c,
+                d,
+                e,
+                f,
+                h,
+                l,
+                self.memory()[locations::LY],
+                cycles
+            ),
+        }
+    }
+
+    pub fn trace_format(&self) -> TraceFormat {
+        self.trace_format
+    }
+
+    /// Selects the format of the lines given to the trace hook
+    pub fn set_trace_format(&mut self, format: TraceFormat) {
+        self.trace_format = format;
+    }
+
+    /// Hands the trace line of the next instruction to the hook, if any
+    pub(crate) fn trace_instruction(&mut self) {
+        if self.hooks.on_trace.is_none() {
+            return;
+        }
+        let line = self.trace_line(self.trace_format);
+        if let Some(hook) = self.hooks.on_trace.as_mut() {
+            hook(&line);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Conversion of the LCD output into images for frontends, in the
+//! [`PixelFormat`] they need.
+//!
+//! The frame is converted on VBlank, once complete, and can go through a
+//! ghosting filter emulating the slow response of the DMG LCD. Many games
+//! flicker sprites on alternate frames to fake transparency, which looks
+//! wrong without it.
+//!
+//! Frames are double buffered: the emulation converts into a back buffer and
+//! swaps it with the [`FrontBuffer`] on VBlank, so a render thread can read
+//! the last complete frame while the next one is emulated, without copies.
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{
+    latency::LatencyEvent,
+    lcd::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    GameBoy,
+};
+
+/// Colors of the four shades, from white to black
+pub const DMG_PALETTE: [[u8; 3]; 4] = [
+    [0xFF, 0xFF, 0xFF],
+    [0xAA, 0xAA, 0xAA],
+    [0x55, 0x55, 0x55],
+    [0x00, 0x00, 0x00],
+];
+
+/// Bytes per pixel of the RGBA frame
+const RGBA_BYTES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Pixel formats a frame can be converted to, row by row without padding
+pub enum PixelFormat {
+    /// One byte per pixel holding the shade (0-3), to look up in the
+    /// [palette](GameBoy::palette) on the GPU. Ghosting is not applied.
+    Indexed,
+    /// Two bytes per pixel, little endian, red in the low bits as in CGB
+    /// palette memory
+    Rgb555,
+    /// Four bytes per pixel, 8 bits per channel
+    Rgba8888,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Indexed => 1,
+            Self::Rgb555 => 2,
+            Self::Rgba8888 => RGBA_BYTES,
+        }
+    }
+
+    /// Size of a whole frame in this format
+    pub fn frame_size(&self) -> usize {
+        SCREEN_WIDTH * SCREEN_HEIGHT * self.bytes_per_pixel()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A complete frame, as shades and as RGBA
+pub struct Frame {
+    /// VBlanks seen before this frame was produced, 0 before the first one
+    number: u64,
+    indexed: Vec<u8>,
+    rgba: Vec<u8>,
+}
+
+impl Frame {
+    fn blank(palette: &[[u8; 3]; 4]) -> Self {
+        Self {
+            number: 0,
+            indexed: vec![0; PixelFormat::Indexed.frame_size()],
+            rgba: palette[0]
+                .into_iter()
+                .chain([0xFF])
+                .cycle()
+                .take(PixelFormat::Rgba8888.frame_size())
+                .collect(),
+        }
+    }
+
+    /// Increases by one with every frame, readers can skip unchanged frames
+    /// and notice dropped ones
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// The shades (0 is white, 3 is black), one byte per pixel, row by row
+    pub fn indexed(&self) -> &[u8] {
+        &self.indexed
+    }
+
+    /// The colors, 8 bits per channel, row by row
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Converts the frame into `out`, which must be [`PixelFormat::frame_size`]
+    /// bytes long. Frontends can write straight into a mapped texture this way.
+    pub fn convert_into(&self, format: PixelFormat, out: &mut [u8]) {
+        assert_eq!(
+            out.len(),
+            format.frame_size(),
+            "The buffer does not fit a {:?} frame",
+            format
+        );
+
+        match format {
+            PixelFormat::Indexed => out.copy_from_slice(&self.indexed),
+            PixelFormat::Rgba8888 => out.copy_from_slice(&self.rgba),
+            PixelFormat::Rgb555 => {
+                let pixels = self.rgba.chunks_exact(RGBA_BYTES);
+                for (out, pixel) in out.chunks_exact_mut(2).zip(pixels) {
+                    let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|c| c as u16 >> 3);
+                    out.copy_from_slice(&(r | g << 5 | b << 10).to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Shared handle on the last complete [`Frame`], cheap to clone and to send
+/// to another thread.
+///
+/// The emulation locks it on every VBlank to swap the buffers, which takes a
+/// frame conversion. Readers must only hold the lock to upload or copy the
+/// frame: while they do, the emulation waits at the next VBlank. Frames
+/// completed in the meantime replace each other, [`Frame::number`] tells.
+pub struct FrontBuffer(Arc<Mutex<Frame>>);
+
+impl FrontBuffer {
+    /// Locks the front frame until the guard is dropped
+    pub fn lock(&self) -> MutexGuard<'_, Frame> {
+        // A reader panicking while holding the frame leaves nothing half written
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Video {
+    /// Weight of the previous frame when blending, 0 disables ghosting
+    ghosting: f32,
+    /// Colors of the four shades
+    palette: [[u8; 3]; 4],
+    /// Frame being converted, swapped with the front one once ready
+    back: Frame,
+    front: FrontBuffer,
+}
+
+impl Video {
+    pub(crate) fn new(ghosting: f32, palette: [[u8; 3]; 4]) -> Self {
+        Self {
+            ghosting: checked_strength(ghosting),
+            palette,
+            back: Frame::blank(&palette),
+            front: FrontBuffer(Arc::new(Mutex::new(Frame::blank(&palette)))),
+        }
+    }
+}
+
+fn checked_strength(strength: f32) -> f32 {
+    assert!(
+        (0.0..1.0).contains(&strength),
+        "The ghosting strength must be in 0..1"
+    );
+    strength
+}
+
+impl GameBoy {
+    /// Returns a handle on the last complete frame, swapped on every VBlank.
+    ///
+    /// Unlike [`GameBoy::frame`] it never shows a frame being drawn, and has
+    /// the ghosting filter applied. The handle can be kept, and moved to a
+    /// render thread, see [`FrontBuffer`] for the locking rules.
+    pub fn frame_front(&self) -> FrontBuffer {
+        self.video.front.clone()
+    }
+
+    /// Converts the last complete frame into `out`, see [`Frame::convert_into`]
+    pub fn frame_into(&self, format: PixelFormat, out: &mut [u8]) {
+        self.video.front.lock().convert_into(format, out);
+    }
+
+    /// Returns the colors of the four shades, from white to black
+    pub fn palette(&self) -> [[u8; 3]; 4] {
+        self.video.palette
+    }
+
+    /// Changes the colors of the four shades, [`DMG_PALETTE`] by default.
+    /// Takes effect from the next frame.
+    pub fn set_palette(&mut self, palette: [[u8; 3]; 4]) {
+        self.video.palette = palette;
+    }
+
+    /// Returns the ghosting strength, see [`GameBoy::set_ghosting`]
+    pub fn ghosting(&self) -> f32 {
+        self.video.ghosting
+    }
+
+    /// Blends each frame with the previous output to emulate the LCD
+    /// response. `strength` is the weight of the previous output, between 0
+    /// (off, the default) and 1 excluded: 0.5 evens out flickering sprites,
+    /// lower values leave a subtle trail.
+    pub fn set_ghosting(&mut self, strength: f32) {
+        self.video.ghosting = checked_strength(strength);
+    }
+
+    /// Converts the frame completed on VBlank into the back buffer, then
+    /// makes it the front one
+    pub(crate) fn produce_frame(&mut self) {
+        let video = &mut self.video;
+        let mut front = video.front.lock();
+        let back = &mut video.back;
+        back.number = front.number + 1;
+        back.indexed.copy_from_slice(&self.lcd.frame);
+
+        // The previous output is the front frame
+        let previous = video.ghosting;
+        let current = 1.0 - previous;
+        let pixels = back.rgba.chunks_exact_mut(RGBA_BYTES);
+        let shown = front.rgba.chunks_exact(RGBA_BYTES);
+        for ((pixel, shown), &shade) in pixels.zip(shown).zip(self.lcd.frame.iter()) {
+            let color = video.palette[shade as usize].into_iter().chain([0xFF]);
+            for ((channel, shown), value) in pixel.iter_mut().zip(shown).zip(color) {
+                *channel = (*shown as f32 * previous + value as f32 * current).round() as u8;
+            }
+        }
+
+        std::mem::swap(&mut *front, back);
+        let number = front.number;
+        drop(front);
+        self.record_latency(LatencyEvent::FramePresented(number));
+    }
+
+    /// Makes a white frame the front one, without ghosting, while the LCD
+    /// shows nothing
+    pub(crate) fn produce_blank_frame(&mut self) {
+        let video = &mut self.video;
+        let mut front = video.front.lock();
+        let mut blank = Frame::blank(&video.palette);
+        blank.number = front.number + 1;
+        video.back = std::mem::replace(&mut *front, blank);
+        let number = front.number;
+        drop(front);
+        self.record_latency(LatencyEvent::FramePresented(number));
+    }
+}
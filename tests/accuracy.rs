@@ -0,0 +1,96 @@
+mod common;
+
+use gbemu::{
+    accuracy::{AccuracyConfig, AccuracyProfile},
+    cpu::{Cpu, Registers},
+    memory::{locations, Memory, RamInit, Read, Write},
+    GameBoy,
+};
+
+/// What a program sees of each quirk
+#[derive(Debug, PartialEq, Eq)]
+struct Quirks {
+    /// Read of WRAM during OAM DMA
+    dma_read: u8,
+    /// Read of the missing external RAM
+    open_bus: u8,
+    /// STAT written in VBlank on DMG requests an interrupt
+    stat_write_bug: bool,
+    /// Blocked accesses are reported
+    reports: bool,
+}
+
+fn quirks(profile: AccuracyProfile) -> Quirks {
+    // LD A,[0xA000]; LD A,0xC0; JP 0xFF80
+    let program = [0xFA, 0x00, 0xA0, 0x3E, 0xC0, 0xC3, 0x80, 0xFF];
+    let mut gb = GameBoy::builder(&common::rom_with_program(&program))
+        .ram_init(RamInit::Zeros)
+        .accuracy(profile)
+        .build();
+    // LDH (DMA),A; LD A,(0xC000); JR -2
+    let routine = [0xE0, 0x46, 0xFA, 0x00, 0xC0, 0x18, 0xFE];
+    gb.memory_mut()[0xFF80..][..routine.len()].copy_from_slice(&routine);
+    gb.write_u8(0xC000, 0x42);
+    let a = |gb: &GameBoy| gb.registers().af.to_be_bytes()[0];
+
+    gb.step();
+    let open_bus = a(&gb);
+    for _ in 0..4 {
+        gb.step();
+    }
+    let dma_read = a(&gb);
+
+    gb.write_u8(locations::LYC, 0x90);
+    gb.run_to_vblank().unwrap();
+    gb.write_u8(locations::IF, 0x00);
+    gb.write_u8(locations::STAT, 0x00);
+    let stat_write_bug = gb.read_u8(locations::IF) & 0b10 != 0;
+
+    Quirks {
+        dma_read,
+        open_bus,
+        stat_write_bug,
+        reports: gb.accuracy().report_oam_dma_violations,
+    }
+}
+
+#[test]
+fn each_profile_changes_what_programs_see() {
+    assert_eq!(
+        quirks(AccuracyProfile::Fast),
+        Quirks {
+            dma_read: 0x42,
+            open_bus: 0xFF,
+            stat_write_bug: false,
+            reports: false,
+        }
+    );
+    assert_eq!(
+        quirks(AccuracyProfile::Balanced),
+        Quirks {
+            dma_read: 0xFF,
+            open_bus: 0xFF,
+            stat_write_bug: true,
+            reports: false,
+        }
+    );
+    // The high byte of the operand was the last value on the bus
+    assert_eq!(
+        quirks(AccuracyProfile::Strict),
+        Quirks {
+            dma_read: 0xFF,
+            open_bus: 0xA0,
+            stat_write_bug: true,
+            reports: true,
+        }
+    );
+}
+
+#[test]
+fn the_default_is_balanced() {
+    assert_eq!(AccuracyProfile::default(), AccuracyProfile::Balanced);
+    assert_eq!(
+        AccuracyConfig::default(),
+        AccuracyConfig::from(AccuracyProfile::Balanced)
+    );
+}
@@ -0,0 +1,199 @@
+use gbemu::instructions::alu::{self, Flags};
+
+/// Flags computed on wider integers, as a reference for the 8-bit operations
+fn reference(result: i32, half: i32, subtract: bool, half_limit: i32) -> Flags {
+    let (half_carry, carry) = if subtract {
+        (half < 0, result < 0)
+    } else {
+        (half > half_limit, result > 0xFF)
+    };
+    Flags::new(result & 0xFF == 0, subtract, half_carry, carry)
+}
+
+#[test]
+fn additions_for_every_operand() {
+    for a in 0..=0xFFu8 {
+        for value in 0..=0xFFu8 {
+            for carry in [false, true] {
+                let c = carry as i32;
+                let sum = a as i32 + value as i32 + c;
+                let half = (a & 0xF) as i32 + (value & 0xF) as i32 + c;
+                let expected = (sum as u8, reference(sum, half, false, 0xF));
+                assert_eq!(
+                    alu::adc8(a, value, carry),
+                    expected,
+                    "ADC {:#04x} {:#04x} {}",
+                    a,
+                    value,
+                    carry
+                );
+                if !carry {
+                    assert_eq!(
+                        alu::add8(a, value),
+                        expected,
+                        "ADD {:#04x} {:#04x}",
+                        a,
+                        value
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn subtractions_for_every_operand() {
+    for a in 0..=0xFFu8 {
+        for value in 0..=0xFFu8 {
+            for carry in [false, true] {
+                let c = carry as i32;
+                let difference = a as i32 - value as i32 - c;
+                let half = (a & 0xF) as i32 - (value & 0xF) as i32 - c;
+                let expected = (difference as u8, reference(difference, half, true, 0));
+                assert_eq!(
+                    alu::sbc8(a, value, carry),
+                    expected,
+                    "SBC {:#04x} {:#04x} {}",
+                    a,
+                    value,
+                    carry
+                );
+                if !carry {
+                    assert_eq!(
+                        alu::sub8(a, value),
+                        expected,
+                        "SUB {:#04x} {:#04x}",
+                        a,
+                        value
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn logic_for_every_operand() {
+    for a in 0..=0xFFu8 {
+        for value in 0..=0xFFu8 {
+            let and = a & value;
+            assert_eq!(
+                alu::and8(a, value),
+                (and, Flags::new(and == 0, false, true, false))
+            );
+            let xor = a ^ value;
+            assert_eq!(
+                alu::xor8(a, value),
+                (xor, Flags::new(xor == 0, false, false, false))
+            );
+            let or = a | value;
+            assert_eq!(
+                alu::or8(a, value),
+                (or, Flags::new(or == 0, false, false, false))
+            );
+        }
+    }
+}
+
+#[test]
+fn increments_keep_the_carry() {
+    for value in 0..=0xFFu8 {
+        let (result, flags) = alu::inc8(value);
+        let (add, add_flags) = alu::add8(value, 1);
+        assert_eq!(result, add);
+        assert_eq!(
+            flags,
+            Flags {
+                carry: None,
+                ..add_flags
+            },
+            "INC {:#04x}",
+            value
+        );
+
+        let (result, flags) = alu::dec8(value);
+        let (sub, sub_flags) = alu::sub8(value, 1);
+        assert_eq!(result, sub);
+        assert_eq!(
+            flags,
+            Flags {
+                carry: None,
+                ..sub_flags
+            },
+            "DEC {:#04x}",
+            value
+        );
+    }
+}
+
+#[test]
+fn sixteen_bit_addition_keeps_zero() {
+    for a in (0..=0xFFFFu32).step_by(0x0F1) {
+        for value in (0..=0xFFFFu32).step_by(0x0E3) {
+            let sum = a + value;
+            let half_carry = (a & 0xFFF) + (value & 0xFFF) > 0xFFF;
+            let expected = Flags {
+                zero: None,
+                ..Flags::new(false, false, half_carry, sum > 0xFFFF)
+            };
+            assert_eq!(
+                alu::add16(a as u16, value as u16),
+                (sum as u16, expected),
+                "ADD {:#06x} {:#06x}",
+                a,
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn stack_offsets_take_the_flags_from_the_low_byte() {
+    for sp in [0x0000, 0x00FF, 0x0F0F, 0xD000, 0xFFF8, 0xFFFF] {
+        for offset in i8::MIN..=i8::MAX {
+            let (result, flags) = alu::add_sp(sp, offset);
+            assert_eq!(result, (sp as i32 + offset as i32) as u16);
+            // Same flags as adding the offset byte to the low byte of SP
+            let (_, low) = alu::add8(sp as u8, offset as u8);
+            assert_eq!(
+                flags,
+                Flags {
+                    zero: Some(false),
+                    ..low
+                },
+                "{:#06x} {}",
+                sp,
+                offset
+            );
+        }
+    }
+}
+
+#[test]
+fn decimal_adjust_of_bcd_operations() {
+    let bcd = |n: u8| (n / 10) << 4 | (n % 10);
+    for x in 0..100u8 {
+        for y in 0..100u8 {
+            let (sum, flags) = alu::add8(bcd(x), bcd(y));
+            let (adjusted, daa) =
+                alu::daa(sum, false, flags.half_carry.unwrap(), flags.carry.unwrap());
+            let expected = (x + y) % 100;
+            assert_eq!(adjusted, bcd(expected), "{} + {}", x, y);
+            assert_eq!(daa.carry, Some(x + y >= 100), "{} + {}", x, y);
+            assert_eq!(daa.zero, Some(expected == 0));
+            assert_eq!((daa.subtract, daa.half_carry), (None, Some(false)));
+
+            let (difference, flags) = alu::sub8(bcd(x), bcd(y));
+            let (adjusted, daa) = alu::daa(
+                difference,
+                true,
+                flags.half_carry.unwrap(),
+                flags.carry.unwrap(),
+            );
+            let expected = (100 + x - y) % 100;
+            assert_eq!(adjusted, bcd(expected), "{} - {}", x, y);
+            assert_eq!(daa.carry, Some(x < y), "{} - {}", x, y);
+            assert_eq!(daa.zero, Some(expected == 0));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use gbemu::{
+    cartridge::HeaderBuilder,
+    cpu::{Cpu, Registers},
+    instructions::{
+        asm::Program, decode, instruction_length, routines::Condition, Assemble, Instr,
+        Register16Index, Register8Index as R,
+    },
+    memory::Read,
+    GameBoy,
+};
+
+#[test]
+fn assembling_inverts_decoding() {
+    let mut mismatches = vec![];
+    let prefixed = (0..=0xFF_u8).map(|cb| [0xCB, cb, 0x00]);
+    let unprefixed = (0..=0xFF_u8)
+        .filter(|opcode| *opcode != 0xCB)
+        .map(|opcode| [opcode, 0x34, 0x12]);
+    for bytes in unprefixed.chain(prefixed) {
+        let length = instruction_length(bytes[0]) as usize;
+        let instruction = decode(&bytes[..length]);
+        let assembled = instruction.assemble();
+        // STOP is assembled with its operand
+        if assembled[..length] != bytes[..length] {
+            mismatches.push(format!("{:02x?}: {:02x?}", &bytes[..length], assembled));
+        }
+    }
+    assert!(mismatches.is_empty(), "{:#?}", mismatches);
+}
+
+#[test]
+fn programs_run() {
+    // Counts down B from 3, adding 2 to A each time
+    let mut program = Program::at(0x0150).ld_a_imm(0).ld_imm(R::B, 3);
+    let start = program.here();
+    program = program
+        .add_imm(2)
+        .dec(R::B)
+        .jr_if_to(Condition::NotZero, start)
+        .ld_mem_a(0xC000)
+        .ld16(Register16Index::HL, 0xC001)
+        .ld(R::HL, R::A);
+    let end = program.here();
+    let program = program.jr_to(end);
+
+    let rom = HeaderBuilder::new().code(&program.bytes()).build();
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..64 {
+        gb.step();
+    }
+
+    assert_eq!(gb.read_u8(0xC000), 6);
+    assert_eq!(gb.read_u8(0xC001), 6);
+    assert_eq!(*gb.registers().pc, end);
+}
+
+#[test]
+fn programs_take_any_instruction() {
+    let bytes = Program::new()
+        .instr(Instr::Daa)
+        .jp(0x0150)
+        .raw(&[0xD3])
+        .into_bytes();
+    assert_eq!(bytes, [0x27, 0xC3, 0x50, 0x01, 0xD3]);
+}
+
+#[test]
+#[should_panic(expected = "out of reach")]
+fn far_relative_jumps_panic() {
+    Program::at(0x0150).jr_to(0x0300);
+}
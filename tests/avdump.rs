@@ -0,0 +1,54 @@
+use gbemu::{
+    avdump::{self, AvRecord},
+    cartridge,
+    memory::{locations, RamInit, Write},
+    GameBoy,
+};
+
+fn console() -> GameBoy {
+    GameBoy::builder(&cartridge::test_rom())
+        .ram_init(RamInit::Seeded(7))
+        .build()
+}
+
+#[test]
+fn dumps_are_reproducible() {
+    let mut dump = Vec::new();
+    avdump::write_dump(&mut console(), 5, &mut dump).unwrap();
+    let golden = avdump::parse_dump(std::str::from_utf8(&dump).unwrap()).unwrap();
+
+    assert_eq!(golden.len(), 5);
+    assert_eq!(golden[4].frame, 5);
+    let actual = console().av_dump(5);
+    assert_eq!(avdump::first_mismatch(&golden, &actual), None);
+}
+
+#[test]
+fn finds_the_first_mismatch() {
+    let mut gb = console();
+    let golden = gb.av_dump(4);
+
+    let mut gb = console();
+    let mut actual = gb.av_dump(2);
+    gb.write_u8(locations::NR50, 0x00);
+    actual.extend(gb.av_dump(2));
+    assert_eq!(avdump::first_mismatch(&golden, &actual), Some(2));
+    assert_ne!(golden[2].audio, actual[2].audio);
+    assert_eq!(golden[2].video, actual[2].video);
+
+    assert_eq!(avdump::first_mismatch(&golden, &golden[..3]), Some(3));
+}
+
+#[test]
+fn records_round_trip_as_text() {
+    let record = AvRecord {
+        frame: 60,
+        video: 0x0123_4567_89AB_CDEF,
+        audio: 0xFEDC_BA98_7654_3210,
+    };
+    let line = record.to_string();
+    assert_eq!(line, "0000003c 0123456789abcdef fedcba9876543210");
+    assert_eq!(line.parse(), Ok(record));
+    assert!("3c 01".parse::<AvRecord>().is_err());
+    assert!("3c 01 02 03".parse::<AvRecord>().is_err());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use gbemu::{
+    backend::{ApuBackend, NullPpu, PpuBackend},
+    lcd::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    memory::Memory,
+    GameBoy,
+};
+
+/// Paints every line black, counting them
+struct BlackPpu(Arc<Mutex<Vec<u8>>>);
+
+impl PpuBackend for BlackPpu {
+    fn render_line(&mut self, _: &[u8], line: u8, _: &mut u8, shades: &mut [u8; SCREEN_WIDTH]) {
+        self.0.lock().unwrap().push(line);
+        shades.fill(3);
+    }
+}
+
+/// Records the sound register writes
+struct RecordingApu(Arc<Mutex<Vec<(u16, u8)>>>);
+
+impl ApuBackend for RecordingApu {
+    fn write(&mut self, address: u16, value: u8) {
+        self.0.lock().unwrap().push((address, value));
+    }
+}
+
+#[test]
+fn the_ppu_backend_draws_the_lines() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let mut gb = GameBoy::builder(&common::rom_with_program(&[0x18, 0xFE]))
+        .ppu_backend(BlackPpu(lines.clone()))
+        .build();
+
+    gb.run_to_vblank().unwrap();
+    assert_eq!(
+        *lines.lock().unwrap(),
+        (0..SCREEN_HEIGHT as u8).collect::<Vec<_>>()
+    );
+    assert!(gb.frame().iter().all(|&shade| shade == 3));
+}
+
+#[test]
+fn the_null_ppu_leaves_the_screen_white() {
+    let mut gb = GameBoy::builder(&common::rom_with_program(&[0x18, 0xFE]))
+        .ppu_backend(NullPpu)
+        .build();
+    // Black background tiles would show with the DMG renderer
+    gb.memory_mut()[0x8000..0x8010].fill(0xFF);
+
+    gb.run_to_vblank().unwrap();
+    assert!(gb.frame().iter().all(|&shade| shade == 0));
+}
+
+#[test]
+fn the_apu_backend_gets_the_sound_register_writes() {
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    // LD A,0x77; LDH (NR50),A; LDH (0x30),A; JR -2
+    let program = [0x3E, 0x77, 0xE0, 0x24, 0xE0, 0x30, 0x18, 0xFE];
+    let mut gb = GameBoy::builder(&common::rom_with_program(&program))
+        .apu_backend(RecordingApu(writes.clone()))
+        .build();
+    writes.lock().unwrap().clear();
+
+    gb.run_frame();
+    assert_eq!(*writes.lock().unwrap(), [(0xFF24, 0x77), (0xFF30, 0x77)]);
+    assert_eq!(gb.memory()[0xFF24], 0x77);
+}
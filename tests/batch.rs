@@ -0,0 +1,46 @@
+mod common;
+
+use std::{collections::HashSet, ops::ControlFlow, sync::Mutex};
+
+use gbemu::{batch, memory::RamInit, GameBoy};
+
+/// Counts from 0 in A, forever
+const PROGRAM: [u8; 3] = [
+    0x3C, // INC A
+    0x18, 0xFD, // JR -3
+];
+
+#[test]
+fn instances_match_sequential_runs() {
+    let rom = common::rom_with_program(&PROGRAM);
+    let builder = |seed| GameBoy::builder(&rom).ram_init(RamInit::Seeded(seed));
+    let configs = (0..12).map(builder).collect();
+    // Instances stop at different frames, so they finish out of order
+    let frames = |index: usize| 1 + index as u64 % 5;
+    let threads = Mutex::new(HashSet::new());
+
+    let hashes = batch::run_parallel_on(4, configs, |index, gb| {
+        threads.lock().unwrap().insert(std::thread::current().id());
+        if gb.frames() < frames(index) {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(gb.state_hash())
+        }
+    });
+
+    assert!(threads.into_inner().unwrap().len() <= 4);
+
+    for (index, hash) in hashes.into_iter().enumerate() {
+        let mut gb = builder(index as u64).build();
+        while gb.frames() < frames(index) {
+            gb.run_frame();
+        }
+        assert_eq!(hash, gb.state_hash(), "Instance {}", index);
+    }
+}
+
+#[test]
+fn empty_batches_return_nothing() {
+    let results: Vec<()> = batch::run_parallel(vec![], |_, _| ControlFlow::Break(()));
+    assert!(results.is_empty());
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use std::{path::PathBuf, time::Duration};
+
+use gbemu::{
+    battery::{AutoSave, PAGE_SIZE},
+    memory::{RamInit, Read, Write},
+    GameBoy,
+};
+
+/// MBC1+RAM+BATTERY with 32 KiB of RAM in 4 banks
+fn console() -> GameBoy {
+    let rom = common::rom_with_header(&[0x18, 0xFE], 0x03, 0x00, 0x03);
+    GameBoy::builder(&rom).sram_init(RamInit::Zeros).build()
+}
+
+fn save_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gbemu-{}-{}.sav", name, std::process::id()))
+}
+
+#[test]
+fn writes_mark_pages_dirty() {
+    let mut gb = console();
+    assert!(!gb.save_ram_dirty());
+
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0xA000, 1);
+    gb.write_u8(0xA3FF, 2);
+    // Bank 1, RAM banking mode
+    gb.write_u8(0x6000, 0x01);
+    gb.write_u8(0x4000, 0x01);
+    gb.write_u8(0xA000, 3);
+    assert!(gb.save_ram_dirty());
+    assert!(!gb.take_ram_disabled());
+
+    assert_eq!(
+        gb.take_dirty_pages(),
+        [
+            0..PAGE_SIZE,
+            PAGE_SIZE..2 * PAGE_SIZE,
+            0x2000..0x2000 + PAGE_SIZE
+        ]
+    );
+    assert!(!gb.save_ram_dirty());
+    assert!(gb.take_dirty_pages().is_empty());
+}
+
+#[test]
+fn disabling_the_ram_after_writes_signals_a_save() {
+    let mut gb = console();
+    // Enabling and disabling alone is no save
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0x0000, 0x00);
+    assert!(!gb.take_ram_disabled());
+
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0xA000, 1);
+    gb.write_u8(0x0000, 0x00);
+    assert!(gb.take_ram_disabled());
+    assert!(!gb.take_ram_disabled());
+}
+
+#[test]
+fn loading_the_ram_leaves_it_clean() {
+    let mut gb = console();
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0xA000, 1);
+    gb.load_battery_ram(&[0; 0x8000]);
+    assert!(!gb.save_ram_dirty());
+}
+
+#[test]
+fn autosave_flushes_on_ram_disable() {
+    let path = save_path("disable");
+    let _ = std::fs::remove_file(&path);
+    let mut gb = console();
+    let mut autosave = AutoSave::new(&path).interval(Duration::from_secs(3600));
+
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0xA000, 0x42);
+    assert!(!autosave.poll(&mut gb).unwrap());
+    assert!(!path.exists());
+
+    gb.write_u8(0x0000, 0x00);
+    assert!(autosave.poll(&mut gb).unwrap());
+    let save = std::fs::read(&path).unwrap();
+    assert_eq!(save.len(), 0x8000);
+    assert_eq!(save[0], 0x42);
+
+    // Only the dirty page is rewritten, the rest of the file is kept
+    std::fs::write(&path, [0xEE; 0x8000]).unwrap();
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0xA200, 0x43);
+    gb.write_u8(0x0000, 0x00);
+    assert!(autosave.poll(&mut gb).unwrap());
+    let save = std::fs::read(&path).unwrap();
+    assert_eq!(save[0], 0xEE);
+    assert_eq!(save[0x200], 0x43);
+    assert_eq!(save[0x201], 0x00);
+    assert_eq!(save[0x400], 0xEE);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn autosave_flushes_after_the_interval() {
+    let path = save_path("interval");
+    let _ = std::fs::remove_file(&path);
+    let mut gb = console();
+    let mut autosave = AutoSave::new(&path)
+        .interval(Duration::ZERO)
+        .on_ram_disable(false);
+
+    assert!(!autosave.poll(&mut gb).unwrap());
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0xA000, 0x42);
+    assert!(autosave.poll(&mut gb).unwrap());
+    assert_eq!(std::fs::read(&path).unwrap()[0], 0x42);
+    assert!(!autosave.poll(&mut gb).unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rom_ram_battery_is_persisted() {
+    // ROM+RAM+BATTERY with 8 KiB of RAM, always enabled
+    let rom = common::rom_with_header(&[0x18, 0xFE], 0x09, 0x00, 0x02);
+    let mut gb = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+
+    gb.write_u8(0xA000, 0x42);
+    gb.write_u8(0xBFFF, 0x43);
+    assert!(gb.save_ram_dirty());
+    assert_eq!(
+        gb.take_dirty_pages(),
+        [0..PAGE_SIZE, 0x2000 - PAGE_SIZE..0x2000]
+    );
+    let save = gb.battery_ram().unwrap().to_vec();
+    assert_eq!(save.len(), 0x2000);
+    assert_eq!((save[0], save[0x1FFF]), (0x42, 0x43));
+
+    let mut loaded = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+    loaded.load_battery_ram(&save);
+    assert!(!loaded.save_ram_dirty());
+    assert_eq!(loaded.read_u8(0xA000), 0x42);
+    assert_eq!(loaded.read_u8(0xBFFF), 0x43);
+}
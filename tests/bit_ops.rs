@@ -0,0 +1,218 @@
+mod common;
+
+use gbemu::{
+    cpu::{Flag, Registers},
+    instructions::{
+        bits::{Bit, Rotate, RotateA, Shift},
+        decode, Instr, Instruction, Register8Index,
+    },
+    GameBoy,
+};
+
+type Constructor = fn(Register8Index) -> Instr;
+/// Expected result and carry out for an input
+type Reference = fn(u8) -> (u8, bool);
+/// Like [`Reference`], also given the incoming carry
+type CarryReference = fn(u8, u8) -> (u8, bool);
+
+fn new_cpu() -> GameBoy {
+    let mut gb = GameBoy::new(&common::rom_with_program(&[]));
+    *gb.registers_mut().hl = 0xC000;
+    gb
+}
+
+/// Runs `instruction` on `value` for register B and for [HL] with the carry
+/// set as given, returning the result, the flags and the cycles of both
+fn run(
+    gb: &mut GameBoy,
+    instruction: Constructor,
+    value: u8,
+    carry: bool,
+) -> [(u8, [bool; 4], usize); 2] {
+    [Register8Index::B, Register8Index::HL].map(|register| {
+        register.set(gb, value);
+        gb.set_flag(Flag::Carry, carry);
+        gb.set_flag(Flag::Subtract, true);
+        gb.set_flag(Flag::HalfCarry, true);
+        let cycles = instruction(register).execute(gb);
+        let flags = [Flag::Zero, Flag::Subtract, Flag::HalfCarry, Flag::Carry]
+            .map(|flag| gb.test_flag(flag));
+        (register.get(gb), flags, cycles)
+    })
+}
+
+#[test]
+fn shifts_for_every_value() {
+    let mut gb = new_cpu();
+    let shifts: [(Constructor, Reference); 3] = [
+        (|r| Instr::Shift(Shift::Sla(r)), |v| (v << 1, v & 0x80 != 0)),
+        (
+            |r| Instr::Shift(Shift::Sra(r)),
+            |v| ((v >> 1) | (v & 0x80), v & 1 != 0),
+        ),
+        (|r| Instr::Shift(Shift::Srl(r)), |v| (v >> 1, v & 1 != 0)),
+    ];
+
+    for (instruction, reference) in shifts {
+        for value in 0..=0xFF {
+            // The carry going in is not shifted in
+            for carry in [false, true] {
+                let (result, carry_out) = reference(value);
+                let flags = [result == 0, false, false, carry_out];
+                let [register, indirect] = run(&mut gb, instruction, value, carry);
+                let name = format!("{:?} {:#04x}", instruction(Register8Index::B), value);
+                assert_eq!(register, (result, flags, 8), "{}", name);
+                assert_eq!(indirect, (result, flags, 16), "{} [HL]", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn rotates_for_every_value() {
+    let mut gb = new_cpu();
+    let rotates: [(Constructor, CarryReference); 4] = [
+        (
+            |r| Instr::Rotate(Rotate::Rlc(r)),
+            |v, _| (v.rotate_left(1), v & 0x80 != 0),
+        ),
+        (
+            |r| Instr::Rotate(Rotate::Rl(r)),
+            |v, c| ((v << 1) | c, v & 0x80 != 0),
+        ),
+        (
+            |r| Instr::Rotate(Rotate::Rrc(r)),
+            |v, _| (v.rotate_right(1), v & 1 != 0),
+        ),
+        (
+            |r| Instr::Rotate(Rotate::Rr(r)),
+            |v, c| ((v >> 1) | (c << 7), v & 1 != 0),
+        ),
+    ];
+
+    for (instruction, reference) in rotates {
+        for value in 0..=0xFF {
+            for carry in [false, true] {
+                let (result, carry_out) = reference(value, carry as u8);
+                let flags = [result == 0, false, false, carry_out];
+                let [register, indirect] = run(&mut gb, instruction, value, carry);
+                let name = format!(
+                    "{:?} {:#04x} carry {}",
+                    instruction(Register8Index::B),
+                    value,
+                    carry
+                );
+                assert_eq!(register, (result, flags, 8), "{}", name);
+                assert_eq!(indirect, (result, flags, 16), "{} [HL]", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn rotates_decode_to_their_mnemonic() {
+    for cb in 0x00..=0x1F {
+        let decoded = decode(&[0xCB, cb]);
+        let expected = match cb >> 3 {
+            0 => matches!(decoded, Instr::Rotate(Rotate::Rlc(_))),
+            1 => matches!(decoded, Instr::Rotate(Rotate::Rrc(_))),
+            2 => matches!(decoded, Instr::Rotate(Rotate::Rl(_))),
+            _ => matches!(decoded, Instr::Rotate(Rotate::Rr(_))),
+        };
+        assert!(
+            expected,
+            "{:#06x} decoded as {:?}",
+            0xCB00 | cb as u16,
+            decoded
+        );
+    }
+}
+
+#[test]
+fn arithmetic_shift_keeps_the_sign() {
+    let mut gb = new_cpu();
+    let [(result, ..), _] = run(&mut gb, |r| Instr::Shift(Shift::Sra(r)), 0x81, false);
+    assert_eq!(result, 0xC0);
+    let [(result, ..), _] = run(&mut gb, |r| Instr::Shift(Shift::Srl(r)), 0x81, false);
+    assert_eq!(result, 0x40);
+}
+
+#[test]
+fn accumulator_rotates_always_clear_zero() {
+    let mut gb = new_cpu();
+    let rotates: [(RotateA, CarryReference); 4] = [
+        (RotateA::Rlca, |v, _| (v.rotate_left(1), v & 0x80 != 0)),
+        (RotateA::Rla, |v, c| ((v << 1) | c, v & 0x80 != 0)),
+        (RotateA::Rrca, |v, _| (v.rotate_right(1), v & 1 != 0)),
+        (RotateA::Rra, |v, c| ((v >> 1) | (c << 7), v & 1 != 0)),
+    ];
+
+    for (rotate, reference) in rotates {
+        for value in 0..=0xFF {
+            for carry in [false, true] {
+                Register8Index::A.set(&mut gb, value);
+                gb.set_flag(Flag::Zero, true);
+                gb.set_flag(Flag::Carry, carry);
+                assert_eq!(Instr::RotateA(rotate).execute(&mut gb), 4);
+
+                let (result, carry_out) = reference(value, carry as u8);
+                let flags = [Flag::Zero, Flag::Subtract, Flag::HalfCarry, Flag::Carry]
+                    .map(|flag| gb.test_flag(flag));
+                assert_eq!(
+                    (Register8Index::A.get(&gb), flags),
+                    (result, [false, false, false, carry_out]),
+                    "{:?} {:#04x}",
+                    rotate,
+                    value
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn prefixed_rotates_of_a_set_zero() {
+    let mut gb = new_cpu();
+    Register8Index::A.set(&mut gb, 0x00);
+    gb.set_flag(Flag::Carry, false);
+    assert_eq!(
+        Instr::Rotate(Rotate::Rlc(Register8Index::A)).execute(&mut gb),
+        8
+    );
+    assert!(gb.test_flag(Flag::Zero));
+}
+
+#[test]
+fn bit_index_comes_from_the_prefixed_byte() {
+    // BIT 0,H and BIT 7,H
+    assert_eq!(
+        decode(&[0xCB, 0x44]),
+        Instr::Bit(Bit::Test(0, Register8Index::H))
+    );
+    assert_eq!(
+        decode(&[0xCB, 0x7C]),
+        Instr::Bit(Bit::Test(7, Register8Index::H))
+    );
+    // RES 3,[HL] and SET 5,A
+    assert_eq!(
+        decode(&[0xCB, 0x9E]),
+        Instr::Bit(Bit::Reset(3, Register8Index::HL))
+    );
+    assert_eq!(
+        decode(&[0xCB, 0xEF]),
+        Instr::Bit(Bit::Set(5, Register8Index::A))
+    );
+
+    let mut gb = new_cpu();
+    Register8Index::H.set(&mut gb, 0x01);
+    decode(&[0xCB, 0x44]).execute(&mut gb);
+    assert!(!gb.test_flag(Flag::Zero), "BIT 0,H with H=0x01");
+    decode(&[0xCB, 0x7C]).execute(&mut gb);
+    assert!(gb.test_flag(Flag::Zero), "BIT 7,H with H=0x01");
+
+    Register8Index::H.set(&mut gb, 0x80);
+    decode(&[0xCB, 0x44]).execute(&mut gb);
+    assert!(gb.test_flag(Flag::Zero), "BIT 0,H with H=0x80");
+    decode(&[0xCB, 0x7C]).execute(&mut gb);
+    assert!(!gb.test_flag(Flag::Zero), "BIT 7,H with H=0x80");
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use gbemu::{
+    bootrom::{BootFailure, BootRom, BootRomError, CGB_BOOT_ROM_SIZE, DMG_BOOT_ROM_SIZE},
+    cartridge,
+    cpu::{CpuMode, Registers},
+    memory::{locations, Memory, Read},
+    run::Condition,
+    GameBoy, Model,
+};
+
+/// A boot ROM sliding down NOPs to the write unmapping it, at its last bytes
+fn dmg_boot_rom() -> BootRom {
+    let mut bytes = vec![0x00; DMG_BOOT_ROM_SIZE];
+    // LD A,1; LDH (BOOT),A
+    bytes[0xFC..].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]);
+    BootRom::new(bytes).unwrap()
+}
+
+#[test]
+fn the_boot_rom_runs_then_unmaps_itself() {
+    let mut gb = GameBoy::builder(&common::rom_with_program(&[0x18, 0xFE]))
+        .boot_rom(dmg_boot_rom())
+        .build();
+    assert_eq!(*gb.registers().pc, 0x0000);
+    assert!(gb.boot_rom_mapped());
+    assert_eq!(gb.read_u8(0x00FC), 0x3E);
+    // The cartridge shows past the boot ROM
+    assert_eq!(gb.read_u8(0x0100), 0x18);
+
+    gb.run_until(&Condition::PcEquals(0x0100), 1).unwrap();
+    assert!(!gb.boot_rom_mapped());
+    assert_eq!(gb.read_u8(0x00FC), 0x00);
+    assert_eq!(gb.read_u8(locations::BOOT), 0xFF);
+}
+
+#[test]
+fn the_cgb_boot_rom_is_mapped_around_the_header() {
+    let boot_rom = BootRom::new(vec![0xAA; CGB_BOOT_ROM_SIZE]).unwrap();
+    assert!(boot_rom.is_cgb());
+    let gb = GameBoy::builder(&cartridge::test_rom())
+        .model(Model::Cgb)
+        .boot_rom(boot_rom)
+        .build();
+
+    assert_eq!(gb.read_u8(0x00FF), 0xAA);
+    assert_eq!(gb.read_u8(0x0134), b'G');
+    assert_eq!(gb.read_u8(0x0200), 0xAA);
+    assert_eq!(gb.read_u8(0x08FF), 0xAA);
+    assert_eq!(gb.read_u8(0x0900), 0x00);
+}
+
+#[test]
+fn boot_roms_of_other_sizes_are_refused() {
+    assert_eq!(
+        BootRom::new(vec![0; 0x200]),
+        Err(BootRomError::InvalidSize(0x200))
+    );
+}
+
+#[test]
+fn failed_boot_checks_lock_up() {
+    // No logo
+    let mut gb = GameBoy::builder(&common::rom_with_program(&[0x18, 0xFE]))
+        .boot_checks(true)
+        .build();
+    assert_eq!(gb.boot_failure(), Some(BootFailure::Logo));
+    gb.run_frame();
+    assert_eq!(gb.registers().mode, CpuMode::Halted);
+    assert_eq!(gb.instructions_retired(), 0);
+
+    let mut rom = cartridge::test_rom();
+    rom[0x0134] ^= 0xFF;
+    let gb = GameBoy::builder(&rom).boot_checks(true).build();
+    assert_eq!(gb.boot_failure(), Some(BootFailure::HeaderChecksum));
+
+    let gb = GameBoy::builder(&cartridge::test_rom())
+        .boot_checks(true)
+        .build();
+    assert_eq!(gb.boot_failure(), None);
+    // Not emulated by default
+    let gb = GameBoy::new(&rom);
+    assert_eq!(gb.boot_failure(), None);
+}
+
+#[test]
+fn the_cgb_only_checks_the_top_half_of_the_logo() {
+    let mut rom = cartridge::test_rom();
+    rom[*locations::NINTENDO_GRAPHICS.end()] ^= 0xFF;
+    cartridge::fix_checksums(&mut rom);
+
+    for (model, failure) in [
+        (Model::Dmg, Some(BootFailure::Logo)),
+        (Model::Sgb, Some(BootFailure::Logo)),
+        (Model::Cgb, None),
+    ] {
+        let gb = GameBoy::builder(&rom)
+            .model(model)
+            .boot_checks(true)
+            .build();
+        assert_eq!(gb.boot_failure(), failure, "{:?}", model);
+    }
+}
+
+#[test]
+fn registers_after_boot_depend_on_the_model() {
+    let rom = cartridge::test_rom();
+    for (model, af, hl, nr52) in [
+        (Model::Dmg, 0x01B0, 0x014D, 0xF1),
+        (Model::Sgb, 0x0100, 0xC060, 0xF0),
+        (Model::Cgb, 0x1180, 0x000D, 0xF1),
+    ] {
+        let gb = GameBoy::builder(&rom).model(model).build();
+        assert_eq!(*gb.registers().af, af, "{:?}", model);
+        assert_eq!(*gb.registers().hl, hl, "{:?}", model);
+        assert_eq!(gb.memory()[locations::NR52], nr52, "{:?}", model);
+    }
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use gbemu::{
+    accuracy::AccuracyProfile,
+    cpu::{Cpu, Registers},
+    memory::fault::{BusFault, BusFaultKind},
+    memory::Read,
+    GameBoy,
+};
+
+#[test]
+fn ram_access_without_ram() {
+    let rom = common::rom_with_cartridge_type(
+        &[
+            0x3E, 0x0A, // LD A,0x0A
+            0xEA, 0x00, 0x00, // LD [0x0000],A ; enable RAM
+            0xFA, 0x00, 0xA0, // LD A,[0xA000]
+        ],
+        0x01,
+    );
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..3 {
+        gb.step();
+    }
+
+    assert_eq!(gb.registers().af.to_be_bytes()[0], 0xFF);
+    assert_eq!(
+        gb.take_bus_faults(),
+        [BusFault {
+            address: 0xA000,
+            kind: BusFaultKind::NoRam,
+        }]
+    );
+    assert!(gb.take_bus_faults().is_empty());
+}
+
+#[test]
+fn rom_bank_past_the_cartridge_is_mirrored() {
+    let rom = common::rom_with_cartridge_type(
+        &[
+            0x3E, 0x03, // LD A,0x03
+            0xEA, 0x00, 0x20, // LD [0x2000],A ; select ROM bank 3
+            0xFA, 0x00, 0x41, // LD A,[0x4100]
+        ],
+        0x01,
+    );
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..3 {
+        gb.step();
+    }
+
+    // Bank 3 of a 2 banks cartridge mirrors bank 1, which is empty
+    assert_eq!(gb.registers().af.to_be_bytes()[0], 0x00);
+    assert_eq!(
+        gb.take_bus_faults(),
+        [BusFault {
+            address: 0x4100,
+            kind: BusFaultKind::RomBankOutOfRange { bank: 3 },
+        }]
+    );
+}
+
+#[test]
+fn unmapped_reads_keep_the_last_bus_value() {
+    let rom = common::rom_with_program(&[
+        0xFA, 0x00, 0xA0, // LD A,[0xA000]
+    ]);
+
+    let mut gb = GameBoy::new(&rom);
+    gb.step();
+    assert_eq!(gb.registers().af.to_be_bytes()[0], 0xFF);
+
+    // The high byte of the operand was the last value on the bus
+    let mut gb = GameBoy::builder(&rom)
+        .accuracy(AccuracyProfile::Strict)
+        .build();
+    gb.step();
+    assert_eq!(gb.registers().af.to_be_bytes()[0], 0xA0);
+}
+
+#[test]
+fn empty_slot_reads_open_bus() {
+    let mut gb = GameBoy::without_cartridge();
+    assert_eq!(gb.read_u8(0x0100), 0xFF);
+    assert_eq!(gb.read_u8(0x4000), 0xFF);
+    // RST 0x38 forever
+    gb.step();
+    assert_eq!(*gb.registers().pc, 0x0038);
+    assert!(gb.take_bus_faults().is_empty());
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use gbemu::{
+    cpu::{
+        ClockConfig, Cpu, CpuMode, Registers, CPU_CLOCK_SPEED, CYCLES_PER_FRAME, SGB_CLOCK_SPEED,
+        SPEED_SWITCH_CYCLES,
+    },
+    memory::{locations, Read, Write},
+    GameBoy, Model,
+};
+
+/// JR -2, so the CPU keeps running without touching anything
+fn idle_rom() -> Vec<u8> {
+    common::rom_with_program(&[0x18, 0xFE])
+}
+
+#[test]
+fn each_model_has_its_clock() {
+    let rom = idle_rom();
+    assert_eq!(GameBoy::new(&rom).clock().speed, CPU_CLOCK_SPEED);
+    assert_eq!(
+        GameBoy::with_model(&rom, Model::Cgb).clock().speed,
+        CPU_CLOCK_SPEED
+    );
+
+    let sgb = GameBoy::with_model(&rom, Model::Sgb).clock();
+    assert_eq!(sgb, ClockConfig::for_model(Model::Sgb));
+    assert_eq!(sgb.speed, SGB_CLOCK_SPEED);
+    assert_eq!(sgb.overclock, 1);
+    // The SGB draws frames faster, the frame length is the same
+    assert!(sgb.frame_rate() > ClockConfig::for_model(Model::Dmg).frame_rate());
+    assert_eq!(
+        ClockConfig::for_model(Model::Dmg).frame_rate(),
+        gbemu::pacing::FRAME_RATE
+    );
+}
+
+#[test]
+fn a_frame_takes_the_same_cycles_at_any_speed() {
+    let rom = idle_rom();
+    for mut gb in [
+        GameBoy::new(&rom),
+        GameBoy::with_model(&rom, Model::Sgb),
+        GameBoy::builder(&rom).clock_speed(8_000_000.0).build(),
+    ] {
+        let executed = gb.run_frame() as u64;
+        assert!(executed.abs_diff(CYCLES_PER_FRAME) <= 12, "{}", executed);
+        assert_eq!(gb.cycles(), executed);
+    }
+}
+
+#[test]
+fn overclocking_gives_more_cpu_time_per_frame() {
+    let rom = idle_rom();
+    let mut gb = GameBoy::builder(&rom).overclock(3).build();
+    assert_eq!(gb.clock().overclock, 3);
+
+    // Measured at the real clock, as seen by the LCD
+    let executed = gb.run_frame() as u64;
+    assert!(executed.abs_diff(CYCLES_PER_FRAME) <= 12, "{}", executed);
+    assert!(gb.cycles().abs_diff(3 * CYCLES_PER_FRAME) <= 12);
+
+    // The LCD keeps its timing: a frame is still CYCLES_PER_FRAME dots long
+    let mut gb = GameBoy::builder(&rom).overclock(3).build();
+    while gb.cycles() < 3 * CYCLES_PER_FRAME {
+        gb.step();
+    }
+    assert_eq!(gb.frames(), 1);
+
+    gb.set_overclock(1);
+    let cycles = gb.cycles();
+    gb.run_frame();
+    assert!((gb.cycles() - cycles).abs_diff(CYCLES_PER_FRAME) <= 12);
+}
+
+/// STOP, then JR -2
+fn stop_rom() -> Vec<u8> {
+    common::rom_with_program(&[0x10, 0x00, 0x18, 0xFE])
+}
+
+#[test]
+fn stop_without_a_speed_switch_armed_stops() {
+    for model in [Model::Dmg, Model::Cgb] {
+        // Only the CGB has KEY1, the DMG reads it with every bit set
+        let mut gb = GameBoy::with_model(&stop_rom(), model);
+        let key1 = gb.read_u8(locations::KEY1);
+        assert_ne!(gb.read_u8(locations::DIV), 0);
+        gb.step();
+
+        assert_eq!(gb.registers().mode, CpuMode::Stopped, "{:?}", model);
+        assert_eq!(*gb.registers().pc, 0x0102);
+        assert_eq!(gb.read_u8(locations::DIV), 0);
+        assert_eq!(gb.read_u8(locations::KEY1), key1);
+        assert!(!gb.double_speed());
+    }
+}
+
+#[test]
+fn stop_with_a_speed_switch_armed_switches() {
+    let mut gb = GameBoy::with_model(&stop_rom(), Model::Cgb);
+    gb.write_u8(locations::KEY1, 0x7F);
+    gb.step();
+
+    assert_eq!(
+        gb.registers().mode,
+        CpuMode::SpeedSwitch(SPEED_SWITCH_CYCLES)
+    );
+    assert_eq!(*gb.registers().pc, 0x0102);
+    assert_eq!(gb.read_u8(locations::DIV), 0);
+    // The switch is done, and no longer armed
+    assert_eq!(gb.read_u8(locations::KEY1), 0xFE);
+    assert!(gb.double_speed());
+
+    // Paused for SPEED_SWITCH_CYCLES, then running again
+    let cycles = gb.cycles();
+    while gb.registers().mode != CpuMode::Running {
+        gb.step();
+    }
+    assert_eq!(gb.cycles() - cycles, SPEED_SWITCH_CYCLES as u64);
+
+    // Switching back to the normal speed
+    gb.write_u8(locations::KEY1, 0xFF);
+    *gb.registers_mut().pc = 0x0100;
+    gb.step();
+    assert_eq!(gb.read_u8(locations::KEY1), 0x7E);
+    assert!(!gb.double_speed());
+}
+
+#[test]
+fn double_speed_gives_twice_the_cpu_time_per_frame() {
+    let mut gb = GameBoy::with_model(&stop_rom(), Model::Cgb);
+    gb.write_u8(locations::KEY1, 0x7F);
+    gb.step();
+    while gb.registers().mode != CpuMode::Running {
+        gb.step();
+    }
+
+    // Measured at the normal speed clock, as seen by the LCD
+    let (cycles, frames) = (gb.cycles(), gb.frames());
+    let executed = gb.run_frame() as u64;
+    assert!(executed.abs_diff(CYCLES_PER_FRAME) <= 12, "{}", executed);
+    assert!((gb.cycles() - cycles).abs_diff(2 * CYCLES_PER_FRAME) <= 24);
+    assert_eq!(gb.frames(), frames + 1);
+}
+
+#[test]
+fn audio_callbacks_add_up_to_one_long_run() {
+    let rom = idle_rom();
+    let build = || GameBoy::builder(&rom).sample_rate(48000).build();
+    let mut short = build();
+    let mut long = build();
+    let div = short.read_u8(locations::DIV);
+
+    // A thousand 1 ms callbacks, 16.4 DIV increments each
+    for _ in 0..1000 {
+        short.run_until_samples(48);
+    }
+    long.run_until_samples(48000);
+
+    assert!(short.cycles().abs_diff(long.cycles()) <= 24);
+    assert!(short.cycles().abs_diff(CPU_CLOCK_SPEED as u64) <= 24);
+    // No fraction of a DIV increment is lost between the calls
+    for gb in [&short, &long] {
+        assert_eq!(
+            gb.read_u8(locations::DIV).wrapping_sub(div),
+            (gb.cycles() / 256) as u8
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Runs the core in lockstep with an oracle, comparing the CPU state
+//! before every instruction.
+use std::{collections::VecDeque, fmt};
+
+use gbemu::cpu::Cpu;
+
+/// Lines of trace shown before the divergence
+const CONTEXT: usize = 8;
+
+/// CPU state in the [Gameboy Doctor](https://github.com/robert/gameboy-doctor) log format,
+/// which other emulators can be instrumented to produce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceLine(pub String);
+
+impl TraceLine {
+    pub fn capture(cpu: &impl Cpu) -> Self {
+        let r = cpu.registers();
+        let pc = *r.pc as usize;
+        let [a, f] = r.af.to_be_bytes();
+        let [b, c] = r.bc.to_be_bytes();
+        let [d, e] = r.de.to_be_bytes();
+        let [h, l] = r.hl.to_be_bytes();
+        Self(format!(
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            a,
+            f,
+            b,
+            c,
+            d,
+            e,
+            h,
+            l,
+            *r.sp,
+            pc,
+            cpu.read_u8(pc),
+            cpu.read_u8(pc + 1),
+            cpu.read_u8(pc + 2),
+            cpu.read_u8(pc + 3),
+        ))
+    }
+}
+
+impl fmt::Display for TraceLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Reference implementation producing the expected state before each instruction
+pub trait Oracle {
+    fn next_state(&mut self) -> Option<TraceLine>;
+}
+
+/// Oracle replaying a log recorded from another emulator
+pub struct TraceOracle<I: Iterator<Item = String>>(pub I);
+
+impl<I: Iterator<Item = String>> Oracle for TraceOracle<I> {
+    fn next_state(&mut self) -> Option<TraceLine> {
+        self.0
+            .by_ref()
+            .map(|line| line.trim().to_string())
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(TraceLine)
+    }
+}
+
+#[derive(Debug)]
+pub struct Divergence {
+    pub instruction: usize,
+    pub expected: TraceLine,
+    pub actual: TraceLine,
+    /// States matching the oracle right before the divergence
+    pub context: Vec<TraceLine>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "diverged at instruction {}", self.instruction)?;
+        for line in &self.context {
+            writeln!(f, "           {}", line)?;
+        }
+        writeln!(f, "expected:  {}", self.expected)?;
+        write!(f, "actual:    {}", self.actual)
+    }
+}
+
+/// Steps the core until the oracle runs out of states or they diverge.
+///
+/// Returns the number of instructions compared.
+pub fn run_lockstep(cpu: &mut impl Cpu, oracle: &mut impl Oracle) -> Result<usize, Divergence> {
+    let mut context = VecDeque::with_capacity(CONTEXT);
+    let mut instruction = 0;
+
+    while let Some(expected) = oracle.next_state() {
+        let actual = TraceLine::capture(cpu);
+        if actual != expected {
+            return Err(Divergence {
+                instruction,
+                expected,
+                actual,
+                context: context.into(),
+            });
+        }
+
+        if context.len() == CONTEXT {
+            context.pop_front();
+        }
+        context.push_back(actual);
+
+        cpu.step();
+        instruction += 1;
+    }
+
+    Ok(instruction)
+}
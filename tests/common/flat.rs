@@ -0,0 +1,258 @@
+//! A CPU backed by a flat 64 KiB memory, with no cartridge nor I/O side
+//! effects, to test instructions in isolation.
+use gbemu::{
+    accuracy::AccuracyConfig,
+    cpu::{Cpu, RegisterFile, Registers},
+    instructions::InstructionDecoder,
+    memory::{dma::OamDma, Memory, MemoryMode, Read, Write},
+    Model,
+};
+
+/// Address the program under test is loaded at
+pub const PROGRAM_START: u16 = 0x0100;
+
+pub struct FlatCpu {
+    registers: RegisterFile,
+    memory: [u8; 0x10000],
+    memory_mode: MemoryMode,
+    oam_dma: OamDma,
+    accuracy: AccuracyConfig,
+}
+
+impl FlatCpu {
+    pub fn new(program: &[u8]) -> Self {
+        let mut cpu = Self {
+            registers: RegisterFile::default(),
+            memory: [0; 0x10000],
+            memory_mode: MemoryMode::RomOnly,
+            oam_dma: OamDma::default(),
+            accuracy: AccuracyConfig::default(),
+        };
+        cpu.memory[PROGRAM_START as usize..][..program.len()].copy_from_slice(program);
+        *cpu.registers.pc = PROGRAM_START;
+        *cpu.registers.sp = 0xFFFE;
+        cpu
+    }
+
+    pub fn poke(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+    }
+
+    pub fn peek(&self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn flag_mask(name: &str) -> Option<u8> {
+        match name {
+            "zf" => Some(0b1000_0000),
+            "nf" => Some(0b0100_0000),
+            "hf" => Some(0b0010_0000),
+            "cf" => Some(0b0001_0000),
+            _ => None,
+        }
+    }
+
+    /// Sets a register (`a`, `bc`, `sp`, ...), a flag (`zf`, `nf`, `hf`, `cf`) or `ime` by name
+    pub fn set(&mut self, name: &str, value: u16) {
+        let r = &mut self.registers;
+        if let Some(mask) = Self::flag_mask(name) {
+            let f = unsafe { &mut r.af.bytes.lo };
+            *f = if value != 0 { *f | mask } else { *f & !mask };
+            return;
+        }
+
+        match name {
+            "a" => r.af.bytes.hi = value as u8,
+            "f" => r.af.bytes.lo = value as u8,
+            "b" => r.bc.bytes.hi = value as u8,
+            "c" => r.bc.bytes.lo = value as u8,
+            "d" => r.de.bytes.hi = value as u8,
+            "e" => r.de.bytes.lo = value as u8,
+            "h" => r.hl.bytes.hi = value as u8,
+            "l" => r.hl.bytes.lo = value as u8,
+            "af" => *r.af = value,
+            "bc" => *r.bc = value,
+            "de" => *r.de = value,
+            "hl" => *r.hl = value,
+            "sp" => *r.sp = value,
+            "pc" => *r.pc = value,
+            "ime" => r.ime = value != 0,
+            _ => panic!("Unknown register: {}", name),
+        }
+    }
+
+    /// Reads a register, flag or `ime` by name
+    pub fn get(&self, name: &str) -> u16 {
+        let r = &self.registers;
+        let [a, f] = r.af.to_be_bytes();
+        if let Some(mask) = Self::flag_mask(name) {
+            return (f & mask != 0) as u16;
+        }
+
+        match name {
+            "a" => a as u16,
+            "f" => f as u16,
+            "b" => r.bc.to_be_bytes()[0] as u16,
+            "c" => r.bc.to_be_bytes()[1] as u16,
+            "d" => r.de.to_be_bytes()[0] as u16,
+            "e" => r.de.to_be_bytes()[1] as u16,
+            "h" => r.hl.to_be_bytes()[0] as u16,
+            "l" => r.hl.to_be_bytes()[1] as u16,
+            "af" => *r.af,
+            "bc" => *r.bc,
+            "de" => *r.de,
+            "hl" => *r.hl,
+            "sp" => *r.sp,
+            "pc" => *r.pc,
+            "ime" => r.ime as u16,
+            _ => panic!("Unknown register: {}", name),
+        }
+    }
+}
+
+impl Memory for FlatCpu {
+    fn memory(&self) -> &[u8; 0x10000] {
+        &self.memory
+    }
+
+    fn memory_mut(&mut self) -> &mut [u8; 0x10000] {
+        &mut self.memory
+    }
+
+    fn cartridge(&self) -> &[u8] {
+        &self.memory
+    }
+
+    fn cartridge_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.memory
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    fn memory_mode(&self) -> MemoryMode {
+        self.memory_mode
+    }
+
+    fn memory_mode_mut(&mut self) -> &mut MemoryMode {
+        &mut self.memory_mode
+    }
+
+    fn oam_dma(&self) -> &OamDma {
+        &self.oam_dma
+    }
+
+    fn oam_dma_mut(&mut self) -> &mut OamDma {
+        &mut self.oam_dma
+    }
+
+    fn accuracy(&self) -> &AccuracyConfig {
+        &self.accuracy
+    }
+}
+
+impl Read for FlatCpu {
+    fn read_u8(&self, address: usize) -> u8 {
+        self.memory[address & 0xFFFF]
+    }
+}
+
+impl Write for FlatCpu {
+    fn write_u8(&mut self, address: usize, value: u8) {
+        self.memory[address & 0xFFFF] = value;
+    }
+}
+
+impl Registers for FlatCpu {
+    fn registers(&self) -> &RegisterFile {
+        &self.registers
+    }
+
+    fn registers_mut(&mut self) -> &mut RegisterFile {
+        &mut self.registers
+    }
+}
+
+impl InstructionDecoder for FlatCpu {}
+
+impl Cpu for FlatCpu {
+    fn model(&self) -> Model {
+        Model::Dmg
+    }
+}
+
+/// Declares instruction tests on a [`FlatCpu`].
+///
+/// ```ignore
+/// cpu_test! {
+///     adc_carry_chain: "ADC A,B carry chain" {
+///         regs { a: 0xFF, b: 0x01, cf: true }
+///         run [0x88]
+///         expect { a: 0x01, zf: false, cf: true, hf: true, cycles: 4 }
+///     }
+/// }
+/// ```
+///
+/// `regs` and `expect` take registers, flags (`zf`, `nf`, `hf`, `cf`) and `ime`,
+/// `expect` also takes the `cycles` taken. `mem` and `expect_mem` set and check
+/// memory by address. A single instruction is executed.
+#[allow(unused_macros)]
+macro_rules! cpu_test {
+    ($(
+        $(#[$attr:meta])*
+        $name:ident: $desc:literal {
+            $(regs { $($reg:ident: $value:expr),* $(,)? })?
+            $(mem { $($addr:literal: $byte:expr),* $(,)? })?
+            run [$($program:expr),+ $(,)?]
+            expect { $($ereg:ident: $evalue:expr),* $(,)? }
+            $(expect_mem { $($eaddr:literal: $ebyte:expr),* $(,)? })?
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[test]
+            fn $name() {
+                #[allow(unused_imports)]
+                use gbemu::cpu::Cpu;
+
+                let mut cpu = $crate::common::flat::FlatCpu::new(&[$($program),+]);
+                $($(cpu.set(stringify!($reg), $value as u16);)*)?
+                $($(cpu.poke($addr, $byte);)*)?
+                let cycles = cpu.step();
+                $(
+                    let actual = match stringify!($ereg) {
+                        "cycles" => cycles as u16,
+                        name => cpu.get(name),
+                    };
+                    assert_eq!(
+                        actual,
+                        $evalue as u16,
+                        "{}: {} is {:#06x}, expected {:#06x}",
+                        $desc,
+                        stringify!($ereg),
+                        actual,
+                        $evalue as u16
+                    );
+                )*
+                $($(
+                    assert_eq!(
+                        cpu.peek($eaddr),
+                        $ebyte,
+                        "{}: [{:#06x}] is {:#04x}, expected {:#04x}",
+                        $desc,
+                        $eaddr,
+                        cpu.peek($eaddr),
+                        $ebyte
+                    );
+                )*)?
+            }
+        )*
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use cpu_test;
@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+pub mod differential;
+pub mod flat;
+
+use std::sync::Mutex;
+
+use gbemu::memory::locations;
+
+/// Builds a 32 KiB ROM only cartridge with the program placed at the entrypoint
+pub fn rom_with_program(program: &[u8]) -> Vec<u8> {
+    rom_with_cartridge_type(program, 0x00)
+}
+
+/// Builds a 32 KiB cartridge of the given type, without external RAM, with
+/// the program placed at the entrypoint
+pub fn rom_with_cartridge_type(program: &[u8], cartridge_type: u8) -> Vec<u8> {
+    rom_with_header(program, cartridge_type, 0x00, 0x00)
+}
+
+/// Builds a cartridge with the given header type and size codes, with the
+/// program placed at the entrypoint
+pub fn rom_with_header(program: &[u8], cartridge_type: u8, rom_size: u8, ram_size: u8) -> Vec<u8> {
+    let mut rom = vec![0; 0x8000 << rom_size];
+    rom[*locations::ENTRYPOINT.start()..][..program.len()].copy_from_slice(program);
+    rom[locations::CARTRIDGE_TYPE] = cartridge_type;
+    rom[locations::ROM_SIZE] = rom_size;
+    rom[locations::RAM_SIZE] = ram_size;
+    rom[locations::COMPLEMENT_CHECK] = rom[0x0134..=0x014C].iter().fold(0u8, |checksum, byte| {
+        checksum.wrapping_sub(*byte).wrapping_sub(1)
+    });
+    rom
+}
+
+/// Keeps the messages logged at debug level and above, with their target
+struct CapturedLogs(Mutex<Vec<(String, String)>>);
+
+impl log::Log for CapturedLogs {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Debug
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            let entry = (record.target().to_string(), record.args().to_string());
+            self.0.lock().unwrap().push(entry);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURED_LOGS: CapturedLogs = CapturedLogs(Mutex::new(Vec::new()));
+
+/// Starts keeping the messages logged, by every test of the binary as tests
+/// run in parallel
+pub fn capture_logs() {
+    if log::set_logger(&CAPTURED_LOGS).is_ok() {
+        log::set_max_level(log::LevelFilter::Debug);
+    }
+}
+
+/// Returns the messages logged to the target since [`capture_logs`]
+pub fn logged(target: &str) -> Vec<String> {
+    let logs = CAPTURED_LOGS.0.lock().unwrap();
+    logs.iter()
+        .filter(|(logged, _)| logged == target)
+        .map(|(_, message)| message.clone())
+        .collect()
+}
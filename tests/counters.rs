@@ -0,0 +1,22 @@
+use gbemu::{
+    cartridge,
+    cpu::{Cpu, CYCLES_PER_FRAME},
+    GameBoy,
+};
+
+#[test]
+fn counters_follow_execution() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    // NOP, JP, LD HL, LD [HL]
+    let cycles: usize = (0..4).map(|_| gb.step()).sum();
+
+    assert_eq!(gb.instructions_retired(), 4);
+    assert_eq!(gb.cycles(), cycles as u64);
+    assert_eq!(cycles, 4 + 16 + 12 + 12);
+    assert_eq!(gb.frames(), 0);
+
+    while gb.cycles() < CYCLES_PER_FRAME * 2 {
+        gb.step();
+    }
+    assert_eq!(gb.frames(), 2);
+}
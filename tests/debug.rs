@@ -0,0 +1,263 @@
+mod common;
+
+use gbemu::{
+    cartridge,
+    cpu::{Cpu, Registers},
+    debug::{self, BankedAddr, FrameKind, SymbolTable, WatchHit},
+    memory::{locations, RamInit, Read, Write},
+    run::Condition,
+    GameBoy,
+};
+
+#[test]
+fn watch_reports_changes() {
+    let rom = cartridge::test_rom();
+    let mut gb = GameBoy::builder(&rom).ram_init(RamInit::Ones).build();
+    gb.debugger_mut().watch(0xC000);
+
+    // The test ROM clears then increments 0xC000
+    assert_eq!(
+        gb.run_until(&Condition::WatchHit, 1),
+        Ok(&Condition::WatchHit)
+    );
+    assert_eq!(
+        gb.debugger_mut().take_watch_hits(),
+        [WatchHit {
+            address: 0xC000,
+            old: 0xFF,
+            new: 0x00,
+            pc: 0x0155,
+        }]
+    );
+
+    gb.step();
+    assert_eq!(
+        gb.debugger_mut().take_watch_hits(),
+        [WatchHit {
+            address: 0xC000,
+            old: 0x00,
+            new: 0x01,
+            pc: 0x0156,
+        }]
+    );
+}
+
+#[test]
+fn freeze_locks_the_value() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    gb.debugger_mut().freeze(0xC000, 0x42);
+
+    for _ in 0..100 {
+        gb.step();
+    }
+    assert_eq!(gb.read_u8(0xC000), 0x42);
+
+    gb.debugger_mut().unfreeze(0xC000);
+    gb.write_u8(0xC000, 0x10);
+    assert_eq!(gb.read_u8(0xC000), 0x10);
+}
+
+#[test]
+fn backtrace_follows_calls_and_returns() {
+    let rom = common::rom_with_program(&[
+        0xCD, 0x10, 0x01, // 0x0100: CALL 0x0110
+        0x00, // 0x0103: NOP
+    ]);
+    let mut rom = rom;
+    rom[0x0110..0x0114].copy_from_slice(&[
+        0xCF, // 0x0110: RST 0x08
+        0xC9, // 0x0111: RET
+        0x00, 0x00,
+    ]);
+    rom[0x0008] = 0xC9; // RET
+
+    let mut gb = GameBoy::new(&rom);
+    gb.debugger_mut().set_symbols(SymbolTable::parse(
+        "; symbols\n00:0008 Rst08\n00:0100 Main\n00:0110 Sub\n",
+    ));
+
+    gb.step();
+    gb.step();
+    let backtrace: Vec<_> = gb.backtrace().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        backtrace,
+        ["00:0008 Rst08", "00:0111 Sub+0x1", "00:0103 Main+0x3"]
+    );
+
+    gb.step();
+    assert_eq!(gb.debugger().call_stack().len(), 1);
+    assert_eq!(gb.debugger().call_stack()[0].kind, FrameKind::Call);
+    gb.step();
+    assert!(gb.debugger().call_stack().is_empty());
+    assert_eq!(gb.backtrace().len(), 1);
+}
+
+fn nested_calls() -> Vec<u8> {
+    let mut rom = common::rom_with_program(&[
+        0xCD, 0x10, 0x01, // 0x0100: CALL 0x0110
+        0x18, 0xFE, // 0x0103: JR -2
+    ]);
+    rom[0x0110..0x0118].copy_from_slice(&[
+        0xCD, 0x20, 0x01, // 0x0110: CALL 0x0120
+        0x00, // 0x0113: NOP
+        0xC9, // 0x0114: RET
+        0x00, 0x00, 0x00,
+    ]);
+    rom[0x0120..0x0123].copy_from_slice(&[
+        0x00, // 0x0120: NOP
+        0x00, // 0x0121: NOP
+        0xC9, // 0x0122: RET
+    ]);
+    rom
+}
+
+#[test]
+fn step_over_runs_the_whole_call() {
+    let mut gb = GameBoy::new(&nested_calls());
+
+    // CALL 0x0110 (24), CALL 0x0120 (24), NOP, NOP, RET (16), NOP, RET (16)
+    assert_eq!(gb.step_over(), Ok(24 + 24 + 4 + 4 + 16 + 4 + 16));
+    assert_eq!(*gb.registers().pc, 0x0103);
+    assert!(gb.debugger().call_stack().is_empty());
+
+    // Not a call, a single step
+    assert_eq!(gb.step_over(), Ok(12));
+}
+
+#[test]
+fn step_out_returns_from_the_innermost_frame() {
+    let mut gb = GameBoy::new(&nested_calls());
+    gb.step();
+    gb.step();
+    assert_eq!(*gb.registers().pc, 0x0120);
+
+    gb.step_out().unwrap();
+    assert_eq!(*gb.registers().pc, 0x0113);
+    gb.step_out().unwrap();
+    assert_eq!(*gb.registers().pc, 0x0103);
+}
+
+#[test]
+fn profiler_attributes_cycles_to_functions() {
+    let mut gb = GameBoy::new(&nested_calls());
+    gb.debugger_mut()
+        .set_symbols(SymbolTable::parse("00:0110 Outer\n00:0120 Inner\n"));
+    gb.profiler_mut().start();
+    gb.step_over().unwrap();
+    gb.profiler_mut().stop();
+    gb.step();
+
+    // A CALL is charged to the callee, a RET to the caller it returns to
+    assert_eq!(
+        gb.profile_report(),
+        "00:0100 16\n00:0100;Outer 44\n00:0100;Outer;Inner 32"
+    );
+    assert_eq!(
+        gb.profiler().self_cycles(),
+        [((0, 0x0110), 44), ((0, 0x0120), 32), ((0, 0x0100), 16)]
+    );
+}
+
+#[test]
+fn io_registers_are_looked_up_by_name() {
+    assert_eq!(locations::name(0xFF41), Some("STAT"));
+    assert_eq!(locations::name(0xFFFF), Some("IE"));
+    assert_eq!(locations::name(0xFF1B), Some("NR31"));
+    assert_eq!(locations::name(0xFF03), None);
+    assert_eq!(locations::by_name("stat"), Some(locations::STAT));
+    assert_eq!(locations::by_name("NOPE"), None);
+
+    // Every register has its own address
+    for pair in locations::IO_REGISTERS.windows(2) {
+        assert!(pair[0].0 < pair[1].0, "{:?}", pair);
+    }
+}
+
+#[test]
+fn addresses_are_parsed_as_hex_or_register_names() {
+    assert_eq!(debug::parse_address("$C000"), Some(0xC000));
+    assert_eq!(debug::parse_address("0x150"), Some(0x0150));
+    assert_eq!(debug::parse_address("ff40"), Some(0xFF40));
+    assert_eq!(debug::parse_address("LY"), Some(0xFF44));
+    assert_eq!(debug::parse_address("nope"), None);
+}
+
+#[test]
+fn banked_addresses_are_parsed_and_displayed() {
+    assert_eq!(
+        debug::parse_banked_address("05:4000"),
+        Some(BankedAddr::new(5, 0x4000))
+    );
+    assert_eq!(
+        debug::parse_banked_address("$4000"),
+        Some(BankedAddr::any(0x4000))
+    );
+    assert_eq!(debug::parse_banked_address("zz:4000"), None);
+    assert_eq!(BankedAddr::new(5, 0x4000).to_string(), "05:4000");
+    assert_eq!(BankedAddr::any(0x4000).to_string(), "4000");
+    // Banks are dropped where none are switched
+    assert_eq!(BankedAddr::new(3, 0xC000), BankedAddr::any(0xC000));
+
+    let symbols = SymbolTable::parse("02:4000 Banked\n00:0150 Main\n");
+    assert_eq!(
+        symbols.address_of("Banked"),
+        Some(BankedAddr::new(2, 0x4000))
+    );
+    assert_eq!(symbols.address_of("Nope"), None);
+}
+
+#[test]
+fn breakpoints_tell_rom_banks_apart() {
+    let mut rom = common::rom_with_header(
+        &[
+            0xCD, 0x00, 0x40, // CALL 0x4000
+            0x3E, 0x02, // LD A,2
+            0xEA, 0x00, 0x20, // LD [0x2000],A
+            0xCD, 0x00, 0x40, // CALL 0x4000
+            0x18, 0xFE, // JR -2
+        ],
+        0x01,
+        0x01,
+        0x00,
+    );
+    // RET in banks 1 and 2
+    rom[0x4000] = 0xC9;
+    rom[0x8000] = 0xC9;
+
+    let mut gb = GameBoy::new(&rom);
+    gb.debugger_mut().add_breakpoint(BankedAddr::new(2, 0x4000));
+    assert!(gb.run_until(&Condition::Breakpoint, 1).is_ok());
+    assert_eq!(*gb.registers().pc, 0x4000);
+    assert_eq!(gb.mapped_bank(0x4000), Some(2));
+    assert_eq!(gb.resolve(BankedAddr::new(1, 0x4000)), None);
+    assert_eq!(gb.resolve(BankedAddr::new(2, 0x4000)), Some(0x4000));
+
+    // Any bank stops at the first call
+    let mut gb = GameBoy::new(&rom);
+    gb.debugger_mut().add_breakpoint(0x4000);
+    assert!(gb.run_until(&Condition::Breakpoint, 1).is_ok());
+    assert_eq!(gb.banked_address(0x4000), BankedAddr::new(1, 0x4000));
+}
+
+#[test]
+fn freezes_follow_the_cartridge_ram_bank() {
+    // MBC1 with 4 RAM banks
+    let rom = common::rom_with_header(&[0x18, 0xFE], 0x03, 0x00, 0x03);
+    let mut gb = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0x6000, 0x01);
+    gb.debugger_mut().freeze(BankedAddr::new(1, 0xA000), 0x42);
+
+    gb.write_u8(0x4000, 0x01);
+    gb.write_u8(0xA000, 0x10);
+    assert_eq!(gb.read_u8(0xA000), 0x42);
+
+    gb.write_u8(0x4000, 0x00);
+    gb.write_u8(0xA000, 0x10);
+    assert_eq!(gb.read_u8(0xA000), 0x10);
+
+    gb.write_u8(0x4000, 0x01);
+    assert_eq!(gb.read_u8(0xA000), 0x42);
+    gb.debugger_mut().unfreeze(BankedAddr::new(1, 0xA000));
+    assert_eq!(gb.read_u8(0xA000), 0x00);
+}
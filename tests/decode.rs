@@ -0,0 +1,222 @@
+mod common;
+
+use gbemu::{
+    cpu::{Cpu, Registers},
+    instructions::{
+        decode, decode_at,
+        loads::{Load16, Load8, LoadDirection},
+        routines::{relative_target, Call, Condition, Jump},
+        Instr, InstructionDecoder, Register16Index, Register16StackIndex, Register8Index,
+        OPCODE_TABLE,
+    },
+    memory::{Read, Write},
+    GameBoy,
+};
+
+#[test]
+fn decode_at_reads_the_operands_without_moving_pc() {
+    let rom = common::rom_with_program(&[
+        0xC3, 0x50, 0x01, // 0x0100: JP $0150
+        0x3E, 0x42, // 0x0103: LD A,$42
+        0xDC, 0x34, 0x12, // 0x0105: CALL C,$1234
+        0xE0, 0x44, // 0x0108: LDH [$FF44],A
+        0xD3, // 0x010A: invalid
+    ]);
+    let gb = GameBoy::new(&rom);
+
+    assert_eq!(
+        decode_at(&gb, 0x0100),
+        (Instr::Jump(Jump::Immediate(None, 0x0150)), 3)
+    );
+    assert_eq!(
+        decode_at(&gb, 0x0103),
+        (Instr::Load8(Load8::Immediate(Register8Index::A, 0x42)), 2)
+    );
+    assert_eq!(
+        decode_at(&gb, 0x0105),
+        (Instr::Call(Call(Some(Condition::Carry), 0x1234)), 3)
+    );
+    assert_eq!(
+        decode_at(&gb, 0x0108),
+        (
+            Instr::Load8(Load8::ImmediatePointer(0x44, LoadDirection::Into)),
+            2
+        )
+    );
+    assert_eq!(decode_at(&gb, 0x010A), (Instr::Invalid(0xD3), 1));
+    assert_eq!(*gb.registers().pc, 0x0100);
+}
+
+/// STOP consumes its operand at execution, depending on the pending
+/// interrupts, and the prefix length is in the CB table
+const NOT_FIXED_LENGTH: [u8; 2] = [0x10, 0xCB];
+
+#[test]
+fn decoded_lengths_match_the_opcode_table() {
+    let mut mismatches = vec![];
+    for opcode in 0..=0xFF_u8 {
+        let info = &OPCODE_TABLE[opcode as usize];
+        let gb = GameBoy::new(&common::rom_with_program(&[opcode, 0x00, 0x00]));
+        let (instruction, length) = decode_at(&gb, 0x0100);
+        if !info.is_valid()
+            || matches!(instruction, Instr::Invalid(_))
+            || NOT_FIXED_LENGTH.contains(&opcode)
+        {
+            continue;
+        }
+        if length != info.length {
+            mismatches.push(format!("{:#04x} {}: {}", opcode, info.mnemonic, length));
+        }
+    }
+    assert!(mismatches.is_empty(), "{:#?}", mismatches);
+}
+
+#[test]
+fn decode_is_pure() {
+    let bytes = [0xCD, 0x34, 0x12];
+    assert_eq!(decode(&bytes), Instr::Call(Call(None, 0x1234)));
+    assert_eq!(decode(&bytes), decode(&bytes));
+    assert_eq!(decode(&[0x00]), Instr::Nop);
+}
+
+#[test]
+fn fetch_instruction_moves_pc_past_the_operands() {
+    let rom = common::rom_with_program(&[
+        0xFA, 0x00, 0xC0, // 0x0100: LD A,[$C000]
+        0xCB, 0x37, // 0x0103: SWAP A
+        0x00, // 0x0105: NOP
+    ]);
+    let mut gb = GameBoy::new(&rom);
+
+    for (bytes, pc) in [
+        (&[0xFA, 0x00, 0xC0][..], 0x0103),
+        (&[0xCB, 0x37][..], 0x0105),
+        (&[0x00][..], 0x0106),
+    ] {
+        assert_eq!(gb.fetch_instruction().as_slice(), bytes);
+        assert_eq!(*gb.registers().pc, pc);
+    }
+}
+
+#[test]
+fn fetch_u16_reads_the_low_byte_first() {
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x34, 0x12]));
+    assert_eq!(gb.fetch_u16(), 0x1234);
+    assert_eq!(*gb.registers().pc, 0x0102);
+}
+
+#[test]
+fn sixteen_bit_immediates_are_little_endian() {
+    let cases = [
+        (
+            [0xC3, 0x50, 0x01],
+            Instr::Jump(Jump::Immediate(None, 0x0150)),
+        ),
+        (
+            [0xCA, 0x00, 0x80],
+            Instr::Jump(Jump::Immediate(Some(Condition::Zero), 0x8000)),
+        ),
+        ([0xCD, 0xCD, 0xAB], Instr::Call(Call(None, 0xABCD))),
+        (
+            [0xEA, 0x01, 0xC0],
+            Instr::Load8(Load8::ImmediateMemory(0xC001, LoadDirection::Into)),
+        ),
+        (
+            [0xFA, 0xFF, 0x00],
+            Instr::Load8(Load8::ImmediateMemory(0x00FF, LoadDirection::From)),
+        ),
+        (
+            [0x21, 0x02, 0x01],
+            Instr::Load16(Load16::Immediate(Register16Index::HL, 0x0102)),
+        ),
+        (
+            [0x08, 0x10, 0xC0],
+            Instr::Load16(Load16::StackToMemory(0xC010)),
+        ),
+    ];
+    for (bytes, expected) in cases {
+        assert_eq!(decode(&bytes), expected, "{:02X?}", bytes);
+    }
+}
+
+#[test]
+fn sixteen_bit_immediates_are_executed_as_decoded() {
+    let rom = common::rom_with_program(&[
+        0x3E, 0x42, // 0x0100: LD A,$42
+        0xEA, 0x01, 0xC0, // 0x0102: LD [$C001],A
+        0xCD, 0x00, 0x02, // 0x0105: CALL $0200
+    ]);
+    let mut gb = GameBoy::new(&rom);
+
+    gb.step();
+    gb.step();
+    assert_eq!(gb.read_u8(0xC001), 0x42);
+    gb.step();
+    assert_eq!(*gb.registers().pc, 0x0200);
+    // The return address is pushed high byte first, so it lies little-endian
+    let sp = *gb.registers().sp as usize;
+    assert_eq!([gb.read_u8(sp), gb.read_u8(sp + 1)], [0x08, 0x01]);
+}
+
+#[test]
+fn stack_operations_select_af_instead_of_sp() {
+    assert_eq!(
+        decode(&[0xF5]),
+        Instr::Load16(Load16::Push(Register16StackIndex::AF))
+    );
+    assert_eq!(
+        decode(&[0xC1]),
+        Instr::Load16(Load16::Pop(Register16StackIndex::BC))
+    );
+    // Other 16-bit operands still select SP
+    assert_eq!(
+        decode(&[0x31, 0xFE, 0xFF]),
+        Instr::Load16(Load16::Immediate(Register16Index::SP, 0xFFFE))
+    );
+}
+
+#[test]
+fn relative_jumps_count_from_the_next_instruction() {
+    assert_eq!(decode(&[0x18, 0xFE]), Instr::Jump(Jump::Relative(None, -2)));
+    assert_eq!(
+        decode(&[0x38, 0x05]),
+        Instr::Jump(Jump::Relative(Some(Condition::Carry), 5))
+    );
+    assert_eq!(relative_target(0x0102, -2), 0x0100);
+    assert_eq!(relative_target(0x0102, 0x7F), 0x0181);
+    assert_eq!(relative_target(0x0102, -128), 0x0082);
+}
+
+#[test]
+fn relative_jumps_wrap_around_the_address_space() {
+    let mut rom = common::rom_with_program(&[]);
+    rom[0x0000..0x0002].copy_from_slice(&[0x18, 0xFC]); // JR -4
+    let mut gb = GameBoy::new(&rom);
+
+    // Backward from 0x0002 to 0xFFFE
+    *gb.registers_mut().pc = 0x0000;
+    assert_eq!(gb.step(), 12);
+    assert_eq!(*gb.registers().pc, 0xFFFE);
+
+    // Forward from 0xFFFF to 0x0001, from HRAM
+    gb.write_u8(0xFFFD, 0x18); // JR +2
+    gb.write_u8(0xFFFE, 0x02);
+    *gb.registers_mut().pc = 0xFFFD;
+    assert_eq!(gb.step(), 12);
+    assert_eq!(*gb.registers().pc, 0x0001);
+}
+
+#[test]
+fn conditional_relative_jumps_take_12_cycles_only_when_taken() {
+    // JR NZ,+4 twice, the first with Z set
+    let rom = common::rom_with_program(&[0x20, 0x04, 0x20, 0x04]);
+    let mut gb = GameBoy::new(&rom);
+
+    gb.registers_mut().af.bytes.lo = 0x80;
+    assert_eq!(gb.step(), 8);
+    assert_eq!(*gb.registers().pc, 0x0102);
+
+    gb.registers_mut().af.bytes.lo = 0x00;
+    assert_eq!(gb.step(), 12);
+    assert_eq!(*gb.registers().pc, 0x0108);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use gbemu::{
+    cpu::Cpu,
+    determinism::{AuditMode, Nondeterminism},
+    memory::RamInit,
+    GameBoy,
+};
+
+fn rom() -> Vec<u8> {
+    // JR -2
+    common::rom_with_program(&[0x18, 0xFE])
+}
+
+fn audited(rom: &[u8], init: RamInit) -> GameBoy {
+    GameBoy::builder(rom)
+        .ram_init(init)
+        .sram_init(init)
+        .determinism_audit(AuditMode::Report)
+        .build()
+}
+
+#[test]
+fn random_power_on_ram_is_reported() {
+    let mut gb = audited(&rom(), RamInit::Random);
+    assert_eq!(gb.take_nondeterminism(), [Nondeterminism::RandomRam]);
+    assert!(gb.take_nondeterminism().is_empty());
+
+    // MBC1+RAM+BATTERY with 8 KiB of RAM
+    let rom = common::rom_with_header(&[0x18, 0xFE], 0x03, 0x00, 0x02);
+    let mut gb = audited(&rom, RamInit::Random);
+    assert_eq!(
+        gb.take_nondeterminism(),
+        [Nondeterminism::RandomRam, Nondeterminism::RandomSram]
+    );
+
+    let mut gb = audited(&rom, RamInit::Seeded(42));
+    assert!(gb.take_nondeterminism().is_empty());
+}
+
+#[test]
+fn host_time_ticks_are_reported_once() {
+    let mut gb = audited(&rom(), RamInit::Zeros);
+    gb.run_frame();
+    gb.run_until_samples(800);
+    assert!(gb.take_nondeterminism().is_empty());
+
+    gb.tick(0.016);
+    gb.tick(0.017);
+    assert_eq!(gb.take_nondeterminism(), [Nondeterminism::HostTimeTick]);
+}
+
+#[test]
+fn nothing_is_recorded_when_off() {
+    let mut gb = GameBoy::new(&rom());
+    gb.tick(0.016);
+    assert_eq!(gb.determinism_audit(), AuditMode::Off);
+    assert!(gb.take_nondeterminism().is_empty());
+
+    gb.set_determinism_audit(AuditMode::Report);
+    gb.tick(0.016);
+    assert_eq!(gb.take_nondeterminism(), [Nondeterminism::HostTimeTick]);
+}
+
+#[test]
+#[should_panic(expected = "Nondeterminism: internal RAM initialised at random")]
+fn strict_audits_panic() {
+    GameBoy::builder(&rom())
+        .determinism_audit(AuditMode::Strict)
+        .build();
+}
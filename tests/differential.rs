@@ -0,0 +1,46 @@
+mod common;
+
+use common::differential::{run_lockstep, TraceOracle};
+use gbemu::GameBoy;
+
+fn check(rom: &[u8], trace: &str) {
+    let mut gb = GameBoy::new(rom);
+    let mut oracle = TraceOracle(trace.lines().map(String::from));
+    if let Err(divergence) = run_lockstep(&mut gb, &mut oracle) {
+        panic!("{}", divergence);
+    }
+}
+
+#[test]
+fn arithmetics_trace() {
+    let rom = common::rom_with_program(&[
+        0x3E, 0x12, // LD A, $12
+        0x47, // LD B, A
+        0x04, // INC B
+        0x80, // ADD A, B
+        0x21, 0x00, 0xC0, // LD HL, $C000
+        0x22, // LD [HL+], A
+        0x3D, // DEC A
+        0x37, // SCF
+        0x8F, // ADC A, A
+        0xD6, 0x50, // SUB $50
+        0x18, 0xFE, // JR -2
+    ]);
+    check(&rom, include_str!("data/arithmetics.log"));
+}
+
+/// Compares against a log recorded from another emulator, set
+/// `GBEMU_DIFF_ROM` and `GBEMU_DIFF_TRACE` to run it.
+#[test]
+fn external_trace() {
+    let (Ok(rom), Ok(trace)) = (
+        std::env::var("GBEMU_DIFF_ROM"),
+        std::env::var("GBEMU_DIFF_TRACE"),
+    ) else {
+        return;
+    };
+
+    let rom = std::fs::read(rom).expect("Failed to read ROM");
+    let trace = std::fs::read_to_string(trace).expect("Failed to read trace");
+    check(&rom, &trace);
+}
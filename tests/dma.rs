@@ -0,0 +1,112 @@
+mod common;
+
+use gbemu::{
+    cpu::Cpu,
+    memory::{
+        dma::{DmaAccess, OAM_DMA_CYCLES},
+        Memory, RamInit,
+    },
+    GameBoy,
+};
+
+/// Starts a transfer from 0xC000 in a HRAM routine, reading WRAM and HRAM
+/// while it runs
+fn console() -> GameBoy {
+    // LD A,0xC0; JP 0xFF80
+    let program = [0x3E, 0xC0, 0xC3, 0x80, 0xFF];
+    let mut gb = GameBoy::builder(&common::rom_with_program(&program))
+        .ram_init(RamInit::Zeros)
+        .build();
+    // LDH (DMA),A; LD A,(0xC000); LDH A,(0x81); JR -2
+    let routine = [0xE0, 0x46, 0xFA, 0x00, 0xC0, 0xF0, 0x81, 0x18, 0xFE];
+    gb.memory_mut()[0xFF80..][..routine.len()].copy_from_slice(&routine);
+    gb.set_dma_trace(true);
+    gb
+}
+
+#[test]
+fn the_trace_records_the_transfer_and_the_blocked_accesses() {
+    let mut gb = console();
+
+    gb.run_frame();
+    let record = gb.take_dma_record();
+    assert_eq!(record.transfers.len(), 1);
+    let transfer = record.transfers[0];
+    assert_eq!(transfer.source, 0xC000);
+    assert_eq!(transfer.end - transfer.start, OAM_DMA_CYCLES as u64);
+
+    let blocked: Vec<_> = record
+        .accesses
+        .iter()
+        .filter(|access| access.blocked)
+        .collect();
+    assert_eq!(
+        blocked,
+        [&DmaAccess {
+            cycle: blocked[0].cycle,
+            pc: 0xFF82,
+            address: 0xC000,
+            write: false,
+            blocked: true,
+        }]
+    );
+    assert!(record
+        .accesses
+        .iter()
+        .any(|access| access.pc == 0xFF85 && access.address == 0xFF81 && !access.blocked));
+    assert!(record
+        .accesses
+        .iter()
+        .all(|access| access.cycle >= transfer.start && access.cycle < transfer.end));
+}
+
+#[test]
+fn a_running_transfer_shows_in_the_next_record() {
+    let mut gb = console();
+    for _ in 0..3 {
+        gb.step();
+    }
+
+    let first = gb.take_dma_record();
+    let second = gb.take_dma_record();
+    assert_eq!(first.transfers, second.transfers);
+    assert_eq!(first.end, second.start);
+
+    gb.run_frame();
+    gb.take_dma_record();
+    assert!(gb.take_dma_record().transfers.is_empty());
+}
+
+#[test]
+fn nothing_is_recorded_without_a_trace() {
+    let mut gb = console();
+    gb.set_dma_trace(false);
+
+    gb.run_frame();
+    let record = gb.take_dma_record();
+    assert!(record.transfers.is_empty());
+    assert!(record.accesses.is_empty());
+}
+
+#[test]
+fn blocked_accesses_are_reported() {
+    common::capture_logs();
+    let violations = || -> Vec<_> {
+        common::logged("gbemu::dma")
+            .into_iter()
+            .filter(|message| message.contains("outside HRAM"))
+            .collect()
+    };
+
+    let mut gb = console();
+    gb.run_frame();
+    assert_eq!(violations(), Vec::<String>::new());
+
+    let mut gb = console();
+    gb.accuracy_mut().report_oam_dma_violations = true;
+    gb.run_frame();
+    assert_eq!(
+        violations(),
+        ["Reading 0xc000 outside HRAM during OAM DMA at PC 0xff82"]
+    );
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use gbemu::{
+    env::{Env, Observation},
+    joypad::Buttons,
+    memory::{RamInit, Read},
+    video::PixelFormat,
+    GameBoy,
+};
+
+/// Copies P1, with the directions selected, to 0xC000 forever
+const PROGRAM: [u8; 11] = [
+    0x3E, 0x20, // LD A,0x20
+    0xE0, 0x00, // LDH [P1],A
+    0xF0, 0x00, // LDH A,[P1]
+    0xEA, 0x00, 0xC0, // LD [0xC000],A
+    0x18, 0xF5, // JR -11
+];
+
+fn right_held(gb: &GameBoy) -> bool {
+    gb.read_u8(0xC000) & 0b0001 == 0
+}
+
+fn new_env(observation: Observation) -> Env {
+    let rom = common::rom_with_program(&PROGRAM);
+    let gb = GameBoy::builder(&rom).ram_init(RamInit::Zeros).build();
+    Env::new(gb, observation)
+        .frame_skip(4)
+        .reward(|gb| right_held(gb) as u8 as f32)
+}
+
+#[test]
+fn steps_hold_the_action_for_the_frame_skip() {
+    let mut env = new_env(Observation::Memory(vec![0xC000..=0xC000]));
+    assert_eq!(env.reset(), [0b1110_1111]);
+
+    let step = env.step(Buttons::RIGHT | Buttons::A);
+    assert_eq!(step.observation, [0b1110_1110]);
+    assert_eq!(step.reward, 4.0);
+    assert!(!step.done);
+
+    let step = env.step(Buttons::LEFT);
+    assert_eq!(step.observation, [0b1110_1101]);
+    assert_eq!(step.reward, 0.0);
+}
+
+#[test]
+fn episodes_end_early_and_replay_the_same() {
+    let mut env = new_env(Observation::Memory(vec![0xC000..=0xC000])).done(right_held);
+    let actions = [Buttons::UP, Buttons::DOWN, Buttons::RIGHT];
+
+    let mut episodes = vec![];
+    for _ in 0..2 {
+        env.reset();
+        let steps: Vec<_> = actions.iter().map(|action| env.step(*action)).collect();
+        episodes.push(steps);
+    }
+
+    let steps = &episodes[0];
+    assert_eq!(steps[2].reward, 1.0);
+    assert!(steps[2].done && !steps[1].done);
+    assert_eq!(episodes[0], episodes[1]);
+}
+
+#[test]
+fn observations_have_the_declared_size() {
+    let observations = [
+        Observation::Screen(PixelFormat::Indexed),
+        Observation::Screen(PixelFormat::Rgba8888),
+        Observation::Memory(vec![0xC000..=0xC0FF, 0xFF80..=0xFFFE]),
+    ];
+    for observation in observations {
+        let mut env = new_env(observation.clone());
+        assert_eq!(env.reset().len(), observation.size(), "{:?}", observation);
+        let step = env.step(Buttons::START);
+        assert_eq!(step.observation.len(), observation.size());
+    }
+    assert_eq!(Observation::Memory(vec![0xC000..=0xC0FF]).size(), 0x100);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use gbemu::{
+    eventlog::{EventLog, FrameRecord},
+    joypad::Buttons,
+    memory::{locations, Write},
+    GameBoy, Interrupt,
+};
+
+#[test]
+fn frames_are_logged_as_json_lines() {
+    let mut rom = common::rom_with_header(
+        &[
+            0x3E, 0x02, // LD A,2
+            0xEA, 0x00, 0x20, // LD [0x2000],A
+            0x3E, b'H', // LD A,'H'
+            0xE0, 0x01, // LDH [SB],A
+            0x3E, 0x81, // LD A,0x81
+            0xE0, 0x02, // LDH [SC],A
+            0xFB, // EI
+            0x18, 0xFE, // JR -2
+        ],
+        0x01,
+        0x01,
+        0x00,
+    );
+    // RETI
+    rom[0x40] = 0xD9;
+
+    let mut gb = GameBoy::new(&rom);
+    gb.write_u8(locations::IE, 0x01);
+    gb.write_u8(locations::IF, 0x00);
+    gb.set_buttons(Buttons::A | Buttons::START);
+
+    let mut log = EventLog::start(&mut gb, Vec::new());
+    gb.run_frame();
+    let record = gb.take_frame_record();
+    assert_eq!(record.frame, 0);
+    assert_eq!(record.interrupts, [Interrupt::VBlank]);
+    assert_eq!(record.bank_switches.len(), 1);
+    assert_eq!(record.bank_switches[0].rom_bank, 2);
+    assert_eq!(record.serial, b"H");
+    assert_eq!(record.state_hash, gb.state_hash());
+
+    gb.run_frame();
+    log.log_frame(&mut gb).unwrap();
+    let text = String::from_utf8(log.into_inner()).unwrap();
+    assert_eq!(
+        text,
+        format!(
+            "{{\"frame\":1,\"buttons\":[\"A\",\"Start\"],\"interrupts\":[\"VBlank\"],\"bank_switches\":[],\"serial\":[],\"state_hash\":\"{:016x}\"}}\n",
+            gb.state_hash()
+        )
+    );
+}
+
+#[test]
+fn json_lists_every_event() {
+    let gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    let record = FrameRecord {
+        frame: 7,
+        buttons: Buttons::LEFT,
+        interrupts: vec![Interrupt::Joypad, Interrupt::TimerOverflow],
+        bank_switches: vec![gb.banking_state()],
+        serial: vec![1, 2],
+        state_hash: 0xABC,
+    };
+    assert_eq!(
+        record.to_json(),
+        "{\"frame\":7,\"buttons\":[\"Left\"],\"interrupts\":[\"Joypad\",\"TimerOverflow\"],\"bank_switches\":[{\"rom\":1,\"ram\":0,\"ram_enabled\":false}],\"serial\":[1,2],\"state_hash\":\"0000000000000abc\"}"
+    );
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use std::ptr;
+
+use gbemu::{
+    ffi::*,
+    lcd::{SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+
+#[test]
+fn a_console_runs_through_the_c_abi() {
+    // LD A,(0xFF00+0x80); INC A; LDH (0x80),A; JR -6
+    let rom = common::rom_with_program(&[0xF0, 0x80, 0x3C, 0xE0, 0x80, 0x18, 0xFA]);
+    unsafe {
+        let gb = gbemu_create();
+        assert!(gbemu_load_rom(gb, rom.as_ptr(), rom.len()));
+        gbemu_set_button(gb, GbemuButton::Start, true);
+        assert!(gbemu_run_frame(gb) > 0);
+
+        let frame = gbemu_framebuffer(gb);
+        let len = SCREEN_WIDTH * SCREEN_HEIGHT * 4;
+        assert_eq!(std::slice::from_raw_parts(frame, len).len(), len);
+
+        let size = gbemu_save_state(gb, ptr::null_mut(), 0);
+        let mut state = vec![0; size];
+        assert_eq!(gbemu_save_state(gb, state.as_mut_ptr(), state.len()), size);
+        gbemu_run_frame(gb);
+        assert!(gbemu_load_state(gb, state.as_ptr(), state.len()));
+        assert_eq!(gbemu_save_state(gb, ptr::null_mut(), 0), size);
+
+        gbemu_destroy(gb);
+    }
+}
+
+#[test]
+fn invalid_input_is_rejected() {
+    unsafe {
+        let gb = gbemu_create();
+        // Too short for a header
+        assert!(!gbemu_load_rom(gb, [0u8; 16].as_ptr(), 16));
+        // Long enough, with a RAM size the header can't hold
+        let rom = common::rom_with_header(&[0x18, 0xFE], 0x00, 0x00, 0x42);
+        assert!(!gbemu_load_rom(gb, rom.as_ptr(), rom.len()));
+        assert!(!gbemu_load_state(gb, b"nope".as_ptr(), 4));
+        assert!(!gbemu_load_rom(ptr::null_mut(), ptr::null(), 0));
+        gbemu_destroy(gb);
+        gbemu_destroy(ptr::null_mut());
+    }
+}
@@ -0,0 +1,330 @@
+mod common;
+
+use gbemu::{
+    cartridge::{
+        CartridgeError, CartridgeFeatures, CartridgeHeader, CartridgeHolder, CartridgeType,
+        HeaderBuilder, HeaderWarning, Mapper, RamSize, RomSize,
+    },
+    cpu::{Cpu, Registers},
+    memory::Memory,
+    GameBoy,
+};
+
+#[test]
+fn lenient_parsing_replaces_invalid_values() {
+    let mut rom = common::rom_with_header(&[], 0xAB, 0x00, 0x42);
+    rom[0x014A] = 0x07;
+
+    let header = CartridgeHeader::parse_lenient(&rom);
+    assert_eq!(header.cart_type.mapper, Mapper::RomOnly);
+    assert!(header.warnings.contains(&HeaderWarning::CartridgeType {
+        code: 0xAB,
+        fallback: Mapper::RomOnly
+    }));
+    assert!(header
+        .warnings
+        .contains(&HeaderWarning::RamSize { code: 0x42 }));
+    assert!(header
+        .warnings
+        .contains(&HeaderWarning::Destination { code: 0x07 }));
+}
+
+#[test]
+fn lenient_build_accepts_short_images() {
+    // NOP sled into an empty ROM
+    let rom = vec![0x00; 0x0200];
+    let gb = GameBoy::builder(&rom).lenient_header(true).build();
+
+    assert_eq!(*gb.registers().pc, 0x0100);
+    assert!(gb
+        .cartridge_header()
+        .warnings
+        .contains(&HeaderWarning::SizeMismatch {
+            declared: 0x8000,
+            actual: 0x0200
+        }));
+}
+
+#[test]
+#[should_panic(expected = "invalid RAM size 0x42 in the header")]
+fn strict_parsing_panics() {
+    let rom = common::rom_with_header(&[], 0x00, 0x00, 0x42);
+    let _ = CartridgeHeader::from(rom.as_slice());
+}
+
+#[test]
+fn sizes_in_bytes_and_banks() {
+    // MBC5+RAM, 1 MiB of ROM and 32 KiB of RAM
+    let rom = common::rom_with_header(&[], 0x1A, 0x05, 0x03);
+    let header = CartridgeHeader::from(rom.as_slice());
+    assert_eq!((header.rom_banks(), header.rom_bytes()), (64, 0x10_0000));
+    assert_eq!((header.ram_banks(), header.ram_bytes()), (4, 0x8000));
+    assert_eq!(header.rom_size.to_string(), "1 MiB");
+    assert_eq!(header.ram_size.to_string(), "32 KiB");
+
+    // MBC2 has 512 half-bytes built in, the header declares none
+    let rom = common::rom_with_header(&[], 0x06, 0x00, 0x00);
+    let header = CartridgeHeader::from(rom.as_slice());
+    assert_eq!((header.ram_banks(), header.ram_bytes()), (0, 0x200));
+    assert_eq!(header.ram_size.to_string(), "none");
+
+    let sizes = [
+        (RomSize::KiB32, "32 KiB"),
+        (RomSize::MiB8, "8 MiB"),
+        (RomSize::MiB1Point1, "1.1 MiB"),
+        (RomSize::MiB1Point2, "1.2 MiB"),
+        (RomSize::MiB1Point5, "1.5 MiB"),
+    ];
+    for (size, text) in sizes {
+        assert_eq!(size.to_string(), text);
+    }
+    assert_eq!(RamSize::KiB128.to_string(), "128 KiB");
+}
+
+#[test]
+fn features_come_from_the_type_byte() {
+    // Code, battery, clock, rumble
+    let cases = [
+        (0x00, false, false, false),
+        (0x01, false, false, false),
+        (0x03, true, false, false),
+        (0x06, true, false, false),
+        (0x0F, true, true, false),
+        (0x10, true, true, false),
+        (0x11, false, false, false),
+        (0x13, true, false, false),
+        (0x1B, true, false, false),
+        (0x1C, false, false, true),
+        (0x1E, true, false, true),
+    ];
+    for (code, battery, rtc, rumble) in cases {
+        let rom = common::rom_with_header(&[], code, 0x00, 0x00);
+        let header = CartridgeHeader::from(rom.as_slice());
+        assert_eq!(header.type_code, code);
+        assert_eq!(
+            (header.has_battery(), header.has_rtc(), header.has_rumble()),
+            (battery, rtc, rumble),
+            "Type {:#04x}",
+            code
+        );
+        assert_eq!(header.battery, battery);
+    }
+}
+
+#[test]
+fn cartridge_type_keeps_the_features() {
+    use CartridgeFeatures as F;
+    let cases = [
+        (0x01, Mapper::MBC1, F::empty()),
+        (0x02, Mapper::MBC1, F::RAM),
+        (0x03, Mapper::MBC1, F::RAM | F::BATTERY),
+        (0x08, Mapper::RomOnly, F::RAM),
+        (0x10, Mapper::MBC3, F::TIMER | F::RAM | F::BATTERY),
+        (0x1D, Mapper::MBC5, F::RUMBLE | F::RAM),
+        (
+            0x22,
+            Mapper::NotSupported,
+            F::SENSOR | F::RUMBLE | F::RAM | F::BATTERY,
+        ),
+        (0x42, Mapper::Unknown, F::empty()),
+    ];
+    for (code, mapper, features) in cases {
+        assert_eq!(
+            CartridgeType::from(code),
+            CartridgeType::new(mapper, features),
+            "Type {:#04x}",
+            code
+        );
+    }
+}
+
+#[test]
+fn lenient_fallback_keeps_the_features() {
+    // HuC1+RAM+BATTERY
+    let rom = common::rom_with_header(&[], 0xFF, 0x00, 0x02);
+    let header = CartridgeHeader::parse_lenient(&rom);
+    assert_eq!(header.cart_type.mapper, Mapper::RomOnly);
+    assert!(header.has_battery());
+}
+
+#[test]
+fn built_headers_parse_cleanly() {
+    let rom = HeaderBuilder::new()
+        .title("BUILT")
+        .cartridge_type(0x1B)
+        .rom_size(RomSize::KiB128)
+        .ram_size(RamSize::KiB32)
+        .color(true)
+        .sgb(true)
+        .version(2)
+        .build();
+    assert_eq!(rom.len(), 128 * 1024);
+
+    let header = CartridgeHeader::from(rom.as_slice());
+    assert!(header.warnings.is_empty(), "{:?}", header.warnings);
+    assert_eq!(header.title, "BUILT");
+    assert!(header.color && header.sgb);
+    assert_eq!(header.cart_type.mapper, Mapper::MBC5);
+    assert_eq!((header.rom_banks(), header.ram_banks()), (8, 4));
+    assert_eq!(header.version, 2);
+
+    let sum = rom
+        .iter()
+        .enumerate()
+        .filter(|(address, _)| !(0x014E..=0x014F).contains(address))
+        .fold(0u16, |sum, (_, byte)| sum.wrapping_add(*byte as u16));
+    assert_eq!(header.global_checksum, sum);
+}
+
+#[test]
+fn built_roms_run_the_code() {
+    // LD A,0x42; JR -2
+    let rom = HeaderBuilder::new().code(&[0x3E, 0x42, 0x18, 0xFE]).build();
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..4 {
+        gb.step();
+    }
+    assert_eq!(gb.registers().af.to_be_bytes()[0], 0x42);
+    assert_eq!(*gb.registers().pc, 0x0152);
+}
+
+#[test]
+fn size_codes_round_trip() {
+    for code in (0x00..=0x08).chain(0x52..=0x54) {
+        assert_eq!(RomSize::from(code).code(), code);
+    }
+    for code in [0x00, 0x02, 0x03, 0x04, 0x05] {
+        assert_eq!(RamSize::from(code).code(), code);
+    }
+}
+
+#[test]
+fn ram_sizes_are_bank_counts() {
+    // Code, banks
+    let cases = [(0x00, 0), (0x02, 1), (0x03, 4), (0x04, 16), (0x05, 8)];
+    for (code, banks) in cases {
+        let size = RamSize::from(code);
+        assert_eq!(size.banks(), banks, "{:#04x}", code);
+        assert_eq!(size.bytes(), banks * 0x2000, "{:#04x}", code);
+
+        // MBC5+RAM+BATTERY
+        let rom = HeaderBuilder::new()
+            .cartridge_type(0x1B)
+            .rom_size(RomSize::KiB64)
+            .ram_size(size)
+            .build();
+        let gb = GameBoy::new(&rom);
+        assert_eq!(gb.ram().len(), size.bytes(), "{:#04x}", code);
+    }
+}
+
+#[test]
+fn rom_sizes_are_bank_counts() {
+    // Code, banks
+    let cases = [
+        (0x00, 2),
+        (0x01, 4),
+        (0x02, 8),
+        (0x03, 16),
+        (0x04, 32),
+        (0x05, 64),
+        (0x06, 128),
+        (0x07, 256),
+        (0x08, 512),
+        (0x52, 72),
+        (0x53, 80),
+        (0x54, 96),
+    ];
+    for (code, banks) in cases {
+        let size = RomSize::from(code);
+        assert_eq!(size.banks(), banks, "{:#04x}", code);
+        assert_eq!(size.bytes(), banks * 0x4000, "{:#04x}", code);
+
+        // MBC5
+        let rom = HeaderBuilder::new()
+            .cartridge_type(0x19)
+            .rom_size(size)
+            .build();
+        assert_eq!(rom.len(), size.bytes(), "{:#04x}", code);
+        let gb = GameBoy::try_new(&rom).unwrap();
+        assert_eq!(gb.cartridge().len(), size.bytes(), "{:#04x}", code);
+    }
+}
+
+#[test]
+fn roms_too_big_for_the_mapper_are_refused() {
+    // Type, code, banks, maximum
+    let cases = [
+        (0x00, 0x01, 4, 2),
+        (0x01, 0x07, 256, 128),
+        (0x05, 0x04, 32, 16),
+        (0x11, 0x08, 512, 256),
+    ];
+    for (cartridge_type, code, banks, max) in cases {
+        let rom = common::rom_with_header(&[], cartridge_type, code, 0x00);
+        assert_eq!(
+            GameBoy::try_new(&rom).err(),
+            Some(CartridgeError::RomTooBig { banks, max }),
+            "{:#04x}",
+            cartridge_type
+        );
+    }
+}
+
+#[test]
+fn images_of_another_size_are_refused() {
+    let mut rom = HeaderBuilder::new()
+        .cartridge_type(0x19)
+        .rom_size(RomSize::KiB64)
+        .build();
+    rom.truncate(0x8000);
+    assert_eq!(
+        GameBoy::try_new(&rom).err(),
+        Some(CartridgeError::SizeMismatch {
+            declared: 0x10000,
+            actual: 0x8000
+        })
+    );
+    // Padded when parsed leniently
+    let gb = GameBoy::builder(&rom).lenient_header(true).build();
+    assert_eq!(gb.cartridge().len(), 0x10000);
+}
+
+#[test]
+fn invalid_headers_are_refused() {
+    // Field offset, value
+    let cases = [
+        (0x0147, 0xAB, "cartridge type"),
+        (0x0148, 0x42, "ROM size"),
+        (0x0149, 0x07, "RAM size"),
+        (0x014A, 0x07, "destination"),
+    ];
+    for (offset, code, field) in cases {
+        let mut rom = common::rom_with_program(&[]);
+        rom[offset] = code;
+        assert_eq!(
+            GameBoy::try_new(&rom).err(),
+            Some(CartridgeError::InvalidHeader { field, code }),
+            "{}",
+            field
+        );
+        // Run with a fallback when parsed leniently
+        GameBoy::builder(&rom).lenient_header(true).build();
+    }
+}
+
+#[test]
+fn truncated_images_are_refused() {
+    let rom = common::rom_with_program(&[]);
+    for len in [1, 0x100, 0x14F] {
+        assert_eq!(
+            GameBoy::try_new(&rom[..len]).err(),
+            Some(CartridgeError::Truncated { len })
+        );
+        assert_eq!(
+            CartridgeHeader::try_parse(&rom[..len]).err(),
+            Some(CartridgeError::Truncated { len })
+        );
+    }
+    assert!(CartridgeHeader::try_parse(&rom[..0x150]).is_ok());
+}
@@ -0,0 +1,72 @@
+use gbemu::{
+    input::{Action, DpadPolicy, InputMapper},
+    joypad::Buttons,
+};
+
+fn mapper(policy: DpadPolicy) -> InputMapper<&'static str> {
+    InputMapper::new()
+        .bind("a", Action::Hold(Buttons::A))
+        .bind("turbo b", Action::Turbo(Buttons::B))
+        .bind("left", Action::Hold(Buttons::LEFT))
+        .bind("right", Action::Hold(Buttons::RIGHT))
+        .dpad_policy(policy)
+}
+
+#[test]
+fn held_keys_press_their_buttons() {
+    let mut input = mapper(DpadPolicy::Allow);
+    input.press("a");
+    input.press("unbound");
+    assert_eq!(input.next_frame().buttons, Buttons::A);
+
+    input.release(&"a");
+    let state = input.next_frame();
+    assert_eq!(state.frame, 1);
+    assert!(state.buttons.is_empty());
+}
+
+#[test]
+fn turbo_follows_the_frames() {
+    // 2 frames pressed, 2 released
+    let mut input = mapper(DpadPolicy::Allow).turbo_rate(15.0).frame_rate(60.0);
+    input.next_frame();
+    input.press("turbo b");
+    let pressed: Vec<_> = (0..8)
+        .map(|_| input.next_frame().buttons.contains(Buttons::B))
+        .collect();
+    assert_eq!(
+        pressed,
+        [true, true, false, false, true, true, false, false]
+    );
+}
+
+#[test]
+fn opposite_directions_follow_the_policy() {
+    let states = |policy| {
+        let mut input = mapper(policy);
+        input.press("right");
+        input.press("left");
+        let both = input.next_frame().buttons;
+        input.release(&"left");
+        input.press("left");
+        input.release(&"right");
+        input.press("right");
+        (both, input.next_frame().buttons)
+    };
+
+    assert_eq!(
+        states(DpadPolicy::Allow),
+        (
+            Buttons::LEFT | Buttons::RIGHT,
+            Buttons::LEFT | Buttons::RIGHT
+        )
+    );
+    assert_eq!(
+        states(DpadPolicy::Neutral),
+        (Buttons::empty(), Buttons::empty())
+    );
+    assert_eq!(
+        states(DpadPolicy::LastPressed),
+        (Buttons::LEFT, Buttons::RIGHT)
+    );
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use gbemu::{
+    cpu::{CpuMode, Register, Registers},
+    memory::{locations, Memory, Write},
+    GameBoy, Interrupt,
+};
+
+#[test]
+fn snapshot_after_power_on() {
+    let gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    let state = gb.inspect();
+
+    assert_eq!(*state.registers.pc, 0x0100);
+    assert!(state.flags.zero);
+    assert!(!state.flags.subtract);
+    assert_eq!(state.cpu_mode, CpuMode::Running);
+    assert_eq!(state.banking.rom_bank, 1);
+    assert_eq!(state.timer.div, 0xAB);
+    assert!(!state.timer.enabled());
+    assert_eq!(state.cycles, 0);
+}
+
+#[test]
+fn snapshot_follows_execution() {
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    gb.write_u8(locations::TAC, 0b101);
+    gb.run_frame();
+    let state = gb.inspect();
+
+    assert!(state.timer.enabled());
+    assert_eq!(state.timer.frequency(), 262144);
+    assert_eq!(state.timer.edge_bit(), 3);
+    assert_eq!(
+        state.timer.ticks_to_overflow(),
+        0x100 - state.timer.tima as u16
+    );
+    assert_eq!(state.frames, 1);
+    assert!(state.cycles > 0);
+    assert!(state.ly < 154);
+    assert_eq!(state.lcd_mode, gb.lcd_mode());
+}
+
+#[test]
+fn pending_interrupts_are_requested_and_enabled() {
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    gb.write_u8(locations::IE, 0b0000_0101);
+    gb.write_u8(locations::IF, 0b0000_0110);
+    let state = gb.inspect();
+
+    assert!(state.is_pending(Interrupt::TimerOverflow));
+    assert!(!state.is_pending(Interrupt::LCDStat));
+    assert_eq!(
+        state.pending_interrupts().collect::<Vec<_>>(),
+        [Interrupt::TimerOverflow]
+    );
+}
+
+#[test]
+fn registers_and_status_are_formatted() {
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    assert_eq!(
+        gb.registers().to_string(),
+        "AF=01B0 BC=0013 DE=00D8 HL=014D SP=FFFE PC=0100 [Z-HC]"
+    );
+
+    let registers = gb.registers_mut();
+    registers.af = Register { value: 0x12A0 };
+    registers.bc = Register { value: 0x3456 };
+    registers.hl = Register { value: 0xC0DE };
+    registers.sp = Register { value: 0xDFF0 };
+    registers.pc = Register { value: 0x4321 };
+    registers.ime = true;
+    registers.mode = CpuMode::Halted;
+    assert_eq!(
+        gb.registers().to_string(),
+        "AF=12A0 BC=3456 DE=00D8 HL=C0DE SP=DFF0 PC=4321 [Z-H-]"
+    );
+    // The value, not the bytes of the union
+    assert_eq!(format!("{:?}", gb.registers().hl), "0xC0DE");
+
+    for (address, value) in [
+        (locations::LY, 0x90),
+        (locations::DIV, 0x12),
+        (locations::TIMA, 0x34),
+        (locations::TAC, 0xFD),
+        (locations::IF, 0xE4),
+        (locations::IE, 0x05),
+    ] {
+        gb.memory_mut()[address] = value;
+    }
+    assert_eq!(
+        gb.status_line(),
+        "AF=12A0 BC=3456 DE=00D8 HL=C0DE SP=DFF0 PC=4321 [Z-H-] IME=1 Halted \
+         ROM=01 RAM=00 LY=90 DIV=12 TIMA=34 TAC=FD IF=E4 IE=05"
+    );
+}
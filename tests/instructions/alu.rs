@@ -0,0 +1,1748 @@
+//! 8-bit and 16-bit arithmetic and logic
+use crate::common::flat::cpu_test;
+
+cpu_test! {
+    add_a_b_00_00: "ADD A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0x80]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    add_a_b_0f_01: "ADD A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0x80]
+        expect { a: 0x10, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_b_ff_01: "ADD A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0x80]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    add_a_b_80_80: "ADD A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0x80]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    add_a_b_3a_c6: "ADD A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0x80]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    add_a_b_12_34: "ADD A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0x80]
+        expect { a: 0x46, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    add_a_b_10_20: "ADD A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0x80]
+        expect { a: 0x30, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    add_a_b_01_02: "ADD A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0x80]
+        expect { a: 0x03, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    add_a_c: "ADD A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0x81]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_d: "ADD A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0x82]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_e: "ADD A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0x83]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_h: "ADD A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0x84]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_l: "ADD A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0x85]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_hl: "ADD A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0x86]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_a_a: "ADD A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0x87]
+        expect { a: 0x74, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    add_a_n8_3a_c6: "ADD A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xC6, 0xC6]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    add_a_n8_0f_0f: "ADD A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xC6, 0x0F]
+        expect { a: 0x1E, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    adc_a_b_00_00: "ADC A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0x88]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_00_00_carry: "ADC A,B with A=0x00 B=0x00 carry" {
+        regs { a: 0x00, b: 0x00, cf: true }
+        run [0x88]
+        expect { a: 0x01, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_0f_01: "ADC A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0x88]
+        expect { a: 0x10, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_0f_01_carry: "ADC A,B with A=0x0F B=0x01 carry" {
+        regs { a: 0x0F, b: 0x01, cf: true }
+        run [0x88]
+        expect { a: 0x11, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_ff_01: "ADC A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0x88]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    adc_a_b_ff_01_carry: "ADC A,B with A=0xFF B=0x01 carry" {
+        regs { a: 0xFF, b: 0x01, cf: true }
+        run [0x88]
+        expect { a: 0x01, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    adc_a_b_80_80: "ADC A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0x88]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    adc_a_b_80_80_carry: "ADC A,B with A=0x80 B=0x80 carry" {
+        regs { a: 0x80, b: 0x80, cf: true }
+        run [0x88]
+        expect { a: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    adc_a_b_3a_c6: "ADC A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0x88]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    adc_a_b_3a_c6_carry: "ADC A,B with A=0x3A B=0xC6 carry" {
+        regs { a: 0x3A, b: 0xC6, cf: true }
+        run [0x88]
+        expect { a: 0x01, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    adc_a_b_12_34: "ADC A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0x88]
+        expect { a: 0x46, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_12_34_carry: "ADC A,B with A=0x12 B=0x34 carry" {
+        regs { a: 0x12, b: 0x34, cf: true }
+        run [0x88]
+        expect { a: 0x47, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_10_20: "ADC A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0x88]
+        expect { a: 0x30, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_10_20_carry: "ADC A,B with A=0x10 B=0x20 carry" {
+        regs { a: 0x10, b: 0x20, cf: true }
+        run [0x88]
+        expect { a: 0x31, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_01_02: "ADC A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0x88]
+        expect { a: 0x03, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_b_01_02_carry: "ADC A,B with A=0x01 B=0x02 carry" {
+        regs { a: 0x01, b: 0x02, cf: true }
+        run [0x88]
+        expect { a: 0x04, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    adc_a_c: "ADC A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0x89]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_d: "ADC A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0x8A]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_e: "ADC A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0x8B]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_h: "ADC A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0x8C]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_l: "ADC A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0x8D]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_hl: "ADC A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0x8E]
+        expect { a: 0x49, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    adc_a_a: "ADC A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0x8F]
+        expect { a: 0x74, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    adc_a_n8_3a_c6: "ADC A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xCE, 0xC6]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    adc_a_n8_3a_c6_carry: "ADC A,n8 with A=0x3A n8=0xC6 carry" {
+        regs { a: 0x3A, cf: true }
+        run [0xCE, 0xC6]
+        expect { a: 0x01, zf: false, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    adc_a_n8_0f_0f: "ADC A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xCE, 0x0F]
+        expect { a: 0x1E, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    adc_a_n8_0f_0f_carry: "ADC A,n8 with A=0x0F n8=0x0F carry" {
+        regs { a: 0x0F, cf: true }
+        run [0xCE, 0x0F]
+        expect { a: 0x1F, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sub_a_b_00_00: "SUB A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0x90]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sub_a_b_0f_01: "SUB A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0x90]
+        expect { a: 0x0E, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sub_a_b_ff_01: "SUB A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0x90]
+        expect { a: 0xFE, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sub_a_b_80_80: "SUB A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0x90]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sub_a_b_3a_c6: "SUB A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0x90]
+        expect { a: 0x74, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    sub_a_b_12_34: "SUB A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0x90]
+        expect { a: 0xDE, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sub_a_b_10_20: "SUB A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0x90]
+        expect { a: 0xF0, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    sub_a_b_01_02: "SUB A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0x90]
+        expect { a: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sub_a_c: "SUB A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0x91]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sub_a_d: "SUB A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0x92]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sub_a_e: "SUB A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0x93]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sub_a_h: "SUB A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0x94]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sub_a_l: "SUB A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0x95]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sub_a_hl: "SUB A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0x96]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 8 }
+    }
+
+    sub_a_a: "SUB A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0x97]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sub_a_n8_3a_c6: "SUB A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xD6, 0xC6]
+        expect { a: 0x74, zf: false, nf: true, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sub_a_n8_0f_0f: "SUB A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xD6, 0x0F]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sbc_a_b_00_00: "SBC A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0x98]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_b_00_00_carry: "SBC A,B with A=0x00 B=0x00 carry" {
+        regs { a: 0x00, b: 0x00, cf: true }
+        run [0x98]
+        expect { a: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_0f_01: "SBC A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0x98]
+        expect { a: 0x0E, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_b_0f_01_carry: "SBC A,B with A=0x0F B=0x01 carry" {
+        regs { a: 0x0F, b: 0x01, cf: true }
+        run [0x98]
+        expect { a: 0x0D, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_b_ff_01: "SBC A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0x98]
+        expect { a: 0xFE, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_b_ff_01_carry: "SBC A,B with A=0xFF B=0x01 carry" {
+        regs { a: 0xFF, b: 0x01, cf: true }
+        run [0x98]
+        expect { a: 0xFD, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_b_80_80: "SBC A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0x98]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_b_80_80_carry: "SBC A,B with A=0x80 B=0x80 carry" {
+        regs { a: 0x80, b: 0x80, cf: true }
+        run [0x98]
+        expect { a: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_3a_c6: "SBC A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0x98]
+        expect { a: 0x74, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_3a_c6_carry: "SBC A,B with A=0x3A B=0xC6 carry" {
+        regs { a: 0x3A, b: 0xC6, cf: true }
+        run [0x98]
+        expect { a: 0x73, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_12_34: "SBC A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0x98]
+        expect { a: 0xDE, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_12_34_carry: "SBC A,B with A=0x12 B=0x34 carry" {
+        regs { a: 0x12, b: 0x34, cf: true }
+        run [0x98]
+        expect { a: 0xDD, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_10_20: "SBC A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0x98]
+        expect { a: 0xF0, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_10_20_carry: "SBC A,B with A=0x10 B=0x20 carry" {
+        regs { a: 0x10, b: 0x20, cf: true }
+        run [0x98]
+        expect { a: 0xEF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_01_02: "SBC A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0x98]
+        expect { a: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_b_01_02_carry: "SBC A,B with A=0x01 B=0x02 carry" {
+        regs { a: 0x01, b: 0x02, cf: true }
+        run [0x98]
+        expect { a: 0xFE, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    sbc_a_c: "SBC A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0x99]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sbc_a_d: "SBC A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0x9A]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sbc_a_e: "SBC A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0x9B]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sbc_a_h: "SBC A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0x9C]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sbc_a_l: "SBC A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0x9D]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    sbc_a_hl: "SBC A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0x9E]
+        expect { a: 0x2B, zf: false, nf: true, hf: true, cf: false, cycles: 8 }
+    }
+
+    sbc_a_a: "SBC A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0x9F]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    sbc_a_n8_3a_c6: "SBC A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xDE, 0xC6]
+        expect { a: 0x74, zf: false, nf: true, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sbc_a_n8_3a_c6_carry: "SBC A,n8 with A=0x3A n8=0xC6 carry" {
+        regs { a: 0x3A, cf: true }
+        run [0xDE, 0xC6]
+        expect { a: 0x73, zf: false, nf: true, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sbc_a_n8_0f_0f: "SBC A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xDE, 0x0F]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sbc_a_n8_0f_0f_carry: "SBC A,n8 with A=0x0F n8=0x0F carry" {
+        regs { a: 0x0F, cf: true }
+        run [0xDE, 0x0F]
+        expect { a: 0xFF, zf: false, nf: true, hf: true, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    and_a_b_00_00: "AND A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0xA0]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_0f_01: "AND A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0xA0]
+        expect { a: 0x01, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_ff_01: "AND A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0xA0]
+        expect { a: 0x01, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_80_80: "AND A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0xA0]
+        expect { a: 0x80, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_3a_c6: "AND A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0xA0]
+        expect { a: 0x02, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_12_34: "AND A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0xA0]
+        expect { a: 0x10, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_10_20: "AND A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0xA0]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_b_01_02: "AND A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0xA0]
+        expect { a: 0x00, zf: true, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_c: "AND A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0xA1]
+        expect { a: 0x0A, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_d: "AND A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0xA2]
+        expect { a: 0x0A, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_e: "AND A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0xA3]
+        expect { a: 0x0A, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_h: "AND A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0xA4]
+        expect { a: 0x0A, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_l: "AND A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0xA5]
+        expect { a: 0x0A, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_hl: "AND A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0xA6]
+        expect { a: 0x0A, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    and_a_a: "AND A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0xA7]
+        expect { a: 0x3A, zf: false, nf: false, hf: true, cf: false, cycles: 4 }
+    }
+
+    and_a_n8_3a_c6: "AND A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xE6, 0xC6]
+        expect { a: 0x02, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    and_a_n8_0f_0f: "AND A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xE6, 0x0F]
+        expect { a: 0x0F, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    xor_a_b_00_00: "XOR A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0xA8]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_0f_01: "XOR A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0xA8]
+        expect { a: 0x0E, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_ff_01: "XOR A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0xA8]
+        expect { a: 0xFE, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_80_80: "XOR A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0xA8]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_3a_c6: "XOR A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0xA8]
+        expect { a: 0xFC, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_12_34: "XOR A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0xA8]
+        expect { a: 0x26, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_10_20: "XOR A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0xA8]
+        expect { a: 0x30, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_b_01_02: "XOR A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0xA8]
+        expect { a: 0x03, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_c: "XOR A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0xA9]
+        expect { a: 0x35, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_d: "XOR A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0xAA]
+        expect { a: 0x35, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_e: "XOR A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0xAB]
+        expect { a: 0x35, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_h: "XOR A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0xAC]
+        expect { a: 0x35, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_l: "XOR A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0xAD]
+        expect { a: 0x35, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_hl: "XOR A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0xAE]
+        expect { a: 0x35, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    xor_a_a: "XOR A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0xAF]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    xor_a_n8_3a_c6: "XOR A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xEE, 0xC6]
+        expect { a: 0xFC, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    xor_a_n8_0f_0f: "XOR A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xEE, 0x0F]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    or_a_b_00_00: "OR A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0xB0]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_0f_01: "OR A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0xB0]
+        expect { a: 0x0F, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_ff_01: "OR A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0xB0]
+        expect { a: 0xFF, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_80_80: "OR A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0xB0]
+        expect { a: 0x80, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_3a_c6: "OR A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0xB0]
+        expect { a: 0xFE, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_12_34: "OR A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0xB0]
+        expect { a: 0x36, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_10_20: "OR A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0xB0]
+        expect { a: 0x30, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_b_01_02: "OR A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0xB0]
+        expect { a: 0x03, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_c: "OR A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0xB1]
+        expect { a: 0x3F, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_d: "OR A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0xB2]
+        expect { a: 0x3F, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_e: "OR A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0xB3]
+        expect { a: 0x3F, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_h: "OR A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0xB4]
+        expect { a: 0x3F, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_l: "OR A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0xB5]
+        expect { a: 0x3F, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_hl: "OR A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0xB6]
+        expect { a: 0x3F, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    or_a_a: "OR A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0xB7]
+        expect { a: 0x3A, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    or_a_n8_3a_c6: "OR A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xF6, 0xC6]
+        expect { a: 0xFE, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    or_a_n8_0f_0f: "OR A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xF6, 0x0F]
+        expect { a: 0x0F, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    cp_a_b_00_00: "CP A,B with A=0x00 B=0x00" {
+        regs { a: 0x00, b: 0x00, cf: false }
+        run [0xB8]
+        expect { a: 0x00, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    cp_a_b_0f_01: "CP A,B with A=0x0F B=0x01" {
+        regs { a: 0x0F, b: 0x01, cf: false }
+        run [0xB8]
+        expect { a: 0x0F, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    cp_a_b_ff_01: "CP A,B with A=0xFF B=0x01" {
+        regs { a: 0xFF, b: 0x01, cf: false }
+        run [0xB8]
+        expect { a: 0xFF, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    cp_a_b_80_80: "CP A,B with A=0x80 B=0x80" {
+        regs { a: 0x80, b: 0x80, cf: false }
+        run [0xB8]
+        expect { a: 0x80, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    cp_a_b_3a_c6: "CP A,B with A=0x3A B=0xC6" {
+        regs { a: 0x3A, b: 0xC6, cf: false }
+        run [0xB8]
+        expect { a: 0x3A, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    cp_a_b_12_34: "CP A,B with A=0x12 B=0x34" {
+        regs { a: 0x12, b: 0x34, cf: false }
+        run [0xB8]
+        expect { a: 0x12, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    cp_a_b_10_20: "CP A,B with A=0x10 B=0x20" {
+        regs { a: 0x10, b: 0x20, cf: false }
+        run [0xB8]
+        expect { a: 0x10, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    cp_a_b_01_02: "CP A,B with A=0x01 B=0x02" {
+        regs { a: 0x01, b: 0x02, cf: false }
+        run [0xB8]
+        expect { a: 0x01, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    cp_a_c: "CP A,C" {
+        regs { a: 0x3A, c: 0x0F, cf: false }
+        run [0xB9]
+        expect { a: 0x3A, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    cp_a_d: "CP A,D" {
+        regs { a: 0x3A, d: 0x0F, cf: false }
+        run [0xBA]
+        expect { a: 0x3A, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    cp_a_e: "CP A,E" {
+        regs { a: 0x3A, e: 0x0F, cf: false }
+        run [0xBB]
+        expect { a: 0x3A, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    cp_a_h: "CP A,H" {
+        regs { a: 0x3A, h: 0x0F, cf: false }
+        run [0xBC]
+        expect { a: 0x3A, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    cp_a_l: "CP A,L" {
+        regs { a: 0x3A, l: 0x0F, cf: false }
+        run [0xBD]
+        expect { a: 0x3A, zf: false, nf: true, hf: true, cf: false, cycles: 4 }
+    }
+
+    cp_a_hl: "CP A,[HL]" {
+        regs { a: 0x3A, hl: 0xC000, cf: false }
+        mem { 0xC000: 0x0F }
+        run [0xBE]
+        expect { a: 0x3A, zf: false, nf: true, hf: true, cf: false, cycles: 8 }
+    }
+
+    cp_a_a: "CP A,A" {
+        regs { a: 0x3A, cf: false }
+        run [0xBF]
+        expect { a: 0x3A, zf: true, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    cp_a_n8_3a_c6: "CP A,n8 with A=0x3A n8=0xC6" {
+        regs { a: 0x3A, cf: false }
+        run [0xFE, 0xC6]
+        expect { a: 0x3A, zf: false, nf: true, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    cp_a_n8_0f_0f: "CP A,n8 with A=0x0F n8=0x0F" {
+        regs { a: 0x0F, cf: false }
+        run [0xFE, 0x0F]
+        expect { a: 0x0F, zf: true, nf: true, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    inc_b_00: "INC B from 0x00" {
+        regs { cf: true, b: 0x00 }
+        run [0x04]
+        expect { b: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_b_00: "DEC B from 0x00" {
+        regs { cf: true, b: 0x00 }
+        run [0x05]
+        expect { b: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_b_0f: "INC B from 0x0F" {
+        regs { cf: true, b: 0x0F }
+        run [0x04]
+        expect { b: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_b_0f: "DEC B from 0x0F" {
+        regs { cf: true, b: 0x0F }
+        run [0x05]
+        expect { b: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_b_ff: "INC B from 0xFF" {
+        regs { cf: true, b: 0xFF }
+        run [0x04]
+        expect { b: 0x00, zf: true, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_b_ff: "DEC B from 0xFF" {
+        regs { cf: true, b: 0xFF }
+        run [0x05]
+        expect { b: 0xFE, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_b_01: "INC B from 0x01" {
+        regs { cf: true, b: 0x01 }
+        run [0x04]
+        expect { b: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_b_01: "DEC B from 0x01" {
+        regs { cf: true, b: 0x01 }
+        run [0x05]
+        expect { b: 0x00, zf: true, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_c_00: "INC C from 0x00" {
+        regs { cf: true, c: 0x00 }
+        run [0x0C]
+        expect { c: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_c_00: "DEC C from 0x00" {
+        regs { cf: true, c: 0x00 }
+        run [0x0D]
+        expect { c: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_c_0f: "INC C from 0x0F" {
+        regs { cf: true, c: 0x0F }
+        run [0x0C]
+        expect { c: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_c_0f: "DEC C from 0x0F" {
+        regs { cf: true, c: 0x0F }
+        run [0x0D]
+        expect { c: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_d_00: "INC D from 0x00" {
+        regs { cf: true, d: 0x00 }
+        run [0x14]
+        expect { d: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_d_00: "DEC D from 0x00" {
+        regs { cf: true, d: 0x00 }
+        run [0x15]
+        expect { d: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_d_0f: "INC D from 0x0F" {
+        regs { cf: true, d: 0x0F }
+        run [0x14]
+        expect { d: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_d_0f: "DEC D from 0x0F" {
+        regs { cf: true, d: 0x0F }
+        run [0x15]
+        expect { d: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_e_00: "INC E from 0x00" {
+        regs { cf: true, e: 0x00 }
+        run [0x1C]
+        expect { e: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_e_00: "DEC E from 0x00" {
+        regs { cf: true, e: 0x00 }
+        run [0x1D]
+        expect { e: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_e_0f: "INC E from 0x0F" {
+        regs { cf: true, e: 0x0F }
+        run [0x1C]
+        expect { e: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_e_0f: "DEC E from 0x0F" {
+        regs { cf: true, e: 0x0F }
+        run [0x1D]
+        expect { e: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_h_00: "INC H from 0x00" {
+        regs { cf: true, h: 0x00 }
+        run [0x24]
+        expect { h: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_h_00: "DEC H from 0x00" {
+        regs { cf: true, h: 0x00 }
+        run [0x25]
+        expect { h: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_h_0f: "INC H from 0x0F" {
+        regs { cf: true, h: 0x0F }
+        run [0x24]
+        expect { h: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_h_0f: "DEC H from 0x0F" {
+        regs { cf: true, h: 0x0F }
+        run [0x25]
+        expect { h: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_l_00: "INC L from 0x00" {
+        regs { cf: true, l: 0x00 }
+        run [0x2C]
+        expect { l: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_l_00: "DEC L from 0x00" {
+        regs { cf: true, l: 0x00 }
+        run [0x2D]
+        expect { l: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_l_0f: "INC L from 0x0F" {
+        regs { cf: true, l: 0x0F }
+        run [0x2C]
+        expect { l: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_l_0f: "DEC L from 0x0F" {
+        regs { cf: true, l: 0x0F }
+        run [0x2D]
+        expect { l: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_hl_00: "INC [HL] from 0x00" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0x34]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 12 }
+        expect_mem { 0xC000: 0x01 }
+    }
+
+    dec_hl_00: "DEC [HL] from 0x00" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0x35]
+        expect { zf: false, nf: true, hf: true, cf: true, cycles: 12 }
+        expect_mem { 0xC000: 0xFF }
+    }
+
+    inc_hl_0f: "INC [HL] from 0x0F" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x0F }
+        run [0x34]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+        expect_mem { 0xC000: 0x10 }
+    }
+
+    dec_hl_0f: "DEC [HL] from 0x0F" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x0F }
+        run [0x35]
+        expect { zf: false, nf: true, hf: false, cf: true, cycles: 12 }
+        expect_mem { 0xC000: 0x0E }
+    }
+
+    inc_a_00: "INC A from 0x00" {
+        regs { cf: true, a: 0x00 }
+        run [0x3C]
+        expect { a: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    dec_a_00: "DEC A from 0x00" {
+        regs { cf: true, a: 0x00 }
+        run [0x3D]
+        expect { a: 0xFF, zf: false, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    inc_a_0f: "INC A from 0x0F" {
+        regs { cf: true, a: 0x0F }
+        run [0x3C]
+        expect { a: 0x10, zf: false, nf: false, hf: true, cf: true, cycles: 4 }
+    }
+
+    dec_a_0f: "DEC A from 0x0F" {
+        regs { cf: true, a: 0x0F }
+        run [0x3D]
+        expect { a: 0x0E, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    inc_bc_0000: "INC BC from 0x0000 with F=0xF0" {
+        regs { bc: 0x0000, f: 0xF0 }
+        run [0x03]
+        expect { bc: 0x0001, f: 0xF0, cycles: 8 }
+    }
+
+    dec_bc_0000: "DEC BC from 0x0000 with F=0xF0" {
+        regs { bc: 0x0000, f: 0xF0 }
+        run [0x0B]
+        expect { bc: 0xFFFF, f: 0xF0, cycles: 8 }
+    }
+
+    inc_bc_0000_flags_clear: "INC BC from 0x0000 with F=0x00" {
+        regs { bc: 0x0000, f: 0x00 }
+        run [0x03]
+        expect { bc: 0x0001, f: 0x00, cycles: 8 }
+    }
+
+    dec_bc_0000_flags_clear: "DEC BC from 0x0000 with F=0x00" {
+        regs { bc: 0x0000, f: 0x00 }
+        run [0x0B]
+        expect { bc: 0xFFFF, f: 0x00, cycles: 8 }
+    }
+
+    inc_bc_ffff: "INC BC from 0xFFFF with F=0xF0" {
+        regs { bc: 0xFFFF, f: 0xF0 }
+        run [0x03]
+        expect { bc: 0x0000, f: 0xF0, cycles: 8 }
+    }
+
+    dec_bc_ffff: "DEC BC from 0xFFFF with F=0xF0" {
+        regs { bc: 0xFFFF, f: 0xF0 }
+        run [0x0B]
+        expect { bc: 0xFFFE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_bc_ffff_flags_clear: "INC BC from 0xFFFF with F=0x00" {
+        regs { bc: 0xFFFF, f: 0x00 }
+        run [0x03]
+        expect { bc: 0x0000, f: 0x00, cycles: 8 }
+    }
+
+    dec_bc_ffff_flags_clear: "DEC BC from 0xFFFF with F=0x00" {
+        regs { bc: 0xFFFF, f: 0x00 }
+        run [0x0B]
+        expect { bc: 0xFFFE, f: 0x00, cycles: 8 }
+    }
+
+    inc_bc_00ff: "INC BC from 0x00FF with F=0xF0" {
+        regs { bc: 0x00FF, f: 0xF0 }
+        run [0x03]
+        expect { bc: 0x0100, f: 0xF0, cycles: 8 }
+    }
+
+    dec_bc_00ff: "DEC BC from 0x00FF with F=0xF0" {
+        regs { bc: 0x00FF, f: 0xF0 }
+        run [0x0B]
+        expect { bc: 0x00FE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_bc_00ff_flags_clear: "INC BC from 0x00FF with F=0x00" {
+        regs { bc: 0x00FF, f: 0x00 }
+        run [0x03]
+        expect { bc: 0x0100, f: 0x00, cycles: 8 }
+    }
+
+    dec_bc_00ff_flags_clear: "DEC BC from 0x00FF with F=0x00" {
+        regs { bc: 0x00FF, f: 0x00 }
+        run [0x0B]
+        expect { bc: 0x00FE, f: 0x00, cycles: 8 }
+    }
+
+    inc_de_0000: "INC DE from 0x0000 with F=0xF0" {
+        regs { de: 0x0000, f: 0xF0 }
+        run [0x13]
+        expect { de: 0x0001, f: 0xF0, cycles: 8 }
+    }
+
+    dec_de_0000: "DEC DE from 0x0000 with F=0xF0" {
+        regs { de: 0x0000, f: 0xF0 }
+        run [0x1B]
+        expect { de: 0xFFFF, f: 0xF0, cycles: 8 }
+    }
+
+    inc_de_0000_flags_clear: "INC DE from 0x0000 with F=0x00" {
+        regs { de: 0x0000, f: 0x00 }
+        run [0x13]
+        expect { de: 0x0001, f: 0x00, cycles: 8 }
+    }
+
+    dec_de_0000_flags_clear: "DEC DE from 0x0000 with F=0x00" {
+        regs { de: 0x0000, f: 0x00 }
+        run [0x1B]
+        expect { de: 0xFFFF, f: 0x00, cycles: 8 }
+    }
+
+    inc_de_ffff: "INC DE from 0xFFFF with F=0xF0" {
+        regs { de: 0xFFFF, f: 0xF0 }
+        run [0x13]
+        expect { de: 0x0000, f: 0xF0, cycles: 8 }
+    }
+
+    dec_de_ffff: "DEC DE from 0xFFFF with F=0xF0" {
+        regs { de: 0xFFFF, f: 0xF0 }
+        run [0x1B]
+        expect { de: 0xFFFE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_de_ffff_flags_clear: "INC DE from 0xFFFF with F=0x00" {
+        regs { de: 0xFFFF, f: 0x00 }
+        run [0x13]
+        expect { de: 0x0000, f: 0x00, cycles: 8 }
+    }
+
+    dec_de_ffff_flags_clear: "DEC DE from 0xFFFF with F=0x00" {
+        regs { de: 0xFFFF, f: 0x00 }
+        run [0x1B]
+        expect { de: 0xFFFE, f: 0x00, cycles: 8 }
+    }
+
+    inc_de_00ff: "INC DE from 0x00FF with F=0xF0" {
+        regs { de: 0x00FF, f: 0xF0 }
+        run [0x13]
+        expect { de: 0x0100, f: 0xF0, cycles: 8 }
+    }
+
+    dec_de_00ff: "DEC DE from 0x00FF with F=0xF0" {
+        regs { de: 0x00FF, f: 0xF0 }
+        run [0x1B]
+        expect { de: 0x00FE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_de_00ff_flags_clear: "INC DE from 0x00FF with F=0x00" {
+        regs { de: 0x00FF, f: 0x00 }
+        run [0x13]
+        expect { de: 0x0100, f: 0x00, cycles: 8 }
+    }
+
+    dec_de_00ff_flags_clear: "DEC DE from 0x00FF with F=0x00" {
+        regs { de: 0x00FF, f: 0x00 }
+        run [0x1B]
+        expect { de: 0x00FE, f: 0x00, cycles: 8 }
+    }
+
+    inc_hl_0000: "INC HL from 0x0000 with F=0xF0" {
+        regs { hl: 0x0000, f: 0xF0 }
+        run [0x23]
+        expect { hl: 0x0001, f: 0xF0, cycles: 8 }
+    }
+
+    dec_hl_0000: "DEC HL from 0x0000 with F=0xF0" {
+        regs { hl: 0x0000, f: 0xF0 }
+        run [0x2B]
+        expect { hl: 0xFFFF, f: 0xF0, cycles: 8 }
+    }
+
+    inc_hl_0000_flags_clear: "INC HL from 0x0000 with F=0x00" {
+        regs { hl: 0x0000, f: 0x00 }
+        run [0x23]
+        expect { hl: 0x0001, f: 0x00, cycles: 8 }
+    }
+
+    dec_hl_0000_flags_clear: "DEC HL from 0x0000 with F=0x00" {
+        regs { hl: 0x0000, f: 0x00 }
+        run [0x2B]
+        expect { hl: 0xFFFF, f: 0x00, cycles: 8 }
+    }
+
+    inc_hl_ffff: "INC HL from 0xFFFF with F=0xF0" {
+        regs { hl: 0xFFFF, f: 0xF0 }
+        run [0x23]
+        expect { hl: 0x0000, f: 0xF0, cycles: 8 }
+    }
+
+    dec_hl_ffff: "DEC HL from 0xFFFF with F=0xF0" {
+        regs { hl: 0xFFFF, f: 0xF0 }
+        run [0x2B]
+        expect { hl: 0xFFFE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_hl_ffff_flags_clear: "INC HL from 0xFFFF with F=0x00" {
+        regs { hl: 0xFFFF, f: 0x00 }
+        run [0x23]
+        expect { hl: 0x0000, f: 0x00, cycles: 8 }
+    }
+
+    dec_hl_ffff_flags_clear: "DEC HL from 0xFFFF with F=0x00" {
+        regs { hl: 0xFFFF, f: 0x00 }
+        run [0x2B]
+        expect { hl: 0xFFFE, f: 0x00, cycles: 8 }
+    }
+
+    inc_hl_00ff: "INC HL from 0x00FF with F=0xF0" {
+        regs { hl: 0x00FF, f: 0xF0 }
+        run [0x23]
+        expect { hl: 0x0100, f: 0xF0, cycles: 8 }
+    }
+
+    dec_hl_00ff: "DEC HL from 0x00FF with F=0xF0" {
+        regs { hl: 0x00FF, f: 0xF0 }
+        run [0x2B]
+        expect { hl: 0x00FE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_hl_00ff_flags_clear: "INC HL from 0x00FF with F=0x00" {
+        regs { hl: 0x00FF, f: 0x00 }
+        run [0x23]
+        expect { hl: 0x0100, f: 0x00, cycles: 8 }
+    }
+
+    dec_hl_00ff_flags_clear: "DEC HL from 0x00FF with F=0x00" {
+        regs { hl: 0x00FF, f: 0x00 }
+        run [0x2B]
+        expect { hl: 0x00FE, f: 0x00, cycles: 8 }
+    }
+
+    inc_sp_0000: "INC SP from 0x0000 with F=0xF0" {
+        regs { sp: 0x0000, f: 0xF0 }
+        run [0x33]
+        expect { sp: 0x0001, f: 0xF0, cycles: 8 }
+    }
+
+    dec_sp_0000: "DEC SP from 0x0000 with F=0xF0" {
+        regs { sp: 0x0000, f: 0xF0 }
+        run [0x3B]
+        expect { sp: 0xFFFF, f: 0xF0, cycles: 8 }
+    }
+
+    inc_sp_0000_flags_clear: "INC SP from 0x0000 with F=0x00" {
+        regs { sp: 0x0000, f: 0x00 }
+        run [0x33]
+        expect { sp: 0x0001, f: 0x00, cycles: 8 }
+    }
+
+    dec_sp_0000_flags_clear: "DEC SP from 0x0000 with F=0x00" {
+        regs { sp: 0x0000, f: 0x00 }
+        run [0x3B]
+        expect { sp: 0xFFFF, f: 0x00, cycles: 8 }
+    }
+
+    inc_sp_ffff: "INC SP from 0xFFFF with F=0xF0" {
+        regs { sp: 0xFFFF, f: 0xF0 }
+        run [0x33]
+        expect { sp: 0x0000, f: 0xF0, cycles: 8 }
+    }
+
+    dec_sp_ffff: "DEC SP from 0xFFFF with F=0xF0" {
+        regs { sp: 0xFFFF, f: 0xF0 }
+        run [0x3B]
+        expect { sp: 0xFFFE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_sp_ffff_flags_clear: "INC SP from 0xFFFF with F=0x00" {
+        regs { sp: 0xFFFF, f: 0x00 }
+        run [0x33]
+        expect { sp: 0x0000, f: 0x00, cycles: 8 }
+    }
+
+    dec_sp_ffff_flags_clear: "DEC SP from 0xFFFF with F=0x00" {
+        regs { sp: 0xFFFF, f: 0x00 }
+        run [0x3B]
+        expect { sp: 0xFFFE, f: 0x00, cycles: 8 }
+    }
+
+    inc_sp_00ff: "INC SP from 0x00FF with F=0xF0" {
+        regs { sp: 0x00FF, f: 0xF0 }
+        run [0x33]
+        expect { sp: 0x0100, f: 0xF0, cycles: 8 }
+    }
+
+    dec_sp_00ff: "DEC SP from 0x00FF with F=0xF0" {
+        regs { sp: 0x00FF, f: 0xF0 }
+        run [0x3B]
+        expect { sp: 0x00FE, f: 0xF0, cycles: 8 }
+    }
+
+    inc_sp_00ff_flags_clear: "INC SP from 0x00FF with F=0x00" {
+        regs { sp: 0x00FF, f: 0x00 }
+        run [0x33]
+        expect { sp: 0x0100, f: 0x00, cycles: 8 }
+    }
+
+    dec_sp_00ff_flags_clear: "DEC SP from 0x00FF with F=0x00" {
+        regs { sp: 0x00FF, f: 0x00 }
+        run [0x3B]
+        expect { sp: 0x00FE, f: 0x00, cycles: 8 }
+    }
+
+    add_hl_bc_0fff_0001: "ADD HL,BC with HL=0x0FFF BC=0x0001" {
+        regs { hl: 0x0FFF, zf: false, nf: true, bc: 0x0001 }
+        run [0x09]
+        expect { hl: 0x1000, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_bc_0fff_0001_z: "ADD HL,BC with HL=0x0FFF BC=0x0001 keeps Z" {
+        regs { hl: 0x0FFF, zf: true, nf: true, bc: 0x0001 }
+        run [0x09]
+        expect { hl: 0x1000, zf: true, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_bc_ffff_0001: "ADD HL,BC with HL=0xFFFF BC=0x0001" {
+        regs { hl: 0xFFFF, zf: false, nf: true, bc: 0x0001 }
+        run [0x09]
+        expect { hl: 0x0000, zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_bc_ffff_0001_z: "ADD HL,BC with HL=0xFFFF BC=0x0001 keeps Z" {
+        regs { hl: 0xFFFF, zf: true, nf: true, bc: 0x0001 }
+        run [0x09]
+        expect { hl: 0x0000, zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_bc_1234_1111: "ADD HL,BC with HL=0x1234 BC=0x1111" {
+        regs { hl: 0x1234, zf: false, nf: true, bc: 0x1111 }
+        run [0x09]
+        expect { hl: 0x2345, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_bc_1234_1111_z: "ADD HL,BC with HL=0x1234 BC=0x1111 keeps Z" {
+        regs { hl: 0x1234, zf: true, nf: true, bc: 0x1111 }
+        run [0x09]
+        expect { hl: 0x2345, zf: true, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_bc_8000_8000: "ADD HL,BC with HL=0x8000 BC=0x8000" {
+        regs { hl: 0x8000, zf: false, nf: true, bc: 0x8000 }
+        run [0x09]
+        expect { hl: 0x0000, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_hl_bc_8000_8000_z: "ADD HL,BC with HL=0x8000 BC=0x8000 keeps Z" {
+        regs { hl: 0x8000, zf: true, nf: true, bc: 0x8000 }
+        run [0x09]
+        expect { hl: 0x0000, zf: true, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_hl_de_0fff_0001: "ADD HL,DE with HL=0x0FFF DE=0x0001" {
+        regs { hl: 0x0FFF, zf: false, nf: true, de: 0x0001 }
+        run [0x19]
+        expect { hl: 0x1000, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_de_0fff_0001_z: "ADD HL,DE with HL=0x0FFF DE=0x0001 keeps Z" {
+        regs { hl: 0x0FFF, zf: true, nf: true, de: 0x0001 }
+        run [0x19]
+        expect { hl: 0x1000, zf: true, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_de_ffff_0001: "ADD HL,DE with HL=0xFFFF DE=0x0001" {
+        regs { hl: 0xFFFF, zf: false, nf: true, de: 0x0001 }
+        run [0x19]
+        expect { hl: 0x0000, zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_de_ffff_0001_z: "ADD HL,DE with HL=0xFFFF DE=0x0001 keeps Z" {
+        regs { hl: 0xFFFF, zf: true, nf: true, de: 0x0001 }
+        run [0x19]
+        expect { hl: 0x0000, zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_de_1234_1111: "ADD HL,DE with HL=0x1234 DE=0x1111" {
+        regs { hl: 0x1234, zf: false, nf: true, de: 0x1111 }
+        run [0x19]
+        expect { hl: 0x2345, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_de_1234_1111_z: "ADD HL,DE with HL=0x1234 DE=0x1111 keeps Z" {
+        regs { hl: 0x1234, zf: true, nf: true, de: 0x1111 }
+        run [0x19]
+        expect { hl: 0x2345, zf: true, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_de_8000_8000: "ADD HL,DE with HL=0x8000 DE=0x8000" {
+        regs { hl: 0x8000, zf: false, nf: true, de: 0x8000 }
+        run [0x19]
+        expect { hl: 0x0000, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_hl_de_8000_8000_z: "ADD HL,DE with HL=0x8000 DE=0x8000 keeps Z" {
+        regs { hl: 0x8000, zf: true, nf: true, de: 0x8000 }
+        run [0x19]
+        expect { hl: 0x0000, zf: true, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_hl_hl_0fff_0fff: "ADD HL,HL with HL=0x0FFF HL=0x0FFF" {
+        regs { hl: 0x0FFF, zf: false, nf: true }
+        run [0x29]
+        expect { hl: 0x1FFE, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_hl_ffff_ffff: "ADD HL,HL with HL=0xFFFF HL=0xFFFF" {
+        regs { hl: 0xFFFF, zf: false, nf: true }
+        run [0x29]
+        expect { hl: 0xFFFE, zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_hl_1234_1234: "ADD HL,HL with HL=0x1234 HL=0x1234" {
+        regs { hl: 0x1234, zf: false, nf: true }
+        run [0x29]
+        expect { hl: 0x2468, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_hl_8000_8000: "ADD HL,HL with HL=0x8000 HL=0x8000" {
+        regs { hl: 0x8000, zf: false, nf: true }
+        run [0x29]
+        expect { hl: 0x0000, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_hl_sp_0fff_0001: "ADD HL,SP with HL=0x0FFF SP=0x0001" {
+        regs { hl: 0x0FFF, zf: false, nf: true, sp: 0x0001 }
+        run [0x39]
+        expect { hl: 0x1000, zf: false, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_sp_0fff_0001_z: "ADD HL,SP with HL=0x0FFF SP=0x0001 keeps Z" {
+        regs { hl: 0x0FFF, zf: true, nf: true, sp: 0x0001 }
+        run [0x39]
+        expect { hl: 0x1000, zf: true, nf: false, hf: true, cf: false, cycles: 8 }
+    }
+
+    add_hl_sp_ffff_0001: "ADD HL,SP with HL=0xFFFF SP=0x0001" {
+        regs { hl: 0xFFFF, zf: false, nf: true, sp: 0x0001 }
+        run [0x39]
+        expect { hl: 0x0000, zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_sp_ffff_0001_z: "ADD HL,SP with HL=0xFFFF SP=0x0001 keeps Z" {
+        regs { hl: 0xFFFF, zf: true, nf: true, sp: 0x0001 }
+        run [0x39]
+        expect { hl: 0x0000, zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    add_hl_sp_1234_1111: "ADD HL,SP with HL=0x1234 SP=0x1111" {
+        regs { hl: 0x1234, zf: false, nf: true, sp: 0x1111 }
+        run [0x39]
+        expect { hl: 0x2345, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_sp_1234_1111_z: "ADD HL,SP with HL=0x1234 SP=0x1111 keeps Z" {
+        regs { hl: 0x1234, zf: true, nf: true, sp: 0x1111 }
+        run [0x39]
+        expect { hl: 0x2345, zf: true, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    add_hl_sp_8000_8000: "ADD HL,SP with HL=0x8000 SP=0x8000" {
+        regs { hl: 0x8000, zf: false, nf: true, sp: 0x8000 }
+        run [0x39]
+        expect { hl: 0x0000, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_hl_sp_8000_8000_z: "ADD HL,SP with HL=0x8000 SP=0x8000 keeps Z" {
+        regs { hl: 0x8000, zf: true, nf: true, sp: 0x8000 }
+        run [0x39]
+        expect { hl: 0x0000, zf: true, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    add_sp_e8_fff8_08: "ADD SP,e8 with SP=0xFFF8 e8=0x08" {
+        regs { sp: 0xFFF8, zf: true, nf: true }
+        run [0xE8, 0x08]
+        expect { sp: 0x0000, zf: false, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 16 }
+    }
+
+    add_sp_e8_000f_01: "ADD SP,e8 with SP=0x000F e8=0x01" {
+        regs { sp: 0x000F, zf: true, nf: true }
+        run [0xE8, 0x01]
+        expect { sp: 0x0010, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 16 }
+    }
+
+    add_sp_e8_00ff_01: "ADD SP,e8 with SP=0x00FF e8=0x01" {
+        regs { sp: 0x00FF, zf: true, nf: true }
+        run [0xE8, 0x01]
+        expect { sp: 0x0100, zf: false, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 16 }
+    }
+
+    add_sp_e8_d000_ff: "ADD SP,e8 with SP=0xD000 e8=0xFF" {
+        regs { sp: 0xD000, zf: true, nf: true }
+        run [0xE8, 0xFF]
+        expect { sp: 0xCFFF, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 16 }
+    }
+
+    add_sp_e8_d000_80: "ADD SP,e8 with SP=0xD000 e8=0x80" {
+        regs { sp: 0xD000, zf: true, nf: true }
+        run [0xE8, 0x80]
+        expect { sp: 0xCF80, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 16 }
+    }
+
+    daa_0a: "DAA with A=0x0A" {
+        regs { a: 0x0A, nf: false, hf: false, cf: false }
+        run [0x27]
+        expect { a: 0x10, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    daa_9a: "DAA with A=0x9A" {
+        regs { a: 0x9A, nf: false, hf: false, cf: false }
+        run [0x27]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    daa_15_h: "DAA with A=0x15 H" {
+        regs { a: 0x15, nf: false, hf: true, cf: false }
+        run [0x27]
+        expect { a: 0x1B, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    daa_00_c: "DAA with A=0x00 C" {
+        regs { a: 0x00, nf: false, hf: false, cf: true }
+        run [0x27]
+        expect { a: 0x60, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    daa_45: "DAA with A=0x45" {
+        regs { a: 0x45, nf: false, hf: false, cf: false }
+        run [0x27]
+        expect { a: 0x45, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    daa_0f_n_h: "DAA with A=0x0F N H" {
+        regs { a: 0x0F, nf: true, hf: true, cf: false }
+        run [0x27]
+        expect { a: 0x09, zf: false, nf: true, hf: false, cf: false, cycles: 4 }
+    }
+
+    daa_a0_n_c: "DAA with A=0xA0 N C" {
+        regs { a: 0xA0, nf: true, hf: false, cf: true }
+        run [0x27]
+        expect { a: 0x40, zf: false, nf: true, hf: false, cf: true, cycles: 4 }
+    }
+
+    daa_99: "DAA with A=0x99" {
+        regs { a: 0x99, nf: false, hf: false, cf: false }
+        run [0x27]
+        expect { a: 0x99, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    daa_00: "DAA with A=0x00" {
+        regs { a: 0x00, nf: false, hf: false, cf: false }
+        run [0x27]
+        expect { a: 0x00, zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    cpl_00: "CPL with A=0x00" {
+        regs { a: 0x00, zf: true, cf: true }
+        run [0x2F]
+        expect { a: 0xFF, zf: true, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    cpl_35: "CPL with A=0x35" {
+        regs { a: 0x35, zf: true, cf: true }
+        run [0x2F]
+        expect { a: 0xCA, zf: true, nf: true, hf: true, cf: true, cycles: 4 }
+    }
+
+    scf: "SCF with C reset" {
+        regs { zf: true, nf: true, hf: true, cf: false }
+        run [0x37]
+        expect { zf: true, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    ccf: "CCF with C" {
+        regs { zf: true, nf: true, hf: true, cf: false }
+        run [0x3F]
+        expect { zf: true, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    scf_set: "SCF with C" {
+        regs { zf: true, nf: true, hf: true, cf: true }
+        run [0x37]
+        expect { zf: true, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    ccf_set: "CCF with C set" {
+        regs { zf: true, nf: true, hf: true, cf: true }
+        run [0x3F]
+        expect { zf: true, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+}
@@ -0,0 +1,1220 @@
+//! Rotates, shifts and single bit operations
+use crate::common::flat::cpu_test;
+
+cpu_test! {
+    rlc_b_80: "RLC B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x00]
+        expect { b: 0x01, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rlc_b_01: "RLC B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x00]
+        expect { b: 0x02, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rlc_b_00: "RLC B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x00]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rlc_b_a5: "RLC B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x00]
+        expect { b: 0x4B, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rlc_c: "RLC C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x01]
+        expect { c: 0x03, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rlc_d: "RLC D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x02]
+        expect { d: 0x03, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rlc_e: "RLC E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x03]
+        expect { e: 0x03, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rlc_h: "RLC H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x04]
+        expect { h: 0x03, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rlc_l: "RLC L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x05]
+        expect { l: 0x03, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rlc_hl: "RLC [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x06]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0x03 }
+    }
+
+    rlc_a: "RLC A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x07]
+        expect { a: 0x03, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rrc_b_80: "RRC B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x08]
+        expect { b: 0x40, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rrc_b_01: "RRC B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x08]
+        expect { b: 0x80, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rrc_b_00: "RRC B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x08]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rrc_b_a5: "RRC B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x08]
+        expect { b: 0xD2, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rrc_c: "RRC C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x09]
+        expect { c: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rrc_d: "RRC D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x0A]
+        expect { d: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rrc_e: "RRC E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x0B]
+        expect { e: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rrc_h: "RRC H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x0C]
+        expect { h: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rrc_l: "RRC L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x0D]
+        expect { l: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rrc_hl: "RRC [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x0E]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0xC0 }
+    }
+
+    rrc_a: "RRC A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x0F]
+        expect { a: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rl_b_80: "RL B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_80_carry: "RL B with B=0x80 carry" {
+        regs { b: 0x80, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x01, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_01: "RL B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x02, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_01_carry: "RL B with B=0x01 carry" {
+        regs { b: 0x01, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x03, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_00: "RL B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_00_carry: "RL B with B=0x00 carry" {
+        regs { b: 0x00, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x01, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_a5: "RL B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x4A, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_b_a5_carry: "RL B with B=0xA5 carry" {
+        regs { b: 0xA5, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x10]
+        expect { b: 0x4B, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rl_c: "RL C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x11]
+        expect { c: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rl_d: "RL D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x12]
+        expect { d: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rl_e: "RL E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x13]
+        expect { e: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rl_h: "RL H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x14]
+        expect { h: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rl_l: "RL L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x15]
+        expect { l: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rl_hl: "RL [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x16]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0x02 }
+    }
+
+    rl_a: "RL A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x17]
+        expect { a: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rr_b_80: "RR B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0x40, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_80_carry: "RR B with B=0x80 carry" {
+        regs { b: 0x80, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0xC0, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_01: "RR B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_01_carry: "RR B with B=0x01 carry" {
+        regs { b: 0x01, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0x80, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_00: "RR B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_00_carry: "RR B with B=0x00 carry" {
+        regs { b: 0x00, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0x80, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_a5: "RR B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0x52, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_b_a5_carry: "RR B with B=0xA5 carry" {
+        regs { b: 0xA5, cf: true, zf: true, nf: true, hf: true }
+        run [0xCB, 0x18]
+        expect { b: 0xD2, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    rr_c: "RR C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x19]
+        expect { c: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rr_d: "RR D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x1A]
+        expect { d: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rr_e: "RR E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x1B]
+        expect { e: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rr_h: "RR H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x1C]
+        expect { h: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rr_l: "RR L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x1D]
+        expect { l: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    rr_hl: "RR [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x1E]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0x40 }
+    }
+
+    rr_a: "RR A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x1F]
+        expect { a: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sla_b_80: "SLA B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x20]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sla_b_01: "SLA B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x20]
+        expect { b: 0x02, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sla_b_00: "SLA B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x20]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sla_b_a5: "SLA B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x20]
+        expect { b: 0x4A, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sla_c: "SLA C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x21]
+        expect { c: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sla_d: "SLA D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x22]
+        expect { d: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sla_e: "SLA E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x23]
+        expect { e: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sla_h: "SLA H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x24]
+        expect { h: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sla_l: "SLA L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x25]
+        expect { l: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sla_hl: "SLA [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x26]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0x02 }
+    }
+
+    sla_a: "SLA A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x27]
+        expect { a: 0x02, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sra_b_80: "SRA B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x28]
+        expect { b: 0xC0, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sra_b_01: "SRA B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x28]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sra_b_00: "SRA B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x28]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    sra_b_a5: "SRA B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x28]
+        expect { b: 0xD2, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    sra_c: "SRA C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x29]
+        expect { c: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sra_d: "SRA D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x2A]
+        expect { d: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sra_e: "SRA E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x2B]
+        expect { e: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sra_h: "SRA H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x2C]
+        expect { h: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sra_l: "SRA L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x2D]
+        expect { l: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    sra_hl: "SRA [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x2E]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0xC0 }
+    }
+
+    sra_a: "SRA A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x2F]
+        expect { a: 0xC0, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    swap_b_80: "SWAP B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x30]
+        expect { b: 0x08, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    swap_b_01: "SWAP B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x30]
+        expect { b: 0x10, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    swap_b_00: "SWAP B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x30]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    swap_b_a5: "SWAP B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x30]
+        expect { b: 0x5A, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    swap_c: "SWAP C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x31]
+        expect { c: 0x18, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    swap_d: "SWAP D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x32]
+        expect { d: 0x18, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    swap_e: "SWAP E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x33]
+        expect { e: 0x18, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    swap_h: "SWAP H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x34]
+        expect { h: 0x18, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    swap_l: "SWAP L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x35]
+        expect { l: 0x18, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    swap_hl: "SWAP [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x36]
+        expect { zf: false, nf: false, hf: false, cf: false, cycles: 16 }
+        expect_mem { 0xC000: 0x18 }
+    }
+
+    swap_a: "SWAP A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x37]
+        expect { a: 0x18, zf: false, nf: false, hf: false, cf: false, cycles: 8 }
+    }
+
+    srl_b_80: "SRL B with B=0x80" {
+        regs { b: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x38]
+        expect { b: 0x40, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    srl_b_01: "SRL B with B=0x01" {
+        regs { b: 0x01, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x38]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    srl_b_00: "SRL B with B=0x00" {
+        regs { b: 0x00, cf: false, zf: false, nf: true, hf: true }
+        run [0xCB, 0x38]
+        expect { b: 0x00, zf: true, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 8 }
+    }
+
+    srl_b_a5: "SRL B with B=0xA5" {
+        regs { b: 0xA5, cf: false, zf: true, nf: true, hf: true }
+        run [0xCB, 0x38]
+        expect { b: 0x52, zf: false, nf: false, hf: false, cf: true, pc: 0x0102, cycles: 8 }
+    }
+
+    srl_c: "SRL C" {
+        regs { c: 0x81 }
+        run [0xCB, 0x39]
+        expect { c: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    srl_d: "SRL D" {
+        regs { d: 0x81 }
+        run [0xCB, 0x3A]
+        expect { d: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    srl_e: "SRL E" {
+        regs { e: 0x81 }
+        run [0xCB, 0x3B]
+        expect { e: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    srl_h: "SRL H" {
+        regs { h: 0x81 }
+        run [0xCB, 0x3C]
+        expect { h: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    srl_l: "SRL L" {
+        regs { l: 0x81 }
+        run [0xCB, 0x3D]
+        expect { l: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    srl_hl: "SRL [HL]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x81 }
+        run [0xCB, 0x3E]
+        expect { zf: false, nf: false, hf: false, cf: true, cycles: 16 }
+        expect_mem { 0xC000: 0x40 }
+    }
+
+    srl_a: "SRL A" {
+        regs { a: 0x81 }
+        run [0xCB, 0x3F]
+        expect { a: 0x40, zf: false, nf: false, hf: false, cf: true, cycles: 8 }
+    }
+
+    bit_0_b_set: "BIT 0,B with the bit set" {
+        regs { cf: true, b: 0x01 }
+        run [0xCB, 0x40]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_0_b_reset: "BIT 0,B with the bit reset" {
+        regs { cf: true, b: 0xFE }
+        run [0xCB, 0x40]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_0_hl_set: "BIT 0,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x01 }
+        run [0xCB, 0x46]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_0_hl_reset: "BIT 0,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xFE }
+        run [0xCB, 0x46]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_0_a_set: "BIT 0,A with the bit set" {
+        regs { cf: true, a: 0x01 }
+        run [0xCB, 0x47]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_0_a_reset: "BIT 0,A with the bit reset" {
+        regs { cf: true, a: 0xFE }
+        run [0xCB, 0x47]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_0_c: "RES 0,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0x81]
+        expect { f: 0xF0, c: 0xFE, cycles: 8 }
+    }
+
+    set_0_c: "SET 0,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xC1]
+        expect { f: 0xF0, c: 0x01, cycles: 8 }
+    }
+
+    res_0_hl: "RES 0,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0x86]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xFE }
+    }
+
+    set_0_hl: "SET 0,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xC6]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x01 }
+    }
+
+    bit_1_b_set: "BIT 1,B with the bit set" {
+        regs { cf: true, b: 0x02 }
+        run [0xCB, 0x48]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_1_b_reset: "BIT 1,B with the bit reset" {
+        regs { cf: true, b: 0xFD }
+        run [0xCB, 0x48]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_1_hl_set: "BIT 1,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x02 }
+        run [0xCB, 0x4E]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_1_hl_reset: "BIT 1,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xFD }
+        run [0xCB, 0x4E]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_1_a_set: "BIT 1,A with the bit set" {
+        regs { cf: true, a: 0x02 }
+        run [0xCB, 0x4F]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_1_a_reset: "BIT 1,A with the bit reset" {
+        regs { cf: true, a: 0xFD }
+        run [0xCB, 0x4F]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_1_c: "RES 1,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0x89]
+        expect { f: 0xF0, c: 0xFD, cycles: 8 }
+    }
+
+    set_1_c: "SET 1,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xC9]
+        expect { f: 0xF0, c: 0x02, cycles: 8 }
+    }
+
+    res_1_hl: "RES 1,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0x8E]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xFD }
+    }
+
+    set_1_hl: "SET 1,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xCE]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x02 }
+    }
+
+    bit_2_b_set: "BIT 2,B with the bit set" {
+        regs { cf: true, b: 0x04 }
+        run [0xCB, 0x50]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_2_b_reset: "BIT 2,B with the bit reset" {
+        regs { cf: true, b: 0xFB }
+        run [0xCB, 0x50]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_2_hl_set: "BIT 2,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x04 }
+        run [0xCB, 0x56]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_2_hl_reset: "BIT 2,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xFB }
+        run [0xCB, 0x56]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_2_a_set: "BIT 2,A with the bit set" {
+        regs { cf: true, a: 0x04 }
+        run [0xCB, 0x57]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_2_a_reset: "BIT 2,A with the bit reset" {
+        regs { cf: true, a: 0xFB }
+        run [0xCB, 0x57]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_2_c: "RES 2,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0x91]
+        expect { f: 0xF0, c: 0xFB, cycles: 8 }
+    }
+
+    set_2_c: "SET 2,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xD1]
+        expect { f: 0xF0, c: 0x04, cycles: 8 }
+    }
+
+    res_2_hl: "RES 2,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0x96]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xFB }
+    }
+
+    set_2_hl: "SET 2,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xD6]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x04 }
+    }
+
+    bit_3_b_set: "BIT 3,B with the bit set" {
+        regs { cf: true, b: 0x08 }
+        run [0xCB, 0x58]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_3_b_reset: "BIT 3,B with the bit reset" {
+        regs { cf: true, b: 0xF7 }
+        run [0xCB, 0x58]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_3_hl_set: "BIT 3,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x08 }
+        run [0xCB, 0x5E]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_3_hl_reset: "BIT 3,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xF7 }
+        run [0xCB, 0x5E]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_3_a_set: "BIT 3,A with the bit set" {
+        regs { cf: true, a: 0x08 }
+        run [0xCB, 0x5F]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_3_a_reset: "BIT 3,A with the bit reset" {
+        regs { cf: true, a: 0xF7 }
+        run [0xCB, 0x5F]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_3_c: "RES 3,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0x99]
+        expect { f: 0xF0, c: 0xF7, cycles: 8 }
+    }
+
+    set_3_c: "SET 3,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xD9]
+        expect { f: 0xF0, c: 0x08, cycles: 8 }
+    }
+
+    res_3_hl: "RES 3,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0x9E]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xF7 }
+    }
+
+    set_3_hl: "SET 3,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xDE]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x08 }
+    }
+
+    bit_4_b_set: "BIT 4,B with the bit set" {
+        regs { cf: true, b: 0x10 }
+        run [0xCB, 0x60]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_4_b_reset: "BIT 4,B with the bit reset" {
+        regs { cf: true, b: 0xEF }
+        run [0xCB, 0x60]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_4_hl_set: "BIT 4,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x10 }
+        run [0xCB, 0x66]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_4_hl_reset: "BIT 4,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xEF }
+        run [0xCB, 0x66]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_4_a_set: "BIT 4,A with the bit set" {
+        regs { cf: true, a: 0x10 }
+        run [0xCB, 0x67]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_4_a_reset: "BIT 4,A with the bit reset" {
+        regs { cf: true, a: 0xEF }
+        run [0xCB, 0x67]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_4_c: "RES 4,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0xA1]
+        expect { f: 0xF0, c: 0xEF, cycles: 8 }
+    }
+
+    set_4_c: "SET 4,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xE1]
+        expect { f: 0xF0, c: 0x10, cycles: 8 }
+    }
+
+    res_4_hl: "RES 4,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0xA6]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xEF }
+    }
+
+    set_4_hl: "SET 4,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xE6]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x10 }
+    }
+
+    bit_5_b_set: "BIT 5,B with the bit set" {
+        regs { cf: true, b: 0x20 }
+        run [0xCB, 0x68]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_5_b_reset: "BIT 5,B with the bit reset" {
+        regs { cf: true, b: 0xDF }
+        run [0xCB, 0x68]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_5_hl_set: "BIT 5,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x20 }
+        run [0xCB, 0x6E]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_5_hl_reset: "BIT 5,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xDF }
+        run [0xCB, 0x6E]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_5_a_set: "BIT 5,A with the bit set" {
+        regs { cf: true, a: 0x20 }
+        run [0xCB, 0x6F]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_5_a_reset: "BIT 5,A with the bit reset" {
+        regs { cf: true, a: 0xDF }
+        run [0xCB, 0x6F]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_5_c: "RES 5,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0xA9]
+        expect { f: 0xF0, c: 0xDF, cycles: 8 }
+    }
+
+    set_5_c: "SET 5,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xE9]
+        expect { f: 0xF0, c: 0x20, cycles: 8 }
+    }
+
+    res_5_hl: "RES 5,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0xAE]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xDF }
+    }
+
+    set_5_hl: "SET 5,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xEE]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x20 }
+    }
+
+    bit_6_b_set: "BIT 6,B with the bit set" {
+        regs { cf: true, b: 0x40 }
+        run [0xCB, 0x70]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_6_b_reset: "BIT 6,B with the bit reset" {
+        regs { cf: true, b: 0xBF }
+        run [0xCB, 0x70]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_6_hl_set: "BIT 6,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x40 }
+        run [0xCB, 0x76]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_6_hl_reset: "BIT 6,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0xBF }
+        run [0xCB, 0x76]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_6_a_set: "BIT 6,A with the bit set" {
+        regs { cf: true, a: 0x40 }
+        run [0xCB, 0x77]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_6_a_reset: "BIT 6,A with the bit reset" {
+        regs { cf: true, a: 0xBF }
+        run [0xCB, 0x77]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_6_c: "RES 6,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0xB1]
+        expect { f: 0xF0, c: 0xBF, cycles: 8 }
+    }
+
+    set_6_c: "SET 6,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xF1]
+        expect { f: 0xF0, c: 0x40, cycles: 8 }
+    }
+
+    res_6_hl: "RES 6,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0xB6]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0xBF }
+    }
+
+    set_6_hl: "SET 6,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xF6]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x40 }
+    }
+
+    bit_7_b_set: "BIT 7,B with the bit set" {
+        regs { cf: true, b: 0x80 }
+        run [0xCB, 0x78]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_7_b_reset: "BIT 7,B with the bit reset" {
+        regs { cf: true, b: 0x7F }
+        run [0xCB, 0x78]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_7_hl_set: "BIT 7,[HL] with the bit set" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x80 }
+        run [0xCB, 0x7E]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_7_hl_reset: "BIT 7,[HL] with the bit reset" {
+        regs { cf: true, hl: 0xC000 }
+        mem { 0xC000: 0x7F }
+        run [0xCB, 0x7E]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 12 }
+    }
+
+    bit_7_a_set: "BIT 7,A with the bit set" {
+        regs { cf: true, a: 0x80 }
+        run [0xCB, 0x7F]
+        expect { zf: false, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    bit_7_a_reset: "BIT 7,A with the bit reset" {
+        regs { cf: true, a: 0x7F }
+        run [0xCB, 0x7F]
+        expect { zf: true, nf: false, hf: true, cf: true, cycles: 8 }
+    }
+
+    res_7_c: "RES 7,C" {
+        regs { f: 0xF0, c: 0xFF }
+        run [0xCB, 0xB9]
+        expect { f: 0xF0, c: 0x7F, cycles: 8 }
+    }
+
+    set_7_c: "SET 7,C" {
+        regs { f: 0xF0, c: 0x00 }
+        run [0xCB, 0xF9]
+        expect { f: 0xF0, c: 0x80, cycles: 8 }
+    }
+
+    res_7_hl: "RES 7,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0xFF }
+        run [0xCB, 0xBE]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x7F }
+    }
+
+    set_7_hl: "SET 7,[HL]" {
+        regs { f: 0xF0, hl: 0xC000 }
+        mem { 0xC000: 0x00 }
+        run [0xCB, 0xFE]
+        expect { f: 0xF0, cycles: 16 }
+        expect_mem { 0xC000: 0x80 }
+    }
+
+    rlca_80: "RLCA with A=0x80" {
+        regs { a: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0x07]
+        expect { a: 0x01, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rlca_01_carry: "RLCA with A=0x01 carry" {
+        regs { a: 0x01, cf: true, zf: true, nf: true, hf: true }
+        run [0x07]
+        expect { a: 0x02, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rlca_00: "RLCA with A=0x00" {
+        regs { a: 0x00, cf: false, zf: true, nf: true, hf: true }
+        run [0x07]
+        expect { a: 0x00, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rlca_95_carry: "RLCA with A=0x95 carry" {
+        regs { a: 0x95, cf: true, zf: true, nf: true, hf: true }
+        run [0x07]
+        expect { a: 0x2B, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rrca_80: "RRCA with A=0x80" {
+        regs { a: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0x0F]
+        expect { a: 0x40, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rrca_01_carry: "RRCA with A=0x01 carry" {
+        regs { a: 0x01, cf: true, zf: true, nf: true, hf: true }
+        run [0x0F]
+        expect { a: 0x80, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rrca_00: "RRCA with A=0x00" {
+        regs { a: 0x00, cf: false, zf: true, nf: true, hf: true }
+        run [0x0F]
+        expect { a: 0x00, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rrca_95_carry: "RRCA with A=0x95 carry" {
+        regs { a: 0x95, cf: true, zf: true, nf: true, hf: true }
+        run [0x0F]
+        expect { a: 0xCA, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rla_80: "RLA with A=0x80" {
+        regs { a: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0x17]
+        expect { a: 0x00, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rla_01_carry: "RLA with A=0x01 carry" {
+        regs { a: 0x01, cf: true, zf: true, nf: true, hf: true }
+        run [0x17]
+        expect { a: 0x03, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rla_00: "RLA with A=0x00" {
+        regs { a: 0x00, cf: false, zf: true, nf: true, hf: true }
+        run [0x17]
+        expect { a: 0x00, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rla_95_carry: "RLA with A=0x95 carry" {
+        regs { a: 0x95, cf: true, zf: true, nf: true, hf: true }
+        run [0x17]
+        expect { a: 0x2B, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rra_80: "RRA with A=0x80" {
+        regs { a: 0x80, cf: false, zf: true, nf: true, hf: true }
+        run [0x1F]
+        expect { a: 0x40, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rra_01_carry: "RRA with A=0x01 carry" {
+        regs { a: 0x01, cf: true, zf: true, nf: true, hf: true }
+        run [0x1F]
+        expect { a: 0x80, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+
+    rra_00: "RRA with A=0x00" {
+        regs { a: 0x00, cf: false, zf: true, nf: true, hf: true }
+        run [0x1F]
+        expect { a: 0x00, zf: false, nf: false, hf: false, cf: false, cycles: 4 }
+    }
+
+    rra_95_carry: "RRA with A=0x95 carry" {
+        regs { a: 0x95, cf: true, zf: true, nf: true, hf: true }
+        run [0x1F]
+        expect { a: 0xCA, zf: false, nf: false, hf: false, cf: true, cycles: 4 }
+    }
+}
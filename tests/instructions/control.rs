@@ -0,0 +1,320 @@
+//! Jumps, calls, returns and CPU control
+use crate::common::flat::cpu_test;
+
+cpu_test! {
+    jp_a16: "JP a16" {
+        run [0xC3, 0x00, 0x20]
+        expect { pc: 0x2000, cycles: 16 }
+    }
+
+    jp_hl: "JP HL" {
+        regs { hl: 0x2000 }
+        run [0xE9]
+        expect { pc: 0x2000, cycles: 4 }
+    }
+
+    jr_forward: "JR e8 forward" {
+        run [0x18, 0x10]
+        expect { pc: 0x0112, cycles: 12 }
+    }
+
+    jr_backward: "JR e8 backward" {
+        run [0x18, 0xFE]
+        expect { pc: 0x0100, cycles: 12 }
+    }
+
+    call_a16: "CALL a16" {
+        regs { sp: 0xD000 }
+        run [0xCD, 0x00, 0x20]
+        expect { pc: 0x2000, sp: 0xCFFE, cycles: 24 }
+        expect_mem { 0xCFFE: 0x03, 0xCFFF: 0x01 }
+    }
+
+    ret: "RET" {
+        regs { sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xC9]
+        expect { pc: 0x2000, sp: 0xD000, cycles: 16 }
+    }
+
+    reti: "RETI" {
+        regs { sp: 0xCFFE, ime: false }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xD9]
+        expect { pc: 0x2000, sp: 0xD000, ime: true, cycles: 16 }
+    }
+
+    jp_nz_taken: "JP NZ taken" {
+        regs { zf: false }
+        run [0xC2, 0x00, 0x20]
+        expect { pc: 0x2000, cycles: 16 }
+    }
+
+    jr_nz_taken: "JR NZ taken" {
+        regs { zf: false }
+        run [0x20, 0x10]
+        expect { pc: 0x0112, cycles: 12 }
+    }
+
+    call_nz_taken: "CALL NZ taken" {
+        regs { zf: false, sp: 0xD000 }
+        run [0xC4, 0x00, 0x20]
+        expect { pc: 0x2000, sp: 0xCFFE, cycles: 24 }
+    }
+
+    ret_nz_taken: "RET NZ taken" {
+        regs { zf: false, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xC0]
+        expect { pc: 0x2000, sp: 0xD000, cycles: 20 }
+    }
+
+    jp_nz_not_taken: "JP NZ not taken" {
+        regs { zf: true }
+        run [0xC2, 0x00, 0x20]
+        expect { pc: 0x0103, cycles: 12 }
+    }
+
+    jr_nz_not_taken: "JR NZ not taken" {
+        regs { zf: true }
+        run [0x20, 0x10]
+        expect { pc: 0x0102, cycles: 8 }
+    }
+
+    call_nz_not_taken: "CALL NZ not taken" {
+        regs { zf: true, sp: 0xD000 }
+        run [0xC4, 0x00, 0x20]
+        expect { pc: 0x0103, sp: 0xD000, cycles: 12 }
+    }
+
+    ret_nz_not_taken: "RET NZ not taken" {
+        regs { zf: true, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xC0]
+        expect { pc: 0x0101, sp: 0xCFFE, cycles: 8 }
+    }
+
+    jp_z_taken: "JP Z taken" {
+        regs { zf: true }
+        run [0xCA, 0x00, 0x20]
+        expect { pc: 0x2000, cycles: 16 }
+    }
+
+    jr_z_taken: "JR Z taken" {
+        regs { zf: true }
+        run [0x28, 0x10]
+        expect { pc: 0x0112, cycles: 12 }
+    }
+
+    call_z_taken: "CALL Z taken" {
+        regs { zf: true, sp: 0xD000 }
+        run [0xCC, 0x00, 0x20]
+        expect { pc: 0x2000, sp: 0xCFFE, cycles: 24 }
+    }
+
+    ret_z_taken: "RET Z taken" {
+        regs { zf: true, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xC8]
+        expect { pc: 0x2000, sp: 0xD000, cycles: 20 }
+    }
+
+    jp_z_not_taken: "JP Z not taken" {
+        regs { zf: false }
+        run [0xCA, 0x00, 0x20]
+        expect { pc: 0x0103, cycles: 12 }
+    }
+
+    jr_z_not_taken: "JR Z not taken" {
+        regs { zf: false }
+        run [0x28, 0x10]
+        expect { pc: 0x0102, cycles: 8 }
+    }
+
+    call_z_not_taken: "CALL Z not taken" {
+        regs { zf: false, sp: 0xD000 }
+        run [0xCC, 0x00, 0x20]
+        expect { pc: 0x0103, sp: 0xD000, cycles: 12 }
+    }
+
+    ret_z_not_taken: "RET Z not taken" {
+        regs { zf: false, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xC8]
+        expect { pc: 0x0101, sp: 0xCFFE, cycles: 8 }
+    }
+
+    jp_nc_taken: "JP NC taken" {
+        regs { cf: false }
+        run [0xD2, 0x00, 0x20]
+        expect { pc: 0x2000, cycles: 16 }
+    }
+
+    jr_nc_taken: "JR NC taken" {
+        regs { cf: false }
+        run [0x30, 0x10]
+        expect { pc: 0x0112, cycles: 12 }
+    }
+
+    call_nc_taken: "CALL NC taken" {
+        regs { cf: false, sp: 0xD000 }
+        run [0xD4, 0x00, 0x20]
+        expect { pc: 0x2000, sp: 0xCFFE, cycles: 24 }
+    }
+
+    ret_nc_taken: "RET NC taken" {
+        regs { cf: false, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xD0]
+        expect { pc: 0x2000, sp: 0xD000, cycles: 20 }
+    }
+
+    jp_nc_not_taken: "JP NC not taken" {
+        regs { cf: true }
+        run [0xD2, 0x00, 0x20]
+        expect { pc: 0x0103, cycles: 12 }
+    }
+
+    jr_nc_not_taken: "JR NC not taken" {
+        regs { cf: true }
+        run [0x30, 0x10]
+        expect { pc: 0x0102, cycles: 8 }
+    }
+
+    call_nc_not_taken: "CALL NC not taken" {
+        regs { cf: true, sp: 0xD000 }
+        run [0xD4, 0x00, 0x20]
+        expect { pc: 0x0103, sp: 0xD000, cycles: 12 }
+    }
+
+    ret_nc_not_taken: "RET NC not taken" {
+        regs { cf: true, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xD0]
+        expect { pc: 0x0101, sp: 0xCFFE, cycles: 8 }
+    }
+
+    jp_c_taken: "JP C taken" {
+        regs { cf: true }
+        run [0xDA, 0x00, 0x20]
+        expect { pc: 0x2000, cycles: 16 }
+    }
+
+    jr_c_taken: "JR C taken" {
+        regs { cf: true }
+        run [0x38, 0x10]
+        expect { pc: 0x0112, cycles: 12 }
+    }
+
+    call_c_taken: "CALL C taken" {
+        regs { cf: true, sp: 0xD000 }
+        run [0xDC, 0x00, 0x20]
+        expect { pc: 0x2000, sp: 0xCFFE, cycles: 24 }
+    }
+
+    ret_c_taken: "RET C taken" {
+        regs { cf: true, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xD8]
+        expect { pc: 0x2000, sp: 0xD000, cycles: 20 }
+    }
+
+    jp_c_not_taken: "JP C not taken" {
+        regs { cf: false }
+        run [0xDA, 0x00, 0x20]
+        expect { pc: 0x0103, cycles: 12 }
+    }
+
+    jr_c_not_taken: "JR C not taken" {
+        regs { cf: false }
+        run [0x38, 0x10]
+        expect { pc: 0x0102, cycles: 8 }
+    }
+
+    call_c_not_taken: "CALL C not taken" {
+        regs { cf: false, sp: 0xD000 }
+        run [0xDC, 0x00, 0x20]
+        expect { pc: 0x0103, sp: 0xD000, cycles: 12 }
+    }
+
+    ret_c_not_taken: "RET C not taken" {
+        regs { cf: false, sp: 0xCFFE }
+        mem { 0xCFFE: 0x00, 0xCFFF: 0x20 }
+        run [0xD8]
+        expect { pc: 0x0101, sp: 0xCFFE, cycles: 8 }
+    }
+
+    rst_00: "RST 00h" {
+        regs { sp: 0xD000 }
+        run [0xC7]
+        expect { pc: 0x0000, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_08: "RST 08h" {
+        regs { sp: 0xD000 }
+        run [0xCF]
+        expect { pc: 0x0008, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_10: "RST 10h" {
+        regs { sp: 0xD000 }
+        run [0xD7]
+        expect { pc: 0x0010, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_18: "RST 18h" {
+        regs { sp: 0xD000 }
+        run [0xDF]
+        expect { pc: 0x0018, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_20: "RST 20h" {
+        regs { sp: 0xD000 }
+        run [0xE7]
+        expect { pc: 0x0020, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_28: "RST 28h" {
+        regs { sp: 0xD000 }
+        run [0xEF]
+        expect { pc: 0x0028, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_30: "RST 30h" {
+        regs { sp: 0xD000 }
+        run [0xF7]
+        expect { pc: 0x0030, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    rst_38: "RST 38h" {
+        regs { sp: 0xD000 }
+        run [0xFF]
+        expect { pc: 0x0038, sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x01, 0xCFFF: 0x01 }
+    }
+
+    nop: "NOP" {
+        regs { f: 0xF0 }
+        run [0x00]
+        expect { pc: 0x0101, f: 0xF0, cycles: 4 }
+    }
+
+    di: "DI" {
+        regs { ime: true }
+        run [0xF3]
+        expect { ime: false, cycles: 4 }
+    }
+
+    ei: "EI" {
+        regs { ime: false }
+        run [0xFB]
+        expect { ime: true, cycles: 4 }
+    }
+}
@@ -0,0 +1,492 @@
+//! Loads and stack operations
+use crate::common::flat::cpu_test;
+
+cpu_test! {
+    ld_b_b: "LD B,B" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x40]
+        expect { b: 0x11, cycles: 4 }
+    }
+
+    ld_b_c: "LD B,C" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x41]
+        expect { b: 0x22, cycles: 4 }
+    }
+
+    ld_b_d: "LD B,D" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x42]
+        expect { b: 0x33, cycles: 4 }
+    }
+
+    ld_b_e: "LD B,E" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x43]
+        expect { b: 0x44, cycles: 4 }
+    }
+
+    ld_b_h: "LD B,H" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x44]
+        expect { b: 0xC0, cycles: 4 }
+    }
+
+    ld_b_l: "LD B,L" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x45]
+        expect { b: 0x10, cycles: 4 }
+    }
+
+    ld_b_hl_ind: "LD B,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x46]
+        expect { b: 0x99, cycles: 8 }
+    }
+
+    ld_b_a: "LD B,A" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x47]
+        expect { b: 0x77, cycles: 4 }
+    }
+
+    ld_c_hl_ind: "LD C,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x4E]
+        expect { c: 0x99, cycles: 8 }
+    }
+
+    ld_d_hl_ind: "LD D,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x56]
+        expect { d: 0x99, cycles: 8 }
+    }
+
+    ld_e_hl_ind: "LD E,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x5E]
+        expect { e: 0x99, cycles: 8 }
+    }
+
+    ld_h_hl_ind: "LD H,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x66]
+        expect { h: 0x99, cycles: 8 }
+    }
+
+    ld_l_b: "LD L,B" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x68]
+        expect { l: 0x11, cycles: 4 }
+    }
+
+    ld_l_c: "LD L,C" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x69]
+        expect { l: 0x22, cycles: 4 }
+    }
+
+    ld_l_d: "LD L,D" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x6A]
+        expect { l: 0x33, cycles: 4 }
+    }
+
+    ld_l_e: "LD L,E" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x6B]
+        expect { l: 0x44, cycles: 4 }
+    }
+
+    ld_l_h: "LD L,H" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x6C]
+        expect { l: 0xC0, cycles: 4 }
+    }
+
+    ld_l_l: "LD L,L" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x6D]
+        expect { l: 0x10, cycles: 4 }
+    }
+
+    ld_l_hl_ind: "LD L,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x6E]
+        expect { l: 0x99, cycles: 8 }
+    }
+
+    ld_l_a: "LD L,A" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x6F]
+        expect { l: 0x77, cycles: 4 }
+    }
+
+    ld_hl_ind_b: "LD [HL],B" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x70]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0x11 }
+    }
+
+    ld_hl_ind_c: "LD [HL],C" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x71]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0x22 }
+    }
+
+    ld_hl_ind_d: "LD [HL],D" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x72]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0x33 }
+    }
+
+    ld_hl_ind_e: "LD [HL],E" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x73]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0x44 }
+    }
+
+    ld_hl_ind_h: "LD [HL],H" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x74]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0xC0 }
+    }
+
+    ld_hl_ind_l: "LD [HL],L" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x75]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0x10 }
+    }
+
+    ld_hl_ind_a: "LD [HL],A" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x77]
+        expect { cycles: 8 }
+        expect_mem { 0xC010: 0x77 }
+    }
+
+    ld_a_b: "LD A,B" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x78]
+        expect { a: 0x11, cycles: 4 }
+    }
+
+    ld_a_c: "LD A,C" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x79]
+        expect { a: 0x22, cycles: 4 }
+    }
+
+    ld_a_d: "LD A,D" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x7A]
+        expect { a: 0x33, cycles: 4 }
+    }
+
+    ld_a_e: "LD A,E" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x7B]
+        expect { a: 0x44, cycles: 4 }
+    }
+
+    ld_a_h: "LD A,H" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x7C]
+        expect { a: 0xC0, cycles: 4 }
+    }
+
+    ld_a_l: "LD A,L" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x7D]
+        expect { a: 0x10, cycles: 4 }
+    }
+
+    ld_a_hl_ind: "LD A,[HL]" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        mem { 0xC010: 0x99 }
+        run [0x7E]
+        expect { a: 0x99, cycles: 8 }
+    }
+
+    ld_a_a: "LD A,A" {
+        regs { b: 0x11, c: 0x22, d: 0x33, e: 0x44, h: 0xC0, l: 0x10, a: 0x77 }
+        run [0x7F]
+        expect { a: 0x77, cycles: 4 }
+    }
+
+    ld_b_n8: "LD B,n8" {
+        run [0x06, 0x5A]
+        expect { b: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_c_n8: "LD C,n8" {
+        run [0x0E, 0x5A]
+        expect { c: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_d_n8: "LD D,n8" {
+        run [0x16, 0x5A]
+        expect { d: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_e_n8: "LD E,n8" {
+        run [0x1E, 0x5A]
+        expect { e: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_h_n8: "LD H,n8" {
+        run [0x26, 0x5A]
+        expect { h: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_l_n8: "LD L,n8" {
+        run [0x2E, 0x5A]
+        expect { l: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_hl_ind_n8: "LD [HL],n8" {
+        regs { hl: 0xC000 }
+        run [0x36, 0x5A]
+        expect { pc: 0x0102, cycles: 12 }
+        expect_mem { 0xC000: 0x5A }
+    }
+
+    ld_a_n8: "LD A,n8" {
+        run [0x3E, 0x5A]
+        expect { a: 0x5A, pc: 0x0102, cycles: 8 }
+    }
+
+    ld_bc_n16: "LD BC,n16" {
+        run [0x01, 0x34, 0x12]
+        expect { bc: 0x1234, pc: 0x0103, cycles: 12 }
+    }
+
+    ld_de_n16: "LD DE,n16" {
+        run [0x11, 0x34, 0x12]
+        expect { de: 0x1234, pc: 0x0103, cycles: 12 }
+    }
+
+    ld_hl_n16: "LD HL,n16" {
+        run [0x21, 0x34, 0x12]
+        expect { hl: 0x1234, pc: 0x0103, cycles: 12 }
+    }
+
+    ld_sp_n16: "LD SP,n16" {
+        run [0x31, 0x34, 0x12]
+        expect { sp: 0x1234, pc: 0x0103, cycles: 12 }
+    }
+
+    ld_bc_ind_a: "LD [BC],A" {
+        regs { a: 0x42, bc: 0xC000 }
+        run [0x02]
+        expect { cycles: 8 }
+        expect_mem { 0xC000: 0x42 }
+    }
+
+    ld_a_bc_ind: "LD A,[BC]" {
+        regs { bc: 0xC000 }
+        mem { 0xC000: 0x24 }
+        run [0x0A]
+        expect { a: 0x24, cycles: 8 }
+    }
+
+    ld_de_ind_a: "LD [DE],A" {
+        regs { a: 0x42, de: 0xC000 }
+        run [0x12]
+        expect { cycles: 8 }
+        expect_mem { 0xC000: 0x42 }
+    }
+
+    ld_a_de_ind: "LD A,[DE]" {
+        regs { de: 0xC000 }
+        mem { 0xC000: 0x24 }
+        run [0x1A]
+        expect { a: 0x24, cycles: 8 }
+    }
+
+    ld_hli_ind_a: "LD [HL+],A" {
+        regs { a: 0x42, hl: 0xC000 }
+        run [0x22]
+        expect { cycles: 8, hl: 0xC001 }
+        expect_mem { 0xC000: 0x42 }
+    }
+
+    ld_a_hli_ind: "LD A,[HL+]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x24 }
+        run [0x2A]
+        expect { a: 0x24, cycles: 8, hl: 0xC001 }
+    }
+
+    ld_hld_ind_a: "LD [HL-],A" {
+        regs { a: 0x42, hl: 0xC000 }
+        run [0x32]
+        expect { cycles: 8, hl: 0xBFFF }
+        expect_mem { 0xC000: 0x42 }
+    }
+
+    ld_a_hld_ind: "LD A,[HL-]" {
+        regs { hl: 0xC000 }
+        mem { 0xC000: 0x24 }
+        run [0x3A]
+        expect { a: 0x24, cycles: 8, hl: 0xBFFF }
+    }
+
+    ld_a16_a: "LD [a16],A" {
+        regs { a: 0x42 }
+        run [0xEA, 0x00, 0xC1]
+        expect { pc: 0x0103, cycles: 16 }
+        expect_mem { 0xC100: 0x42 }
+    }
+
+    ld_a_a16: "LD A,[a16]" {
+        mem { 0xC100: 0x42 }
+        run [0xFA, 0x00, 0xC1]
+        expect { a: 0x42, pc: 0x0103, cycles: 16 }
+    }
+
+    ldh_a8_a: "LDH [a8],A" {
+        regs { a: 0x42 }
+        run [0xE0, 0x80]
+        expect { pc: 0x0102, cycles: 12 }
+        expect_mem { 0xFF80: 0x42 }
+    }
+
+    ldh_a_a8: "LDH A,[a8]" {
+        mem { 0xFF80: 0x42 }
+        run [0xF0, 0x80]
+        expect { a: 0x42, pc: 0x0102, cycles: 12 }
+    }
+
+    ldh_c_a: "LDH [C],A" {
+        regs { a: 0x42, c: 0x81 }
+        run [0xE2]
+        expect { cycles: 8 }
+        expect_mem { 0xFF81: 0x42 }
+    }
+
+    ldh_a_c: "LDH A,[C]" {
+        regs { c: 0x81 }
+        mem { 0xFF81: 0x42 }
+        run [0xF2]
+        expect { a: 0x42, cycles: 8 }
+    }
+
+    ld_a16_sp: "LD [a16],SP" {
+        regs { sp: 0xBEEF }
+        run [0x08, 0x00, 0xC1]
+        expect { pc: 0x0103, cycles: 20 }
+        expect_mem { 0xC100: 0xEF, 0xC101: 0xBE }
+    }
+
+    ld_sp_hl: "LD SP,HL" {
+        regs { hl: 0xC123 }
+        run [0xF9]
+        expect { sp: 0xC123, cycles: 8 }
+    }
+
+    ld_hl_sp_e8_fff8_08: "LD HL,SP+e8 with SP=0xFFF8 e8=0x08" {
+        regs { sp: 0xFFF8, zf: true, nf: true }
+        run [0xF8, 0x08]
+        expect { hl: 0x0000, sp: 0xFFF8, zf: false, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 12 }
+    }
+
+    ld_hl_sp_e8_000f_01: "LD HL,SP+e8 with SP=0x000F e8=0x01" {
+        regs { sp: 0x000F, zf: true, nf: true }
+        run [0xF8, 0x01]
+        expect { hl: 0x0010, sp: 0x000F, zf: false, nf: false, hf: true, cf: false, pc: 0x0102, cycles: 12 }
+    }
+
+    ld_hl_sp_e8_00ff_01: "LD HL,SP+e8 with SP=0x00FF e8=0x01" {
+        regs { sp: 0x00FF, zf: true, nf: true }
+        run [0xF8, 0x01]
+        expect { hl: 0x0100, sp: 0x00FF, zf: false, nf: false, hf: true, cf: true, pc: 0x0102, cycles: 12 }
+    }
+
+    ld_hl_sp_e8_d000_ff: "LD HL,SP+e8 with SP=0xD000 e8=0xFF" {
+        regs { sp: 0xD000, zf: true, nf: true }
+        run [0xF8, 0xFF]
+        expect { hl: 0xCFFF, sp: 0xD000, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 12 }
+    }
+
+    ld_hl_sp_e8_d000_80: "LD HL,SP+e8 with SP=0xD000 e8=0x80" {
+        regs { sp: 0xD000, zf: true, nf: true }
+        run [0xF8, 0x80]
+        expect { hl: 0xCF80, sp: 0xD000, zf: false, nf: false, hf: false, cf: false, pc: 0x0102, cycles: 12 }
+    }
+
+    push_bc: "PUSH BC" {
+        regs { bc: 0x1234, sp: 0xD000 }
+        run [0xC5]
+        expect { sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x34, 0xCFFF: 0x12 }
+    }
+
+    pop_bc: "POP BC" {
+        regs { sp: 0xCFFE }
+        mem { 0xCFFE: 0x34, 0xCFFF: 0x12 }
+        run [0xC1]
+        expect { bc: 0x1234, sp: 0xD000, cycles: 12 }
+    }
+
+    push_de: "PUSH DE" {
+        regs { de: 0x1234, sp: 0xD000 }
+        run [0xD5]
+        expect { sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x34, 0xCFFF: 0x12 }
+    }
+
+    pop_de: "POP DE" {
+        regs { sp: 0xCFFE }
+        mem { 0xCFFE: 0x34, 0xCFFF: 0x12 }
+        run [0xD1]
+        expect { de: 0x1234, sp: 0xD000, cycles: 12 }
+    }
+
+    push_hl: "PUSH HL" {
+        regs { hl: 0x1234, sp: 0xD000 }
+        run [0xE5]
+        expect { sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0x34, 0xCFFF: 0x12 }
+    }
+
+    pop_hl: "POP HL" {
+        regs { sp: 0xCFFE }
+        mem { 0xCFFE: 0x34, 0xCFFF: 0x12 }
+        run [0xE1]
+        expect { hl: 0x1234, sp: 0xD000, cycles: 12 }
+    }
+
+    push_af: "PUSH AF" {
+        regs { af: 0x12F0, sp: 0xD000 }
+        run [0xF5]
+        expect { sp: 0xCFFE, cycles: 16 }
+        expect_mem { 0xCFFE: 0xF0, 0xCFFF: 0x12 }
+    }
+
+    pop_af: "POP AF" {
+        regs { sp: 0xCFFE }
+        mem { 0xCFFE: 0xF0, 0xCFFF: 0x12 }
+        run [0xF1]
+        expect { af: 0x12F0, sp: 0xD000, cycles: 12 }
+    }
+
+    pop_af_masks_flags: "POP AF ignores the low nibble of F" {
+        regs { sp: 0xCFFE }
+        mem { 0xCFFE: 0xFF, 0xCFFF: 0x12 }
+        run [0xF1]
+        expect { af: 0x12F0, cycles: 12 }
+    }
+}
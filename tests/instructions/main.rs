@@ -0,0 +1,9 @@
+//! Instruction level tests, each executing a single instruction on a flat
+//! memory CPU.
+#[path = "../common/mod.rs"]
+mod common;
+
+mod alu;
+mod bits;
+mod control;
+mod loads;
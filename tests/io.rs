@@ -0,0 +1,59 @@
+mod common;
+
+use gbemu::{
+    memory::{io::IoHandler, locations, Memory, Read, Write},
+    GameBoy,
+};
+
+fn console() -> GameBoy {
+    GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]))
+}
+
+#[test]
+fn subsystems_trap_their_registers() {
+    let mut gb = console();
+
+    gb.write_u8(locations::DIV, 0x12);
+    assert_eq!(gb.read_u8(locations::DIV), 0);
+
+    let ly = gb.read_u8(locations::LY);
+    gb.write_u8(locations::LY, ly.wrapping_add(1));
+    assert_eq!(gb.read_u8(locations::LY), ly);
+
+    // Only the selection bits are stored, no button is held
+    gb.write_u8(locations::P1, 0x00);
+    assert_eq!(gb.read_u8(locations::P1), 0b1100_1111);
+
+    gb.write_u8(locations::TAC, 0b101);
+    assert_eq!(gb.read_u8(locations::TAC), 0b1111_1101);
+}
+
+#[test]
+fn plain_registers_are_stored() {
+    let mut gb = console();
+    gb.write_u8(locations::BGP, 0xE4);
+    assert_eq!(gb.read_u8(locations::BGP), 0xE4);
+}
+
+#[test]
+fn installed_handlers_replace_storage() {
+    let mut gb = console();
+    // An unused register, wired to a fake peripheral
+    gb.install_io_handler(
+        0xFF7F,
+        IoHandler {
+            read: Some(|gb, address| gb.memory()[address] ^ 0xFF),
+            write: Some(|gb, address, value| gb.memory_mut()[address] = value.wrapping_add(1)),
+        },
+    );
+
+    gb.write_u8(0xFF7F, 0x10);
+    assert_eq!(gb.memory()[0xFF7F], 0x11);
+    assert_eq!(gb.read_u8(0xFF7F), 0xEE);
+}
+
+#[test]
+#[should_panic(expected = "not an I/O register")]
+fn handlers_are_limited_to_io_registers() {
+    console().install_io_handler(0xC000, IoHandler::write(|_, _, _| ()));
+}
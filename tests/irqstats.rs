@@ -0,0 +1,66 @@
+mod common;
+
+use gbemu::{irqstats::LatencyHistogram, GameBoy, Interrupt};
+
+/// Enables the timer interrupt, then requests it three times by writing IF,
+/// with IME as given. The handlers return at once.
+fn console(ime: bool) -> GameBoy {
+    let ei = if ime { 0xFB } else { 0x00 };
+    // LD A,0x04; LDH (IE),A; EI or NOP; LDH (IF),A three times; JR -2
+    let program = [
+        0x3E, 0x04, 0xE0, 0xFF, ei, 0xE0, 0x0F, 0xE0, 0x0F, 0xE0, 0x0F, 0x18, 0xFE,
+    ];
+    let mut rom = common::rom_with_program(&program);
+    for handler in [0x40, 0x48, 0x50, 0x58, 0x60] {
+        rom[handler] = 0xD9; // RETI
+    }
+    let mut gb = GameBoy::new(&rom);
+    gb.set_interrupt_stats(true);
+    gb
+}
+
+#[test]
+fn serviced_interrupts_are_timed() {
+    let mut gb = console(true);
+    for _ in 0..3 {
+        gb.run_frame();
+    }
+
+    let stats = gb.take_interrupt_stats();
+    let timer = stats.get(Interrupt::TimerOverflow);
+    // Every request is serviced right after the write to IF, by the dispatch
+    assert_eq!(timer.count, 3);
+    assert!(0 < timer.min && timer.min <= timer.max);
+    assert_eq!((timer.min, timer.max), (20, 20));
+    assert_eq!(timer.buckets[LatencyHistogram::bucket(20)], timer.count);
+    assert_eq!(timer.mean(), Some(20.0));
+    assert_eq!(stats.get(Interrupt::Joypad).count, 0);
+
+    assert_eq!(
+        gb.take_interrupt_stats()
+            .get(Interrupt::TimerOverflow)
+            .count,
+        0
+    );
+}
+
+#[test]
+fn requests_not_serviced_are_not_counted() {
+    let mut gb = console(false);
+    for _ in 0..3 {
+        gb.run_frame();
+    }
+
+    let timer = *gb.take_interrupt_stats().get(Interrupt::TimerOverflow);
+    assert_eq!(timer, LatencyHistogram::default());
+    assert_eq!(timer.mean(), None);
+}
+
+#[test]
+fn histogram_buckets_are_powers_of_two() {
+    assert_eq!(LatencyHistogram::bucket(0), 0);
+    assert_eq!(LatencyHistogram::bucket(1), 1);
+    assert_eq!(LatencyHistogram::bucket(4), 3);
+    assert_eq!(LatencyHistogram::bucket(7), 3);
+    assert_eq!(LatencyHistogram::bucket(u64::MAX), 23);
+}
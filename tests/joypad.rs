@@ -0,0 +1,179 @@
+mod common;
+
+use gbemu::{
+    cpu::{Cpu, CpuMode, Registers},
+    joypad::Buttons,
+    memory::{locations, Read, Write},
+    GameBoy, Model,
+};
+
+const SELECT_DIRECTIONS: u8 = 0b0010_0000;
+const SELECT_ACTIONS: u8 = 0b0001_0000;
+
+fn new_console() -> GameBoy {
+    // JR -2
+    GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]))
+}
+
+#[test]
+fn p1_reads_the_selected_lines() {
+    let mut gb = new_console();
+    gb.set_buttons(Buttons::RIGHT | Buttons::UP | Buttons::B | Buttons::START);
+
+    let cases = [
+        (SELECT_DIRECTIONS, 0b1110_1010),
+        (SELECT_ACTIONS, 0b1101_0101),
+        (0b0000_0000, 0b1100_0000),
+        (0b0011_0000, 0b1111_1111),
+    ];
+    for (select, expected) in cases {
+        // The low bits can't be written
+        gb.write_u8(locations::P1, select | 0b1111);
+        assert_eq!(
+            gb.read_u8(locations::P1),
+            expected,
+            "Select {:#010b}",
+            select
+        );
+    }
+
+    gb.write_u8(locations::P1, SELECT_DIRECTIONS);
+    gb.set_buttons(Buttons::empty());
+    assert_eq!(gb.read_u8(locations::P1), 0b1110_1111);
+}
+
+#[test]
+fn presses_on_selected_lines_request_the_interrupt() {
+    let mut gb = new_console();
+    gb.write_u8(locations::P1, SELECT_ACTIONS);
+    gb.write_u8(locations::IF, 0);
+
+    // Directions are not selected
+    gb.set_buttons(Buttons::LEFT);
+    assert_eq!(gb.read_u8(locations::IF) & 0b1_0000, 0);
+    gb.set_buttons(Buttons::LEFT | Buttons::A);
+    assert_ne!(gb.read_u8(locations::IF) & 0b1_0000, 0);
+
+    // Releases and held buttons are no edges
+    gb.write_u8(locations::IF, 0);
+    gb.set_buttons(Buttons::A);
+    gb.set_buttons(Buttons::empty());
+    assert_eq!(gb.read_u8(locations::IF) & 0b1_0000, 0);
+}
+
+#[test]
+fn held_buttons_leave_stop() {
+    // STOP, then JR -2
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x10, 0x00, 0x18, 0xFE]));
+    gb.write_u8(locations::P1, SELECT_DIRECTIONS);
+    gb.step();
+    assert_eq!(gb.registers().mode, CpuMode::Stopped);
+    gb.step();
+    assert_eq!(gb.registers().mode, CpuMode::Stopped);
+
+    gb.set_buttons(Buttons::DOWN);
+    gb.step();
+    assert_eq!(gb.registers().mode, CpuMode::Running);
+}
+
+#[test]
+fn buttons_combine_as_sets() {
+    let mut buttons = Buttons::A | Buttons::LEFT;
+    assert!(buttons.contains(Buttons::A));
+    assert!(!buttons.contains(Buttons::A | Buttons::B));
+    buttons |= Buttons::B;
+    buttons.remove(Buttons::A | Buttons::LEFT);
+    assert_eq!(buttons, Buttons::B);
+    assert_eq!(Buttons::from_bits(buttons.bits()), buttons);
+
+    let all = Buttons::ALL
+        .into_iter()
+        .fold(Buttons::empty(), |a, b| a | b);
+    assert_eq!(all.bits(), 0xFF);
+}
+
+/// Sends a SGB command packet by pulsing P14 and P15
+fn send_packet(gb: &mut GameBoy, packet: [u8; 16]) {
+    gb.write_u8(locations::P1, 0b0000_0000);
+    gb.write_u8(locations::P1, 0b0011_0000);
+    for byte in packet {
+        for bit in 0..8 {
+            let pulse = if byte >> bit & 1 == 1 {
+                SELECT_ACTIONS
+            } else {
+                SELECT_DIRECTIONS
+            };
+            gb.write_u8(locations::P1, pulse);
+            gb.write_u8(locations::P1, 0b0011_0000);
+        }
+    }
+    // Stop bit
+    gb.write_u8(locations::P1, SELECT_DIRECTIONS);
+    gb.write_u8(locations::P1, 0b0011_0000);
+}
+
+/// Reads the player ID then both lines, as multiplayer games do, returning
+/// the ID
+fn read_player(gb: &mut GameBoy) -> u8 {
+    gb.write_u8(locations::P1, 0b0011_0000);
+    let id = gb.read_u8(locations::P1) & 0b1111;
+    gb.write_u8(locations::P1, SELECT_DIRECTIONS);
+    gb.write_u8(locations::P1, SELECT_ACTIONS);
+    gb.write_u8(locations::P1, 0b0011_0000);
+    id
+}
+
+fn mlt_req(players: u8) -> [u8; 16] {
+    let mut packet = [0; 16];
+    packet[0] = 0x11 << 3 | 1;
+    packet[1] = players;
+    packet
+}
+
+fn console(model: Model) -> GameBoy {
+    GameBoy::builder(&common::rom_with_program(&[0x18, 0xFE]))
+        .model(model)
+        .build()
+}
+
+#[test]
+fn sgb_multiplayer_mode_cycles_the_player_ids() {
+    let mut gb = console(Model::Sgb);
+    assert_eq!(read_player(&mut gb), 0xF);
+    assert_eq!(read_player(&mut gb), 0xF);
+
+    send_packet(&mut gb, mlt_req(0b01));
+    let ids: Vec<_> = (0..4).map(|_| read_player(&mut gb)).collect();
+    assert_eq!(ids, [0xF, 0xE, 0xF, 0xE]);
+
+    send_packet(&mut gb, mlt_req(0b11));
+    let ids: Vec<_> = (0..5).map(|_| read_player(&mut gb)).collect();
+    assert_eq!(ids, [0xF, 0xE, 0xD, 0xC, 0xF]);
+
+    send_packet(&mut gb, mlt_req(0b00));
+    assert_eq!(read_player(&mut gb), 0xF);
+    assert_eq!(read_player(&mut gb), 0xF);
+}
+
+#[test]
+fn sgb_other_players_hold_nothing() {
+    let mut gb = console(Model::Sgb);
+    gb.set_buttons(Buttons::A | Buttons::RIGHT);
+    send_packet(&mut gb, mlt_req(0b01));
+
+    gb.write_u8(locations::P1, SELECT_DIRECTIONS);
+    assert_eq!(gb.read_u8(locations::P1) & 0b1111, 0b1110);
+    read_player(&mut gb);
+    gb.write_u8(locations::P1, SELECT_DIRECTIONS);
+    assert_eq!(gb.read_u8(locations::P1) & 0b1111, 0b1111);
+}
+
+#[test]
+fn other_models_ignore_sgb_packets() {
+    for model in [Model::Dmg, Model::Cgb] {
+        let mut gb = console(model);
+        send_packet(&mut gb, mlt_req(0b11));
+        let ids: Vec<_> = (0..4).map(|_| read_player(&mut gb)).collect();
+        assert_eq!(ids, [0xF; 4], "{:?}", model);
+    }
+}
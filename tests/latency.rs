@@ -0,0 +1,52 @@
+mod common;
+
+use gbemu::{
+    joypad::Buttons,
+    latency::{self, LatencyEvent},
+    GameBoy,
+};
+
+/// Reads P1 in a loop
+fn new_console() -> GameBoy {
+    // LDH A,[P1]; JR -4
+    GameBoy::new(&common::rom_with_program(&[0xF0, 0x00, 0x18, 0xFC]))
+}
+
+#[test]
+fn inputs_are_followed_until_presented() {
+    let mut gb = new_console();
+    gb.run_frame();
+    gb.set_latency_tracking(true);
+
+    gb.set_buttons(Buttons::A);
+    // Unchanged buttons are not an input
+    gb.set_buttons(Buttons::A);
+    gb.run_frame();
+    gb.run_frame();
+
+    let records = gb.take_latency_records();
+    let events: Vec<_> = records.iter().map(|record| record.event).collect();
+    assert_eq!(
+        events[..2],
+        [LatencyEvent::Input(Buttons::A), LatencyEvent::JoypadRead]
+    );
+    assert!(matches!(events[2], LatencyEvent::FramePresented(_)));
+    // The following reads and frames
+    assert!(events[3..]
+        .iter()
+        .all(|event| matches!(event, LatencyEvent::FramePresented(_))));
+
+    let samples = latency::samples(&records);
+    assert_eq!(samples.len(), 1);
+    let sample = samples[0];
+    assert!(sample.cycles_to_read() <= 16);
+    assert!(sample.input_to_read() <= sample.input_to_presented());
+}
+
+#[test]
+fn nothing_is_recorded_by_default() {
+    let mut gb = new_console();
+    gb.set_buttons(Buttons::START);
+    gb.run_frame();
+    assert!(gb.take_latency_records().is_empty());
+}
@@ -0,0 +1,297 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gbemu::{
+    accuracy::AccuracyProfile,
+    cartridge,
+    cpu::{Registers, CYCLES_PER_FRAME},
+    lcd::{
+        Mode, DOTS_PER_LINE, LINES_PER_FRAME, PPU_EVENT_TIMEOUT_FRAMES, SCREEN_HEIGHT,
+        SCREEN_WIDTH, VISIBLE_LINES,
+    },
+    memory::{locations, RamInit, Read, Write},
+    run::{Condition, Timeout},
+    GameBoy, Model,
+};
+
+mod common;
+
+#[test]
+fn hooks_follow_the_frame() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let vblanks = Rc::new(RefCell::new(0));
+    let hblanks = Rc::new(RefCell::new(Vec::new()));
+    let modes = Rc::new(RefCell::new(Vec::new()));
+
+    let counter = vblanks.clone();
+    gb.hooks_mut().on_vblank = Some(Box::new(move || *counter.borrow_mut() += 1));
+    let lines = hblanks.clone();
+    gb.hooks_mut().on_hblank = Some(Box::new(move |line| lines.borrow_mut().push(line)));
+    let changes = modes.clone();
+    gb.hooks_mut().on_mode_change = Some(Box::new(move |mode| changes.borrow_mut().push(mode)));
+
+    gb.run_until(&Condition::Frames(2), 3).unwrap();
+
+    assert_eq!(*vblanks.borrow(), 2);
+    let expected: Vec<u8> = (0..VISIBLE_LINES).chain(0..VISIBLE_LINES).collect();
+    assert_eq!(*hblanks.borrow(), expected);
+    assert_eq!(
+        modes.borrow()[..4],
+        [Mode::Drawing, Mode::HBlank, Mode::OamScan, Mode::Drawing]
+    );
+    assert_eq!(modes.borrow().len(), 2 * (3 * VISIBLE_LINES as usize + 1));
+}
+
+#[test]
+fn ly_and_stat_track_the_timing() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let line_cycles = CYCLES_PER_FRAME / LINES_PER_FRAME as u64;
+
+    gb.run_until(&Condition::Cycles(10 * line_cycles + 100), 1)
+        .unwrap();
+    assert_eq!(gb.read_u8(locations::LY), 10);
+    assert_eq!(gb.lcd_mode(), Mode::Drawing);
+    assert_eq!(gb.read_u8(locations::STAT) & 0b11, Mode::Drawing as u8);
+
+    gb.run_until(&Condition::Cycles(140 * line_cycles), 1)
+        .unwrap();
+    assert_eq!(gb.read_u8(locations::LY), 150);
+    assert_eq!(gb.lcd_mode(), Mode::VBlank);
+
+    assert_eq!(gb.read_u8(locations::STAT) & 0b100, 0);
+
+    // LYC is 0 after boot
+    gb.run_until(&Condition::Cycles(4 * line_cycles), 2)
+        .unwrap();
+    assert_eq!(gb.read_u8(locations::LY), 0);
+    assert_eq!(gb.read_u8(locations::STAT) & 0b100, 0b100);
+}
+
+#[test]
+fn ly_is_read_only() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    gb.run_until(&Condition::Cycles(3000), 1).unwrap();
+    let ly = gb.read_u8(locations::LY);
+    assert_ne!(ly, 0);
+
+    gb.write_u8(locations::LY, 0x42);
+    assert_eq!(gb.read_u8(locations::LY), ly);
+}
+
+#[test]
+fn lcd_off_resets_the_timing() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    gb.run_until(&Condition::Cycles(3000), 1).unwrap();
+
+    let lcdc = gb.read_u8(locations::LCDC);
+    gb.write_u8(locations::LCDC, lcdc & 0x7F);
+    assert_eq!(gb.read_u8(locations::LY), 0);
+    assert_eq!(gb.lcd_mode(), Mode::HBlank);
+    assert_eq!(gb.read_u8(locations::STAT) & 0b11, 0);
+
+    // Stopped while off
+    gb.run_until(&Condition::Cycles(3000), 1).unwrap();
+    assert_eq!(gb.read_u8(locations::LY), 0);
+    assert_eq!(gb.lcd_mode(), Mode::HBlank);
+
+    // The first line skips the OAM scan
+    gb.write_u8(locations::LCDC, lcdc);
+    gb.run_until(&Condition::Cycles(40), 1).unwrap();
+    assert_eq!(gb.lcd_mode(), Mode::HBlank);
+    gb.run_until(&Condition::Cycles(60), 1).unwrap();
+    assert_eq!(gb.lcd_mode(), Mode::Drawing);
+    gb.run_until(&Condition::Cycles(456 - 100), 1).unwrap();
+    assert_eq!(gb.read_u8(locations::LY), 1);
+    assert_eq!(gb.lcd_mode(), Mode::OamScan);
+}
+
+#[test]
+fn frame_shows_background_and_sprites() {
+    let rom = cartridge::test_rom();
+    let mut gb = GameBoy::builder(&rom).ram_init(RamInit::Zeros).build();
+
+    // Tile 1, first row: colors 3 3 1 1 2 2 0 0, at the top left corner
+    gb.write_u8(0x8010, 0b1111_0000);
+    gb.write_u8(0x8011, 0b1100_1100);
+    gb.write_u8(0x9800, 1);
+    // Tile 2, first row: color 1, shown at x = 4 over the background
+    gb.write_u8(0x8020, 0xFF);
+    for (i, byte) in [16, 8 + 4, 2, 0].into_iter().enumerate() {
+        gb.write_u8(0xFE00 + i, byte);
+    }
+    gb.write_u8(locations::BGP, 0b11_10_01_00);
+    gb.write_u8(locations::OBP0, 0b00_00_11_00);
+    gb.write_u8(locations::LCDC, 0x93);
+
+    gb.run_until(&Condition::Frames(1), 2).unwrap();
+    assert_eq!(gb.frame().len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+    assert_eq!(
+        gb.frame()[..16],
+        [3, 3, 1, 1, 3, 3, 3, 3, 3, 3, 3, 3, 0, 0, 0, 0]
+    );
+    assert!(gb.frame()[SCREEN_WIDTH..].iter().all(|&shade| shade == 0));
+}
+
+#[test]
+fn runs_stop_at_vblank_and_scanlines() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+
+    gb.run_to_vblank().unwrap();
+    assert_eq!(gb.lcd_mode(), Mode::VBlank);
+    assert_eq!(gb.read_u8(locations::LY), VISIBLE_LINES);
+
+    // The next one, a frame later
+    let cycles = gb.run_to_vblank().unwrap();
+    assert!(cycles.abs_diff(CYCLES_PER_FRAME) < 32, "{}", cycles);
+
+    gb.run_to_scanline(42).unwrap();
+    assert_eq!(gb.read_u8(locations::LY), 42);
+    assert_eq!(gb.lcd_mode(), Mode::OamScan);
+
+    // Already on the line, waits for the next frame
+    let cycles = gb.run_to_scanline(42).unwrap();
+    assert!(cycles > CYCLES_PER_FRAME - DOTS_PER_LINE);
+    assert_eq!(gb.read_u8(locations::LY), 42);
+}
+
+#[test]
+fn runs_to_ppu_events_time_out_while_the_lcd_is_off() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let lcdc = gb.read_u8(locations::LCDC);
+    gb.write_u8(locations::LCDC, lcdc & 0x7F);
+
+    let timeout = Err(Timeout {
+        frames: PPU_EVENT_TIMEOUT_FRAMES,
+    });
+    assert_eq!(gb.run_to_vblank(), timeout);
+    assert_eq!(gb.run_to_scanline(0), timeout);
+}
+
+/// Enables the interrupts in `ie` and halts in a loop, with a handler
+/// incrementing B at `vector`
+fn halting_rom(ie: u8, vector: usize) -> GameBoy {
+    // XOR A; LDH (IF),A; LD A,ie; LDH (IE),A; EI; HALT; JR -3
+    let program = [
+        0xAF, 0xE0, 0x0F, 0x3E, ie, 0xE0, 0xFF, 0xFB, 0x76, 0x18, 0xFD,
+    ];
+    let mut rom = common::rom_with_program(&program);
+    // INC B; RETI
+    rom[vector..vector + 2].copy_from_slice(&[0x04, 0xD9]);
+    GameBoy::new(&rom)
+}
+
+fn b(gb: &GameBoy) -> u8 {
+    gb.registers().bc.to_be_bytes()[0]
+}
+
+#[test]
+fn vblank_handler_runs_once_per_frame() {
+    let mut gb = halting_rom(0b0001, 0x40);
+    let start = b(&gb);
+    for _ in 0..5 {
+        gb.run_frame();
+    }
+    assert_eq!(b(&gb), start.wrapping_add(5));
+
+    gb.run_until(&Condition::Frames(3), 4).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(8));
+
+    gb.run_to_vblank().unwrap();
+    assert_eq!(gb.read_u8(locations::IF) & 0b1, 0b1);
+    gb.run_until(&Condition::PcEquals(0x0041), 1).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(9));
+}
+
+#[test]
+fn stat_sources_interrupt_on_rising_edges() {
+    // The LYC=LY source rises once per frame
+    let mut gb = halting_rom(0b0010, 0x48);
+    gb.write_u8(locations::LYC, 0x40);
+    gb.write_u8(locations::STAT, 0x40);
+    let start = b(&gb);
+    gb.run_until(&Condition::Frames(3), 4).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(3));
+
+    // The HBlank source rises on each visible line
+    let mut gb = halting_rom(0b0010, 0x48);
+    gb.write_u8(locations::STAT, 0x08);
+    let start = b(&gb);
+    gb.run_until(&Condition::Frames(1), 2).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(VISIBLE_LINES));
+
+    // With the mode 0, 1 and 2 sources the line stays high from the last
+    // HBlank to the first drawing
+    let mut gb = halting_rom(0b0010, 0x48);
+    gb.write_u8(locations::STAT, 0x38);
+    let start = b(&gb);
+    gb.run_until(&Condition::Frames(1), 2).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(VISIBLE_LINES));
+}
+
+/// STAT is written in VBlank, then in the OAM scan of line 1
+fn stat_write_interrupts(model: Model, profile: AccuracyProfile) -> [bool; 2] {
+    let rom = cartridge::test_rom();
+    let mut gb = GameBoy::builder(&rom)
+        .model(model)
+        .accuracy(profile)
+        .build();
+    gb.write_u8(locations::LYC, 0x90);
+
+    let write_stat = |gb: &mut GameBoy| {
+        gb.write_u8(locations::IF, 0x00);
+        gb.write_u8(locations::STAT, 0x00);
+        gb.read_u8(locations::IF) & 0b10 != 0
+    };
+    gb.run_to_vblank().unwrap();
+    let in_vblank = write_stat(&mut gb);
+    gb.run_to_scanline(1).unwrap();
+    [in_vblank, write_stat(&mut gb)]
+}
+
+#[test]
+fn stat_writes_request_an_interrupt_on_dmg() {
+    assert_eq!(
+        stat_write_interrupts(Model::Dmg, AccuracyProfile::Balanced),
+        [true, false]
+    );
+    assert_eq!(
+        stat_write_interrupts(Model::Cgb, AccuracyProfile::Balanced),
+        [false, false]
+    );
+    assert_eq!(
+        stat_write_interrupts(Model::Dmg, AccuracyProfile::Fast),
+        [false, false]
+    );
+}
+
+#[test]
+fn stat_write_bug_needs_a_low_stat_line() {
+    let mut gb = GameBoy::builder(&cartridge::test_rom())
+        .model(Model::Dmg)
+        .build();
+    gb.write_u8(locations::LYC, 0x90);
+    gb.write_u8(locations::STAT, 0x10);
+    gb.run_to_vblank().unwrap();
+
+    // The VBlank source already holds the line high: no new edge
+    gb.write_u8(locations::IF, 0x00);
+    gb.write_u8(locations::STAT, 0x10);
+    assert_eq!(gb.read_u8(locations::IF) & 0b10, 0);
+
+    // Disabling it lets the line fall without a request
+    gb.write_u8(locations::IF, 0x00);
+    gb.write_u8(locations::STAT, 0x00);
+    assert_eq!(gb.read_u8(locations::IF) & 0b10, 0);
+
+    // Now the bug raises the low line even though no source is enabled
+    gb.write_u8(locations::STAT, 0x00);
+    assert_eq!(gb.read_u8(locations::IF) & 0b10, 0b10);
+}
+
+#[test]
+fn stat_mode_bits_are_read_only() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    gb.run_to_vblank().unwrap();
+    gb.write_u8(locations::STAT, 0xFC);
+    assert_eq!(gb.read_u8(locations::STAT) & 0b11, Mode::VBlank as u8);
+    assert_eq!(gb.read_u8(locations::STAT) & 0x78, 0x78);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use gbemu::{
+    lint::{Lint, LintKind},
+    GameBoy,
+};
+
+fn lints(program: &[u8]) -> Vec<Lint> {
+    let mut gb = GameBoy::new(&common::rom_with_program(program));
+    gb.set_lints(true);
+    gb.run_frame();
+    gb.run_frame();
+    gb.take_lints()
+}
+
+#[test]
+fn echo_ram_accesses_are_reported_once() {
+    // LD A,(0xE000); JR -5
+    let lints = lints(&[0xFA, 0x00, 0xE0, 0x18, 0xFB]);
+    assert_eq!(
+        lints,
+        [Lint {
+            pc: 0x0100,
+            address: 0xE000,
+            kind: LintKind::EchoRam
+        }]
+    );
+    assert_eq!(lints[0].to_string(), "PC 0x0100: echo RAM access at 0xe000");
+}
+
+#[test]
+fn write_only_register_reads_are_reported() {
+    // NOP; LDH A,(NR13); JR -4
+    let lints = lints(&[0x00, 0xF0, 0x13, 0x18, 0xFC]);
+    assert_eq!(
+        lints,
+        [Lint {
+            pc: 0x0101,
+            address: 0xFF13,
+            kind: LintKind::WriteOnlyRead
+        }]
+    );
+}
+
+#[cfg(feature = "ppu")]
+#[test]
+fn vram_writes_while_drawing_are_reported() {
+    // LD (0x8000),A; JR -5
+    let lints = lints(&[0xEA, 0x00, 0x80, 0x18, 0xFB]);
+    assert_eq!(
+        lints,
+        [Lint {
+            pc: 0x0100,
+            address: 0x8000,
+            kind: LintKind::VramWriteInMode3
+        }]
+    );
+}
+
+#[test]
+fn lints_are_off_by_default() {
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0xFA, 0x00, 0xE0, 0x18, 0xFB]));
+    gb.run_frame();
+    assert!(!gb.lints_enabled());
+    assert!(gb.take_lints().is_empty());
+}
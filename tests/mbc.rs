@@ -0,0 +1,392 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use gbemu::{
+    cpu::{Cpu, Registers},
+    memory::{RamInit, Read, Write},
+    GameBoy,
+};
+
+fn a(gb: &GameBoy) -> u8 {
+    gb.registers().af.to_be_bytes()[0]
+}
+
+#[test]
+fn mbc30_selects_high_rom_banks() {
+    let mut rom = common::rom_with_header(
+        &[
+            0x3E, 0x90, // LD A,0x90
+            0xEA, 0x00, 0x20, // LD [0x2000],A
+            0xFA, 0x00, 0x40, // LD A,[0x4000]
+        ],
+        0x13,
+        0x07,
+        0x05,
+    );
+    rom[0x90 * 0x4000] = 0x42;
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..3 {
+        gb.step();
+    }
+
+    assert_eq!(gb.banking_state().rom_bank, 0x90);
+    assert_eq!(a(&gb), 0x42);
+}
+
+#[test]
+fn mbc30_selects_high_ram_banks() {
+    let rom = common::rom_with_header(
+        &[
+            0x3E, 0x0A, // LD A,0x0A
+            0xEA, 0x00, 0x00, // LD [0x0000],A ; enable RAM
+            0x3E, 0x07, // LD A,0x07
+            0xEA, 0x00, 0x40, // LD [0x4000],A ; select RAM bank 7
+            0x3E, 0x42, // LD A,0x42
+            0xEA, 0x00, 0xA0, // LD [0xA000],A
+        ],
+        0x13,
+        0x07,
+        0x05,
+    );
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..6 {
+        gb.step();
+    }
+
+    assert_eq!(gb.banking_state().ram_bank, 7);
+    assert_eq!(gb.battery_ram().unwrap()[7 * 0x2000], 0x42);
+}
+
+#[test]
+fn mbc3_ignores_high_bits() {
+    let mut rom = common::rom_with_header(
+        &[
+            0x3E, 0x81, // LD A,0x81
+            0xEA, 0x00, 0x20, // LD [0x2000],A
+            0xFA, 0x00, 0x40, // LD A,[0x4000]
+        ],
+        0x13,
+        0x06,
+        0x03,
+    );
+    rom[0x4000] = 0x42;
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..3 {
+        gb.step();
+    }
+
+    assert_eq!(gb.banking_state().rom_bank, 0x01);
+    assert_eq!(a(&gb), 0x42);
+}
+
+#[test]
+fn sram_init_patterns() {
+    let rom = common::rom_with_header(&[], 0x03, 0x00, 0x02);
+
+    let gb = GameBoy::builder(&rom).sram_init(RamInit::Ones).build();
+    assert!(gb.battery_ram().unwrap().iter().all(|&byte| byte == 0xFF));
+
+    let gb = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+    assert!(gb.battery_ram().unwrap().iter().all(|&byte| byte == 0x00));
+
+    let seeded = |seed| {
+        GameBoy::builder(&rom)
+            .sram_init(RamInit::Seeded(seed))
+            .build()
+            .battery_ram()
+            .unwrap()
+            .to_vec()
+    };
+    assert_eq!(seeded(1), seeded(1));
+    assert_ne!(seeded(1), seeded(2));
+}
+
+#[test]
+fn disabled_ram_reads_open_bus() {
+    let rom = common::rom_with_header(
+        &[
+            0xFA, 0x00, 0xA0, // LD A,[0xA000]
+        ],
+        0x03,
+        0x00,
+        0x02,
+    );
+
+    let mut gb = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+    gb.step();
+    assert_eq!(a(&gb), 0xFF);
+
+    let mut gb = GameBoy::builder(&rom)
+        .sram_init(RamInit::Zeros)
+        .disabled_ram_value(0x00)
+        .build();
+    gb.step();
+    assert_eq!(a(&gb), 0x00);
+}
+
+#[test]
+fn mbc5_selects_banks_past_256() {
+    let mut rom = common::rom_with_header(
+        &[
+            0x3E, 0x01, // LD A,0x01
+            0xEA, 0x00, 0x30, // LD [0x3000],A ; upper bit
+            0x3E, 0xFF, // LD A,0xFF
+            0xEA, 0x00, 0x20, // LD [0x2000],A ; lower bits
+            0xFA, 0x00, 0x40, // LD A,[0x4000]
+        ],
+        0x19,
+        0x08,
+        0x00,
+    );
+    rom[0x1FF * 0x4000] = 0x42;
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..5 {
+        gb.step();
+    }
+
+    assert_eq!(gb.banking_state().rom_bank, 0x1FF);
+    assert_eq!(a(&gb), 0x42);
+}
+
+#[test]
+fn mbc5_maps_bank_0_and_0x100() {
+    let mut rom = common::rom_with_header(
+        &[
+            0x3E, 0x00, // LD A,0x00
+            0xEA, 0x00, 0x20, // LD [0x2000],A ; lower bits
+            0xFA, 0x00, 0x40, // LD A,[0x4000]
+            0x3E, 0x01, // LD A,0x01
+            0xEA, 0x00, 0x30, // LD [0x3000],A ; upper bit
+            0xFA, 0x00, 0x40, // LD A,[0x4000]
+        ],
+        0x19,
+        0x08,
+        0x00,
+    );
+    rom[0x0000] = 0x24;
+    rom[0x4000] = 0x99;
+    rom[0x100 * 0x4000] = 0x42;
+    let mut gb = GameBoy::new(&rom);
+
+    for _ in 0..3 {
+        gb.step();
+    }
+    assert_eq!(gb.banking_state().rom_bank, 0x000);
+    assert_eq!(a(&gb), 0x24);
+
+    for _ in 0..3 {
+        gb.step();
+    }
+    assert_eq!(gb.banking_state().rom_bank, 0x100);
+    assert_eq!(a(&gb), 0x42);
+}
+
+/// Writes 0x0B to the MBC5 RAM bank register of a cartridge of the type
+fn mbc5_ram_bank_write(cartridge_type: u8) -> GameBoy {
+    let rom = common::rom_with_header(
+        &[
+            0x3E, 0x0B, // LD A,0x0B
+            0xEA, 0x00, 0x40, // LD [0x4000],A
+        ],
+        cartridge_type,
+        0x01,
+        0x04,
+    );
+    let mut gb = GameBoy::new(&rom);
+    for _ in 0..2 {
+        gb.step();
+    }
+    gb
+}
+
+#[test]
+fn mbc5_bit_3_selects_ram_banks_without_rumble() {
+    // MBC5+RAM+BATTERY
+    let state = mbc5_ram_bank_write(0x1B).banking_state();
+    assert_eq!(state.ram_bank, 0x0B);
+    assert!(!state.rumble);
+}
+
+#[test]
+fn mbc5_bit_3_drives_the_rumble_motor() {
+    // MBC5+RUMBLE+RAM+BATTERY
+    let state = mbc5_ram_bank_write(0x1E).banking_state();
+    assert_eq!(state.ram_bank, 0x03);
+    assert!(state.rumble);
+}
+
+/// An MBC1 cartridge with 32 KiB of RAM, every ROM bank starting with its
+/// number
+fn mbc1_console(rom_size: u8) -> GameBoy {
+    let mut rom = common::rom_with_header(&[], 0x03, rom_size, 0x03);
+    for bank in 0..rom.len() / 0x4000 {
+        rom[bank * 0x4000] = bank as u8;
+    }
+    GameBoy::builder(&rom).sram_init(RamInit::Zeros).build()
+}
+
+#[test]
+fn mbc1_upper_bits_select_the_switchable_bank_in_both_modes() {
+    let mut gb = mbc1_console(0x05);
+    assert_eq!(gb.banking_state().ram_banking, Some(false));
+
+    gb.write_u8(0x4000, 0x01);
+    gb.write_u8(0x2000, 0x02);
+    assert_eq!(gb.read_u8(0x4000), 0x22);
+    assert_eq!(gb.read_u8(0x0000), 0x00);
+
+    gb.write_u8(0x6000, 0x01);
+    assert_eq!(gb.read_u8(0x4000), 0x22);
+    assert_eq!(gb.banking_state().rom_bank, 0x22);
+}
+
+#[test]
+fn mbc1_mode_1_maps_the_upper_bits_at_bank_0() {
+    let mut gb = mbc1_console(0x05);
+    gb.write_u8(0x4000, 0x01);
+    gb.write_u8(0x6000, 0x01);
+    assert_eq!(gb.read_u8(0x0000), 0x20);
+
+    gb.write_u8(0x6000, 0x00);
+    assert_eq!(gb.read_u8(0x0000), 0x00);
+}
+
+#[test]
+fn mbc1_bank_0_selects_bank_1_above_the_upper_bits() {
+    let mut gb = mbc1_console(0x05);
+    gb.write_u8(0x4000, 0x01);
+    gb.write_u8(0x2000, 0x00);
+    assert_eq!(gb.read_u8(0x4000), 0x21);
+    // Only the lower 5 bits are compared to 0
+    gb.write_u8(0x2000, 0x20);
+    assert_eq!(gb.read_u8(0x4000), 0x21);
+}
+
+#[test]
+fn mbc1_ram_is_banked_in_mode_1_only() {
+    let mut gb = mbc1_console(0x04);
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0x4000, 0x02);
+    gb.write_u8(0xA000, 0x42);
+    gb.write_u8(0x6000, 0x01);
+    gb.write_u8(0xA000, 0x43);
+
+    let ram = gb.battery_ram().unwrap();
+    assert_eq!((ram[0], ram[2 * 0x2000]), (0x42, 0x43));
+    // The upper bits don't reach the ROM of smaller cartridges
+    assert_eq!(gb.read_u8(0x0000), 0x00);
+    assert_eq!(gb.read_u8(0x4000), 0x01);
+    assert!(gb.take_bus_faults().is_empty());
+}
+
+#[test]
+fn mbc1_banking_state_shows_the_banks_read_in_mode_1() {
+    // 512 KiB: the upper bits only select the RAM bank
+    let mut gb = mbc1_console(0x04);
+    gb.write_u8(0x6000, 0x01);
+    gb.write_u8(0x4000, 0x02);
+    gb.write_u8(0x2000, 0x23);
+    let state = gb.banking_state();
+    assert_eq!(
+        (state.rom_bank0, state.rom_bank, state.ram_bank),
+        (0x00, 0x03, 2)
+    );
+    assert_eq!(gb.read_u8(0x0000), state.rom_bank0 as u8);
+    assert_eq!(gb.read_u8(0x4000), state.rom_bank as u8);
+
+    // 1 MiB: they select the ROM banks, of both areas, and the RAM bank
+    let mut gb = mbc1_console(0x05);
+    gb.write_u8(0x6000, 0x01);
+    gb.write_u8(0x4000, 0x01);
+    gb.write_u8(0x2000, 0x03);
+    let state = gb.banking_state();
+    assert_eq!(
+        (state.rom_bank0, state.rom_bank, state.ram_bank),
+        (0x20, 0x23, 1)
+    );
+    assert_eq!(gb.read_u8(0x0000), state.rom_bank0 as u8);
+    assert_eq!(gb.read_u8(0x4000), state.rom_bank as u8);
+
+    // Back in mode 0 bank 0 and the first RAM bank are mapped
+    gb.write_u8(0x6000, 0x00);
+    let state = gb.banking_state();
+    assert_eq!(
+        (state.rom_bank0, state.rom_bank, state.ram_bank),
+        (0x00, 0x23, 0)
+    );
+}
+
+#[test]
+fn bank_switch_hook_gets_the_mapped_banks() {
+    let mut gb = mbc1_console(0x05);
+    let switches = Rc::new(RefCell::new(Vec::new()));
+    let log = switches.clone();
+    gb.hooks_mut().on_bank_switch = Some(Box::new(move |state| {
+        log.borrow_mut().push((state.rom_bank, state.ram_bank))
+    }));
+
+    gb.write_u8(0x2000, 0x03);
+    gb.write_u8(0x4000, 0x01);
+    // Same banks, no switch
+    gb.write_u8(0x2000, 0x03);
+    gb.write_u8(0x2000, 0x00);
+    assert_eq!(*switches.borrow(), [(0x03, 0), (0x23, 0), (0x21, 0)]);
+}
+
+#[test]
+fn rom_ram_maps_its_ram_without_enabling_it() {
+    let rom = common::rom_with_header(
+        &[
+            0x3E, 0x42, // LD A,0x42
+            0xEA, 0x00, 0xA0, // LD [0xA000],A
+            0xEA, 0x00, 0x00, // LD [0x0000],A ; no RAM enable register
+            0xAF, // XOR A
+            0xFA, 0x00, 0xA0, // LD A,[0xA000]
+        ],
+        0x08,
+        0x00,
+        0x02,
+    );
+    let mut gb = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+    for _ in 0..5 {
+        gb.step();
+    }
+
+    assert_eq!(a(&gb), 0x42);
+    let state = gb.banking_state();
+    assert!(state.ram_enabled);
+    assert_eq!((state.rom_bank, state.ram_bank), (1, 0));
+    // The rest of the 8 KiB is untouched
+    assert_eq!(gb.read_u8(0xBFFF), 0x00);
+    // Without a battery there is nothing to persist
+    assert_eq!(gb.battery_ram(), None);
+}
+
+#[test]
+fn rom_writes_are_logged_with_the_instruction_address() {
+    common::capture_logs();
+
+    let rom = common::rom_with_cartridge_type(
+        &[
+            0x3E, 0x02, // LD A,0x02
+            0xEA, 0x00, 0x20, // LD [0x2000],A
+        ],
+        0x01,
+    );
+    let mut gb = GameBoy::new(&rom);
+    gb.set_log_rom_writes(true);
+    gb.step();
+    gb.step();
+
+    let logged: Vec<_> = common::logged("gbemu::mbc")
+        .into_iter()
+        .filter(|message| message.starts_with("ROM write"))
+        .collect();
+    assert_eq!(logged.len(), 1);
+    assert!(
+        logged[0].starts_with("ROM write 0x2000 <- 0x02 at PC 0x0102: ROM bank"),
+        "{}",
+        logged[0]
+    );
+}
@@ -0,0 +1,32 @@
+//! Stub register behavior of the subsystems compiled out, run with e.g.
+//! `cargo test --no-default-features --test minimal`
+#![cfg(not(all(feature = "ppu", feature = "serial")))]
+
+mod common;
+
+use gbemu::{memory::locations, GameBoy};
+
+#[cfg(not(feature = "ppu"))]
+#[test]
+fn ly_stays_in_vblank_without_the_ppu() {
+    use gbemu::memory::{Read, Write};
+
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    gb.run_frame();
+    gb.write_u8(locations::LY, 0x12);
+    assert_eq!(gb.read_u8(locations::LY), 0x90);
+}
+
+#[cfg(not(feature = "serial"))]
+#[test]
+fn serial_registers_are_plain_storage_without_the_serial_port() {
+    use gbemu::memory::Read;
+
+    // LD A,0x42; LDH (SB),A; LD A,0x81; LDH (SC),A; JR -2
+    let program = [0x3E, 0x42, 0xE0, 0x01, 0x3E, 0x81, 0xE0, 0x02, 0x18, 0xFE];
+    let mut gb = GameBoy::new(&common::rom_with_program(&program));
+    gb.run_frame();
+    assert_eq!(gb.read_u8(locations::SB), 0x42);
+    assert_eq!(gb.read_u8(locations::SC) & 0x81, 0x81);
+    assert!(gb.serial_output().is_empty());
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use gbemu::{
+    joypad::Buttons,
+    memory::RamInit,
+    netplay::{frame_hash, InputDelay, Rollback},
+    GameBoy,
+};
+
+/// Sums the action buttons read from P1 into 0xC000, so every input leaves
+/// its mark on the state
+fn console() -> GameBoy {
+    let rom = common::rom_with_program(&[
+        0x3E, 0x10, // LD A,0x10
+        0xE0, 0x00, // LDH [P1],A
+        0xF0, 0x00, // LDH A,[P1]
+        0x21, 0x00, 0xC0, // LD HL,0xC000
+        0x86, // ADD A,[HL]
+        0x77, // LD [HL],A
+        0x18, 0xF3, // JR -13
+    ]);
+    GameBoy::builder(&rom).ram_init(RamInit::Zeros).build()
+}
+
+fn run_frame(gb: &mut GameBoy, buttons: &Buttons) {
+    gb.set_buttons(*buttons);
+    gb.run_frame();
+}
+
+#[test]
+fn corrections_resimulate_with_the_actual_inputs() {
+    let inputs = [
+        Buttons::default(),
+        Buttons::A,
+        Buttons::A,
+        Buttons::B,
+        Buttons::default(),
+        Buttons::START,
+    ];
+    let mut expected = console();
+    for input in &inputs {
+        run_frame(&mut expected, input);
+    }
+
+    // Nothing held is predicted for every frame
+    let mut gb = console();
+    let mut rollback = Rollback::new(8, run_frame);
+    for _ in &inputs {
+        rollback.advance(&mut gb, Buttons::default());
+    }
+    assert_eq!(rollback.frame(), inputs.len() as u64);
+    assert_ne!(frame_hash(&gb), frame_hash(&expected));
+
+    for (frame, input) in inputs.iter().enumerate() {
+        assert!(rollback.correct(&mut gb, frame as u64, *input));
+    }
+    assert_eq!(frame_hash(&gb), frame_hash(&expected));
+    assert_eq!(rollback.frame(), inputs.len() as u64);
+
+    // Confirming the input emulated changes nothing
+    assert!(rollback.correct(&mut gb, 3, Buttons::B));
+    assert_eq!(frame_hash(&gb), frame_hash(&expected));
+}
+
+#[test]
+fn delayed_inputs_start_with_the_initial_one() {
+    let mut delay = InputDelay::new(3, 0);
+    assert_eq!(delay.delay(), 3);
+    assert_eq!([1, 2, 3].map(|input| delay.push(input)), [0, 0, 0]);
+    assert_eq!([4, 5].map(|input| delay.push(input)), [1, 2]);
+    assert_eq!(delay.delay(), 3);
+
+    let mut none = InputDelay::new(0, 0);
+    assert_eq!(none.push(7), 7);
+}
+
+#[test]
+fn old_and_confirmed_frames_are_not_rolled_back() {
+    let mut gb = console();
+    let mut rollback = Rollback::new(2, run_frame);
+    for _ in 0..3 {
+        rollback.advance(&mut gb, Buttons::default());
+    }
+
+    // Only the last two frames are kept
+    assert!(!rollback.correct(&mut gb, 0, Buttons::A));
+    let hash = frame_hash(&gb);
+    assert!(rollback.correct(&mut gb, 1, Buttons::A));
+    assert_ne!(frame_hash(&gb), hash);
+
+    rollback.confirm(2);
+    assert!(!rollback.correct(&mut gb, 1, Buttons::B));
+    assert!(rollback.correct(&mut gb, 2, Buttons::B));
+
+    // Confirming past the current frame confirms every frame emulated, the
+    // next ones can still be corrected
+    rollback.confirm(10);
+    assert!(!rollback.correct(&mut gb, 2, Buttons::A));
+    rollback.advance(&mut gb, Buttons::default());
+    assert!(rollback.correct(&mut gb, 3, Buttons::A));
+}
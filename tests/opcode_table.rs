@@ -0,0 +1,137 @@
+mod common;
+
+use gbemu::{
+    cpu::{Cpu, Registers},
+    instructions::{disassemble, CB_OPCODE_TABLE, OPCODE_TABLE},
+    GameBoy,
+};
+
+/// Runs a single instruction from the entrypoint, returning the cycles taken
+/// and the bytes consumed
+fn run(program: &[u8]) -> (usize, u16) {
+    let rom = common::rom_with_program(program);
+    let mut gb = GameBoy::new(&rom);
+    let cycles = gb.step();
+    (cycles, *gb.registers().pc - 0x0100)
+}
+
+/// Instructions that move PC somewhere else or stop the CPU
+fn is_control_flow(mnemonic: &str) -> bool {
+    ["JR", "JP", "CALL", "RET", "RST", "HALT", "STOP", "PREFIX"]
+        .iter()
+        .any(|prefix| mnemonic.starts_with(prefix))
+}
+
+#[test]
+fn unprefixed_lengths_and_cycles() {
+    let mut mismatches = vec![];
+    for (opcode, info) in OPCODE_TABLE.iter().enumerate() {
+        if !info.is_valid() || is_control_flow(info.mnemonic) {
+            continue;
+        }
+
+        let actual = std::panic::catch_unwind(|| run(&[opcode as u8, 0x00, 0x00]));
+        match actual {
+            Ok((cycles, length))
+                if cycles == info.cycles as usize && length == info.length as u16 => {}
+            Ok((cycles, length)) => mismatches.push(format!(
+                "{:#04x} {}: {} bytes {} cycles, expected {} bytes {} cycles",
+                opcode, info.mnemonic, length, cycles, info.length, info.cycles
+            )),
+            Err(_) => mismatches.push(format!("{:#04x} {}: panicked", opcode, info.mnemonic)),
+        }
+    }
+
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
+
+#[test]
+fn prefixed_lengths_and_cycles() {
+    let mut mismatches = vec![];
+    for (opcode, info) in CB_OPCODE_TABLE.iter().enumerate() {
+        let actual = std::panic::catch_unwind(|| run(&[0xCB, opcode as u8]));
+        match actual {
+            Ok((cycles, length))
+                if cycles == info.cycles as usize && length == info.length as u16 => {}
+            Ok((cycles, length)) => mismatches.push(format!(
+                "{:#06x} {}: {} bytes {} cycles, expected {} bytes {} cycles",
+                0xCB00 | opcode,
+                info.mnemonic,
+                length,
+                cycles,
+                info.length,
+                info.cycles
+            )),
+            Err(_) => mismatches.push(format!(
+                "{:#06x} {}: panicked",
+                0xCB00 | opcode,
+                info.mnemonic
+            )),
+        }
+    }
+
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
+
+#[test]
+fn prefixed_timing_depends_on_the_operand() {
+    for (opcode, info) in CB_OPCODE_TABLE.iter().enumerate() {
+        let expected = match (opcode & 0b111, opcode) {
+            (6, 0x40..=0x7F) => 12,
+            (6, _) => 16,
+            _ => 8,
+        };
+        assert_eq!(info.cycles as usize, expected, "{}", info.mnemonic);
+        assert_eq!(run(&[0xCB, opcode as u8]).0, expected, "{}", info.mnemonic);
+    }
+}
+
+#[test]
+fn disassembly_fills_the_operands() {
+    let rom = common::rom_with_program(&[
+        0x01, 0x34, 0x12, // 0x0100: LD BC,$1234
+        0x18, 0xFC, // 0x0103: JR $0101
+        0xE0, 0x44, // 0x0105: LDH [$FF44],A
+        0xF8, 0xFE, // 0x0107: LD HL,SP-2
+        0xCB, 0x7C, // 0x0109: BIT 7,H
+    ]);
+    let gb = GameBoy::new(&rom);
+
+    let mut address = 0x0100;
+    let mut lines = vec![];
+    for _ in 0..5 {
+        let (text, length) = disassemble(&gb, address);
+        lines.push(text);
+        address += length as usize;
+    }
+    assert_eq!(
+        lines,
+        [
+            "LD BC,$1234",
+            "JR $0101",
+            "LDH [$FF44],A ; LY",
+            "LD HL,SP-2",
+            "BIT 7,H"
+        ]
+    );
+}
+
+#[test]
+fn executed_instructions_are_checked_against_the_table() {
+    let nop = &OPCODE_TABLE[0x00];
+    assert_eq!(nop.check(1, 4), Ok(()));
+    let mismatch = nop.check(2, 8).unwrap_err();
+    assert_eq!(
+        mismatch.to_string(),
+        "NOP moved PC by 2 and took 8 cycles, the table says 1 bytes and 4 cycles"
+    );
+
+    // JR NZ,e8: PC goes anywhere, the cycles depend on the condition
+    let jr_nz = &OPCODE_TABLE[0x20];
+    assert_eq!(jr_nz.check(0xFFF0, 8), Ok(()));
+    assert_eq!(jr_nz.check(0xFFF0, 12), Ok(()));
+    assert!(jr_nz.check(2, 16).is_err());
+
+    // Invalid opcodes lock up, there is nothing to check
+    assert_eq!(OPCODE_TABLE[0xD3].check(0, 0), Ok(()));
+}
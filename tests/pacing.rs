@@ -0,0 +1,132 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use gbemu::{
+    cpu::{CPU_CLOCK_SPEED, CYCLES_PER_FRAME},
+    pacing::{Clock, FrameLimiter, MAX_DRIFT, SPIN_THRESHOLD},
+};
+
+/// Time advanced by each spin
+const SPIN: Duration = Duration::from_micros(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wait {
+    Sleep(Duration),
+    Spin,
+}
+
+/// Host time that only moves when the limiter waits, or the test says so
+#[derive(Clone, Default)]
+struct FakeClock {
+    now: Rc<Cell<Duration>>,
+    waits: Rc<RefCell<Vec<Wait>>>,
+}
+
+impl FakeClock {
+    fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+    fn take_waits(&self) -> Vec<Wait> {
+        self.waits.take()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.waits.borrow_mut().push(Wait::Sleep(duration));
+        self.advance(duration);
+    }
+
+    fn spin(&self) {
+        self.waits.borrow_mut().push(Wait::Spin);
+        self.advance(SPIN);
+    }
+}
+
+/// Host time of the frames, accounted from the total cycles like the limiter
+fn frames(count: u64) -> Duration {
+    Duration::from_secs_f64((count * CYCLES_PER_FRAME) as f64 / CPU_CLOCK_SPEED)
+}
+
+/// Sleeps until the last stretch, then spins through it
+fn expected_waits(remaining: Duration) -> Vec<Wait> {
+    let mut waits = vec![];
+    let mut spun = Duration::ZERO;
+    if remaining > SPIN_THRESHOLD {
+        waits.push(Wait::Sleep(remaining - SPIN_THRESHOLD));
+        spun = remaining - SPIN_THRESHOLD;
+    }
+    while spun < remaining {
+        waits.push(Wait::Spin);
+        spun += SPIN;
+    }
+    waits
+}
+
+#[test]
+fn frames_on_time_wait_for_the_host() {
+    let clock = FakeClock::default();
+    let mut limiter = FrameLimiter::with_clock(clock.clone());
+
+    limiter.wait_frame();
+    assert_eq!(clock.take_waits(), expected_waits(frames(1)));
+    assert!(clock.now() >= frames(1));
+
+    // Half the frame went into emulating it
+    let start = clock.now();
+    clock.advance(frames(1) / 2);
+    limiter.wait_frame();
+    assert_eq!(
+        clock.take_waits(),
+        expected_waits(frames(2) - start - frames(1) / 2)
+    );
+    assert!(clock.now() >= frames(2));
+}
+
+#[test]
+fn short_waits_only_spin() {
+    let clock = FakeClock::default();
+    let mut limiter = FrameLimiter::with_clock(clock.clone());
+
+    clock.advance(frames(1) - SPIN_THRESHOLD);
+    limiter.wait_frame();
+    let waits = clock.take_waits();
+    assert!(waits.iter().all(|wait| *wait == Wait::Spin), "{:?}", waits);
+    assert_eq!(waits, expected_waits(SPIN_THRESHOLD));
+}
+
+#[test]
+fn late_frames_catch_up_within_the_drift() {
+    let clock = FakeClock::default();
+    let mut limiter = FrameLimiter::with_clock(clock.clone());
+
+    // Three frames worth of time for the first one: the next two don't wait
+    clock.advance(frames(3));
+    for _ in 0..3 {
+        limiter.wait_frame();
+    }
+    assert_eq!(clock.take_waits(), []);
+    limiter.wait_frame();
+    assert_eq!(clock.take_waits(), expected_waits(frames(4) - frames(3)));
+}
+
+#[test]
+fn frames_late_past_the_drift_start_over() {
+    let clock = FakeClock::default();
+    let mut limiter = FrameLimiter::with_clock(clock.clone());
+
+    // The backlog is dropped instead of being raced through
+    clock.advance(frames(1) + MAX_DRIFT + Duration::from_millis(1));
+    limiter.wait_frame();
+    assert_eq!(clock.take_waits(), []);
+    limiter.wait_frame();
+    assert_eq!(clock.take_waits(), expected_waits(frames(1)));
+}
@@ -0,0 +1,150 @@
+use gbemu::cartridge::{
+    apply_bps, apply_ips, apply_patch, fix_checksums, global_checksum, header_checksum, verify,
+    CartridgeHeader, HeaderBuilder, PatchError,
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn varint(mut value: usize, out: &mut Vec<u8>) {
+    loop {
+        let bits = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(bits | 0x80);
+            return;
+        }
+        out.push(bits);
+        value -= 1;
+    }
+}
+
+/// Wraps BPS actions with the header and the checksums
+fn bps(source: &[u8], target: &[u8], actions: &[u8]) -> Vec<u8> {
+    let mut patch = b"BPS1".to_vec();
+    varint(source.len(), &mut patch);
+    varint(target.len(), &mut patch);
+    varint(0, &mut patch);
+    patch.extend_from_slice(actions);
+    patch.extend_from_slice(&crc32(source).to_le_bytes());
+    patch.extend_from_slice(&crc32(target).to_le_bytes());
+    patch.extend_from_slice(&crc32(&patch).to_le_bytes());
+    patch
+}
+
+#[test]
+fn ips_records_and_runs() {
+    let rom = vec![0; 16];
+    let mut patch = b"PATCH".to_vec();
+    // 2 bytes at 0x000004
+    patch.extend_from_slice(&[0x00, 0x00, 0x04, 0x00, 0x02, 0xAA, 0xBB]);
+    // A run of 3 0xCC at 0x00000A
+    patch.extend_from_slice(&[0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x03, 0xCC]);
+    // 2 bytes past the end
+    patch.extend_from_slice(&[0x00, 0x00, 0x11, 0x00, 0x02, 0xDD, 0xEE]);
+    patch.extend_from_slice(b"EOF");
+
+    let patched = apply_ips(&rom, &patch).unwrap();
+    assert_eq!(patched.len(), 19);
+    assert_eq!(&patched[4..6], [0xAA, 0xBB]);
+    assert_eq!(&patched[10..13], [0xCC; 3]);
+    assert_eq!(&patched[16..], [0x00, 0xDD, 0xEE]);
+
+    // Truncation extension
+    patch.extend_from_slice(&[0x00, 0x00, 0x08]);
+    assert_eq!(apply_ips(&rom, &patch).unwrap().len(), 8);
+}
+
+#[test]
+fn ips_rejects_broken_patches() {
+    let rom = vec![0; 16];
+    assert_eq!(
+        apply_ips(&rom, b"PATCH\x00\x00\x04\x00\x02\xAA"),
+        Err(PatchError::Truncated)
+    );
+    assert_eq!(
+        apply_ips(&rom, b"PATCH\xFF\xFF\xFF\x00\x00\x00\x02\x00EOF"),
+        Err(PatchError::OutOfBounds { offset: 0xFF_FFFF })
+    );
+    assert_eq!(apply_ips(&rom, b"PITCH"), Err(PatchError::InvalidMagic));
+}
+
+#[test]
+fn bps_actions() {
+    let source = b"ABCDEFGH".to_vec();
+    let target = b"ABxyFGFGFGFGCD".to_vec();
+    let mut actions = vec![];
+    // SourceRead 2: AB
+    varint((2 - 1) << 2, &mut actions);
+    // TargetRead 2: xy
+    varint((2 - 1) << 2 | 1, &mut actions);
+    actions.extend_from_slice(b"xy");
+    // SourceCopy 2 from +5: FG
+    varint((2 - 1) << 2 | 2, &mut actions);
+    varint(5 << 1, &mut actions);
+    // TargetCopy 6 from +4, overlapping its output: FGFGFG
+    varint((6 - 1) << 2 | 3, &mut actions);
+    varint(4 << 1, &mut actions);
+    // SourceCopy 2 from -5 after the last copy (7): CD
+    varint((2 - 1) << 2 | 2, &mut actions);
+    varint(5 << 1 | 1, &mut actions);
+
+    let patch = bps(&source, &target, &actions);
+    assert_eq!(apply_bps(&source, &patch).unwrap(), target);
+    assert_eq!(apply_patch(&source, &patch).unwrap(), target);
+}
+
+#[test]
+fn bps_checks_the_checksums() {
+    let source = b"ABCD".to_vec();
+    let mut actions = vec![];
+    varint((4 - 1) << 2, &mut actions);
+    let patch = bps(&source, &source, &actions);
+
+    assert_eq!(apply_bps(b"ABCE", &patch), Err(PatchError::SourceMismatch));
+
+    let mut corrupted = patch.clone();
+    corrupted[5] ^= 1;
+    assert_eq!(apply_bps(&source, &corrupted), Err(PatchError::Corrupted));
+}
+
+#[test]
+fn unknown_patches_are_rejected() {
+    assert_eq!(apply_patch(&[0; 4], b"UPS1"), Err(PatchError::InvalidMagic));
+}
+
+#[test]
+fn checksums_are_repaired_after_patching() {
+    let rom = HeaderBuilder::new().title("PATCHME").build();
+    assert!(verify(&rom).is_valid());
+
+    // Renames the game
+    let mut patch = b"PATCH\x00\x01\x34\x00\x07PATCHED".to_vec();
+    patch.extend_from_slice(b"EOF");
+    let mut patched = apply_ips(&rom, &patch).unwrap();
+    let before = verify(&patched);
+    assert!(before.logo);
+    assert!(!before.header_checksum && !before.global_checksum);
+    assert!(!before.boots());
+
+    fix_checksums(&mut patched);
+    assert!(verify(&patched).is_valid());
+    assert_eq!(header_checksum(&patched), patched[0x014D]);
+    assert_eq!(
+        global_checksum(&patched).to_be_bytes(),
+        patched[0x014E..=0x014F]
+    );
+    assert_eq!(CartridgeHeader::from(patched.as_slice()).title, "PATCHED");
+}
@@ -0,0 +1,208 @@
+mod common;
+
+use gbemu::{
+    cpu::CYCLES_PER_FRAME,
+    determinism::{AuditMode, Nondeterminism},
+    memory::{Read, Write},
+    rtc::{ManualTime, RtcRegisters, RtcTime, FOOTER_SIZE},
+    savestate::SaveState,
+    GameBoy,
+};
+
+const SECONDS: u8 = 0x08;
+const MINUTES: u8 = 0x09;
+const HOURS: u8 = 0x0A;
+const DAYS_LOW: u8 = 0x0B;
+const DAYS_HIGH: u8 = 0x0C;
+
+const START: u64 = 1_700_000_000;
+
+/// MBC3+TIMER+RAM+BATTERY with 32 KiB of RAM
+fn rom() -> Vec<u8> {
+    // JR -2
+    common::rom_with_header(&[0x18, 0xFE], 0x10, 0x00, 0x03)
+}
+
+/// A console whose frames last exactly one second
+fn new_console(time: RtcTime) -> GameBoy {
+    let mut gb = GameBoy::builder(&rom())
+        .clock_speed(CYCLES_PER_FRAME as f64)
+        .rtc_time(time)
+        .build();
+    // Enable RAM and clock
+    gb.write_u8(0x0000, 0x0A);
+    gb
+}
+
+fn latch(gb: &mut GameBoy) {
+    gb.write_u8(0x6000, 0x00);
+    gb.write_u8(0x6000, 0x01);
+}
+
+fn read(gb: &mut GameBoy, register: u8) -> u8 {
+    gb.write_u8(0x4000, register);
+    gb.read_u8(0xA000)
+}
+
+fn write(gb: &mut GameBoy, register: u8, value: u8) {
+    gb.write_u8(0x4000, register);
+    gb.write_u8(0xA000, value);
+}
+
+fn latched(gb: &mut GameBoy) -> [u8; 5] {
+    [SECONDS, MINUTES, HOURS, DAYS_LOW, DAYS_HIGH].map(|register| read(gb, register))
+}
+
+fn manual(time: &ManualTime) -> RtcTime {
+    RtcTime::Source(Box::new(time.clone()))
+}
+
+#[test]
+fn emulated_time_advances_with_the_frames() {
+    let mut gb = new_console(RtcTime::Emulated);
+    for _ in 0..61 {
+        gb.run_frame();
+    }
+    // Until latched the registers keep their value
+    assert_eq!(latched(&mut gb), [0, 0, 0, 0, 0]);
+    latch(&mut gb);
+    assert_eq!(latched(&mut gb), [1, 1, 0, 0, 0]);
+
+    // Latching needs a 0 to 1 transition
+    gb.run_frame();
+    gb.write_u8(0x6000, 0x01);
+    assert_eq!(read(&mut gb, SECONDS), 1);
+    latch(&mut gb);
+    assert_eq!(read(&mut gb, SECONDS), 2);
+}
+
+#[test]
+fn time_sources_set_the_pace() {
+    let time = ManualTime::new(START);
+    let mut gb = new_console(manual(&time));
+    // Frozen while the source does not move
+    gb.run_frame();
+    latch(&mut gb);
+    assert_eq!(latched(&mut gb), [0, 0, 0, 0, 0]);
+
+    time.advance(25 * 3600 + 2 * 60 + 3);
+    latch(&mut gb);
+    assert_eq!(latched(&mut gb), [3, 2, 1, 1, 0]);
+
+    time.advance(300 * 24 * 3600);
+    latch(&mut gb);
+    assert_eq!(read(&mut gb, DAYS_LOW), (301 - 256) as u8);
+    assert_eq!(read(&mut gb, DAYS_HIGH), 0b0000_0001);
+    // Past 511 days the counter wraps and the carry is set
+    time.advance(300 * 24 * 3600);
+    latch(&mut gb);
+    assert_eq!(read(&mut gb, DAYS_LOW), (601 - 512) as u8);
+    assert_eq!(read(&mut gb, DAYS_HIGH), 0b1000_0000);
+}
+
+#[test]
+fn games_set_and_halt_the_clock() {
+    let time = ManualTime::new(START);
+    let mut gb = new_console(manual(&time));
+    write(&mut gb, DAYS_HIGH, 0b0100_0000);
+    write(&mut gb, HOURS, 23);
+    write(&mut gb, MINUTES, 59);
+    write(&mut gb, SECONDS, 50);
+    assert_eq!(latched(&mut gb), [50, 59, 23, 0, 0b0100_0000]);
+
+    // Halted
+    time.advance(100);
+    latch(&mut gb);
+    assert_eq!(read(&mut gb, SECONDS), 50);
+
+    write(&mut gb, DAYS_HIGH, 0);
+    time.advance(15);
+    assert_eq!(
+        gb.rtc(),
+        RtcRegisters {
+            seconds: 5,
+            minutes: 0,
+            hours: 0,
+            days: 1,
+            halted: false,
+            carry: false,
+        }
+    );
+}
+
+#[test]
+fn footers_fast_forward_the_clock() {
+    let time = ManualTime::new(START);
+    let mut gb = new_console(manual(&time));
+    gb.set_rtc(RtcRegisters {
+        minutes: 10,
+        ..RtcRegisters::default()
+    });
+    latch(&mut gb);
+    let mut save = gb.battery_ram().unwrap().to_vec();
+    let footer = gb.rtc_footer().unwrap();
+    assert_eq!(footer[40..], START.to_le_bytes());
+    save.extend(footer);
+
+    // An hour later, also from the 44 bytes variant
+    for len in [FOOTER_SIZE, FOOTER_SIZE - 4] {
+        let later = ManualTime::new(START + 3600);
+        let mut gb = new_console(manual(&later));
+        gb.load_battery_ram(&save[..save.len() - FOOTER_SIZE + len]);
+        assert_eq!(read(&mut gb, MINUTES), 10, "Footer of {} bytes", len);
+        latch(&mut gb);
+        assert_eq!(read(&mut gb, MINUTES), 10);
+        assert_eq!(read(&mut gb, HOURS), 1);
+    }
+
+    // Emulated time has no timestamp, the clock resumes where it stopped
+    let mut gb = new_console(RtcTime::Emulated);
+    gb.load_battery_ram(&save);
+    assert_eq!(gb.rtc().minutes, 10);
+    assert_eq!(gb.rtc().hours, 0);
+    assert_eq!(gb.rtc_footer().unwrap()[40..], [0; 8]);
+}
+
+#[test]
+fn save_states_keep_the_clock() {
+    let mut gb = new_console(RtcTime::Emulated);
+    for _ in 0..5 {
+        gb.run_frame();
+    }
+    let state = gb.save_state();
+    for _ in 0..5 {
+        gb.run_frame();
+    }
+    assert_eq!(gb.rtc().seconds, 10);
+    gb.load_state(&state).unwrap();
+    assert_eq!(gb.rtc().seconds, 5);
+
+    // States from before the clock ran load with it stopped at 0: version 1
+    // had neither the clock (18 bytes) nor the fields of version 3 after it,
+    // 72 bytes with the default features
+    let mut bytes = state.to_bytes();
+    bytes[8..10].copy_from_slice(&1u16.to_le_bytes());
+    bytes.truncate(bytes.len() - 18 - 72);
+    gb.load_state(&SaveState::from_bytes(&bytes).unwrap())
+        .unwrap();
+    assert_eq!(gb.rtc(), RtcRegisters::default());
+}
+
+#[test]
+fn host_time_is_nondeterministic() {
+    let mut gb = GameBoy::builder(&rom())
+        .rtc_time(RtcTime::host())
+        .determinism_audit(AuditMode::Report)
+        .build();
+    gb.take_nondeterminism();
+    gb.rtc();
+    assert_eq!(gb.take_nondeterminism(), [Nondeterminism::HostRtc]);
+}
+
+#[test]
+fn only_cartridges_with_a_clock_have_a_footer() {
+    // MBC3+RAM+BATTERY
+    let rom = common::rom_with_header(&[0x18, 0xFE], 0x13, 0x00, 0x03);
+    let mut gb = GameBoy::new(&rom);
+    assert_eq!(gb.rtc_footer(), None);
+}
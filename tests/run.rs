@@ -0,0 +1,120 @@
+mod common;
+
+use gbemu::{
+    cartridge,
+    cpu::{Cpu, Registers},
+    memory::{locations, Read},
+    run::{Condition, Timeout},
+    GameBoy, Interrupt,
+};
+
+#[test]
+fn stops_at_the_first_condition_met() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let condition = Condition::Any(vec![Condition::PcEquals(0x1234), Condition::Frames(2)]);
+
+    assert_eq!(gb.run_until(&condition, 10), Ok(&Condition::Frames(2)));
+    assert_eq!(gb.frames(), 2);
+    assert_eq!(
+        gb.run_until(&Condition::Cycles(100), 10),
+        Ok(&Condition::Cycles(100))
+    );
+}
+
+#[test]
+fn times_out() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+
+    assert_eq!(
+        gb.run_until(&Condition::PcEquals(0x1234), 3),
+        Err(Timeout { frames: 3 })
+    );
+}
+
+#[test]
+fn waits_for_serial_output() {
+    let mut program = vec![];
+    for byte in b"Passed" {
+        // LD A,byte; LDH [SB],A; LD A,0x81; LDH [SC],A
+        program.extend([0x3E, *byte, 0xE0, 0x01, 0x3E, 0x81, 0xE0, 0x02]);
+    }
+    // JR -2
+    program.extend([0x18, 0xFE]);
+    let mut gb = GameBoy::new(&common::rom_with_program(&program));
+
+    let condition = Condition::SerialOutputContains("Passed".into());
+    assert_eq!(gb.run_until(&condition, 1), Ok(&condition));
+    assert_eq!(gb.serial_output(), b"Passed");
+}
+
+#[test]
+fn interrupts_are_serviced_between_instructions() {
+    // LD A,0x04; LDH (IE),A; EI; LDH (IF),A; HALT; JR -2
+    let program = [0x3E, 0x04, 0xE0, 0xFF, 0xFB, 0xE0, 0x0F, 0x76, 0x18, 0xFE];
+    let mut rom = common::rom_with_program(&program);
+    // INC B; RETI
+    rom[0x50..0x52].copy_from_slice(&[0x04, 0xD9]);
+    let mut gb = GameBoy::new(&rom);
+    let b = |gb: &GameBoy| gb.registers().bc.to_be_bytes()[0];
+    let start = b(&gb);
+
+    // The handler runs without waiting for the end of a frame
+    assert_eq!(
+        gb.run_until(&Condition::PcEquals(0x0050), 1),
+        Ok(&Condition::PcEquals(0x0050))
+    );
+    assert_eq!(gb.frames(), 0);
+    gb.run_until(&Condition::Frames(1), 2).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(1));
+}
+
+#[test]
+fn requested_interrupts_run_their_handler() {
+    // LD A,0x08; LDH (IE),A; XOR A; LDH (IF),A; EI; JR -2
+    let program = [0x3E, 0x08, 0xE0, 0xFF, 0xAF, 0xE0, 0x0F, 0xFB, 0x18, 0xFE];
+    let mut rom = common::rom_with_program(&program);
+    // INC B; RETI
+    rom[0x58..0x5A].copy_from_slice(&[0x04, 0xD9]);
+    let mut gb = GameBoy::new(&rom);
+    let b = |gb: &GameBoy| gb.registers().bc.to_be_bytes()[0];
+    gb.run_until(&Condition::PcEquals(0x0108), 1).unwrap();
+    gb.step();
+    let start = b(&gb);
+    assert!(gb.registers().ime);
+
+    gb.request_interrupt(Interrupt::SerialTranferComplete);
+    assert_eq!(gb.read_u8(locations::IF) & 0b1_1111, 0b0_1000);
+    assert_eq!(
+        gb.run_until(&Condition::PcEquals(0x0058), 1),
+        Ok(&Condition::PcEquals(0x0058))
+    );
+    assert_eq!(gb.read_u8(locations::IF) & 0b1_1111, 0);
+    gb.run_until(&Condition::PcEquals(0x0108), 1).unwrap();
+    assert_eq!(b(&gb), start.wrapping_add(1));
+}
+
+#[test]
+fn the_timer_runs_while_stepping() {
+    // LD A,0x05; LDH (TAC),A; LD A,0x04; LDH (IE),A; XOR A; LDH (TIMA),A;
+    // LDH (IF),A; EI; HALT; JR -2
+    let program = [
+        0x3E, 0x05, 0xE0, 0x07, 0x3E, 0x04, 0xE0, 0xFF, 0xAF, 0xE0, 0x05, 0xE0, 0x0F, 0xFB, 0x76,
+        0x18, 0xFE,
+    ];
+    let mut rom = common::rom_with_program(&program);
+    // INC B; RETI
+    rom[0x50..0x52].copy_from_slice(&[0x04, 0xD9]);
+    let mut gb = GameBoy::new(&rom);
+    let b = |gb: &GameBoy| gb.registers().bc.to_be_bytes()[0];
+    let (start, div) = (b(&gb), gb.read_u8(locations::DIV));
+
+    // TIMA overflows every 256 * 16 T-cycles
+    gb.run_until(&Condition::PcEquals(0x0050), 1).unwrap();
+    gb.run_until(&Condition::Frames(1), 2).unwrap();
+    let overflows = b(&gb).wrapping_sub(start) as u64;
+    assert!(overflows.abs_diff(gb.cycles() / 4096) <= 1, "{}", overflows);
+    assert_eq!(
+        gb.read_u8(locations::DIV).wrapping_sub(div),
+        (gb.cycles() / 256) as u8
+    );
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use gbemu::{
+    memory::{RamInit, Read, Write},
+    GameBoy,
+};
+
+/// Increments 0xC000 forever
+fn counter() -> GameBoy {
+    // LD HL,0xC000; INC [HL]; JR -3
+    let rom = common::rom_with_program(&[0x21, 0x00, 0xC0, 0x34, 0x18, 0xFD]);
+    GameBoy::builder(&rom).ram_init(RamInit::Zeros).build()
+}
+
+#[test]
+fn paused_consoles_only_advance_on_request() {
+    let mut gb = counter();
+    gb.run_frame();
+    gb.pause();
+    assert!(gb.is_paused());
+
+    let (frames, count) = (gb.frames(), gb.read_u8(0xC000));
+    assert_eq!(gb.run_frame(), 0);
+    gb.run_until_samples(1024);
+    assert_eq!((gb.frames(), gb.read_u8(0xC000)), (frames, count));
+
+    assert!(gb.advance_one_frame() > 0);
+    assert_eq!(gb.frames(), frames + 1);
+
+    gb.resume();
+    gb.run_frame();
+    assert_eq!(gb.frames(), frames + 2);
+}
+
+#[test]
+fn run_ahead_leaves_the_state_of_a_plain_run() {
+    let mut plain = counter();
+    let mut ahead = counter();
+    for _ in 0..5 {
+        plain.run_frame();
+        ahead.run_frame_ahead(2);
+    }
+
+    assert_eq!(ahead.frames(), plain.frames());
+    assert_eq!(ahead.cycles(), plain.cycles());
+    assert_eq!(ahead.state_hash(), plain.state_hash());
+
+    // Writes made after the rollback stick
+    ahead.write_u8(0xC001, 0x42);
+    ahead.run_frame_ahead(1);
+    assert_eq!(ahead.read_u8(0xC001), 0x42);
+}
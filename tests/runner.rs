@@ -0,0 +1,115 @@
+#![cfg(feature = "runner")]
+
+mod common;
+
+use std::time::Duration;
+
+use gbemu::{
+    memory::{Read, Write},
+    runner::{Command, Event, Runner},
+    GameBoy,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends "Hi" on the serial port, then loops forever
+fn new_runner() -> Runner<u8> {
+    let mut program = vec![];
+    for byte in b"Hi" {
+        // LD A,byte; LDH [SB],A; LD A,0x81; LDH [SC],A
+        program.extend([0x3E, *byte, 0xE0, 0x01, 0x3E, 0x81, 0xE0, 0x02]);
+    }
+    // JR -2
+    program.extend([0x18, 0xFE]);
+    let rom = common::rom_with_program(&program);
+
+    let runner = Runner::spawn(
+        move || GameBoy::new(&rom),
+        |gb, input| gb.write_u8(0xC000, *input),
+    );
+    runner.send(Command::Throttle(false));
+    runner
+}
+
+/// Waits for an event, skipping frames and serial output
+fn reply(runner: &Runner<u8>) -> Event {
+    loop {
+        match runner.events().recv_timeout(TIMEOUT).unwrap() {
+            Event::Frame(_) | Event::Serial(_) => {}
+            event => return event,
+        }
+    }
+}
+
+#[test]
+fn frames_and_serial_output_come_out() {
+    let runner = new_runner();
+    let mut serial = vec![];
+    let mut frames = vec![];
+    while frames.len() < 3 {
+        match runner.events().recv_timeout(TIMEOUT).unwrap() {
+            Event::Serial(byte) => serial.push(byte),
+            Event::Frame(number) => frames.push(number),
+            event => panic!("Unexpected {:?}", event),
+        }
+    }
+
+    assert_eq!(serial, b"Hi");
+    assert!(frames.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(runner.front_buffer().lock().number() >= frames[2]);
+}
+
+#[test]
+fn commands_are_replied() {
+    let runner = new_runner();
+
+    runner.send(Command::Pause);
+    assert!(matches!(reply(&runner), Event::Paused));
+    let number = runner.front_buffer().lock().number();
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(runner.front_buffer().lock().number(), number);
+
+    runner.send(Command::SaveState);
+    let Event::State(state) = reply(&runner) else {
+        panic!("Expected a save state");
+    };
+
+    runner.send(Command::Input(0x42));
+    runner.send(Command::Resume);
+    assert!(matches!(reply(&runner), Event::Resumed));
+
+    runner.send(Command::LoadState(state));
+    assert!(matches!(reply(&runner), Event::StateLoaded(Ok(()))));
+    runner.send(Command::Reset);
+    assert!(matches!(reply(&runner), Event::Reset));
+
+    runner.send(Command::Stop);
+    assert!(matches!(reply(&runner), Event::Stopped));
+}
+
+#[test]
+fn inputs_reach_the_console() {
+    let runner = Runner::spawn(
+        || GameBoy::new(&common::rom_with_program(&[0x18, 0xFE])),
+        |gb, input: &u8| {
+            let count = gb.read_u8(0xC000);
+            gb.write_u8(0xC000, count.wrapping_add(*input));
+        },
+    );
+    runner.send(Command::Throttle(false));
+    runner.send(Command::Input(1));
+    runner.send(Command::Pause);
+    assert!(matches!(reply(&runner), Event::Paused));
+    runner.send(Command::SaveState);
+    let Event::State(state) = reply(&runner) else {
+        panic!("Expected a save state");
+    };
+
+    // The input was applied once per frame
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    gb.load_state(&state).unwrap();
+    assert!(gb.read_u8(0xC000) > 0);
+
+    // Dropping the runner stops the thread, even while paused
+    drop(runner);
+}
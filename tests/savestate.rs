@@ -0,0 +1,234 @@
+mod common;
+
+use gbemu::{
+    cartridge,
+    cpu::Registers,
+    memory::{locations, RamInit, Read, Write},
+    savestate::{SaveState, SaveStateError, SlotManager, STATE_VERSION},
+    GameBoy,
+};
+
+/// Bytes appended to the body by version 3, with the default features
+const VERSION_3_FIELDS: usize = 72;
+/// Bytes appended to the body by version 2
+const VERSION_2_FIELDS: usize = 18;
+
+/// Rewrites a state as if an older version wrote it, without the fields it
+/// did not have
+fn downgrade(state: &SaveState, version: u16) -> Vec<u8> {
+    let mut bytes = state.to_bytes();
+    bytes[8..10].copy_from_slice(&version.to_le_bytes());
+    let mut len = bytes.len() - VERSION_3_FIELDS;
+    if version < 2 {
+        len -= VERSION_2_FIELDS;
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+#[test]
+fn runs_resume_identically_after_a_load() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    // Mid-frame, mid-line, with the timer counting
+    gb.write_u8(locations::TAC, 0b101);
+    gb.run_frame();
+    gb.run_until_samples(123);
+    let state = gb.save_state();
+
+    let run = |gb: &mut GameBoy| {
+        for _ in 0..3 {
+            gb.run_frame();
+        }
+        (gb.state_hash(), gb.frame().to_vec())
+    };
+    let expected = run(&mut gb);
+    gb.load_state(&state).unwrap();
+    assert_eq!(run(&mut gb), expected);
+
+    // From the bytes, into a fresh console
+    let mut loaded = GameBoy::new(&cartridge::test_rom());
+    loaded
+        .load_state(&SaveState::from_bytes(&state.to_bytes()).unwrap())
+        .unwrap();
+    assert_eq!(run(&mut loaded), expected);
+}
+
+#[test]
+fn slots_round_trip_through_files() {
+    let dir = std::env::temp_dir().join(format!("gbemu-slots-{}", std::process::id()));
+    let slots = SlotManager::new(&dir, "../GAME/TITLE");
+    // The title can't escape the directory
+    assert_eq!(slots.slot_path(1), dir.join("___GAME_TITLE.ss1"));
+
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    assert_eq!(slots.occupied_slots(), []);
+    gb.run_frame();
+    slots.save_slot(&gb, 1, Some(vec![1, 2, 3])).unwrap();
+    let pc = *gb.registers().pc;
+    gb.run_frame();
+    slots.save_slot(&gb, 3, None).unwrap();
+    assert_eq!(slots.occupied_slots(), [1, 3]);
+
+    assert_eq!(slots.read_slot(1).unwrap().thumbnail, Some(vec![1, 2, 3]));
+    assert_eq!(slots.read_slot(3).unwrap().thumbnail, None);
+    slots.load_slot(&mut gb, 1).unwrap();
+    assert_eq!(*gb.registers().pc, pc);
+    assert!(matches!(
+        slots.load_slot(&mut gb, 2),
+        Err(SaveStateError::Io(_))
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn newer_versions_are_refused() {
+    let gb = GameBoy::new(&cartridge::test_rom());
+    let mut bytes = gb.save_state().to_bytes();
+    bytes[8..10].copy_from_slice(&(STATE_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        SaveState::from_bytes(&bytes),
+        Err(SaveStateError::UnsupportedVersion(version)) if version == STATE_VERSION + 1
+    ));
+}
+
+#[test]
+fn older_versions_are_migrated() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    gb.run_frame();
+    let state = gb.save_state();
+    let hash = gb.state_hash();
+    gb.run_frame();
+
+    for version in [1, 2] {
+        let migrated = SaveState::from_bytes(&downgrade(&state, version)).unwrap();
+        gb.load_state(&migrated).unwrap();
+        assert_eq!(gb.state_hash(), hash);
+    }
+
+    let mut bytes = downgrade(&state, 1);
+    bytes[8..10].copy_from_slice(&0u16.to_le_bytes());
+    assert!(matches!(
+        SaveState::from_bytes(&bytes),
+        Err(SaveStateError::UnsupportedVersion(0))
+    ));
+}
+
+#[test]
+fn malformed_states_are_refused() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let bytes = gb.save_state().to_bytes();
+
+    assert!(matches!(
+        SaveState::from_bytes(b"NOTSTATE\x03\x00"),
+        Err(SaveStateError::InvalidMagic)
+    ));
+    assert!(matches!(
+        SaveState::from_bytes(&bytes[..12]),
+        Err(SaveStateError::Truncated)
+    ));
+
+    // The header is complete, the body is cut short
+    let truncated = SaveState::from_bytes(&bytes[..bytes.len() - 1]).unwrap();
+    let hash = gb.state_hash();
+    assert!(matches!(
+        gb.load_state(&truncated),
+        Err(SaveStateError::Truncated)
+    ));
+    assert_eq!(gb.state_hash(), hash);
+
+    let other = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    assert!(matches!(
+        gb.load_state(&other.save_state()),
+        Err(SaveStateError::CartridgeMismatch)
+    ));
+}
+
+#[test]
+fn deltas_restore_the_state() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let baseline = gb.save_state();
+    gb.run_frame();
+    let delta = gb.state_delta(&baseline);
+    assert!(delta.len() < baseline.to_bytes().len() / 10);
+
+    let mut peer = GameBoy::new(&cartridge::test_rom());
+    peer.apply_delta(&baseline, &delta).unwrap();
+    assert_eq!(peer.state_hash(), gb.state_hash());
+    assert_eq!(peer.save_state(), gb.save_state());
+
+    // Against itself, nothing changed
+    let delta = gb.state_delta(&gb.save_state());
+    peer.apply_delta(&gb.save_state(), &delta).unwrap();
+    assert_eq!(peer.state_hash(), gb.state_hash());
+}
+
+#[test]
+fn malformed_deltas_are_refused() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    let baseline = gb.save_state();
+    gb.run_frame();
+    let delta = gb.state_delta(&baseline);
+    let mut peer = GameBoy::new(&cartridge::test_rom());
+    let hash = peer.state_hash();
+
+    assert!(matches!(
+        peer.apply_delta(&baseline, &delta[..delta.len() - 1]),
+        Err(SaveStateError::Truncated)
+    ));
+
+    // A body far longer than the baseline and the delta could describe
+    let mut huge = delta.clone();
+    huge[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        peer.apply_delta(&baseline, &huge),
+        Err(SaveStateError::Corrupted)
+    ));
+
+    // A skip overflowing the position after a first record, then a record
+    // past the end
+    let len = (baseline.to_bytes().len() as u32).to_le_bytes();
+    let overflow = [&len[..], &[0x00, 0x01, 0x00], &[0xFF; 9], &[0x01, 0x00]].concat();
+    assert!(matches!(
+        peer.apply_delta(&baseline, &overflow),
+        Err(SaveStateError::Corrupted)
+    ));
+    let past_end = [&len[..], &[0xFF, 0xFF, 0x7F, 0x01, 0x00]].concat();
+    assert!(matches!(
+        peer.apply_delta(&baseline, &past_end),
+        Err(SaveStateError::Corrupted)
+    ));
+
+    assert_eq!(peer.state_hash(), hash);
+}
+
+#[test]
+fn state_hashes_follow_the_state() {
+    let run = || {
+        let mut gb = GameBoy::builder(&cartridge::test_rom())
+            .ram_init(RamInit::Seeded(7))
+            .build();
+        for _ in 0..3 {
+            gb.run_frame();
+        }
+        gb
+    };
+    let mut gb = run();
+    let hash = gb.state_hash();
+    assert_eq!(run().state_hash(), hash);
+
+    // Reloading the state the hash was taken on gives it back
+    let state = gb.save_state();
+    gb.run_frame();
+    assert_ne!(gb.state_hash(), hash);
+    gb.load_state(&state).unwrap();
+    assert_eq!(gb.state_hash(), hash);
+
+    // A single byte of WRAM changes it
+    let mut other = run();
+    let value = other.read_u8(0xD000);
+    other.write_u8(0xD000, value ^ 1);
+    assert_ne!(other.state_hash(), hash);
+    other.write_u8(0xD000, value);
+    assert_eq!(other.state_hash(), hash);
+}
@@ -0,0 +1,351 @@
+mod common;
+
+use std::{cell::RefCell, net::TcpListener, rc::Rc, thread, time::Duration};
+
+use gbemu::{
+    cpu::Cpu,
+    memory::{locations, RamInit, Read, Write},
+    serial::{
+        mobile, FourPlayerAdapter, MobileAdapter, Phase, SerialDevice, TcpLink, TextSink,
+        TRANSFER_DOTS,
+    },
+    GameBoy,
+};
+
+/// Loads SB, starts a transfer with the given SC value and waits for its
+/// end, then loops forever
+fn transfer(byte: u8, sc: u8) -> Vec<u8> {
+    common::rom_with_program(&[
+        0x3E, byte, // LD A,byte
+        0xE0, 0x01, // LDH [SB],A
+        0x3E, sc, // LD A,sc
+        0xE0, 0x02, // LDH [SC],A
+        0xF0, 0x02, // LDH A,[SC]
+        0xCB, 0x7F, // BIT 7,A
+        0x20, 0xFA, // JR NZ,-6
+        0x18, 0xFE, // JR -2
+    ])
+}
+
+/// Answers every transfer with the byte sent plus one
+struct Incrementer(Option<u8>);
+
+impl SerialDevice for Incrementer {
+    fn send(&mut self, byte: u8) {
+        self.0 = Some(byte);
+    }
+
+    fn receive(&mut self) -> u8 {
+        self.0.take().unwrap().wrapping_add(1)
+    }
+}
+
+#[test]
+fn transfers_take_eight_bit_times() {
+    let mut gb = GameBoy::new(&transfer(0x42, 0x81));
+    // Up to the write starting the transfer
+    for _ in 0..3 {
+        gb.step();
+    }
+    let start = gb.cycles();
+    gb.step();
+    assert_eq!(gb.serial_output(), [0x42]);
+
+    while gb.read_u8(locations::SC) & 0x80 != 0 {
+        gb.step();
+        assert!(
+            gb.cycles() - start < 2 * TRANSFER_DOTS,
+            "transfer never ended"
+        );
+    }
+    assert!(gb.cycles() - start >= TRANSFER_DOTS);
+    // Nothing plugged, the line idles high
+    assert_eq!(gb.read_u8(locations::SB), 0xFF);
+    assert_eq!(gb.read_u8(locations::IF) & 0b1000, 0b1000);
+}
+
+#[test]
+fn devices_answer_transfers() {
+    let mut gb = GameBoy::new(&transfer(0x42, 0x81));
+    gb.connect_serial(Incrementer(None));
+    gb.run_frame();
+    assert_eq!(gb.read_u8(locations::SB), 0x43);
+
+    assert!(gb.disconnect_serial().is_some());
+}
+
+#[test]
+fn text_sink_collects_printable_lines() {
+    let mut sink = TextSink::new();
+    sink.extend(b"Passed\r\n\x01\tall\xFF\n\nte");
+    assert_eq!(sink.lines(), ["Passed", "\tall", ""]);
+    assert_eq!(sink.partial_line(), "te");
+    assert_eq!(sink.text(), "Passed\n\tall\n\nte");
+
+    sink.flush();
+    assert_eq!(sink.lines().last().unwrap(), "te");
+    assert_eq!(sink.partial_line(), "");
+}
+
+#[test]
+fn text_sink_gives_lines_to_the_callback() {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let mut sink = TextSink::with_callback({
+        let lines = lines.clone();
+        move |line| lines.borrow_mut().push(line.to_string())
+    });
+    sink.extend(b"one\ntwo\nthr");
+    assert_eq!(*lines.borrow(), ["one", "two"]);
+    assert!(sink.lines().is_empty());
+    assert_eq!(sink.text(), "thr");
+}
+
+#[test]
+fn text_sink_decodes_the_serial_output_once() {
+    let mut gb = GameBoy::new(&transfer(b'A', 0x81));
+    let mut sink = TextSink::new();
+    gb.run_frame();
+    sink.update(&gb);
+    sink.update(&gb);
+    assert_eq!(sink.text(), "A");
+}
+
+#[test]
+fn external_clock_waits_without_a_peer() {
+    let mut gb = GameBoy::new(&transfer(0x42, 0x80));
+    gb.run_frame();
+    assert_eq!(gb.read_u8(locations::SC), 0x80);
+    assert!(gb.serial_output().is_empty());
+}
+
+#[test]
+fn tcp_links_trade_bytes() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // The console clocking the transfers blocks for the answers, each one
+    // runs on its own thread
+    let slave = thread::spawn(move || {
+        let mut gb = GameBoy::new(&transfer(0x24, 0x80));
+        gb.connect_serial(TcpLink::accept(&listener).unwrap());
+        for _ in 0..120 {
+            gb.run_frame();
+            if gb.read_u8(locations::SC) & 0x80 == 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        gb.read_u8(locations::SB)
+    });
+    let mut gb = GameBoy::new(&transfer(0x42, 0x81));
+    let link = TcpLink::connect(address)
+        .unwrap()
+        .timeout(Duration::from_secs(5));
+    gb.connect_serial(link);
+    gb.run_frame();
+
+    assert_eq!(gb.read_u8(locations::SB), 0x24);
+    assert_eq!(slave.join().unwrap(), 0x42);
+}
+
+#[test]
+fn tcp_links_time_out_without_a_listener() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let peer = thread::spawn(move || TcpLink::accept(&listener).unwrap());
+
+    let mut link = TcpLink::connect(address)
+        .unwrap()
+        .timeout(Duration::from_millis(20));
+    let mut peer = peer.join().unwrap();
+    link.send(0x42);
+    assert_eq!(link.receive(), 0xFF);
+    // The transfer was cancelled, the peer does not see it
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(peer.poll_external(0x24), None);
+    assert!(link.is_connected());
+}
+
+#[test]
+fn simultaneous_transfers_read_idle_line() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let peer = thread::spawn(move || {
+        let mut link = TcpLink::accept(&listener)
+            .unwrap()
+            .timeout(Duration::from_secs(5));
+        link.send(0x11);
+        link.receive()
+    });
+
+    let mut link = TcpLink::connect(address)
+        .unwrap()
+        .timeout(Duration::from_secs(5));
+    link.send(0x22);
+    assert_eq!(link.receive(), 0xFF);
+    assert_eq!(peer.join().unwrap(), 0xFF);
+}
+
+/// Sends the bytes of a table at 0xD000 with the external clock, one per
+/// transfer, storing the bytes received from 0xC000
+fn adapter_player(table: &[u8]) -> GameBoy {
+    let rom = common::rom_with_program(&[
+        0x21, 0x00, 0xC0, // LD HL,0xC000
+        0x11, 0x00, 0xD0, // LD DE,0xD000
+        0x1A, // LD A,[DE]
+        0x13, // INC DE
+        0xE0, 0x01, // LDH [SB],A
+        0x3E, 0x80, // LD A,0x80
+        0xE0, 0x02, // LDH [SC],A
+        0xF0, 0x02, // LDH A,[SC]
+        0xCB, 0x7F, // BIT 7,A
+        0x20, 0xFA, // JR NZ,-6
+        0xF0, 0x01, // LDH A,[SB]
+        0x22, // LD [HL+],A
+        0x18, 0xED, // JR -19
+    ]);
+    let mut gb = GameBoy::builder(&rom).ram_init(RamInit::Zeros).build();
+    for (i, byte) in table.iter().enumerate() {
+        gb.write_u8(0xD000 + i, *byte);
+    }
+    gb
+}
+
+#[test]
+fn four_player_adapter_relays_packets() {
+    let mut consoles = [
+        adapter_player(&[
+            0x88, 0x88, 0x00, 0x02, // Ping
+            0xAA, 0x88, 0x00, 0x02, // Ping, start
+            0x00, 0x00, 0x00, 0x00, // Restart
+            0x11, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Data
+        ]),
+        adapter_player(&[
+            0x88, 0x88, 0x00, 0x00, // Ping
+            0x88, 0x88, 0x00, 0x00, // Ping
+            0x00, 0x00, 0x00, 0x00, // Restart
+            0x33, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Data
+        ]),
+    ];
+    let mut adapter = FourPlayerAdapter::new();
+    for _ in 0..3 {
+        adapter.run_frame(&mut consoles);
+    }
+    assert_eq!(adapter.connected(), 0b11);
+    assert_eq!(adapter.phase(), Phase::Transmission);
+    assert_eq!(adapter.packet_size(), 2);
+    for _ in 0..2 {
+        adapter.run_frame(&mut consoles);
+    }
+
+    let received = |gb: &GameBoy, range: std::ops::Range<usize>| {
+        range.map(|i| gb.read_u8(0xC000 + i)).collect::<Vec<_>>()
+    };
+    assert_eq!(received(&consoles[0], 0..4), [0xFE, 0x01, 0x01, 0x01]);
+    assert_eq!(received(&consoles[1], 4..8), [0xFE, 0x32, 0x32, 0x32]);
+    assert_eq!(received(&consoles[1], 8..12), [0xCC; 4]);
+    // The data of the first packet, in the second one
+    for gb in &consoles {
+        assert_eq!(
+            received(gb, 20..28),
+            [0x11, 0x22, 0x33, 0x44, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+    }
+}
+
+/// Sends a packet to the adapter as the console would, returning the reply
+/// command and data
+fn mobile_command(adapter: &mut MobileAdapter, command: u8, data: &[u8]) -> (u8, Vec<u8>) {
+    let mut exchange = |byte: u8| {
+        adapter.send(byte);
+        adapter.receive()
+    };
+
+    let mut body = vec![command, 0x00];
+    body.extend((data.len() as u16).to_be_bytes());
+    body.extend(data);
+    let checksum = body.iter().map(|byte| *byte as u16).sum::<u16>();
+    for byte in [0x99, 0x66]
+        .iter()
+        .chain(&body)
+        .chain(&checksum.to_be_bytes())
+    {
+        assert_eq!(exchange(*byte), mobile::IDLE);
+    }
+    assert_eq!(exchange(0x81), mobile::ADAPTER_ID);
+    assert_eq!(exchange(0x00), command ^ 0x80);
+
+    // The reply, clocked by the console idling
+    let mut reply = vec![];
+    while reply.len() < 6 || reply.len() < 8 + u16::from_be_bytes([reply[4], reply[5]]) as usize {
+        reply.push(exchange(0x4B));
+    }
+    assert_eq!(reply[..2], [0x99, 0x66]);
+    let sum = reply[2..reply.len() - 2]
+        .iter()
+        .map(|byte| *byte as u16)
+        .sum::<u16>();
+    assert_eq!(reply[reply.len() - 2..], sum.to_be_bytes());
+    assert_eq!(exchange(0x80), mobile::ADAPTER_ID);
+    assert_eq!(exchange(command ^ 0x80), 0x00);
+    assert_eq!(exchange(0x4B), mobile::IDLE);
+
+    (reply[2], reply[6..reply.len() - 2].to_vec())
+}
+
+#[test]
+fn mobile_adapter_answers_commands() {
+    let mut adapter = MobileAdapter::new()
+        .server_data(*b"HTTP/1.0 200 OK")
+        .dns("gameboy.datacenter.ne.jp", [10, 0, 0, 1]);
+
+    assert_eq!(
+        mobile_command(&mut adapter, mobile::BEGIN_SESSION, b"NINTENDO"),
+        (0x90, b"NINTENDO".to_vec())
+    );
+    assert_eq!(
+        mobile_command(&mut adapter, mobile::WRITE_CONFIG, &[0x10, 1, 2, 3]),
+        (0x9A, vec![0x10, 3])
+    );
+    assert_eq!(
+        mobile_command(&mut adapter, mobile::READ_CONFIG, &[0x0F, 5]),
+        (0x99, vec![0x0F, 0, 1, 2, 3, 0])
+    );
+    assert_eq!(mobile_command(&mut adapter, mobile::DIAL, b"#9677").0, 0x92);
+    assert_eq!(
+        mobile_command(&mut adapter, mobile::TELEPHONE_STATUS, &[]).1[0],
+        0x05
+    );
+    assert_eq!(
+        mobile_command(&mut adapter, mobile::DNS_QUERY, b"gameboy.datacenter.ne.jp"),
+        (0xA8, vec![10, 0, 0, 1])
+    );
+    let (command, data) = mobile_command(&mut adapter, mobile::TRANSFER_DATA, &[0x00]);
+    assert_eq!(command, 0x95);
+    assert_eq!(data[1..], *b"HTTP/1.0 200 OK");
+    // Unknown command
+    assert_eq!(
+        mobile_command(&mut adapter, 0x7F, &[]),
+        (0x6E, vec![0x7F, 0x00])
+    );
+
+    let packets = adapter.take_packets();
+    assert_eq!(packets.len(), 8);
+    assert_eq!(packets[2].command, mobile::READ_CONFIG);
+    assert_eq!(adapter.config()[0x10..0x13], [1, 2, 3]);
+}
+
+#[test]
+fn mobile_adapter_rejects_bad_checksums() {
+    let mut adapter = MobileAdapter::new();
+    let mut exchange = |byte: u8| {
+        adapter.send(byte);
+        adapter.receive()
+    };
+    for byte in [0x99, 0x66, 0x10, 0x00, 0x00, 0x00, 0x00, 0x11] {
+        exchange(byte);
+    }
+    assert_eq!(exchange(0x81), mobile::ADAPTER_ID);
+    assert_eq!(exchange(0x00), 0xF1);
+    assert_eq!(exchange(0x4B), mobile::IDLE);
+}
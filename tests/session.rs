@@ -0,0 +1,80 @@
+mod common;
+
+use gbemu::{
+    memory::{RamInit, Read, Write},
+    session::{Session, SessionHandle},
+    GameBoy,
+};
+
+/// Adds every input to a WRAM counter, where the test can read it back
+fn new_session(capacity: usize) -> (Session<u8>, SessionHandle<u8>) {
+    let rom = common::rom_with_program(&[0x18, 0xFE]);
+    let gb = GameBoy::builder(&rom).ram_init(RamInit::Zeros).build();
+    Session::new(gb, capacity, |gb, input| {
+        let count = gb.read_u8(0xC000);
+        gb.write_u8(0xC000, count.wrapping_add(*input));
+    })
+}
+
+#[tokio::test]
+async fn frames_are_queued_until_full() {
+    let (mut session, mut handle) = new_session(2);
+    let number = session.run_frames(5).await;
+    assert!(number >= 4);
+
+    let first = handle.frames.recv().await.unwrap();
+    let second = handle.frames.recv().await.unwrap();
+    assert!(first.number() < second.number());
+    assert!(handle.frames.try_recv().is_err());
+
+    // The queue has room again
+    let number = session.run_frames(2).await;
+    handle.frames.recv().await.unwrap();
+    assert_eq!(handle.frames.recv().await.unwrap().number(), number);
+}
+
+#[tokio::test]
+async fn last_input_is_applied_every_frame() {
+    let (mut session, handle) = new_session(4);
+    session.run_frame().await;
+    assert_eq!(session.gb().read_u8(0xC000), 0);
+
+    handle.inputs.send(5).await.unwrap();
+    handle.inputs.send(1).await.unwrap();
+    session.run_frames(3).await;
+    assert_eq!(session.gb().read_u8(0xC000), 3);
+}
+
+#[tokio::test]
+async fn sessions_are_serializable() {
+    let (mut session, handle) = new_session(4);
+    handle.inputs.send(1).await.unwrap();
+    session.run_frames(2).await;
+    let bytes = session.to_bytes();
+
+    let (mut restored, _handle) = new_session(4);
+    restored.load_bytes(&bytes).unwrap();
+    assert_eq!(restored.gb().state_hash(), session.gb().state_hash());
+
+    // Inputs are not part of the state
+    session.load_bytes(&bytes).unwrap();
+    session.run_frame().await;
+    assert_eq!(session.gb().read_u8(0xC000), 2);
+    assert!(restored.load_bytes(b"garbage").is_err());
+}
+
+#[tokio::test]
+async fn run_stops_when_the_handle_is_dropped() {
+    let (mut session, mut handle) = new_session(4);
+    let local = tokio::task::LocalSet::new();
+    let task = local.spawn_local(async move {
+        session.run().await;
+        session
+    });
+
+    let frame = local.run_until(handle.frames.recv()).await.unwrap();
+    assert!(frame.number() > 0);
+    drop(handle);
+    let session = local.run_until(task).await.unwrap();
+    assert!(session.gb().frames() > 0);
+}
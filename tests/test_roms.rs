@@ -0,0 +1,112 @@
+//! Acceptance tests running blargg's test ROMs, which report their result on
+//! the serial port, and mooneye's, which report it in the registers. The ROMs
+//! are not distributed with the crate, place them in `tests/roms` (or point
+//! `GBEMU_TEST_ROMS` to their directory) and run
+//!
+//! ```sh
+//! cargo test --features test-roms --test test_roms
+//! ```
+//!
+//! The ROMs are driven step by step, which clocks the timer `instr_timing`
+//! measures the instructions with like a full frame would.
+use std::path::PathBuf;
+
+use gbemu::{
+    cpu::{Cpu, Registers, CYCLES_PER_FRAME},
+    memory::Read,
+    run::Condition,
+    serial::TextSink,
+    GameBoy,
+};
+
+/// Emulated time after which a ROM is considered stuck
+const TIMEOUT_FRAMES: u64 = 60 * 60;
+
+fn read_rom(name: &str) -> Vec<u8> {
+    let directory = std::env::var_os("GBEMU_TEST_ROMS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/roms"));
+    let path = directory.join(name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err))
+}
+
+/// Runs a ROM until it reports its result, returning the serial output
+fn run(name: &str) -> String {
+    let rom = read_rom(name);
+    let mut gb = GameBoy::new(&rom);
+    let done = Condition::Any(vec![
+        Condition::SerialOutputContains("Passed".to_string()),
+        Condition::SerialOutputContains("Failed".to_string()),
+    ]);
+    let result = gb.run_until(&done, TIMEOUT_FRAMES);
+    let mut text = TextSink::new();
+    text.update(&gb);
+    let output = text.text();
+    if let Err(timeout) = result {
+        panic!("{}: {}\n{}", name, timeout, output);
+    }
+    output
+}
+
+fn assert_passes(name: &str) {
+    let output = run(name);
+    assert!(output.contains("Passed"), "{}:\n{}", name, output);
+}
+
+/// Runs a mooneye ROM until its `LD B,B` breakpoint, reached with the
+/// Fibonacci numbers in the registers when passed
+fn assert_mooneye_passes(name: &str) {
+    let rom = read_rom(name);
+    let mut gb = GameBoy::new(&rom);
+    while gb.read_u8(*gb.registers().pc as usize) != 0x40 {
+        assert!(
+            gb.cycles() < TIMEOUT_FRAMES * CYCLES_PER_FRAME,
+            "{}: timed out",
+            name
+        );
+        gb.step();
+    }
+    let registers = gb.registers();
+    assert_eq!(
+        [*registers.bc, *registers.de, *registers.hl],
+        [0x0305, 0x080D, 0x1522],
+        "{}",
+        name
+    );
+}
+
+/// Checks the cycles taken by every instruction against the opcode table
+#[test]
+fn instr_timing() {
+    assert_passes("instr_timing.gb");
+}
+
+/// Checks when memory accesses happen within an instruction
+#[test]
+fn mem_timing() {
+    assert_passes("mem_timing.gb");
+}
+
+/// Checks the MBC1 ROM banking of every cartridge size, in both modes
+#[test]
+fn mbc1_rom_banks() {
+    for size in ["512kb", "1Mb", "2Mb", "4Mb", "8Mb", "16Mb"] {
+        assert_mooneye_passes(&format!("mbc1/rom_{}.gb", size));
+    }
+}
+
+/// Checks the bits decoded by each MBC1 register
+#[test]
+fn mbc1_register_bits() {
+    for register in ["bank1", "bank2", "mode", "ramg"] {
+        assert_mooneye_passes(&format!("mbc1/bits_{}.gb", register));
+    }
+}
+
+/// Checks the MBC1 RAM banking, in both modes
+#[test]
+fn mbc1_ram_banks() {
+    for size in ["64kb", "256kb"] {
+        assert_mooneye_passes(&format!("mbc1/ram_{}.gb", size));
+    }
+}
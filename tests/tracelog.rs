@@ -0,0 +1,42 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use gbemu::{cpu::Cpu, tracelog::TraceFormat, GameBoy};
+
+#[test]
+fn lines_match_other_emulators() {
+    let gb = GameBoy::new(&common::rom_with_program(&[0x18, 0xFE]));
+    assert_eq!(
+        gb.trace_line(TraceFormat::Bgb),
+        "A:01 F:Z-HC BC:0013 DE:00D8 HL:014D SP:FFFE PC:0100 (cy: 0)"
+    );
+    assert_eq!(
+        gb.trace_line(TraceFormat::Gambatte),
+        "PC:0100 SP:FFFE A:01 B:00 C:13 D:00 E:D8 F:B0 H:01 L:4D LY:00 cy:0"
+    );
+    assert_eq!(gb.trace_line(TraceFormat::Native), gb.status_line());
+}
+
+#[test]
+fn the_hook_gets_a_line_per_instruction() {
+    // LD A,0x42; HALT (IME off and nothing pending: halted for good)
+    let mut gb = GameBoy::new(&common::rom_with_program(&[0x3E, 0x42, 0x76]));
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let trace = lines.clone();
+    gb.hooks_mut().on_trace = Some(Box::new(move |line| {
+        trace.borrow_mut().push(line.to_string())
+    }));
+    gb.set_trace_format(TraceFormat::Bgb);
+
+    for _ in 0..10 {
+        gb.step();
+    }
+    assert_eq!(
+        *lines.borrow(),
+        [
+            "A:01 F:Z-HC BC:0013 DE:00D8 HL:014D SP:FFFE PC:0100 (cy: 0)",
+            "A:42 F:Z-HC BC:0013 DE:00D8 HL:014D SP:FFFE PC:0102 (cy: 8)",
+        ]
+    );
+}
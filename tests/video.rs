@@ -0,0 +1,191 @@
+mod common;
+
+use gbemu::{
+    cpu::{Cpu, CYCLES_PER_FRAME},
+    lcd::{Mode, SCREEN_HEIGHT, SCREEN_WIDTH},
+    memory::{locations, RamInit, Write},
+    run::Condition,
+    video::{PixelFormat, DMG_PALETTE},
+    GameBoy,
+};
+
+/// A console showing a blank background, all of it in the shade chosen by BGP
+fn new_console(ghosting: f32) -> GameBoy {
+    // JR -2
+    let rom = common::rom_with_program(&[0x18, 0xFE]);
+    let mut gb = GameBoy::builder(&rom)
+        .ram_init(RamInit::Zeros)
+        .ghosting(ghosting)
+        .build();
+    gb.write_u8(locations::LCDC, 0x91);
+    // Start from VBlank, so that every frame shown is drawn and converted whole
+    while gb.lcd_mode() != Mode::VBlank {
+        gb.step();
+    }
+    gb
+}
+
+/// Shows a frame entirely in the given shade, returning the color of the
+/// top left pixel of the RGBA frame
+fn show(gb: &mut GameBoy, shade: u8) -> [u8; 4] {
+    gb.write_u8(locations::BGP, shade);
+    gb.run_until(&Condition::Cycles(CYCLES_PER_FRAME), 2)
+        .unwrap();
+    gb.frame_front().lock().rgba()[..4].try_into().unwrap()
+}
+
+#[test]
+fn rgba_frame_follows_the_shades() {
+    let mut gb = new_console(0.0);
+    assert_eq!(gb.ghosting(), 0.0);
+    assert_eq!(
+        gb.frame_front().lock().rgba().len(),
+        SCREEN_WIDTH * SCREEN_HEIGHT * 4
+    );
+
+    for shade in [3, 1, 0, 2] {
+        let [r, g, b, a] = show(&mut gb, shade);
+        assert_eq!([r, g, b], DMG_PALETTE[shade as usize]);
+        assert_eq!(a, 0xFF);
+    }
+    assert!(gb
+        .frame_front()
+        .lock()
+        .rgba()
+        .chunks_exact(4)
+        .all(|pixel| pixel == [0x55, 0x55, 0x55, 0xFF]));
+}
+
+#[test]
+fn ghosting_blends_with_the_previous_output() {
+    let mut gb = new_console(0.5);
+    assert_eq!(show(&mut gb, 3), [0x80, 0x80, 0x80, 0xFF]);
+    assert_eq!(show(&mut gb, 3), [0x40, 0x40, 0x40, 0xFF]);
+
+    // Alternating black and white frames look gray
+    for _ in 0..20 {
+        show(&mut gb, 0);
+        show(&mut gb, 3);
+    }
+    let [gray, ..] = show(&mut gb, 0);
+    assert!((0x90..=0xB0).contains(&gray), "{:#04x}", gray);
+
+    // Without ghosting the output changes at once
+    gb.set_ghosting(0.0);
+    assert_eq!(show(&mut gb, 3), [0x00, 0x00, 0x00, 0xFF]);
+}
+
+#[test]
+#[should_panic(expected = "ghosting strength")]
+fn ghosting_cannot_freeze_the_output() {
+    new_console(0.0).set_ghosting(1.0);
+}
+
+#[test]
+fn frames_convert_to_every_format() {
+    let mut gb = new_console(0.0);
+    let green = [
+        [0xE0, 0xF8, 0xD0],
+        [0x88, 0xC0, 0x70],
+        [0x34, 0x68, 0x56],
+        [0x08, 0x18, 0x20],
+    ];
+    gb.set_palette(green);
+    assert_eq!(gb.palette(), green);
+    show(&mut gb, 2);
+
+    let mut indexed = vec![0xFF; PixelFormat::Indexed.frame_size()];
+    gb.frame_into(PixelFormat::Indexed, &mut indexed);
+    assert!(indexed.iter().all(|&shade| shade == 2));
+
+    let mut rgba = vec![0; PixelFormat::Rgba8888.frame_size()];
+    gb.frame_into(PixelFormat::Rgba8888, &mut rgba);
+    assert_eq!(rgba, gb.frame_front().lock().rgba());
+    assert_eq!(rgba[..4], [0x34, 0x68, 0x56, 0xFF]);
+
+    let mut rgb555 = vec![0; PixelFormat::Rgb555.frame_size()];
+    gb.frame_into(PixelFormat::Rgb555, &mut rgb555);
+    let expected = (0x34 >> 3) | (0x68 >> 3) << 5 | (0x56 >> 3) << 10;
+    assert!(rgb555
+        .chunks_exact(2)
+        .all(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]) == expected));
+}
+
+#[test]
+#[should_panic(expected = "does not fit")]
+fn frames_need_a_buffer_of_the_right_size() {
+    let gb = new_console(0.0);
+    gb.frame_into(PixelFormat::Rgb555, &mut [0; SCREEN_WIDTH * SCREEN_HEIGHT]);
+}
+
+#[test]
+fn front_buffer_is_swapped_on_vblank() {
+    let mut gb = new_console(0.0);
+    let front = gb.frame_front();
+    let number = front.lock().number();
+    assert!(number > 0);
+
+    // A render thread sees every frame once complete
+    let reader = {
+        let front = front.clone();
+        std::thread::spawn(move || {
+            let frame = front.lock();
+            (frame.number(), frame.indexed()[0])
+        })
+    };
+    assert_eq!(reader.join().unwrap(), (number, 0));
+
+    gb.write_u8(locations::BGP, 3);
+    // Halfway through the frame the front one is untouched
+    gb.run_until(&Condition::Cycles(CYCLES_PER_FRAME / 2), 2)
+        .unwrap();
+    assert_eq!(front.lock().number(), number);
+    assert_eq!(front.lock().indexed()[0], 0);
+    assert_eq!(gb.frame()[0], 3);
+
+    gb.run_until(&Condition::Cycles(CYCLES_PER_FRAME / 2), 2)
+        .unwrap();
+    let frame = front.lock();
+    assert_eq!(frame.number(), number + 1);
+    assert!(frame.indexed().iter().all(|&shade| shade == 3));
+}
+
+#[test]
+fn lcd_off_shows_white_until_the_second_frame_back_on() {
+    let mut gb = new_console(0.5);
+    let white = [0xFF, 0xFF, 0xFF, 0xFF];
+    assert_ne!(show(&mut gb, 0xFF), white);
+
+    gb.write_u8(locations::LCDC, 0x11);
+    let blank = |gb: &GameBoy| {
+        gb.frame().iter().all(|shade| *shade == 0)
+            && gb
+                .frame_front()
+                .lock()
+                .rgba()
+                .chunks_exact(4)
+                .all(|pixel| pixel == white)
+    };
+    assert!(blank(&gb));
+    let number = gb.frame_front().lock().number();
+
+    // Nothing is produced while off
+    gb.run_until(&Condition::Cycles(3 * CYCLES_PER_FRAME), 4)
+        .unwrap();
+    assert!(blank(&gb));
+    assert_eq!(gb.frame_front().lock().number(), number);
+
+    // The first frame back on is drawn but not shown
+    gb.write_u8(locations::LCDC, 0x91);
+    gb.run_to_vblank().unwrap();
+    assert!(gb.frame().iter().all(|shade| *shade == 3));
+    assert!(gb
+        .frame_front()
+        .lock()
+        .rgba()
+        .chunks_exact(4)
+        .all(|pixel| pixel == white));
+    gb.run_to_vblank().unwrap();
+    assert_eq!(gb.frame_front().lock().number(), number + 2);
+    assert_ne!(&gb.frame_front().lock().rgba()[..4], white);
+}
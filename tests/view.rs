@@ -0,0 +1,93 @@
+mod common;
+
+use gbemu::{
+    cartridge,
+    memory::{
+        view::{OamEntry, Region, OAM_ENTRIES},
+        RamInit, Read, Write,
+    },
+    GameBoy,
+};
+
+#[test]
+fn regions_are_seen_from_their_start() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    gb.write_u8(0xC123, 0x12);
+    gb.write_u8(0x9800, 0x34);
+    gb.write_u8(0xFF80, 0x56);
+    assert_eq!(gb.wram()[0x123], 0x12);
+    assert_eq!(gb.vram()[0x1800], 0x34);
+    assert_eq!(gb.vram_bank(0).unwrap()[0x1800], 0x34);
+    assert_eq!(gb.vram_bank(1), None);
+    assert_eq!(gb.hram()[0], 0x56);
+
+    gb.wram_mut()[0x1FFF] = 0x78;
+    gb.vram_mut()[0] = 0x9A;
+    gb.hram_mut()[0x7E] = 0xBC;
+    assert_eq!(gb.read_u8(0xDFFF), 0x78);
+    // Through the echo too
+    assert_eq!(gb.read_u8(0xE123), 0x12);
+    assert_eq!(gb.read_u8(0x8000), 0x9A);
+    assert_eq!(gb.read_u8(0xFFFE), 0xBC);
+}
+
+#[test]
+fn oam_is_seen_as_sprites() {
+    let mut gb = GameBoy::new(&cartridge::test_rom());
+    for (i, byte) in [0x20, 0x18, 0x05, 0xB0].into_iter().enumerate() {
+        gb.write_u8(0xFE04 + i, byte);
+    }
+
+    let oam = gb.oam();
+    assert_eq!(oam.len(), OAM_ENTRIES);
+    let sprite = oam[1];
+    assert_eq!(
+        sprite,
+        OamEntry {
+            y: 0x20,
+            x: 0x18,
+            tile: 0x05,
+            attributes: 0xB0,
+        }
+    );
+    assert!(sprite.behind_background() && sprite.x_flip() && sprite.palette());
+    assert!(!sprite.y_flip());
+
+    gb.set_oam_entry(
+        39,
+        OamEntry {
+            tile: 0x42,
+            ..sprite
+        },
+    );
+    assert_eq!(gb.read_bytes(0xFE9C..=0xFE9F), [0x20, 0x18, 0x42, 0xB0]);
+}
+
+#[test]
+fn regions_iterate_over_every_bank() {
+    // MBC1 with 4 RAM banks, bank 2 mapped
+    let rom = common::rom_with_header(&[0x18, 0xFE], 0x03, 0x01, 0x03);
+    let mut gb = GameBoy::builder(&rom).sram_init(RamInit::Zeros).build();
+    gb.write_u8(0x0000, 0x0A);
+    gb.write_u8(0x6000, 0x01);
+    gb.write_u8(0x4000, 0x02);
+    gb.write_u8(0xA010, 0x42);
+
+    let ram: Vec<_> = gb.iter_region(Region::CartRam).collect();
+    assert_eq!(ram.len(), 4 * Region::CartRam.bank_size());
+    assert_eq!(ram.last().unwrap().0, 3);
+    let written: Vec<_> = ram
+        .into_iter()
+        .filter(|(_, _, value)| *value != 0)
+        .collect();
+    assert_eq!(written, [(2, 0x10, 0x42)]);
+
+    assert_eq!(gb.iter_region(Region::Rom).count(), rom.len());
+    assert_eq!(
+        gb.iter_region(Region::Rom).nth(0x147),
+        Some((0, 0x147, 0x03))
+    );
+    assert_eq!(gb.iter_region(Region::Rom).last().unwrap().0, 3);
+    assert_eq!(gb.iter_region(Region::Oam).count(), OAM_ENTRIES * 4);
+    assert!(gb.iter_region(Region::Hram).all(|(bank, _, _)| bank == 0));
+}